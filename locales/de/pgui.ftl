@@ -0,0 +1,56 @@
+# Deutsche Übersetzung.
+
+## Menus
+
+menu-file = Datei
+menu-query = Abfrage
+menu-view = Ansicht
+menu-help = Hilfe
+menu-quit = Beenden
+menu-new-window = Neues Fenster
+menu-open = Öffnen...
+menu-open-recent = Zuletzt geöffnet...
+menu-save = Speichern
+menu-save-as = Speichern unter...
+menu-search-database = Datenbank durchsuchen...
+menu-run-statement = Anweisung ausführen
+menu-run-selection = Auswahl ausführen
+menu-run-script = Skript ausführen
+menu-zen-mode = Zen-Modus
+menu-view-logs = Protokolle anzeigen
+
+## Header bar
+
+header-high-contrast = Hoher Kontrast
+header-reduce-motion = Bewegung reduzieren
+header-updates = Aktualisierungen
+header-update-available = v{ $version } verfügbar!
+header-themes = Designs
+header-production = PRODUKTION
+theme-dialog-title = Designs
+
+## Footer bar
+
+footer-toggle-tables = Tabellenbereich ein-/ausblenden
+footer-toggle-results-position = Position der Ergebnisse wechseln
+footer-performance = Leistung
+footer-locks = Sperren
+footer-activity = Aktivität
+footer-migrations = Migrationen
+footer-save-workspace = Arbeitsbereich speichern
+footer-zen-mode = Zen-Modus
+footer-toggle-agent = Agent-Bereich ein-/ausblenden
+footer-toggle-history = Verlauf ein-/ausblenden
+footer-toggle-jobs = Aufträge ein-/ausblenden
+footer-notifications = Benachrichtigungen
+
+## Side panels
+
+panel-tables = Tabellen
+panel-agent = Agent
+panel-history = Verlauf
+panel-jobs = Aufträge
+panel-move-left = Ins linke Dock verschieben
+panel-move-right = Ins rechte Dock verschieben
+panel-hide = Bereich ausblenden
+zen-mode-exit = Zen-Modus beenden
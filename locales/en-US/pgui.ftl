@@ -0,0 +1,57 @@
+# English messages. Every other locale translates a subset of these ids;
+# anything it leaves out is shown in English.
+
+## Menus
+
+menu-file = File
+menu-query = Query
+menu-view = View
+menu-help = Help
+menu-quit = Quit
+menu-new-window = New Window
+menu-open = Open...
+menu-open-recent = Open Recent...
+menu-save = Save
+menu-save-as = Save As...
+menu-search-database = Search Database...
+menu-run-statement = Run Statement
+menu-run-selection = Run Selection
+menu-run-script = Run Script
+menu-zen-mode = Zen Mode
+menu-view-logs = View Logs
+
+## Header bar
+
+header-high-contrast = High Contrast
+header-reduce-motion = Reduce Motion
+header-updates = Updates
+header-update-available = v{ $version } available!
+header-themes = Themes
+header-production = PRODUCTION
+theme-dialog-title = Themes
+
+## Footer bar
+
+footer-toggle-tables = Toggle Tables Panel
+footer-toggle-results-position = Toggle Results Position
+footer-performance = Performance
+footer-locks = Locks
+footer-activity = Activity
+footer-migrations = Migrations
+footer-save-workspace = Save Workspace
+footer-zen-mode = Zen Mode
+footer-toggle-agent = Toggle Agent Panel
+footer-toggle-history = Toggle History Panel
+footer-toggle-jobs = Toggle Jobs Panel
+footer-notifications = Notifications
+
+## Side panels
+
+panel-tables = Tables
+panel-agent = Agent
+panel-history = History
+panel-jobs = Jobs
+panel-move-left = Move to Left Dock
+panel-move-right = Move to Right Dock
+panel-hide = Hide Panel
+zen-mode-exit = Exit Zen Mode
@@ -5,15 +5,21 @@ mod themes;
 mod window;
 mod workspace;
 
+use std::path::PathBuf;
+
 use assets::*;
-use gpui::{App, AppContext as _, Application, KeyBinding, actions};
+use gpui::{App, AppContext as _, Application, KeyBinding, Menu, MenuItem, actions};
 use gpui_component::{ActiveTheme as _, Root, theme};
+use services::i18n::tr;
+use services::sql::{sql_file_args, sql_file_from_url};
 use themes::*;
-use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _};
+use tracing_subscriber::{
+    EnvFilter, Layer as _, fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _,
+};
 use window::*;
 use workspace::*;
 
-actions!(window, [Quit]);
+actions!(window, [Quit, NewWindow]);
 
 fn init_logging() {
     // Check for --debug flag or -d
@@ -26,19 +32,64 @@ fn init_logging() {
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"))
     };
 
+    // The log file behind Help > View Logs keeps info and above; sqlx
+    // logs statements at info, so it stays at warn to keep SQL out.
+    let file_filter = if debug {
+        EnvFilter::new("debug")
+    } else {
+        EnvFilter::new("info,sqlx=warn")
+    };
+    let file_layer = services::diagnostics::logs_dir()
+        .and_then(|dir| services::diagnostics::RotatingLog::open(&dir))
+        .map_err(|e| eprintln!("Failed to open log file: {}", e))
+        .ok()
+        .map(|log| {
+            fmt::layer()
+                .with_ansi(false)
+                .with_target(true)
+                .with_writer(move || log.clone())
+                .with_filter(file_filter)
+        });
+
+    // The log filter only applies to the fmt output, so server notices
+    // still reach the notice layer at the default "warn" level. Console
+    // logs go to stderr, leaving stdout to CLI output.
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(true))
-        .with(filter)
+        .with(
+            fmt::layer()
+                .with_target(true)
+                .with_writer(std::io::stderr)
+                .with_filter(filter),
+        )
+        .with(file_layer)
+        .with(services::notice_layer())
         .init();
+
+    services::diagnostics::install_panic_hook();
 }
 
 fn main() {
     init_logging();
+    services::i18n::init();
+
+    // `pgui query ...` runs headless and exits
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = services::cli::run_cli(&args) {
+        std::process::exit(code);
+    }
     tracing::info!("Starting PGUI v{}", env!("CARGO_PKG_VERSION"));
 
     // Create app w/ assets
     let application = Application::new().with_assets(Assets);
 
+    // `.sql` files the OS asks us to open, e.g. double-clicked in Finder
+    let (open_tx, open_rx) = async_channel::unbounded::<PathBuf>();
+    application.on_open_urls(move |urls| {
+        for path in urls.iter().filter_map(|url| sql_file_from_url(url)) {
+            let _ = open_tx.try_send(path);
+        }
+    });
+
     application.run(|cx: &mut App| {
         // Close app on macOS close icon click
         cx.on_window_closed(|cx| {
@@ -48,24 +99,120 @@ fn main() {
         })
         .detach();
 
-        // Setup window options and workspace
-        let window_options = get_window_options(cx);
-        cx.open_window(window_options, |win, cx| {
-            gpui_component::init(cx);
-            theme::init(cx);
-            state::init(cx);
-            change_color_mode(cx.theme().mode, win, cx);
+        // Restore the window placement remembered for this display
+        // configuration, then setup window options and workspace
+        let layout_key = display_layout_key(cx);
+        cx.spawn(async move |cx| {
+            let saved_bounds = load_window_bounds(&layout_key).await;
+            cx.update(|cx| {
+                let window_options = get_window_options(cx, saved_bounds);
+                cx.open_window(window_options, |win, cx| {
+                    gpui_component::init(cx);
+                    theme::init(cx);
+                    state::init(cx);
+                    change_color_mode(cx.theme().mode, win, cx);
+                    Appearance::init(win, cx);
+                    ThemeSettings::init(win, cx);
 
-            let workspace_view = Workspace::view(win, cx);
-            cx.new(|cx| Root::new(workspace_view, win, cx))
+                    let workspace_view = Workspace::view(win, cx);
+                    // The connection marked to open at startup, in the
+                    // first window only
+                    state::auto_connect(state::window_id(win), cx);
+                    cx.new(|cx| Root::new(workspace_view, win, cx))
+                })
+                .unwrap();
+
+                // A file passed on the command line, then any the OS sends
+                if let Some(path) = sql_file_args(std::env::args().skip(1)).pop() {
+                    state::open_file(path, cx);
+                }
+                cx.spawn(async move |cx| {
+                    while let Ok(path) = open_rx.recv().await {
+                        let _ = cx.update(|cx| state::open_file(path, cx));
+                    }
+                })
+                .detach();
+
+                // Bring app to front
+                cx.activate(true);
+            })
         })
-        .unwrap();
+        .detach();
 
         // Close app w/ cmd-q
         cx.on_action(|_: &Quit, cx| cx.quit());
-        cx.bind_keys([KeyBinding::new("cmd-q", Quit, None)]);
+        cx.on_action(|_: &NewWindow, cx| open_new_window(cx));
+        cx.on_action(|_: &ViewLogs, cx| open_log_viewer(cx));
+        cx.bind_keys([
+            KeyBinding::new("cmd-q", Quit, None),
+            KeyBinding::new("secondary-shift-n", NewWindow, None),
+            KeyBinding::new("secondary-o", OpenFile, None),
+            KeyBinding::new("secondary-s", SaveFile, None),
+            KeyBinding::new("secondary-shift-s", SaveFileAs, None),
+            KeyBinding::new("secondary-p", SearchDatabase, None),
+            KeyBinding::new("secondary-r", RunStatement, None),
+            KeyBinding::new("secondary-shift-r", RunSelection, None),
+            KeyBinding::new("secondary-alt-r", RunScript, None),
+            KeyBinding::new("secondary-k z", ToggleZenMode, None),
+            KeyBinding::new("up", CellUp, Some("ResultsGrid")),
+            KeyBinding::new("down", CellDown, Some("ResultsGrid")),
+            KeyBinding::new("left", CellLeft, Some("ResultsGrid")),
+            KeyBinding::new("right", CellRight, Some("ResultsGrid")),
+            KeyBinding::new("home", CellRowStart, Some("ResultsGrid")),
+            KeyBinding::new("end", CellRowEnd, Some("ResultsGrid")),
+            KeyBinding::new("secondary-home", CellFirstRow, Some("ResultsGrid")),
+            KeyBinding::new("secondary-end", CellLastRow, Some("ResultsGrid")),
+            KeyBinding::new("pageup", CellPageUp, Some("ResultsGrid")),
+            KeyBinding::new("pagedown", CellPageDown, Some("ResultsGrid")),
+            KeyBinding::new("enter", InspectCell, Some("ResultsGrid")),
+            KeyBinding::new("space", ToggleRowDetail, Some("ResultsGrid")),
+            KeyBinding::new("enter", SelectItem, Some("TablesTree")),
+            KeyBinding::new("secondary-1", QuickSlot1, None),
+            KeyBinding::new("secondary-2", QuickSlot2, None),
+            KeyBinding::new("secondary-3", QuickSlot3, None),
+            KeyBinding::new("secondary-4", QuickSlot4, None),
+            KeyBinding::new("secondary-5", QuickSlot5, None),
+            KeyBinding::new("secondary-6", QuickSlot6, None),
+            KeyBinding::new("secondary-7", QuickSlot7, None),
+            KeyBinding::new("secondary-8", QuickSlot8, None),
+            KeyBinding::new("secondary-9", QuickSlot9, None),
+        ]);
 
-        // Bring app to front
-        cx.activate(true);
+        cx.set_menus(vec![
+            Menu {
+                name: "PGUI".into(),
+                items: vec![MenuItem::action(tr("menu-quit"), Quit)],
+            },
+            Menu {
+                name: tr("menu-file").into(),
+                items: vec![
+                    MenuItem::action(tr("menu-new-window"), NewWindow),
+                    MenuItem::separator(),
+                    MenuItem::action(tr("menu-open"), OpenFile),
+                    MenuItem::action(tr("menu-open-recent"), OpenRecentFile),
+                    MenuItem::separator(),
+                    MenuItem::action(tr("menu-save"), SaveFile),
+                    MenuItem::action(tr("menu-save-as"), SaveFileAs),
+                    MenuItem::separator(),
+                    MenuItem::action(tr("menu-search-database"), SearchDatabase),
+                ],
+            },
+            Menu {
+                name: tr("menu-query").into(),
+                items: vec![
+                    MenuItem::action(tr("menu-run-statement"), RunStatement),
+                    MenuItem::action(tr("menu-run-selection"), RunSelection),
+                    MenuItem::action(tr("menu-run-script"), RunScript),
+                ],
+            },
+            Menu {
+                name: tr("menu-view").into(),
+                items: vec![MenuItem::action(tr("menu-zen-mode"), ToggleZenMode)],
+            },
+            Menu {
+                name: tr("menu-help").into(),
+                items: vec![MenuItem::action(tr("menu-view-logs"), ViewLogs)],
+            },
+        ]);
     });
 }
@@ -232,11 +232,15 @@ impl Agent {
     }
 
     /// Get the current conversation history
-    #[allow(dead_code)]
     pub fn get_conversation(&self) -> &[Message] {
         &self.conversation
     }
 
+    /// Replace the conversation history, e.g. to resume a previous session
+    pub fn set_conversation(&mut self, conversation: Vec<Message>) {
+        self.conversation = conversation;
+    }
+
     /// Clear the conversation history
     pub fn clear_conversation(&mut self) {
         self.conversation.clear();
@@ -15,6 +15,8 @@ pub enum AgentRequest {
     Chat {
         content: String,
         files: Vec<std::path::PathBuf>,
+        /// Description of the tables relevant to the message
+        schema_context: Option<String>,
     },
     /// Provide results for tool calls
     ToolResults(Vec<ToolResultData>),
@@ -147,6 +149,16 @@ impl UiMessage {
         }
     }
 
+    /// Create a new system note, e.g. about the context sent along
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: MessageRole::System,
+            content: content.into(),
+            timestamp: Utc::now(),
+            metadata: None,
+        }
+    }
+
     /// Create a new tool call message
     pub fn tool_call(tool_name: String, tool_input: Value) -> Self {
         Self {
@@ -3,11 +3,15 @@
 //! This module provides:
 //! - `client` - The Agent client for communicating with Anthropic's API
 //! - `messages` - Request/response types and UI message types
+//! - `privacy` - What may be sent to the model, per connection
+//! - `schema_context` - Picking the tables relevant to a request
 //! - `types` - Core types like Tool, Message, ContentBlock
 
 mod client;
 mod files;
 mod messages;
+mod privacy;
+mod schema_context;
 mod types;
 
 // Re-export main client types
@@ -28,6 +32,13 @@ pub use messages::{
     ToolCallData, ToolResultData, UiMessage,
 };
 
+// Re-export privacy settings
+pub use privacy::{AgentPrivacy, LiteralPolicy};
+
+// Re-export schema context selection
+#[allow(unused_imports)]
+pub use schema_context::{MAX_CONTEXT_TABLES, SchemaContext, SchemaSelection};
+
 // Re-export core types
 #[allow(unused_imports)]
 pub use types::{ContentBlock, FileSource, Message, Tool, ToolDefinition};
@@ -0,0 +1,458 @@
+//! What the agent features may send to the model.
+//!
+//! Per connection, schemas, tables and columns can be left out of the
+//! schema description and the schema tools, and literal values in SQL can
+//! be stripped or replaced by a hash before a query is sent for completion
+//! or explanation.
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::{DatabaseSchema, TableSchema};
+
+/// How literal values in SQL sent to the model are treated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LiteralPolicy {
+    /// Send literals as written.
+    #[default]
+    Keep,
+    /// Replace every string and number literal with `?`.
+    Strip,
+    /// Replace literals with a short hash, so equal values still look
+    /// equal to the model.
+    Hash,
+}
+
+impl LiteralPolicy {
+    pub const ALL: [LiteralPolicy; 3] = [
+        LiteralPolicy::Keep,
+        LiteralPolicy::Strip,
+        LiteralPolicy::Hash,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LiteralPolicy::Keep => "Keep",
+            LiteralPolicy::Strip => "Strip",
+            LiteralPolicy::Hash => "Hash",
+        }
+    }
+}
+
+/// A connection's agent privacy settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentPrivacy {
+    /// `schema`, `schema.table` or `schema.table.column` patterns, where
+    /// `*` matches any run of characters. Matching ignores case.
+    pub excluded: Vec<String>,
+    pub literals: LiteralPolicy,
+}
+
+impl AgentPrivacy {
+    pub fn excludes_table(&self, schema: &str, table: &str) -> bool {
+        self.patterns().any(|pattern| match pattern.as_slice() {
+            [s] => glob_match(s, schema),
+            [s, t] => glob_match(s, schema) && glob_match(t, table),
+            _ => false,
+        })
+    }
+
+    pub fn excludes_column(&self, schema: &str, table: &str, column: &str) -> bool {
+        self.excludes_table(schema, table)
+            || self.patterns().any(|pattern| match pattern.as_slice() {
+                [s, t, c] => glob_match(s, schema) && glob_match(t, table) && glob_match(c, column),
+                _ => false,
+            })
+    }
+
+    fn patterns(&self) -> impl Iterator<Item = Vec<&str>> {
+        self.excluded
+            .iter()
+            .map(|pattern| pattern.trim())
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| pattern.split('.').map(str::trim).collect())
+    }
+
+    /// `schema` without the excluded tables, and without the excluded
+    /// columns and the keys, indexes and constraints that mention them.
+    pub fn filter_schema(&self, schema: &DatabaseSchema) -> DatabaseSchema {
+        if self.excluded.is_empty() {
+            return schema.clone();
+        }
+        let tables: Vec<TableSchema> = schema
+            .tables
+            .iter()
+            .filter(|table| !self.excludes_table(&table.table_schema, &table.table_name))
+            .map(|table| self.filter_table(table))
+            .collect();
+        DatabaseSchema {
+            total_tables: tables.len(),
+            tables,
+        }
+    }
+
+    fn filter_table(&self, table: &TableSchema) -> TableSchema {
+        let hidden =
+            |column: &str| self.excludes_column(&table.table_schema, &table.table_name, column);
+        let mut table = table.clone();
+        table.columns.retain(|c| !hidden(&c.column_name));
+        table.primary_keys.retain(|c| !hidden(c));
+        table.foreign_keys.retain(|fk| {
+            !hidden(&fk.column_name)
+                && !self.excludes_column(
+                    &fk.foreign_table_schema,
+                    &fk.foreign_table_name,
+                    &fk.foreign_column_name,
+                )
+        });
+        table
+            .indexes
+            .retain(|idx| !idx.columns.iter().any(|c| hidden(c)));
+        table
+            .constraints
+            .retain(|constraint| !constraint.columns.iter().any(|c| hidden(c)));
+        table
+    }
+
+    /// `sql` with its literals treated as `literals` says.
+    pub fn redact_sql(&self, sql: &str) -> String {
+        match self.literals {
+            LiteralPolicy::Keep => sql.to_string(),
+            policy => redact_literals(sql, policy),
+        }
+    }
+
+    /// `error` with the row values that constraint errors quote, such as
+    /// `Key (email)=(a@b.c)` or MySQL's `Duplicate entry 'a@b.c'`, treated
+    /// as `literals` says.
+    pub fn redact_error(&self, error: &str) -> String {
+        match self.literals {
+            LiteralPolicy::Keep => error.to_string(),
+            policy => redact_error_values(error, policy),
+        }
+    }
+}
+
+/// `*` matches any run of characters; everything else matches itself,
+/// ignoring ASCII case.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let text = text.to_ascii_lowercase();
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn redact_literals(sql: &str, policy: LiteralPolicy) -> String {
+    let replace = |literal: &str| match policy {
+        LiteralPolicy::Hash => format!("'#{}'", short_hash(literal)),
+        _ => "?".to_string(),
+    };
+
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.char_indices().peekable();
+    let mut prev: Option<char> = None;
+    while let Some((i, c)) = chars.next() {
+        let rest = &sql[i..];
+        let end = if rest.starts_with("--") {
+            let end = rest.find('\n').map_or(sql.len(), |n| i + n);
+            out.push_str(&sql[i..end]);
+            end
+        } else if rest.starts_with("/*") {
+            let end = rest[2..].find("*/").map_or(sql.len(), |n| i + 2 + n + 2);
+            out.push_str(&sql[i..end]);
+            end
+        } else if c == '\'' {
+            let end = closing_quote(sql, i, '\'');
+            out.push_str(&replace(&sql[i..end]));
+            end
+        } else if c == '"' {
+            let end = closing_quote(sql, i, '"');
+            out.push_str(&sql[i..end]);
+            end
+        } else if c == '$' && !prev.is_some_and(is_ident_char) {
+            match dollar_body(sql, i) {
+                // Bodies are code: redact the literals inside them
+                Some((tag, body, end)) => {
+                    out.push_str(tag);
+                    out.push_str(&redact_literals(body, policy));
+                    out.push_str(tag);
+                    end
+                }
+                None => {
+                    // `$1` placeholders stay as they are
+                    let len = rest[1..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(rest.len() - 1);
+                    out.push_str(&rest[..1 + len]);
+                    i + 1 + len
+                }
+            }
+        } else if c.is_ascii_digit() && !prev.is_some_and(is_ident_char) {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                .unwrap_or(rest.len());
+            out.push_str(&replace(&rest[..len]));
+            i + len
+        } else {
+            out.push(c);
+            i + c.len_utf8()
+        };
+
+        prev = sql[..end].chars().next_back();
+        while chars.peek().is_some_and(|(j, _)| *j < end) {
+            chars.next();
+        }
+    }
+    out
+}
+
+/// Text that opens a value in an error message, and the character that
+/// closes it.
+const ERROR_VALUES: [(&str, char); 3] = [
+    (")=(", ')'),
+    ("Failing row contains (", ')'),
+    ("Duplicate entry '", '\''),
+];
+
+fn redact_error_values(error: &str, policy: LiteralPolicy) -> String {
+    let mut out = String::with_capacity(error.len());
+    let mut rest = error;
+    while let Some((at, marker, close)) = ERROR_VALUES
+        .iter()
+        .filter_map(|(marker, close)| rest.find(marker).map(|at| (at, *marker, *close)))
+        .min_by_key(|(at, ..)| *at)
+    {
+        let start = at + marker.len();
+        let len = value_len(&rest[start..], close);
+        out.push_str(&rest[..start]);
+        out.push_str(&match policy {
+            LiteralPolicy::Hash => format!("#{}", short_hash(&rest[start..start + len])),
+            _ => "?".to_string(),
+        });
+        rest = &rest[start + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Length of the value before its closing `close`. Parentheses nest
+/// inside a parenthesized value.
+fn value_len(text: &str, close: char) -> usize {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        if c == '(' && close == ')' {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return i;
+            }
+            depth -= 1;
+        }
+    }
+    text.len()
+}
+
+/// End of the quoted text opening at `start`, after its closing quote.
+/// A doubled quote is an escaped one.
+fn closing_quote(sql: &str, start: usize, quote: char) -> usize {
+    let mut i = start + 1;
+    while let Some(at) = sql[i..].find(quote) {
+        i += at + 1;
+        if !sql[i..].starts_with(quote) {
+            return i;
+        }
+        i += 1;
+    }
+    sql.len()
+}
+
+/// The tag, body and end of a `$tag$ ... $tag$` body opening at `start`.
+fn dollar_body(sql: &str, start: usize) -> Option<(&str, &str, usize)> {
+    let tag_len = sql[start + 1..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map(|n| n + 2)?;
+    let tag = &sql[start..start + tag_len];
+    if !tag.ends_with('$') || tag[1..tag_len - 1].starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let body_start = start + tag_len;
+    let body_len = sql[body_start..].find(tag)?;
+    Some((
+        tag,
+        &sql[body_start..body_start + body_len],
+        body_start + body_len + tag_len,
+    ))
+}
+
+/// First 8 hex digits of the FNV-1a hash of `text`.
+fn short_hash(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{ColumnDetail, ForeignKeyInfo};
+
+    fn privacy(excluded: &[&str], literals: LiteralPolicy) -> AgentPrivacy {
+        AgentPrivacy {
+            excluded: excluded.iter().map(|s| s.to_string()).collect(),
+            literals,
+        }
+    }
+
+    fn column(name: &str) -> ColumnDetail {
+        ColumnDetail {
+            column_name: name.to_string(),
+            data_type: "text".to_string(),
+            is_nullable: true,
+            column_default: None,
+            ordinal_position: 1,
+            character_maximum_length: None,
+            numeric_precision: None,
+            numeric_scale: None,
+            full_type: None,
+            collation: None,
+            extra: None,
+            description: None,
+        }
+    }
+
+    fn table(schema: &str, name: &str, columns: &[&str]) -> TableSchema {
+        TableSchema {
+            table_name: name.to_string(),
+            table_schema: schema.to_string(),
+            table_type: "BASE TABLE".to_string(),
+            columns: columns.iter().map(|c| column(c)).collect(),
+            primary_keys: vec![columns[0].to_string()],
+            foreign_keys: vec![],
+            indexes: vec![],
+            constraints: vec![],
+            triggers: vec![],
+            description: None,
+        }
+    }
+
+    #[test]
+    fn matches_schema_table_and_column_patterns() {
+        let p = privacy(
+            &["hr", "public.audit_*", "*.*.ssn", "public.users.password"],
+            LiteralPolicy::Keep,
+        );
+        assert!(p.excludes_table("HR", "employees"));
+        assert!(p.excludes_table("public", "audit_log"));
+        assert!(!p.excludes_table("public", "users"));
+        assert!(p.excludes_column("public", "users", "password"));
+        assert!(p.excludes_column("billing", "customers", "SSN"));
+        assert!(!p.excludes_column("public", "users", "email"));
+        assert!(!privacy(&[], LiteralPolicy::Keep).excludes_table("public", "users"));
+    }
+
+    #[test]
+    fn filters_tables_columns_and_references() {
+        let mut orders = table("public", "orders", &["id", "user_id", "card_number"]);
+        orders.foreign_keys.push(ForeignKeyInfo {
+            constraint_name: "orders_user_fk".to_string(),
+            column_name: "user_id".to_string(),
+            foreign_table_schema: "hr".to_string(),
+            foreign_table_name: "people".to_string(),
+            foreign_column_name: "id".to_string(),
+        });
+        let schema = DatabaseSchema {
+            tables: vec![orders, table("hr", "people", &["id", "name"])],
+            total_tables: 2,
+        };
+
+        let filtered = privacy(&["hr", "public.orders.card_number"], LiteralPolicy::Keep)
+            .filter_schema(&schema);
+        assert_eq!(filtered.total_tables, 1);
+        let orders = &filtered.tables[0];
+        let columns: Vec<&str> = orders
+            .columns
+            .iter()
+            .map(|c| c.column_name.as_str())
+            .collect();
+        assert_eq!(columns, vec!["id", "user_id"]);
+        assert!(orders.foreign_keys.is_empty());
+    }
+
+    #[test]
+    fn strips_or_hashes_literals() {
+        let sql =
+            "SELECT id, \"it's\" FROM t2 WHERE email = 'a@b.c' AND n > 42 AND x = $1 -- 'kept'";
+        assert_eq!(
+            privacy(&[], LiteralPolicy::Strip).redact_sql(sql),
+            "SELECT id, \"it's\" FROM t2 WHERE email = ? AND n > ? AND x = $1 -- 'kept'"
+        );
+        let hashed = privacy(&[], LiteralPolicy::Hash).redact_sql("SELECT 'a', 'a', 'b'");
+        let parts: Vec<&str> = hashed.trim_start_matches("SELECT ").split(", ").collect();
+        assert_eq!(parts[0], parts[1]);
+        assert_ne!(parts[0], parts[2]);
+        assert!(parts[0].starts_with("'#"));
+        assert_eq!(privacy(&[], LiteralPolicy::Keep).redact_sql(sql), sql);
+    }
+
+    #[test]
+    fn redacts_inside_function_bodies() {
+        let sql = "DO $$ BEGIN PERFORM 'secret' || 'it''s'; END $$";
+        assert_eq!(
+            privacy(&[], LiteralPolicy::Strip).redact_sql(sql),
+            "DO $$ BEGIN PERFORM ? || ?; END $$"
+        );
+    }
+
+    #[test]
+    fn redacts_values_in_errors() {
+        let strip = privacy(&[], LiteralPolicy::Strip);
+        assert_eq!(
+            strip.redact_error(
+                "duplicate key value violates unique constraint \"users_email_key\"\n\
+                 DETAIL: Key (email)=(a@b.c) already exists."
+            ),
+            "duplicate key value violates unique constraint \"users_email_key\"\n\
+             DETAIL: Key (email)=(?) already exists."
+        );
+        assert_eq!(
+            strip.redact_error("Failing row contains (1, null, lower(x))."),
+            "Failing row contains (?)."
+        );
+        assert_eq!(
+            strip.redact_error("Duplicate entry 'a@b.c' for key 'users.email'"),
+            "Duplicate entry '?' for key 'users.email'"
+        );
+
+        let hash = privacy(&[], LiteralPolicy::Hash);
+        let first = hash.redact_error("Key (email)=(a@b.c) already exists.");
+        assert_eq!(
+            first,
+            hash.redact_error("Key (email)=(a@b.c) already exists.")
+        );
+        assert!(first.starts_with("Key (email)=(#"));
+        assert!(!first.contains("a@b.c"));
+        let error = "Key (email)=(a@b.c) already exists.";
+        assert_eq!(privacy(&[], LiteralPolicy::Keep).redact_error(error), error);
+    }
+}
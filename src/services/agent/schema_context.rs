@@ -0,0 +1,372 @@
+//! Picking the tables relevant to a request for the model's schema context.
+//!
+//! Sending every table of a large database with each prompt is slow and
+//! costly. Tables are indexed locally with BM25 over their names, column
+//! names, descriptions and foreign keys, and only the best matches for the
+//! text of a request are described to the model.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::services::{DatabaseSchema, TableSchema};
+
+/// Most tables described for one request. Schemas this small or smaller
+/// are sent whole.
+pub const MAX_CONTEXT_TABLES: usize = 12;
+
+/// BM25 term frequency saturation.
+const K1: f32 = 1.2;
+/// BM25 document length normalization.
+const B: f32 = 0.75;
+/// Weight of table name terms over column and description terms.
+const TABLE_NAME_WEIGHT: f32 = 3.0;
+
+/// The tables of a schema, described for the model and indexed for search.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaContext {
+    tables: Vec<IndexedTable>,
+    /// Tables containing each term.
+    document_frequency: HashMap<String, usize>,
+    average_length: f32,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedTable {
+    /// `schema.table`.
+    name: String,
+    description: String,
+    terms: HashMap<String, f32>,
+    length: f32,
+    /// Indexes of the tables this one has foreign keys to.
+    references: Vec<usize>,
+}
+
+/// The schema context chosen for one request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaSelection {
+    /// `schema.table` names of the described tables, most relevant first.
+    pub tables: Vec<String>,
+    pub total_tables: usize,
+    /// The description sent to the model, `None` when no table was picked.
+    pub text: Option<String>,
+}
+
+impl SchemaSelection {
+    /// Whether some tables were left out.
+    pub fn is_partial(&self) -> bool {
+        self.tables.len() < self.total_tables
+    }
+}
+
+impl SchemaContext {
+    /// Index the tables of `schema`, keeping `describe`'s text for each.
+    pub fn new(schema: &DatabaseSchema, describe: impl Fn(&TableSchema) -> String) -> Self {
+        let positions: HashMap<(String, String), usize> = schema
+            .tables
+            .iter()
+            .enumerate()
+            .map(|(ix, t)| ((t.table_schema.clone(), t.table_name.clone()), ix))
+            .collect();
+
+        let tables: Vec<IndexedTable> = schema
+            .tables
+            .iter()
+            .map(|table| {
+                let terms = table_terms(table);
+                let references = table
+                    .foreign_keys
+                    .iter()
+                    .filter_map(|fk| {
+                        positions
+                            .get(&(
+                                fk.foreign_table_schema.clone(),
+                                fk.foreign_table_name.clone(),
+                            ))
+                            .copied()
+                    })
+                    .collect();
+                IndexedTable {
+                    name: format!("{}.{}", table.table_schema, table.table_name),
+                    description: describe(table),
+                    length: terms.values().sum(),
+                    terms,
+                    references,
+                }
+            })
+            .collect();
+
+        let mut document_frequency = HashMap::new();
+        for table in &tables {
+            for term in table.terms.keys() {
+                *document_frequency.entry(term.clone()).or_insert(0) += 1;
+            }
+        }
+        let average_length = if tables.is_empty() {
+            0.0
+        } else {
+            tables.iter().map(|t| t.length).sum::<f32>() / tables.len() as f32
+        };
+
+        Self {
+            tables,
+            document_frequency,
+            average_length,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// The tables to describe for a request about `text`: all of them for
+    /// a small schema, otherwise the best matches and the tables they
+    /// reference, up to [`MAX_CONTEXT_TABLES`].
+    pub fn select(&self, text: &str) -> SchemaSelection {
+        let picked: Vec<usize> = if self.tables.len() <= MAX_CONTEXT_TABLES {
+            (0..self.tables.len()).collect()
+        } else {
+            self.relevant(text, MAX_CONTEXT_TABLES)
+        };
+
+        let total_tables = self.tables.len();
+        let text = (!picked.is_empty()).then(|| {
+            let header = if picked.len() < total_tables {
+                format!(
+                    "# Database Schema ({} of {} tables, the ones most relevant to this request)\n\n",
+                    picked.len(),
+                    total_tables
+                )
+            } else {
+                format!("# Database Schema ({} tables)\n\n", total_tables)
+            };
+            picked.iter().fold(header, |mut out, &ix| {
+                out.push_str(&self.tables[ix].description);
+                out
+            })
+        });
+
+        SchemaSelection {
+            tables: picked
+                .iter()
+                .map(|&ix| self.tables[ix].name.clone())
+                .collect(),
+            total_tables,
+            text,
+        }
+    }
+
+    /// Up to `limit` tables matching `text`, best first, followed by the
+    /// tables the matches reference while there is room.
+    fn relevant(&self, text: &str, limit: usize) -> Vec<usize> {
+        let query: HashSet<String> = tokenize(text).collect();
+        let mut scored: Vec<(usize, f32)> = self
+            .tables
+            .iter()
+            .enumerate()
+            .map(|(ix, table)| (ix, self.score(table, &query)))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut picked: Vec<usize> = scored.iter().take(limit).map(|(ix, _)| *ix).collect();
+        let mut ix = 0;
+        while ix < picked.len() && picked.len() < limit {
+            for &referenced in &self.tables[picked[ix]].references {
+                if picked.len() < limit && !picked.contains(&referenced) {
+                    picked.push(referenced);
+                }
+            }
+            ix += 1;
+        }
+        picked
+    }
+
+    fn score(&self, table: &IndexedTable, query: &HashSet<String>) -> f32 {
+        let documents = self.tables.len() as f32;
+        query
+            .iter()
+            .filter_map(|term| {
+                let frequency = *table.terms.get(term)?;
+                let containing = self.document_frequency[term] as f32;
+                let idf = ((documents - containing + 0.5) / (containing + 0.5) + 1.0).ln();
+                let norm = K1 * (1.0 - B + B * table.length / self.average_length.max(1.0));
+                Some(idf * frequency * (K1 + 1.0) / (frequency + norm))
+            })
+            .sum()
+    }
+}
+
+/// Weighted terms describing `table`.
+fn table_terms(table: &TableSchema) -> HashMap<String, f32> {
+    let mut terms = HashMap::new();
+    let mut add = |text: &str, weight: f32| {
+        for term in tokenize(text) {
+            *terms.entry(term).or_insert(0.0) += weight;
+        }
+    };
+
+    add(&table.table_name, TABLE_NAME_WEIGHT);
+    add(&table.table_schema, 1.0);
+    if let Some(description) = &table.description {
+        add(description, 1.0);
+    }
+    for column in &table.columns {
+        add(&column.column_name, 1.0);
+        if let Some(description) = &column.description {
+            add(description, 1.0);
+        }
+    }
+    for fk in &table.foreign_keys {
+        add(&fk.foreign_table_name, 1.0);
+    }
+    terms
+}
+
+/// Lowercased words of `text`, split at punctuation, underscores and
+/// camelCase humps, with a plural `s` dropped so `orders` finds `order`.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .flat_map(split_camel_case)
+        .filter(|word| word.len() > 1)
+        .map(|word| {
+            let word = word.to_lowercase();
+            match word.strip_suffix('s') {
+                Some(stem) if stem.len() > 2 && !stem.ends_with(['s', 'u', 'i']) => {
+                    stem.to_string()
+                }
+                _ => word,
+            }
+        })
+}
+
+fn split_camel_case(word: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    for pair in chars.windows(2) {
+        let ((_, prev), (ix, next)) = (pair[0], pair[1]);
+        if prev.is_lowercase() && next.is_uppercase() {
+            parts.push(&word[start..ix]);
+            start = ix;
+        }
+    }
+    parts.push(&word[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{ColumnDetail, ForeignKeyInfo};
+
+    fn column(name: &str) -> ColumnDetail {
+        ColumnDetail {
+            column_name: name.to_string(),
+            data_type: "text".to_string(),
+            is_nullable: true,
+            column_default: None,
+            ordinal_position: 1,
+            character_maximum_length: None,
+            numeric_precision: None,
+            numeric_scale: None,
+            full_type: None,
+            collation: None,
+            extra: None,
+            description: None,
+        }
+    }
+
+    fn table(name: &str, columns: &[&str], references: &[&str]) -> TableSchema {
+        TableSchema {
+            table_name: name.to_string(),
+            table_schema: "public".to_string(),
+            table_type: "BASE TABLE".to_string(),
+            columns: columns.iter().map(|c| column(c)).collect(),
+            primary_keys: vec!["id".to_string()],
+            foreign_keys: references
+                .iter()
+                .map(|r| ForeignKeyInfo {
+                    constraint_name: format!("{}_{}_fkey", name, r),
+                    column_name: format!("{}_id", r),
+                    foreign_table_schema: "public".to_string(),
+                    foreign_table_name: r.to_string(),
+                    foreign_column_name: "id".to_string(),
+                })
+                .collect(),
+            indexes: vec![],
+            constraints: vec![],
+            triggers: vec![],
+            description: None,
+        }
+    }
+
+    /// `tables` plus enough unrelated tables to make selection kick in.
+    fn context(tables: Vec<TableSchema>) -> SchemaContext {
+        let mut tables = tables;
+        for ix in 0..MAX_CONTEXT_TABLES {
+            tables.push(table(&format!("filler_{}", ix), &["id", "payload"], &[]));
+        }
+        let schema = DatabaseSchema {
+            total_tables: tables.len(),
+            tables,
+        };
+        SchemaContext::new(&schema, |t| format!("{}\n", t.table_name))
+    }
+
+    #[test]
+    fn picks_tables_matching_names_and_columns() {
+        let context = context(vec![
+            table("customers", &["id", "email", "full_name"], &[]),
+            table("invoices", &["id", "amount_due"], &[]),
+        ]);
+
+        let selection = context.select("which customer has the most orders by email");
+        assert_eq!(selection.tables, vec!["public.customers"]);
+        assert!(selection.is_partial());
+        assert_eq!(
+            selection.text.as_deref(),
+            Some(
+                "# Database Schema (1 of 14 tables, the ones most relevant to this request)\n\ncustomers\n"
+            )
+        );
+
+        let selection = context.select("SELECT sum(amountDue) FROM");
+        assert_eq!(selection.tables, vec!["public.invoices"]);
+    }
+
+    #[test]
+    fn adds_referenced_tables_after_the_matches() {
+        let context = context(vec![
+            table("customers", &["id", "email"], &[]),
+            table("orders", &["id", "placed_at"], &["customers"]),
+        ]);
+
+        let selection = context.select("orders placed this week");
+        assert_eq!(selection.tables, vec!["public.orders", "public.customers"]);
+    }
+
+    #[test]
+    fn sends_small_schemas_whole_and_nothing_without_matches() {
+        let schema = DatabaseSchema {
+            total_tables: 2,
+            tables: vec![table("a", &["id"], &[]), table("b", &["id"], &[])],
+        };
+        let small = SchemaContext::new(&schema, |t| format!("{}\n", t.table_name));
+        let selection = small.select("unrelated");
+        assert_eq!(selection.tables, vec!["public.a", "public.b"]);
+        assert!(!selection.is_partial());
+        assert_eq!(
+            selection.text.as_deref(),
+            Some("# Database Schema (2 tables)\n\na\nb\n")
+        );
+
+        let selection = context(vec![table("ledger", &["id"], &[])]).select("nothing like this");
+        assert!(selection.tables.is_empty());
+        assert_eq!(selection.text, None);
+    }
+
+    #[test]
+    fn tokenizes_identifiers_into_words() {
+        let words: Vec<String> = tokenize("customerOrders.full_name, Status").collect();
+        assert_eq!(words, vec!["customer", "order", "full", "name", "status"]);
+    }
+}
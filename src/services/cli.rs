@@ -0,0 +1,333 @@
+//! Headless `pgui query` command: run SQL against a saved connection and
+//! print the result, without opening a window.
+//!
+//! ```text
+//! pgui query --connection staging --sql "select 1" --format json
+//! ```
+
+use std::io::{Read as _, Write as _};
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result, anyhow};
+
+use crate::services::{
+    AppStore, ConnectionInfo, ConnectionsRepository, DatabaseManager, QueryExecutionResult,
+    export_to_csv, export_to_json,
+    storage::{CREDENTIAL_STORE_KEY, CredentialStore, Vault, open_vault, use_store},
+};
+
+/// Overrides the keychain password for the connection.
+const PASSWORD_ENV: &str = "PGUI_PASSWORD";
+/// Unlocks the vault when passwords are kept there rather than in the
+/// keychain.
+const MASTER_PASSWORD_ENV: &str = "PGUI_MASTER_PASSWORD";
+
+const USAGE: &str = "\
+Run a query against a saved connection and print the result.
+
+USAGE:
+    pgui query --connection <NAME> (--sql <SQL> | --file <PATH>) [--format csv|json]
+
+OPTIONS:
+    -c, --connection <NAME>    Saved connection name or id
+    -s, --sql <SQL>            SQL to run; `-` reads it from stdin
+    -f, --file <PATH>          Read the SQL from a file
+        --format <FORMAT>      csv (default) or json
+    -h, --help                 Show this help
+
+The password comes from the keychain, or from PGUI_PASSWORD when set.
+When passwords are kept in the encrypted vault, PGUI_MASTER_PASSWORD
+unlocks it.
+SSH tunnels configured on the connection are used as in the app.";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlSource {
+    Inline(String),
+    Stdin,
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCommand {
+    pub connection: String,
+    pub sql: SqlSource,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Query(QueryCommand),
+    Help,
+}
+
+/// Parse the arguments after `query`.
+fn parse_query_args(args: &[String]) -> Result<Command> {
+    let mut connection = None;
+    let mut sql = None;
+    let mut format = OutputFormat::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| anyhow!("{} needs a value", flag))
+        };
+        match flag {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-c" | "--connection" => connection = Some(value()?),
+            "-s" | "--sql" => {
+                let value = value()?;
+                sql = Some(if value == "-" {
+                    SqlSource::Stdin
+                } else {
+                    SqlSource::Inline(value)
+                });
+            }
+            "-f" | "--file" => sql = Some(SqlSource::File(PathBuf::from(value()?))),
+            "--format" => {
+                format = match value()?.to_ascii_lowercase().as_str() {
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    other => return Err(anyhow!("Unknown format '{}'; use csv or json", other)),
+                }
+            }
+            other => return Err(anyhow!("Unexpected argument '{}'", other)),
+        }
+    }
+
+    Ok(Command::Query(QueryCommand {
+        connection: connection.ok_or_else(|| anyhow!("--connection is required"))?,
+        sql: sql.ok_or_else(|| anyhow!("--sql or --file is required"))?,
+        format,
+    }))
+}
+
+/// Run `pgui query ...` when those are the arguments, returning the exit
+/// code. `None` means the app should start normally.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let (first, rest) = args.split_first()?;
+    if first != "query" {
+        return None;
+    }
+
+    let command = match parse_query_args(rest) {
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return Some(0);
+        }
+        Ok(Command::Query(command)) => command,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return Some(2);
+        }
+    };
+
+    match smol::block_on(run_query(command)) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            Some(1)
+        }
+    }
+}
+
+/// A saved connection by id, exact name, or case-insensitive name.
+fn find_connection(connections: Vec<ConnectionInfo>, name: &str) -> Result<ConnectionInfo> {
+    if let Some(found) = connections
+        .iter()
+        .find(|c| c.id.to_string() == name || c.name == name)
+    {
+        return Ok(found.clone());
+    }
+    let mut matches = connections
+        .into_iter()
+        .filter(|c| c.name.eq_ignore_ascii_case(name));
+    match (matches.next(), matches.next()) {
+        (Some(found), None) => Ok(found),
+        (Some(_), Some(_)) => Err(anyhow!(
+            "More than one connection is named '{}'; use its exact name or id",
+            name
+        )),
+        (None, _) => Err(anyhow!("No saved connection named '{}'", name)),
+    }
+}
+
+/// Read passwords from the store the app uses, unlocking the vault with
+/// PGUI_MASTER_PASSWORD rather than connecting without a password.
+async fn open_credential_store(store: &AppStore) -> Result<()> {
+    let credential_store = store
+        .workspace_state()
+        .get_json::<CredentialStore>(CREDENTIAL_STORE_KEY)
+        .await?
+        .unwrap_or_default();
+    use_store(credential_store);
+    if credential_store == CredentialStore::Vault {
+        let master_password = std::env::var(MASTER_PASSWORD_ENV).map_err(|_| {
+            anyhow!(
+                "The password vault is locked; set {} or {}",
+                MASTER_PASSWORD_ENV,
+                PASSWORD_ENV
+            )
+        })?;
+        open_vault(Vault::unlock(&Vault::default_path()?, &master_password)?);
+    }
+    Ok(())
+}
+
+async fn run_query(command: QueryCommand) -> Result<()> {
+    let sql = match command.sql {
+        SqlSource::Inline(sql) => sql,
+        SqlSource::File(path) => std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        SqlSource::Stdin => {
+            let mut sql = String::new();
+            std::io::stdin().read_to_string(&mut sql)?;
+            sql
+        }
+    };
+    if sql.trim().is_empty() {
+        return Err(anyhow!("No SQL to run"));
+    }
+
+    let store = AppStore::singleton().await?;
+    let mut info = find_connection(store.connections().load_all().await?, &command.connection)?;
+    info.password = match std::env::var(PASSWORD_ENV) {
+        Ok(password) => password,
+        Err(_) => {
+            open_credential_store(store).await?;
+            ConnectionsRepository::get_connection_password(&info.id).unwrap_or_default()
+        }
+    };
+
+    // Print every row, not just the first page the app shows.
+    let db_manager = DatabaseManager::new();
+    db_manager.set_max_rows(None).await;
+    db_manager
+        .connect(&info)
+        .await
+        .with_context(|| format!("Failed to connect to {}", info.name))?;
+    let result = db_manager.execute_query_enhanced(&sql).await;
+    let _ = db_manager.disconnect().await;
+
+    match result {
+        QueryExecutionResult::Select(result) => {
+            let output = match command.format {
+                OutputFormat::Csv => export_to_csv(&result)?,
+                OutputFormat::Json => export_to_json(&result)?,
+            };
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(output.as_bytes())?;
+            if !output.ends_with('\n') {
+                stdout.write_all(b"\n")?;
+            }
+            Ok(())
+        }
+        QueryExecutionResult::Modified(modified) => {
+            eprintln!("{} rows affected", modified.rows_affected);
+            Ok(())
+        }
+        QueryExecutionResult::Error(error) => Err(anyhow!(error.message)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_query_arguments() {
+        assert_eq!(
+            parse_query_args(&args(&["--connection", "staging", "--sql=select 1"])).unwrap(),
+            Command::Query(QueryCommand {
+                connection: "staging".to_string(),
+                sql: SqlSource::Inline("select 1".to_string()),
+                format: OutputFormat::Csv,
+            })
+        );
+        assert_eq!(
+            parse_query_args(&args(&["-c", "prod", "-f", "q.sql", "--format", "JSON"])).unwrap(),
+            Command::Query(QueryCommand {
+                connection: "prod".to_string(),
+                sql: SqlSource::File(PathBuf::from("q.sql")),
+                format: OutputFormat::Json,
+            })
+        );
+        assert_eq!(
+            parse_query_args(&args(&["-c", "prod", "-s", "-"])).unwrap(),
+            Command::Query(QueryCommand {
+                connection: "prod".to_string(),
+                sql: SqlSource::Stdin,
+                format: OutputFormat::Csv,
+            })
+        );
+        assert_eq!(
+            parse_query_args(&args(&["-c", "prod", "--help"])).unwrap(),
+            Command::Help
+        );
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse_query_args(&args(&["--sql", "select 1"])).is_err());
+        assert!(parse_query_args(&args(&["-c", "prod"])).is_err());
+        assert!(parse_query_args(&args(&["-c", "prod", "-s", "x", "--format", "xml"])).is_err());
+        assert!(parse_query_args(&args(&["-c"])).is_err());
+        assert!(parse_query_args(&args(&["-c", "prod", "-s", "x", "--verbose"])).is_err());
+    }
+
+    #[test]
+    fn only_handles_the_query_command() {
+        assert_eq!(run_cli(&args(&[])), None);
+        assert_eq!(run_cli(&args(&["report.sql"])), None);
+        assert_eq!(run_cli(&args(&["query", "--help"])), Some(0));
+        assert_eq!(run_cli(&args(&["query", "--format", "xml"])), Some(2));
+    }
+
+    #[test]
+    fn finds_connections_by_name_or_id() {
+        let connection = |name: &str| ConnectionInfo {
+            name: name.to_string(),
+            ..ConnectionInfo::default()
+        };
+        let connections = vec![
+            connection("Staging"),
+            connection("prod"),
+            connection("PROD"),
+        ];
+
+        assert_eq!(
+            find_connection(connections.clone(), "staging")
+                .unwrap()
+                .name,
+            "Staging"
+        );
+        assert_eq!(
+            find_connection(connections.clone(), "PROD").unwrap().name,
+            "PROD"
+        );
+        assert!(find_connection(connections.clone(), "Prod").is_err());
+        let id = connections[1].id.to_string();
+        assert_eq!(
+            find_connection(connections.clone(), &id).unwrap().name,
+            "prod"
+        );
+        assert!(find_connection(connections, "dev").is_err());
+    }
+}
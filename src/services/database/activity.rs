@@ -0,0 +1,190 @@
+//! Sessions from `pg_stat_activity`, and alerts for transactions left open.
+//!
+//! A transaction held open for long keeps VACUUM from cleaning up the rows
+//! it can still see, and one idle in transaction usually means a client
+//! forgot to commit. Both are flagged once they pass their threshold.
+
+use serde::{Deserialize, Serialize};
+
+/// Thresholds offered for long transactions, in seconds.
+pub const LONG_TRANSACTION_OPTIONS: [u64; 4] = [60, 300, 900, 3600];
+
+/// Thresholds offered for sessions idle in transaction, in seconds.
+pub const IDLE_IN_TRANSACTION_OPTIONS: [u64; 4] = [30, 60, 300, 900];
+
+/// A client session of the current database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionActivity {
+    pub pid: i32,
+    pub username: Option<String>,
+    pub application_name: Option<String>,
+    pub client_addr: Option<String>,
+    pub state: Option<String>,
+    pub query: Option<String>,
+    /// Seconds since its transaction began, while one is open.
+    pub transaction_secs: Option<f64>,
+    /// Seconds since its state last changed.
+    pub state_secs: Option<f64>,
+}
+
+impl SessionActivity {
+    /// `idle in transaction`, or `idle in transaction (aborted)`.
+    pub fn is_idle_in_transaction(&self) -> bool {
+        self.state
+            .as_deref()
+            .is_some_and(|state| state.starts_with("idle in transaction"))
+    }
+}
+
+/// When to flag a session; `None` turns an alert off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionAlertSettings {
+    pub long_transaction_secs: Option<u64>,
+    pub idle_in_transaction_secs: Option<u64>,
+}
+
+impl Default for TransactionAlertSettings {
+    fn default() -> Self {
+        Self {
+            long_transaction_secs: Some(300),
+            idle_in_transaction_secs: Some(60),
+        }
+    }
+}
+
+impl TransactionAlertSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.long_transaction_secs.is_some() || self.idle_in_transaction_secs.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionAlertKind {
+    LongTransaction,
+    IdleInTransaction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionAlert {
+    pub pid: i32,
+    pub kind: TransactionAlertKind,
+    /// How long the transaction has been open, or idle.
+    pub secs: u64,
+}
+
+impl TransactionAlert {
+    pub fn message(&self) -> String {
+        match self.kind {
+            TransactionAlertKind::LongTransaction => format!(
+                "Session {} has had a transaction open for {}",
+                self.pid,
+                format_duration(self.secs)
+            ),
+            TransactionAlertKind::IdleInTransaction => format!(
+                "Session {} has been idle in transaction for {}",
+                self.pid,
+                format_duration(self.secs)
+            ),
+        }
+    }
+}
+
+/// Sessions past a threshold of `settings`. A session idle in transaction
+/// past its threshold is flagged for that rather than for its age.
+pub fn transaction_alerts(
+    sessions: &[SessionActivity],
+    settings: &TransactionAlertSettings,
+) -> Vec<TransactionAlert> {
+    sessions
+        .iter()
+        .filter_map(|session| {
+            let idle = settings
+                .idle_in_transaction_secs
+                .zip(session.state_secs)
+                .filter(|(limit, secs)| session.is_idle_in_transaction() && *secs >= *limit as f64)
+                .map(|(_, secs)| (TransactionAlertKind::IdleInTransaction, secs));
+            let long = settings
+                .long_transaction_secs
+                .zip(session.transaction_secs)
+                .filter(|(limit, secs)| *secs >= *limit as f64)
+                .map(|(_, secs)| (TransactionAlertKind::LongTransaction, secs));
+            idle.or(long).map(|(kind, secs)| TransactionAlert {
+                pid: session.pid,
+                kind,
+                secs: secs as u64,
+            })
+        })
+        .collect()
+}
+
+/// `45s`, `5m 12s`, `2h 3m`.
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(
+        pid: i32,
+        state: &str,
+        transaction: Option<f64>,
+        state_secs: f64,
+    ) -> SessionActivity {
+        SessionActivity {
+            pid,
+            username: Some("app".to_string()),
+            application_name: None,
+            client_addr: None,
+            state: Some(state.to_string()),
+            query: None,
+            transaction_secs: transaction,
+            state_secs: Some(state_secs),
+        }
+    }
+
+    #[test]
+    fn flags_long_and_idle_transactions() {
+        let sessions = vec![
+            session(1, "active", Some(10.0), 10.0),
+            session(2, "active", Some(400.0), 2.0),
+            session(3, "idle in transaction", Some(90.0), 75.0),
+            session(4, "idle in transaction (aborted)", Some(900.0), 20.0),
+            session(5, "idle", None, 5000.0),
+        ];
+        let alerts = transaction_alerts(&sessions, &TransactionAlertSettings::default());
+        let flagged: Vec<(i32, TransactionAlertKind, u64)> =
+            alerts.iter().map(|a| (a.pid, a.kind, a.secs)).collect();
+        assert_eq!(
+            flagged,
+            vec![
+                (2, TransactionAlertKind::LongTransaction, 400),
+                (3, TransactionAlertKind::IdleInTransaction, 75),
+                (4, TransactionAlertKind::LongTransaction, 900),
+            ]
+        );
+        assert_eq!(
+            alerts[0].message(),
+            "Session 2 has had a transaction open for 6m 40s"
+        );
+
+        let off = TransactionAlertSettings {
+            long_transaction_secs: None,
+            idle_in_transaction_secs: None,
+        };
+        assert!(!off.is_enabled());
+        assert!(transaction_alerts(&sessions, &off).is_empty());
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(312), "5m 12s");
+        assert_eq!(format_duration(7380), "2h 3m");
+    }
+}
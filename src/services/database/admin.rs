@@ -0,0 +1,170 @@
+//! Creating and dropping databases and schemas.
+//!
+//! On MySQL a schema is a database, so only databases are managed there,
+//! with a character set instead of an encoding and no owner or template.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::storage::DatabaseDriver;
+
+/// A database to create. Empty fields are left to the server's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewDatabase {
+    pub name: String,
+    pub owner: String,
+    /// `ENCODING` on Postgres, `CHARACTER SET` on MySQL.
+    pub encoding: String,
+    pub template: String,
+}
+
+/// A schema to create. An empty owner leaves it to the current user.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewSchema {
+    pub name: String,
+    pub owner: String,
+}
+
+fn required_name(name: &str) -> Result<&str> {
+    match name.trim() {
+        "" => Err(anyhow!("Enter a name")),
+        name => Ok(name),
+    }
+}
+
+pub fn create_database_sql(driver: &DatabaseDriver, database: &NewDatabase) -> Result<String> {
+    let name = required_name(&database.name)?;
+    let owner = database.owner.trim();
+    let encoding = database.encoding.trim();
+    let template = database.template.trim();
+
+    let mut sql = format!("CREATE DATABASE {}", driver.quote_ident(name));
+    match driver {
+        DatabaseDriver::Postgres => {
+            if !owner.is_empty() {
+                sql.push_str(&format!(" OWNER {}", driver.quote_ident(owner)));
+            }
+            if !encoding.is_empty() {
+                sql.push_str(&format!(" ENCODING {}", driver.quote_literal(encoding)));
+            }
+            if !template.is_empty() {
+                sql.push_str(&format!(" TEMPLATE {}", driver.quote_ident(template)));
+            }
+        }
+        DatabaseDriver::MySql => {
+            if !owner.is_empty() || !template.is_empty() {
+                return Err(anyhow!("MySQL databases have no owner or template"));
+            }
+            if !encoding.is_empty() {
+                if !encoding
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+                {
+                    return Err(anyhow!("{} is not a character set name", encoding));
+                }
+                sql.push_str(&format!(" CHARACTER SET {}", encoding));
+            }
+        }
+    }
+    sql.push(';');
+    Ok(sql)
+}
+
+/// `DROP DATABASE`; `force` ends other sessions first (Postgres 13+).
+pub fn drop_database_sql(driver: &DatabaseDriver, name: &str, force: bool) -> Result<String> {
+    let name = required_name(name)?;
+    Ok(match driver {
+        DatabaseDriver::Postgres if force => {
+            format!("DROP DATABASE {} WITH (FORCE);", driver.quote_ident(name))
+        }
+        _ => format!("DROP DATABASE {};", driver.quote_ident(name)),
+    })
+}
+
+pub fn create_schema_sql(driver: &DatabaseDriver, schema: &NewSchema) -> Result<String> {
+    if *driver != DatabaseDriver::Postgres {
+        return Err(anyhow!("On MySQL, create a database instead"));
+    }
+    let name = required_name(&schema.name)?;
+    let mut sql = format!("CREATE SCHEMA {}", driver.quote_ident(name));
+    let owner = schema.owner.trim();
+    if !owner.is_empty() {
+        sql.push_str(&format!(" AUTHORIZATION {}", driver.quote_ident(owner)));
+    }
+    sql.push(';');
+    Ok(sql)
+}
+
+/// `DROP SCHEMA`; `cascade` drops everything in it as well.
+pub fn drop_schema_sql(driver: &DatabaseDriver, name: &str, cascade: bool) -> Result<String> {
+    if *driver != DatabaseDriver::Postgres {
+        return Err(anyhow!("On MySQL, drop the database instead"));
+    }
+    let name = required_name(name)?;
+    Ok(format!(
+        "DROP SCHEMA {}{};",
+        driver.quote_ident(name),
+        if cascade { " CASCADE" } else { "" }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_databases() {
+        let database = NewDatabase {
+            name: "analytics".to_string(),
+            owner: "reporting".to_string(),
+            encoding: "UTF8".to_string(),
+            template: "template0".to_string(),
+        };
+        assert_eq!(
+            create_database_sql(&DatabaseDriver::Postgres, &database).unwrap(),
+            "CREATE DATABASE \"analytics\" OWNER \"reporting\" ENCODING 'UTF8' \
+             TEMPLATE \"template0\";"
+        );
+        assert!(create_database_sql(&DatabaseDriver::MySql, &database).is_err());
+        assert_eq!(
+            create_database_sql(
+                &DatabaseDriver::MySql,
+                &NewDatabase {
+                    name: "analytics".to_string(),
+                    encoding: "utf8mb4".to_string(),
+                    ..NewDatabase::default()
+                }
+            )
+            .unwrap(),
+            "CREATE DATABASE `analytics` CHARACTER SET utf8mb4;"
+        );
+        assert!(create_database_sql(&DatabaseDriver::Postgres, &NewDatabase::default()).is_err());
+    }
+
+    #[test]
+    fn drops_databases_and_schemas() {
+        assert_eq!(
+            drop_database_sql(&DatabaseDriver::Postgres, "old", true).unwrap(),
+            "DROP DATABASE \"old\" WITH (FORCE);"
+        );
+        assert_eq!(
+            drop_database_sql(&DatabaseDriver::MySql, "old", true).unwrap(),
+            "DROP DATABASE `old`;"
+        );
+        assert_eq!(
+            drop_schema_sql(&DatabaseDriver::Postgres, "staging", true).unwrap(),
+            "DROP SCHEMA \"staging\" CASCADE;"
+        );
+        assert_eq!(
+            create_schema_sql(
+                &DatabaseDriver::Postgres,
+                &NewSchema {
+                    name: "staging".to_string(),
+                    owner: "etl".to_string(),
+                }
+            )
+            .unwrap(),
+            "CREATE SCHEMA \"staging\" AUTHORIZATION \"etl\";"
+        );
+        assert!(drop_schema_sql(&DatabaseDriver::MySql, "staging", false).is_err());
+    }
+}
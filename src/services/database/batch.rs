@@ -0,0 +1,239 @@
+//! Batch COMMENT / GRANT scripts for several tables at once.
+//!
+//! The scripts are loaded into the editor for review rather than run
+//! directly, so they are plain literal SQL.
+
+use crate::services::storage::DatabaseDriver;
+
+use super::types::TableInfo;
+
+/// Table-level privileges offered by the batch grant editor.
+pub fn table_privileges(driver: &DatabaseDriver) -> &'static [&'static str] {
+    match driver {
+        DatabaseDriver::Postgres => &[
+            "SELECT",
+            "INSERT",
+            "UPDATE",
+            "DELETE",
+            "TRUNCATE",
+            "REFERENCES",
+            "TRIGGER",
+        ],
+        DatabaseDriver::MySql => &[
+            "SELECT",
+            "INSERT",
+            "UPDATE",
+            "DELETE",
+            "ALTER",
+            "INDEX",
+            "REFERENCES",
+            "TRIGGER",
+        ],
+    }
+}
+
+fn qualified(driver: &DatabaseDriver, table: &TableInfo) -> String {
+    format!(
+        "{}.{}",
+        driver.quote_ident(&table.table_schema),
+        driver.quote_ident(&table.table_name)
+    )
+}
+
+fn is_view(table: &TableInfo) -> bool {
+    table.table_type.contains("VIEW")
+}
+
+/// Object keyword for `COMMENT ON` (Postgres).
+fn comment_target(table: &TableInfo) -> &'static str {
+    match table.table_type.as_str() {
+        "MATERIALIZED VIEW" => "MATERIALIZED VIEW",
+        "VIEW" => "VIEW",
+        "FOREIGN" => "FOREIGN TABLE",
+        _ => "TABLE",
+    }
+}
+
+/// Expand `{schema}` and `{table}` in a comment template.
+pub fn expand_comment_template(template: &str, table: &TableInfo) -> String {
+    template
+        .replace("{schema}", &table.table_schema)
+        .replace("{table}", &table.table_name)
+}
+
+/// One COMMENT statement per table. An empty template clears the comment.
+///
+/// MySQL only stores comments on base tables, so views are listed as
+/// skipped there.
+pub fn comment_script(driver: &DatabaseDriver, tables: &[TableInfo], template: &str) -> String {
+    tables
+        .iter()
+        .map(|table| {
+            let comment = expand_comment_template(template, table);
+            match driver {
+                DatabaseDriver::Postgres => format!(
+                    "COMMENT ON {} {} IS {};",
+                    comment_target(table),
+                    qualified(driver, table),
+                    if comment.is_empty() {
+                        "NULL".to_string()
+                    } else {
+                        driver.quote_literal(&comment)
+                    }
+                ),
+                DatabaseDriver::MySql if is_view(table) => format!(
+                    "-- skipped {}: MySQL views cannot have comments",
+                    qualified(driver, table)
+                ),
+                DatabaseDriver::MySql => format!(
+                    "ALTER TABLE {} COMMENT = {};",
+                    qualified(driver, table),
+                    driver.quote_literal(&comment)
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One GRANT statement per table.
+///
+/// On Postgres `grantee` is a role name (quoted unless it is `PUBLIC`). On
+/// MySQL it is an account and is used as written, e.g. `'app'@'%'`.
+pub fn grant_script(
+    driver: &DatabaseDriver,
+    tables: &[TableInfo],
+    privileges: &[String],
+    grantee: &str,
+) -> String {
+    if privileges.is_empty() || grantee.trim().is_empty() {
+        return String::new();
+    }
+
+    let grantee = grantee.trim();
+    let grantee = match driver {
+        DatabaseDriver::Postgres if grantee.eq_ignore_ascii_case("public") => "PUBLIC".to_string(),
+        DatabaseDriver::Postgres => driver.quote_ident(grantee),
+        DatabaseDriver::MySql => grantee.to_string(),
+    };
+    let privileges = privileges.join(", ");
+
+    tables
+        .iter()
+        .map(|table| match driver {
+            DatabaseDriver::Postgres => format!(
+                "GRANT {} ON TABLE {} TO {};",
+                privileges,
+                qualified(driver, table),
+                grantee
+            ),
+            DatabaseDriver::MySql => format!(
+                "GRANT {} ON {} TO {};",
+                privileges,
+                qualified(driver, table),
+                grantee
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables() -> Vec<TableInfo> {
+        vec![
+            TableInfo {
+                table_schema: "public".to_string(),
+                table_name: "orders".to_string(),
+                table_type: "BASE TABLE".to_string(),
+            },
+            TableInfo {
+                table_schema: "public".to_string(),
+                table_name: "order_totals".to_string(),
+                table_type: "VIEW".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn comment_expands_template_per_table() {
+        let script = comment_script(
+            &DatabaseDriver::Postgres,
+            &tables(),
+            "Owned by billing ({schema}.{table})",
+        );
+        assert_eq!(
+            script,
+            "COMMENT ON TABLE \"public\".\"orders\" IS 'Owned by billing (public.orders)';\n\
+             COMMENT ON VIEW \"public\".\"order_totals\" IS 'Owned by billing (public.order_totals)';"
+        );
+    }
+
+    #[test]
+    fn empty_comment_clears_on_postgres() {
+        let script = comment_script(&DatabaseDriver::Postgres, &tables()[..1], "");
+        assert_eq!(script, "COMMENT ON TABLE \"public\".\"orders\" IS NULL;");
+    }
+
+    #[test]
+    fn foreign_tables_use_their_own_comment_target() {
+        let table = TableInfo {
+            table_schema: "analytics".to_string(),
+            table_name: "events".to_string(),
+            table_type: "FOREIGN".to_string(),
+        };
+        assert_eq!(
+            comment_script(&DatabaseDriver::Postgres, &[table], "remote"),
+            "COMMENT ON FOREIGN TABLE \"analytics\".\"events\" IS 'remote';"
+        );
+    }
+
+    #[test]
+    fn mysql_comment_skips_views() {
+        let script = comment_script(&DatabaseDriver::MySql, &tables(), "it's billing");
+        assert_eq!(
+            script,
+            "ALTER TABLE `public`.`orders` COMMENT = 'it''s billing';\n\
+             -- skipped `public`.`order_totals`: MySQL views cannot have comments"
+        );
+    }
+
+    #[test]
+    fn grant_quotes_role_but_not_public() {
+        let privileges = vec!["SELECT".to_string(), "UPDATE".to_string()];
+        assert_eq!(
+            grant_script(
+                &DatabaseDriver::Postgres,
+                &tables()[..1],
+                &privileges,
+                "report_reader"
+            ),
+            "GRANT SELECT, UPDATE ON TABLE \"public\".\"orders\" TO \"report_reader\";"
+        );
+        assert_eq!(
+            grant_script(
+                &DatabaseDriver::Postgres,
+                &tables()[..1],
+                &privileges,
+                "public"
+            ),
+            "GRANT SELECT, UPDATE ON TABLE \"public\".\"orders\" TO PUBLIC;"
+        );
+    }
+
+    #[test]
+    fn grant_needs_privileges_and_grantee() {
+        assert!(grant_script(&DatabaseDriver::Postgres, &tables(), &[], "app").is_empty());
+        assert!(
+            grant_script(
+                &DatabaseDriver::MySql,
+                &tables(),
+                &["SELECT".to_string()],
+                " "
+            )
+            .is_empty()
+        );
+    }
+}
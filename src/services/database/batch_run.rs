@@ -0,0 +1,300 @@
+//! Running one script against several saved connections, or every database
+//! of a server, one target after another, such as a hotfix applied to each
+//! shard.
+//!
+//! Each target gets a short-lived pool of its own, so the open connection
+//! is left alone. A target stops at its first failing statement; whether
+//! the remaining targets still run is up to the caller.
+
+use crate::services::storage::ConnectionInfo;
+
+use super::types::QueryExecutionResult;
+
+/// A connection to run a batch on, with the database it runs in.
+#[derive(Debug, Clone)]
+pub struct BatchTarget {
+    pub connection: ConnectionInfo,
+}
+
+impl BatchTarget {
+    /// `connection`, in `database` when given instead of its own.
+    pub fn new(connection: ConnectionInfo, database: Option<String>) -> Self {
+        let mut connection = connection;
+        if let Some(database) = database {
+            connection.database = database;
+        }
+        Self { connection }
+    }
+
+    /// `shard-1 · app`
+    pub fn label(&self) -> String {
+        format!("{} · {}", self.connection.name, self.connection.database)
+    }
+}
+
+/// What running the batch did on one target.
+#[derive(Debug, Clone)]
+pub struct BatchTargetResult {
+    /// Every statement of the batch.
+    pub statements: Vec<String>,
+    /// The statements run and their results, up to the first failure.
+    pub results: Vec<(String, QueryExecutionResult)>,
+    /// Why the target could not be reached.
+    pub connection_error: Option<String>,
+}
+
+impl BatchTargetResult {
+    pub fn from_results(statements: Vec<String>, results: Vec<QueryExecutionResult>) -> Self {
+        let results = statements.iter().cloned().zip(results).collect();
+        Self {
+            statements,
+            results,
+            connection_error: None,
+        }
+    }
+
+    pub fn connection_failed(statements: Vec<String>, error: String) -> Self {
+        Self {
+            statements,
+            results: vec![],
+            connection_error: Some(error),
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.connection_error.is_none()
+            && self.results.len() == self.statements.len()
+            && !self
+                .results
+                .iter()
+                .any(|(_, result)| matches!(result, QueryExecutionResult::Error(_)))
+    }
+
+    /// Rows returned or affected by all statements.
+    pub fn rows(&self) -> u64 {
+        self.results
+            .iter()
+            .map(|(_, result)| match result {
+                QueryExecutionResult::Select(r) => r.row_count as u64,
+                QueryExecutionResult::Modified(m) => m.rows_affected,
+                QueryExecutionResult::Error(_) => 0,
+            })
+            .sum()
+    }
+
+    pub fn execution_time_ms(&self) -> u128 {
+        self.results
+            .iter()
+            .map(|(_, result)| match result {
+                QueryExecutionResult::Select(r) => r.execution_time_ms,
+                QueryExecutionResult::Modified(m) => m.execution_time_ms,
+                QueryExecutionResult::Error(e) => e.execution_time_ms,
+            })
+            .sum()
+    }
+
+    /// Why the target failed, naming the statement that did.
+    pub fn error(&self) -> Option<String> {
+        if let Some(error) = &self.connection_error {
+            return Some(format!("Could not connect: {}", error));
+        }
+        self.results
+            .iter()
+            .enumerate()
+            .find_map(|(ix, (_, result))| match result {
+                QueryExecutionResult::Error(e) => Some(format!(
+                    "Statement {} of {}: {}",
+                    ix + 1,
+                    self.statements.len(),
+                    e.message
+                )),
+                _ => None,
+            })
+    }
+
+    /// `3/3 statements · 12 rows · 40 ms`, or what went wrong.
+    pub fn summary(&self) -> String {
+        if let Some(error) = self.error() {
+            return error;
+        }
+        format!(
+            "{}/{} statements · {} row{} · {} ms",
+            self.results.len(),
+            self.statements.len(),
+            self.rows(),
+            if self.rows() == 1 { "" } else { "s" },
+            self.execution_time_ms()
+        )
+    }
+}
+
+/// Where a target is in a batch run.
+#[derive(Debug, Clone)]
+pub enum BatchStatus {
+    Pending,
+    Running,
+    Done(BatchTargetResult),
+    /// Not run because an earlier target failed.
+    Skipped,
+}
+
+impl BatchStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BatchStatus::Pending => "Pending",
+            BatchStatus::Running => "Running",
+            BatchStatus::Done(result) if result.succeeded() => "OK",
+            BatchStatus::Done(_) => "Failed",
+            BatchStatus::Skipped => "Skipped",
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        matches!(self, BatchStatus::Done(result) if !result.succeeded())
+    }
+}
+
+/// `2 of 3 targets succeeded · 1 failed`
+pub fn batch_summary(runs: &[(BatchTarget, BatchStatus)]) -> String {
+    let count = |f: fn(&BatchStatus) -> bool| runs.iter().filter(|(_, s)| f(s)).count();
+    let succeeded = count(|s| matches!(s, BatchStatus::Done(r) if r.succeeded()));
+    let failed = count(BatchStatus::failed);
+    let skipped = count(|s| matches!(s, BatchStatus::Skipped));
+
+    let mut summary = format!("{} of {} targets succeeded", succeeded, runs.len());
+    if failed > 0 {
+        summary.push_str(&format!(" · {} failed", failed));
+    }
+    if skipped > 0 {
+        summary.push_str(&format!(" · {} skipped", skipped));
+    }
+    summary
+}
+
+/// A plain-text report of a batch run, one line per target, for pasting
+/// into a ticket.
+pub fn batch_report(script: &str, runs: &[(BatchTarget, BatchStatus)]) -> String {
+    let width = runs
+        .iter()
+        .map(|(target, _)| target.label().chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut report = format!("{}\n\n", batch_summary(runs));
+    for (target, status) in runs {
+        let detail = match status {
+            BatchStatus::Done(result) => result.summary(),
+            _ => String::new(),
+        };
+        let line = format!(
+            "{:<8}{:<width$}  {}",
+            status.label(),
+            target.label(),
+            detail,
+            width = width
+        );
+        report.push_str(line.trim_end());
+        report.push('\n');
+    }
+    report.push_str("\nScript:\n");
+    report.push_str(script.trim());
+    report.push('\n');
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::types::{ErrorResult, ModifiedResult};
+
+    fn target(name: &str, database: Option<&str>) -> BatchTarget {
+        BatchTarget::new(
+            ConnectionInfo {
+                name: name.to_string(),
+                database: "postgres".to_string(),
+                ..ConnectionInfo::default()
+            },
+            database.map(str::to_string),
+        )
+    }
+
+    fn modified(rows: u64) -> QueryExecutionResult {
+        QueryExecutionResult::Modified(ModifiedResult {
+            rows_affected: rows,
+            execution_time_ms: 5,
+        })
+    }
+
+    fn statements() -> Vec<String> {
+        vec![
+            "UPDATE a SET x = 1".to_string(),
+            "UPDATE b SET y = 2".to_string(),
+        ]
+    }
+
+    #[test]
+    fn summarizes_a_target() {
+        let ok = BatchTargetResult::from_results(statements(), vec![modified(2), modified(1)]);
+        assert!(ok.succeeded());
+        assert_eq!(ok.summary(), "2/2 statements · 3 rows · 10 ms");
+
+        let failed = BatchTargetResult::from_results(
+            statements(),
+            vec![
+                modified(2),
+                QueryExecutionResult::Error(ErrorResult {
+                    message: "relation \"b\" does not exist".to_string(),
+                    execution_time_ms: 1,
+                }),
+            ],
+        );
+        assert!(!failed.succeeded());
+        assert_eq!(
+            failed.summary(),
+            "Statement 2 of 2: relation \"b\" does not exist"
+        );
+
+        let unreachable =
+            BatchTargetResult::connection_failed(statements(), "timed out".to_string());
+        assert!(!unreachable.succeeded());
+        assert_eq!(unreachable.summary(), "Could not connect: timed out");
+    }
+
+    #[test]
+    fn reports_every_target() {
+        assert_eq!(target("shard-1", None).label(), "shard-1 · postgres");
+        let runs = vec![
+            (
+                target("shard-1", Some("app")),
+                BatchStatus::Done(BatchTargetResult::from_results(
+                    statements(),
+                    vec![modified(1), modified(0)],
+                )),
+            ),
+            (
+                target("shard-2", Some("app")),
+                BatchStatus::Done(BatchTargetResult::connection_failed(
+                    statements(),
+                    "refused".to_string(),
+                )),
+            ),
+            (target("shard-3", Some("app")), BatchStatus::Skipped),
+        ];
+        assert_eq!(
+            batch_summary(&runs),
+            "1 of 3 targets succeeded · 1 failed · 1 skipped"
+        );
+
+        let report = batch_report("UPDATE a SET x = 1;", &runs);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[2],
+            "OK      shard-1 · app  2/2 statements · 1 row · 10 ms"
+        );
+        assert_eq!(
+            lines[3],
+            "Failed  shard-2 · app  Could not connect: refused"
+        );
+        assert_eq!(lines[4], "Skipped shard-3 · app");
+        assert!(report.ends_with("Script:\nUPDATE a SET x = 1;\n"));
+    }
+}
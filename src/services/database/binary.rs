@@ -0,0 +1,96 @@
+//! Binary cell values (`bytea`, `BLOB`, ...).
+//!
+//! Drivers show binary values as hex: `\x...` on Postgres and `0x...` on
+//! MySQL. These helpers turn that text back into bytes and recognize the
+//! image formats the results grid can preview.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    Png,
+    Jpeg,
+}
+
+impl ImageKind {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageKind::Png => "png",
+            ImageKind::Jpeg => "jpg",
+        }
+    }
+}
+
+/// Whether a result column of type `type_name` holds binary values.
+pub fn is_binary_type(type_name: &str) -> bool {
+    matches!(
+        type_name.to_uppercase().as_str(),
+        "BYTEA" | "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BINARY" | "VARBINARY"
+    )
+}
+
+/// The bytes of a hex-encoded binary cell, or `None` when the text is not
+/// one.
+pub fn decode_binary(value: &str) -> Option<Vec<u8>> {
+    let hex_digits = value
+        .strip_prefix("\\x")
+        .or_else(|| value.strip_prefix("0x"))?;
+    hex::decode(hex_digits).ok()
+}
+
+/// The image format of `bytes`, from its magic bytes.
+pub fn image_kind(bytes: &[u8]) -> Option<ImageKind> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(ImageKind::Png)
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(ImageKind::Jpeg)
+    } else {
+        None
+    }
+}
+
+/// Human-readable size of a binary value.
+pub fn format_byte_size(len: usize) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if len < 1024 {
+        return format!("{} bytes", len);
+    }
+    let mut size = len as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_postgres_and_mysql_hex() {
+        assert_eq!(
+            decode_binary("\\xdeadbeef"),
+            Some(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(decode_binary("0x00ff"), Some(vec![0x00, 0xff]));
+        assert_eq!(decode_binary("\\x"), Some(vec![]));
+        assert_eq!(decode_binary("deadbeef"), None);
+        assert_eq!(decode_binary("\\xzz"), None);
+    }
+
+    #[test]
+    fn recognizes_images_by_magic_bytes() {
+        let png = decode_binary("\\x89504e470d0a1a0a0000000d49484452").unwrap();
+        assert_eq!(image_kind(&png), Some(ImageKind::Png));
+        assert_eq!(image_kind(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(ImageKind::Jpeg));
+        assert_eq!(image_kind(b"GIF89a"), None);
+        assert_eq!(image_kind(&[]), None);
+    }
+
+    #[test]
+    fn byte_sizes() {
+        assert_eq!(format_byte_size(512), "512 bytes");
+        assert_eq!(format_byte_size(1536), "1.5 KB");
+        assert_eq!(format_byte_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
@@ -0,0 +1,189 @@
+//! How the results grid arranges a query's columns: their order, which
+//! are hidden or pinned, and their widths.
+//!
+//! Layouts are remembered per query shape, so re-running a query with
+//! other values keeps its arrangement. Columns are matched by name, so a
+//! layout still applies when the query gains or loses columns.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::services::storage::normalize_query;
+
+/// Approximate width of a character in the grid, for fitting columns.
+const CHAR_WIDTH: f32 = 7.5;
+
+/// Cell padding and room for the sort icon.
+const CELL_PADDING: f32 = 32.;
+
+pub const MIN_COLUMN_WIDTH: f32 = 60.;
+
+/// Widest a column is made by fitting it to its values.
+pub const MAX_FIT_WIDTH: f32 = 480.;
+
+/// Rows measured when fitting a column to its values.
+pub const FIT_SAMPLE_ROWS: usize = 500;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColumnLayout {
+    /// Column names in display order. Columns not listed follow in result
+    /// order.
+    #[serde(default)]
+    pub order: Vec<String>,
+    #[serde(default)]
+    pub hidden: Vec<String>,
+    /// Columns kept at the left while scrolling sideways.
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// Widths set by resizing or fitting, in pixels.
+    #[serde(default)]
+    pub widths: BTreeMap<String, f32>,
+}
+
+impl ColumnLayout {
+    /// Workspace state key for the layout of `sql`'s results.
+    pub fn key(sql: &str) -> String {
+        let digest = Sha256::digest(normalize_query(sql).as_bytes());
+        format!("column_layout:{}", hex::encode(&digest[..8]))
+    }
+
+    /// Result columns to show, as indexes into `names`, in display order:
+    /// pinned columns first, then the rest as ordered.
+    pub fn arrange(&self, names: &[String]) -> Vec<usize> {
+        let mut placed = vec![false; names.len()];
+        let mut order = vec![];
+        for name in &self.order {
+            // Repeated names are matched in turn
+            if let Some(ix) = (0..names.len()).find(|&ix| !placed[ix] && &names[ix] == name) {
+                placed[ix] = true;
+                order.push(ix);
+            }
+        }
+        order.extend((0..names.len()).filter(|&ix| !placed[ix]));
+        order.retain(|&ix| !self.hidden.contains(&names[ix]));
+
+        let (mut pinned, rest): (Vec<usize>, Vec<usize>) = order
+            .into_iter()
+            .partition(|&ix| self.pinned.contains(&names[ix]));
+        pinned.extend(rest);
+        pinned
+    }
+
+    pub fn is_pinned(&self, name: &str) -> bool {
+        self.pinned.iter().any(|pinned| pinned == name)
+    }
+
+    pub fn toggle_pinned(&mut self, name: &str) {
+        if self.is_pinned(name) {
+            self.pinned.retain(|pinned| pinned != name);
+        } else {
+            self.pinned.push(name.to_string());
+        }
+    }
+
+    pub fn hide(&mut self, name: &str) {
+        if !self.hidden.iter().any(|hidden| hidden == name) {
+            self.hidden.push(name.to_string());
+        }
+    }
+
+    pub fn width(&self, name: &str) -> Option<f32> {
+        self.widths.get(name).copied()
+    }
+
+    pub fn set_width(&mut self, name: &str, width: f32) {
+        self.widths
+            .insert(name.to_string(), width.max(MIN_COLUMN_WIDTH));
+    }
+}
+
+/// Width fitting `header` and the first line of each of `values`.
+pub fn fit_width<'a>(header: &str, values: impl IntoIterator<Item = &'a str>) -> f32 {
+    let longest = values
+        .into_iter()
+        .map(|value| value.lines().next().unwrap_or("").chars().count())
+        .chain([header.chars().count()])
+        .max()
+        .unwrap_or(0);
+    (longest as f32 * CHAR_WIDTH + CELL_PADDING).clamp(MIN_COLUMN_WIDTH, MAX_FIT_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn arranges_ordered_hidden_and_pinned_columns() {
+        let columns = names(&["id", "name", "email", "created_at"]);
+        assert_eq!(ColumnLayout::default().arrange(&columns), vec![0, 1, 2, 3]);
+
+        let layout = ColumnLayout {
+            order: names(&["email", "gone", "id"]),
+            hidden: names(&["name"]),
+            pinned: names(&["created_at"]),
+            ..Default::default()
+        };
+        // Pinned first, then the saved order, then columns it did not list
+        assert_eq!(layout.arrange(&columns), vec![3, 2, 0]);
+    }
+
+    #[test]
+    fn repeated_names_are_matched_in_turn() {
+        let columns = names(&["id", "id", "total"]);
+        let layout = ColumnLayout {
+            order: names(&["total", "id"]),
+            ..Default::default()
+        };
+        assert_eq!(layout.arrange(&columns), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn pins_and_hides_by_name() {
+        let mut layout = ColumnLayout::default();
+        layout.toggle_pinned("id");
+        assert!(layout.is_pinned("id"));
+        layout.toggle_pinned("id");
+        assert!(!layout.is_pinned("id"));
+
+        layout.hide("email");
+        layout.hide("email");
+        assert_eq!(layout.hidden, names(&["email"]));
+
+        layout.set_width("email", 10.);
+        assert_eq!(layout.width("email"), Some(MIN_COLUMN_WIDTH));
+    }
+
+    #[test]
+    fn layouts_are_keyed_by_query_shape() {
+        assert_eq!(
+            ColumnLayout::key("SELECT * FROM users WHERE id = 1"),
+            ColumnLayout::key("select *  from users where id = 42")
+        );
+        assert_ne!(
+            ColumnLayout::key("SELECT * FROM users"),
+            ColumnLayout::key("SELECT * FROM orders")
+        );
+        assert!(ColumnLayout::key("SELECT 1").starts_with("column_layout:"));
+    }
+
+    #[test]
+    fn fits_widths_to_values() {
+        assert_eq!(fit_width("id", ["1", "2"]), MIN_COLUMN_WIDTH);
+        assert_eq!(fit_width("email", ["someone@example.com"]), 19. * 7.5 + 32.);
+        // Only the first line of a multi-line value counts
+        assert_eq!(
+            fit_width("memo", ["short\n".repeat(100).as_str()]),
+            5. * 7.5 + 32.
+        );
+        assert_eq!(
+            fit_width("body", ["x".repeat(1000).as_str()]),
+            MAX_FIT_WIDTH
+        );
+    }
+}
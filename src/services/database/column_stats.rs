@@ -0,0 +1,119 @@
+//! Summary statistics of one column of a result, computed client-side
+//! over the rows already fetched.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use super::types::ResultRows;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub rows: usize,
+    pub nulls: usize,
+    pub distinct: usize,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// Most frequent non-NULL values with their counts, most frequent first.
+    pub top_values: Vec<(String, usize)>,
+}
+
+/// Order values numerically when both parse as numbers, as text otherwise.
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+/// Statistics of result column `col` over the rows at `row_indexes`,
+/// keeping the `top` most frequent values.
+pub fn column_stats(
+    rows: &ResultRows,
+    row_indexes: impl IntoIterator<Item = usize>,
+    col: usize,
+    top: usize,
+) -> ColumnStats {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut total = 0;
+    let mut nulls = 0;
+    for row in row_indexes {
+        total += 1;
+        let (value, is_null) = rows.cell_text(row, col);
+        if is_null {
+            nulls += 1;
+        } else {
+            *counts.entry(value).or_default() += 1;
+        }
+    }
+
+    let min = counts.keys().min_by(|a, b| compare_values(a, b)).cloned();
+    let max = counts.keys().max_by(|a, b| compare_values(a, b)).cloned();
+    let distinct = counts.len();
+
+    let mut top_values: Vec<(String, usize)> = counts.into_iter().collect();
+    top_values.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+    top_values.truncate(top);
+
+    ColumnStats {
+        rows: total,
+        nulls,
+        distinct,
+        min,
+        max,
+        top_values,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single_column(values: &[Option<&str>]) -> ResultRows {
+        ResultRows::from_text(values.iter().map(|v| vec![v.map(String::from)]).collect())
+    }
+
+    #[test]
+    fn counts_nulls_distinct_and_top_values() {
+        let rows = single_column(&[
+            Some("b"),
+            Some("a"),
+            None,
+            Some("b"),
+            Some("c"),
+            None,
+            Some("a"),
+            Some("b"),
+        ]);
+        let stats = column_stats(&rows, 0..rows.len(), 0, 2);
+
+        assert_eq!(stats.rows, 8);
+        assert_eq!(stats.nulls, 2);
+        assert_eq!(stats.distinct, 3);
+        assert_eq!(stats.min.as_deref(), Some("a"));
+        assert_eq!(stats.max.as_deref(), Some("c"));
+        assert_eq!(
+            stats.top_values,
+            vec![("b".to_string(), 3), ("a".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn numbers_compare_numerically() {
+        let rows = single_column(&[Some("9"), Some("10"), Some("-2.5"), Some("100")]);
+        let stats = column_stats(&rows, 0..rows.len(), 0, 10);
+        assert_eq!(stats.min.as_deref(), Some("-2.5"));
+        assert_eq!(stats.max.as_deref(), Some("100"));
+    }
+
+    #[test]
+    fn only_the_given_rows_count() {
+        let rows = single_column(&[Some("x"), None, Some("y")]);
+        let stats = column_stats(&rows, [0, 2], 0, 10);
+        assert_eq!(stats.rows, 2);
+        assert_eq!(stats.nulls, 0);
+
+        let stats = column_stats(&rows, [1], 0, 10);
+        assert_eq!(stats.min, None);
+        assert!(stats.top_values.is_empty());
+    }
+}
@@ -0,0 +1,497 @@
+//! Row-level data comparison of a single table across two connections.
+//!
+//! Rows are matched by primary key. Each backend hashes every row on the
+//! server (`md5` over the row's text representation) and returns only
+//! `(key, hash)` pairs in keyset-paginated batches, so the comparison never
+//! has to pull full tables over the wire. Both sides are read in key order
+//! and compared a page at a time, so neither is held in memory. Full row
+//! values are fetched only for the listed keys that differ, and only when
+//! a sync script is requested.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::services::storage::DatabaseDriver;
+
+/// Number of `(key, hash)` pairs fetched per round trip.
+pub const COMPARE_BATCH_SIZE: usize = 5_000;
+
+/// Number of keys looked up per round trip when fetching full rows for the
+/// sync script.
+pub const COMPARE_FETCH_CHUNK: usize = 200;
+
+/// Differences a report lists and scripts; any beyond are only counted.
+pub const COMPARE_LISTED_DIFFS: usize = 1_000;
+
+/// Columns participating in a comparison. `key_columns` is always a prefix
+/// of `columns`; `key_types` holds the declared type of each key column so
+/// backends can cast keyset bounds back to the native type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareColumns {
+    pub key_columns: Vec<String>,
+    pub key_types: Vec<String>,
+    pub columns: Vec<String>,
+}
+
+impl CompareColumns {
+    /// Build the column set from both sides' column lists. Only columns
+    /// present on both sides are hashed, in source order, with the primary
+    /// key columns first.
+    pub fn from_sides(
+        key_columns: Vec<(String, String)>,
+        source_columns: &[String],
+        target_columns: &[String],
+    ) -> Self {
+        let key_names: Vec<String> = key_columns.iter().map(|(name, _)| name.clone()).collect();
+        let mut columns = key_names.clone();
+        columns.extend(
+            source_columns
+                .iter()
+                .filter(|c| !key_names.contains(c) && target_columns.contains(c))
+                .cloned(),
+        );
+
+        Self {
+            key_columns: key_names,
+            key_types: key_columns.into_iter().map(|(_, ty)| ty).collect(),
+            columns,
+        }
+    }
+
+    /// Non-key columns, in hashing order.
+    pub fn value_columns(&self) -> &[String] {
+        &self.columns[self.key_columns.len()..]
+    }
+}
+
+/// Primary key (rendered as text) and the server-computed hash of a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowHash {
+    pub key: Vec<String>,
+    pub hash: String,
+}
+
+/// A full row fetched for sync script generation. `values` follows
+/// [`CompareColumns::columns`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncRow {
+    pub key: Vec<String>,
+    pub values: Vec<Option<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowDiffKind {
+    /// Present in the source, absent from the target.
+    Missing,
+    /// Present in the target, absent from the source.
+    Extra,
+    /// Present on both sides with different values.
+    Different,
+}
+
+impl RowDiffKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RowDiffKind::Missing => "missing",
+            RowDiffKind::Extra => "extra",
+            RowDiffKind::Different => "different",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowDiff {
+    pub key: Vec<String>,
+    pub kind: RowDiffKind,
+}
+
+/// Outcome of comparing one table between a source and a target connection.
+#[derive(Debug, Clone)]
+pub struct DataCompareReport {
+    pub table_schema: String,
+    pub table_name: String,
+    pub columns: CompareColumns,
+    pub source_rows: usize,
+    pub target_rows: usize,
+    /// The first [`COMPARE_LISTED_DIFFS`] differences found, a page at a
+    /// time in key order.
+    pub diffs: Vec<RowDiff>,
+    missing: usize,
+    extra: usize,
+    different: usize,
+    /// INSERT/UPDATE statements that bring the target in line with the
+    /// source for the listed differences. Only populated when requested.
+    pub sync_script: Option<String>,
+}
+
+impl DataCompareReport {
+    pub fn new(table_schema: &str, table_name: &str, columns: CompareColumns) -> Self {
+        Self {
+            table_schema: table_schema.to_string(),
+            table_name: table_name.to_string(),
+            columns,
+            source_rows: 0,
+            target_rows: 0,
+            diffs: vec![],
+            missing: 0,
+            extra: 0,
+            different: 0,
+            sync_script: None,
+        }
+    }
+
+    /// Count `diffs`, listing them while there is room.
+    pub fn record(&mut self, diffs: Vec<RowDiff>) {
+        for diff in diffs {
+            match diff.kind {
+                RowDiffKind::Missing => self.missing += 1,
+                RowDiffKind::Extra => self.extra += 1,
+                RowDiffKind::Different => self.different += 1,
+            }
+            if self.diffs.len() < COMPARE_LISTED_DIFFS {
+                self.diffs.push(diff);
+            }
+        }
+    }
+
+    pub fn count(&self, kind: RowDiffKind) -> usize {
+        match kind {
+            RowDiffKind::Missing => self.missing,
+            RowDiffKind::Extra => self.extra,
+            RowDiffKind::Different => self.different,
+        }
+    }
+
+    /// Differences found, listed or not.
+    fn total(&self) -> usize {
+        self.missing + self.extra + self.different
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.total() == 0
+    }
+
+    /// One-line summary suitable for a notification.
+    pub fn summary(&self) -> String {
+        if self.is_identical() {
+            return format!(
+                "{}.{}: {} rows identical",
+                self.table_schema, self.table_name, self.source_rows
+            );
+        }
+        format!(
+            "{}.{}: {} missing, {} extra, {} different",
+            self.table_schema,
+            self.table_name,
+            self.count(RowDiffKind::Missing),
+            self.count(RowDiffKind::Extra),
+            self.count(RowDiffKind::Different),
+        )
+    }
+
+    /// Render the report as a SQL comment block followed by the sync
+    /// script (if any), ready to be loaded into the editor.
+    pub fn to_sql(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "-- Data comparison: {}.{}\n",
+            self.table_schema, self.table_name
+        ));
+        out.push_str(&format!(
+            "-- Key: {}\n",
+            self.columns.key_columns.join(", ")
+        ));
+        out.push_str(&format!(
+            "-- Source rows: {}, target rows: {}\n",
+            self.source_rows, self.target_rows
+        ));
+        out.push_str(&format!("-- {}\n", self.summary()));
+
+        for diff in &self.diffs {
+            out.push_str(&format!(
+                "--   {:<9} ({})\n",
+                diff.kind.as_str(),
+                diff.key.join(", ")
+            ));
+        }
+        let unlisted = self.total() - self.diffs.len();
+        if unlisted > 0 {
+            out.push_str(&format!("--   ... and {} more, not listed\n", unlisted));
+        }
+
+        if let Some(script) = &self.sync_script {
+            out.push('\n');
+            if unlisted > 0 {
+                out.push_str(&format!(
+                    "-- Syncs the first {} differences only\n",
+                    self.diffs.len()
+                ));
+            }
+            out.push_str(script);
+        }
+
+        out
+    }
+}
+
+/// Compares one page of source rows with the target rows in the same key
+/// range. The target rows come a batch at a time, so a target with many
+/// more rows than the source is never held in memory.
+pub struct PageDiff<'a> {
+    source: &'a [RowHash],
+    hashes: HashMap<&'a [String], &'a str>,
+    matched: HashSet<&'a [String]>,
+}
+
+impl<'a> PageDiff<'a> {
+    pub fn new(source: &'a [RowHash]) -> Self {
+        Self {
+            source,
+            hashes: source
+                .iter()
+                .map(|r| (r.key.as_slice(), r.hash.as_str()))
+                .collect(),
+            matched: HashSet::new(),
+        }
+    }
+
+    /// Target rows the source page lacks, and those whose hash differs,
+    /// in target order.
+    pub fn target_rows(&mut self, target: &[RowHash]) -> Vec<RowDiff> {
+        target
+            .iter()
+            .filter_map(|row| match self.hashes.get_key_value(row.key.as_slice()) {
+                Some((key, hash)) => {
+                    self.matched.insert(*key);
+                    (*hash != row.hash).then(|| RowDiff {
+                        key: row.key.clone(),
+                        kind: RowDiffKind::Different,
+                    })
+                }
+                None => Some(RowDiff {
+                    key: row.key.clone(),
+                    kind: RowDiffKind::Extra,
+                }),
+            })
+            .collect()
+    }
+
+    /// Source rows no target row matched, in source order.
+    pub fn missing(self) -> Vec<RowDiff> {
+        self.source
+            .iter()
+            .filter(|row| !self.matched.contains(row.key.as_slice()))
+            .map(|row| RowDiff {
+                key: row.key.clone(),
+                kind: RowDiffKind::Missing,
+            })
+            .collect()
+    }
+}
+
+/// Generate INSERT statements for missing rows and UPDATE statements for
+/// different rows, using the source values in `rows`. Extra rows are
+/// listed as commented-out DELETEs so they are never removed by accident.
+pub fn generate_sync_script(
+    driver: &DatabaseDriver,
+    report: &DataCompareReport,
+    rows: &[SyncRow],
+) -> String {
+    let table = format!(
+        "{}.{}",
+        driver.quote_ident(&report.table_schema),
+        driver.quote_ident(&report.table_name)
+    );
+    let cols = &report.columns;
+    let row_map: HashMap<&[String], &SyncRow> =
+        rows.iter().map(|r| (r.key.as_slice(), r)).collect();
+
+    let literal = |value: &Option<String>| match value {
+        Some(v) => driver.quote_literal(v),
+        None => "NULL".to_string(),
+    };
+    let key_predicate = |key: &[String]| {
+        cols.key_columns
+            .iter()
+            .zip(key)
+            .map(|(c, v)| format!("{} = {}", driver.quote_ident(c), driver.quote_literal(v)))
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    };
+
+    let mut out = String::new();
+
+    for diff in &report.diffs {
+        match diff.kind {
+            RowDiffKind::Missing => {
+                let Some(row) = row_map.get(diff.key.as_slice()) else {
+                    continue;
+                };
+                out.push_str(&format!(
+                    "INSERT INTO {} ({}) VALUES ({});\n",
+                    table,
+                    cols.columns
+                        .iter()
+                        .map(|c| driver.quote_ident(c))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    row.values
+                        .iter()
+                        .map(literal)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            RowDiffKind::Different => {
+                let Some(row) = row_map.get(diff.key.as_slice()) else {
+                    continue;
+                };
+                let assignments = cols
+                    .value_columns()
+                    .iter()
+                    .zip(&row.values[cols.key_columns.len()..])
+                    .map(|(c, v)| format!("{} = {}", driver.quote_ident(c), literal(v)))
+                    .collect::<Vec<_>>();
+                if assignments.is_empty() {
+                    continue;
+                }
+                out.push_str(&format!(
+                    "UPDATE {} SET {} WHERE {};\n",
+                    table,
+                    assignments.join(", "),
+                    key_predicate(&diff.key)
+                ));
+            }
+            RowDiffKind::Extra => {
+                out.push_str(&format!(
+                    "-- DELETE FROM {} WHERE {};\n",
+                    table,
+                    key_predicate(&diff.key)
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(key: &str, hash: &str) -> RowHash {
+        RowHash {
+            key: vec![key.to_string()],
+            hash: hash.to_string(),
+        }
+    }
+
+    fn columns() -> CompareColumns {
+        CompareColumns::from_sides(
+            vec![("id".to_string(), "integer".to_string())],
+            &["id".to_string(), "name".to_string(), "only_src".to_string()],
+            &["name".to_string(), "id".to_string()],
+        )
+    }
+
+    #[test]
+    fn compare_columns_keeps_common_columns_with_keys_first() {
+        let cols = columns();
+        assert_eq!(cols.columns, vec!["id", "name"]);
+        assert_eq!(cols.value_columns(), &["name".to_string()]);
+        assert_eq!(cols.key_types, vec!["integer"]);
+    }
+
+    #[test]
+    fn diff_detects_missing_extra_and_different() {
+        let source = vec![hash("1", "a"), hash("2", "b"), hash("3", "c")];
+        let mut page = PageDiff::new(&source);
+
+        // The target rows of the page, in two batches
+        let mut diffs = page.target_rows(&[hash("1", "a"), hash("2", "x")]);
+        diffs.extend(page.target_rows(&[hash("4", "d")]));
+        diffs.extend(page.missing());
+        assert_eq!(
+            diffs,
+            vec![
+                RowDiff {
+                    key: vec!["2".to_string()],
+                    kind: RowDiffKind::Different
+                },
+                RowDiff {
+                    key: vec!["4".to_string()],
+                    kind: RowDiffKind::Extra
+                },
+                RowDiff {
+                    key: vec!["3".to_string()],
+                    kind: RowDiffKind::Missing
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_of_identical_sets_is_empty() {
+        let rows = vec![hash("1", "a"), hash("2", "b")];
+        let mut page = PageDiff::new(&rows);
+        assert!(page.target_rows(&rows).is_empty());
+        assert!(page.missing().is_empty());
+    }
+
+    #[test]
+    fn sync_script_inserts_updates_and_comments_deletes() {
+        let mut report = DataCompareReport::new("public", "users", columns());
+        report.record(vec![
+            RowDiff {
+                key: vec!["2".to_string()],
+                kind: RowDiffKind::Different,
+            },
+            RowDiff {
+                key: vec!["3".to_string()],
+                kind: RowDiffKind::Missing,
+            },
+            RowDiff {
+                key: vec!["4".to_string()],
+                kind: RowDiffKind::Extra,
+            },
+        ]);
+        let rows = vec![
+            SyncRow {
+                key: vec!["2".to_string()],
+                values: vec![Some("2".to_string()), Some("O'Brien".to_string())],
+            },
+            SyncRow {
+                key: vec!["3".to_string()],
+                values: vec![Some("3".to_string()), None],
+            },
+        ];
+
+        let script = generate_sync_script(&DatabaseDriver::Postgres, &report, &rows);
+        assert_eq!(
+            script,
+            "UPDATE \"public\".\"users\" SET \"name\" = 'O''Brien' WHERE \"id\" = '2';\n\
+             INSERT INTO \"public\".\"users\" (\"id\", \"name\") VALUES ('3', NULL);\n\
+             -- DELETE FROM \"public\".\"users\" WHERE \"id\" = '4';\n"
+        );
+    }
+
+    #[test]
+    fn report_lists_a_capped_number_of_differences() {
+        let mut report = DataCompareReport::new("public", "users", columns());
+        let missing = (0..COMPARE_LISTED_DIFFS + 5).map(|ix| RowDiff {
+            key: vec![ix.to_string()],
+            kind: RowDiffKind::Missing,
+        });
+        report.record(missing.collect());
+        report.record(vec![RowDiff {
+            key: vec!["x".to_string()],
+            kind: RowDiffKind::Extra,
+        }]);
+
+        assert_eq!(report.diffs.len(), COMPARE_LISTED_DIFFS);
+        assert_eq!(report.count(RowDiffKind::Missing), COMPARE_LISTED_DIFFS + 5);
+        assert_eq!(report.count(RowDiffKind::Extra), 1);
+        let sql = report.to_sql();
+        assert_eq!(sql.lines().count(), 4 + COMPARE_LISTED_DIFFS + 1);
+        assert!(sql.ends_with("--   ... and 6 more, not listed\n"));
+    }
+}
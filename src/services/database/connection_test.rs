@@ -0,0 +1,345 @@
+//! Staged diagnostics behind "Test Connection". Each step of reaching the
+//! server is checked in turn, so a failure says where it happened and
+//! what to try next.
+
+use std::future::Future;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::services::storage::SslMode;
+
+/// Time allowed for each network stage: DNS lookup, TCP connect and the
+/// TLS probe.
+pub const STAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Postgres `SSLRequest`: length 8, then the request code 80877103.
+pub const PG_SSL_REQUEST: [u8; 8] = [0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f];
+
+/// `CLIENT_SSL` in the capability flags of a MySQL handshake.
+const MYSQL_CLIENT_SSL: u16 = 0x0800;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStage {
+    Dns,
+    Tcp,
+    SshTunnel,
+    Tls,
+    Authentication,
+    Query,
+}
+
+impl TestStage {
+    pub const ALL: [TestStage; 6] = [
+        TestStage::Dns,
+        TestStage::Tcp,
+        TestStage::SshTunnel,
+        TestStage::Tls,
+        TestStage::Authentication,
+        TestStage::Query,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TestStage::Dns => "DNS resolution",
+            TestStage::Tcp => "TCP connection",
+            TestStage::SshTunnel => "SSH tunnel",
+            TestStage::Tls => "TLS negotiation",
+            TestStage::Authentication => "Authentication",
+            TestStage::Query => "Test query",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone)]
+pub struct StageResult {
+    pub stage: TestStage,
+    pub status: StageStatus,
+    pub detail: String,
+    /// What to try when the stage failed.
+    pub hint: Option<String>,
+    pub duration_ms: Option<u128>,
+}
+
+/// Outcome of each stage of a connection test, in order.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionReport {
+    pub stages: Vec<StageResult>,
+}
+
+impl ConnectionReport {
+    pub fn passed(&self) -> bool {
+        self.stages.iter().all(|s| s.status != StageStatus::Failed)
+    }
+
+    /// The first stage that failed.
+    pub fn failure(&self) -> Option<&StageResult> {
+        self.stages.iter().find(|s| s.status == StageStatus::Failed)
+    }
+
+    pub(crate) fn pass(&mut self, stage: TestStage, detail: impl Into<String>, started: Instant) {
+        self.push(
+            stage,
+            StageStatus::Passed,
+            detail.into(),
+            None,
+            Some(started),
+        );
+    }
+
+    pub(crate) fn fail(
+        &mut self,
+        stage: TestStage,
+        detail: impl Into<String>,
+        hint: Option<String>,
+        started: Instant,
+    ) {
+        self.push(
+            stage,
+            StageStatus::Failed,
+            detail.into(),
+            hint,
+            Some(started),
+        );
+    }
+
+    pub(crate) fn skip(&mut self, stage: TestStage, detail: impl Into<String>) {
+        self.push(stage, StageStatus::Skipped, detail.into(), None, None);
+    }
+
+    /// Mark the stages not reached after a failure as skipped.
+    pub(crate) fn skip_remaining(&mut self) {
+        for stage in TestStage::ALL {
+            if !self.stages.iter().any(|s| s.stage == stage) {
+                self.skip(stage, "Not run after an earlier failure");
+            }
+        }
+    }
+
+    fn push(
+        &mut self,
+        stage: TestStage,
+        status: StageStatus,
+        detail: String,
+        hint: Option<String>,
+        started: Option<Instant>,
+    ) {
+        self.stages.push(StageResult {
+            stage,
+            status,
+            detail,
+            hint,
+            duration_ms: started.map(|at| at.elapsed().as_millis()),
+        });
+    }
+}
+
+/// `future`, or a `TimedOut` error after [`STAGE_TIMEOUT`].
+pub(crate) async fn with_timeout<T>(future: impl Future<Output = io::Result<T>>) -> io::Result<T> {
+    smol::future::or(future, async {
+        smol::Timer::after(STAGE_TIMEOUT).await;
+        Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"))
+    })
+    .await
+}
+
+/// Whether a Postgres server's one-byte answer to [`PG_SSL_REQUEST`]
+/// accepts TLS; `None` when the reply is not from Postgres.
+pub fn pg_accepts_tls(reply: u8) -> Option<bool> {
+    match reply {
+        b'S' => Some(true),
+        b'N' => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the initial handshake packet a MySQL server sends advertises
+/// TLS; `None` when the packet is not a protocol 10 handshake.
+pub fn mysql_offers_tls(packet: &[u8]) -> Option<bool> {
+    // 3-byte length and sequence id, then protocol version 10.
+    let payload = packet.get(4..)?;
+    if *payload.first()? != 10 {
+        return None;
+    }
+    // NUL-terminated server version, connection id (4), first part of
+    // the auth data (8) and a filler byte precede the capability flags.
+    let version_end = payload.get(1..)?.iter().position(|&b| b == 0)? + 1;
+    let flags_at = version_end + 1 + 4 + 8 + 1;
+    let flags = u16::from_le_bytes([*payload.get(flags_at)?, *payload.get(flags_at + 1)?]);
+    Some(flags & MYSQL_CLIENT_SSL != 0)
+}
+
+/// Status, detail and hint of the TLS stage when the server does or does
+/// not offer TLS under `mode`.
+pub fn tls_outcome(mode: &SslMode, offered: bool) -> (StageStatus, String, Option<String>) {
+    match (offered, mode) {
+        (true, _) => (StageStatus::Passed, "Server accepts TLS".to_string(), None),
+        (false, SslMode::Disable | SslMode::Prefer) => (
+            StageStatus::Passed,
+            "Server does not offer TLS; connecting without it".to_string(),
+            None,
+        ),
+        (false, _) => (
+            StageStatus::Failed,
+            format!(
+                "Server does not offer TLS, which SSL mode {} needs",
+                mode.as_str()
+            ),
+            Some("Set SSL mode to Prefer or Disable, or enable SSL on the server.".to_string()),
+        ),
+    }
+}
+
+pub fn dns_hint() -> String {
+    "Check the host name for typos, or use the server's IP address.".to_string()
+}
+
+pub fn tcp_hint(kind: io::ErrorKind, host: &str, port: u16) -> String {
+    match kind {
+        io::ErrorKind::ConnectionRefused => format!(
+            "Nothing is listening on {}:{}. Check the port and that the server is running.",
+            host, port
+        ),
+        io::ErrorKind::TimedOut => format!(
+            "{}:{} did not answer. A firewall may block the port, or the server may only \
+             listen on localhost.",
+            host, port
+        ),
+        _ => "Check the host and port, and that this machine can reach them.".to_string(),
+    }
+}
+
+pub fn ssh_hint(message: &str) -> String {
+    let message = message.to_lowercase();
+    if message.contains("auth") || message.contains("passphrase") || message.contains("key") {
+        "The SSH server rejected the login. Check the SSH user and the key, passphrase or \
+         password."
+            .to_string()
+    } else {
+        "Check the SSH host and port, and that the SSH server allows forwarding to the \
+         database host."
+            .to_string()
+    }
+}
+
+/// What to try after the server refused a login, from the SQLSTATE and
+/// message of the error.
+pub fn login_hint(code: Option<&str>, message: &str, database: &str) -> Option<String> {
+    let message = message.to_lowercase();
+    match code {
+        Some("28000") if message.contains("pg_hba") => Some(
+            "The server's pg_hba.conf does not allow this user from this host. Ask an \
+             administrator, or try another SSL mode."
+                .to_string(),
+        ),
+        Some("28P01" | "28000") => Some("Check the user name and password.".to_string()),
+        Some("3D000") => Some(format!(
+            "The database \"{}\" does not exist on this server.",
+            database
+        )),
+        Some("42000") if message.contains("unknown database") => Some(format!(
+            "The database \"{}\" does not exist on this server.",
+            database
+        )),
+        Some("53300" | "08004") => Some(
+            "The server has no free connections. Try again later, or raise max_connections."
+                .to_string(),
+        ),
+        _ if message.contains("tls")
+            || message.contains("ssl")
+            || message.contains("certificate") =>
+        {
+            Some(
+                "TLS negotiation failed. Check the SSL mode, and that the server's certificate \
+                 is trusted by this machine."
+                    .to_string(),
+            )
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Handshake of a MySQL 8 server, cut after the capability flags.
+    fn handshake(flags: u16) -> Vec<u8> {
+        let mut payload = vec![10];
+        payload.extend_from_slice(b"8.0.36\0");
+        payload.extend_from_slice(&[1, 0, 0, 0]);
+        payload.extend_from_slice(&[0x41; 8]);
+        payload.push(0);
+        payload.extend_from_slice(&flags.to_le_bytes());
+        let mut packet = (payload.len() as u32).to_le_bytes()[..3].to_vec();
+        packet.push(0);
+        packet.extend(payload);
+        packet
+    }
+
+    #[test]
+    fn reads_tls_support_from_servers() {
+        assert_eq!(pg_accepts_tls(b'S'), Some(true));
+        assert_eq!(pg_accepts_tls(b'N'), Some(false));
+        assert_eq!(pg_accepts_tls(b'E'), None);
+        assert_eq!(mysql_offers_tls(&handshake(0xffff)), Some(true));
+        assert_eq!(mysql_offers_tls(&handshake(0xf7ff)), Some(false));
+        assert_eq!(mysql_offers_tls(b"\x05\0\0\0\xffoops"), None);
+        assert_eq!(mysql_offers_tls(&handshake(0xffff)[..12]), None);
+    }
+
+    #[test]
+    fn tls_is_only_required_by_strict_modes() {
+        assert_eq!(tls_outcome(&SslMode::Require, true).0, StageStatus::Passed);
+        assert_eq!(tls_outcome(&SslMode::Prefer, false).0, StageStatus::Passed);
+        let (status, _, hint) = tls_outcome(&SslMode::VerifyFull, false);
+        assert_eq!(status, StageStatus::Failed);
+        assert!(hint.is_some());
+    }
+
+    #[test]
+    fn hints_follow_the_error() {
+        assert!(
+            tcp_hint(io::ErrorKind::ConnectionRefused, "db", 5432).contains("Nothing is listening")
+        );
+        assert!(
+            login_hint(Some("28P01"), "password authentication failed", "app")
+                .unwrap()
+                .contains("password")
+        );
+        assert!(
+            login_hint(Some("28000"), "no pg_hba.conf entry for host", "app")
+                .unwrap()
+                .contains("pg_hba.conf")
+        );
+        assert!(
+            login_hint(Some("3D000"), "database \"app\" does not exist", "app")
+                .unwrap()
+                .contains("\"app\"")
+        );
+        assert!(login_hint(Some("42000"), "Unknown database 'app'", "app").is_some());
+        assert_eq!(login_hint(Some("42601"), "syntax error", "app"), None);
+        assert!(ssh_hint("Authentication failed").contains("rejected the login"));
+    }
+
+    #[test]
+    fn stages_after_a_failure_are_skipped() {
+        let mut report = ConnectionReport::default();
+        report.pass(TestStage::Dns, "resolved", Instant::now());
+        report.fail(TestStage::Tcp, "refused", None, Instant::now());
+        report.skip_remaining();
+        assert!(!report.passed());
+        assert_eq!(report.failure().map(|s| s.stage), Some(TestStage::Tcp));
+        assert_eq!(
+            report.stages.iter().map(|s| s.stage).collect::<Vec<_>>(),
+            TestStage::ALL
+        );
+        assert_eq!(report.stages[5].status, StageStatus::Skipped);
+    }
+}
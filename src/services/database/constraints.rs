@@ -0,0 +1,326 @@
+//! Adding foreign key, check and unique constraints to a table.
+//!
+//! On Postgres a foreign key or check constraint can be added `NOT VALID`,
+//! which skips scanning the existing rows and holds its lock only briefly;
+//! a later `VALIDATE CONSTRAINT` checks those rows without blocking writes.
+//! That pair is how constraints go onto large tables.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::storage::DatabaseDriver;
+
+/// What a foreign key does when the referenced row is deleted or updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferentialAction {
+    #[default]
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+impl ReferentialAction {
+    pub fn all() -> Vec<ReferentialAction> {
+        vec![
+            ReferentialAction::NoAction,
+            ReferentialAction::Restrict,
+            ReferentialAction::Cascade,
+            ReferentialAction::SetNull,
+            ReferentialAction::SetDefault,
+        ]
+    }
+
+    pub fn sql(&self) -> &'static str {
+        match self {
+            ReferentialAction::NoAction => "NO ACTION",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::SetNull => "SET NULL",
+            ReferentialAction::SetDefault => "SET DEFAULT",
+        }
+    }
+}
+
+/// The constraint being added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintSpec {
+    ForeignKey {
+        columns: Vec<String>,
+        referenced_schema: String,
+        referenced_table: String,
+        referenced_columns: Vec<String>,
+        on_delete: ReferentialAction,
+        on_update: ReferentialAction,
+    },
+    Check {
+        expression: String,
+    },
+    Unique {
+        columns: Vec<String>,
+    },
+}
+
+/// How existing rows are checked against the new constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConstraintValidation {
+    /// Check every row while adding it.
+    #[default]
+    Immediate,
+    /// Add it `NOT VALID`; only new and updated rows are checked.
+    NotValid,
+    /// Add it `NOT VALID`, then `VALIDATE CONSTRAINT` in a second step.
+    NotValidThenValidate,
+}
+
+/// The name Postgres would give the constraint: `orders_customer_id_fkey`,
+/// `orders_check`, `users_email_key`.
+pub fn default_constraint_name(table_name: &str, spec: &ConstraintSpec) -> String {
+    let (columns, suffix) = match spec {
+        ConstraintSpec::ForeignKey { columns, .. } => (columns.as_slice(), "fkey"),
+        ConstraintSpec::Check { .. } => ([].as_slice(), "check"),
+        ConstraintSpec::Unique { columns } => (columns.as_slice(), "key"),
+    };
+    let mut parts = vec![table_name.to_string()];
+    parts.extend(columns.iter().cloned());
+    parts.push(suffix.to_string());
+    parts.join("_")
+}
+
+/// The statements adding `spec` as `name` to `table_schema.table_name`.
+pub fn add_constraint_sql(
+    driver: &DatabaseDriver,
+    table_schema: &str,
+    table_name: &str,
+    name: &str,
+    spec: &ConstraintSpec,
+    validation: ConstraintValidation,
+) -> Result<Vec<String>> {
+    let quote_list = |columns: &[String]| {
+        columns
+            .iter()
+            .map(|c| driver.quote_ident(c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let definition = match spec {
+        ConstraintSpec::ForeignKey {
+            columns,
+            referenced_schema,
+            referenced_table,
+            referenced_columns,
+            on_delete,
+            on_update,
+        } => {
+            if columns.is_empty() {
+                return Err(anyhow!("Pick the columns of the foreign key"));
+            }
+            if referenced_table.is_empty() {
+                return Err(anyhow!("Pick the referenced table"));
+            }
+            if referenced_columns.len() != columns.len() {
+                return Err(anyhow!(
+                    "Pick {} referenced column{} to match",
+                    columns.len(),
+                    if columns.len() == 1 { "" } else { "s" }
+                ));
+            }
+            let mut definition = format!(
+                "FOREIGN KEY ({}) REFERENCES {}.{} ({})",
+                quote_list(columns),
+                driver.quote_ident(referenced_schema),
+                driver.quote_ident(referenced_table),
+                quote_list(referenced_columns)
+            );
+            if *on_delete != ReferentialAction::NoAction {
+                definition.push_str(&format!(" ON DELETE {}", on_delete.sql()));
+            }
+            if *on_update != ReferentialAction::NoAction {
+                definition.push_str(&format!(" ON UPDATE {}", on_update.sql()));
+            }
+            definition
+        }
+        ConstraintSpec::Check { expression } => {
+            let expression = expression.trim().trim_end_matches(';');
+            if expression.is_empty() {
+                return Err(anyhow!("Write the condition rows must meet"));
+            }
+            format!("CHECK ({})", expression)
+        }
+        ConstraintSpec::Unique { columns } => {
+            if columns.is_empty() {
+                return Err(anyhow!("Pick the columns that must be unique"));
+            }
+            format!("UNIQUE ({})", quote_list(columns))
+        }
+    };
+
+    if validation != ConstraintValidation::Immediate {
+        if *driver != DatabaseDriver::Postgres {
+            return Err(anyhow!("Only Postgres can add a constraint NOT VALID"));
+        }
+        if matches!(spec, ConstraintSpec::Unique { .. }) {
+            return Err(anyhow!(
+                "Unique constraints are always validated; on a large table, build a unique \
+                 index CONCURRENTLY first"
+            ));
+        }
+    }
+
+    let table = format!(
+        "{}.{}",
+        driver.quote_ident(table_schema),
+        driver.quote_ident(table_name)
+    );
+    let name = match name.trim() {
+        "" => default_constraint_name(table_name, spec),
+        name => name.to_string(),
+    };
+    let not_valid = if validation == ConstraintValidation::Immediate {
+        ""
+    } else {
+        " NOT VALID"
+    };
+
+    let mut statements = vec![format!(
+        "ALTER TABLE {} ADD CONSTRAINT {} {}{};",
+        table,
+        driver.quote_ident(&name),
+        definition,
+        not_valid
+    )];
+    if validation == ConstraintValidation::NotValidThenValidate {
+        statements.push(format!(
+            "ALTER TABLE {} VALIDATE CONSTRAINT {};",
+            table,
+            driver.quote_ident(&name)
+        ));
+    }
+    Ok(statements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn foreign_key() -> ConstraintSpec {
+        ConstraintSpec::ForeignKey {
+            columns: vec!["customer_id".to_string()],
+            referenced_schema: "public".to_string(),
+            referenced_table: "customers".to_string(),
+            referenced_columns: vec!["id".to_string()],
+            on_delete: ReferentialAction::Cascade,
+            on_update: ReferentialAction::NoAction,
+        }
+    }
+
+    #[test]
+    fn adds_foreign_key_not_valid_then_validates() {
+        assert_eq!(
+            add_constraint_sql(
+                &DatabaseDriver::Postgres,
+                "public",
+                "orders",
+                "",
+                &foreign_key(),
+                ConstraintValidation::NotValidThenValidate,
+            )
+            .unwrap(),
+            vec![
+                "ALTER TABLE \"public\".\"orders\" ADD CONSTRAINT \"orders_customer_id_fkey\" \
+                 FOREIGN KEY (\"customer_id\") REFERENCES \"public\".\"customers\" (\"id\") \
+                 ON DELETE CASCADE NOT VALID;",
+                "ALTER TABLE \"public\".\"orders\" VALIDATE CONSTRAINT \
+                 \"orders_customer_id_fkey\";",
+            ]
+        );
+    }
+
+    #[test]
+    fn adds_check_and_unique_constraints() {
+        assert_eq!(
+            add_constraint_sql(
+                &DatabaseDriver::MySql,
+                "shop",
+                "orders",
+                "positive_total",
+                &ConstraintSpec::Check {
+                    expression: "total >= 0;".to_string(),
+                },
+                ConstraintValidation::Immediate,
+            )
+            .unwrap(),
+            vec!["ALTER TABLE `shop`.`orders` ADD CONSTRAINT `positive_total` CHECK (total >= 0);"]
+        );
+        let unique = ConstraintSpec::Unique {
+            columns: vec!["email".to_string(), "tenant_id".to_string()],
+        };
+        assert_eq!(
+            add_constraint_sql(
+                &DatabaseDriver::Postgres,
+                "public",
+                "users",
+                "",
+                &unique,
+                ConstraintValidation::Immediate,
+            )
+            .unwrap(),
+            vec![
+                "ALTER TABLE \"public\".\"users\" ADD CONSTRAINT \"users_email_tenant_id_key\" \
+                 UNIQUE (\"email\", \"tenant_id\");"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_constraints() {
+        let add = |driver, spec: &ConstraintSpec, validation| {
+            add_constraint_sql(&driver, "public", "orders", "", spec, validation)
+        };
+        assert!(
+            add(
+                DatabaseDriver::MySql,
+                &foreign_key(),
+                ConstraintValidation::NotValid
+            )
+            .is_err()
+        );
+        assert!(
+            add(
+                DatabaseDriver::Postgres,
+                &ConstraintSpec::Unique {
+                    columns: vec!["id".to_string()]
+                },
+                ConstraintValidation::NotValid
+            )
+            .is_err()
+        );
+        let mismatched = ConstraintSpec::ForeignKey {
+            columns: vec!["a".to_string(), "b".to_string()],
+            referenced_schema: "public".to_string(),
+            referenced_table: "t".to_string(),
+            referenced_columns: vec!["id".to_string()],
+            on_delete: ReferentialAction::NoAction,
+            on_update: ReferentialAction::NoAction,
+        };
+        assert!(
+            add(
+                DatabaseDriver::Postgres,
+                &mismatched,
+                ConstraintValidation::Immediate
+            )
+            .is_err()
+        );
+        assert!(
+            add(
+                DatabaseDriver::Postgres,
+                &ConstraintSpec::Check {
+                    expression: " ".to_string()
+                },
+                ConstraintValidation::Immediate
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! How cell values are shown in the results grid.
+//!
+//! Cells keep the value as decoded from the server; the date and time
+//! and whitespace settings only change the text the grid displays, so
+//! exports and generated statements always see the original.
+
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Local, Locale, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Time zone that time zone aware timestamps are shown in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeZoneDisplay {
+    /// As decoded, without any conversion or reformatting.
+    #[default]
+    Raw,
+    Utc,
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateTimeFormat {
+    /// `2024-05-01T13:45:00Z`
+    #[default]
+    Iso,
+    /// The system locale's date and time representation.
+    Locale,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateTimeDisplay {
+    #[serde(default)]
+    pub zone: TimeZoneDisplay,
+    #[serde(default)]
+    pub format: DateTimeFormat,
+}
+
+/// How NULL cells are told apart from values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NullDisplay {
+    /// An italic `NULL` on a tinted background, which no value looks like.
+    #[default]
+    Chip,
+    /// A muted italic `NULL`.
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellDisplay {
+    #[serde(default)]
+    pub null: NullDisplay,
+    /// Show empty strings as such and make leading, trailing and
+    /// whitespace-only text visible.
+    #[serde(default)]
+    pub whitespace_markers: bool,
+}
+
+static SYSTEM_LOCALE: LazyLock<Locale> = LazyLock::new(|| {
+    sys_locale::get_locale()
+        .and_then(|tag| Locale::try_from(tag.replace('-', "_").as_str()).ok())
+        .unwrap_or(Locale::POSIX)
+});
+
+/// Decoded timestamps with a time zone end in ` UTC`.
+fn parse_instant(value: &str) -> Option<DateTime<Utc>> {
+    let naive = value.strip_suffix(" UTC")?;
+    NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+/// The text to show for a cell of type `type_name` holding `value`, or
+/// `None` to show the value as it is.
+pub fn display_value(value: &str, type_name: &str, display: &DateTimeDisplay) -> Option<String> {
+    if display.zone == TimeZoneDisplay::Raw {
+        return None;
+    }
+    let locale = *SYSTEM_LOCALE;
+
+    match type_name.to_uppercase().as_str() {
+        // Postgres `timestamp` and `timestamptz`, MySQL `datetime` and
+        // `timestamp`. Only values with a zone are converted.
+        "TIMESTAMPTZ" | "TIMESTAMP" | "DATETIME" => {
+            if let Some(instant) = parse_instant(value) {
+                return Some(match (display.zone, display.format) {
+                    (TimeZoneDisplay::Local, DateTimeFormat::Iso) => instant
+                        .with_timezone(&Local)
+                        .to_rfc3339_opts(SecondsFormat::AutoSi, false),
+                    (TimeZoneDisplay::Local, DateTimeFormat::Locale) => instant
+                        .with_timezone(&Local)
+                        .format_localized("%x %X %:z", locale)
+                        .to_string(),
+                    (_, DateTimeFormat::Iso) => {
+                        instant.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                    }
+                    (_, DateTimeFormat::Locale) => {
+                        instant.format_localized("%x %X UTC", locale).to_string()
+                    }
+                });
+            }
+            let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+            Some(match display.format {
+                DateTimeFormat::Iso => naive.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+                DateTimeFormat::Locale => naive
+                    .and_utc()
+                    .format_localized("%x %X", locale)
+                    .to_string(),
+            })
+        }
+        "DATE" => match display.format {
+            DateTimeFormat::Iso => None,
+            DateTimeFormat::Locale => {
+                let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+                Some(date.format_localized("%x", locale).to_string())
+            }
+        },
+        "TIME" => match display.format {
+            DateTimeFormat::Iso => None,
+            DateTimeFormat::Locale => {
+                let time = NaiveTime::parse_from_str(value, "%H:%M:%S%.f").ok()?;
+                let on_epoch = NaiveDate::default().and_time(time).and_utc();
+                Some(on_epoch.format_localized("%X", locale).to_string())
+            }
+        },
+        _ => None,
+    }
+}
+
+fn whitespace_marker(c: char) -> char {
+    match c {
+        ' ' => '·',
+        '\t' => '→',
+        '\n' => '↵',
+        '\r' => '␍',
+        _ => '␣',
+    }
+}
+
+/// `value` with the whitespace at either end replaced by visible markers,
+/// or `None` when it has none. Whitespace inside the text is left alone.
+pub fn mark_whitespace(value: &str) -> Option<String> {
+    let rest = value.trim_start();
+    let leading = &value[..value.len() - rest.len()];
+    let middle = rest.trim_end();
+    let trailing = &rest[middle.len()..];
+    if leading.is_empty() && trailing.is_empty() {
+        return None;
+    }
+    Some(
+        leading
+            .chars()
+            .map(whitespace_marker)
+            .chain(middle.chars())
+            .chain(trailing.chars().map(whitespace_marker))
+            .collect(),
+    )
+}
+
+/// Editor language for a text cell of type `type_name`, for highlighting
+/// it when it is opened on its own.
+pub fn cell_language(type_name: &str, value: &str) -> &'static str {
+    match type_name.to_uppercase().as_str() {
+        "JSON" | "JSONB" => return "json",
+        "XML" => return "html",
+        _ => {}
+    }
+    let trimmed = value.trim_start();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(value).is_ok()
+    {
+        "json"
+    } else if trimmed.starts_with('<') {
+        "html"
+    } else {
+        "text"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(zone: TimeZoneDisplay, format: DateTimeFormat) -> DateTimeDisplay {
+        DateTimeDisplay { zone, format }
+    }
+
+    #[test]
+    fn raw_leaves_values_alone() {
+        let raw = DateTimeDisplay::default();
+        assert_eq!(
+            display_value("2024-05-01 13:45:00 UTC", "TIMESTAMPTZ", &raw),
+            None
+        );
+    }
+
+    #[test]
+    fn iso_timestamps() {
+        let utc = display(TimeZoneDisplay::Utc, DateTimeFormat::Iso);
+        assert_eq!(
+            display_value("2024-05-01 13:45:00.250 UTC", "TIMESTAMPTZ", &utc).as_deref(),
+            Some("2024-05-01T13:45:00.250Z")
+        );
+        assert_eq!(
+            display_value("2024-05-01 13:45:00", "TIMESTAMP", &utc).as_deref(),
+            Some("2024-05-01T13:45:00")
+        );
+        // Dates and times are already ISO; other types are never touched.
+        assert_eq!(display_value("2024-05-01", "DATE", &utc), None);
+        assert_eq!(display_value("2024-05-01 13:45:00", "TEXT", &utc), None);
+    }
+
+    #[test]
+    fn local_zone_converts_instants_only() {
+        let local = display(TimeZoneDisplay::Local, DateTimeFormat::Iso);
+        let expected = "2024-05-01T13:45:00Z"
+            .parse::<DateTime<Utc>>()
+            .unwrap()
+            .with_timezone(&Local)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, false);
+        assert_eq!(
+            display_value("2024-05-01 13:45:00 UTC", "TIMESTAMP", &local),
+            Some(expected)
+        );
+        // Without a zone there is nothing to convert from.
+        assert_eq!(
+            display_value("2024-05-01 13:45:00", "DATETIME", &local).as_deref(),
+            Some("2024-05-01T13:45:00")
+        );
+    }
+
+    #[test]
+    fn cell_languages() {
+        assert_eq!(cell_language("JSONB", "not json"), "json");
+        assert_eq!(cell_language("XML", ""), "html");
+        assert_eq!(cell_language("TEXT", " {\"a\": [1, 2]}"), "json");
+        assert_eq!(cell_language("TEXT", "{not json"), "text");
+        assert_eq!(cell_language("VARCHAR", "<note>hi</note>"), "html");
+        assert_eq!(cell_language("TEXT", "plain words"), "text");
+    }
+
+    #[test]
+    fn marks_whitespace_at_either_end() {
+        assert_eq!(mark_whitespace("plain words"), None);
+        assert_eq!(mark_whitespace(""), None);
+        assert_eq!(mark_whitespace("  padded\t").as_deref(), Some("··padded→"));
+        assert_eq!(mark_whitespace(" \n").as_deref(), Some("·↵"));
+        assert_eq!(mark_whitespace("\u{a0}x").as_deref(), Some("␣x"));
+    }
+
+    #[test]
+    fn unparseable_values_are_shown_as_they_are() {
+        let locale = display(TimeZoneDisplay::Utc, DateTimeFormat::Locale);
+        assert_eq!(display_value("infinity", "TIMESTAMPTZ", &locale), None);
+        assert_eq!(display_value("not a date", "DATE", &locale), None);
+        assert!(display_value("2024-05-01", "DATE", &locale).is_some());
+    }
+}
@@ -0,0 +1,507 @@
+//! Generated INSERT/UPDATE/DELETE statements for row editing.
+//!
+//! The "New row" form builds a parameterized INSERT (values travel as
+//! bind parameters, cast to the column type on Postgres). Row actions in
+//! the results grid build literal statements instead, because they are
+//! shown to the user for review before they run. Script templates from the
+//! tables tree use `:name` placeholders, filled in by the parameters dialog
+//! when the script runs.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::storage::DatabaseDriver;
+
+/// A column as seen by the row editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditableColumn {
+    pub name: String,
+    /// `information_schema` data type, for display.
+    pub data_type: String,
+    /// Full SQL type (e.g. `character varying(20)`, `mood`), used for casts.
+    pub sql_type: String,
+    pub is_nullable: bool,
+    pub column_default: Option<String>,
+    /// Allowed labels when the column is an enum; empty otherwise.
+    pub enum_values: Vec<String>,
+    pub is_primary_key: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableEditInfo {
+    pub table_schema: String,
+    pub table_name: String,
+    pub columns: Vec<EditableColumn>,
+}
+
+impl TableEditInfo {
+    pub fn qualified_name(&self, driver: &DatabaseDriver) -> String {
+        format!(
+            "{}.{}",
+            driver.quote_ident(&self.table_schema),
+            driver.quote_ident(&self.table_name)
+        )
+    }
+
+    pub fn primary_keys(&self) -> Vec<&EditableColumn> {
+        self.columns.iter().filter(|c| c.is_primary_key).collect()
+    }
+
+    fn column(&self, name: &str) -> Option<&EditableColumn> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+/// SQL text plus its bind parameters, in placeholder order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamStatement {
+    pub sql: String,
+    pub params: Vec<Option<String>>,
+}
+
+/// Build a parameterized INSERT. Columns absent from `values` are left
+/// out so the server applies their defaults; `None` binds an explicit NULL.
+pub fn build_insert(
+    driver: &DatabaseDriver,
+    info: &TableEditInfo,
+    values: &[(String, Option<String>)],
+) -> ParamStatement {
+    let table = info.qualified_name(driver);
+
+    if values.is_empty() {
+        let sql = match driver {
+            DatabaseDriver::Postgres => format!("INSERT INTO {} DEFAULT VALUES", table),
+            DatabaseDriver::MySql => format!("INSERT INTO {} () VALUES ()", table),
+        };
+        return ParamStatement {
+            sql,
+            params: vec![],
+        };
+    }
+
+    let columns = values
+        .iter()
+        .map(|(name, _)| driver.quote_ident(name))
+        .collect::<Vec<_>>();
+    let placeholders = values
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| match driver {
+            DatabaseDriver::Postgres => match info.column(name) {
+                Some(col) => format!("CAST(${} AS {})", i + 1, col.sql_type),
+                None => format!("${}", i + 1),
+            },
+            DatabaseDriver::MySql => "?".to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    ParamStatement {
+        sql: format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            columns.join(", "),
+            placeholders.join(", ")
+        ),
+        params: values.iter().map(|(_, value)| value.clone()).collect(),
+    }
+}
+
+/// Build a DELETE matching `row` by primary key. `row` holds the values of
+/// a result row by column name.
+pub fn delete_row_sql(
+    driver: &DatabaseDriver,
+    info: &TableEditInfo,
+    row: &[(String, Option<String>)],
+) -> Result<String> {
+    Ok(format!(
+        "DELETE FROM {} WHERE {};",
+        info.qualified_name(driver),
+        key_predicate(driver, info, row)?
+    ))
+}
+
+/// Build an UPDATE setting `column` to `value` in the row matching `row`
+/// by primary key.
+pub fn update_cell_sql(
+    driver: &DatabaseDriver,
+    info: &TableEditInfo,
+    row: &[(String, Option<String>)],
+    column: &str,
+    value: &Option<String>,
+) -> Result<String> {
+    if info.column(column).is_none() {
+        return Err(anyhow!(
+            "{} is not a column of {}.{}",
+            column,
+            info.table_schema,
+            info.table_name
+        ));
+    }
+    Ok(format!(
+        "UPDATE {} SET {} = {} WHERE {};",
+        info.qualified_name(driver),
+        driver.quote_ident(column),
+        literal(driver, value),
+        key_predicate(driver, info, row)?
+    ))
+}
+
+/// Build an INSERT copying `row`. Primary key columns with a default
+/// (serial, identity, generated uuid, ...) are left out so the copy gets a
+/// fresh key.
+pub fn duplicate_row_sql(
+    driver: &DatabaseDriver,
+    info: &TableEditInfo,
+    row: &[(String, Option<String>)],
+) -> String {
+    let copied: Vec<&(String, Option<String>)> = row
+        .iter()
+        .filter(|(name, _)| match info.column(name) {
+            Some(col) => !(col.is_primary_key && col.column_default.is_some()),
+            None => false,
+        })
+        .collect();
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({});",
+        info.qualified_name(driver),
+        copied
+            .iter()
+            .map(|(name, _)| driver.quote_ident(name))
+            .collect::<Vec<_>>()
+            .join(", "),
+        copied
+            .iter()
+            .map(|(_, value)| literal(driver, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Statement templates offered for a table in the tables tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptTemplate {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl ScriptTemplate {
+    pub const ALL: [ScriptTemplate; 4] = [
+        ScriptTemplate::Select,
+        ScriptTemplate::Insert,
+        ScriptTemplate::Update,
+        ScriptTemplate::Delete,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScriptTemplate::Select => "SELECT",
+            ScriptTemplate::Insert => "INSERT",
+            ScriptTemplate::Update => "UPDATE",
+            ScriptTemplate::Delete => "DELETE",
+        }
+    }
+}
+
+/// Build a `template` statement for the table. INSERT leaves out primary
+/// key columns with a default; UPDATE and DELETE match the row by primary
+/// key and fail for tables without one.
+pub fn script_template_sql(
+    driver: &DatabaseDriver,
+    info: &TableEditInfo,
+    template: ScriptTemplate,
+) -> Result<String> {
+    let table = info.qualified_name(driver);
+    let assignments = |columns: &[&EditableColumn]| {
+        columns
+            .iter()
+            .map(|col| format!("{} = {}", driver.quote_ident(&col.name), placeholder(col)))
+            .collect::<Vec<_>>()
+    };
+    let key_match = || {
+        let keys = info.primary_keys();
+        if keys.is_empty() {
+            return Err(anyhow!(
+                "{}.{} has no primary key",
+                info.table_schema,
+                info.table_name
+            ));
+        }
+        Ok(assignments(&keys).join("\n  AND "))
+    };
+
+    Ok(match template {
+        ScriptTemplate::Select => format!(
+            "SELECT {}\nFROM {};",
+            info.columns
+                .iter()
+                .map(|col| driver.quote_ident(&col.name))
+                .collect::<Vec<_>>()
+                .join(",\n       "),
+            table
+        ),
+        ScriptTemplate::Insert => {
+            let columns: Vec<&EditableColumn> = info
+                .columns
+                .iter()
+                .filter(|col| !(col.is_primary_key && col.column_default.is_some()))
+                .collect();
+            format!(
+                "INSERT INTO {} ({})\nVALUES ({});",
+                table,
+                columns
+                    .iter()
+                    .map(|col| driver.quote_ident(&col.name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                columns
+                    .iter()
+                    .map(|col| placeholder(col))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        ScriptTemplate::Update => {
+            let columns: Vec<&EditableColumn> = info
+                .columns
+                .iter()
+                .filter(|col| !col.is_primary_key)
+                .collect();
+            if columns.is_empty() {
+                return Err(anyhow!(
+                    "{}.{} has no columns outside its primary key",
+                    info.table_schema,
+                    info.table_name
+                ));
+            }
+            format!(
+                "UPDATE {}\nSET {}\nWHERE {};",
+                table,
+                assignments(&columns).join(",\n    "),
+                key_match()?
+            )
+        }
+        ScriptTemplate::Delete => format!("DELETE FROM {}\nWHERE {};", table, key_match()?),
+    })
+}
+
+/// `:name` placeholder for a column, with characters that cannot appear in
+/// a placeholder name replaced.
+fn placeholder(column: &EditableColumn) -> String {
+    let name: String = column
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!(":{}", name)
+}
+
+fn key_predicate(
+    driver: &DatabaseDriver,
+    info: &TableEditInfo,
+    row: &[(String, Option<String>)],
+) -> Result<String> {
+    let keys = info.primary_keys();
+    if keys.is_empty() {
+        return Err(anyhow!(
+            "{}.{} has no primary key",
+            info.table_schema,
+            info.table_name
+        ));
+    }
+
+    keys.iter()
+        .map(|key| {
+            let value = row
+                .iter()
+                .find(|(name, _)| *name == key.name)
+                .map(|(_, value)| value)
+                .ok_or_else(|| anyhow!("Primary key column {} is not in the result", key.name))?;
+            Ok(match value {
+                Some(v) => format!(
+                    "{} = {}",
+                    driver.quote_ident(&key.name),
+                    driver.quote_literal(v)
+                ),
+                None => format!("{} IS NULL", driver.quote_ident(&key.name)),
+            })
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|parts| parts.join(" AND "))
+}
+
+fn literal(driver: &DatabaseDriver, value: &Option<String>) -> String {
+    match value {
+        Some(v) => driver.quote_literal(v),
+        None => "NULL".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, sql_type: &str, pk: bool, default: Option<&str>) -> EditableColumn {
+        EditableColumn {
+            name: name.to_string(),
+            data_type: sql_type.to_string(),
+            sql_type: sql_type.to_string(),
+            is_nullable: !pk,
+            column_default: default.map(String::from),
+            enum_values: vec![],
+            is_primary_key: pk,
+        }
+    }
+
+    fn users() -> TableEditInfo {
+        TableEditInfo {
+            table_schema: "public".to_string(),
+            table_name: "users".to_string(),
+            columns: vec![
+                column(
+                    "id",
+                    "integer",
+                    true,
+                    Some("nextval('users_id_seq'::regclass)"),
+                ),
+                column("name", "text", false, None),
+                column("mood", "mood", false, None),
+            ],
+        }
+    }
+
+    fn row() -> Vec<(String, Option<String>)> {
+        vec![
+            ("id".to_string(), Some("7".to_string())),
+            ("name".to_string(), Some("O'Brien".to_string())),
+            ("mood".to_string(), None),
+        ]
+    }
+
+    #[test]
+    fn insert_casts_postgres_params_to_column_type() {
+        let stmt = build_insert(
+            &DatabaseDriver::Postgres,
+            &users(),
+            &[
+                ("name".to_string(), Some("Ann".to_string())),
+                ("mood".to_string(), Some("happy".to_string())),
+            ],
+        );
+        assert_eq!(
+            stmt.sql,
+            "INSERT INTO \"public\".\"users\" (\"name\", \"mood\") \
+             VALUES (CAST($1 AS text), CAST($2 AS mood))"
+        );
+        assert_eq!(
+            stmt.params,
+            vec![Some("Ann".to_string()), Some("happy".to_string())]
+        );
+    }
+
+    #[test]
+    fn insert_uses_plain_placeholders_on_mysql() {
+        let stmt = build_insert(
+            &DatabaseDriver::MySql,
+            &users(),
+            &[("name".to_string(), None)],
+        );
+        assert_eq!(stmt.sql, "INSERT INTO `public`.`users` (`name`) VALUES (?)");
+        assert_eq!(stmt.params, vec![None]);
+    }
+
+    #[test]
+    fn insert_without_values_uses_defaults() {
+        let pg = build_insert(&DatabaseDriver::Postgres, &users(), &[]);
+        assert_eq!(pg.sql, "INSERT INTO \"public\".\"users\" DEFAULT VALUES");
+        let my = build_insert(&DatabaseDriver::MySql, &users(), &[]);
+        assert_eq!(my.sql, "INSERT INTO `public`.`users` () VALUES ()");
+    }
+
+    #[test]
+    fn delete_matches_on_primary_key() {
+        let sql = delete_row_sql(&DatabaseDriver::Postgres, &users(), &row()).unwrap();
+        assert_eq!(sql, "DELETE FROM \"public\".\"users\" WHERE \"id\" = '7';");
+    }
+
+    #[test]
+    fn delete_requires_primary_key_in_result() {
+        let partial = vec![("name".to_string(), Some("Ann".to_string()))];
+        assert!(delete_row_sql(&DatabaseDriver::Postgres, &users(), &partial).is_err());
+    }
+
+    #[test]
+    fn update_sets_one_column_by_primary_key() {
+        let sql = update_cell_sql(
+            &DatabaseDriver::Postgres,
+            &users(),
+            &row(),
+            "name",
+            &Some("Ann's".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "UPDATE \"public\".\"users\" SET \"name\" = 'Ann''s' WHERE \"id\" = '7';"
+        );
+
+        let sql = update_cell_sql(&DatabaseDriver::MySql, &users(), &row(), "mood", &None).unwrap();
+        assert_eq!(
+            sql,
+            "UPDATE `public`.`users` SET `mood` = NULL WHERE `id` = '7';"
+        );
+        assert!(
+            update_cell_sql(&DatabaseDriver::Postgres, &users(), &row(), "nope", &None).is_err()
+        );
+    }
+
+    #[test]
+    fn duplicate_skips_defaulted_primary_key() {
+        let sql = duplicate_row_sql(&DatabaseDriver::Postgres, &users(), &row());
+        assert_eq!(
+            sql,
+            "INSERT INTO \"public\".\"users\" (\"name\", \"mood\") VALUES ('O''Brien', NULL);"
+        );
+    }
+
+    #[test]
+    fn script_templates_use_named_placeholders() {
+        let pg = DatabaseDriver::Postgres;
+        assert_eq!(
+            script_template_sql(&pg, &users(), ScriptTemplate::Select).unwrap(),
+            "SELECT \"id\",\n       \"name\",\n       \"mood\"\nFROM \"public\".\"users\";"
+        );
+        // The serial key is left to its default.
+        assert_eq!(
+            script_template_sql(&pg, &users(), ScriptTemplate::Insert).unwrap(),
+            "INSERT INTO \"public\".\"users\" (\"name\", \"mood\")\nVALUES (:name, :mood);"
+        );
+        assert_eq!(
+            script_template_sql(&pg, &users(), ScriptTemplate::Update).unwrap(),
+            "UPDATE \"public\".\"users\"\nSET \"name\" = :name,\n    \"mood\" = :mood\nWHERE \"id\" = :id;"
+        );
+        assert_eq!(
+            script_template_sql(&DatabaseDriver::MySql, &users(), ScriptTemplate::Delete).unwrap(),
+            "DELETE FROM `public`.`users`\nWHERE `id` = :id;"
+        );
+    }
+
+    #[test]
+    fn keyless_tables_have_no_update_or_delete_template() {
+        let mut info = users();
+        info.columns[0].is_primary_key = false;
+        info.columns[1].name = "full name".to_string();
+        assert!(
+            script_template_sql(&DatabaseDriver::Postgres, &info, ScriptTemplate::Update).is_err()
+        );
+        assert!(
+            script_template_sql(&DatabaseDriver::Postgres, &info, ScriptTemplate::Delete).is_err()
+        );
+        assert_eq!(
+            script_template_sql(&DatabaseDriver::Postgres, &info, ScriptTemplate::Insert).unwrap(),
+            "INSERT INTO \"public\".\"users\" (\"id\", \"full name\", \"mood\")\n\
+             VALUES (:id, :full_name, :mood);"
+        );
+    }
+}
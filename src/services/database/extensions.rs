@@ -0,0 +1,116 @@
+//! Postgres extensions available on, or installed in, the connected
+//! database, and the statements that install, update and drop them.
+
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionInfo {
+    pub name: String,
+    /// Version `CREATE EXTENSION` installs by default.
+    pub default_version: Option<String>,
+    /// `None` when the extension is available but not installed.
+    pub installed_version: Option<String>,
+    /// Schema the extension's objects were installed into.
+    pub schema: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl ExtensionInfo {
+    pub fn is_installed(&self) -> bool {
+        self.installed_version.is_some()
+    }
+
+    /// Installed at a different version than the server's default, which
+    /// is usually an upgrade left over from a package update.
+    pub fn has_update(&self) -> bool {
+        matches!(
+            (&self.installed_version, &self.default_version),
+            (Some(installed), Some(default)) if installed != default
+        )
+    }
+}
+
+/// A table, view or function that belongs to an extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionObject {
+    pub extension: String,
+    pub object_schema: String,
+    pub object_name: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtensionAction {
+    Install,
+    /// Update to the server's default version.
+    Update,
+    Drop,
+}
+
+/// The statement for `action` on `extension`. Drops are `RESTRICT`, so
+/// they fail while other objects still depend on the extension.
+pub fn extension_sql(extension: &ExtensionInfo, action: ExtensionAction) -> String {
+    let name = DRIVER.quote_ident(&extension.name);
+    match action {
+        ExtensionAction::Install => format!("CREATE EXTENSION IF NOT EXISTS {};", name),
+        ExtensionAction::Update => match &extension.default_version {
+            Some(version) => format!(
+                "ALTER EXTENSION {} UPDATE TO {};",
+                name,
+                DRIVER.quote_literal(version)
+            ),
+            None => format!("ALTER EXTENSION {} UPDATE;", name),
+        },
+        ExtensionAction::Drop => format!("DROP EXTENSION {};", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extension(installed: Option<&str>) -> ExtensionInfo {
+        ExtensionInfo {
+            name: "pg_trgm".to_string(),
+            default_version: Some("1.6".to_string()),
+            installed_version: installed.map(str::to_string),
+            schema: installed.map(|_| "public".to_string()),
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn update_is_offered_for_other_versions() {
+        assert!(!extension(None).has_update());
+        assert!(!extension(Some("1.6")).has_update());
+        assert!(extension(Some("1.5")).has_update());
+    }
+
+    #[test]
+    fn generates_statements_per_action() {
+        let ext = extension(Some("1.5"));
+        assert_eq!(
+            extension_sql(&ext, ExtensionAction::Install),
+            "CREATE EXTENSION IF NOT EXISTS \"pg_trgm\";"
+        );
+        assert_eq!(
+            extension_sql(&ext, ExtensionAction::Update),
+            "ALTER EXTENSION \"pg_trgm\" UPDATE TO '1.6';"
+        );
+        assert_eq!(
+            extension_sql(&ext, ExtensionAction::Drop),
+            "DROP EXTENSION \"pg_trgm\";"
+        );
+    }
+
+    #[test]
+    fn update_without_default_version_uses_server_default() {
+        let mut ext = extension(Some("1.5"));
+        ext.default_version = None;
+        assert_eq!(
+            extension_sql(&ext, ExtensionAction::Update),
+            "ALTER EXTENSION \"pg_trgm\" UPDATE;"
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Cross-database setup through `postgres_fdw` or `dblink`.
+//!
+//! Generates the foreign server, user mapping and (for `postgres_fdw`)
+//! schema import needed to query another saved connection from the active
+//! one. Like the batch scripts, the result is loaded into the editor for
+//! review. The remote password is left as a placeholder so it never ends
+//! up in the editor or the query history.
+
+use crate::services::storage::{ConnectionInfo, DatabaseDriver};
+
+use super::extensions::ExtensionInfo;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+/// Placeholder for the remote password in the user mapping.
+pub const PASSWORD_PLACEHOLDER: &str = "<password>";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CrossDatabaseTool {
+    /// Foreign tables imported into a local schema.
+    PostgresFdw,
+    /// Ad-hoc `dblink()` queries against the foreign server.
+    Dblink,
+}
+
+impl CrossDatabaseTool {
+    pub const ALL: [CrossDatabaseTool; 2] =
+        [CrossDatabaseTool::PostgresFdw, CrossDatabaseTool::Dblink];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CrossDatabaseTool::PostgresFdw => "postgres_fdw",
+            CrossDatabaseTool::Dblink => "dblink",
+        }
+    }
+
+    fn wrapper(&self) -> &'static str {
+        match self {
+            CrossDatabaseTool::PostgresFdw => "postgres_fdw",
+            CrossDatabaseTool::Dblink => "dblink_fdw",
+        }
+    }
+}
+
+/// Tools the server offers, each with whether its extension is already
+/// installed in the current database.
+pub fn available_tools(extensions: &[ExtensionInfo]) -> Vec<(CrossDatabaseTool, bool)> {
+    CrossDatabaseTool::ALL
+        .into_iter()
+        .filter_map(|tool| {
+            extensions
+                .iter()
+                .find(|e| e.name == tool.extension())
+                .map(|e| (tool, e.is_installed()))
+        })
+        .collect()
+}
+
+/// Default foreign server name for a saved connection: its name in
+/// lowercase with anything but letters and digits turned into `_`.
+pub fn server_name_for(connection_name: &str) -> String {
+    let name = connection_name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    let name = name.trim_matches('_');
+    if name.is_empty() {
+        "remote".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignServerSetup {
+    pub tool: CrossDatabaseTool,
+    /// Add `CREATE EXTENSION` for a tool that is not installed yet.
+    pub install_extension: bool,
+    pub server_name: String,
+    pub host: String,
+    pub port: usize,
+    pub dbname: String,
+    pub user: String,
+    /// The target is reached through an SSH tunnel from pgui, which the
+    /// database server cannot use.
+    pub tunneled: bool,
+    /// Schema to import from (`postgres_fdw` only).
+    pub remote_schema: String,
+    /// Local schema the foreign tables are imported into.
+    pub local_schema: String,
+}
+
+impl ForeignServerSetup {
+    /// Setup for reaching `target`, with names derived from it.
+    pub fn new(tool: CrossDatabaseTool, target: &ConnectionInfo) -> Self {
+        let server_name = server_name_for(&target.name);
+        Self {
+            tool,
+            install_extension: false,
+            host: target.hostname.clone(),
+            port: target.port,
+            dbname: target.database.clone(),
+            user: target.username.clone(),
+            tunneled: target.ssh.is_some(),
+            remote_schema: "public".to_string(),
+            local_schema: server_name.clone(),
+            server_name,
+        }
+    }
+}
+
+/// The setup script; empty until the server name (and for `postgres_fdw`
+/// both schemas) are filled in.
+pub fn foreign_server_script(setup: &ForeignServerSetup) -> String {
+    let server_name = setup.server_name.trim();
+    let remote_schema = setup.remote_schema.trim();
+    let local_schema = setup.local_schema.trim();
+    if server_name.is_empty()
+        || (setup.tool == CrossDatabaseTool::PostgresFdw
+            && (remote_schema.is_empty() || local_schema.is_empty()))
+    {
+        return String::new();
+    }
+
+    let server = DRIVER.quote_ident(server_name);
+    let mut statements = vec![format!(
+        "-- Query {}:{}/{} from this database through {}.",
+        setup.host,
+        setup.port,
+        setup.dbname,
+        setup.tool.extension()
+    )];
+
+    if setup.tunneled {
+        statements.push(
+            "-- pgui reaches this connection through an SSH tunnel. The database server\n\
+             -- connects directly, so the host below must be reachable from it."
+                .to_string(),
+        );
+    }
+
+    if setup.install_extension {
+        statements.push(format!(
+            "CREATE EXTENSION IF NOT EXISTS {};",
+            setup.tool.extension()
+        ));
+    }
+
+    statements.push(format!(
+        "CREATE SERVER {} FOREIGN DATA WRAPPER {}\n    OPTIONS (host {}, port {}, dbname {});",
+        server,
+        setup.tool.wrapper(),
+        DRIVER.quote_literal(&setup.host),
+        DRIVER.quote_literal(&setup.port.to_string()),
+        DRIVER.quote_literal(&setup.dbname)
+    ));
+
+    statements.push(format!(
+        "-- Replace {} with the remote password before running.\n\
+         CREATE USER MAPPING FOR CURRENT_USER SERVER {}\n    OPTIONS (user {}, password {});",
+        PASSWORD_PLACEHOLDER,
+        server,
+        DRIVER.quote_literal(&setup.user),
+        DRIVER.quote_literal(PASSWORD_PLACEHOLDER)
+    ));
+
+    match setup.tool {
+        CrossDatabaseTool::PostgresFdw => {
+            let local = DRIVER.quote_ident(local_schema);
+            statements.push(format!(
+                "CREATE SCHEMA IF NOT EXISTS {};\n\
+                 IMPORT FOREIGN SCHEMA {} FROM SERVER {} INTO {};",
+                local,
+                DRIVER.quote_ident(remote_schema),
+                server,
+                local
+            ));
+            statements.push(format!(
+                "-- The remote tables can now be joined like local ones, e.g.\n\
+                 -- SELECT * FROM {}.some_table;",
+                local
+            ));
+        }
+        CrossDatabaseTool::Dblink => {
+            statements.push(format!(
+                "-- Query the remote database, naming the result columns, e.g.\n\
+                 -- SELECT * FROM dblink({}, 'SELECT id, name FROM users')\n\
+                 --     AS t(id integer, name text);",
+                DRIVER.quote_literal(server_name)
+            ));
+        }
+    }
+
+    statements.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extension(name: &str, installed: bool) -> ExtensionInfo {
+        ExtensionInfo {
+            name: name.to_string(),
+            default_version: Some("1.0".to_string()),
+            installed_version: installed.then(|| "1.0".to_string()),
+            schema: None,
+            comment: None,
+        }
+    }
+
+    fn target() -> ConnectionInfo {
+        // `ConnectionInfo` implements `Drop`, so no struct update syntax.
+        let mut info = ConnectionInfo::default();
+        info.name = "Analytics (prod)".to_string();
+        info.hostname = "db.example.com".to_string();
+        info.port = 5433;
+        info.database = "analytics".to_string();
+        info.username = "reporter".to_string();
+        info
+    }
+
+    #[test]
+    fn available_tools_follow_server_extensions() {
+        let extensions = vec![
+            extension("dblink", true),
+            extension("hstore", true),
+            extension("postgres_fdw", false),
+        ];
+        assert_eq!(
+            available_tools(&extensions),
+            vec![
+                (CrossDatabaseTool::PostgresFdw, false),
+                (CrossDatabaseTool::Dblink, true),
+            ]
+        );
+        assert!(available_tools(&[extension("hstore", true)]).is_empty());
+    }
+
+    #[test]
+    fn server_name_is_derived_from_connection_name() {
+        assert_eq!(server_name_for("Analytics (prod)"), "analytics__prod");
+        assert_eq!(server_name_for("orders-db"), "orders_db");
+        assert_eq!(server_name_for("!!"), "remote");
+    }
+
+    #[test]
+    fn postgres_fdw_script_imports_schema() {
+        let mut setup = ForeignServerSetup::new(CrossDatabaseTool::PostgresFdw, &target());
+        setup.install_extension = true;
+
+        assert_eq!(
+            foreign_server_script(&setup),
+            "-- Query db.example.com:5433/analytics from this database through postgres_fdw.\n\n\
+             CREATE EXTENSION IF NOT EXISTS postgres_fdw;\n\n\
+             CREATE SERVER \"analytics__prod\" FOREIGN DATA WRAPPER postgres_fdw\n    \
+             OPTIONS (host 'db.example.com', port '5433', dbname 'analytics');\n\n\
+             -- Replace <password> with the remote password before running.\n\
+             CREATE USER MAPPING FOR CURRENT_USER SERVER \"analytics__prod\"\n    \
+             OPTIONS (user 'reporter', password '<password>');\n\n\
+             CREATE SCHEMA IF NOT EXISTS \"analytics__prod\";\n\
+             IMPORT FOREIGN SCHEMA \"public\" FROM SERVER \"analytics__prod\" INTO \"analytics__prod\";\n\n\
+             -- The remote tables can now be joined like local ones, e.g.\n\
+             -- SELECT * FROM \"analytics__prod\".some_table;"
+        );
+    }
+
+    #[test]
+    fn dblink_script_skips_import_and_installed_extension() {
+        let mut setup = ForeignServerSetup::new(CrossDatabaseTool::Dblink, &target());
+        setup.server_name = "o'brien".to_string();
+
+        let script = foreign_server_script(&setup);
+        assert!(!script.contains("CREATE EXTENSION"));
+        assert!(!script.contains("IMPORT FOREIGN SCHEMA"));
+        assert!(script.contains("CREATE SERVER \"o'brien\" FOREIGN DATA WRAPPER dblink_fdw"));
+        assert!(script.contains("dblink('o''brien', "));
+    }
+
+    #[test]
+    fn incomplete_setup_generates_nothing() {
+        let mut setup = ForeignServerSetup::new(CrossDatabaseTool::PostgresFdw, &target());
+        setup.local_schema = "  ".to_string();
+        assert_eq!(foreign_server_script(&setup), "");
+
+        setup.tool = CrossDatabaseTool::Dblink;
+        assert!(!foreign_server_script(&setup).is_empty());
+
+        setup.server_name.clear();
+        assert_eq!(foreign_server_script(&setup), "");
+    }
+
+    #[test]
+    fn tunneled_target_gets_a_reachability_note() {
+        let mut setup = ForeignServerSetup::new(CrossDatabaseTool::Dblink, &target());
+        setup.tunneled = true;
+        assert!(foreign_server_script(&setup).contains("SSH tunnel"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Client-side filtering of a result's rows, without re-running the query.
+//!
+//! A filter has an optional pattern matched against every cell of a row
+//! and optional per-column patterns that must all match their column.
+//! Patterns are case-insensitive substrings, or regular expressions.
+
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+
+use super::types::ResultRows;
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn new(text: &str, regex: bool) -> Result<Option<Self>> {
+        if text.is_empty() {
+            return Ok(None);
+        }
+        if regex {
+            let re = RegexBuilder::new(text)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("Invalid regex: {}", text))?;
+            Ok(Some(Pattern::Regex(re)))
+        } else {
+            Ok(Some(Pattern::Substring(text.to_lowercase())))
+        }
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        match self {
+            Pattern::Substring(needle) => value.to_lowercase().contains(needle),
+            Pattern::Regex(re) => re.is_match(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ResultFilter {
+    any_column: Option<Pattern>,
+    /// Pattern per result column; `None` where the column is unfiltered.
+    columns: Vec<Option<Pattern>>,
+}
+
+impl ResultFilter {
+    /// A filter from the search box text and the per-column texts, indexed
+    /// by result column. Fails on an invalid regex.
+    pub fn new(any_column: &str, column_filters: &[String], regex: bool) -> Result<Self> {
+        Ok(Self {
+            any_column: Pattern::new(any_column, regex)?,
+            columns: column_filters
+                .iter()
+                .map(|text| Pattern::new(text, regex))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.any_column.is_none() && self.columns.iter().all(Option::is_none)
+    }
+
+    fn row_matches(&self, rows: &ResultRows, row: usize, column_count: usize) -> bool {
+        let columns_match = self.columns.iter().enumerate().all(|(col, pattern)| {
+            pattern
+                .as_ref()
+                .is_none_or(|p| col < column_count && p.is_match(&rows.cell_text(row, col).0))
+        });
+        columns_match
+            && self.any_column.as_ref().is_none_or(|p| {
+                (0..column_count).any(|col| p.is_match(&rows.cell_text(row, col).0))
+            })
+    }
+
+    /// Indexes of the rows that pass the filter, in order.
+    pub fn matching_rows(&self, rows: &ResultRows, column_count: usize) -> Vec<usize> {
+        (0..rows.len())
+            .filter(|&row| self.row_matches(rows, row, column_count))
+            .collect()
+    }
+
+    /// Whether a cell of result column `col` should be highlighted.
+    pub fn highlights(&self, col: usize, value: &str) -> bool {
+        let column_hit = self
+            .columns
+            .get(col)
+            .and_then(Option::as_ref)
+            .is_some_and(|p| p.is_match(value));
+        column_hit || self.any_column.as_ref().is_some_and(|p| p.is_match(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> ResultRows {
+        let row =
+            |id: &str, email: Option<&str>| vec![Some(id.to_string()), email.map(String::from)];
+        ResultRows::from_text(vec![
+            row("1", Some("Alice@example.com")),
+            row("2", Some("bob@test.org")),
+            row("3", None),
+            row("42", Some("carol@example.com")),
+        ])
+    }
+
+    #[test]
+    fn empty_filter_keeps_every_row() {
+        let filter = ResultFilter::new("", &[String::new(), String::new()], false).unwrap();
+        assert!(filter.is_empty());
+        assert_eq!(filter.matching_rows(&rows(), 2), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn substring_matches_any_column_case_insensitively() {
+        let filter = ResultFilter::new("EXAMPLE", &[], false).unwrap();
+        assert_eq!(filter.matching_rows(&rows(), 2), vec![0, 3]);
+        assert!(filter.highlights(1, "alice@example.com"));
+        assert!(!filter.highlights(0, "1"));
+
+        // NULL cells read as "NULL".
+        let filter = ResultFilter::new("null", &[], false).unwrap();
+        assert_eq!(filter.matching_rows(&rows(), 2), vec![2]);
+    }
+
+    #[test]
+    fn column_filters_must_all_match() {
+        let columns = vec!["2".to_string(), "example".to_string()];
+        let filter = ResultFilter::new("", &columns, false).unwrap();
+        assert_eq!(filter.matching_rows(&rows(), 2), vec![3]);
+        assert!(filter.highlights(0, "42"));
+        assert!(!filter.highlights(0, "13"));
+    }
+
+    #[test]
+    fn regex_patterns_and_errors() {
+        let filter = ResultFilter::new(r"^\d$", &[], true).unwrap();
+        assert_eq!(filter.matching_rows(&rows(), 2), vec![0, 1, 2]);
+
+        let filter = ResultFilter::new("", &[String::new(), r"\.org$".to_string()], true).unwrap();
+        assert_eq!(filter.matching_rows(&rows(), 2), vec![1]);
+
+        let err = ResultFilter::new("(", &[], true).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid regex"));
+    }
+}
@@ -0,0 +1,79 @@
+//! Queries that follow a foreign key from a result cell, to the row it
+//! points at or to the rows that point at it.
+
+use crate::services::storage::DatabaseDriver;
+
+use super::types::{ForeignKeyInfo, ReferencingKey};
+
+/// `SELECT` of the row `fk` points at when its column holds `value`.
+pub fn referenced_row_sql(driver: &DatabaseDriver, fk: &ForeignKeyInfo, value: &str) -> String {
+    select_where(
+        driver,
+        &fk.foreign_table_schema,
+        &fk.foreign_table_name,
+        &fk.foreign_column_name,
+        value,
+    )
+}
+
+/// `SELECT` of the rows of `key`'s table that point at `value`.
+pub fn referencing_rows_sql(driver: &DatabaseDriver, key: &ReferencingKey, value: &str) -> String {
+    select_where(
+        driver,
+        &key.table_schema,
+        &key.table_name,
+        &key.column_name,
+        value,
+    )
+}
+
+fn select_where(
+    driver: &DatabaseDriver,
+    schema: &str,
+    table: &str,
+    column: &str,
+    value: &str,
+) -> String {
+    format!(
+        "SELECT * FROM {}.{} WHERE {} = {}",
+        driver.quote_ident(schema),
+        driver.quote_ident(table),
+        driver.quote_ident(column),
+        driver.quote_literal(value)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_the_referenced_row() {
+        let fk = ForeignKeyInfo {
+            constraint_name: "orders_user_id_fkey".to_string(),
+            column_name: "user_id".to_string(),
+            foreign_table_schema: "public".to_string(),
+            foreign_table_name: "users".to_string(),
+            foreign_column_name: "id".to_string(),
+        };
+        assert_eq!(
+            referenced_row_sql(&DatabaseDriver::Postgres, &fk, "42"),
+            r#"SELECT * FROM "public"."users" WHERE "id" = '42'"#
+        );
+    }
+
+    #[test]
+    fn selects_referencing_rows() {
+        let key = ReferencingKey {
+            constraint_name: "orders_user_id_fkey".to_string(),
+            table_schema: "shop".to_string(),
+            table_name: "orders".to_string(),
+            column_name: "user_id".to_string(),
+            referenced_column: "id".to_string(),
+        };
+        assert_eq!(
+            referencing_rows_sql(&DatabaseDriver::MySql, &key, "o'neil"),
+            "SELECT * FROM `shop`.`orders` WHERE `user_id` = 'o''neil'"
+        );
+    }
+}
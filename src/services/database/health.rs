@@ -0,0 +1,158 @@
+//! Connection health: round-trip latency of periodic `SELECT 1` pings.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Pings kept for the sparkline.
+pub const HEALTH_SAMPLES: usize = 30;
+
+/// Latency above which a ping counts as slow.
+const SLOW_LATENCY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthLevel {
+    /// No pings yet.
+    Unknown,
+    Good,
+    /// The last ping was slow, or a recent one failed.
+    Degraded,
+    /// The last ping failed.
+    Down,
+}
+
+/// State of the SSH tunnel under the connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TunnelStatus {
+    /// The connection does not go through a tunnel.
+    #[default]
+    Direct,
+    Up,
+    Down,
+}
+
+/// The most recent pings, oldest first. `None` is a failed ping.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConnectionHealth {
+    samples: VecDeque<Option<Duration>>,
+}
+
+impl ConnectionHealth {
+    pub fn record(&mut self, latency: Option<Duration>) {
+        if self.samples.len() == HEALTH_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = Option<Duration>> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn latest(&self) -> Option<Option<Duration>> {
+        self.samples.back().copied()
+    }
+
+    /// Failed pings since the last one that succeeded.
+    pub fn consecutive_failures(&self) -> usize {
+        self.samples
+            .iter()
+            .rev()
+            .take_while(|s| s.is_none())
+            .count()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        let ok: Vec<Duration> = self.samples.iter().flatten().copied().collect();
+        let count = u32::try_from(ok.len()).ok().filter(|&n| n > 0)?;
+        Some(ok.iter().sum::<Duration>() / count)
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.samples.iter().flatten().max().copied()
+    }
+
+    pub fn level(&self) -> HealthLevel {
+        match self.latest() {
+            None => HealthLevel::Unknown,
+            Some(None) => HealthLevel::Down,
+            Some(Some(latency)) => {
+                if latency > SLOW_LATENCY || self.samples.iter().any(Option::is_none) {
+                    HealthLevel::Degraded
+                } else {
+                    HealthLevel::Good
+                }
+            }
+        }
+    }
+
+    /// Each ping's latency as a fraction of the slowest one, for drawing
+    /// the sparkline. Failed pings are `None`.
+    pub fn sparkline(&self) -> Vec<Option<f32>> {
+        let max = self.max().map_or(0.0, |d| d.as_secs_f32());
+        self.samples
+            .iter()
+            .map(|sample| {
+                sample.map(|d| {
+                    if max > 0.0 {
+                        d.as_secs_f32() / max
+                    } else {
+                        0.0
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Option<Duration> {
+        Some(Duration::from_millis(n))
+    }
+
+    #[test]
+    fn keeps_the_latest_samples() {
+        let mut health = ConnectionHealth::default();
+        for n in 0..(HEALTH_SAMPLES as u64 + 5) {
+            health.record(ms(n));
+        }
+        assert_eq!(health.samples().count(), HEALTH_SAMPLES);
+        assert_eq!(health.samples().next(), Some(ms(5)));
+        assert_eq!(health.latest(), Some(ms(HEALTH_SAMPLES as u64 + 4)));
+    }
+
+    #[test]
+    fn levels() {
+        let mut health = ConnectionHealth::default();
+        assert_eq!(health.level(), HealthLevel::Unknown);
+        health.record(ms(10));
+        assert_eq!(health.level(), HealthLevel::Good);
+        health.record(ms(400));
+        assert_eq!(health.level(), HealthLevel::Degraded);
+        health.record(None);
+        health.record(None);
+        assert_eq!(health.level(), HealthLevel::Down);
+        assert_eq!(health.consecutive_failures(), 2);
+        // A recent failure still marks a fast link as flaky.
+        health.record(ms(10));
+        assert_eq!(health.level(), HealthLevel::Degraded);
+        assert_eq!(health.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn statistics_and_sparkline() {
+        let mut health = ConnectionHealth::default();
+        health.record(ms(10));
+        health.record(None);
+        health.record(ms(30));
+        assert_eq!(health.average(), ms(20));
+        assert_eq!(health.max(), ms(30));
+        let line = health.sparkline();
+        assert_eq!(line.len(), 3);
+        assert!((line[0].unwrap() - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(line[1], None);
+        assert_eq!(line[2], Some(1.0));
+    }
+}
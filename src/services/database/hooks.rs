@@ -0,0 +1,254 @@
+//! Commands run before and after each query from the editor, per
+//! connection: SQL sent to the database, or a shell command.
+//!
+//! SQL hooks run on the same connection as the query, so a `SET ROLE` or
+//! `SET search_path` in the before hook holds for the query and the after
+//! hook. The session is reset before the connection goes back to the
+//! pool. Shell hooks are told about the query through `PGUI_*`
+//! environment variables.
+
+use anyhow::{Context as _, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use super::types::QueryExecutionResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HookKind {
+    #[default]
+    Sql,
+    Shell,
+}
+
+impl HookKind {
+    pub const ALL: [HookKind; 2] = [HookKind::Sql, HookKind::Shell];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HookKind::Sql => "SQL",
+            HookKind::Shell => "Shell",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExecutionHook {
+    pub kind: HookKind,
+    pub command: String,
+}
+
+impl ExecutionHook {
+    pub fn is_set(&self) -> bool {
+        !self.command.trim().is_empty()
+    }
+}
+
+/// The hooks of one connection; a hook with no command is off.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExecutionHooks {
+    pub before: ExecutionHook,
+    pub after: ExecutionHook,
+}
+
+impl ExecutionHooks {
+    pub fn is_active(&self) -> bool {
+        self.before.is_set() || self.after.is_set()
+    }
+
+    /// `Hooks: before (SQL) · after (Shell)`, or `None` when both are off.
+    pub fn summary(&self) -> Option<String> {
+        let stages: Vec<String> = [
+            (HookStage::Before, &self.before),
+            (HookStage::After, &self.after),
+        ]
+        .into_iter()
+        .filter(|(_, hook)| hook.is_set())
+        .map(|(stage, hook)| format!("{} ({})", stage.name(), hook.kind.label()))
+        .collect();
+        (!stages.is_empty()).then(|| format!("Hooks: {}", stages.join(" · ")))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    Before,
+    After,
+}
+
+impl HookStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookStage::Before => "before",
+            HookStage::After => "after",
+        }
+    }
+}
+
+/// What a hook is told about the query it runs around.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookContext {
+    pub stage: HookStage,
+    pub connection: String,
+    pub database: String,
+    pub query: String,
+    /// How the query went, for hooks run after it.
+    pub outcome: Option<HookOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookOutcome {
+    pub succeeded: bool,
+    /// Rows returned or affected.
+    pub rows: u64,
+    pub execution_time_ms: u128,
+    pub error: Option<String>,
+}
+
+impl HookOutcome {
+    pub fn from_result(result: &QueryExecutionResult) -> Self {
+        match result {
+            QueryExecutionResult::Select(r) => HookOutcome {
+                succeeded: true,
+                rows: r.row_count as u64,
+                execution_time_ms: r.execution_time_ms,
+                error: None,
+            },
+            QueryExecutionResult::Modified(m) => HookOutcome {
+                succeeded: true,
+                rows: m.rows_affected,
+                execution_time_ms: m.execution_time_ms,
+                error: None,
+            },
+            QueryExecutionResult::Error(e) => HookOutcome {
+                succeeded: false,
+                rows: 0,
+                execution_time_ms: e.execution_time_ms,
+                error: Some(e.message.clone()),
+            },
+        }
+    }
+}
+
+impl HookContext {
+    /// The environment of a shell hook.
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("PGUI_HOOK", self.stage.name().to_string()),
+            ("PGUI_CONNECTION", self.connection.clone()),
+            ("PGUI_DATABASE", self.database.clone()),
+            ("PGUI_QUERY", self.query.clone()),
+        ];
+        if let Some(outcome) = &self.outcome {
+            let status = if outcome.succeeded {
+                "success"
+            } else {
+                "error"
+            };
+            env.push(("PGUI_STATUS", status.to_string()));
+            env.push(("PGUI_ROWS", outcome.rows.to_string()));
+            env.push(("PGUI_DURATION_MS", outcome.execution_time_ms.to_string()));
+            env.push(("PGUI_ERROR", outcome.error.clone().unwrap_or_default()));
+        }
+        env
+    }
+}
+
+/// Run `command` with the system shell, failing with its output when it
+/// exits unsuccessfully.
+pub(crate) async fn run_shell_hook(command: &str, context: &HookContext) -> Result<()> {
+    let mut process = if cfg!(target_os = "windows") {
+        let mut process = smol::process::Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    } else {
+        let mut process = smol::process::Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    };
+    let output = process
+        .envs(context.env())
+        .output()
+        .await
+        .context("Failed to start the shell")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let message = [stderr.trim(), stdout.trim()]
+            .into_iter()
+            .find(|m| !m.is_empty())
+            .unwrap_or("no output");
+        return Err(anyhow!("exited with {}: {}", output.status, message));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::types::{ErrorResult, ModifiedResult};
+
+    fn hook(kind: HookKind, command: &str) -> ExecutionHook {
+        ExecutionHook {
+            kind,
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn summarizes_active_hooks() {
+        let mut hooks = ExecutionHooks::default();
+        assert!(!hooks.is_active());
+        assert_eq!(hooks.summary(), None);
+
+        hooks.before = hook(HookKind::Sql, "SET ROLE analyst;");
+        hooks.after = hook(HookKind::Shell, "  ");
+        assert!(hooks.is_active());
+        assert_eq!(hooks.summary().as_deref(), Some("Hooks: before (SQL)"));
+
+        hooks.after = hook(HookKind::Shell, "logger \"$PGUI_QUERY\"");
+        assert_eq!(
+            hooks.summary().as_deref(),
+            Some("Hooks: before (SQL) · after (Shell)")
+        );
+    }
+
+    #[test]
+    fn describes_the_query_to_shell_hooks() {
+        let mut context = HookContext {
+            stage: HookStage::Before,
+            connection: "prod".to_string(),
+            database: "shop".to_string(),
+            query: "SELECT 1".to_string(),
+            outcome: None,
+        };
+        let names: Vec<&str> = context.env().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "PGUI_HOOK",
+                "PGUI_CONNECTION",
+                "PGUI_DATABASE",
+                "PGUI_QUERY"
+            ]
+        );
+
+        context.stage = HookStage::After;
+        context.outcome = Some(HookOutcome::from_result(&QueryExecutionResult::Modified(
+            ModifiedResult {
+                rows_affected: 3,
+                execution_time_ms: 12,
+            },
+        )));
+        let env = context.env();
+        assert!(env.contains(&("PGUI_HOOK", "after".to_string())));
+        assert!(env.contains(&("PGUI_STATUS", "success".to_string())));
+        assert!(env.contains(&("PGUI_ROWS", "3".to_string())));
+        assert!(env.contains(&("PGUI_DURATION_MS", "12".to_string())));
+
+        let failed = HookOutcome::from_result(&QueryExecutionResult::Error(ErrorResult {
+            message: "Query failed: boom".to_string(),
+            execution_time_ms: 1,
+        }));
+        assert!(!failed.succeeded);
+        assert_eq!(failed.error.as_deref(), Some("Query failed: boom"));
+    }
+}
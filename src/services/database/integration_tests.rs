@@ -0,0 +1,458 @@
+//! End-to-end tests against a real Postgres.
+//!
+//! Gated behind the `integration-tests` feature:
+//!
+//! ```sh
+//! cargo test --features integration-tests integration_tests
+//! ```
+//!
+//! The first test to run starts the `db` service from `docker-compose.yml`
+//! (seeded from `init.sql`) and waits for its healthcheck. Set
+//! `PGUI_TEST_NO_COMPOSE=1` when Postgres is already running, e.g. as a CI
+//! service container, and `PGUI_TEST_PG_PORT` if it is not on 5432. The
+//! compose container is left running so repeated runs are fast; stop it
+//! with `docker compose down`.
+//!
+//! Tests only read the seeded tables. Anything they write goes to tables
+//! named `pgui_it_<uuid>` that the test drops again, so tests can run in
+//! parallel against the same database.
+//!
+//! What we cover here:
+//! - Query execution: SELECT, modifications, errors, bound parameters.
+//! - Cancelling a running job on the server.
+//! - Decoding of the column types the grid renders as text.
+//! - Schema introspection of the seeded tables.
+//! - Server notices captured alongside a result.
+//! - The history pipeline: execute, record, load back.
+//!
+//! MySQL is not covered yet; the compose file only has Postgres.
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use tempfile::TempDir;
+use tracing_subscriber::layer::SubscriberExt as _;
+use uuid::Uuid;
+
+use super::hooks::{ExecutionHook, ExecutionHooks, HookContext, HookKind, HookStage};
+use super::manager::DatabaseManager;
+use super::notices::notice_layer;
+use super::types::{QueryExecutionResult, QueryResult};
+use crate::services::sql::{BoundParams, parse_params};
+use crate::services::storage::{AppStore, ConnectionInfo, DatabaseDriver};
+
+const CONNECT_ATTEMPTS: usize = 30;
+
+/// Start the compose `db` service once per test process.
+fn ensure_postgres() {
+    static STARTED: OnceLock<()> = OnceLock::new();
+    STARTED.get_or_init(|| {
+        if std::env::var_os("PGUI_TEST_NO_COMPOSE").is_some() {
+            return;
+        }
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let status = Command::new("docker")
+            .args(["compose", "up", "-d", "--wait", "db"])
+            .current_dir(root)
+            .status()
+            .expect("failed to run `docker compose`; is Docker installed?");
+        assert!(status.success(), "`docker compose up` failed");
+    });
+}
+
+fn connection_info() -> ConnectionInfo {
+    let port = std::env::var("PGUI_TEST_PG_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(5432);
+    // `ConnectionInfo` implements `Drop`, so no struct update syntax.
+    let mut info = ConnectionInfo::default();
+    info.name = "integration".to_string();
+    info.port = port;
+    info
+}
+
+/// A manager connected to the test database. Retries while the server
+/// finishes starting up.
+async fn connected() -> DatabaseManager {
+    ensure_postgres();
+    let info = connection_info();
+    let manager = DatabaseManager::new();
+
+    for attempt in 1..=CONNECT_ATTEMPTS {
+        match manager.connect(&info).await {
+            Ok(()) => return manager,
+            Err(e) if attempt == CONNECT_ATTEMPTS => {
+                panic!("could not connect to the test database: {}", e)
+            }
+            Err(_) => smol::Timer::after(Duration::from_secs(1)).await,
+        }
+    }
+    unreachable!()
+}
+
+fn select(result: QueryExecutionResult) -> QueryResult {
+    match result {
+        QueryExecutionResult::Select(r) => r,
+        QueryExecutionResult::Modified(m) => panic!("expected rows, got {:?}", m),
+        QueryExecutionResult::Error(e) => panic!("query failed: {}", e.message),
+    }
+}
+
+fn scratch_table() -> String {
+    format!("pgui_it_{}", Uuid::new_v4().simple())
+}
+
+#[test]
+fn select_returns_rows_and_metadata() {
+    smol::block_on(async {
+        let db = connected().await;
+        let result = select(
+            db.execute_query_enhanced("SELECT id, name, email FROM users ORDER BY id")
+                .await,
+        );
+
+        assert_eq!(result.row_count, 6);
+        assert_eq!(result.columns.len(), 3);
+        assert_eq!(result.columns[1].name, "name");
+        assert_eq!(
+            result.columns[0].table_name.as_deref(),
+            Some("public.users")
+        );
+        assert_eq!(result.columns[0].is_nullable, Some(false));
+        assert_eq!(result.columns[1].is_nullable, Some(true));
+
+        let first = result.row(0).unwrap();
+        assert_eq!(first.cells[1].value, "Alpha");
+        assert_eq!(first.cells[2].value, "alpha@example.com");
+    });
+}
+
+#[test]
+fn select_without_limit_is_capped() {
+    smol::block_on(async {
+        let db = connected().await;
+        let result = select(
+            db.execute_query_enhanced("SELECT * FROM generate_series(1, 5000)")
+                .await,
+        );
+        assert_eq!(result.row_count, 1_000);
+    });
+}
+
+#[test]
+fn decodes_common_types() {
+    smol::block_on(async {
+        let db = connected().await;
+        let result = select(
+            db.execute_query_enhanced(
+                "SELECT 42::int2, 42::int4, 9000000000::int8, 1.5::float4, 2.25::float8, \
+                 123.4500::numeric(10,4), true, 'text'::text, 'abc'::varchar, NULL::int4",
+            )
+            .await,
+        );
+
+        let values = (0..result.columns.len())
+            .map(|col| result.rows.cell_text(0, col))
+            .collect::<Vec<_>>();
+        let text = |v: &str| (v.to_string(), false);
+
+        assert_eq!(values[0], text("42"));
+        assert_eq!(values[1], text("42"));
+        assert_eq!(values[2], text("9000000000"));
+        assert_eq!(values[3], text("1.5"));
+        assert_eq!(values[4], text("2.25"));
+        assert_eq!(values[5], text("123.4500"));
+        assert_eq!(values[6], text("true"));
+        assert_eq!(values[7], text("text"));
+        assert_eq!(values[8], text("abc"));
+        assert_eq!(values[9], ("NULL".to_string(), true));
+    });
+}
+
+#[test]
+fn decodes_dates_network_types_arrays_and_ranges() {
+    smol::block_on(async {
+        let db = connected().await;
+        let result = select(
+            db.execute_query_enhanced(
+                "SELECT '2024-05-01'::date, 'infinity'::timestamptz, '13:45'::time, \
+                 '1 year 2 mons 3 days 04:05:06.5'::interval, \
+                 '00010203-0405-0607-0809-0a0b0c0d0e0f'::uuid, '192.168.0.1/24'::inet, \
+                 '10.0.0.0/8'::cidr, '08:00:2b:01:02:03'::macaddr, B'1011'::bit(4), \
+                 12.5::money, ARRAY[1, NULL, 3], '[1,10)'::int4range, ROW(1, 'a b')",
+            )
+            .await,
+        );
+
+        let values = (0..result.columns.len())
+            .map(|col| result.rows.cell_text(0, col).0)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            values,
+            vec![
+                "2024-05-01",
+                "infinity",
+                "13:45:00",
+                "1 year 2 mons 3 days 04:05:06.5",
+                "00010203-0405-0607-0809-0a0b0c0d0e0f",
+                "192.168.0.1/24",
+                "10.0.0.0/8",
+                "08:00:2b:01:02:03",
+                "1011",
+                "12.50",
+                "{1,NULL,3}",
+                "[1,10)",
+                "(1,\"a b\")",
+            ]
+        );
+    });
+}
+
+#[test]
+fn modification_reports_rows_affected() {
+    smol::block_on(async {
+        let db = connected().await;
+        let table = scratch_table();
+
+        let created = db
+            .execute_query_enhanced(&format!("CREATE TABLE {} (id int, note text)", table))
+            .await;
+        assert!(matches!(created, QueryExecutionResult::Modified(_)));
+
+        let inserted = db
+            .execute_query_enhanced(&format!(
+                "INSERT INTO {} VALUES (1, 'a'), (2, 'b'), (3, NULL)",
+                table
+            ))
+            .await;
+        let dropped = db
+            .execute_query_enhanced(&format!("DROP TABLE {}", table))
+            .await;
+
+        match inserted {
+            QueryExecutionResult::Modified(m) => assert_eq!(m.rows_affected, 3),
+            other => panic!("expected a modification, got {:?}", other),
+        }
+        assert!(matches!(dropped, QueryExecutionResult::Modified(_)));
+    });
+}
+
+#[test]
+fn errors_carry_the_server_message() {
+    smol::block_on(async {
+        let db = connected().await;
+        match db
+            .execute_query_enhanced("SELECT * FROM no_such_table")
+            .await
+        {
+            QueryExecutionResult::Error(e) => assert!(e.message.contains("no_such_table")),
+            other => panic!("expected an error, got {:?}", other),
+        }
+    });
+}
+
+#[test]
+fn bound_parameters_use_described_types() {
+    smol::block_on(async {
+        let db = connected().await;
+        let query = parse_params(
+            &DatabaseDriver::Postgres,
+            "SELECT name FROM users WHERE id = :id OR email = $1 ORDER BY id",
+        )
+        .unwrap();
+
+        let types = db.describe_params(&query).await.unwrap();
+        assert_eq!(types.len(), 2);
+        assert_eq!(types[1].as_deref(), Some("integer"));
+
+        let bound = BoundParams {
+            query,
+            types,
+            values: vec![Some("beta@example.com".to_string()), Some("1".to_string())],
+        };
+        let mut run = db.run_job(Uuid::new_v4(), "", Some(&bound), None).await;
+        let result = select(run.results.remove(0));
+
+        assert_eq!(result.row_count, 2);
+        assert_eq!(result.rows.cell_text(0, 0).0, "Alpha");
+        assert_eq!(result.rows.cell_text(1, 0).0, "Beta");
+    });
+}
+
+#[test]
+fn captures_raised_notices() {
+    // Notices reach `capture_notices` through the tracing layer.
+    let subscriber = tracing_subscriber::registry().with(notice_layer());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    smol::block_on(async {
+        let db = connected().await;
+        let run = db
+            .run_job(
+                Uuid::new_v4(),
+                "DO $$ BEGIN RAISE NOTICE 'hello from pgui'; END $$",
+                None,
+                None,
+            )
+            .await;
+
+        assert!(matches!(run.results[..], [QueryExecutionResult::Modified(_)]));
+        assert!(
+            run.notices
+                .iter()
+                .any(|n| n.severity == "NOTICE" && n.message.contains("hello from pgui"))
+        );
+    });
+}
+
+#[test]
+fn hooks_share_the_query_connection() {
+    smol::block_on(async {
+        let db = connected().await;
+        let hooks = ExecutionHooks {
+            before: ExecutionHook {
+                kind: HookKind::Sql,
+                command: "SET application_name = 'pgui-hook'".to_string(),
+            },
+            after: ExecutionHook::default(),
+        };
+        let context = HookContext {
+            stage: HookStage::Before,
+            connection: "test".to_string(),
+            database: "pgui_test".to_string(),
+            query: String::new(),
+            outcome: None,
+        };
+        let sql = "SELECT current_setting('application_name')";
+
+        let mut run = db
+            .run_job(Uuid::new_v4(), sql, None, Some((&hooks, &context)))
+            .await;
+        let result = select(run.results.remove(0));
+        assert_eq!(result.rows.cell_text(0, 0).0, "pgui-hook");
+
+        // The setting went with the session when it was reset
+        let mut run = db.run_job(Uuid::new_v4(), sql, None, None).await;
+        let result = select(run.results.remove(0));
+        assert_ne!(result.rows.cell_text(0, 0).0, "pgui-hook");
+    });
+}
+
+#[test]
+fn reports_transactions_left_open() {
+    smol::block_on(async {
+        let db = connected().await;
+        let run = db.run_job(Uuid::new_v4(), "SELECT 1", None, None).await;
+        assert_eq!(run.transaction_open, Some(false));
+
+        let run = db
+            .run_job(Uuid::new_v4(), "BEGIN; SELECT 1; ROLLBACK", None, None)
+            .await;
+        assert_eq!(run.transaction_open, Some(false));
+
+        let run = db.run_job(Uuid::new_v4(), "BEGIN; SELECT 1", None, None).await;
+        assert_eq!(run.transaction_open, Some(true));
+    });
+}
+
+#[test]
+fn cancels_a_running_job() {
+    smol::block_on(async {
+        let db = connected().await;
+        let job_id = Uuid::new_v4();
+
+        let run = db.run_job(job_id, "SELECT pg_sleep(30)", None, None);
+        let cancel = async {
+            smol::Timer::after(Duration::from_millis(500)).await;
+            db.cancel_job(job_id).await.unwrap()
+        };
+        let (mut run, cancelled) = futures::join!(run, cancel);
+
+        assert!(cancelled);
+        match run.results.remove(0) {
+            QueryExecutionResult::Error(e) => assert!(e.message.contains("canceling statement")),
+            other => panic!("expected a cancellation error, got {:?}", other),
+        }
+        assert!(!db.cancel_job(job_id).await.unwrap());
+    });
+}
+
+#[test]
+fn introspects_seeded_schema() {
+    smol::block_on(async {
+        let db = connected().await;
+
+        let tables = db.get_tables().await.unwrap();
+        for name in ["users", "orders", "order_items", "advanced_types_test"] {
+            assert!(
+                tables.iter().any(|t| t.table_name == name),
+                "missing table {}",
+                name
+            );
+        }
+
+        let schema = db
+            .get_schema(Some(vec!["order_items".to_string()]))
+            .await
+            .unwrap();
+        let order_items = schema
+            .tables
+            .iter()
+            .find(|t| t.table_name == "order_items")
+            .expect("order_items in schema");
+
+        assert_eq!(order_items.primary_keys, vec!["id".to_string()]);
+        assert!(
+            order_items
+                .columns
+                .iter()
+                .any(|c| c.column_name == "quantity")
+        );
+        assert!(
+            order_items
+                .foreign_keys
+                .iter()
+                .any(|fk| fk.foreign_table_name == "orders")
+        );
+    });
+}
+
+#[test]
+fn history_records_executed_queries() {
+    smol::block_on(async {
+        let db = connected().await;
+        let dir = TempDir::new().unwrap();
+        let store = AppStore::from_path(dir.path().join("pgui.db"))
+            .await
+            .unwrap();
+        let connection_id = Uuid::new_v4();
+
+        let sql = "SELECT count(*) FROM users";
+        let result = select(db.execute_query_enhanced(sql).await);
+        store
+            .history()
+            .record(
+                &connection_id,
+                sql,
+                result.execution_time_ms as i64,
+                None,
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let history = store
+            .history()
+            .load_for_connection(&connection_id, 10)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].sql, sql);
+        assert!(history[0].success);
+    });
+}
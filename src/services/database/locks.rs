@@ -0,0 +1,209 @@
+//! Who blocks whom, from `pg_locks` and `pg_stat_activity`.
+//!
+//! Sessions are arranged as a forest: each root holds locks others wait on
+//! without waiting itself, and every waiting session is listed under each
+//! session it waits on. Cancelling or terminating a root unblocks its tree.
+
+/// A session that holds or waits on a lock another session is involved in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockSession {
+    pub pid: i32,
+    pub username: Option<String>,
+    pub application_name: Option<String>,
+    pub state: Option<String>,
+    pub query: Option<String>,
+    /// Seconds since the session's transaction, or else its query, began.
+    pub duration_secs: Option<f64>,
+    /// Sessions this one waits on, from `pg_blocking_pids`.
+    pub blocked_by: Vec<i32>,
+    /// Lock modes and the relations they are on, e.g.
+    /// `RowExclusiveLock on orders`; waits are marked `(waiting)`.
+    pub locks: Vec<String>,
+}
+
+/// A session's place in the blocking tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockingNode {
+    pub session: LockSession,
+    pub depth: usize,
+    /// Blocks others without waiting itself (or is part of a cycle).
+    pub is_root: bool,
+    /// Distinct sessions waiting on this one, directly or not.
+    pub blocked_count: usize,
+}
+
+/// How to stop a blocking session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendSignal {
+    /// Cancel its current statement.
+    Cancel,
+    /// Close its connection, rolling back its transaction.
+    Terminate,
+}
+
+impl BackendSignal {
+    pub fn title(&self) -> &'static str {
+        match self {
+            BackendSignal::Cancel => "Cancel Query",
+            BackendSignal::Terminate => "Terminate Session",
+        }
+    }
+
+    pub fn sql(&self, pid: i32) -> String {
+        match self {
+            BackendSignal::Cancel => format!("SELECT pg_cancel_backend({});", pid),
+            BackendSignal::Terminate => format!("SELECT pg_terminate_backend({});", pid),
+        }
+    }
+}
+
+/// Arrange `sessions` into blocking trees in display order, roots first by
+/// how many sessions they hold up. Sessions neither blocking nor blocked
+/// are left out.
+pub fn blocking_tree(sessions: &[LockSession]) -> Vec<BlockingNode> {
+    let involved: Vec<&LockSession> = sessions
+        .iter()
+        .filter(|s| {
+            !s.blocked_by.is_empty() || sessions.iter().any(|o| o.blocked_by.contains(&s.pid))
+        })
+        .collect();
+    let waiters = |pid: i32| -> Vec<&LockSession> {
+        involved
+            .iter()
+            .copied()
+            .filter(|s| s.blocked_by.contains(&pid))
+            .collect()
+    };
+    let blocked_count = |pid: i32| {
+        let mut seen = vec![pid];
+        let mut queue = vec![pid];
+        while let Some(current) = queue.pop() {
+            for waiter in waiters(current) {
+                if !seen.contains(&waiter.pid) {
+                    seen.push(waiter.pid);
+                    queue.push(waiter.pid);
+                }
+            }
+        }
+        seen.len() - 1
+    };
+
+    // Waiting on nothing that is still around; a cycle has no such session,
+    // so its members are picked up as roots afterwards.
+    let mut roots: Vec<&LockSession> = involved
+        .iter()
+        .copied()
+        .filter(|s| {
+            !s.blocked_by
+                .iter()
+                .any(|pid| involved.iter().any(|o| o.pid == *pid))
+        })
+        .collect();
+    roots.sort_by_key(|s| std::cmp::Reverse(blocked_count(s.pid)));
+
+    let mut nodes = vec![];
+    let push_tree = |root: &LockSession, nodes: &mut Vec<BlockingNode>| {
+        let mut stack = vec![(root, 0, vec![root.pid])];
+        while let Some((session, depth, path)) = stack.pop() {
+            nodes.push(BlockingNode {
+                session: session.clone(),
+                depth,
+                is_root: depth == 0,
+                blocked_count: blocked_count(session.pid),
+            });
+            for waiter in waiters(session.pid).into_iter().rev() {
+                if !path.contains(&waiter.pid) {
+                    let mut path = path.clone();
+                    path.push(waiter.pid);
+                    stack.push((waiter, depth + 1, path));
+                }
+            }
+        }
+    };
+
+    for root in roots {
+        push_tree(root, &mut nodes);
+    }
+    for session in &involved {
+        if !nodes.iter().any(|n| n.session.pid == session.pid) {
+            push_tree(session, &mut nodes);
+        }
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(pid: i32, blocked_by: &[i32]) -> LockSession {
+        LockSession {
+            pid,
+            username: Some("app".to_string()),
+            application_name: None,
+            state: Some("active".to_string()),
+            query: Some(format!("-- session {}", pid)),
+            duration_secs: Some(1.0),
+            blocked_by: blocked_by.to_vec(),
+            locks: vec![],
+        }
+    }
+
+    fn shape(nodes: &[BlockingNode]) -> Vec<(i32, usize, bool, usize)> {
+        nodes
+            .iter()
+            .map(|n| (n.session.pid, n.depth, n.is_root, n.blocked_count))
+            .collect()
+    }
+
+    #[test]
+    fn lists_waiters_under_their_root_blockers() {
+        let sessions = vec![
+            session(1, &[]),
+            session(2, &[1]),
+            session(3, &[2]),
+            session(4, &[]),
+            session(5, &[4]),
+            session(6, &[4]),
+            session(7, &[]),
+        ];
+        assert_eq!(
+            shape(&blocking_tree(&sessions)),
+            vec![
+                (1, 0, true, 2),
+                (2, 1, false, 1),
+                (3, 2, false, 0),
+                (4, 0, true, 2),
+                (5, 1, false, 0),
+                (6, 1, false, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_sessions_waiting_on_two_blockers_under_both() {
+        let sessions = vec![session(1, &[]), session(2, &[]), session(3, &[1, 2])];
+        assert_eq!(
+            shape(&blocking_tree(&sessions)),
+            vec![
+                (1, 0, true, 1),
+                (3, 1, false, 0),
+                (2, 0, true, 1),
+                (3, 1, false, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn breaks_cycles() {
+        let sessions = vec![session(1, &[2]), session(2, &[1])];
+        assert_eq!(
+            shape(&blocking_tree(&sessions)),
+            vec![(1, 0, true, 1), (2, 1, false, 1)]
+        );
+        assert_eq!(
+            BackendSignal::Terminate.sql(42),
+            "SELECT pg_terminate_backend(42);"
+        );
+    }
+}
@@ -3,19 +3,64 @@ use async_lock::RwLock;
 use futures::stream::BoxStream;
 use futures::StreamExt;
 use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
-use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::{PgPool, PgPoolCopyExt, PgPoolOptions, PgRow};
+use sqlx::{Connection as _, Executor as _, MySql, Postgres};
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
+use super::activity::SessionActivity;
+use super::admin::{
+    NewDatabase, NewSchema, create_database_sql, create_schema_sql, drop_database_sql,
+    drop_schema_sql,
+};
+use super::compare::{
+    COMPARE_BATCH_SIZE, COMPARE_FETCH_CHUNK, CompareColumns, DataCompareReport, PageDiff,
+    RowDiffKind, RowHash, SyncRow, generate_sync_script,
+};
+use super::connection_test::{
+    ConnectionReport, PG_SSL_REQUEST, StageStatus, TestStage, dns_hint, login_hint,
+    mysql_offers_tls, pg_accepts_tls, ssh_hint, tcp_hint, tls_outcome, with_timeout,
+};
+use super::dml::{ParamStatement, TableEditInfo};
+use super::extensions::{ExtensionAction, ExtensionInfo, ExtensionObject, extension_sql};
+use super::health::TunnelStatus;
+use super::hooks::{
+    ExecutionHook, ExecutionHooks, HookContext, HookKind, HookOutcome, HookStage, run_shell_hook,
+};
+use super::locks::{BackendSignal, LockSession};
+use super::migrations::{AppliedMigration, MigrationFile, TrackingTable};
 use super::mysql as my_backend;
+use super::notices::{ServerNotice, capture_notices};
+use super::performance::{ALL_STATEMENTS, PerformanceReport, StatementStats, TOP_STATEMENTS};
 use super::postgres as pg_backend;
+use super::roles::{RoleDefinition, password_salt, reset_password_sql};
+use super::row_limit::{DEFAULT_MAX_ROWS, PlanEstimate};
+use super::search::ObjectSource;
+use super::sequences::{SequenceInfo, restart_sequence_sql, sync_sequence_sql};
+use super::server_settings::ServerSetting;
+use super::tablespaces::{TableStorage, TablespaceInfo};
+use super::tagging::{job_tag, query_tag, tag_sql};
+use super::triggers::set_trigger_enabled_sql;
 use super::types::{
-    DatabaseInfo, DatabaseSchema, ErrorResult, QueryExecutionResult, TableInfo,
+    ColumnDetail, DatabaseInfo, DatabaseSchema, ErrorResult, ForeignKeyInfo,
+    QueryExecutionResult, ReferencingKey, RoutineInfo, TableDetails, TableInfo, TriggerInfo,
 };
+use super::user_types::UserTypeInfo;
+use crate::services::export::{CopyProgress, copy_to_csv, copy_to_csv_statement};
+use crate::services::sql::{BoundParams, ParamQuery, split_statements};
 use crate::services::ssh::SshTunnel;
-use crate::services::storage::{ConnectionInfo, ConnectionsRepository, DatabaseDriver};
+use crate::services::storage::{
+    ConnectionInfo, ConnectionsRepository, DatabaseDriver, SslMode, TableSizeSample,
+};
 
 /// A live connection pool. Variant matches the backing database engine.
+/// Cloning shares the underlying pool.
+#[derive(Clone)]
 pub(crate) enum Pool {
     Postgres(PgPool),
     MySql(MySqlPool),
@@ -28,6 +73,218 @@ impl Pool {
             Pool::MySql(p) => p.close().await,
         }
     }
+
+    fn driver(&self) -> DatabaseDriver {
+        match self {
+            Pool::Postgres(_) => DatabaseDriver::Postgres,
+            Pool::MySql(_) => DatabaseDriver::MySql,
+        }
+    }
+
+    async fn execute_maintenance(&self, sql: &str) -> Result<()> {
+        match self {
+            Pool::Postgres(p) => pg_backend::sequences::execute(p, sql).await,
+            Pool::MySql(p) => my_backend::sequences::execute(p, sql).await,
+        }
+    }
+
+    async fn compare_table_columns(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<(Vec<(String, String)>, Vec<String>)> {
+        match self {
+            Pool::Postgres(p) => {
+                pg_backend::compare::table_columns(p, table_schema, table_name).await
+            }
+            Pool::MySql(p) => my_backend::compare::table_columns(p, table_schema, table_name).await,
+        }
+    }
+
+    /// One keyset page of `(key, hash)` pairs for a table, after `after`
+    /// and up to `until` when given.
+    async fn row_hashes(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+        cols: &CompareColumns,
+        after: Option<&[String]>,
+        until: Option<&[String]>,
+    ) -> Result<Vec<RowHash>> {
+        match self {
+            Pool::Postgres(p) => {
+                pg_backend::compare::fetch_row_hashes(
+                    p,
+                    table_schema,
+                    table_name,
+                    cols,
+                    after,
+                    until,
+                    COMPARE_BATCH_SIZE,
+                )
+                .await
+            }
+            Pool::MySql(p) => {
+                my_backend::compare::fetch_row_hashes(
+                    p,
+                    table_schema,
+                    table_name,
+                    cols,
+                    after,
+                    until,
+                    COMPARE_BATCH_SIZE,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn fetch_sync_rows(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+        cols: &CompareColumns,
+        keys: &[Vec<String>],
+    ) -> Result<Vec<SyncRow>> {
+        let mut rows = Vec::new();
+        for chunk in keys.chunks(COMPARE_FETCH_CHUNK) {
+            let batch = match self {
+                Pool::Postgres(p) => {
+                    pg_backend::compare::fetch_rows(p, table_schema, table_name, cols, chunk)
+                        .await?
+                }
+                Pool::MySql(p) => {
+                    my_backend::compare::fetch_rows(p, table_schema, table_name, cols, chunk)
+                        .await?
+                }
+            };
+            rows.extend(batch);
+        }
+        Ok(rows)
+    }
+
+    /// Take one connection out of the pool, for statements that must share
+    /// a session.
+    async fn session(&self) -> Result<Session> {
+        Ok(match self {
+            Pool::Postgres(p) => Session::Postgres(p.acquire().await?),
+            Pool::MySql(p) => Session::MySql(p.acquire().await?),
+        })
+    }
+}
+
+/// A connection held out of a [`Pool`], so that `SET`, temporary tables
+/// and open transactions carry from one statement to the next. Goes back
+/// to the pool when dropped.
+pub(crate) enum Session {
+    Postgres(PoolConnection<Postgres>),
+    MySql(PoolConnection<MySql>),
+}
+
+impl Session {
+    fn driver(&self) -> DatabaseDriver {
+        match self {
+            Session::Postgres(_) => DatabaseDriver::Postgres,
+            Session::MySql(_) => DatabaseDriver::MySql,
+        }
+    }
+
+    async fn execute(&mut self, sql: &str, max_rows: Option<usize>) -> QueryExecutionResult {
+        match self {
+            Session::Postgres(c) => pg_backend::query::execute(&mut **c, sql, max_rows).await,
+            Session::MySql(c) => my_backend::query::execute(&mut **c, sql, max_rows).await,
+        }
+    }
+
+    async fn execute_bound(
+        &mut self,
+        sql: &str,
+        params: &[Option<String>],
+    ) -> QueryExecutionResult {
+        match self {
+            Session::Postgres(c) => pg_backend::query::execute_bound(&mut **c, sql, params).await,
+            Session::MySql(c) => my_backend::query::execute_bound(&mut **c, sql, params).await,
+        }
+    }
+
+    /// Whether the session is inside a transaction block. Postgres tells by
+    /// its transaction having started before the current statement, and
+    /// refuses the query inside a failed one. MySQL has no way to ask, so
+    /// it is `None` there.
+    async fn transaction_open(&mut self) -> Option<bool> {
+        match self {
+            Session::Postgres(c) => {
+                match sqlx::query_scalar::<_, bool>(
+                    "SELECT transaction_timestamp() <> statement_timestamp()",
+                )
+                .fetch_one(&mut **c)
+                .await
+                {
+                    Ok(open) => Some(open),
+                    Err(e) => e
+                        .as_database_error()
+                        .and_then(|e| e.code())
+                        .is_some_and(|code| code == "25P02")
+                        .then_some(true),
+                }
+            }
+            Session::MySql(_) => None,
+        }
+    }
+
+    /// Undo what SQL hooks may have set on the session before its
+    /// connection goes back to the pool. `DISCARD ALL` clears settings,
+    /// prepared statements and temporary tables but refuses to run inside
+    /// a transaction the query left open, so the role is reset on its own
+    /// first. MySQL has no statement that resets a session, so its
+    /// connection is closed instead, as is one that cannot be reset.
+    async fn reset(self) {
+        match self {
+            Session::Postgres(mut c) => {
+                if let Err(e) = c.execute("RESET ROLE").await {
+                    tracing::warn!("Closing a connection whose role could not be reset: {}", e);
+                    let _ = c.close().await;
+                } else if c.execute("DISCARD ALL").await.is_ok() {
+                    // The server dropped the statements sqlx has prepared
+                    let _ = c.clear_cached_statements().await;
+                }
+            }
+            Session::MySql(c) => {
+                let _ = c.close().await;
+            }
+        }
+    }
+}
+
+/// What an editor job came to: the statements of a script with a result
+/// each, or a lone statement's result, and how its hooks went.
+pub struct JobRun {
+    /// Empty for a query with parameters, which runs as it is.
+    pub statements: Vec<String>,
+    pub results: Vec<QueryExecutionResult>,
+    pub notices: Vec<ServerNotice>,
+    pub before_error: Option<anyhow::Error>,
+    pub after_error: Option<anyhow::Error>,
+    /// Whether the session was left inside a transaction, as the server
+    /// reports it. `None` when that is not known.
+    pub transaction_open: Option<bool>,
+}
+
+impl JobRun {
+    fn failed(message: String) -> Self {
+        let error = ErrorResult {
+            message,
+            execution_time_ms: 0,
+        };
+        Self {
+            statements: vec![],
+            results: vec![QueryExecutionResult::Error(error)],
+            notices: vec![],
+            before_error: None,
+            after_error: None,
+            transaction_open: None,
+        }
+    }
 }
 
 /// Front-door for all database operations.
@@ -41,6 +298,17 @@ pub struct DatabaseManager {
     /// Held to keep the tunnel alive for the duration of the connection.
     /// Dropped on `disconnect()`.
     tunnel: Arc<RwLock<Option<SshTunnel>>>,
+    /// Comment prepended to user statements when the connection has
+    /// `tag_queries` enabled.
+    query_tag: Arc<RwLock<Option<String>>>,
+    /// Number of the window the connection belongs to, for the query tag.
+    tab: Arc<RwLock<Option<usize>>>,
+    /// Rows fetched for a SELECT without its own LIMIT; `None` fetches
+    /// them all.
+    max_rows: Arc<RwLock<Option<usize>>>,
+    /// Pools of running jobs, by job id. A job keeps the pool it started
+    /// on, so it carries on when the active connection changes.
+    jobs: Arc<RwLock<HashMap<Uuid, Pool>>>,
 }
 
 impl std::fmt::Debug for DatabaseManager {
@@ -54,6 +322,10 @@ impl DatabaseManager {
         Self {
             pool: Arc::new(RwLock::new(None)),
             tunnel: Arc::new(RwLock::new(None)),
+            query_tag: Arc::new(RwLock::new(None)),
+            tab: Arc::new(RwLock::new(None)),
+            max_rows: Arc::new(RwLock::new(Some(DEFAULT_MAX_ROWS))),
+            jobs: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -73,24 +345,21 @@ impl DatabaseManager {
             let mut guard = self.tunnel.write().await;
             *guard = tunnel;
         }
+        {
+            let mut guard = self.query_tag.write().await;
+            let tab = *self.tab.read().await;
+            *guard = info.tag_queries.then(|| query_tag(info, tab));
+        }
         Ok(())
     }
 
-    /// Test a connection without storing it. Tunnel (if any) is torn
-    /// down when this function returns.
-    pub async fn test_connection(info: &ConnectionInfo) -> Result<()> {
-        let (pool, _tunnel) = build_test_pool(info).await?;
-        match pool {
-            Pool::Postgres(p) => {
-                sqlx::query("SELECT 1").fetch_one(&p).await?;
-                p.close().await;
-            }
-            Pool::MySql(p) => {
-                sqlx::query("SELECT 1").fetch_one(&p).await?;
-                p.close().await;
-            }
-        }
-        Ok(())
+    /// Test a connection stage by stage without storing it. Tunnel (if
+    /// any) is torn down when this function returns.
+    pub async fn test_connection(info: &ConnectionInfo) -> ConnectionReport {
+        let mut report = ConnectionReport::default();
+        run_connection_test(info, &mut report).await;
+        report.skip_remaining();
+        report
     }
 
     pub async fn disconnect(&self) -> Result<()> {
@@ -102,21 +371,43 @@ impl DatabaseManager {
             let mut guard = self.tunnel.write().await;
             guard.take()
         };
+        self.query_tag.write().await.take();
         match pool {
             Some(p) => {
-                p.close().await;
+                // Closing waits for connections in use, so leave the pool to
+                // the running jobs; it closes when the last of them drops it.
+                if self.jobs.read().await.is_empty() {
+                    p.close().await;
+                }
                 Ok(())
             }
             None => Err(anyhow!("No active database connection to disconnect")),
         }
     }
 
-    pub async fn is_connected(&self) -> bool {
+    /// Round-trip time of a `SELECT 1`.
+    pub async fn ping(&self) -> Result<Duration> {
         let guard = self.pool.read().await;
+        let started = Instant::now();
         match guard.as_ref() {
-            Some(Pool::Postgres(p)) => sqlx::query("SELECT 1").fetch_one(p).await.is_ok(),
-            Some(Pool::MySql(p)) => sqlx::query("SELECT 1").fetch_one(p).await.is_ok(),
-            None => false,
+            Some(Pool::Postgres(p)) => {
+                sqlx::query("SELECT 1").fetch_one(p).await?;
+            }
+            Some(Pool::MySql(p)) => {
+                sqlx::query("SELECT 1").fetch_one(p).await?;
+            }
+            None => return Err(anyhow!("Database not connected")),
+        }
+        Ok(started.elapsed())
+    }
+
+    /// Whether the SSH tunnel is up. Sends a keepalive that blocks until
+    /// the server answers, so run it off the foreground thread.
+    pub async fn tunnel_status(&self) -> TunnelStatus {
+        match self.tunnel.read().await.as_ref() {
+            None => TunnelStatus::Direct,
+            Some(tunnel) if tunnel.is_alive() => TunnelStatus::Up,
+            Some(_) => TunnelStatus::Down,
         }
     }
 
@@ -124,11 +415,59 @@ impl DatabaseManager {
     // Driver-dispatched API
     // ====================================================================
 
+    /// `sql` with the connection's source tag prepended, if tagging is on.
+    async fn tagged(&self, sql: &str) -> String {
+        tag_sql(self.query_tag.read().await.as_deref(), sql)
+    }
+
+    /// Number the window this manager connects for, so its query tag names
+    /// it. Applies from the next connect.
+    pub async fn set_tab(&self, tab: usize) {
+        *self.tab.write().await = Some(tab);
+    }
+
+    /// Limit the rows fetched for a SELECT without its own LIMIT; `None`
+    /// fetches them all.
+    pub async fn set_max_rows(&self, max_rows: Option<usize>) {
+        *self.max_rows.write().await = max_rows;
+    }
+
+    pub async fn max_rows(&self) -> Option<usize> {
+        *self.max_rows.read().await
+    }
+
+    /// Estimated rows `sql` returns, from the server's query plan. `None`
+    /// when the plan gives no estimate.
+    pub async fn estimate_rows(&self, sql: &str) -> Result<Option<u64>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::query::estimate_rows(p, sql).await,
+            Some(Pool::MySql(p)) => my_backend::query::estimate_rows(p, sql).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// What the server's query plan expects of `sql`: its cost on Postgres
+    /// and the rows it returns or reads.
+    pub async fn estimate_plan(&self, sql: &str) -> Result<PlanEstimate> {
+        let pool = self.pool.read().await.clone();
+        match pool {
+            Some(Pool::Postgres(p)) => pg_backend::query::estimate_plan(&p, sql).await,
+            Some(Pool::MySql(p)) => Ok(PlanEstimate {
+                cost: None,
+                rows: my_backend::query::estimate_rows(&p, sql).await?,
+            }),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
     pub async fn execute_query_enhanced(&self, sql: &str) -> QueryExecutionResult {
+        let sql = self.tagged(sql).await;
+        let max_rows = self.max_rows().await;
         let guard = self.pool.read().await;
         match guard.as_ref() {
-            Some(Pool::Postgres(p)) => pg_backend::query::execute(p, sql).await,
-            Some(Pool::MySql(p)) => my_backend::query::execute(p, sql).await,
+            Some(Pool::Postgres(p)) => pg_backend::query::execute(p, &sql, max_rows).await,
+            Some(Pool::MySql(p)) => my_backend::query::execute(p, &sql, max_rows).await,
             None => QueryExecutionResult::Error(ErrorResult {
                 message: "Database not connected".to_string(),
                 execution_time_ms: 0,
@@ -145,6 +484,160 @@ impl DatabaseManager {
         }
     }
 
+    /// Current size of each table, for tracking growth.
+    pub async fn get_table_sizes(&self) -> Result<Vec<TableSizeSample>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_table_sizes(p).await,
+            Some(Pool::MySql(p)) => my_backend::schema::get_table_sizes(p).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// One page of the table listing, for loading huge catalogs
+    /// incrementally. Pass the last table of the previous page as `after`;
+    /// a page shorter than `limit` is the last one.
+    pub async fn get_tables_page(
+        &self,
+        after: Option<&TableInfo>,
+        limit: usize,
+    ) -> Result<Vec<TableInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_tables_page(p, after, limit).await,
+            Some(Pool::MySql(p)) => my_backend::schema::get_tables_page(p, after, limit).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    pub async fn get_routines(&self) -> Result<Vec<RoutineInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_routines(p).await,
+            Some(Pool::MySql(p)) => my_backend::schema::get_routines(p).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Enums, composite types and domains. Empty on MySQL, whose enums are
+    /// declared inline on each column.
+    pub async fn get_user_types(&self) -> Result<Vec<UserTypeInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_user_types(p).await,
+            Some(Pool::MySql(_)) => Ok(vec![]),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Bodies of views and routines, for searching inside them.
+    pub async fn get_object_sources(&self) -> Result<Vec<ObjectSource>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_object_sources(p).await,
+            Some(Pool::MySql(p)) => my_backend::schema::get_object_sources(p).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    pub async fn get_routine_definition(&self, routine: &RoutineInfo) -> Result<String> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_routine_definition(p, routine).await,
+            Some(Pool::MySql(p)) => my_backend::schema::get_routine_definition(p, routine).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// `CREATE` statement of a view or materialized view.
+    pub async fn get_view_definition(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<String> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::schema::get_view_definition(p, table_schema, table_name).await
+            }
+            Some(Pool::MySql(p)) => {
+                my_backend::schema::get_view_definition(p, table_schema, table_name).await
+            }
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Foreign server and inheritance details of a table.
+    pub async fn get_table_details(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<TableDetails> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::schema::get_table_details(p, table_schema, table_name).await
+            }
+            Some(Pool::MySql(_)) => Err(anyhow!("MySQL has no foreign or inherited tables")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    pub async fn refresh_materialized_view(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<()> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::schema::refresh_materialized_view(p, table_schema, table_name).await
+            }
+            Some(Pool::MySql(_)) => Err(anyhow!("MySQL does not support materialized views")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    pub async fn get_triggers(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<TriggerInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::schema::get_triggers(p, table_schema, table_name).await
+            }
+            Some(Pool::MySql(p)) => {
+                my_backend::schema::get_triggers(p, table_schema, table_name).await
+            }
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Enable or disable one trigger. Returns the statement that was run.
+    pub async fn set_trigger_enabled(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+        trigger_name: &str,
+        enabled: bool,
+    ) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        let sql = set_trigger_enabled_sql(
+            &pool.driver(),
+            table_schema,
+            table_name,
+            trigger_name,
+            enabled,
+        )?;
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
     pub async fn get_databases(&self) -> Result<Vec<DatabaseInfo>> {
         let guard = self.pool.read().await;
         match guard.as_ref() {
@@ -154,6 +647,25 @@ impl DatabaseManager {
         }
     }
 
+    pub async fn get_schemas(&self) -> Result<Vec<String>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_schemas(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("MySQL does not support search_path")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// The session's effective `search_path`.
+    pub async fn get_search_path(&self) -> Result<Vec<String>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::schema::get_search_path(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("MySQL does not support search_path")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
     pub async fn get_table_columns(
         &self,
         table_name: &str,
@@ -171,6 +683,24 @@ impl DatabaseManager {
         }
     }
 
+    /// Columns of one table, for the structure editor.
+    pub async fn get_column_details(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<ColumnDetail>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::schema::fetch_table_columns(table_name, table_schema, p).await
+            }
+            Some(Pool::MySql(p)) => {
+                my_backend::schema::fetch_table_columns(table_name, table_schema, p).await
+            }
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
     pub async fn get_schema(&self, specific_tables: Option<Vec<String>>) -> Result<DatabaseSchema> {
         let guard = self.pool.read().await;
         match guard.as_ref() {
@@ -180,53 +710,838 @@ impl DatabaseManager {
         }
     }
 
-    /// Streaming row export. Currently Postgres-only; the MySQL export
-    /// path falls back to the in-memory `QueryResult` exporter.
-    #[allow(dead_code)]
-    pub async fn stream_query<'a>(
-        &'a self,
-        sql: &'a str,
-    ) -> Result<BoxStream<'a, Result<PgRow, sqlx::Error>>, String> {
+    /// Column details (types, nullability, defaults, enum labels, primary
+    /// key membership) used by the row editor.
+    pub async fn get_table_edit_info(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<TableEditInfo> {
         let guard = self.pool.read().await;
         match guard.as_ref() {
             Some(Pool::Postgres(p)) => {
-                let pool = p.clone();
-                let stream = sqlx::query(sql).fetch(&pool);
-                Ok(stream.boxed())
+                pg_backend::schema::get_table_edit_info(p, table_schema, table_name).await
             }
-            Some(Pool::MySql(_)) => {
-                Err("Streaming export is not yet implemented for MySQL".to_string())
+            Some(Pool::MySql(p)) => {
+                my_backend::schema::get_table_edit_info(p, table_schema, table_name).await
             }
-            None => Err("Database not connected".to_string()),
+            None => Err(anyhow!("Database not connected")),
         }
     }
-}
-
-// ============================================================================
-// Pool construction
-// ============================================================================
-
-/// Build the live pool used by [`DatabaseManager::connect`].
-async fn build_pool(info: &ConnectionInfo) -> Result<(Pool, Option<SshTunnel>)> {
-    let (host, port, tunnel) = open_tunnel_if_needed(info)?;
 
-    let pool = match info.driver {
+    /// Foreign keys of a table, one entry per column.
+    pub async fn get_foreign_keys(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::schema::get_foreign_keys(p, table_schema, table_name).await
+            }
+            Some(Pool::MySql(p)) => {
+                my_backend::schema::get_foreign_keys(p, table_schema, table_name).await
+            }
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Foreign key columns of other tables that point at a table.
+    pub async fn get_referencing_keys(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<ReferencingKey>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::schema::get_referencing_keys(p, table_schema, table_name).await
+            }
+            Some(Pool::MySql(p)) => {
+                my_backend::schema::get_referencing_keys(p, table_schema, table_name).await
+            }
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Execute a generated statement with its bind parameters.
+    pub async fn execute_statement(&self, statement: &ParamStatement) -> QueryExecutionResult {
+        let sql = self.tagged(&statement.sql).await;
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::query::execute_params(p, &sql, &statement.params).await
+            }
+            Some(Pool::MySql(p)) => {
+                my_backend::query::execute_params(p, &sql, &statement.params).await
+            }
+            None => QueryExecutionResult::Error(ErrorResult {
+                message: "Database not connected".to_string(),
+                execution_time_ms: 0,
+            }),
+        }
+    }
+
+    /// Type hints for the parameters of `query`, one per label. MySQL
+    /// does not report parameter types, so there they are all `None`.
+    pub async fn describe_params(&self, query: &ParamQuery) -> Result<Vec<Option<String>>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::query::describe_params(p, &query.sql).await,
+            Some(Pool::MySql(_)) => Ok(vec![None; query.labels.len()]),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Run an editor query as the job `job_id`, with its parameter values
+    /// bound when given and the connection's execution hooks around it.
+    /// A query without parameters is split into statements, which run one
+    /// after another and stop at the first that fails.
+    ///
+    /// Hooks, query and statements all run on one connection, so a `SET`
+    /// in the before hook or early in a script holds for the rest. When an
+    /// SQL hook ran, the session is reset before the connection goes back
+    /// to the pool.
+    ///
+    /// The job holds on to the current pool rather than the connection
+    /// lock, so connecting elsewhere does not wait for it, and its
+    /// statements carry [`job_tag`] so [`cancel_job`](Self::cancel_job)
+    /// can find them on the server.
+    pub async fn run_job(
+        &self,
+        job_id: Uuid,
+        sql: &str,
+        params: Option<&BoundParams>,
+        hooks: Option<(&ExecutionHooks, &HookContext)>,
+    ) -> JobRun {
+        let held = match self.pool.read().await.clone() {
+            Some(pool) => pool.session().await.map(|session| (pool, session)),
+            None => Err(anyhow!("Database not connected")),
+        };
+        let (pool, mut session) = match held {
+            Ok(held) => held,
+            Err(e) => return JobRun::failed(e.to_string()),
+        };
+        let (hooks, context) = hooks.unzip();
+        let hooks = hooks.cloned().unwrap_or_default();
+
+        let before_error = match context.filter(|_| hooks.before.is_set()) {
+            Some(context) => {
+                let context = HookContext {
+                    stage: HookStage::Before,
+                    outcome: None,
+                    ..context.clone()
+                };
+                self.run_hook(&mut session, &hooks.before, &context)
+                    .await
+                    .err()
+            }
+            None => None,
+        };
+
+        let statements = match params {
+            None if before_error.is_none() => split_statements(&pool.driver(), sql),
+            _ => vec![],
+        };
+        let (results, notices) = if let Some(e) = &before_error {
+            let error = ErrorResult {
+                message: format!("Before-query hook failed: {}", e),
+                execution_time_ms: 0,
+            };
+            (vec![QueryExecutionResult::Error(error)], vec![])
+        } else if statements.len() > 1 {
+            self.run_statements(job_id, &pool, &mut session, &statements)
+                .await
+        } else {
+            // A lone statement runs without psql meta-commands around it
+            let sql = statements.first().map_or(sql, String::as_str);
+            let (result, notices) = self
+                .run_statement(job_id, &pool, &mut session, sql, params)
+                .await;
+            (vec![result], notices)
+        };
+
+        let after_error = match (context, results.last()) {
+            (Some(context), Some(result)) if hooks.after.is_set() && before_error.is_none() => {
+                let context = HookContext {
+                    stage: HookStage::After,
+                    outcome: Some(HookOutcome::from_result(result)),
+                    ..context.clone()
+                };
+                self.run_hook(&mut session, &hooks.after, &context)
+                    .await
+                    .err()
+            }
+            _ => None,
+        };
+
+        let transaction_open = session.transaction_open().await;
+        let hooked = [&hooks.before, &hooks.after]
+            .iter()
+            .any(|hook| hook.is_set() && hook.kind == HookKind::Sql);
+        if hooked {
+            session.reset().await;
+        }
+
+        JobRun {
+            statements,
+            results,
+            notices,
+            before_error,
+            after_error,
+            transaction_open,
+        }
+    }
+
+    /// Run one statement of a job on its session, collecting the notices
+    /// the server sent while it ran.
+    async fn run_statement(
+        &self,
+        job_id: Uuid,
+        pool: &Pool,
+        session: &mut Session,
+        sql: &str,
+        params: Option<&BoundParams>,
+    ) -> (QueryExecutionResult, Vec<ServerNotice>) {
+        let (sql, binds) = match params {
+            Some(bound) => (
+                bound.query.typed_sql(&bound.types),
+                Some(bound.query.bind_values(&bound.values)),
+            ),
+            None => (sql.to_string(), None),
+        };
+        let sql = tag_sql(Some(&job_tag(&job_id)), &self.tagged(&sql).await);
+        let max_rows = self.max_rows().await;
+
+        self.jobs.write().await.insert(job_id, pool.clone());
+        let output = capture_notices(async {
+            match &binds {
+                Some(b) => session.execute_bound(&sql, b).await,
+                None => session.execute(&sql, max_rows).await,
+            }
+        })
+        .await;
+        self.jobs.write().await.remove(&job_id);
+
+        output
+    }
+
+    /// Run the statements of a script on a job's session one after
+    /// another, stopping at the first one that fails.
+    async fn run_statements(
+        &self,
+        job_id: Uuid,
+        pool: &Pool,
+        session: &mut Session,
+        statements: &[String],
+    ) -> (Vec<QueryExecutionResult>, Vec<ServerNotice>) {
+        let tag = job_tag(&job_id);
+        let mut tagged = Vec::with_capacity(statements.len());
+        for statement in statements {
+            tagged.push(tag_sql(Some(&tag), &self.tagged(statement).await));
+        }
+        let max_rows = self.max_rows().await;
+
+        self.jobs.write().await.insert(job_id, pool.clone());
+        let output = capture_notices(async {
+            let mut results = Vec::with_capacity(tagged.len());
+            for sql in &tagged {
+                let result = session.execute(sql, max_rows).await;
+                let failed = matches!(result, QueryExecutionResult::Error(_));
+                results.push(result);
+                if failed {
+                    break;
+                }
+            }
+            results
+        })
+        .await;
+        self.jobs.write().await.remove(&job_id);
+
+        output
+    }
+
+    /// Run an execution hook on the session of the query it runs around.
+    /// SQL hooks run statement by statement, stopping at the first that
+    /// fails.
+    async fn run_hook(
+        &self,
+        session: &mut Session,
+        hook: &ExecutionHook,
+        context: &HookContext,
+    ) -> Result<()> {
+        match hook.kind {
+            HookKind::Shell => run_shell_hook(&hook.command, context).await,
+            HookKind::Sql => {
+                let max_rows = self.max_rows().await;
+                for statement in split_statements(&session.driver(), &hook.command) {
+                    let sql = self.tagged(&statement).await;
+                    if let QueryExecutionResult::Error(e) = session.execute(&sql, max_rows).await {
+                        return Err(anyhow!(e.message));
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Run `statements` in turn on a batch target through a pool of its
+    /// own, stopping at the first that fails. Fails only when the target
+    /// cannot be reached.
+    pub async fn run_on_target(
+        target: &ConnectionInfo,
+        statements: &[String],
+    ) -> Result<Vec<QueryExecutionResult>> {
+        let (pool, _tunnel) = build_test_pool(target).await?;
+        let tag = target.tag_queries.then(|| query_tag(target, None));
+        let mut results = vec![];
+        for statement in statements {
+            let sql = tag_sql(tag.as_deref(), statement);
+            let result = match &pool {
+                Pool::Postgres(p) => {
+                    pg_backend::query::execute(p, &sql, Some(DEFAULT_MAX_ROWS)).await
+                }
+                Pool::MySql(p) => {
+                    my_backend::query::execute(p, &sql, Some(DEFAULT_MAX_ROWS)).await
+                }
+            };
+            let failed = matches!(result, QueryExecutionResult::Error(_));
+            results.push(result);
+            if failed {
+                break;
+            }
+        }
+        pool.close().await;
+        Ok(results)
+    }
+
+    /// Ask the server to cancel the statement of a running job. Returns
+    /// `false` when the job has already finished.
+    pub async fn cancel_job(&self, job_id: Uuid) -> Result<bool> {
+        let Some(pool) = self.jobs.read().await.get(&job_id).cloned() else {
+            return Ok(false);
+        };
+        let tag = job_tag(&job_id);
+        match pool {
+            Pool::Postgres(p) => pg_backend::query::cancel_tagged(&p, &tag).await,
+            Pool::MySql(p) => my_backend::query::cancel_tagged(&p, &tag).await,
+        }
+    }
+
+    /// Extensions available on the server, marking the installed ones.
+    pub async fn list_extensions(&self) -> Result<Vec<ExtensionInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::extensions::list_extensions(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("MySQL does not support extensions")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Tables, views and functions owned by installed extensions. Empty on
+    /// MySQL, which has no extensions.
+    pub async fn list_extension_objects(&self) -> Result<Vec<ExtensionObject>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::extensions::list_extension_objects(p).await,
+            Some(Pool::MySql(_)) => Ok(vec![]),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Install, update or drop an extension. Returns the statement that
+    /// was run.
+    pub async fn run_extension_action(
+        &self,
+        extension: &ExtensionInfo,
+        action: ExtensionAction,
+    ) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        if pool.driver() != DatabaseDriver::Postgres {
+            return Err(anyhow!("MySQL does not support extensions"));
+        }
+        let sql = extension_sql(extension, action);
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
+    /// Sequences (AUTO_INCREMENT counters on MySQL) with their current
+    /// values and the maximum of the column they feed.
+    pub async fn list_sequences(&self) -> Result<Vec<SequenceInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::sequences::list_sequences(p).await,
+            Some(Pool::MySql(p)) => my_backend::sequences::list_sequences(p).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Restart a sequence at `value`, or at its start value. Returns the
+    /// statement that was run.
+    pub async fn restart_sequence(&self, seq: &SequenceInfo, value: Option<i64>) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        let sql = restart_sequence_sql(&pool.driver(), seq, value);
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
+    /// Move a sequence past the current maximum of its owning column.
+    /// Returns the statement that was run.
+    pub async fn sync_sequence(&self, seq: &SequenceInfo) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        let sql = sync_sequence_sql(&pool.driver(), seq)?;
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
+    /// Create a database. Returns the statement that was run.
+    pub async fn create_database(&self, database: &NewDatabase) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        let sql = create_database_sql(&pool.driver(), database)?;
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
+    /// Drop a database other than the connected one. Returns the statement
+    /// that was run.
+    pub async fn drop_database(&self, name: &str, force: bool) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        let sql = drop_database_sql(&pool.driver(), name, force)?;
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
+    /// Create a schema (Postgres). Returns the statement that was run.
+    pub async fn create_schema(&self, schema: &NewSchema) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        let sql = create_schema_sql(&pool.driver(), schema)?;
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
+    /// Drop a schema (Postgres). Returns the statement that was run.
+    pub async fn drop_schema(&self, name: &str, cascade: bool) -> Result<String> {
+        let guard = self.pool.read().await;
+        let pool = guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+        let sql = drop_schema_sql(&pool.driver(), name, cascade)?;
+        pool.execute_maintenance(&sql).await?;
+        Ok(sql)
+    }
+
+    /// Tablespaces with their size and the relations placed in them.
+    pub async fn get_tablespaces(&self) -> Result<Vec<TablespaceInfo>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::tablespaces::list_tablespaces(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("MySQL does not have tablespaces to browse")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// The server's configuration parameters (Postgres).
+    pub async fn get_server_settings(&self) -> Result<Vec<ServerSetting>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::server_settings::list_server_settings(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("Server configuration is only available on Postgres")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// A table's tablespace and storage parameters; `None` on MySQL.
+    pub async fn get_table_storage(
+        &self,
+        table_schema: &str,
+        table_name: &str,
+    ) -> Result<Option<TableStorage>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::tablespaces::table_storage(p, table_schema, table_name)
+                    .await
+                    .map(Some)
+            }
+            Some(Pool::MySql(_)) => Ok(None),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Roles with their login, validity, connection limit and memberships.
+    pub async fn get_roles(&self) -> Result<Vec<RoleDefinition>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::roles::list_roles(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("Role management is only available on Postgres")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Set a role's password, sent as a SCRAM verifier. Returns the
+    /// statement that was run.
+    pub async fn reset_role_password(&self, role: &str, password: &str) -> Result<String> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                let sql = reset_password_sql(role, password, &password_salt())?;
+                pg_backend::sequences::execute(p, &sql).await?;
+                Ok(sql)
+            }
+            Some(Pool::MySql(_)) => Err(anyhow!("Role management is only available on Postgres")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Migrations recorded in `table`, none when it does not exist yet.
+    pub async fn applied_migrations(&self, table: &TrackingTable) -> Result<Vec<AppliedMigration>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::migrations::applied_migrations(p, table).await,
+            Some(Pool::MySql(p)) => my_backend::migrations::applied_migrations(p, table).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Run a migration file and record it in `table`, creating the table
+    /// first if needed. Returns how long the migration took in
+    /// milliseconds.
+    pub async fn apply_migration(
+        &self,
+        table: &TrackingTable,
+        file: &MigrationFile,
+    ) -> Result<u128> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::migrations::apply_migration(p, table, file).await
+            }
+            Some(Pool::MySql(p)) => my_backend::migrations::apply_migration(p, table, file).await,
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Most expensive statements from `pg_stat_statements`, with index
+    /// suggestions for large tables that are mostly scanned sequentially.
+    pub async fn performance_report(&self) -> Result<PerformanceReport> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::performance::performance_report(p, TOP_STATEMENTS).await
+            }
+            Some(Pool::MySql(_)) => Err(anyhow!(
+                "The performance panel requires PostgreSQL with pg_stat_statements"
+            )),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Statements of the current database from `pg_stat_statements`, by
+    /// total execution time.
+    pub async fn statement_stats(&self) -> Result<Vec<StatementStats>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                pg_backend::performance::statement_stats(p, ALL_STATEMENTS).await
+            }
+            Some(Pool::MySql(_)) => Err(anyhow!(
+                "Statement statistics require PostgreSQL with pg_stat_statements"
+            )),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Reset `pg_stat_statements`, for every database on the server.
+    pub async fn reset_statement_stats(&self) -> Result<()> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::performance::reset_statement_stats(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!(
+                "Statement statistics require PostgreSQL with pg_stat_statements"
+            )),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Client sessions of the current database other than this one.
+    pub async fn session_activity(&self) -> Result<Vec<SessionActivity>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::activity::sessions(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("Session activity requires PostgreSQL")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Sessions of the current database involved in lock waits.
+    pub async fn lock_sessions(&self) -> Result<Vec<LockSession>> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => pg_backend::locks::lock_sessions(p).await,
+            Some(Pool::MySql(_)) => Err(anyhow!("The lock monitor requires PostgreSQL")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// Cancel the query of, or terminate, the session `pid`. Returns the
+    /// statement that was run.
+    pub async fn signal_backend(&self, pid: i32, signal: BackendSignal) -> Result<String> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                if !pg_backend::locks::signal_backend(p, pid, signal).await? {
+                    return Err(anyhow!("Session {} is gone or could not be signalled", pid));
+                }
+                Ok(signal.sql(pid))
+            }
+            Some(Pool::MySql(_)) => Err(anyhow!("The lock monitor requires PostgreSQL")),
+            None => Err(anyhow!("Database not connected")),
+        }
+    }
+
+    /// The driver of the active connection, if any.
+    pub async fn driver(&self) -> Option<DatabaseDriver> {
+        self.pool.read().await.as_ref().map(|p| p.driver())
+    }
+
+    /// Compare the rows of one table between the active connection (the
+    /// source) and `target`, matched by primary key. Both sides must use
+    /// the same driver and the table must have a primary key.
+    ///
+    /// When `with_script` is set, the report carries INSERT/UPDATE
+    /// statements that would bring the target in line with the source.
+    pub async fn compare_table_data(
+        &self,
+        target: &ConnectionInfo,
+        table_schema: &str,
+        table_name: &str,
+        with_script: bool,
+    ) -> Result<DataCompareReport> {
+        // A handle of its own, so the comparison does not hold the pool
+        // lock while it runs
+        let source = self
+            .pool
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow!("Database not connected"))?;
+
+        if source.driver() != target.driver {
+            return Err(anyhow!(
+                "Cannot compare {} data with {}",
+                source.driver().as_str(),
+                target.driver.as_str()
+            ));
+        }
+
+        let (target_pool, _tunnel) = build_test_pool(target).await?;
+        let result =
+            compare_pools(&source, &target_pool, table_schema, table_name, with_script).await;
+        target_pool.close().await;
+        result
+    }
+
+    /// Streaming row export. Currently Postgres-only; the MySQL export
+    /// path falls back to the in-memory `QueryResult` exporter.
+    #[allow(dead_code)]
+    pub async fn stream_query<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> Result<BoxStream<'a, Result<PgRow, sqlx::Error>>, String> {
+        let guard = self.pool.read().await;
+        match guard.as_ref() {
+            Some(Pool::Postgres(p)) => {
+                let pool = p.clone();
+                let stream = sqlx::query(sql).fetch(&pool);
+                Ok(stream.boxed())
+            }
+            Some(Pool::MySql(_)) => {
+                Err("Streaming export is not yet implemented for MySQL".to_string())
+            }
+            None => Err("Database not connected".to_string()),
+        }
+    }
+
+    /// Export the rows of `sql` to a CSV file with `COPY ... TO STDOUT`,
+    /// which skips decoding them. Postgres only. Setting `cancelled`
+    /// cancels the statement on the server, and `None` is returned.
+    pub async fn copy_to_csv(
+        &self,
+        sql: &str,
+        output_path: &Path,
+        cancelled: Arc<AtomicBool>,
+        progress: impl Fn(CopyProgress),
+    ) -> Result<Option<CopyProgress>> {
+        let pool = match self.pool.read().await.as_ref() {
+            Some(Pool::Postgres(p)) => p.clone(),
+            Some(Pool::MySql(_)) => return Err(anyhow!("MySQL does not support COPY")),
+            None => return Err(anyhow!("Database not connected")),
+        };
+        let statement = copy_to_csv_statement(sql)
+            .ok_or_else(|| anyhow!("Only a single SELECT can be exported with COPY"))?;
+        let tag = format!("/* pgui:copy:{} */", Uuid::new_v4());
+        let chunks = pool.copy_out_raw(&tag_sql(Some(&tag), &statement)).await?;
+
+        let finished = AtomicBool::new(false);
+        let copy = async {
+            let result = copy_to_csv(chunks, output_path, &cancelled, progress).await;
+            finished.store(true, Ordering::Relaxed);
+            result
+        };
+        // Cancel on the server even while it is still working out the
+        // first rows, when no chunk arrives to notice the flag. The flag is
+        // checked first: a copy that stopped because of it has finished
+        // too, while the server may still be running the query.
+        let watch = async {
+            loop {
+                if cancelled.load(Ordering::Relaxed) {
+                    if let Err(e) = pg_backend::query::cancel_tagged(&pool, &tag).await {
+                        tracing::warn!("Failed to cancel COPY export: {}", e);
+                    }
+                    break;
+                }
+                if finished.load(Ordering::Relaxed) {
+                    break;
+                }
+                smol::Timer::after(Duration::from_millis(100)).await;
+            }
+        };
+        let (result, ()) = futures::join!(copy, watch);
+        result
+    }
+}
+
+// ============================================================================
+// Data comparison
+// ============================================================================
+
+async fn compare_pools(
+    source: &Pool,
+    target: &Pool,
+    table_schema: &str,
+    table_name: &str,
+    with_script: bool,
+) -> Result<DataCompareReport> {
+    let (source_keys, source_columns) = source
+        .compare_table_columns(table_schema, table_name)
+        .await?;
+    let (target_keys, target_columns) = target
+        .compare_table_columns(table_schema, table_name)
+        .await
+        .map_err(|e| anyhow!("Target: {}", e))?;
+
+    if source_keys.is_empty() {
+        return Err(anyhow!(
+            "{}.{} has no primary key to match rows on",
+            table_schema,
+            table_name
+        ));
+    }
+    let source_key_names: Vec<&String> = source_keys.iter().map(|(name, _)| name).collect();
+    let target_key_names: Vec<&String> = target_keys.iter().map(|(name, _)| name).collect();
+    if source_key_names != target_key_names {
+        return Err(anyhow!(
+            "Primary keys differ between source and target for {}.{}",
+            table_schema,
+            table_name
+        ));
+    }
+
+    let cols = CompareColumns::from_sides(source_keys, &source_columns, &target_columns);
+    let mut report = DataCompareReport::new(table_schema, table_name, cols);
+
+    // Each page of the source is compared with the target rows in the same
+    // key range, so both tables are read once, in key order, and only a
+    // page of each is held at a time
+    let mut after: Option<Vec<String>> = None;
+    loop {
+        let source_page = source
+            .row_hashes(table_schema, table_name, &report.columns, after.as_deref(), None)
+            .await?;
+        let until = match source_page.last() {
+            Some(last) if source_page.len() == COMPARE_BATCH_SIZE => Some(last.key.clone()),
+            _ => None,
+        };
+
+        let mut page = PageDiff::new(&source_page);
+        let mut target_after = after.clone();
+        loop {
+            let batch = target
+                .row_hashes(
+                    table_schema,
+                    table_name,
+                    &report.columns,
+                    target_after.as_deref(),
+                    until.as_deref(),
+                )
+                .await?;
+            report.target_rows += batch.len();
+            report.record(page.target_rows(&batch));
+            if batch.len() < COMPARE_BATCH_SIZE {
+                break;
+            }
+            target_after = batch.last().map(|last| last.key.clone());
+        }
+        report.source_rows += source_page.len();
+        report.record(page.missing());
+        match until {
+            Some(until) => after = Some(until),
+            None => break,
+        }
+    }
+
+    if with_script && !report.is_identical() {
+        let keys: Vec<Vec<String>> = report
+            .diffs
+            .iter()
+            .filter(|d| d.kind != RowDiffKind::Extra)
+            .map(|d| d.key.clone())
+            .collect();
+        let rows = source
+            .fetch_sync_rows(table_schema, table_name, &report.columns, &keys)
+            .await?;
+        report.sync_script = Some(generate_sync_script(&source.driver(), &report, &rows));
+    }
+
+    Ok(report)
+}
+
+// ============================================================================
+// Pool construction
+// ============================================================================
+
+/// Build the live pool used by [`DatabaseManager::connect`].
+async fn build_pool(info: &ConnectionInfo) -> Result<(Pool, Option<SshTunnel>)> {
+    let (host, port, tunnel) = open_tunnel_if_needed(info)?;
+
+    let pool = match info.driver {
         DatabaseDriver::Postgres => {
             let opts = info.to_pg_connect_options_for(&host, port);
-            let pool = PgPoolOptions::new()
-                .max_connections(5)
-                .acquire_timeout(Duration::from_secs(10))
-                .connect_with(opts)
-                .await?;
+            let pool = pg_pool_options(info, 5).connect_with(opts).await?;
             Pool::Postgres(pool)
         }
         DatabaseDriver::MySql => {
             let opts = info.to_mysql_connect_options_for(&host, port);
-            let pool = MySqlPoolOptions::new()
-                .max_connections(5)
-                .acquire_timeout(Duration::from_secs(10))
-                .connect_with(opts)
-                .await?;
+            let pool = mysql_pool_options(info, 5).connect_with(opts).await?;
             Pool::MySql(pool)
         }
     };
@@ -234,27 +1549,200 @@ async fn build_pool(info: &ConnectionInfo) -> Result<(Pool, Option<SshTunnel>)>
     Ok((pool, tunnel))
 }
 
-/// Build a one-shot pool used by [`DatabaseManager::test_connection`].
+/// The stages of [`DatabaseManager::test_connection`], recorded in
+/// `report`. Returns `None` at the first failure.
+async fn run_connection_test(info: &ConnectionInfo, report: &mut ConnectionReport) -> Option<()> {
+    // Through SSH the database host is resolved and reached by the SSH
+    // server, so the network stages check the SSH host instead.
+    let (net_host, net_port) = match &info.ssh {
+        Some(cfg) => (cfg.host.clone(), cfg.port),
+        None => (info.hostname.clone(), info.port as u16),
+    };
+
+    let started = Instant::now();
+    let addr = match with_timeout(smol::net::resolve((net_host.as_str(), net_port))).await {
+        Ok(addrs) if !addrs.is_empty() => {
+            report.pass(
+                TestStage::Dns,
+                format!("{} resolved to {}", net_host, addrs[0].ip()),
+                started,
+            );
+            addrs[0]
+        }
+        Ok(_) => {
+            let detail = format!("{} has no addresses", net_host);
+            report.fail(TestStage::Dns, detail, Some(dns_hint()), started);
+            return None;
+        }
+        Err(e) => {
+            let detail = format!("Could not resolve {}: {}", net_host, e);
+            report.fail(TestStage::Dns, detail, Some(dns_hint()), started);
+            return None;
+        }
+    };
+
+    let started = Instant::now();
+    if let Err(e) = with_timeout(smol::net::TcpStream::connect(addr)).await {
+        let detail = format!("Could not reach {}:{}: {}", net_host, net_port, e);
+        let hint = tcp_hint(e.kind(), &net_host, net_port);
+        report.fail(TestStage::Tcp, detail, Some(hint), started);
+        return None;
+    }
+    report.pass(
+        TestStage::Tcp,
+        format!("Reached {}:{}", net_host, net_port),
+        started,
+    );
+
+    let (host, port, _tunnel) = match &info.ssh {
+        None => {
+            report.skip(TestStage::SshTunnel, "No SSH tunnel configured");
+            (info.hostname.clone(), info.port as u16, None)
+        }
+        Some(cfg) => {
+            let started = Instant::now();
+            let passphrase = ConnectionsRepository::get_ssh_key_passphrase(&info.id);
+            match SshTunnel::connect(cfg, info.hostname.clone(), info.port as u16, passphrase) {
+                Ok(tunnel) => {
+                    report.pass(
+                        TestStage::SshTunnel,
+                        format!(
+                            "Forwarding to {}:{} through {}",
+                            info.hostname, info.port, cfg.host
+                        ),
+                        started,
+                    );
+                    let port = tunnel.local_port();
+                    ("127.0.0.1".to_string(), port, Some(tunnel))
+                }
+                Err(e) => {
+                    let hint = ssh_hint(&e.to_string());
+                    report.fail(TestStage::SshTunnel, e.to_string(), Some(hint), started);
+                    return None;
+                }
+            }
+        }
+    };
+
+    if info.ssl_mode == SslMode::Disable {
+        report.skip(TestStage::Tls, "SSL mode is Disable");
+    } else {
+        let started = Instant::now();
+        match with_timeout(probe_tls(&info.driver, &host, port)).await {
+            Ok(offered) => {
+                let (status, detail, hint) = tls_outcome(&info.ssl_mode, offered);
+                if status == StageStatus::Failed {
+                    report.fail(TestStage::Tls, detail, hint, started);
+                    return None;
+                }
+                report.pass(TestStage::Tls, detail, started);
+            }
+            Err(e) => {
+                let detail = format!("No answer to the TLS request: {}", e);
+                let hint = format!(
+                    "Check that {}:{} is a {} server.",
+                    info.hostname,
+                    info.port,
+                    info.driver.as_str()
+                );
+                report.fail(TestStage::Tls, detail, Some(hint), started);
+                return None;
+            }
+        }
+    }
+
+    let started = Instant::now();
+    let pool = match info.driver {
+        DatabaseDriver::Postgres => pg_pool_options(info, 1)
+            .connect_with(info.to_pg_connect_options_for(&host, port))
+            .await
+            .map(Pool::Postgres),
+        DatabaseDriver::MySql => mysql_pool_options(info, 1)
+            .connect_with(info.to_mysql_connect_options_for(&host, port))
+            .await
+            .map(Pool::MySql),
+    };
+    let pool = match pool {
+        Ok(pool) => {
+            report.pass(
+                TestStage::Authentication,
+                format!("Logged in as {} to {}", info.username, info.database),
+                started,
+            );
+            pool
+        }
+        Err(e) => {
+            let code = match &e {
+                sqlx::Error::Database(db_err) => db_err.code().map(|code| code.to_string()),
+                _ => None,
+            };
+            let message = e.to_string();
+            let hint = login_hint(code.as_deref(), &message, &info.database);
+            report.fail(TestStage::Authentication, message, hint, started);
+            return None;
+        }
+    };
+
+    let started = Instant::now();
+    let version: std::result::Result<String, sqlx::Error> = match &pool {
+        Pool::Postgres(p) => sqlx::query_scalar("SELECT version()").fetch_one(p).await,
+        Pool::MySql(p) => sqlx::query_scalar("SELECT VERSION()").fetch_one(p).await,
+    };
+    match pool {
+        Pool::Postgres(p) => p.close().await,
+        Pool::MySql(p) => p.close().await,
+    }
+    match version {
+        Ok(version) => report.pass(TestStage::Query, version, started),
+        Err(e) => {
+            report.fail(TestStage::Query, e.to_string(), None, started);
+            return None;
+        }
+    }
+    Some(())
+}
+
+/// Whether the server at `host:port` offers TLS, asked the way its
+/// driver would before logging in.
+async fn probe_tls(driver: &DatabaseDriver, host: &str, port: u16) -> std::io::Result<bool> {
+    use smol::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+    let mut stream = smol::net::TcpStream::connect((host, port)).await?;
+    let offered = match driver {
+        DatabaseDriver::Postgres => {
+            stream.write_all(&PG_SSL_REQUEST).await?;
+            let mut reply = [0u8; 1];
+            stream.read_exact(&mut reply).await?;
+            pg_accepts_tls(reply[0])
+        }
+        DatabaseDriver::MySql => {
+            let mut packet = [0u8; 256];
+            let read = stream.read(&mut packet).await?;
+            mysql_offers_tls(&packet[..read])
+        }
+    };
+    offered.ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unexpected reply for a {} server", driver.as_str()),
+        )
+    })
+}
+
+/// Build a one-shot pool for the target side of
+/// [`DatabaseManager::compare_table_data`].
 async fn build_test_pool(info: &ConnectionInfo) -> Result<(Pool, Option<SshTunnel>)> {
     let (host, port, tunnel) = open_tunnel_if_needed(info)?;
 
     let pool = match info.driver {
         DatabaseDriver::Postgres => {
             let opts = info.to_pg_connect_options_for(&host, port);
-            let pool = PgPoolOptions::new()
-                .max_connections(1)
-                .acquire_timeout(Duration::from_secs(10))
-                .connect_with(opts)
-                .await?;
+            let pool = pg_pool_options(info, 1).connect_with(opts).await?;
             Pool::Postgres(pool)
         }
         DatabaseDriver::MySql => {
             let opts = info.to_mysql_connect_options_for(&host, port);
-            let pool = MySqlPoolOptions::new()
-                .max_connections(1)
-                .acquire_timeout(Duration::from_secs(10))
-                .connect_with(opts)
-                .await?;
+            let pool = mysql_pool_options(info, 1).connect_with(opts).await?;
             Pool::MySql(pool)
         }
     };
@@ -262,6 +1750,41 @@ async fn build_test_pool(info: &ConnectionInfo) -> Result<(Pool, Option<SshTunne
     Ok((pool, tunnel))
 }
 
+/// Pool options for `info`. Its session SQL runs on every new session, so
+/// settings like `search_path` hold on each pooled connection.
+fn pg_pool_options(info: &ConnectionInfo, max_connections: u32) -> PgPoolOptions {
+    let options = PgPoolOptions::new()
+        .max_connections(max_connections)
+        .acquire_timeout(Duration::from_secs(10));
+    match info.session_sql() {
+        Some(sql) => options.after_connect(move |conn, _meta| {
+            let sql = sql.clone();
+            Box::pin(async move {
+                sqlx::raw_sql(&sql).execute(conn).await?;
+                Ok(())
+            })
+        }),
+        None => options,
+    }
+}
+
+/// MySQL counterpart of [`pg_pool_options`].
+fn mysql_pool_options(info: &ConnectionInfo, max_connections: u32) -> MySqlPoolOptions {
+    let options = MySqlPoolOptions::new()
+        .max_connections(max_connections)
+        .acquire_timeout(Duration::from_secs(10));
+    match info.session_sql() {
+        Some(sql) => options.after_connect(move |conn, _meta| {
+            let sql = sql.clone();
+            Box::pin(async move {
+                sqlx::raw_sql(&sql).execute(conn).await?;
+                Ok(())
+            })
+        }),
+        None => options,
+    }
+}
+
 /// Returns `(host, port, tunnel)` for the actual TCP endpoint to connect
 /// to. When SSH is enabled this is `127.0.0.1:<random>` and `tunnel` is
 /// `Some(...)`; otherwise the original host/port.
@@ -270,14 +1793,26 @@ fn open_tunnel_if_needed(info: &ConnectionInfo) -> Result<(String, u16, Option<S
         None => Ok((info.hostname.clone(), info.port as u16, None)),
         Some(cfg) => {
             let passphrase = ConnectionsRepository::get_ssh_key_passphrase(&info.id);
-            let tunnel = SshTunnel::connect(
-                cfg,
-                info.hostname.clone(),
-                info.port as u16,
-                passphrase,
-            )?;
+            let tunnel =
+                SshTunnel::connect(cfg, info.hostname.clone(), info.port as u16, passphrase)?;
             let port = tunnel.local_port();
             Ok(("127.0.0.1".to_string(), port, Some(tunnel)))
         }
     }
 }
+
+/// SQLSTATEs a server answers a rejected login with: `28P01` is
+/// Postgres' invalid password, `28000` is MySQL's access denied (1045) and
+/// Postgres' invalid authorization.
+const AUTHENTICATION_SQLSTATES: [&str; 2] = ["28P01", "28000"];
+
+/// Whether `err`, from [`DatabaseManager::connect`], means the server
+/// rejected the credentials, so asking for another password may help.
+pub fn is_authentication_error(err: &anyhow::Error) -> bool {
+    let Some(sqlx::Error::Database(db_err)) = err.downcast_ref::<sqlx::Error>() else {
+        return false;
+    };
+    db_err
+        .code()
+        .is_some_and(|code| AUTHENTICATION_SQLSTATES.contains(&&*code))
+}
@@ -0,0 +1,416 @@
+//! Versioned `.sql` migrations from a local directory, applied in order and
+//! recorded in a tracking table.
+//!
+//! File names follow the common conventions, so directories written for
+//! other tools work as they are:
+//!
+//! - `20240101120000_create_users.sql` or `0001_create_users.up.sql`
+//!   (sqlx, golang-migrate); `.down.sql` files are ignored
+//! - `V1__create_users.sql` or `V1.2__add_index.sql` (Flyway, refinery);
+//!   undo (`U`) and repeatable (`R__`) files are ignored
+//!
+//! pgui keeps its own tracking table, named per connection, rather than
+//! reading another tool's, so the formats of those never matter.
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::services::storage::DatabaseDriver;
+
+/// Tracking table used when none is configured.
+pub const DEFAULT_TRACKING_TABLE: &str = "pgui_schema_migrations";
+
+/// Where the migrations of one connection live and where their state is
+/// recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MigrationSettings {
+    pub directory: Option<PathBuf>,
+    /// `table` or `schema.table`; empty for [`DEFAULT_TRACKING_TABLE`].
+    pub tracking_table: String,
+}
+
+impl MigrationSettings {
+    pub fn tracking_table(&self) -> Result<TrackingTable> {
+        let name = self.tracking_table.trim();
+        TrackingTable::parse(if name.is_empty() {
+            DEFAULT_TRACKING_TABLE
+        } else {
+            name
+        })
+    }
+}
+
+/// The table recording which migrations have been applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackingTable {
+    pub schema: Option<String>,
+    pub name: String,
+}
+
+impl TrackingTable {
+    /// `table` or `schema.table`, each a plain identifier.
+    pub fn parse(name: &str) -> Result<Self> {
+        let parts: Vec<&str> = name.trim().split('.').collect();
+        let valid = |part: &str| {
+            part.bytes()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == b'_')
+                && part.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_')
+        };
+        if parts.len() > 2 || !parts.iter().all(|part| valid(part)) {
+            return Err(anyhow!(
+                "\"{}\" is not a valid tracking table; use letters, digits and \
+                 underscores, as table or schema.table",
+                name.trim()
+            ));
+        }
+        Ok(match parts.as_slice() {
+            [schema, name] => TrackingTable {
+                schema: Some(schema.to_string()),
+                name: name.to_string(),
+            },
+            _ => TrackingTable {
+                schema: None,
+                name: parts[0].to_string(),
+            },
+        })
+    }
+
+    /// The table name quoted for `driver`.
+    pub fn quoted(&self, driver: &DatabaseDriver) -> String {
+        let quote = |ident: &str| match driver {
+            DatabaseDriver::Postgres => format!("\"{}\"", ident),
+            DatabaseDriver::MySql => format!("`{}`", ident),
+        };
+        match &self.schema {
+            Some(schema) => format!("{}.{}", quote(schema), quote(&self.name)),
+            None => quote(&self.name),
+        }
+    }
+
+    pub fn create_sql(&self, driver: &DatabaseDriver) -> String {
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n    \
+             version VARCHAR(255) PRIMARY KEY,\n    \
+             description VARCHAR(255) NOT NULL,\n    \
+             checksum VARCHAR(64) NOT NULL,\n    \
+             execution_ms BIGINT NOT NULL,\n    \
+             applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP\n)",
+            self.quoted(driver)
+        )
+    }
+
+    pub fn insert_sql(&self, driver: &DatabaseDriver) -> String {
+        let values = match driver {
+            DatabaseDriver::Postgres => "$1, $2, $3, $4",
+            DatabaseDriver::MySql => "?, ?, ?, ?",
+        };
+        format!(
+            "INSERT INTO {} (version, description, checksum, execution_ms) VALUES ({})",
+            self.quoted(driver),
+            values
+        )
+    }
+}
+
+impl std::fmt::Display for TrackingTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.schema {
+            Some(schema) => write!(f, "{}.{}", schema, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// A migration file of the directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationFile {
+    pub version: String,
+    pub description: String,
+    pub file_name: String,
+    pub sql: String,
+    pub checksum: String,
+}
+
+/// A row of the tracking table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedMigration {
+    pub version: String,
+    pub description: String,
+    pub checksum: String,
+    pub applied_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file has been edited since.
+    Changed,
+    /// Applied, but the file is gone.
+    Missing,
+}
+
+impl MigrationState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "Applied",
+            MigrationState::Pending => "Pending",
+            MigrationState::Changed => "Changed",
+            MigrationState::Missing => "Missing",
+        }
+    }
+}
+
+/// One version, from the directory, the tracking table or both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationEntry {
+    pub version: String,
+    pub description: String,
+    pub state: MigrationState,
+    pub file: Option<MigrationFile>,
+    pub applied_at: Option<String>,
+}
+
+/// The version and description of a migration file, or `None` for files
+/// that are not forward migrations.
+pub fn parse_migration_name(file_name: &str) -> Option<(String, String)> {
+    let stem = file_name.strip_suffix(".sql")?;
+    if stem.ends_with(".down") {
+        return None;
+    }
+    let stem = stem.strip_suffix(".up").unwrap_or(stem);
+
+    let (version, description) = match stem.strip_prefix(['V', 'v']) {
+        Some(rest) => rest.split_once("__")?,
+        None => {
+            let end = stem
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(stem.len());
+            let (version, rest) = stem.split_at(end);
+            (version, rest.trim_start_matches(['_', '-']))
+        }
+    };
+    let numeric = !version.is_empty()
+        && version
+            .split(['.', '_'])
+            .all(|part| !part.is_empty() && part.bytes().all(|c| c.is_ascii_digit()));
+    numeric.then(|| (version.to_string(), description.replace('_', " ")))
+}
+
+/// Order versions by their numeric parts, so `V2` comes before `V10`,
+/// `1.2` before `1.10`, and `02` is the same version as `2`.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<u128> {
+        v.split(['.', '_'])
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+/// A stable checksum of a migration's SQL, ignoring line endings.
+pub fn migration_checksum(sql: &str) -> String {
+    // FNV-1a, so the checksum stays the same across builds.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in sql.replace("\r\n", "\n").bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// The migrations of `directory`, in version order.
+pub fn load_migrations(directory: &Path) -> Result<Vec<MigrationFile>> {
+    let entries = std::fs::read_dir(directory)
+        .with_context(|| format!("Failed to read {}", directory.display()))?;
+
+    let mut files: Vec<MigrationFile> = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some((version, description)) = parse_migration_name(file_name) else {
+            continue;
+        };
+        let sql = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", file_name))?;
+        files.push(MigrationFile {
+            version,
+            description,
+            file_name: file_name.to_string(),
+            checksum: migration_checksum(&sql),
+            sql,
+        });
+    }
+
+    files.sort_by(|a, b| compare_versions(&a.version, &b.version));
+    if let Some(pair) = files
+        .windows(2)
+        .find(|pair| compare_versions(&pair[0].version, &pair[1].version) == Ordering::Equal)
+    {
+        return Err(anyhow!(
+            "{} and {} have the same version",
+            pair[0].file_name,
+            pair[1].file_name
+        ));
+    }
+    Ok(files)
+}
+
+/// Every version of the directory and the tracking table with its state,
+/// in version order.
+pub fn migration_plan(
+    files: &[MigrationFile],
+    applied: &[AppliedMigration],
+) -> Vec<MigrationEntry> {
+    let mut entries: Vec<MigrationEntry> = files
+        .iter()
+        .map(|file| {
+            let record = applied.iter().find(|a| a.version == file.version);
+            let state = match record {
+                Some(record) if record.checksum != file.checksum => MigrationState::Changed,
+                Some(_) => MigrationState::Applied,
+                None => MigrationState::Pending,
+            };
+            MigrationEntry {
+                version: file.version.clone(),
+                description: file.description.clone(),
+                state,
+                file: Some(file.clone()),
+                applied_at: record.and_then(|r| r.applied_at.clone()),
+            }
+        })
+        .collect();
+
+    for record in applied {
+        if !files.iter().any(|f| f.version == record.version) {
+            entries.push(MigrationEntry {
+                version: record.version.clone(),
+                description: record.description.clone(),
+                state: MigrationState::Missing,
+                file: None,
+                applied_at: record.applied_at.clone(),
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| compare_versions(&a.version, &b.version));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_migration_file_names() {
+        let parsed = |name: &str| parse_migration_name(name);
+        assert_eq!(
+            parsed("20240101120000_create_users.sql"),
+            Some(("20240101120000".to_string(), "create users".to_string()))
+        );
+        assert_eq!(
+            parsed("0002_add_email.up.sql"),
+            Some(("0002".to_string(), "add email".to_string()))
+        );
+        assert_eq!(
+            parsed("V1.2__add_index.sql"),
+            Some(("1.2".to_string(), "add index".to_string()))
+        );
+        assert_eq!(parsed("0002_add_email.down.sql"), None);
+        assert_eq!(parsed("U1__undo.sql"), None);
+        assert_eq!(parsed("R__views.sql"), None);
+        assert_eq!(parsed("README.md"), None);
+        assert_eq!(parsed("seed.sql"), None);
+
+        assert_eq!(compare_versions("2", "10"), Ordering::Less);
+        assert_eq!(compare_versions("1.10", "1.2"), Ordering::Greater);
+    }
+
+    #[test]
+    fn loads_a_directory_in_version_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, sql) in [
+            ("V10__later.sql", "SELECT 10;"),
+            ("V2__earlier.sql", "SELECT 2;"),
+            ("V2__earlier.down.sql", "SELECT -2;"),
+            ("notes.txt", "not a migration"),
+        ] {
+            std::fs::write(dir.path().join(name), sql).unwrap();
+        }
+        let files = load_migrations(dir.path()).unwrap();
+        let names: Vec<&str> = files.iter().map(|f| f.file_name.as_str()).collect();
+        assert_eq!(names, vec!["V2__earlier.sql", "V10__later.sql"]);
+
+        std::fs::write(dir.path().join("V02__clash.sql"), "SELECT 0;").unwrap();
+        let err = load_migrations(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("have the same version"), "{}", err);
+    }
+
+    #[test]
+    fn plans_against_the_tracking_table() {
+        let file = |version: &str, sql: &str| MigrationFile {
+            version: version.to_string(),
+            description: format!("step {}", version),
+            file_name: format!("V{}__step.sql", version),
+            sql: sql.to_string(),
+            checksum: migration_checksum(sql),
+        };
+        let applied = |version: &str, sql: &str| AppliedMigration {
+            version: version.to_string(),
+            description: format!("step {}", version),
+            checksum: migration_checksum(sql),
+            applied_at: Some("2024-01-01 12:00:00".to_string()),
+        };
+
+        let files = vec![
+            file("1", "CREATE TABLE a (id int);\n"),
+            file("2", "CREATE TABLE b (id int, name text);"),
+            file("4", "CREATE TABLE d (id int);"),
+        ];
+        let records = vec![
+            applied("1", "CREATE TABLE a (id int);\r\n"),
+            applied("2", "CREATE TABLE b (id int);"),
+            applied("3", "CREATE TABLE c (id int);"),
+        ];
+        let states: Vec<(String, MigrationState)> = migration_plan(&files, &records)
+            .into_iter()
+            .map(|e| (e.version, e.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("1".to_string(), MigrationState::Applied),
+                ("2".to_string(), MigrationState::Changed),
+                ("3".to_string(), MigrationState::Missing),
+                ("4".to_string(), MigrationState::Pending),
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_the_tracking_table() {
+        let table = TrackingTable::parse("ops.schema_migrations").unwrap();
+        assert_eq!(
+            table.quoted(&DatabaseDriver::Postgres),
+            "\"ops\".\"schema_migrations\""
+        );
+        assert_eq!(
+            table.insert_sql(&DatabaseDriver::MySql),
+            "INSERT INTO `ops`.`schema_migrations` \
+             (version, description, checksum, execution_ms) VALUES (?, ?, ?, ?)"
+        );
+        assert!(TrackingTable::parse("bad name").is_err());
+        assert!(TrackingTable::parse("a.b.c").is_err());
+        assert_eq!(
+            MigrationSettings::default().tracking_table().unwrap().name,
+            DEFAULT_TRACKING_TABLE
+        );
+    }
+}
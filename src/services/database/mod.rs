@@ -1,13 +1,111 @@
+mod activity;
+mod admin;
+mod batch;
+mod batch_run;
+mod binary;
+mod column_layout;
+mod column_stats;
+mod compare;
+mod connection_test;
+mod constraints;
+mod display;
+mod dml;
+mod extensions;
+mod fdw;
+mod filter;
+mod foreign_keys;
+mod health;
+mod hooks;
+#[cfg(all(test, feature = "integration-tests"))]
+mod integration_tests;
+mod locks;
 mod manager;
+mod migrations;
 mod mysql;
+mod notices;
+mod performance;
+mod pg_wire;
 mod postgres;
+mod roles;
+mod row_limit;
+mod schema_changes;
+mod search;
+mod sequences;
+mod server_settings;
+mod structure;
+mod tablespaces;
+mod tagging;
+mod triggers;
 mod types;
+mod user_types;
 
-pub use manager::DatabaseManager;
+pub use activity::{
+    IDLE_IN_TRANSACTION_OPTIONS, LONG_TRANSACTION_OPTIONS, SessionActivity, TransactionAlert,
+    TransactionAlertKind, TransactionAlertSettings, format_duration, transaction_alerts,
+};
+pub use admin::{NewDatabase, NewSchema};
+pub use batch::{comment_script, grant_script, table_privileges};
+pub use batch_run::{
+    BatchStatus, BatchTarget, BatchTargetResult, batch_report, batch_summary,
+};
+pub use binary::{ImageKind, decode_binary, format_byte_size, image_kind, is_binary_type};
+pub use column_layout::{ColumnLayout, FIT_SAMPLE_ROWS, fit_width};
+pub use column_stats::{ColumnStats, column_stats};
+#[allow(unused_imports)]
+pub use compare::{DataCompareReport, RowDiff, RowDiffKind};
+pub use connection_test::{ConnectionReport, StageResult, StageStatus, TestStage};
+pub use constraints::{
+    ConstraintSpec, ConstraintValidation, ReferentialAction, add_constraint_sql,
+};
+pub use display::{
+    CellDisplay, DateTimeDisplay, DateTimeFormat, NullDisplay, TimeZoneDisplay, cell_language,
+    display_value, mark_whitespace,
+};
+pub use dml::{
+    EditableColumn, ParamStatement, ScriptTemplate, TableEditInfo, build_insert, delete_row_sql,
+    duplicate_row_sql, script_template_sql, update_cell_sql,
+};
+pub use extensions::{ExtensionAction, ExtensionInfo, extension_sql};
+pub use fdw::{CrossDatabaseTool, ForeignServerSetup, available_tools, foreign_server_script};
+pub use filter::ResultFilter;
+pub use foreign_keys::{referenced_row_sql, referencing_rows_sql};
+pub use health::{ConnectionHealth, HEALTH_SAMPLES, HealthLevel, TunnelStatus};
+pub use hooks::{ExecutionHook, ExecutionHooks, HookContext, HookKind, HookOutcome, HookStage};
+pub use locks::{BackendSignal, BlockingNode, LockSession, blocking_tree};
+pub use manager::{DatabaseManager, JobRun, is_authentication_error};
+pub use migrations::{
+    DEFAULT_TRACKING_TABLE, MigrationEntry, MigrationFile, MigrationSettings, MigrationState,
+    TrackingTable, load_migrations, migration_plan,
+};
+pub use notices::{ServerNotice, notice_layer};
+pub use performance::{
+    IndexSuggestion, PerformanceReport, StatementSort, StatementStats, sort_statements,
+};
+pub use roles::{
+    RoleDefinition, alter_role_sql, create_role_sql, password_salt, reset_password_sql,
+};
+pub use row_limit::{
+    DEFAULT_MAX_ROWS, LARGE_RESULT_ROWS, PlanEstimate, format_row_count, has_row_limit,
+    is_row_query, with_limit,
+};
+pub use schema_changes::{
+    SchemaChange, SchemaChangeEntry, SchemaChangeKind, SchemaSnapshot, change_log, schema_changes,
+};
+pub use search::{ObjectHit, ObjectMatch, ObjectSource, search_objects};
+pub use sequences::{SequenceInfo, restart_sequence_sql};
+pub use server_settings::{ServerSetting, SettingsFilter, group_settings};
+pub use structure::{ColumnChange, ColumnDefinition, alter_table_sql};
+pub use tablespaces::{
+    TableStorage, TablespaceInfo, parse_storage_parameters, storage_parameters_sql,
+};
+pub use triggers::set_trigger_enabled_sql;
+pub use user_types::{EnumValuePosition, UserTypeInfo, UserTypeKind, add_enum_value_sql};
 
 #[allow(unused_imports)]
 pub use types::{
-    ColumnDetail, ConstraintInfo, DatabaseInfo, DatabaseSchema, ErrorResult, ForeignKeyInfo,
-    IndexInfo, QueryExecutionResult, QueryResult, ResultCell, ResultColumnMetadata, ResultRow,
-    TableInfo, TableSchema,
+    CellValue, ColumnDetail, ConstraintInfo, DatabaseInfo, DatabaseSchema, ErrorResult,
+    ForeignKeyInfo, ForeignTableInfo, IndexInfo, ModifiedResult, QueryExecutionResult,
+    QueryResult, ReferencingKey, ResultCell, ResultColumnMetadata, ResultRow, ResultRows,
+    RoutineInfo, RowSource, TableDetails, TableInfo, TableSchema, TriggerInfo,
+    with_active_schema,
 };
@@ -0,0 +1,153 @@
+//! MySQL side of the cross-connection data comparison.
+
+use anyhow::{Result, anyhow};
+use sqlx::{MySqlPool, Row};
+
+use crate::services::database::compare::{CompareColumns, RowHash, SyncRow};
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::MySql;
+
+/// Returns `(primary key columns with their types, all column names)`.
+pub async fn table_columns(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<(Vec<(String, String)>, Vec<String>)> {
+    let columns = super::schema::fetch_table_columns(table_name, table_schema, pool).await?;
+    if columns.is_empty() {
+        return Err(anyhow!("Table {}.{} not found", table_schema, table_name));
+    }
+    let primary_keys = super::schema::fetch_primary_keys(table_name, table_schema, pool).await?;
+
+    let keys = primary_keys
+        .into_iter()
+        .filter_map(|pk| {
+            columns
+                .iter()
+                .find(|c| c.column_name == pk)
+                .map(|c| (pk.clone(), c.data_type.clone()))
+        })
+        .collect();
+
+    Ok((keys, columns.into_iter().map(|c| c.column_name).collect()))
+}
+
+/// Fetch the next batch of `(key, MD5(row))` pairs ordered by primary key,
+/// starting strictly after `after` and ending at `until` when given.
+///
+/// `CONCAT_WS` skips NULL arguments, so each column contributes an
+/// `ISNULL()` flag alongside its value to keep `NULL` and `''` distinct.
+pub async fn fetch_row_hashes(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+    cols: &CompareColumns,
+    after: Option<&[String]>,
+    until: Option<&[String]>,
+    limit: usize,
+) -> Result<Vec<RowHash>> {
+    let keys = quoted(&cols.key_columns);
+    let key_count = cols.key_columns.len();
+
+    let hashed = quoted(&cols.columns)
+        .iter()
+        .map(|c| format!("ISNULL({c}), CAST({c} AS CHAR)"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut sql = format!(
+        "SELECT {}, MD5(CONCAT_WS('|', {})) AS row_hash FROM {}.{}",
+        keys.iter()
+            .map(|k| format!("CAST({} AS CHAR)", k))
+            .collect::<Vec<_>>()
+            .join(", "),
+        hashed,
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name),
+    );
+    let placeholders = vec!["?"; key_count].join(", ");
+    let mut bounds = Vec::new();
+    if after.is_some() {
+        bounds.push(format!("({}) > ({})", keys.join(", "), placeholders));
+    }
+    if until.is_some() {
+        bounds.push(format!("({}) <= ({})", keys.join(", "), placeholders));
+    }
+    if !bounds.is_empty() {
+        sql.push_str(&format!(" WHERE {}", bounds.join(" AND ")));
+    }
+    sql.push_str(&format!(" ORDER BY {} LIMIT {}", keys.join(", "), limit));
+
+    let mut query = sqlx::query(&sql);
+    for value in after.into_iter().chain(until).flatten() {
+        query = query.bind(value);
+    }
+
+    let rows = query.fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            let key = (0..key_count)
+                .map(|i| row.try_get::<String, _>(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(RowHash {
+                key,
+                hash: row.try_get("row_hash")?,
+            })
+        })
+        .collect()
+}
+
+/// Fetch full rows (as text) for the given primary keys.
+pub async fn fetch_rows(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+    cols: &CompareColumns,
+    keys: &[Vec<String>],
+) -> Result<Vec<SyncRow>> {
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let key_count = cols.key_columns.len();
+    let tuple = format!("({})", vec!["?"; key_count].join(", "));
+
+    let sql = format!(
+        "SELECT {} FROM {}.{} WHERE ({}) IN ({})",
+        quoted(&cols.columns)
+            .iter()
+            .map(|c| format!("CAST({} AS CHAR)", c))
+            .collect::<Vec<_>>()
+            .join(", "),
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name),
+        quoted(&cols.key_columns).join(", "),
+        vec![tuple; keys.len()].join(", "),
+    );
+
+    let mut query = sqlx::query(&sql);
+    for key in keys {
+        for value in key {
+            query = query.bind(value);
+        }
+    }
+
+    let rows = query.fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            let values = (0..cols.columns.len())
+                .map(|i| row.try_get::<Option<String>, _>(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            let key = values[..key_count]
+                .iter()
+                .map(|v| v.clone().unwrap_or_default())
+                .collect();
+            Ok(SyncRow { key, values })
+        })
+        .collect()
+}
+
+fn quoted(columns: &[String]) -> Vec<String> {
+    columns.iter().map(|c| DRIVER.quote_ident(c)).collect()
+}
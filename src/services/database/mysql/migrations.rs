@@ -0,0 +1,75 @@
+//! Applying migrations and reading the tracking table.
+//!
+//! MySQL commits DDL statements implicitly, so a failing migration may
+//! leave the statements before the failing one applied.
+
+use std::time::Instant;
+
+use anyhow::{Context as _, Result};
+use sqlx::{MySqlPool, Row};
+
+use crate::services::database::migrations::{AppliedMigration, MigrationFile, TrackingTable};
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::MySql;
+
+/// The recorded migrations, none when the tracking table does not exist
+/// yet.
+pub async fn applied_migrations(
+    pool: &MySqlPool,
+    table: &TrackingTable,
+) -> Result<Vec<AppliedMigration>> {
+    let exists: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM information_schema.TABLES
+         WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?",
+    )
+    .bind(table.schema.as_deref())
+    .bind(&table.name)
+    .fetch_one(pool)
+    .await?;
+    if exists == 0 {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        "SELECT version, description, checksum, CAST(applied_at AS CHAR) AS applied_at FROM {}",
+        table.quoted(&DRIVER)
+    );
+    let rows = sqlx::query(&sql).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| AppliedMigration {
+            version: row.get("version"),
+            description: row.get("description"),
+            checksum: row.get("checksum"),
+            applied_at: row.get("applied_at"),
+        })
+        .collect())
+}
+
+/// Run a migration and record it in one transaction. Returns how long it
+/// took in milliseconds.
+pub async fn apply_migration(
+    pool: &MySqlPool,
+    table: &TrackingTable,
+    file: &MigrationFile,
+) -> Result<u128> {
+    sqlx::query(&table.create_sql(&DRIVER))
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to create {}", table))?;
+
+    let start = Instant::now();
+    let mut tx = pool.begin().await?;
+    sqlx::raw_sql(&file.sql).execute(&mut *tx).await?;
+    let elapsed = start.elapsed().as_millis();
+    sqlx::query(&table.insert_sql(&DRIVER))
+        .bind(&file.version)
+        .bind(&file.description)
+        .bind(&file.checksum)
+        .bind(elapsed as i64)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(elapsed)
+}
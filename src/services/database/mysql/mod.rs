@@ -1,5 +1,8 @@
 //! MySQL backend implementation. Targets MySQL 8.4 LTS but is wire- and
 //! `information_schema`-compatible with the 8.0 series as well.
 
+pub mod compare;
+pub mod migrations;
 pub mod query;
 pub mod schema;
+pub mod sequences;
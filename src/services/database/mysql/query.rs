@@ -5,16 +5,24 @@
 //! and `ordinal`. `table_name` and `is_nullable` are left as `None` for
 //! ad-hoc queries; structural lookups should use `schema::get_schema`.
 
-use sqlx::mysql::{MySqlColumn, MySqlPool, MySqlRow};
+use sqlx::mysql::{MySqlColumn, MySqlConnection, MySqlPool, MySqlRow};
 use sqlx::query::Query;
-use sqlx::{Column, Execute as _, Row, TypeInfo, ValueRef};
+use sqlx::{Acquire, Column, Execute as _, MySql, Row, TypeInfo, ValueRef};
 
+use crate::services::database::row_limit::{is_row_query, limited_sql, truncate_rows};
+use crate::services::database::tagging::skip_leading_comments;
 use crate::services::database::types::{
-    ErrorResult, ModifiedResult, QueryExecutionResult, QueryResult, ResultCell,
-    ResultColumnMetadata, ResultRow,
+    CellValue, ErrorResult, ModifiedResult, QueryExecutionResult, QueryResult,
+    ResultColumnMetadata, ResultRows, RowSource,
 };
 
-pub async fn execute(pool: &MySqlPool, sql: &str) -> QueryExecutionResult {
+/// Run `sql`, fetching at most `max_rows` rows of a SELECT without its own
+/// LIMIT. `db` is the pool, or a connection held across statements so
+/// they share its session.
+pub async fn execute<'a, A>(db: A, sql: &str, max_rows: Option<usize>) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = MySql>,
+{
     let sql = sql.trim();
     if sql.is_empty() {
         return QueryExecutionResult::Error(ErrorResult {
@@ -23,16 +31,59 @@ pub async fn execute(pool: &MySqlPool, sql: &str) -> QueryExecutionResult {
         });
     }
 
+    let mut conn = match db.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return query_failed(e, std::time::Instant::now()),
+    };
     if is_select_query(sql) {
-        execute_select_query(sql, pool).await
+        execute_select_query(sql, &mut *conn, max_rows).await
     } else {
-        execute_modification_query(sql, pool).await
+        execute_modification_query(sql, &mut *conn).await
+    }
+}
+
+fn query_failed(e: sqlx::Error, start_time: std::time::Instant) -> QueryExecutionResult {
+    QueryExecutionResult::Error(ErrorResult {
+        message: format!("Query failed: {}", e),
+        execution_time_ms: start_time.elapsed().as_millis(),
+    })
+}
+
+async fn execute_modification_query(sql: &str, conn: &mut MySqlConnection) -> QueryExecutionResult {
+    let start_time = std::time::Instant::now();
+    match sqlx::query(sql).execute(conn).await {
+        Ok(result) => QueryExecutionResult::Modified(ModifiedResult {
+            rows_affected: result.rows_affected(),
+            execution_time_ms: start_time.elapsed().as_millis(),
+        }),
+        Err(e) => QueryExecutionResult::Error(ErrorResult {
+            message: format!("Query failed: {}", e),
+            execution_time_ms: start_time.elapsed().as_millis(),
+        }),
     }
 }
 
-async fn execute_modification_query(sql: &str, pool: &MySqlPool) -> QueryExecutionResult {
+/// Execute a data-modifying statement with text bind parameters
+/// (`None` binds NULL).
+pub async fn execute_params<'a, A>(
+    db: A,
+    sql: &str,
+    params: &[Option<String>],
+) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = MySql>,
+{
     let start_time = std::time::Instant::now();
-    match sqlx::query(sql).execute(pool).await {
+    let mut conn = match db.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return query_failed(e, start_time),
+    };
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = query.bind(param.clone());
+    }
+
+    match query.execute(&mut *conn).await {
         Ok(result) => QueryExecutionResult::Modified(ModifiedResult {
             rows_affected: result.rows_affected(),
             execution_time_ms: start_time.elapsed().as_millis(),
@@ -44,14 +95,85 @@ async fn execute_modification_query(sql: &str, pool: &MySqlPool) -> QueryExecuti
     }
 }
 
-pub(crate) async fn execute_internal(
+/// Execute a query with text bind parameters, fetching the rows of a
+/// SELECT. Unlike `execute`, no row limit is added.
+pub async fn execute_bound<'a, A>(
+    db: A,
+    sql: &str,
+    params: &[Option<String>],
+) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = MySql>,
+{
+    if !is_select_query(sql) {
+        return execute_params(db, sql, params).await;
+    }
+
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = query.bind(param.clone());
+    }
+    execute_internal(query, db).await
+}
+
+/// Kill the statements whose text contains `tag`, other than this one.
+/// Returns whether any was found.
+pub async fn cancel_tagged(pool: &MySqlPool, tag: &str) -> anyhow::Result<bool> {
+    let ids: Vec<u64> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM information_schema.processlist
+        WHERE id <> CONNECTION_ID()
+          AND LOCATE(?, info) > 0
+        "#,
+    )
+    .bind(tag)
+    .fetch_all(pool)
+    .await?;
+
+    for id in &ids {
+        sqlx::query(&format!("KILL QUERY {}", id))
+            .execute(pool)
+            .await?;
+    }
+    Ok(!ids.is_empty())
+}
+
+/// The optimizer's estimate of the rows `sql` reads, from `EXPLAIN`: the
+/// most rows examined for any one table. Nothing is run.
+pub async fn estimate_rows(pool: &MySqlPool, sql: &str) -> anyhow::Result<Option<u64>> {
+    let plan = sqlx::query(&format!("EXPLAIN {}", sql))
+        .fetch_all(pool)
+        .await?;
+    Ok(plan
+        .iter()
+        .filter_map(|row| {
+            row.try_get::<Option<u64>, _>("rows")
+                .or_else(|_| {
+                    row.try_get::<Option<i64>, _>("rows")
+                        .map(|rows| rows.map(|r| r.max(0) as u64))
+                })
+                .ok()
+                .flatten()
+        })
+        .max())
+}
+
+pub(crate) async fn execute_internal<'a, A>(
     query: Query<'_, sqlx::MySql, sqlx::mysql::MySqlArguments>,
-    pool: &MySqlPool,
-) -> QueryExecutionResult {
+    db: A,
+) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = MySql>,
+{
     let start_time = std::time::Instant::now();
     let original_query = query.sql().to_string();
+    let mut conn = match db.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return query_failed(e, start_time),
+    };
 
-    match query.fetch_all(pool).await {
+    match query.fetch_all(&mut *conn).await {
         Ok(rows) => {
             let execution_time = start_time.elapsed().as_millis();
 
@@ -59,21 +181,23 @@ pub(crate) async fn execute_internal(
                 return QueryExecutionResult::Select(QueryResult {
                     original_query,
                     columns: vec![],
-                    rows: vec![],
+                    rows: ResultRows::default(),
                     row_count: 0,
                     execution_time_ms: execution_time,
+                    truncated_at: None,
                 });
             }
 
             let columns = build_column_metadata(&rows[0]);
-            let result_rows = convert_rows(&rows);
+            let row_count = rows.len();
 
             QueryExecutionResult::Select(QueryResult {
                 original_query,
                 columns,
-                rows: result_rows,
-                row_count: rows.len(),
+                rows: ResultRows::new(MySqlRows(rows)),
+                row_count,
                 execution_time_ms: execution_time,
+                truncated_at: None,
             })
         }
         Err(e) => QueryExecutionResult::Error(ErrorResult {
@@ -83,39 +207,44 @@ pub(crate) async fn execute_internal(
     }
 }
 
-async fn execute_select_query(sql: &str, pool: &MySqlPool) -> QueryExecutionResult {
+async fn execute_select_query(
+    sql: &str,
+    conn: &mut MySqlConnection,
+    max_rows: Option<usize>,
+) -> QueryExecutionResult {
     let start_time = std::time::Instant::now();
     let original_query = sql.to_string();
 
-    let limited_sql = if !sql.to_lowercase().contains(" limit ") {
-        format!("{} LIMIT {}", sql.trim_end_matches(';'), 1_000)
-    } else {
-        sql.to_string()
-    };
+    // SHOW, DESCRIBE and EXPLAIN don't take a LIMIT.
+    let max_rows = max_rows.filter(|_| is_row_query(sql));
+    let (limited_sql, max_rows) = limited_sql(sql, max_rows);
 
-    match sqlx::query(limited_sql.as_ref()).fetch_all(pool).await {
-        Ok(rows) => {
+    match sqlx::query(limited_sql.as_ref()).fetch_all(conn).await {
+        Ok(mut rows) => {
             let execution_time = start_time.elapsed().as_millis();
+            let truncated_at = truncate_rows(&mut rows, max_rows);
 
             if rows.is_empty() {
                 return QueryExecutionResult::Select(QueryResult {
                     original_query,
                     columns: vec![],
-                    rows: vec![],
+                    rows: ResultRows::default(),
                     row_count: 0,
                     execution_time_ms: execution_time,
+                    truncated_at: None,
                 });
             }
 
             let columns = build_column_metadata(&rows[0]);
-            let result_rows = convert_rows(&rows);
+            let row_count = rows.len();
 
             QueryExecutionResult::Select(QueryResult {
                 original_query,
                 columns,
-                rows: result_rows,
-                row_count: rows.len(),
+                rows: ResultRows::new(MySqlRows(rows)),
+                row_count,
                 execution_time_ms: execution_time,
+                truncated_at,
             })
         }
         Err(e) => QueryExecutionResult::Error(ErrorResult {
@@ -126,8 +255,7 @@ async fn execute_select_query(sql: &str, pool: &MySqlPool) -> QueryExecutionResu
 }
 
 fn is_select_query(sql: &str) -> bool {
-    let lower = sql.to_lowercase();
-    let trimmed = lower.trim_start();
+    let trimmed = skip_leading_comments(sql).to_lowercase();
     trimmed.starts_with("select")
         || trimmed.starts_with("with")
         || trimmed.starts_with("show")
@@ -147,125 +275,115 @@ fn build_column_metadata(first_row: &MySqlRow) -> Vec<ResultColumnMetadata> {
             ordinal,
             table_name: None,
             is_nullable: None,
+            source_column: None,
         })
         .collect()
 }
 
-fn convert_rows(rows: &[MySqlRow]) -> Vec<ResultRow> {
-    rows.iter().map(convert_row).collect()
-}
+/// Rows as fetched, decoded when a cell is read.
+struct MySqlRows(Vec<MySqlRow>);
 
-fn convert_row(row: &MySqlRow) -> ResultRow {
-    let cells = row
-        .columns()
-        .iter()
-        .enumerate()
-        .map(|(i, column)| convert_cell(row, column, i))
-        .collect();
-
-    ResultRow { cells }
-}
+impl RowSource for MySqlRows {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
 
-fn build_cell_column_metadata(column: &MySqlColumn, ordinal: usize) -> ResultColumnMetadata {
-    ResultColumnMetadata {
-        name: column.name().to_string(),
-        type_name: column.type_info().name().to_string(),
-        ordinal,
-        table_name: None,
-        is_nullable: None,
+    fn cell(&self, row: usize, col: usize) -> CellValue {
+        let Some(row) = self.0.get(row) else {
+            return CellValue::Null;
+        };
+        match row.columns().get(col) {
+            Some(column) => extract_cell_value(row, column, col),
+            None => CellValue::Null,
+        }
     }
 }
 
-fn decode_cell_value(row: &MySqlRow, column: &MySqlColumn, index: usize) -> (String, bool) {
+fn decode_cell_value(row: &MySqlRow, column: &MySqlColumn, index: usize) -> CellValue {
     // Try string first — MySQL's text protocol can render most types.
     if let Ok(v) = row.try_get::<String, _>(index) {
-        return (v, false);
+        return CellValue::Text(v);
     }
 
     match column.type_info().name() {
         "BOOLEAN" | "TINYINT" => row
             .try_get::<i8, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "SMALLINT" => row
             .try_get::<i16, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "MEDIUMINT" | "INT" => row
             .try_get::<i32, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "BIGINT" => row
             .try_get::<i64, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "TINYINT UNSIGNED" => row
             .try_get::<u8, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "SMALLINT UNSIGNED" => row
             .try_get::<u16, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "MEDIUMINT UNSIGNED" | "INT UNSIGNED" => row
             .try_get::<u32, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "BIGINT UNSIGNED" => row
             .try_get::<u64, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "FLOAT" => row
             .try_get::<f32, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "DOUBLE" => row
             .try_get::<f64, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "DECIMAL" => row
             .try_get::<rust_decimal::Decimal, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "DATE" => row
             .try_get::<chrono::NaiveDate, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "TIME" => row
             .try_get::<chrono::NaiveTime, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "DATETIME" => row
             .try_get::<chrono::NaiveDateTime, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "TIMESTAMP" => row
             .try_get::<chrono::DateTime<chrono::Utc>, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+            .map(|v| CellValue::Text(v.to_string()))
+            .unwrap_or_else(|_| undecoded(column)),
         "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BINARY" | "VARBINARY" => row
             .try_get::<Vec<u8>, _>(index)
-            .map(|v| (format!("0x{}", hex::encode(&v)), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
-        _ => ("NULL".to_string(), true),
+            .map(|v| CellValue::Text(format!("0x{}", hex::encode(&v))))
+            .unwrap_or_else(|_| undecoded(column)),
+        _ => undecoded(column),
     }
 }
 
-fn extract_cell_value(row: &MySqlRow, column: &MySqlColumn, index: usize) -> (String, bool) {
-    match row.try_get_raw(index) {
-        Ok(raw_value) if raw_value.is_null() => ("NULL".to_string(), true),
-        Ok(_) => decode_cell_value(row, column, index),
-        Err(_) => ("ERROR".to_string(), false),
-    }
+/// A value none of the decoders above read. It is not NULL, so it must
+/// not be shown as one.
+fn undecoded(column: &MySqlColumn) -> CellValue {
+    CellValue::Undecoded(column.type_info().name().to_lowercase())
 }
 
-fn convert_cell(row: &MySqlRow, column: &MySqlColumn, index: usize) -> ResultCell {
-    let column_metadata = build_cell_column_metadata(column, index);
-    let (value, is_null) = extract_cell_value(row, column, index);
-
-    ResultCell {
-        value,
-        is_null,
-        column_metadata,
+fn extract_cell_value(row: &MySqlRow, column: &MySqlColumn, index: usize) -> CellValue {
+    match row.try_get_raw(index) {
+        Ok(raw_value) if raw_value.is_null() => CellValue::Null,
+        Ok(_) => decode_cell_value(row, column, index),
+        Err(_) => undecoded(column),
     }
 }
@@ -5,13 +5,20 @@
 //! Postgres backend; the active database is derived from
 //! `DATABASE()` so that listings stay scoped to the connected DB.
 
-use anyhow::Result;
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
 use sqlx::{MySql, MySqlPool, Row};
 
+use crate::services::database::dml::{EditableColumn, TableEditInfo};
+use crate::services::database::search::ObjectSource;
 use crate::services::database::types::{
     ColumnDetail, ConstraintInfo, DatabaseInfo, DatabaseSchema, ForeignKeyInfo, IndexInfo,
-    QueryExecutionResult, TableInfo, TableSchema,
+    QueryExecutionResult, ReferencingKey, RoutineInfo, TableInfo, TableSchema, TriggerInfo,
 };
+use crate::services::storage::{DatabaseDriver, TableSizeSample};
+
+const DRIVER: DatabaseDriver = DatabaseDriver::MySql;
 
 const SYSTEM_SCHEMAS: &[&str] = &["mysql", "information_schema", "performance_schema", "sys"];
 
@@ -57,6 +64,254 @@ pub async fn get_tables(pool: &MySqlPool) -> Result<Vec<TableInfo>> {
         .collect())
 }
 
+/// Size of each base table, data and indexes, sampled now. The sizes are
+/// InnoDB's estimates, refreshed as it updates its statistics.
+pub async fn get_table_sizes(pool: &MySqlPool) -> Result<Vec<TableSizeSample>> {
+    let query = r#"
+        SELECT
+            TABLE_SCHEMA AS table_schema,
+            TABLE_NAME AS table_name,
+            CAST(COALESCE(DATA_LENGTH, 0) + COALESCE(INDEX_LENGTH, 0) AS SIGNED) AS total_bytes
+        FROM information_schema.TABLES
+        WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE = 'BASE TABLE'
+        ORDER BY TABLE_NAME
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+    let sampled_at = chrono::Utc::now();
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TableSizeSample {
+            table_schema: row.get("table_schema"),
+            table_name: row.get("table_name"),
+            total_bytes: row.get("total_bytes"),
+            sampled_at,
+        })
+        .collect())
+}
+
+/// One keyset page of [`get_tables`]: up to `limit` tables ordered after
+/// `after`. Listings are scoped to one database, so the name is the key.
+pub async fn get_tables_page(
+    pool: &MySqlPool,
+    after: Option<&TableInfo>,
+    limit: usize,
+) -> Result<Vec<TableInfo>> {
+    let query = r#"
+        SELECT
+            TABLE_NAME       AS table_name,
+            TABLE_SCHEMA     AS table_schema,
+            TABLE_TYPE       AS table_type
+        FROM information_schema.TABLES
+        WHERE TABLE_SCHEMA = DATABASE()
+            AND (? IS NULL OR TABLE_NAME > ?)
+        ORDER BY TABLE_NAME
+        LIMIT ?
+    "#;
+
+    let after_name = after.map(|t| t.table_name.as_str());
+    let rows = sqlx::query(query)
+        .bind(after_name)
+        .bind(after_name)
+        .bind(limit as u64)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TableInfo {
+            table_name: row.get("table_name"),
+            table_schema: row.get("table_schema"),
+            table_type: row.get("table_type"),
+        })
+        .collect())
+}
+
+/// Stored functions and procedures in the current database, with their
+/// parameter lists assembled from `information_schema.PARAMETERS`.
+pub async fn get_routines(pool: &MySqlPool) -> Result<Vec<RoutineInfo>> {
+    let query = r#"
+        SELECT
+            CAST(r.ROUTINE_SCHEMA AS CHAR) AS routine_schema,
+            CAST(r.ROUTINE_NAME AS CHAR) AS routine_name,
+            CAST(r.ROUTINE_TYPE AS CHAR) AS routine_type,
+            CAST(COALESCE(p.arguments, '') AS CHAR) AS arguments,
+            CAST(CASE WHEN r.ROUTINE_TYPE = 'FUNCTION' THEN r.DTD_IDENTIFIER END AS CHAR)
+                AS result_type
+        FROM information_schema.ROUTINES r
+        LEFT JOIN (
+            SELECT
+                SPECIFIC_SCHEMA,
+                SPECIFIC_NAME,
+                ROUTINE_TYPE,
+                GROUP_CONCAT(
+                    CONCAT_WS(' ', PARAMETER_MODE, PARAMETER_NAME, DTD_IDENTIFIER)
+                    ORDER BY ORDINAL_POSITION
+                    SEPARATOR ', '
+                ) AS arguments
+            FROM information_schema.PARAMETERS
+            WHERE ORDINAL_POSITION > 0
+            GROUP BY SPECIFIC_SCHEMA, SPECIFIC_NAME, ROUTINE_TYPE
+        ) p
+            ON p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA
+            AND p.SPECIFIC_NAME = r.SPECIFIC_NAME
+            AND p.ROUTINE_TYPE = r.ROUTINE_TYPE
+        WHERE r.ROUTINE_SCHEMA = DATABASE()
+        ORDER BY r.ROUTINE_NAME
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RoutineInfo {
+            routine_schema: row.get("routine_schema"),
+            routine_name: row.get("routine_name"),
+            routine_type: row.get("routine_type"),
+            arguments: row.get("arguments"),
+            result_type: row.get("result_type"),
+        })
+        .collect())
+}
+
+/// Bodies of the views and routines of the connected database, for
+/// searching inside them. MySQL has no overloading, so routines carry no
+/// arguments.
+pub async fn get_object_sources(pool: &MySqlPool) -> Result<Vec<ObjectSource>> {
+    let query = r#"
+        SELECT
+            CAST(TABLE_SCHEMA AS CHAR) AS object_schema,
+            CAST(TABLE_NAME AS CHAR) AS object_name,
+            CAST(COALESCE(VIEW_DEFINITION, '') AS CHAR) AS definition
+        FROM information_schema.VIEWS
+        WHERE TABLE_SCHEMA = DATABASE()
+        UNION ALL
+        SELECT
+            CAST(ROUTINE_SCHEMA AS CHAR),
+            CAST(ROUTINE_NAME AS CHAR),
+            CAST(COALESCE(ROUTINE_DEFINITION, '') AS CHAR)
+        FROM information_schema.ROUTINES
+        WHERE ROUTINE_SCHEMA = DATABASE()
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ObjectSource {
+            schema: row.get("object_schema"),
+            name: row.get("object_name"),
+            arguments: None,
+            definition: row.get("definition"),
+        })
+        .collect())
+}
+
+/// Source from `SHOW CREATE FUNCTION/PROCEDURE`. The column is NULL when
+/// the user lacks the privilege to see the body.
+pub async fn get_routine_definition(pool: &MySqlPool, routine: &RoutineInfo) -> Result<String> {
+    let kind = if routine.routine_type == "PROCEDURE" {
+        "PROCEDURE"
+    } else {
+        "FUNCTION"
+    };
+    let sql = format!(
+        "SHOW CREATE {} {}.{}",
+        kind,
+        DRIVER.quote_ident(&routine.routine_schema),
+        DRIVER.quote_ident(&routine.routine_name)
+    );
+
+    let row = sqlx::query(&sql).fetch_one(pool).await?;
+    let column = if kind == "PROCEDURE" {
+        "Create Procedure"
+    } else {
+        "Create Function"
+    };
+    row.try_get::<Option<String>, _>(column)?
+        .ok_or_else(|| anyhow!("Not allowed to view the source of {}", routine.signature()))
+}
+
+pub async fn get_view_definition(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<String> {
+    let sql = format!(
+        "SHOW CREATE VIEW {}.{}",
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name)
+    );
+    let row = sqlx::query(&sql).fetch_one(pool).await?;
+    Ok(row.try_get("Create View")?)
+}
+
+const TRIGGERS_QUERY: &str = r#"
+    SELECT
+        EVENT_OBJECT_SCHEMA AS table_schema,
+        EVENT_OBJECT_TABLE  AS table_name,
+        TRIGGER_NAME        AS trigger_name,
+        ACTION_TIMING       AS timing,
+        EVENT_MANIPULATION  AS event
+    FROM information_schema.TRIGGERS
+"#;
+
+/// MySQL has no way to disable a trigger, so they are always reported as
+/// enabled.
+fn trigger_from_row(row: &sqlx::mysql::MySqlRow) -> TriggerInfo {
+    TriggerInfo {
+        trigger_name: row.try_get("trigger_name").unwrap_or_default(),
+        timing: row.try_get("timing").unwrap_or_default(),
+        event: row.try_get("event").unwrap_or_default(),
+        function_name: None,
+        enabled: true,
+    }
+}
+
+/// Triggers on a table.
+pub async fn get_triggers(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<Vec<TriggerInfo>> {
+    let query = format!(
+        "{} WHERE EVENT_OBJECT_SCHEMA = ? AND EVENT_OBJECT_TABLE = ? ORDER BY TRIGGER_NAME",
+        TRIGGERS_QUERY
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(trigger_from_row).collect())
+}
+
+/// Every trigger in the current database, keyed by `(schema, table)`, so
+/// the schema load needs one query for all tables.
+async fn fetch_all_triggers(
+    pool: &MySqlPool,
+) -> Result<HashMap<(String, String), Vec<TriggerInfo>>> {
+    let query = format!(
+        "{} WHERE EVENT_OBJECT_SCHEMA = DATABASE() ORDER BY TRIGGER_NAME",
+        TRIGGERS_QUERY
+    );
+
+    let rows = sqlx::query(&query).fetch_all(pool).await?;
+    let mut triggers: HashMap<(String, String), Vec<TriggerInfo>> = HashMap::new();
+    for row in &rows {
+        let table_schema: String = row.try_get("table_schema").unwrap_or_default();
+        let table_name: String = row.try_get("table_name").unwrap_or_default();
+        triggers
+            .entry((table_schema, table_name))
+            .or_default()
+            .push(trigger_from_row(row));
+    }
+    Ok(triggers)
+}
+
 pub async fn get_table_columns(
     pool: &MySqlPool,
     table_name: &str,
@@ -97,6 +352,7 @@ pub async fn get_schema(
     "#;
 
     let table_rows = sqlx::query(table_query).fetch_all(pool).await?;
+    let mut all_triggers = fetch_all_triggers(pool).await?;
     let mut tables = Vec::new();
 
     for table_row in table_rows {
@@ -119,6 +375,9 @@ pub async fn get_schema(
         let foreign_keys = fetch_foreign_keys(&table_name, &table_schema, pool).await?;
         let indexes = fetch_indexes(&table_name, &table_schema, pool).await?;
         let constraints = fetch_constraints(&table_name, &table_schema, pool).await?;
+        let triggers = all_triggers
+            .remove(&(table_schema.clone(), table_name.clone()))
+            .unwrap_or_default();
 
         tables.push(TableSchema {
             table_name,
@@ -129,6 +388,7 @@ pub async fn get_schema(
             foreign_keys,
             indexes,
             constraints,
+            triggers,
             description,
         });
     }
@@ -140,7 +400,7 @@ pub async fn get_schema(
     })
 }
 
-async fn fetch_table_columns(
+pub async fn fetch_table_columns(
     table_name: &str,
     table_schema: &str,
     pool: &MySqlPool,
@@ -155,6 +415,9 @@ async fn fetch_table_columns(
             CHARACTER_MAXIMUM_LENGTH AS character_maximum_length,
             NUMERIC_PRECISION        AS numeric_precision,
             NUMERIC_SCALE            AS numeric_scale,
+            CAST(COLUMN_TYPE AS CHAR)    AS full_type,
+            CAST(COLLATION_NAME AS CHAR) AS collation,
+            CAST(EXTRA AS CHAR)          AS extra,
             COLUMN_COMMENT           AS description
         FROM information_schema.COLUMNS
         WHERE TABLE_NAME = ? AND TABLE_SCHEMA = ?
@@ -193,6 +456,12 @@ async fn fetch_table_columns(
                 .try_get::<String, _>("description")
                 .ok()
                 .filter(|s| !s.is_empty());
+            let full_type = row.try_get::<String, _>("full_type").ok();
+            let collation = row.try_get::<String, _>("collation").ok();
+            let extra = row
+                .try_get::<String, _>("extra")
+                .ok()
+                .filter(|s| !s.is_empty());
 
             ColumnDetail {
                 column_name: row.get("column_name"),
@@ -203,13 +472,16 @@ async fn fetch_table_columns(
                 character_maximum_length,
                 numeric_precision,
                 numeric_scale,
+                full_type,
+                collation,
+                extra,
                 description,
             }
         })
         .collect())
 }
 
-async fn fetch_primary_keys(
+pub(super) async fn fetch_primary_keys(
     table_name: &str,
     table_schema: &str,
     pool: &MySqlPool,
@@ -232,6 +504,52 @@ async fn fetch_primary_keys(
     Ok(rows.into_iter().map(|row| row.get("column_name")).collect())
 }
 
+/// Foreign keys of a table, one entry per column.
+pub async fn get_foreign_keys(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<Vec<ForeignKeyInfo>> {
+    fetch_foreign_keys(table_name, table_schema, pool).await
+}
+
+/// Foreign key columns of other tables that point at a table.
+pub async fn get_referencing_keys(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<Vec<ReferencingKey>> {
+    let query = r#"
+        SELECT
+            kcu.CONSTRAINT_NAME        AS constraint_name,
+            kcu.TABLE_SCHEMA           AS table_schema,
+            kcu.TABLE_NAME             AS table_name,
+            kcu.COLUMN_NAME            AS column_name,
+            kcu.REFERENCED_COLUMN_NAME AS referenced_column
+        FROM information_schema.KEY_COLUMN_USAGE kcu
+        WHERE kcu.REFERENCED_TABLE_SCHEMA = ?
+          AND kcu.REFERENCED_TABLE_NAME = ?
+        ORDER BY kcu.TABLE_SCHEMA, kcu.TABLE_NAME, kcu.ORDINAL_POSITION
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ReferencingKey {
+            constraint_name: row.get("constraint_name"),
+            table_schema: row.get("table_schema"),
+            table_name: row.get("table_name"),
+            column_name: row.get("column_name"),
+            referenced_column: row.get("referenced_column"),
+        })
+        .collect())
+}
+
 async fn fetch_foreign_keys(
     table_name: &str,
     table_schema: &str,
@@ -375,3 +693,120 @@ async fn fetch_constraints(
         })
         .collect())
 }
+
+/// Column details for the row editor. MySQL reports enum members only in
+/// `COLUMN_TYPE` (e.g. `enum('a','b')`), so they are parsed from there.
+pub async fn get_table_edit_info(
+    pool: &MySqlPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<TableEditInfo> {
+    let columns = fetch_table_columns(table_name, table_schema, pool).await?;
+    let primary_keys = fetch_primary_keys(table_name, table_schema, pool).await?;
+
+    let query = r#"
+        SELECT
+            COLUMN_NAME AS column_name,
+            COLUMN_TYPE AS column_type
+        FROM information_schema.COLUMNS
+        WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+    let types: HashMap<String, String> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let name: String = row.try_get("column_name").ok()?;
+            let column_type: String = row.try_get("column_type").ok()?;
+            Some((name, column_type))
+        })
+        .collect();
+
+    Ok(TableEditInfo {
+        table_schema: table_schema.to_string(),
+        table_name: table_name.to_string(),
+        columns: columns
+            .into_iter()
+            .map(|col| {
+                let sql_type = types
+                    .get(&col.column_name)
+                    .cloned()
+                    .unwrap_or_else(|| col.data_type.clone());
+                EditableColumn {
+                    is_primary_key: primary_keys.contains(&col.column_name),
+                    enum_values: parse_enum_values(&sql_type),
+                    name: col.column_name,
+                    data_type: col.data_type,
+                    sql_type,
+                    is_nullable: col.is_nullable,
+                    column_default: col.column_default,
+                }
+            })
+            .collect(),
+    })
+}
+
+/// Parse the members of an `enum('a','b')` column type. Returns an empty
+/// list for any other type.
+fn parse_enum_values(column_type: &str) -> Vec<String> {
+    let Some(inner) = column_type
+        .strip_prefix("enum(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return vec![];
+    };
+
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut in_quote = false;
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if in_quote && chars.peek() == Some(&'\'') => {
+                current.push('\'');
+                chars.next();
+            }
+            '\'' => {
+                if in_quote {
+                    values.push(std::mem::take(&mut current));
+                }
+                in_quote = !in_quote;
+            }
+            _ if in_quote => current.push(c),
+            _ => {}
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_enum_values;
+
+    #[test]
+    fn parses_enum_members() {
+        assert_eq!(
+            parse_enum_values("enum('small','medium','large')"),
+            vec!["small", "medium", "large"]
+        );
+    }
+
+    #[test]
+    fn parses_escaped_quotes_and_commas() {
+        assert_eq!(
+            parse_enum_values("enum('it''s','a,b')"),
+            vec!["it's", "a,b"]
+        );
+    }
+
+    #[test]
+    fn non_enum_types_have_no_members() {
+        assert!(parse_enum_values("varchar(20)").is_empty());
+        assert!(parse_enum_values("set('a','b')").is_empty());
+    }
+}
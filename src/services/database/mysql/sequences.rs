@@ -0,0 +1,77 @@
+//! MySQL AUTO_INCREMENT counters, presented as sequences.
+
+use anyhow::Result;
+use sqlx::{MySqlPool, Row};
+
+use crate::services::database::sequences::{SequenceInfo, SequenceOwner};
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::MySql;
+
+/// List tables with an AUTO_INCREMENT column in the current database. The
+/// "sequence" takes the table's name; `last_value` is one below the next
+/// counter value.
+pub async fn list_sequences(pool: &MySqlPool) -> Result<Vec<SequenceInfo>> {
+    let query = r#"
+        SELECT
+            CAST(t.TABLE_SCHEMA AS CHAR) AS table_schema,
+            CAST(t.TABLE_NAME AS CHAR) AS table_name,
+            CAST(c.COLUMN_NAME AS CHAR) AS column_name,
+            CAST(t.AUTO_INCREMENT AS SIGNED) AS next_value
+        FROM information_schema.TABLES t
+        JOIN information_schema.COLUMNS c
+            ON c.TABLE_SCHEMA = t.TABLE_SCHEMA
+            AND c.TABLE_NAME = t.TABLE_NAME
+            AND c.EXTRA LIKE '%auto_increment%'
+        WHERE t.TABLE_SCHEMA = DATABASE()
+        ORDER BY t.TABLE_NAME
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    let mut sequences = Vec::with_capacity(rows.len());
+    for row in rows {
+        let owner = SequenceOwner {
+            table_schema: row.get("table_schema"),
+            table_name: row.get("table_name"),
+            column_name: row.get("column_name"),
+        };
+        // information_schema statistics can be cached; NULL means the
+        // counter has not been initialised yet.
+        let next_value: Option<i64> = row.get("next_value");
+        let column_max = column_max(pool, &owner).await;
+
+        sequences.push(SequenceInfo {
+            sequence_schema: owner.table_schema.clone(),
+            sequence_name: owner.table_name.clone(),
+            last_value: next_value.map(|next| next - 1),
+            start_value: 1,
+            increment_by: 1,
+            owner: Some(owner),
+            is_identity: true,
+            column_max,
+        });
+    }
+
+    Ok(sequences)
+}
+
+async fn column_max(pool: &MySqlPool, owner: &SequenceOwner) -> Option<i64> {
+    let sql = format!(
+        "SELECT CAST(MAX({}) AS SIGNED) FROM {}.{}",
+        DRIVER.quote_ident(&owner.column_name),
+        DRIVER.quote_ident(&owner.table_schema),
+        DRIVER.quote_ident(&owner.table_name)
+    );
+    sqlx::query_scalar::<_, Option<i64>>(&sql)
+        .fetch_one(pool)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Run a generated maintenance statement.
+pub async fn execute(pool: &MySqlPool, sql: &str) -> Result<()> {
+    sqlx::query(sql).execute(pool).await?;
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//! Server notices (`RAISE NOTICE`, warnings) raised while a query runs.
+//!
+//! sqlx reports Postgres notices as tracing events under
+//! [`NOTICE_TARGET`] rather than returning them with the result.
+//! [`notice_layer`] routes each of those events to the enclosing
+//! [`capture_notices`] call, so concurrent queries keep their own notices.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Instrument as _, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Target sqlx logs Postgres `NoticeResponse` messages under.
+pub const NOTICE_TARGET: &str = "sqlx::postgres::notice";
+
+const CAPTURE_SPAN: &str = "pgui_capture_notices";
+
+static NEXT_CAPTURE: AtomicU64 = AtomicU64::new(0);
+static CAPTURES: LazyLock<Mutex<HashMap<u64, Vec<ServerNotice>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerNotice {
+    /// Postgres severity, e.g. `NOTICE` or `WARNING`.
+    pub severity: &'static str,
+    pub message: String,
+}
+
+/// Run `future` and collect the notices the server sent while it ran.
+pub async fn capture_notices<F: Future>(future: F) -> (F::Output, Vec<ServerNotice>) {
+    let id = NEXT_CAPTURE.fetch_add(1, Ordering::Relaxed);
+    CAPTURES.lock().unwrap().insert(id, Vec::new());
+
+    let span = tracing::span!(Level::TRACE, CAPTURE_SPAN, capture = id);
+    let output = future.instrument(span).await;
+
+    let notices = CAPTURES.lock().unwrap().remove(&id).unwrap_or_default();
+    (output, notices)
+}
+
+/// Layer feeding [`capture_notices`]. It only subscribes to its own span
+/// and to sqlx notice events, whatever the log filter is.
+pub fn notice_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    NoticeLayer.with_filter(filter_fn(|meta| {
+        meta.name() == CAPTURE_SPAN || meta.target() == NOTICE_TARGET
+    }))
+}
+
+struct NoticeLayer;
+
+/// Span extension holding the key into `CAPTURES`.
+struct CaptureId(u64);
+
+impl<S> Layer<S> for NoticeLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != CAPTURE_SPAN {
+            return;
+        }
+        let mut visitor = CaptureVisitor(None);
+        attrs.record(&mut visitor);
+        if let (Some(capture), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(CaptureId(capture));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if event.metadata().target() != NOTICE_TARGET {
+            return;
+        }
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        let Some(capture) = scope
+            .into_iter()
+            .find_map(|span| span.extensions().get::<CaptureId>().map(|c| c.0))
+        else {
+            return;
+        };
+
+        let mut visitor = MessageVisitor(None);
+        event.record(&mut visitor);
+        let notice = ServerNotice {
+            severity: severity(*event.metadata().level()),
+            message: visitor.0.unwrap_or_default(),
+        };
+        if let Some(notices) = CAPTURES.lock().unwrap().get_mut(&capture) {
+            notices.push(notice);
+        }
+    }
+}
+
+/// Undo sqlx's mapping of Postgres severities onto tracing levels.
+fn severity(level: Level) -> &'static str {
+    match level {
+        Level::ERROR => "ERROR",
+        Level::WARN => "WARNING",
+        Level::INFO => "NOTICE",
+        Level::DEBUG => "DEBUG",
+        Level::TRACE => "INFO",
+    }
+}
+
+struct CaptureVisitor(Option<u64>);
+
+impl Visit for CaptureVisitor {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "capture" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+struct MessageVisitor(Option<String>);
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" && self.0.is_none() {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt as _;
+
+    fn with_layer<T>(f: impl FnOnce() -> T) -> T {
+        let subscriber = tracing_subscriber::registry().with(notice_layer());
+        tracing::subscriber::with_default(subscriber, f)
+    }
+
+    #[test]
+    fn captures_notices_raised_inside_the_future() {
+        let (output, notices) = with_layer(|| {
+            futures::executor::block_on(capture_notices(async {
+                tracing::event!(target: NOTICE_TARGET, Level::INFO, message = "row count: 3");
+                tracing::event!(target: NOTICE_TARGET, Level::WARN, message = "deprecated");
+                42
+            }))
+        });
+
+        assert_eq!(output, 42);
+        assert_eq!(
+            notices,
+            vec![
+                ServerNotice {
+                    severity: "NOTICE",
+                    message: "row count: 3".to_string(),
+                },
+                ServerNotice {
+                    severity: "WARNING",
+                    message: "deprecated".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_notices_outside_a_capture() {
+        let notices = with_layer(|| {
+            tracing::event!(target: NOTICE_TARGET, Level::INFO, message = "stray");
+            futures::executor::block_on(capture_notices(async {
+                tracing::info!(message = "not a notice");
+            }))
+            .1
+        });
+
+        assert!(notices.is_empty());
+    }
+
+    #[test]
+    fn nested_captures_keep_their_own_notices() {
+        let (inner, outer) = with_layer(|| {
+            futures::executor::block_on(capture_notices(async {
+                tracing::event!(target: NOTICE_TARGET, Level::INFO, message = "outer");
+                capture_notices(async {
+                    tracing::event!(target: NOTICE_TARGET, Level::INFO, message = "inner");
+                })
+                .await
+                .1
+            }))
+        });
+
+        assert_eq!(inner.len(), 1);
+        assert_eq!(inner[0].message, "inner");
+        assert_eq!(outer.len(), 1);
+        assert_eq!(outer[0].message, "outer");
+    }
+}
@@ -0,0 +1,535 @@
+//! Expensive statements from `pg_stat_statements` and index suggestions.
+//!
+//! The heuristic is deliberately simple: a table is a candidate when it is
+//! large and scanned sequentially more often than through an index, and a
+//! column is suggested when one of the top statements filters that table
+//! on it with a constant (`col = $1`, `col IN (...)`, `col > 10`) and no
+//! existing index starts with it. Suggestions are for review, not proof.
+
+use std::collections::HashMap;
+
+use crate::services::storage::DatabaseDriver;
+
+/// Tables with fewer live rows than this are cheap to scan anyway.
+pub const LARGE_TABLE_ROWS: i64 = 10_000;
+
+/// Number of statements listed in the report.
+pub const TOP_STATEMENTS: usize = 25;
+
+/// Number of statements listed in the statement statistics panel.
+pub const ALL_STATEMENTS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementStats {
+    pub query: String,
+    pub calls: i64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub rows: i64,
+    /// Shared buffer blocks found in cache, and read from disk.
+    pub shared_blks_hit: i64,
+    pub shared_blks_read: i64,
+}
+
+impl StatementStats {
+    /// Share of shared buffer reads served from cache, when there were any.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let total = self.shared_blks_hit + self.shared_blks_read;
+        (total > 0).then(|| self.shared_blks_hit as f64 / total as f64)
+    }
+}
+
+/// Column the statement statistics panel is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementSort {
+    Calls,
+    TotalTime,
+    MeanTime,
+    Rows,
+    HitRatio,
+}
+
+impl StatementSort {
+    pub const ALL: [StatementSort; 5] = [
+        StatementSort::TotalTime,
+        StatementSort::MeanTime,
+        StatementSort::Calls,
+        StatementSort::Rows,
+        StatementSort::HitRatio,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatementSort::Calls => "Calls",
+            StatementSort::TotalTime => "Total",
+            StatementSort::MeanTime => "Mean",
+            StatementSort::Rows => "Rows",
+            StatementSort::HitRatio => "Hit ratio",
+        }
+    }
+}
+
+/// Sort `statements` by `sort`. Statements without a hit ratio sort below
+/// every ratio.
+pub fn sort_statements(statements: &mut [StatementStats], sort: StatementSort, descending: bool) {
+    let key = |s: &StatementStats| match sort {
+        StatementSort::Calls => s.calls as f64,
+        StatementSort::TotalTime => s.total_ms,
+        StatementSort::MeanTime => s.mean_ms,
+        StatementSort::Rows => s.rows as f64,
+        StatementSort::HitRatio => s.hit_ratio().unwrap_or(-1.),
+    };
+    statements.sort_by(|a, b| {
+        let ordering = key(a).total_cmp(&key(b));
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+/// Scan counters for one table, from `pg_stat_user_tables`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableScanStats {
+    pub table_schema: String,
+    pub table_name: String,
+    pub seq_scan: i64,
+    pub seq_tup_read: i64,
+    pub idx_scan: i64,
+    pub live_rows: i64,
+    /// Leading column of each existing index.
+    pub indexed_columns: Vec<String>,
+}
+
+impl TableScanStats {
+    /// Large, and read sequentially more often than through an index.
+    pub fn is_seq_scan_heavy(&self) -> bool {
+        self.live_rows >= LARGE_TABLE_ROWS && self.seq_scan > self.idx_scan
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSuggestion {
+    pub table_schema: String,
+    pub table_name: String,
+    pub column: String,
+    /// Why the index is suggested, for display.
+    pub reason: String,
+    /// Total time of the statements that would use it.
+    pub total_ms: f64,
+}
+
+impl IndexSuggestion {
+    /// The `CREATE INDEX CONCURRENTLY` statement, offered for review.
+    pub fn create_sql(&self) -> String {
+        let driver = DatabaseDriver::Postgres;
+        format!(
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS {} ON {}.{} ({});",
+            driver.quote_ident(&format!("{}_{}_idx", self.table_name, self.column)),
+            driver.quote_ident(&self.table_schema),
+            driver.quote_ident(&self.table_name),
+            driver.quote_ident(&self.column)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PerformanceReport {
+    pub statements: Vec<StatementStats>,
+    pub suggestions: Vec<IndexSuggestion>,
+}
+
+/// Suggest single-column indexes for seq-scan heavy tables, from the
+/// constant predicates in `statements`. Sorted by the time at stake.
+pub fn suggest_indexes(
+    statements: &[StatementStats],
+    tables: &[TableScanStats],
+) -> Vec<IndexSuggestion> {
+    let mut suggestions: HashMap<(String, String, String), IndexSuggestion> = HashMap::new();
+
+    for stmt in statements {
+        let tokens = tokenize(&stmt.query);
+        let referenced = referenced_tables(&tokens);
+
+        for (qualifier, column) in constant_predicates(&tokens) {
+            let target = match &qualifier {
+                Some(q) => referenced.iter().find(|r| r.alias == *q || r.name == *q),
+                None if referenced.len() == 1 => referenced.first(),
+                None => None,
+            };
+            let Some(target) = target else {
+                continue;
+            };
+            let Some(table) = tables.iter().find(|t| {
+                t.table_name == target.name
+                    && target.schema.as_ref().is_none_or(|s| *s == t.table_schema)
+            }) else {
+                continue;
+            };
+            if !table.is_seq_scan_heavy() || table.indexed_columns.contains(&column) {
+                continue;
+            }
+
+            let key = (
+                table.table_schema.clone(),
+                table.table_name.clone(),
+                column.clone(),
+            );
+            let entry = suggestions.entry(key).or_insert_with(|| IndexSuggestion {
+                table_schema: table.table_schema.clone(),
+                table_name: table.table_name.clone(),
+                column: column.clone(),
+                reason: format!(
+                    "{} seq scans vs {} index scans on ~{} rows",
+                    table.seq_scan, table.idx_scan, table.live_rows
+                ),
+                total_ms: 0.0,
+            });
+            entry.total_ms += stmt.total_ms;
+        }
+    }
+
+    let mut suggestions: Vec<IndexSuggestion> = suggestions.into_values().collect();
+    suggestions.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+    suggestions
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Identifier or keyword, unquoted. Keywords are compared uppercased.
+    Word(String),
+    /// A double-quoted identifier, never a keyword.
+    Quoted(String),
+    /// A constant: number, string literal or bind parameter.
+    Value,
+    Symbol(String),
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn ident(&self) -> Option<String> {
+        match self {
+            Token::Word(w) if !is_reserved(w) => Some(w.to_lowercase()),
+            Token::Quoted(q) => Some(q.clone()),
+            _ => None,
+        }
+    }
+}
+
+const RESERVED: &str = "\
+    SELECT FROM WHERE AND OR NOT JOIN INNER LEFT RIGHT FULL OUTER CROSS NATURAL \
+    LATERAL ON USING GROUP ORDER BY HAVING LIMIT OFFSET UNION EXCEPT INTERSECT \
+    AS SET RETURNING FOR WINDOW IN IS NULL LIKE ILIKE BETWEEN UPDATE DELETE \
+    INSERT INTO VALUES WITH ONLY";
+
+fn is_reserved(word: &str) -> bool {
+    RESERVED
+        .split_whitespace()
+        .any(|k| word.eq_ignore_ascii_case(k))
+}
+
+fn tokenize(sql: &str) -> Vec<Token> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '\'' {
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '\'' && chars.get(i + 1) == Some(&'\'') {
+                    i += 2;
+                } else if chars[i] == '\'' {
+                    break;
+                } else {
+                    i += 1;
+                }
+            }
+            i += 1;
+            tokens.push(Token::Value);
+        } else if c == '"' {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            tokens.push(Token::Quoted(
+                chars[start..i.min(chars.len())].iter().collect(),
+            ));
+            i += 1;
+        } else if c == '$' || c.is_ascii_digit() {
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Value);
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else if matches!(c, '<' | '>' | '!') && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Symbol(format!("{}=", c)));
+            i += 2;
+        } else if c == '<' && chars.get(i + 1) == Some(&'>') {
+            tokens.push(Token::Symbol("<>".to_string()));
+            i += 2;
+        } else {
+            tokens.push(Token::Symbol(c.to_string()));
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// A table in a FROM/JOIN/UPDATE clause.
+#[derive(Debug, Clone, PartialEq)]
+struct TableRef {
+    schema: Option<String>,
+    name: String,
+    alias: String,
+}
+
+fn referenced_tables(tokens: &[Token]) -> Vec<TableRef> {
+    let mut tables = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let starts_ref = tokens[i].is_keyword("FROM")
+            || tokens[i].is_keyword("JOIN")
+            || tokens[i].is_keyword("UPDATE");
+        i += 1;
+        if !starts_ref {
+            continue;
+        }
+        if tokens.get(i).is_some_and(|t| t.is_keyword("ONLY")) {
+            i += 1;
+        }
+
+        let Some(first) = tokens.get(i).and_then(Token::ident) else {
+            continue;
+        };
+        i += 1;
+        let (schema, name) = if tokens.get(i) == Some(&Token::Symbol(".".to_string())) {
+            match tokens.get(i + 1).and_then(Token::ident) {
+                Some(name) => {
+                    i += 2;
+                    (Some(first), name)
+                }
+                None => (None, first),
+            }
+        } else {
+            (None, first)
+        };
+
+        if tokens.get(i).is_some_and(|t| t.is_keyword("AS")) {
+            i += 1;
+        }
+        let alias = match tokens.get(i).and_then(Token::ident) {
+            Some(alias) => {
+                i += 1;
+                alias
+            }
+            None => name.clone(),
+        };
+        tables.push(TableRef {
+            schema,
+            name,
+            alias,
+        });
+    }
+    tables
+}
+
+/// `(qualifier, column)` for each `[qualifier.]column <op> <constant>`
+/// predicate following WHERE, AND or OR.
+fn constant_predicates(tokens: &[Token]) -> Vec<(Option<String>, String)> {
+    let mut predicates = Vec::new();
+    let mut in_where = false;
+
+    for i in 0..tokens.len() {
+        if tokens[i].is_keyword("WHERE") {
+            in_where = true;
+        } else if ["GROUP", "ORDER", "LIMIT", "RETURNING", "UNION"]
+            .iter()
+            .any(|k| tokens[i].is_keyword(k))
+        {
+            in_where = false;
+        }
+
+        let after_connective = tokens[i].is_keyword("WHERE")
+            || tokens[i].is_keyword("AND")
+            || tokens[i].is_keyword("OR")
+            || tokens[i] == Token::Symbol("(".to_string());
+        if !in_where || !after_connective {
+            continue;
+        }
+
+        let Some(first) = tokens.get(i + 1).and_then(Token::ident) else {
+            continue;
+        };
+        let (qualifier, column, next) =
+            if tokens.get(i + 2) == Some(&Token::Symbol(".".to_string())) {
+                match tokens.get(i + 3).and_then(Token::ident) {
+                    Some(column) => (Some(first), column, i + 4),
+                    None => continue,
+                }
+            } else {
+                (None, first, i + 2)
+            };
+
+        let is_comparison = match tokens.get(next) {
+            Some(Token::Symbol(op)) => ["=", "<", ">", "<=", ">="].contains(&op.as_str()),
+            Some(t) => t.is_keyword("IN") || t.is_keyword("LIKE") || t.is_keyword("BETWEEN"),
+            None => false,
+        };
+        let operand = match tokens.get(next + 1) {
+            Some(Token::Symbol(open)) if open == "(" => tokens.get(next + 2),
+            other => other,
+        };
+        if is_comparison && operand == Some(&Token::Value) {
+            predicates.push((qualifier, column));
+        }
+    }
+
+    predicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stmt(query: &str, total_ms: f64) -> StatementStats {
+        StatementStats {
+            query: query.to_string(),
+            calls: 100,
+            total_ms,
+            mean_ms: total_ms / 100.0,
+            rows: 100,
+            shared_blks_hit: 0,
+            shared_blks_read: 0,
+        }
+    }
+
+    fn table(name: &str, live_rows: i64, seq_scan: i64, indexed: &[&str]) -> TableScanStats {
+        TableScanStats {
+            table_schema: "public".to_string(),
+            table_name: name.to_string(),
+            seq_scan,
+            seq_tup_read: seq_scan * live_rows,
+            idx_scan: 10,
+            live_rows,
+            indexed_columns: indexed.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn finds_constant_predicates() {
+        let tokens = tokenize(
+            "SELECT * FROM orders o WHERE o.customer_id = $1 AND status IN ($2, $3) \
+             AND o.total > other.total ORDER BY created_at",
+        );
+        assert_eq!(
+            constant_predicates(&tokens),
+            vec![
+                (Some("o".to_string()), "customer_id".to_string()),
+                (None, "status".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_aliases_and_schemas() {
+        let tokens =
+            tokenize("SELECT 1 FROM public.orders AS o JOIN \"Customers\" c ON c.id = o.cid");
+        assert_eq!(
+            referenced_tables(&tokens),
+            vec![
+                TableRef {
+                    schema: Some("public".to_string()),
+                    name: "orders".to_string(),
+                    alias: "o".to_string(),
+                },
+                TableRef {
+                    schema: None,
+                    name: "Customers".to_string(),
+                    alias: "c".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn suggests_unindexed_filter_on_large_seq_scanned_table() {
+        let statements = vec![
+            stmt("SELECT * FROM orders WHERE customer_id = $1", 500.0),
+            stmt(
+                "SELECT * FROM orders o WHERE o.customer_id = $1 LIMIT $2",
+                250.0,
+            ),
+            stmt("SELECT * FROM orders WHERE id = $1", 900.0),
+            stmt("SELECT * FROM tiny WHERE code = $1", 900.0),
+        ];
+        let tables = vec![
+            table("orders", 1_000_000, 5_000, &["id"]),
+            table("tiny", 50, 5_000, &[]),
+        ];
+
+        let suggestions = suggest_indexes(&statements, &tables);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].column, "customer_id");
+        assert_eq!(suggestions[0].total_ms, 750.0);
+        assert_eq!(
+            suggestions[0].create_sql(),
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS \"orders_customer_id_idx\" \
+             ON \"public\".\"orders\" (\"customer_id\");"
+        );
+    }
+
+    #[test]
+    fn ignores_tables_mostly_read_through_indexes() {
+        let statements = vec![stmt("SELECT * FROM orders WHERE customer_id = $1", 500.0)];
+        let tables = vec![table("orders", 1_000_000, 1, &[])];
+        assert!(suggest_indexes(&statements, &tables).is_empty());
+    }
+
+    #[test]
+    fn sorts_by_hit_ratio_with_unknown_ratios_last() {
+        let mut cached = stmt("SELECT 1", 10.0);
+        cached.shared_blks_hit = 99;
+        cached.shared_blks_read = 1;
+        let mut cold = stmt("SELECT 2", 30.0);
+        cold.shared_blks_hit = 1;
+        cold.shared_blks_read = 3;
+        let unknown = stmt("SELECT 3", 20.0);
+        assert_eq!(cold.hit_ratio(), Some(0.25));
+        assert_eq!(unknown.hit_ratio(), None);
+
+        let mut statements = vec![unknown, cold, cached];
+        sort_statements(&mut statements, StatementSort::HitRatio, true);
+        let queries: Vec<&str> = statements.iter().map(|s| s.query.as_str()).collect();
+        assert_eq!(queries, vec!["SELECT 1", "SELECT 2", "SELECT 3"]);
+
+        sort_statements(&mut statements, StatementSort::TotalTime, false);
+        let queries: Vec<&str> = statements.iter().map(|s| s.query.as_str()).collect();
+        assert_eq!(queries, vec!["SELECT 1", "SELECT 3", "SELECT 2"]);
+    }
+}
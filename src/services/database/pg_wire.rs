@@ -0,0 +1,793 @@
+//! Postgres values in the binary wire format, written out the way Postgres
+//! prints them.
+//!
+//! The results grid reads the common types with sqlx's typed getters.
+//! Everything else is read from the raw bytes here: dates and times
+//! (including `infinity`), intervals, uuids, network addresses, bit
+//! strings, money, arrays, ranges and anonymous records. Types this module
+//! does not know, such as enums, are shown as text when their bytes are.
+
+use std::fmt::Write as _;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+
+const BOOL: u32 = 16;
+const BYTEA: u32 = 17;
+const CHAR: u32 = 18;
+const NAME: u32 = 19;
+const INT8: u32 = 20;
+const INT2: u32 = 21;
+const INT4: u32 = 23;
+const TEXT: u32 = 25;
+const OID: u32 = 26;
+const JSON: u32 = 114;
+const XML: u32 = 142;
+const CIDR: u32 = 650;
+const FLOAT4: u32 = 700;
+const FLOAT8: u32 = 701;
+const UNKNOWN: u32 = 705;
+const MACADDR8: u32 = 774;
+const MONEY: u32 = 790;
+const MACADDR: u32 = 829;
+const INET: u32 = 869;
+const BPCHAR: u32 = 1042;
+const VARCHAR: u32 = 1043;
+const DATE: u32 = 1082;
+const TIME: u32 = 1083;
+const TIMESTAMP: u32 = 1114;
+const TIMESTAMPTZ: u32 = 1184;
+const INTERVAL: u32 = 1186;
+const TIMETZ: u32 = 1266;
+const BIT: u32 = 1560;
+const VARBIT: u32 = 1562;
+const NUMERIC: u32 = 1700;
+const RECORD: u32 = 2249;
+const UUID: u32 = 2950;
+const JSONB: u32 = 3802;
+
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LOWER_INCLUSIVE: u8 = 0x02;
+const RANGE_UPPER_INCLUSIVE: u8 = 0x04;
+const RANGE_LOWER_INFINITE: u8 = 0x08;
+const RANGE_UPPER_INFINITE: u8 = 0x10;
+
+/// Reads big-endian fields off the front of a value.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        self.take(2)?.try_into().ok().map(i16::from_be_bytes)
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.take(4)?.try_into().ok().map(i32::from_be_bytes)
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        self.take(8)?.try_into().ok().map(i64::from_be_bytes)
+    }
+
+    /// A length-prefixed value, `Some(None)` when it is NULL.
+    fn field(&mut self) -> Option<Option<&'a [u8]>> {
+        let len = self.i32()?;
+        if len < 0 {
+            return Some(None);
+        }
+        self.take(len as usize).map(Some)
+    }
+}
+
+fn fixed<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+    bytes.try_into().ok()
+}
+
+fn utf8(bytes: &[u8]) -> Option<String> {
+    std::str::from_utf8(bytes).ok().map(str::to_string)
+}
+
+/// Bytes of a type this module does not know, when they read as text.
+fn printable(bytes: &[u8]) -> Option<String> {
+    utf8(bytes).filter(|text| {
+        !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+    })
+}
+
+/// Rust writes infinite floats as `inf`.
+fn float_text(text: String) -> String {
+    match text.as_str() {
+        "inf" => "Infinity".to_string(),
+        "-inf" => "-Infinity".to_string(),
+        _ => text,
+    }
+}
+
+/// Postgres counts dates and timestamps from 2000-01-01.
+fn postgres_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .unwrap_or_default()
+}
+
+fn date_text(bytes: &[u8]) -> Option<String> {
+    match i32::from_be_bytes(fixed(bytes)?) {
+        i32::MAX => Some("infinity".to_string()),
+        i32::MIN => Some("-infinity".to_string()),
+        days => postgres_epoch()
+            .date()
+            .checked_add_signed(Duration::days(days.into()))
+            .map(|date| date.to_string()),
+    }
+}
+
+/// A timestamp, or `Err` holding `infinity` or `-infinity`.
+fn timestamp(bytes: &[u8]) -> Option<Result<NaiveDateTime, &'static str>> {
+    match i64::from_be_bytes(fixed(bytes)?) {
+        i64::MAX => Some(Err("infinity")),
+        i64::MIN => Some(Err("-infinity")),
+        micros => postgres_epoch()
+            .checked_add_signed(Duration::microseconds(micros))
+            .map(Ok),
+    }
+}
+
+fn time_text(micros: i64) -> Option<String> {
+    // `24:00:00` is a valid Postgres time but not a chrono one.
+    if micros == 86_400_000_000 {
+        return Some("24:00:00".to_string());
+    }
+    let seconds = u32::try_from(micros.div_euclid(1_000_000)).ok()?;
+    let nanos = (micros.rem_euclid(1_000_000) * 1_000) as u32;
+    NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanos).map(|time| time.to_string())
+}
+
+/// `+02`, `-05:30`: hours, then minutes and seconds when not zero.
+fn offset_text(seconds_east: i32) -> String {
+    let sign = if seconds_east < 0 { '-' } else { '+' };
+    let total = seconds_east.unsigned_abs();
+    let (hours, minutes, seconds) = (total / 3600, total % 3600 / 60, total % 60);
+    let mut text = format!("{}{:02}", sign, hours);
+    if minutes != 0 || seconds != 0 {
+        let _ = write!(text, ":{:02}", minutes);
+    }
+    if seconds != 0 {
+        let _ = write!(text, ":{:02}", seconds);
+    }
+    text
+}
+
+fn timetz_text(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let time = time_text(reader.i64()?)?;
+    // The zone is sent as seconds west of UTC.
+    let zone = reader.i32()?;
+    Some(format!("{}{}", time, offset_text(-zone)))
+}
+
+/// `1 year 2 mons 3 days 04:05:06.5`, as with `IntervalStyle = postgres`.
+fn interval_text(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let (micros, days, months) = (reader.i64()?, reader.i32()?, reader.i32()?);
+    let unit =
+        |count: i32, name: &str| format!("{} {}{}", count, name, if count == 1 { "" } else { "s" });
+
+    let mut parts = vec![];
+    if months / 12 != 0 {
+        parts.push(unit(months / 12, "year"));
+    }
+    if months % 12 != 0 {
+        parts.push(unit(months % 12, "mon"));
+    }
+    if days != 0 {
+        parts.push(unit(days, "day"));
+    }
+    if micros != 0 || parts.is_empty() {
+        let sign = if micros < 0 { "-" } else { "" };
+        let total = micros.unsigned_abs();
+        let (seconds, fraction) = (total / 1_000_000, total % 1_000_000);
+        let mut time = format!(
+            "{}{:02}:{:02}:{:02}",
+            sign,
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        );
+        if fraction != 0 {
+            let digits = format!("{:06}", fraction);
+            let _ = write!(time, ".{}", digits.trim_end_matches('0'));
+        }
+        parts.push(time);
+    }
+    Some(parts.join(" "))
+}
+
+fn numeric_text(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let ndigits = reader.i16()?;
+    let weight = i32::from(reader.i16()?);
+    let sign = reader.i16()? as u16;
+    let scale = reader.i16()? as u16 as usize;
+    match sign {
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        0x0000 | 0x4000 => {}
+        _ => return None,
+    }
+    // Base 10000 digits, the first `weight + 1` of them before the point.
+    let digits = (0..ndigits)
+        .map(|_| reader.i16())
+        .collect::<Option<Vec<_>>>()?;
+    let digit = |ix: i32| {
+        usize::try_from(ix)
+            .ok()
+            .and_then(|ix| digits.get(ix))
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    }
+    for ix in 0..=weight {
+        let _ = if ix == 0 {
+            write!(text, "{}", digit(ix))
+        } else {
+            write!(text, "{:04}", digit(ix))
+        };
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut ix = weight + 1;
+        while fraction.len() < scale {
+            let _ = write!(fraction, "{:04}", digit(ix));
+            ix += 1;
+        }
+        fraction.truncate(scale);
+        let _ = write!(text, ".{}", fraction);
+    }
+    Some(text)
+}
+
+/// Two decimal places, without the currency symbol `lc_monetary` would add.
+fn money_text(bytes: &[u8]) -> Option<String> {
+    let cents = i64::from_be_bytes(fixed(bytes)?);
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    Some(format!("{}{}.{:02}", sign, cents / 100, cents % 100))
+}
+
+fn inet_text(oid: u32, bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let family = reader.u8()?;
+    let bits = reader.u8()?;
+    let _is_cidr = reader.u8()?;
+    let len = reader.u8()?;
+    let address = reader.take(len.into())?;
+    let (address, max_bits) = match family {
+        2 => (Ipv4Addr::from(fixed::<4>(address)?).to_string(), 32),
+        3 => (Ipv6Addr::from(fixed::<16>(address)?).to_string(), 128),
+        _ => return None,
+    };
+    // `inet` leaves out a netmask covering the whole address.
+    Some(if oid == CIDR || bits != max_bits {
+        format!("{}/{}", address, bits)
+    } else {
+        address
+    })
+}
+
+fn mac_text(bytes: &[u8]) -> Option<String> {
+    if bytes.len() != 6 && bytes.len() != 8 {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<_>>()
+            .join(":"),
+    )
+}
+
+fn bit_text(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let len = usize::try_from(reader.i32()?).ok()?;
+    let data = reader.0;
+    if data.len() * 8 < len {
+        return None;
+    }
+    Some(
+        (0..len)
+            .map(|ix| {
+                if data[ix / 8] & (0x80 >> (ix % 8)) != 0 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect(),
+    )
+}
+
+/// An array element, quoted as `array_out` does when it would otherwise
+/// read as several elements, NULL or nothing.
+fn array_element(value: &str) -> String {
+    let quote = value.is_empty()
+        || value.eq_ignore_ascii_case("null")
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '{' | '}' | ',' | '"' | '\\'));
+    if quote {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A range bound or record field, quoted as `range_out` and `record_out`
+/// do.
+fn bound_element(value: &str) -> String {
+    let quote = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | ',' | '"' | '\\'));
+    if quote {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn nest_elements(dims: &[usize], elements: &[String], text: &mut String) {
+    text.push('{');
+    match dims {
+        [_, inner @ ..] if !inner.is_empty() => {
+            let size = inner.iter().product();
+            for (ix, chunk) in elements.chunks(size).enumerate() {
+                if ix > 0 {
+                    text.push(',');
+                }
+                nest_elements(inner, chunk, text);
+            }
+        }
+        _ => text.push_str(&elements.join(",")),
+    }
+    text.push('}');
+}
+
+/// `{1,2,NULL}`, `{{1,2},{3,4}}`, with `[0:1]=` first when an array does
+/// not start at 1.
+pub fn format_array(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let ndim = usize::try_from(reader.i32()?).ok()?;
+    let _has_nulls = reader.i32()?;
+    let element_oid = reader.i32()? as u32;
+
+    let mut dims = vec![];
+    let mut bounds = String::new();
+    let mut default_bounds = true;
+    for _ in 0..ndim {
+        let len = usize::try_from(reader.i32()?).ok()?;
+        let lower = reader.i32()?;
+        default_bounds &= lower == 1;
+        let _ = write!(bounds, "[{}:{}]", lower, i64::from(lower) + len as i64 - 1);
+        dims.push(len);
+    }
+    let count: usize = dims.iter().product();
+    if ndim == 0 || count == 0 {
+        return Some("{}".to_string());
+    }
+
+    let elements = (0..count)
+        .map(|_| match reader.field()? {
+            None => Some("NULL".to_string()),
+            Some(bytes) => format_value(element_oid, bytes).map(|v| array_element(&v)),
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut text = if default_bounds {
+        String::new()
+    } else {
+        format!("{}=", bounds)
+    };
+    nest_elements(&dims, &elements, &mut text);
+    Some(text)
+}
+
+fn range_subtype(oid: u32) -> Option<u32> {
+    match oid {
+        3904 => Some(INT4),
+        3906 => Some(NUMERIC),
+        3908 => Some(TIMESTAMP),
+        3910 => Some(TIMESTAMPTZ),
+        3912 => Some(DATE),
+        3926 => Some(INT8),
+        _ => None,
+    }
+}
+
+fn multirange_subtype(oid: u32) -> Option<u32> {
+    match oid {
+        4451 => Some(INT4),
+        4532 => Some(NUMERIC),
+        4533 => Some(TIMESTAMP),
+        4534 => Some(TIMESTAMPTZ),
+        4535 => Some(DATE),
+        4536 => Some(INT8),
+        _ => None,
+    }
+}
+
+/// `[1,10)`, `(,5]` or `empty`.
+fn range_text(subtype: u32, bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let flags = reader.u8()?;
+    if flags & RANGE_EMPTY != 0 {
+        return Some("empty".to_string());
+    }
+    let mut bound = |infinite: bool| {
+        if infinite {
+            return Some(String::new());
+        }
+        let bytes = reader.field()??;
+        format_value(subtype, bytes).map(|v| bound_element(&v))
+    };
+    let lower = bound(flags & RANGE_LOWER_INFINITE != 0)?;
+    let upper = bound(flags & RANGE_UPPER_INFINITE != 0)?;
+    Some(format!(
+        "{}{},{}{}",
+        if flags & RANGE_LOWER_INCLUSIVE != 0 {
+            '['
+        } else {
+            '('
+        },
+        lower,
+        upper,
+        if flags & RANGE_UPPER_INCLUSIVE != 0 {
+            ']'
+        } else {
+            ')'
+        }
+    ))
+}
+
+/// `{[1,3),[5,7)}`.
+fn multirange_text(subtype: u32, bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let count = usize::try_from(reader.i32()?).ok()?;
+    let ranges = (0..count)
+        .map(|_| range_text(subtype, reader.field()??))
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("{{{}}}", ranges.join(",")))
+}
+
+/// `(1,"two words",)` for `ROW(...)`; a NULL field is left empty.
+fn record_text(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader(bytes);
+    let count = usize::try_from(reader.i32()?).ok()?;
+    let fields = (0..count)
+        .map(|_| {
+            let oid = reader.i32()? as u32;
+            match reader.field()? {
+                None => Some(String::new()),
+                Some(bytes) => format_value(oid, bytes).map(|v| bound_element(&v)),
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("({})", fields.join(",")))
+}
+
+/// The text of a non-array value of the type `oid` sent as `bytes`, or
+/// `None` when it cannot be read.
+pub fn format_value(oid: u32, bytes: &[u8]) -> Option<String> {
+    match oid {
+        BOOL => match bytes {
+            [0] => Some("false".to_string()),
+            [1] => Some("true".to_string()),
+            _ => None,
+        },
+        INT2 => fixed(bytes).map(|b| i16::from_be_bytes(b).to_string()),
+        INT4 => fixed(bytes).map(|b| i32::from_be_bytes(b).to_string()),
+        INT8 => fixed(bytes).map(|b| i64::from_be_bytes(b).to_string()),
+        OID => fixed(bytes).map(|b| u32::from_be_bytes(b).to_string()),
+        FLOAT4 => fixed(bytes).map(|b| float_text(f32::from_be_bytes(b).to_string())),
+        FLOAT8 => fixed(bytes).map(|b| float_text(f64::from_be_bytes(b).to_string())),
+        NUMERIC => numeric_text(bytes),
+        MONEY => money_text(bytes),
+        TEXT | VARCHAR | BPCHAR | NAME | CHAR | JSON | XML | UNKNOWN => utf8(bytes),
+        // A version byte, then the JSON text.
+        JSONB => match bytes.split_first() {
+            Some((1, json)) => utf8(json),
+            _ => None,
+        },
+        BYTEA => Some(format!("\\x{}", hex::encode(bytes))),
+        DATE => date_text(bytes),
+        TIME => fixed(bytes).and_then(|b| time_text(i64::from_be_bytes(b))),
+        TIMETZ => timetz_text(bytes),
+        TIMESTAMP => timestamp(bytes).map(|t| t.map_or_else(str::to_string, |t| t.to_string())),
+        TIMESTAMPTZ => {
+            timestamp(bytes).map(|t| t.map_or_else(str::to_string, |t| t.and_utc().to_string()))
+        }
+        INTERVAL => interval_text(bytes),
+        UUID => uuid::Uuid::from_slice(bytes)
+            .ok()
+            .map(|uuid| uuid.to_string()),
+        INET | CIDR => inet_text(oid, bytes),
+        MACADDR | MACADDR8 => mac_text(bytes),
+        BIT | VARBIT => bit_text(bytes),
+        RECORD => record_text(bytes),
+        _ => {
+            if let Some(subtype) = range_subtype(oid) {
+                range_text(subtype, bytes)
+            } else if let Some(subtype) = multirange_subtype(oid) {
+                multirange_text(subtype, bytes)
+            } else {
+                // Enums and extension types such as `citext` are sent as text
+                printable(bytes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be32(value: i32) -> Vec<u8> {
+        value.to_be_bytes().to_vec()
+    }
+
+    fn be64(value: i64) -> Vec<u8> {
+        value.to_be_bytes().to_vec()
+    }
+
+    /// A length-prefixed field, as inside arrays, ranges and records.
+    fn field(bytes: &[u8]) -> Vec<u8> {
+        [be32(bytes.len() as i32), bytes.to_vec()].concat()
+    }
+
+    fn text(oid: u32, bytes: &[u8]) -> Option<String> {
+        format_value(oid, bytes)
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(text(BOOL, &[1]).as_deref(), Some("true"));
+        assert_eq!(text(INT2, &[0, 42]).as_deref(), Some("42"));
+        assert_eq!(
+            text(INT8, &be64(9_000_000_000)).as_deref(),
+            Some("9000000000")
+        );
+        assert_eq!(
+            text(FLOAT8, &f64::INFINITY.to_be_bytes()).as_deref(),
+            Some("Infinity")
+        );
+        assert_eq!(text(TEXT, b"plain").as_deref(), Some("plain"));
+        assert_eq!(text(JSONB, b"\x01{\"a\":1}").as_deref(), Some("{\"a\":1}"));
+        assert_eq!(text(BYTEA, &[0xde, 0xad]).as_deref(), Some("\\xdead"));
+        assert_eq!(text(INT4, &[0, 1]), None);
+    }
+
+    #[test]
+    fn numerics() {
+        // 123.4500 as numeric(10,4): digits 123 and 4500
+        let numeric = [0, 2, 0, 0, 0, 0, 0, 4, 0, 123, 0x11, 0x94];
+        assert_eq!(text(NUMERIC, &numeric).as_deref(), Some("123.4500"));
+        // -0.0005: one digit, 5, at weight -1
+        let small = [0, 1, 0xff, 0xff, 0x40, 0, 0, 4, 0, 5];
+        assert_eq!(text(NUMERIC, &small).as_deref(), Some("-0.0005"));
+        // 10000: one digit, 1, at weight 1
+        let large = [0, 1, 0, 1, 0, 0, 0, 0, 0, 1];
+        assert_eq!(text(NUMERIC, &large).as_deref(), Some("10000"));
+        assert_eq!(
+            text(NUMERIC, &[0, 0, 0, 0, 0xc0, 0, 0, 0]).as_deref(),
+            Some("NaN")
+        );
+    }
+
+    #[test]
+    fn money() {
+        assert_eq!(text(MONEY, &be64(123_456)).as_deref(), Some("1234.56"));
+        assert_eq!(text(MONEY, &be64(-5)).as_deref(), Some("-0.05"));
+    }
+
+    #[test]
+    fn dates_and_timestamps() {
+        assert_eq!(text(DATE, &be32(8887)).as_deref(), Some("2024-05-01"));
+        assert_eq!(text(DATE, &be32(i32::MAX)).as_deref(), Some("infinity"));
+        assert_eq!(text(DATE, &be32(-1)).as_deref(), Some("1999-12-31"));
+
+        let micros = 767_886_300_250_000;
+        assert_eq!(
+            text(TIMESTAMP, &be64(micros)).as_deref(),
+            Some("2024-05-01 13:45:00.250")
+        );
+        assert_eq!(
+            text(TIMESTAMPTZ, &be64(micros)).as_deref(),
+            Some("2024-05-01 13:45:00.250 UTC")
+        );
+        assert_eq!(
+            text(TIMESTAMPTZ, &be64(i64::MIN)).as_deref(),
+            Some("-infinity")
+        );
+    }
+
+    #[test]
+    fn times() {
+        let micros = 49_500_000_000;
+        assert_eq!(text(TIME, &be64(micros)).as_deref(), Some("13:45:00"));
+        assert_eq!(
+            text(TIME, &be64(86_400_000_000)).as_deref(),
+            Some("24:00:00")
+        );
+        // 13:45:00+02, the zone sent as seconds west
+        let timetz = [be64(micros), be32(-7200)].concat();
+        assert_eq!(text(TIMETZ, &timetz).as_deref(), Some("13:45:00+02"));
+        let timetz = [be64(micros), be32(19_800)].concat();
+        assert_eq!(text(TIMETZ, &timetz).as_deref(), Some("13:45:00-05:30"));
+    }
+
+    #[test]
+    fn intervals() {
+        let interval = |micros: i64, days: i32, months: i32| {
+            text(INTERVAL, &[be64(micros), be32(days), be32(months)].concat())
+        };
+        assert_eq!(
+            interval(14_706_500_000, 3, 14).as_deref(),
+            Some("1 year 2 mons 3 days 04:05:06.5")
+        );
+        assert_eq!(interval(0, 1, 0).as_deref(), Some("1 day"));
+        assert_eq!(
+            interval(-3_600_000_000, 0, -1).as_deref(),
+            Some("-1 mons -01:00:00")
+        );
+        assert_eq!(interval(0, 0, 0).as_deref(), Some("00:00:00"));
+    }
+
+    #[test]
+    fn uuids() {
+        let bytes: Vec<u8> = (0..16).collect();
+        assert_eq!(
+            text(UUID, &bytes).as_deref(),
+            Some("00010203-0405-0607-0809-0a0b0c0d0e0f")
+        );
+    }
+
+    #[test]
+    fn network_addresses() {
+        assert_eq!(
+            text(INET, &[2, 32, 0, 4, 192, 168, 0, 1]).as_deref(),
+            Some("192.168.0.1")
+        );
+        assert_eq!(
+            text(INET, &[2, 24, 0, 4, 192, 168, 0, 1]).as_deref(),
+            Some("192.168.0.1/24")
+        );
+        assert_eq!(
+            text(CIDR, &[2, 32, 1, 4, 10, 0, 0, 1]).as_deref(),
+            Some("10.0.0.1/32")
+        );
+        let mut v6 = vec![3, 64, 1, 16, 0x20, 0x01, 0x0d, 0xb8];
+        v6.extend([0; 12]);
+        assert_eq!(text(CIDR, &v6).as_deref(), Some("2001:db8::/64"));
+        assert_eq!(
+            text(MACADDR, &[0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]).as_deref(),
+            Some("08:00:2b:01:02:03")
+        );
+        assert_eq!(text(MACADDR, &[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn bit_strings() {
+        let bits = [be32(10), vec![0b1011_0000, 0b0100_0000]].concat();
+        assert_eq!(text(VARBIT, &bits).as_deref(), Some("1011000001"));
+        assert_eq!(text(BIT, &[be32(9), vec![0xff]].concat()), None);
+    }
+
+    #[test]
+    fn arrays() {
+        let int4s = [
+            be32(1),
+            be32(1),
+            be32(INT4 as i32),
+            be32(3),
+            be32(1),
+            field(&be32(1)),
+            be32(-1),
+            field(&be32(3)),
+        ]
+        .concat();
+        assert_eq!(format_array(&int4s).as_deref(), Some("{1,NULL,3}"));
+
+        let texts = [
+            be32(2),
+            be32(0),
+            be32(TEXT as i32),
+            be32(2),
+            be32(0),
+            be32(1),
+            be32(1),
+            field(b"a b"),
+            field(b"NULL"),
+        ]
+        .concat();
+        assert_eq!(
+            format_array(&texts).as_deref(),
+            Some("[0:1][1:1]={{\"a b\"},{\"NULL\"}}")
+        );
+
+        let empty = [be32(0), be32(0), be32(INT4 as i32)].concat();
+        assert_eq!(format_array(&empty).as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn ranges() {
+        let int4range = [
+            vec![RANGE_LOWER_INCLUSIVE],
+            field(&be32(1)),
+            field(&be32(10)),
+        ]
+        .concat();
+        assert_eq!(text(3904, &int4range).as_deref(), Some("[1,10)"));
+
+        let unbounded = [
+            vec![RANGE_LOWER_INFINITE | RANGE_UPPER_INCLUSIVE],
+            field(&be32(8887)),
+        ]
+        .concat();
+        assert_eq!(text(3912, &unbounded).as_deref(), Some("(,2024-05-01]"));
+        assert_eq!(text(3910, &[RANGE_EMPTY]).as_deref(), Some("empty"));
+
+        let tsrange = [
+            vec![RANGE_LOWER_INCLUSIVE | RANGE_UPPER_INFINITE],
+            field(&be64(0)),
+        ]
+        .concat();
+        assert_eq!(
+            text(3908, &tsrange).as_deref(),
+            Some("[\"2000-01-01 00:00:00\",)")
+        );
+
+        let multirange = [be32(2), field(&int4range), field(&[RANGE_EMPTY])].concat();
+        assert_eq!(text(4451, &multirange).as_deref(), Some("{[1,10),empty}"));
+    }
+
+    #[test]
+    fn records_and_unknown_types() {
+        let record = [
+            be32(3),
+            be32(INT4 as i32),
+            field(&be32(1)),
+            be32(TEXT as i32),
+            field(b"two words"),
+            be32(TEXT as i32),
+            be32(-1),
+        ]
+        .concat();
+        assert_eq!(text(RECORD, &record).as_deref(), Some("(1,\"two words\",)"));
+
+        // An enum label reads as text; binary data of an unknown type does not
+        assert_eq!(text(90_000, b"happy").as_deref(), Some("happy"));
+        assert_eq!(text(90_000, &[1, 0, 2]), None);
+    }
+}
@@ -0,0 +1,43 @@
+//! Client sessions from `pg_stat_activity`.
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::services::database::activity::SessionActivity;
+
+/// Client sessions of the current database other than this one, longest
+/// open transaction first.
+pub async fn sessions(pool: &PgPool) -> Result<Vec<SessionActivity>> {
+    let query = r#"
+        SELECT
+            pid,
+            usename::text AS username,
+            application_name,
+            host(client_addr) AS client_addr,
+            state,
+            query,
+            EXTRACT(EPOCH FROM now() - xact_start)::float8 AS transaction_secs,
+            EXTRACT(EPOCH FROM now() - state_change)::float8 AS state_secs
+        FROM pg_stat_activity
+        WHERE datname = current_database()
+          AND pid <> pg_backend_pid()
+          AND backend_type = 'client backend'
+        ORDER BY xact_start NULLS LAST, pid
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| SessionActivity {
+            pid: row.get("pid"),
+            username: row.get("username"),
+            application_name: row.get("application_name"),
+            client_addr: row.get("client_addr"),
+            state: row.get("state"),
+            query: row.get("query"),
+            transaction_secs: row.get("transaction_secs"),
+            state_secs: row.get("state_secs"),
+        })
+        .collect())
+}
@@ -0,0 +1,171 @@
+//! PostgreSQL side of the cross-connection data comparison.
+
+use anyhow::{Result, anyhow};
+use sqlx::{PgPool, Row};
+
+use crate::services::database::compare::{CompareColumns, RowHash, SyncRow};
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+/// Returns `(primary key columns with their types, all column names)`.
+/// Key types are named by `format_type` without a modifier, so they can be
+/// cast to whatever the column is: arrays, domains and types outside the
+/// search path included, and `varchar(n)` without truncating.
+pub async fn table_columns(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<(Vec<(String, String)>, Vec<String>)> {
+    let columns = super::schema::fetch_table_columns(table_name, table_schema, pool).await?;
+    if columns.is_empty() {
+        return Err(anyhow!("Table {}.{} not found", table_schema, table_name));
+    }
+
+    let keys: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT a.attname::text, format_type(a.atttypid, NULL)
+        FROM pg_index i
+        JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
+        WHERE i.indrelid = format('%I.%I', $1, $2)::regclass
+          AND i.indisprimary
+        ORDER BY array_position(i.indkey::int2[], a.attnum)
+        "#,
+    )
+    .bind(table_schema)
+    .bind(table_name)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((keys, columns.into_iter().map(|c| c.column_name).collect()))
+}
+
+/// Fetch the next batch of `(key, md5(row))` pairs ordered by primary key,
+/// starting strictly after `after` and ending at `until` when given.
+pub async fn fetch_row_hashes(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+    cols: &CompareColumns,
+    after: Option<&[String]>,
+    until: Option<&[String]>,
+    limit: usize,
+) -> Result<Vec<RowHash>> {
+    let keys = quoted(&cols.key_columns);
+    let key_count = cols.key_columns.len();
+
+    let mut sql = format!(
+        "SELECT {}, md5(ROW({})::text) AS row_hash FROM {}.{}",
+        keys.iter()
+            .map(|k| format!("{}::text", k))
+            .collect::<Vec<_>>()
+            .join(", "),
+        quoted(&cols.columns).join(", "),
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name),
+    );
+    let mut bounds = Vec::new();
+    if after.is_some() {
+        bounds.push(format!(
+            "({}) > ({})",
+            keys.join(", "),
+            key_casts(cols, 1).join(", ")
+        ));
+    }
+    if until.is_some() {
+        bounds.push(format!(
+            "({}) <= ({})",
+            keys.join(", "),
+            key_casts(cols, bounds.len() * key_count + 1).join(", ")
+        ));
+    }
+    if !bounds.is_empty() {
+        sql.push_str(&format!(" WHERE {}", bounds.join(" AND ")));
+    }
+    sql.push_str(&format!(" ORDER BY {} LIMIT {}", keys.join(", "), limit));
+
+    let mut query = sqlx::query(&sql);
+    for value in after.into_iter().chain(until).flatten() {
+        query = query.bind(value);
+    }
+
+    let rows = query.fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            let key = (0..key_count)
+                .map(|i| row.try_get::<String, _>(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(RowHash {
+                key,
+                hash: row.try_get("row_hash")?,
+            })
+        })
+        .collect()
+}
+
+/// Fetch full rows (as text) for the given primary keys.
+pub async fn fetch_rows(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+    cols: &CompareColumns,
+    keys: &[Vec<String>],
+) -> Result<Vec<SyncRow>> {
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let key_count = cols.key_columns.len();
+    let tuples = (0..keys.len())
+        .map(|i| format!("({})", key_casts(cols, i * key_count + 1).join(", ")))
+        .collect::<Vec<_>>();
+
+    let sql = format!(
+        "SELECT {} FROM {}.{} WHERE ({}) IN ({})",
+        quoted(&cols.columns)
+            .iter()
+            .map(|c| format!("{}::text", c))
+            .collect::<Vec<_>>()
+            .join(", "),
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name),
+        quoted(&cols.key_columns).join(", "),
+        tuples.join(", "),
+    );
+
+    let mut query = sqlx::query(&sql);
+    for key in keys {
+        for value in key {
+            query = query.bind(value);
+        }
+    }
+
+    let rows = query.fetch_all(pool).await?;
+    rows.into_iter()
+        .map(|row| {
+            let values = (0..cols.columns.len())
+                .map(|i| row.try_get::<Option<String>, _>(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            let key = values[..key_count]
+                .iter()
+                .map(|v| v.clone().unwrap_or_default())
+                .collect();
+            Ok(SyncRow { key, values })
+        })
+        .collect()
+}
+
+fn quoted(columns: &[String]) -> Vec<String> {
+    columns.iter().map(|c| DRIVER.quote_ident(c)).collect()
+}
+
+/// `CAST($n AS <type>)` placeholders for each key column, numbered from
+/// `first`. Keys travel as text and are cast back so comparisons use the
+/// column's native ordering (and its index).
+fn key_casts(cols: &CompareColumns, first: usize) -> Vec<String> {
+    cols.key_types
+        .iter()
+        .enumerate()
+        .map(|(i, ty)| format!("CAST(${} AS {})", first + i, ty))
+        .collect()
+}
@@ -0,0 +1,77 @@
+//! PostgreSQL extension introspection.
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::services::database::extensions::{ExtensionInfo, ExtensionObject};
+
+/// Extensions the server can install, with the installed version for the
+/// ones present in the current database.
+pub async fn list_extensions(pool: &PgPool) -> Result<Vec<ExtensionInfo>> {
+    let query = r#"
+        SELECT
+            a.name::text AS name,
+            a.default_version,
+            a.installed_version,
+            n.nspname::text AS schema,
+            a.comment
+        FROM pg_available_extensions a
+        LEFT JOIN pg_extension e ON e.extname = a.name
+        LEFT JOIN pg_namespace n ON n.oid = e.extnamespace
+        ORDER BY a.installed_version IS NULL, a.name
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ExtensionInfo {
+            name: row.get("name"),
+            default_version: row.get("default_version"),
+            installed_version: row.get("installed_version"),
+            schema: row.get("schema"),
+            comment: row.get("comment"),
+        })
+        .collect())
+}
+
+/// Relations and functions that belong to an installed extension.
+pub async fn list_extension_objects(pool: &PgPool) -> Result<Vec<ExtensionObject>> {
+    let query = r#"
+        SELECT
+            e.extname::text AS extension,
+            n.nspname::text AS object_schema,
+            c.relname::text AS object_name
+        FROM pg_depend d
+        JOIN pg_extension e ON e.oid = d.refobjid
+        JOIN pg_class c ON c.oid = d.objid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE d.deptype = 'e'
+            AND d.refclassid = 'pg_extension'::regclass
+            AND d.classid = 'pg_class'::regclass
+        UNION
+        SELECT
+            e.extname::text,
+            n.nspname::text,
+            p.proname::text
+        FROM pg_depend d
+        JOIN pg_extension e ON e.oid = d.refobjid
+        JOIN pg_proc p ON p.oid = d.objid
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE d.deptype = 'e'
+            AND d.refclassid = 'pg_extension'::regclass
+            AND d.classid = 'pg_proc'::regclass
+        ORDER BY 1, 2, 3
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ExtensionObject {
+            extension: row.get("extension"),
+            object_schema: row.get("object_schema"),
+            object_name: row.get("object_name"),
+        })
+        .collect())
+}
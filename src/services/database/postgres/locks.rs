@@ -0,0 +1,71 @@
+//! Sessions involved in lock waits, and signals to stop them.
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::services::database::locks::{BackendSignal, LockSession};
+
+/// Sessions of the current database that wait on a lock or hold one
+/// another session waits on.
+pub async fn lock_sessions(pool: &PgPool) -> Result<Vec<LockSession>> {
+    let query = r#"
+        WITH involved AS (
+            SELECT a.pid, pg_blocking_pids(a.pid) AS blocked_by
+            FROM pg_stat_activity a
+            WHERE a.datname = current_database()
+        )
+        SELECT
+            a.pid,
+            a.usename::text AS username,
+            a.application_name,
+            a.state,
+            a.query,
+            EXTRACT(EPOCH FROM now() - COALESCE(a.xact_start, a.query_start))::float8
+                AS duration_secs,
+            i.blocked_by,
+            COALESCE(
+                (
+                    SELECT array_agg(DISTINCT
+                        l.mode
+                        || COALESCE(' on ' || l.relation::regclass::text, '')
+                        || CASE WHEN l.granted THEN '' ELSE ' (waiting)' END)
+                    FROM pg_locks l
+                    WHERE l.pid = a.pid AND l.locktype IN ('relation', 'tuple', 'transactionid')
+                ),
+                '{}'
+            ) AS locks
+        FROM pg_stat_activity a
+        JOIN involved i ON i.pid = a.pid
+        WHERE cardinality(i.blocked_by) > 0
+           OR a.pid IN (SELECT unnest(blocked_by) FROM involved)
+        ORDER BY a.pid
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| LockSession {
+            pid: row.get("pid"),
+            username: row.get("username"),
+            application_name: row.get("application_name"),
+            state: row.get("state"),
+            query: row.get("query"),
+            duration_secs: row.get("duration_secs"),
+            blocked_by: row.get("blocked_by"),
+            locks: row.get("locks"),
+        })
+        .collect())
+}
+
+/// Send `signal` to the backend `pid`. Returns whether it was delivered.
+pub async fn signal_backend(pool: &PgPool, pid: i32, signal: BackendSignal) -> Result<bool> {
+    let query = match signal {
+        BackendSignal::Cancel => "SELECT pg_cancel_backend($1)",
+        BackendSignal::Terminate => "SELECT pg_terminate_backend($1)",
+    };
+    Ok(sqlx::query_scalar::<_, bool>(query)
+        .bind(pid)
+        .fetch_one(pool)
+        .await?)
+}
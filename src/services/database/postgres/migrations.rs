@@ -0,0 +1,75 @@
+//! Applying migrations and reading the tracking table.
+
+use std::time::Instant;
+
+use anyhow::{Context as _, Result};
+use sqlx::{PgPool, Row};
+
+use crate::services::database::migrations::{AppliedMigration, MigrationFile, TrackingTable};
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+/// The recorded migrations, none when the tracking table does not exist
+/// yet.
+pub async fn applied_migrations(
+    pool: &PgPool,
+    table: &TrackingTable,
+) -> Result<Vec<AppliedMigration>> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (
+            SELECT 1 FROM information_schema.tables
+            WHERE table_schema = COALESCE($1, current_schema()) AND table_name = $2
+        )",
+    )
+    .bind(table.schema.as_deref())
+    .bind(&table.name)
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        "SELECT version, description, checksum, applied_at::text AS applied_at FROM {}",
+        table.quoted(&DRIVER)
+    );
+    let rows = sqlx::query(&sql).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .map(|row| AppliedMigration {
+            version: row.get("version"),
+            description: row.get("description"),
+            checksum: row.get("checksum"),
+            applied_at: row.get("applied_at"),
+        })
+        .collect())
+}
+
+/// Run a migration and record it in one transaction, so a failing file
+/// leaves neither its changes nor a record behind. Returns how long it
+/// took in milliseconds.
+pub async fn apply_migration(
+    pool: &PgPool,
+    table: &TrackingTable,
+    file: &MigrationFile,
+) -> Result<u128> {
+    sqlx::query(&table.create_sql(&DRIVER))
+        .execute(pool)
+        .await
+        .with_context(|| format!("Failed to create {}", table))?;
+
+    let start = Instant::now();
+    let mut tx = pool.begin().await?;
+    sqlx::raw_sql(&file.sql).execute(&mut *tx).await?;
+    let elapsed = start.elapsed().as_millis();
+    sqlx::query(&table.insert_sql(&DRIVER))
+        .bind(&file.version)
+        .bind(&file.description)
+        .bind(&file.checksum)
+        .bind(elapsed as i64)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(elapsed)
+}
@@ -1,4 +1,14 @@
 //! PostgreSQL backend implementation.
 
+pub mod activity;
+pub mod compare;
+pub mod extensions;
+pub mod locks;
+pub mod migrations;
+pub mod performance;
 pub mod query;
+pub mod roles;
 pub mod schema;
+pub mod sequences;
+pub mod server_settings;
+pub mod tablespaces;
@@ -0,0 +1,138 @@
+//! PostgreSQL statement statistics and table scan counters.
+
+use anyhow::{Result, anyhow};
+use sqlx::{PgPool, Row};
+
+use crate::services::database::performance::{
+    PerformanceReport, StatementStats, TableScanStats, suggest_indexes,
+};
+
+/// Whether `pg_stat_statements` is installed in the connected database.
+pub async fn has_pg_stat_statements(pool: &PgPool) -> Result<bool> {
+    let query = "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'pg_stat_statements')";
+    Ok(sqlx::query_scalar::<_, bool>(query).fetch_one(pool).await?)
+}
+
+/// Statements of the current database by total execution time.
+pub async fn top_statements(pool: &PgPool, limit: usize) -> Result<Vec<StatementStats>> {
+    // The timing columns were renamed in Postgres 13.
+    let version: i32 = sqlx::query_scalar("SELECT current_setting('server_version_num')::int")
+        .fetch_one(pool)
+        .await?;
+    let (total, mean) = if version >= 130000 {
+        ("total_exec_time", "mean_exec_time")
+    } else {
+        ("total_time", "mean_time")
+    };
+
+    let query = format!(
+        r#"
+        SELECT
+            s.query,
+            s.calls,
+            s.{total}::float8 AS total_ms,
+            s.{mean}::float8 AS mean_ms,
+            s.rows,
+            s.shared_blks_hit,
+            s.shared_blks_read
+        FROM pg_stat_statements s
+        JOIN pg_database d ON d.oid = s.dbid
+        WHERE d.datname = current_database()
+        ORDER BY s.{total} DESC
+        LIMIT $1
+        "#
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| StatementStats {
+            query: row.get("query"),
+            calls: row.get("calls"),
+            total_ms: row.get("total_ms"),
+            mean_ms: row.get("mean_ms"),
+            rows: row.get("rows"),
+            shared_blks_hit: row.get("shared_blks_hit"),
+            shared_blks_read: row.get("shared_blks_read"),
+        })
+        .collect())
+}
+
+/// Scan counters of user tables, with the leading column of each index.
+pub async fn table_scan_stats(pool: &PgPool) -> Result<Vec<TableScanStats>> {
+    let query = r#"
+        SELECT
+            s.schemaname::text AS table_schema,
+            s.relname::text AS table_name,
+            COALESCE(s.seq_scan, 0) AS seq_scan,
+            COALESCE(s.seq_tup_read, 0) AS seq_tup_read,
+            COALESCE(s.idx_scan, 0) AS idx_scan,
+            COALESCE(s.n_live_tup, 0) AS live_rows,
+            COALESCE(
+                array_agg(a.attname::text) FILTER (WHERE a.attname IS NOT NULL),
+                '{}'
+            ) AS indexed_columns
+        FROM pg_stat_user_tables s
+        LEFT JOIN pg_index i ON i.indrelid = s.relid
+        LEFT JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = i.indkey[0]
+        GROUP BY s.schemaname, s.relname, s.seq_scan, s.seq_tup_read, s.idx_scan, s.n_live_tup
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TableScanStats {
+            table_schema: row.get("table_schema"),
+            table_name: row.get("table_name"),
+            seq_scan: row.get("seq_scan"),
+            seq_tup_read: row.get("seq_tup_read"),
+            idx_scan: row.get("idx_scan"),
+            live_rows: row.get("live_rows"),
+            indexed_columns: row.get("indexed_columns"),
+        })
+        .collect())
+}
+
+async fn require_pg_stat_statements(pool: &PgPool) -> Result<()> {
+    if !has_pg_stat_statements(pool).await? {
+        return Err(anyhow!(
+            "pg_stat_statements is not installed. Add it to shared_preload_libraries \
+             and run CREATE EXTENSION pg_stat_statements;"
+        ));
+    }
+    Ok(())
+}
+
+/// Statement statistics of the current database, once the extension is
+/// known to be installed.
+pub async fn statement_stats(pool: &PgPool, limit: usize) -> Result<Vec<StatementStats>> {
+    require_pg_stat_statements(pool).await?;
+    top_statements(pool, limit).await
+}
+
+/// Discard all statistics gathered by `pg_stat_statements`.
+pub async fn reset_statement_stats(pool: &PgPool) -> Result<()> {
+    require_pg_stat_statements(pool).await?;
+    sqlx::query("SELECT pg_stat_statements_reset()")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn performance_report(pool: &PgPool, limit: usize) -> Result<PerformanceReport> {
+    require_pg_stat_statements(pool).await?;
+
+    let statements = top_statements(pool, limit).await?;
+    let tables = table_scan_stats(pool).await?;
+    let suggestions = suggest_indexes(&statements, &tables);
+
+    Ok(PerformanceReport {
+        statements,
+        suggestions,
+    })
+}
@@ -1,23 +1,38 @@
 //! PostgreSQL query execution and row → `QueryResult` conversion.
 
 use sqlx::postgres::types::Oid;
-use sqlx::postgres::{PgColumn, PgRow};
+use sqlx::postgres::{PgColumn, PgRow, PgTypeKind, PgValueFormat};
 use sqlx::query::Query;
-use sqlx::{Column, Execute as _, PgPool, Row, TypeInfo, ValueRef};
+use sqlx::{
+    Acquire, Column, Either, Execute as _, Executor as _, PgConnection, PgPool, Postgres, Row,
+    TypeInfo, ValueRef,
+};
 use std::collections::{HashMap, HashSet};
 
+use crate::services::database::pg_wire::{format_array, format_value};
+use crate::services::database::row_limit::{
+    PlanEstimate, limited_sql, parse_plan_cost, parse_plan_rows, truncate_rows,
+};
+use crate::services::database::tagging::skip_leading_comments;
 use crate::services::database::types::{
-    ErrorResult, ModifiedResult, QueryExecutionResult, QueryResult, ResultCell,
-    ResultColumnMetadata, ResultRow,
+    CellValue, ErrorResult, ModifiedResult, QueryExecutionResult, QueryResult,
+    ResultColumnMetadata, ResultRows, RowSource,
 };
 
-/// Internal: maps OID -> qualified table name and (OID, column) -> nullable.
+/// Internal: maps OID -> qualified table name and (OID, attnum) -> the
+/// column's name and whether it is nullable.
 pub(crate) struct TableMetadata {
     pub oid_to_table_name: HashMap<Oid, String>,
-    pub column_nullable_map: HashMap<(Oid, String), bool>,
+    pub column_nullable_map: HashMap<(Oid, i16), (String, bool)>,
 }
 
-pub async fn execute(pool: &PgPool, sql: &str) -> QueryExecutionResult {
+/// Run `sql`, fetching at most `max_rows` rows of a SELECT without its own
+/// LIMIT. `db` is the pool, or a connection held across statements so
+/// they share its session.
+pub async fn execute<'a, A>(db: A, sql: &str, max_rows: Option<usize>) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = Postgres>,
+{
     let sql = sql.trim();
     if sql.is_empty() {
         return QueryExecutionResult::Error(ErrorResult {
@@ -26,16 +41,27 @@ pub async fn execute(pool: &PgPool, sql: &str) -> QueryExecutionResult {
         });
     }
 
+    let mut conn = match db.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return query_failed(e, std::time::Instant::now()),
+    };
     if is_select_query(sql) {
-        execute_select_query(sql, pool).await
+        execute_select_query(sql, &mut *conn, max_rows).await
     } else {
-        execute_modification_query(sql, pool).await
+        execute_modification_query(sql, &mut *conn).await
     }
 }
 
-async fn execute_modification_query(sql: &str, pool: &PgPool) -> QueryExecutionResult {
+fn query_failed(e: sqlx::Error, start_time: std::time::Instant) -> QueryExecutionResult {
+    QueryExecutionResult::Error(ErrorResult {
+        message: format!("Query failed: {}", e),
+        execution_time_ms: start_time.elapsed().as_millis(),
+    })
+}
+
+async fn execute_modification_query(sql: &str, conn: &mut PgConnection) -> QueryExecutionResult {
     let start_time = std::time::Instant::now();
-    match sqlx::query(sql).execute(pool).await {
+    match sqlx::query(sql).execute(conn).await {
         Ok(result) => QueryExecutionResult::Modified(ModifiedResult {
             rows_affected: result.rows_affected(),
             execution_time_ms: start_time.elapsed().as_millis(),
@@ -47,14 +73,145 @@ async fn execute_modification_query(sql: &str, pool: &PgPool) -> QueryExecutionR
     }
 }
 
-pub(crate) async fn execute_internal(
+/// Execute a data-modifying statement with text bind parameters
+/// (`None` binds NULL).
+pub async fn execute_params<'a, A>(
+    db: A,
+    sql: &str,
+    params: &[Option<String>],
+) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    let start_time = std::time::Instant::now();
+    let mut conn = match db.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return query_failed(e, start_time),
+    };
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = query.bind(param.clone());
+    }
+
+    match query.execute(&mut *conn).await {
+        Ok(result) => QueryExecutionResult::Modified(ModifiedResult {
+            rows_affected: result.rows_affected(),
+            execution_time_ms: start_time.elapsed().as_millis(),
+        }),
+        Err(e) => QueryExecutionResult::Error(ErrorResult {
+            message: format!("Query failed: {}", e),
+            execution_time_ms: start_time.elapsed().as_millis(),
+        }),
+    }
+}
+
+/// Execute a query with text bind parameters, fetching the rows of a
+/// SELECT. Unlike `execute`, no row limit is added.
+pub async fn execute_bound<'a, A>(
+    db: A,
+    sql: &str,
+    params: &[Option<String>],
+) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    if !is_select_query(sql) {
+        return execute_params(db, sql, params).await;
+    }
+
+    let mut query = sqlx::query(sql);
+    for param in params {
+        query = query.bind(param.clone());
+    }
+    execute_internal(query, db).await
+}
+
+/// Parameter types Postgres infers for `sql`, by parameter number, as
+/// `format_type` names them without a modifier: `bpchar` rather than
+/// `character`, which a cast would read as `character(1)`. `None` where
+/// the server cannot tell.
+pub async fn describe_params(pool: &PgPool, sql: &str) -> anyhow::Result<Vec<Option<String>>> {
+    let describe = pool.describe(sql).await?;
+    let oids: Vec<Option<Oid>> = match describe.parameters() {
+        Some(Either::Left(types)) => types
+            .iter()
+            .map(|ty| ty.oid().filter(|_| !ty.is_null() && ty.name() != "UNKNOWN"))
+            .collect(),
+        Some(Either::Right(count)) => vec![None; count],
+        None => vec![],
+    };
+    let known: Vec<Oid> = oids.iter().flatten().copied().collect();
+    if known.is_empty() {
+        return Ok(vec![None; oids.len()]);
+    }
+
+    let names: HashMap<Oid, String> = sqlx::query_as::<_, (Oid, String)>(
+        "SELECT t.oid, format_type(t.oid, NULL) FROM unnest($1::oid[]) AS t(oid)",
+    )
+    .bind(&known)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    Ok(oids
+        .iter()
+        .map(|oid| oid.and_then(|oid| names.get(&oid).cloned()))
+        .collect())
+}
+
+/// Cancel the statements whose text contains `tag`, other than this
+/// one. Returns whether any was signalled.
+pub async fn cancel_tagged(pool: &PgPool, tag: &str) -> anyhow::Result<bool> {
+    let cancelled: Vec<bool> = sqlx::query_scalar(
+        r#"
+        SELECT pg_cancel_backend(pid)
+        FROM pg_stat_activity
+        WHERE pid <> pg_backend_pid()
+          AND position($1 in query) > 0
+        "#,
+    )
+    .bind(tag)
+    .fetch_all(pool)
+    .await?;
+    Ok(cancelled.into_iter().any(|c| c))
+}
+
+/// The planner's estimate of the rows `sql` returns, from `EXPLAIN`
+/// (which reads `reltuples` and column statistics). Nothing is run.
+pub async fn estimate_rows(pool: &PgPool, sql: &str) -> anyhow::Result<Option<u64>> {
+    let plan: Option<String> = sqlx::query_scalar(&format!("EXPLAIN {}", sql))
+        .fetch_optional(pool)
+        .await?;
+    Ok(plan.as_deref().and_then(parse_plan_rows))
+}
+
+/// The planner's cost and row estimates for `sql`, from the top node of
+/// its `EXPLAIN`. Nothing is run.
+pub async fn estimate_plan(pool: &PgPool, sql: &str) -> anyhow::Result<PlanEstimate> {
+    let plan: Option<String> = sqlx::query_scalar(&format!("EXPLAIN {}", sql))
+        .fetch_optional(pool)
+        .await?;
+    Ok(PlanEstimate {
+        cost: plan.as_deref().and_then(parse_plan_cost),
+        rows: plan.as_deref().and_then(parse_plan_rows),
+    })
+}
+
+pub(crate) async fn execute_internal<'a, A>(
     query: Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments>,
-    pool: &PgPool,
-) -> QueryExecutionResult {
+    db: A,
+) -> QueryExecutionResult
+where
+    A: Acquire<'a, Database = Postgres>,
+{
     let start_time = std::time::Instant::now();
     let original_query = query.sql().to_string();
+    let mut conn = match db.acquire().await {
+        Ok(conn) => conn,
+        Err(e) => return query_failed(e, start_time),
+    };
 
-    match query.fetch_all(pool).await {
+    match query.fetch_all(&mut *conn).await {
         Ok(rows) => {
             let execution_time = start_time.elapsed().as_millis();
 
@@ -62,22 +219,24 @@ pub(crate) async fn execute_internal(
                 return QueryExecutionResult::Select(QueryResult {
                     original_query,
                     columns: vec![],
-                    rows: vec![],
+                    rows: ResultRows::default(),
                     row_count: 0,
                     execution_time_ms: execution_time,
+                    truncated_at: None,
                 });
             }
 
-            let metadata = fetch_table_metadata(&rows, pool).await;
+            let metadata = fetch_table_metadata(&rows, &mut *conn).await;
             let columns = build_column_metadata(&rows[0], &metadata);
-            let result_rows = convert_rows(&rows, &metadata);
+            let row_count = rows.len();
 
             QueryExecutionResult::Select(QueryResult {
                 original_query,
                 columns,
-                rows: result_rows,
-                row_count: rows.len(),
+                rows: ResultRows::new(PgRows(rows)),
+                row_count,
                 execution_time_ms: execution_time,
+                truncated_at: None,
             })
         }
         Err(e) => QueryExecutionResult::Error(ErrorResult {
@@ -87,40 +246,46 @@ pub(crate) async fn execute_internal(
     }
 }
 
-async fn execute_select_query(sql: &str, pool: &PgPool) -> QueryExecutionResult {
+async fn execute_select_query(
+    sql: &str,
+    conn: &mut PgConnection,
+    max_rows: Option<usize>,
+) -> QueryExecutionResult {
     let start_time = std::time::Instant::now();
     let original_query = sql.to_string();
 
-    let limited_sql = if !sql.to_lowercase().contains(" limit ") {
-        format!("{} LIMIT {}", sql.trim_end_matches(';'), 1_000)
-    } else {
-        sql.to_string()
-    };
+    let (limited_sql, max_rows) = limited_sql(sql, max_rows);
 
-    match sqlx::query(limited_sql.as_ref()).fetch_all(pool).await {
-        Ok(rows) => {
+    match sqlx::query(limited_sql.as_ref())
+        .fetch_all(&mut *conn)
+        .await
+    {
+        Ok(mut rows) => {
             let execution_time = start_time.elapsed().as_millis();
+            let truncated_at = truncate_rows(&mut rows, max_rows);
 
             if rows.is_empty() {
                 return QueryExecutionResult::Select(QueryResult {
                     original_query,
                     columns: vec![],
-                    rows: vec![],
+                    rows: ResultRows::default(),
                     row_count: 0,
                     execution_time_ms: execution_time,
+                    truncated_at: None,
                 });
             }
 
-            let metadata = fetch_table_metadata(&rows, pool).await;
+            let metadata = fetch_table_metadata(&rows, conn).await;
             let columns = build_column_metadata(&rows[0], &metadata);
-            let result_rows = convert_rows(&rows, &metadata);
+            let row_count = rows.len();
 
             QueryExecutionResult::Select(QueryResult {
                 original_query,
                 columns,
-                rows: result_rows,
-                row_count: rows.len(),
+                rows: ResultRows::new(PgRows(rows)),
+                row_count,
                 execution_time_ms: execution_time,
+                truncated_at,
             })
         }
         Err(e) => QueryExecutionResult::Error(ErrorResult {
@@ -131,12 +296,11 @@ async fn execute_select_query(sql: &str, pool: &PgPool) -> QueryExecutionResult
 }
 
 fn is_select_query(sql: &str) -> bool {
-    let lower = sql.to_lowercase();
-    let trimmed = lower.trim_start();
+    let trimmed = skip_leading_comments(sql).to_lowercase();
     trimmed.starts_with("select") || trimmed.starts_with("with")
 }
 
-async fn fetch_table_metadata(rows: &[PgRow], pool: &PgPool) -> TableMetadata {
+async fn fetch_table_metadata(rows: &[PgRow], conn: &mut PgConnection) -> TableMetadata {
     let mut relation_oids = HashSet::new();
 
     for col in rows[0].columns() {
@@ -146,16 +310,16 @@ async fn fetch_table_metadata(rows: &[PgRow], pool: &PgPool) -> TableMetadata {
     }
 
     let mut oid_to_table_name: HashMap<Oid, String> = HashMap::new();
-    let mut column_nullable_map: HashMap<(Oid, String), bool> = HashMap::new();
+    let mut column_nullable_map: HashMap<(Oid, i16), (String, bool)> = HashMap::new();
 
     for oid in relation_oids {
-        if let Some(table_name) = fetch_table_name(oid, pool).await {
+        if let Some(table_name) = fetch_table_name(oid, conn).await {
             oid_to_table_name.insert(oid, table_name);
         }
 
-        if let Ok(nullable_info) = fetch_nullable_info(oid, pool).await {
-            for (col_name, is_nullable) in nullable_info {
-                column_nullable_map.insert((oid, col_name), is_nullable);
+        if let Ok(nullable_info) = fetch_nullable_info(oid, conn).await {
+            for (attnum, col_name, is_nullable) in nullable_info {
+                column_nullable_map.insert((oid, attnum), (col_name, is_nullable));
             }
         }
     }
@@ -166,7 +330,7 @@ async fn fetch_table_metadata(rows: &[PgRow], pool: &PgPool) -> TableMetadata {
     }
 }
 
-async fn fetch_table_name(oid: Oid, pool: &PgPool) -> Option<String> {
+async fn fetch_table_name(oid: Oid, conn: &mut PgConnection) -> Option<String> {
     let query = r#"
         SELECT n.nspname || '.' || c.relname as full_name
         FROM pg_class c
@@ -176,32 +340,41 @@ async fn fetch_table_name(oid: Oid, pool: &PgPool) -> Option<String> {
 
     sqlx::query(query)
         .bind(&oid)
-        .fetch_one(pool)
+        .fetch_one(conn)
         .await
         .ok()?
         .try_get::<String, _>(0)
         .ok()
 }
 
-async fn fetch_nullable_info(oid: Oid, pool: &PgPool) -> Result<Vec<(String, bool)>, sqlx::Error> {
+async fn fetch_nullable_info(
+    oid: Oid,
+    conn: &mut PgConnection,
+) -> Result<Vec<(i16, String, bool)>, sqlx::Error> {
     let query = r#"
-        SELECT attname, NOT attnotnull as is_nullable
+        SELECT attnum, attname, NOT attnotnull as is_nullable
         FROM pg_attribute
         WHERE attrelid = $1
         AND attnum > 0
         AND NOT attisdropped
     "#;
 
-    let rows = sqlx::query(query).bind(&oid).fetch_all(pool).await?;
+    let rows = sqlx::query(query).bind(&oid).fetch_all(conn).await?;
 
     Ok(rows
         .iter()
-        .filter_map(
-            |row| match (row.try_get::<String, _>(0), row.try_get::<bool, _>(1)) {
-                (Ok(col_name), Ok(is_nullable)) => Some((col_name, is_nullable)),
+        .filter_map(|row| {
+            match (
+                row.try_get::<i16, _>(0),
+                row.try_get::<String, _>(1),
+                row.try_get::<bool, _>(2),
+            ) {
+                (Ok(attnum), Ok(col_name), Ok(is_nullable)) => {
+                    Some((attnum, col_name, is_nullable))
+                }
                 _ => None,
-            },
-        )
+            }
+        })
         .collect())
 }
 
@@ -215,118 +388,98 @@ fn build_column_metadata(first_row: &PgRow, metadata: &TableMetadata) -> Vec<Res
                 .relation_id()
                 .and_then(|oid| metadata.oid_to_table_name.get(&oid).cloned());
 
-            let is_nullable = col.relation_id().and_then(|oid| {
-                metadata
-                    .column_nullable_map
-                    .get(&(oid, col.name().to_string()))
-                    .copied()
-            });
+            // Looked up by attribute number, as the result name may be an alias
+            let source = col
+                .relation_id()
+                .zip(col.relation_attribute_no())
+                .and_then(|key| metadata.column_nullable_map.get(&key));
 
             ResultColumnMetadata {
                 name: col.name().to_string(),
                 type_name: col.type_info().name().to_string(),
                 ordinal,
                 table_name,
-                is_nullable,
+                is_nullable: source.map(|(_, is_nullable)| *is_nullable),
+                source_column: source.map(|(name, _)| name.clone()),
             }
         })
         .collect()
 }
 
-fn convert_rows(rows: &[PgRow], metadata: &TableMetadata) -> Vec<ResultRow> {
-    rows.iter().map(|row| convert_row(row, metadata)).collect()
-}
+/// Rows as fetched, decoded when a cell is read.
+struct PgRows(Vec<PgRow>);
 
-fn convert_row(row: &PgRow, metadata: &TableMetadata) -> ResultRow {
-    let cells = row
-        .columns()
-        .iter()
-        .enumerate()
-        .map(|(i, column)| convert_cell(row, column, i, metadata))
-        .collect();
-
-    ResultRow { cells }
-}
+impl RowSource for PgRows {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
 
-fn build_cell_column_metadata(
-    column: &PgColumn,
-    ordinal: usize,
-    metadata: &TableMetadata,
-) -> ResultColumnMetadata {
-    let table_name = column
-        .relation_id()
-        .and_then(|oid| metadata.oid_to_table_name.get(&oid).cloned());
-
-    let is_nullable = column.relation_id().and_then(|oid| {
-        metadata
-            .column_nullable_map
-            .get(&(oid, column.name().to_string()))
-            .copied()
-    });
-
-    ResultColumnMetadata {
-        name: column.name().to_string(),
-        type_name: column.type_info().name().to_string(),
-        ordinal,
-        table_name,
-        is_nullable,
+    fn cell(&self, row: usize, col: usize) -> CellValue {
+        let Some(row) = self.0.get(row) else {
+            return CellValue::Null;
+        };
+        match row.columns().get(col) {
+            Some(column) => extract_cell_value(row, column, col),
+            None => CellValue::Null,
+        }
     }
 }
 
-fn decode_cell_value(row: &PgRow, column: &PgColumn, index: usize) -> (String, bool) {
+fn decode_cell_value(row: &PgRow, column: &PgColumn, index: usize) -> CellValue {
     if let Ok(v) = row.try_get::<String, _>(index) {
-        return (v, false);
+        return CellValue::Text(v);
     }
 
-    match column.type_info().name() {
-        "BOOL" => row
-            .try_get::<bool, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
-        "INT2" | "INT4" => row
-            .try_get::<i32, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
-        "INT8" => row
-            .try_get::<i64, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
-        "FLOAT4" => row
-            .try_get::<f32, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
-        "FLOAT8" => row
-            .try_get::<f64, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
+    let typed = match column.type_info().name() {
+        "BOOL" => row.try_get::<bool, _>(index).map(|v| v.to_string()).ok(),
+        "INT2" => row.try_get::<i16, _>(index).map(|v| v.to_string()).ok(),
+        "INT4" => row.try_get::<i32, _>(index).map(|v| v.to_string()).ok(),
+        "INT8" => row.try_get::<i64, _>(index).map(|v| v.to_string()).ok(),
+        "FLOAT4" => row.try_get::<f32, _>(index).map(|v| v.to_string()).ok(),
+        "FLOAT8" => row.try_get::<f64, _>(index).map(|v| v.to_string()).ok(),
+        // Numbers beyond `Decimal`'s 28 digits are read from the wire below
         "NUMERIC" => row
             .try_get::<rust_decimal::Decimal, _>(index)
-            .map(|v| (v.to_string(), false))
-            .unwrap_or_else(|_| ("NULL".to_string(), true)),
-        _ => ("NULL".to_string(), true),
-    }
+            .map(|v| v.to_string())
+            .ok(),
+        "BYTEA" => row
+            .try_get::<Vec<u8>, _>(index)
+            .map(|v| format!("\\x{}", hex::encode(&v)))
+            .ok(),
+        _ => None,
+    };
+
+    typed
+        .or_else(|| wire_value(row, column, index))
+        .map(CellValue::Text)
+        .unwrap_or_else(|| undecoded(column))
 }
 
-fn extract_cell_value(row: &PgRow, column: &PgColumn, index: usize) -> (String, bool) {
-    match row.try_get_raw(index) {
-        Ok(raw_value) if raw_value.is_null() => ("NULL".to_string(), true),
-        Ok(_) => decode_cell_value(row, column, index),
-        Err(_) => ("ERROR".to_string(), false),
+/// Dates and times, intervals, network addresses, arrays, ranges and the
+/// other types without a typed getter above, read from the raw value.
+fn wire_value(row: &PgRow, column: &PgColumn, index: usize) -> Option<String> {
+    let value = row.try_get_raw(index).ok()?;
+    let bytes = value.as_bytes().ok()?;
+    if value.format() == PgValueFormat::Text {
+        return std::str::from_utf8(bytes).ok().map(str::to_string);
+    }
+    let type_info = column.type_info();
+    match type_info.kind() {
+        PgTypeKind::Array(_) => format_array(bytes),
+        _ => format_value(type_info.oid()?.0, bytes),
     }
 }
 
-fn convert_cell(
-    row: &PgRow,
-    column: &PgColumn,
-    index: usize,
-    metadata: &TableMetadata,
-) -> ResultCell {
-    let column_metadata = build_cell_column_metadata(column, index, metadata);
-    let (value, is_null) = extract_cell_value(row, column, index);
-
-    ResultCell {
-        value,
-        is_null,
-        column_metadata,
+/// A value none of the decoders above read. It is not NULL, so it must
+/// not be shown as one.
+fn undecoded(column: &PgColumn) -> CellValue {
+    CellValue::Undecoded(column.type_info().name().to_lowercase())
+}
+
+fn extract_cell_value(row: &PgRow, column: &PgColumn, index: usize) -> CellValue {
+    match row.try_get_raw(index) {
+        Ok(raw_value) if raw_value.is_null() => CellValue::Null,
+        Ok(_) => decode_cell_value(row, column, index),
+        Err(_) => undecoded(column),
     }
 }
@@ -0,0 +1,49 @@
+//! PostgreSQL role listing.
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::services::database::roles::RoleDefinition;
+
+/// Roles other than the built-in `pg_*` ones, with the roles each is a
+/// member of.
+pub async fn list_roles(pool: &PgPool) -> Result<Vec<RoleDefinition>> {
+    let query = r#"
+        SELECT
+            r.rolname,
+            r.rolcanlogin,
+            r.rolvaliduntil::text AS valid_until,
+            r.rolconnlimit,
+            COALESCE(
+                ARRAY(
+                    SELECT g.rolname
+                    FROM pg_auth_members m
+                    JOIN pg_roles g ON g.oid = m.roleid
+                    WHERE m.member = r.oid
+                    ORDER BY g.rolname
+                ),
+                '{}'
+            ) AS member_of
+        FROM pg_roles r
+        WHERE r.rolname NOT LIKE 'pg\_%'
+        ORDER BY r.rolname
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let connection_limit: i32 = row.get("rolconnlimit");
+            RoleDefinition {
+                name: row.get("rolname"),
+                login: row.get("rolcanlogin"),
+                valid_until: row
+                    .get::<Option<String>, _>("valid_until")
+                    .filter(|v| v != "infinity"),
+                connection_limit: (connection_limit >= 0).then_some(connection_limit),
+                member_of: row.get("member_of"),
+            }
+        })
+        .collect())
+}
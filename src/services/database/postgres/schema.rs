@@ -1,12 +1,21 @@
 //! PostgreSQL schema introspection.
 
-use anyhow::Result;
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
 use sqlx::{PgPool, Postgres, Row};
 
+use crate::services::database::dml::{EditableColumn, TableEditInfo};
+use crate::services::database::search::ObjectSource;
 use crate::services::database::types::{
-    ColumnDetail, ConstraintInfo, DatabaseInfo, DatabaseSchema, ForeignKeyInfo, IndexInfo,
-    QueryExecutionResult, TableInfo, TableSchema,
+    ColumnDetail, ConstraintInfo, DatabaseInfo, DatabaseSchema, ForeignKeyInfo, ForeignTableInfo,
+    IndexInfo, QueryExecutionResult, ReferencingKey, RoutineInfo, TableDetails, TableInfo,
+    TableSchema, TriggerInfo,
 };
+use crate::services::database::user_types::{UserTypeInfo, UserTypeKind};
+use crate::services::storage::{DatabaseDriver, TableSizeSample};
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
 
 pub async fn get_databases(pool: &PgPool) -> Result<Vec<DatabaseInfo>> {
     let query = r#"
@@ -26,14 +35,59 @@ pub async fn get_databases(pool: &PgPool) -> Result<Vec<DatabaseInfo>> {
         .collect())
 }
 
+/// Schemas a user can put on the search path, i.e. everything but the
+/// system and toast schemas.
+pub async fn get_schemas(pool: &PgPool) -> Result<Vec<String>> {
+    let query = r#"
+        SELECT nspname::text AS nspname
+        FROM pg_namespace
+        WHERE nspname NOT LIKE 'pg\_%'
+            AND nspname <> 'information_schema'
+        ORDER BY nspname
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+    Ok(rows.into_iter().map(|row| row.get("nspname")).collect())
+}
+
+/// The effective search path of a session: existing schemas only, in
+/// order, with `"$user"` resolved and `pg_catalog` left out.
+pub async fn get_search_path(pool: &PgPool) -> Result<Vec<String>> {
+    let query = r#"
+        SELECT schema::text AS schema
+        FROM unnest(current_schemas(false)) WITH ORDINALITY AS s(schema, position)
+        ORDER BY position
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+    Ok(rows.into_iter().map(|row| row.get("schema")).collect())
+}
+
 pub async fn get_tables(pool: &PgPool) -> Result<Vec<TableInfo>> {
+    // Materialized views are not part of information_schema.tables, and
+    // inheritance children are reported there as plain base tables.
     let query = r#"
         SELECT
-            table_name,
-            table_schema,
-            table_type
+            table_name::text AS table_name,
+            table_schema::text AS table_schema,
+            CASE WHEN table_type = 'BASE TABLE' AND EXISTS (
+                SELECT 1
+                FROM pg_inherits i
+                JOIN pg_class c ON c.oid = i.inhrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE n.nspname = table_schema
+                    AND c.relname = table_name
+                    AND NOT c.relispartition
+            ) THEN 'INHERITED TABLE' ELSE table_type::text END AS table_type
         FROM information_schema.tables
         WHERE table_schema NOT IN ('information_schema', 'pg_catalog')
+        UNION ALL
+        SELECT
+            matviewname::text,
+            schemaname::text,
+            'MATERIALIZED VIEW'
+        FROM pg_matviews
+        WHERE schemaname NOT IN ('information_schema', 'pg_catalog')
         ORDER BY table_schema, table_name
     "#;
 
@@ -49,6 +103,458 @@ pub async fn get_tables(pool: &PgPool) -> Result<Vec<TableInfo>> {
         .collect())
 }
 
+/// Size of each table and materialized view, with its indexes and TOAST
+/// data, sampled now.
+pub async fn get_table_sizes(pool: &PgPool) -> Result<Vec<TableSizeSample>> {
+    let query = r#"
+        SELECT
+            n.nspname AS table_schema,
+            c.relname AS table_name,
+            pg_total_relation_size(c.oid) AS total_bytes
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE c.relkind IN ('r', 'm')
+            AND n.nspname NOT IN ('information_schema', 'pg_catalog')
+            AND n.nspname NOT LIKE 'pg\_toast%'
+        ORDER BY n.nspname, c.relname
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+    let sampled_at = chrono::Utc::now();
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TableSizeSample {
+            table_schema: row.get("table_schema"),
+            table_name: row.get("table_name"),
+            total_bytes: row.get("total_bytes"),
+            sampled_at,
+        })
+        .collect())
+}
+
+/// One keyset page of [`get_tables`]: up to `limit` tables ordered after
+/// `after` by `(table_schema, table_name)`.
+pub async fn get_tables_page(
+    pool: &PgPool,
+    after: Option<&TableInfo>,
+    limit: usize,
+) -> Result<Vec<TableInfo>> {
+    let query = r#"
+        SELECT table_name, table_schema, table_type
+        FROM (
+            SELECT
+                table_name::text AS table_name,
+                table_schema::text AS table_schema,
+                CASE WHEN table_type = 'BASE TABLE' AND EXISTS (
+                    SELECT 1
+                    FROM pg_inherits i
+                    JOIN pg_class c ON c.oid = i.inhrelid
+                    JOIN pg_namespace n ON n.oid = c.relnamespace
+                    WHERE n.nspname = table_schema
+                        AND c.relname = table_name
+                        AND NOT c.relispartition
+                ) THEN 'INHERITED TABLE' ELSE table_type::text END AS table_type
+            FROM information_schema.tables
+            WHERE table_schema NOT IN ('information_schema', 'pg_catalog')
+            UNION ALL
+            SELECT
+                matviewname::text,
+                schemaname::text,
+                'MATERIALIZED VIEW'
+            FROM pg_matviews
+            WHERE schemaname NOT IN ('information_schema', 'pg_catalog')
+        ) t
+        WHERE $1::text IS NULL OR (table_schema, table_name) > ($1::text, $2::text)
+        ORDER BY table_schema, table_name
+        LIMIT $3
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(after.map(|t| t.table_schema.as_str()))
+        .bind(after.map(|t| t.table_name.as_str()))
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TableInfo {
+            table_name: row.get("table_name"),
+            table_schema: row.get("table_schema"),
+            table_type: row.get("table_type"),
+        })
+        .collect())
+}
+
+/// User functions and procedures, excluding those installed by extensions.
+pub async fn get_routines(pool: &PgPool) -> Result<Vec<RoutineInfo>> {
+    let query = r#"
+        SELECT
+            n.nspname::text AS routine_schema,
+            p.proname::text AS routine_name,
+            CASE p.prokind WHEN 'p' THEN 'PROCEDURE' ELSE 'FUNCTION' END AS routine_type,
+            pg_get_function_identity_arguments(p.oid) AS arguments,
+            CASE WHEN p.prokind = 'p' THEN NULL ELSE pg_get_function_result(p.oid) END AS result_type
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname NOT IN ('information_schema', 'pg_catalog')
+            AND n.nspname NOT LIKE 'pg_toast%'
+            AND p.prokind IN ('f', 'p')
+            AND NOT EXISTS (
+                SELECT 1 FROM pg_depend d
+                WHERE d.objid = p.oid
+                    AND d.classid = 'pg_proc'::regclass
+                    AND d.deptype = 'e'
+            )
+        ORDER BY n.nspname, p.proname, arguments
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RoutineInfo {
+            routine_schema: row.get("routine_schema"),
+            routine_name: row.get("routine_name"),
+            routine_type: row.get("routine_type"),
+            arguments: row.get("arguments"),
+            result_type: row.get("result_type"),
+        })
+        .collect())
+}
+
+/// Enums, standalone composite types and domains outside the system
+/// schemas, leaving out those installed by extensions.
+pub async fn get_user_types(pool: &PgPool) -> Result<Vec<UserTypeInfo>> {
+    let query = r#"
+        SELECT
+            n.nspname::text AS type_schema,
+            t.typname::text AS type_name,
+            t.typtype::text AS kind,
+            ARRAY(
+                SELECT e.enumlabel::text FROM pg_enum e
+                WHERE e.enumtypid = t.oid
+                ORDER BY e.enumsortorder
+            ) AS labels,
+            CASE t.typtype
+                WHEN 'c' THEN (
+                    SELECT string_agg(
+                        quote_ident(a.attname) || ' ' || format_type(a.atttypid, a.atttypmod),
+                        ', ' ORDER BY a.attnum
+                    )
+                    FROM pg_attribute a
+                    WHERE a.attrelid = t.typrelid AND a.attnum > 0 AND NOT a.attisdropped
+                )
+                WHEN 'd' THEN concat_ws(
+                    ' ',
+                    format_type(t.typbasetype, t.typtypmod),
+                    CASE WHEN t.typnotnull THEN 'NOT NULL' END,
+                    (
+                        SELECT string_agg(pg_get_constraintdef(c.oid), ' ' ORDER BY c.conname)
+                        FROM pg_constraint c
+                        WHERE c.contypid = t.oid
+                    )
+                )
+            END AS definition,
+            ARRAY(
+                SELECT DISTINCT a.attname::text
+                FROM pg_attribute a
+                JOIN pg_class c ON c.oid = a.attrelid
+                WHERE a.atttypid = t.oid
+                    AND a.attnum > 0
+                    AND NOT a.attisdropped
+                    AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+            ) AS column_names
+        FROM pg_type t
+        JOIN pg_namespace n ON n.oid = t.typnamespace
+        LEFT JOIN pg_class r ON r.oid = t.typrelid
+        WHERE t.typtype IN ('e', 'c', 'd')
+            -- Every table has a row type; only standalone composites count.
+            AND (t.typtype <> 'c' OR r.relkind = 'c')
+            AND n.nspname NOT IN ('information_schema', 'pg_catalog')
+            AND n.nspname NOT LIKE 'pg_toast%'
+            AND NOT EXISTS (
+                SELECT 1 FROM pg_depend d
+                WHERE d.objid = t.oid
+                    AND d.classid = 'pg_type'::regclass
+                    AND d.deptype = 'e'
+            )
+        ORDER BY n.nspname, t.typname
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let kind: String = row.get("kind");
+            Some(UserTypeInfo {
+                type_schema: row.get("type_schema"),
+                type_name: row.get("type_name"),
+                kind: UserTypeKind::from_typtype(&kind)?,
+                labels: row.get("labels"),
+                definition: row.get("definition"),
+                column_names: row.get("column_names"),
+            })
+        })
+        .collect())
+}
+
+/// Bodies of all views, materialized views and routines listed in the
+/// table tree, for searching inside them.
+pub async fn get_object_sources(pool: &PgPool) -> Result<Vec<ObjectSource>> {
+    let query = r#"
+        SELECT
+            schemaname::text AS object_schema,
+            viewname::text AS object_name,
+            NULL::text AS arguments,
+            COALESCE(definition, '') AS definition
+        FROM pg_views
+        WHERE schemaname NOT IN ('information_schema', 'pg_catalog')
+        UNION ALL
+        SELECT schemaname::text, matviewname::text, NULL, COALESCE(definition, '')
+        FROM pg_matviews
+        WHERE schemaname NOT IN ('information_schema', 'pg_catalog')
+        UNION ALL
+        SELECT
+            n.nspname::text,
+            p.proname::text,
+            pg_get_function_identity_arguments(p.oid),
+            COALESCE(p.prosrc, '')
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname NOT IN ('information_schema', 'pg_catalog')
+            AND n.nspname NOT LIKE 'pg_toast%'
+            AND p.prokind IN ('f', 'p')
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ObjectSource {
+            schema: row.get("object_schema"),
+            name: row.get("object_name"),
+            arguments: row.get("arguments"),
+            definition: row.get("definition"),
+        })
+        .collect())
+}
+
+/// Full `CREATE OR REPLACE FUNCTION/PROCEDURE` source.
+pub async fn get_routine_definition(pool: &PgPool, routine: &RoutineInfo) -> Result<String> {
+    let query = r#"
+        SELECT pg_get_functiondef(p.oid) AS definition
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname = $1
+            AND p.proname = $2
+            AND pg_get_function_identity_arguments(p.oid) = $3
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(&routine.routine_schema)
+        .bind(&routine.routine_name)
+        .bind(&routine.arguments)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| anyhow!("Routine {} not found", routine.signature()))?;
+
+    Ok(row.get("definition"))
+}
+
+/// Persistence, foreign server and inheritance of a table.
+pub async fn get_table_details(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<TableDetails> {
+    let query = r#"
+        SELECT
+            c.relpersistence = 't' AS temporary,
+            s.srvname::text AS server_name,
+            w.fdwname::text AS wrapper,
+            COALESCE(s.srvoptions, '{}') AS server_options,
+            COALESCE(ft.ftoptions, '{}') AS table_options,
+            ARRAY(
+                SELECT format('%I.%I', pn.nspname, p.relname)
+                FROM pg_inherits i
+                JOIN pg_class p ON p.oid = i.inhparent
+                JOIN pg_namespace pn ON pn.oid = p.relnamespace
+                WHERE i.inhrelid = c.oid
+                ORDER BY i.inhseqno
+            ) AS parents,
+            ARRAY(
+                SELECT format('%I.%I', chn.nspname, ch.relname)
+                FROM pg_inherits i
+                JOIN pg_class ch ON ch.oid = i.inhrelid
+                JOIN pg_namespace chn ON chn.oid = ch.relnamespace
+                WHERE i.inhparent = c.oid
+                ORDER BY 1
+            ) AS children
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        LEFT JOIN pg_foreign_table ft ON ft.ftrelid = c.oid
+        LEFT JOIN pg_foreign_server s ON s.oid = ft.ftserver
+        LEFT JOIN pg_foreign_data_wrapper w ON w.oid = s.srvfdw
+        WHERE n.nspname = $1 AND c.relname = $2
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| anyhow!("Table {}.{} not found", table_schema, table_name))?;
+
+    let server_name: Option<String> = row.get("server_name");
+    let foreign = server_name.map(|server_name| ForeignTableInfo {
+        server_name,
+        wrapper: row.get::<Option<String>, _>("wrapper").unwrap_or_default(),
+        server_options: row.get("server_options"),
+        table_options: row.get("table_options"),
+    });
+
+    Ok(TableDetails {
+        temporary: row.get("temporary"),
+        foreign,
+        parents: row.get("parents"),
+        children: row.get("children"),
+    })
+}
+
+/// `CREATE` statement for a view or materialized view.
+pub async fn get_view_definition(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<String> {
+    let query = r#"
+        SELECT
+            c.relkind::text AS relkind,
+            pg_get_viewdef(c.oid, true) AS definition
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND c.relkind IN ('v', 'm')
+    "#;
+
+    let row = sqlx::query(query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| anyhow!("View {}.{} not found", table_schema, table_name))?;
+
+    let relkind: String = row.get("relkind");
+    let definition: String = row.get("definition");
+    let create = if relkind == "m" {
+        "CREATE MATERIALIZED VIEW"
+    } else {
+        "CREATE OR REPLACE VIEW"
+    };
+
+    Ok(format!(
+        "{} {}.{} AS\n{}",
+        create,
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name),
+        definition.trim_end()
+    ))
+}
+
+pub async fn refresh_materialized_view(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<()> {
+    let sql = format!(
+        "REFRESH MATERIALIZED VIEW {}.{}",
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name)
+    );
+    sqlx::query(&sql).execute(pool).await?;
+    Ok(())
+}
+
+/// User-defined triggers, with `tgtype` decoded into timing and event.
+const TRIGGERS_QUERY: &str = r#"
+    SELECT
+        n.nspname AS table_schema,
+        c.relname AS table_name,
+        t.tgname AS trigger_name,
+        CASE
+            WHEN t.tgtype::int & 2 <> 0 THEN 'BEFORE'
+            WHEN t.tgtype::int & 64 <> 0 THEN 'INSTEAD OF'
+            ELSE 'AFTER'
+        END AS timing,
+        concat_ws(' OR ',
+            CASE WHEN t.tgtype::int & 4 <> 0 THEN 'INSERT' END,
+            CASE WHEN t.tgtype::int & 16 <> 0 THEN 'UPDATE' END,
+            CASE WHEN t.tgtype::int & 8 <> 0 THEN 'DELETE' END,
+            CASE WHEN t.tgtype::int & 32 <> 0 THEN 'TRUNCATE' END
+        ) AS event,
+        pn.nspname || '.' || p.proname AS function_name,
+        t.tgenabled <> 'D' AS enabled
+    FROM pg_trigger t
+    JOIN pg_class c ON c.oid = t.tgrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    JOIN pg_proc p ON p.oid = t.tgfoid
+    JOIN pg_namespace pn ON pn.oid = p.pronamespace
+    WHERE NOT t.tgisinternal
+"#;
+
+fn trigger_from_row(row: &sqlx::postgres::PgRow) -> TriggerInfo {
+    TriggerInfo {
+        trigger_name: row.get("trigger_name"),
+        timing: row.get("timing"),
+        event: row.get("event"),
+        function_name: row.get("function_name"),
+        enabled: row.get("enabled"),
+    }
+}
+
+/// User-defined triggers on a table.
+pub async fn get_triggers(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<Vec<TriggerInfo>> {
+    let query = format!(
+        "{} AND n.nspname = $1 AND c.relname = $2 ORDER BY t.tgname",
+        TRIGGERS_QUERY
+    );
+
+    let rows = sqlx::query(&query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(trigger_from_row).collect())
+}
+
+/// Every user-defined trigger outside the system schemas, keyed by
+/// `(schema, table)`, so the schema load needs one query for all tables.
+async fn fetch_all_triggers(pool: &PgPool) -> Result<HashMap<(String, String), Vec<TriggerInfo>>> {
+    let query = format!(
+        "{} AND n.nspname NOT IN ('information_schema', 'pg_catalog') ORDER BY t.tgname",
+        TRIGGERS_QUERY
+    );
+
+    let rows = sqlx::query(&query).fetch_all(pool).await?;
+    let mut triggers: HashMap<(String, String), Vec<TriggerInfo>> = HashMap::new();
+    for row in &rows {
+        triggers
+            .entry((row.get("table_schema"), row.get("table_name")))
+            .or_default()
+            .push(trigger_from_row(row));
+    }
+    Ok(triggers)
+}
+
 pub async fn get_table_columns(
     pool: &PgPool,
     table_name: &str,
@@ -89,6 +595,7 @@ pub async fn get_schema(
     "#;
 
     let table_rows = sqlx::query(table_query).fetch_all(pool).await?;
+    let mut all_triggers = fetch_all_triggers(pool).await?;
     let mut tables = Vec::new();
 
     for table_row in table_rows {
@@ -108,6 +615,9 @@ pub async fn get_schema(
         let foreign_keys = fetch_foreign_keys(&table_name, &table_schema, pool).await?;
         let indexes = fetch_indexes(&table_name, &table_schema, pool).await?;
         let constraints = fetch_constraints(&table_name, &table_schema, pool).await?;
+        let triggers = all_triggers
+            .remove(&(table_schema.clone(), table_name.clone()))
+            .unwrap_or_default();
 
         tables.push(TableSchema {
             table_name,
@@ -118,6 +628,7 @@ pub async fn get_schema(
             foreign_keys,
             indexes,
             constraints,
+            triggers,
             description,
         });
     }
@@ -129,7 +640,7 @@ pub async fn get_schema(
     })
 }
 
-async fn fetch_table_columns(
+pub async fn fetch_table_columns(
     table_name: &str,
     table_schema: &str,
     pool: &PgPool,
@@ -144,6 +655,12 @@ async fn fetch_table_columns(
             c.character_maximum_length,
             c.numeric_precision,
             c.numeric_scale,
+            (
+                SELECT format_type(a.atttypid, a.atttypmod)
+                FROM pg_catalog.pg_attribute a
+                WHERE a.attrelid = (quote_ident(c.table_schema) || '.' || quote_ident(c.table_name))::regclass
+                    AND a.attname = c.column_name
+            ) as full_type,
             col_description((c.table_schema || '.' || c.table_name)::regclass, c.ordinal_position) as description
         FROM information_schema.columns c
         WHERE c.table_name = $1 AND c.table_schema = $2
@@ -169,13 +686,16 @@ async fn fetch_table_columns(
                 character_maximum_length: row.get("character_maximum_length"),
                 numeric_precision: row.get("numeric_precision"),
                 numeric_scale: row.get("numeric_scale"),
+                full_type: row.get("full_type"),
+                collation: None,
+                extra: None,
                 description: row.get("description"),
             }
         })
         .collect())
 }
 
-async fn fetch_primary_keys(
+pub(super) async fn fetch_primary_keys(
     table_name: &str,
     table_schema: &str,
     pool: &PgPool,
@@ -201,6 +721,60 @@ async fn fetch_primary_keys(
     Ok(rows.into_iter().map(|row| row.get("column_name")).collect())
 }
 
+/// Foreign keys of a table, one entry per column.
+pub async fn get_foreign_keys(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<Vec<ForeignKeyInfo>> {
+    fetch_foreign_keys(table_name, table_schema, pool).await
+}
+
+/// Foreign key columns of other tables that point at a table.
+pub async fn get_referencing_keys(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<Vec<ReferencingKey>> {
+    let query = r#"
+        SELECT
+            c.conname::text AS constraint_name,
+            n.nspname::text AS table_schema,
+            t.relname::text AS table_name,
+            a.attname::text AS column_name,
+            fa.attname::text AS referenced_column
+        FROM pg_constraint c
+        JOIN pg_class t ON t.oid = c.conrelid
+        JOIN pg_namespace n ON n.oid = t.relnamespace
+        JOIN pg_class ft ON ft.oid = c.confrelid
+        JOIN pg_namespace fns ON fns.oid = ft.relnamespace
+        CROSS JOIN LATERAL unnest(c.conkey, c.confkey) AS k(attnum, fattnum)
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+        JOIN pg_attribute fa ON fa.attrelid = c.confrelid AND fa.attnum = k.fattnum
+        WHERE c.contype = 'f'
+            AND fns.nspname = $1
+            AND ft.relname = $2
+        ORDER BY n.nspname, t.relname, c.conname
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ReferencingKey {
+            constraint_name: row.get("constraint_name"),
+            table_schema: row.get("table_schema"),
+            table_name: row.get("table_name"),
+            column_name: row.get("column_name"),
+            referenced_column: row.get("referenced_column"),
+        })
+        .collect())
+}
+
 async fn fetch_foreign_keys(
     table_name: &str,
     table_schema: &str,
@@ -326,3 +900,76 @@ async fn fetch_constraints(
         })
         .collect())
 }
+
+/// Column details for the row editor, including the exact SQL type (for
+/// parameter casts) and enum labels.
+pub async fn get_table_edit_info(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<TableEditInfo> {
+    let columns = fetch_table_columns(table_name, table_schema, pool).await?;
+    let primary_keys = fetch_primary_keys(table_name, table_schema, pool).await?;
+
+    let query = r#"
+        SELECT
+            a.attname AS column_name,
+            format_type(a.atttypid, a.atttypmod) AS sql_type,
+            CASE WHEN t.typtype = 'e' THEN
+                ARRAY(
+                    SELECT e.enumlabel::TEXT
+                    FROM pg_enum e
+                    WHERE e.enumtypid = t.oid
+                    ORDER BY e.enumsortorder
+                )
+            ELSE ARRAY[]::TEXT[]
+            END AS enum_values
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_type t ON t.oid = a.atttypid
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND a.attnum > 0
+            AND NOT a.attisdropped
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+    let types: HashMap<String, (String, Vec<String>)> = rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.get("column_name"),
+                (row.get("sql_type"), row.get("enum_values")),
+            )
+        })
+        .collect();
+
+    Ok(TableEditInfo {
+        table_schema: table_schema.to_string(),
+        table_name: table_name.to_string(),
+        columns: columns
+            .into_iter()
+            .map(|col| {
+                let (sql_type, enum_values) = types
+                    .get(&col.column_name)
+                    .cloned()
+                    .unwrap_or_else(|| (col.data_type.clone(), vec![]));
+                EditableColumn {
+                    is_primary_key: primary_keys.contains(&col.column_name),
+                    name: col.column_name,
+                    data_type: col.data_type,
+                    sql_type,
+                    is_nullable: col.is_nullable,
+                    column_default: col.column_default,
+                    enum_values,
+                }
+            })
+            .collect(),
+    })
+}
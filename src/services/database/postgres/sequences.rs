@@ -0,0 +1,96 @@
+//! PostgreSQL sequence introspection and maintenance.
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::services::database::sequences::{SequenceInfo, SequenceOwner};
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+/// List user sequences with their owning column (for `serial` and identity
+/// columns) and that column's current maximum.
+pub async fn list_sequences(pool: &PgPool) -> Result<Vec<SequenceInfo>> {
+    let query = r#"
+        SELECT
+            s.schemaname AS sequence_schema,
+            s.sequencename AS sequence_name,
+            s.last_value,
+            s.start_value,
+            s.increment_by,
+            tn.nspname AS table_schema,
+            t.relname AS table_name,
+            a.attname AS column_name,
+            COALESCE(d.deptype = 'i', false) AS is_identity
+        FROM pg_sequences s
+        JOIN pg_namespace sn ON sn.nspname = s.schemaname
+        JOIN pg_class sc ON sc.relname = s.sequencename AND sc.relnamespace = sn.oid
+        LEFT JOIN pg_depend d
+            ON d.objid = sc.oid
+            AND d.classid = 'pg_class'::regclass
+            AND d.refclassid = 'pg_class'::regclass
+            AND d.deptype IN ('a', 'i')
+        LEFT JOIN pg_class t ON t.oid = d.refobjid
+        LEFT JOIN pg_namespace tn ON tn.oid = t.relnamespace
+        LEFT JOIN pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid
+        WHERE s.schemaname NOT IN ('pg_catalog', 'information_schema')
+        ORDER BY s.schemaname, s.sequencename
+    "#;
+
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    let mut sequences = Vec::with_capacity(rows.len());
+    for row in rows {
+        let table_schema: Option<String> = row.get("table_schema");
+        let table_name: Option<String> = row.get("table_name");
+        let column_name: Option<String> = row.get("column_name");
+        let owner = match (table_schema, table_name, column_name) {
+            (Some(table_schema), Some(table_name), Some(column_name)) => Some(SequenceOwner {
+                table_schema,
+                table_name,
+                column_name,
+            }),
+            _ => None,
+        };
+
+        let column_max = match &owner {
+            Some(owner) => column_max(pool, owner).await,
+            None => None,
+        };
+
+        sequences.push(SequenceInfo {
+            sequence_schema: row.get("sequence_schema"),
+            sequence_name: row.get("sequence_name"),
+            last_value: row.get("last_value"),
+            start_value: row.get("start_value"),
+            increment_by: row.get("increment_by"),
+            owner,
+            is_identity: row.get("is_identity"),
+            column_max,
+        });
+    }
+
+    Ok(sequences)
+}
+
+/// `MAX()` of the owning column. Failures (permissions, non-integer
+/// columns) are treated as unknown rather than failing the listing.
+async fn column_max(pool: &PgPool, owner: &SequenceOwner) -> Option<i64> {
+    let sql = format!(
+        "SELECT MAX({})::bigint FROM {}.{}",
+        DRIVER.quote_ident(&owner.column_name),
+        DRIVER.quote_ident(&owner.table_schema),
+        DRIVER.quote_ident(&owner.table_name)
+    );
+    sqlx::query_scalar::<_, Option<i64>>(&sql)
+        .fetch_one(pool)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Run a generated maintenance statement.
+pub async fn execute(pool: &PgPool, sql: &str) -> Result<()> {
+    sqlx::query(sql).execute(pool).await?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! PostgreSQL server configuration from `pg_settings`.
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::services::database::server_settings::ServerSetting;
+
+/// Every configuration parameter, ordered by category then name.
+pub async fn list_server_settings(pool: &PgPool) -> Result<Vec<ServerSetting>> {
+    let query = r#"
+        SELECT
+            name,
+            category,
+            setting,
+            unit,
+            boot_val,
+            source,
+            context,
+            short_desc,
+            pending_restart
+        FROM pg_settings
+        ORDER BY category, name
+    "#;
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| ServerSetting {
+            name: row.get("name"),
+            category: row.get("category"),
+            setting: row.get("setting"),
+            unit: row.get("unit"),
+            boot_val: row.get("boot_val"),
+            source: row.get("source"),
+            context: row.get("context"),
+            short_desc: row.get("short_desc"),
+            pending_restart: row.get("pending_restart"),
+        })
+        .collect())
+}
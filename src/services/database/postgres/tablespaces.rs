@@ -0,0 +1,100 @@
+//! PostgreSQL tablespaces and table storage parameters.
+
+use anyhow::Result;
+use sqlx::{PgPool, Row};
+
+use crate::services::database::tablespaces::{TableStorage, TablespaceInfo, TablespaceRelation};
+
+/// Every tablespace with its size, where readable, and the relations of
+/// the current database placed in it.
+pub async fn list_tablespaces(pool: &PgPool) -> Result<Vec<TablespaceInfo>> {
+    // Reading a tablespace's size needs CREATE on it or pg_read_all_stats,
+    // except for the current database's default.
+    let query = r#"
+        SELECT
+            t.spcname,
+            pg_get_userbyid(t.spcowner) AS owner,
+            pg_tablespace_location(t.oid) AS location,
+            t.oid = d.dattablespace AS is_database_default,
+            CASE
+                WHEN t.oid = d.dattablespace
+                    OR has_tablespace_privilege(t.oid, 'CREATE')
+                    OR pg_has_role('pg_read_all_stats', 'USAGE')
+                THEN pg_tablespace_size(t.oid)
+            END AS size_bytes
+        FROM pg_tablespace t
+        CROSS JOIN pg_database d
+        WHERE d.datname = current_database()
+        ORDER BY t.spcname
+    "#;
+    let rows = sqlx::query(query).fetch_all(pool).await?;
+
+    let relations_query = r#"
+        SELECT
+            ts.spcname,
+            n.nspname,
+            c.relname,
+            CASE c.relkind
+                WHEN 'i' THEN 'index'
+                WHEN 'I' THEN 'index'
+                WHEN 'm' THEN 'materialized view'
+                ELSE 'table'
+            END AS kind
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        JOIN pg_tablespace ts ON ts.oid = c.reltablespace
+        WHERE c.relkind IN ('r', 'p', 'm', 'i', 'I')
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+            AND n.nspname NOT LIKE 'pg\_toast%'
+        ORDER BY n.nspname, c.relname
+    "#;
+    let relation_rows = sqlx::query(relations_query).fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let name: String = row.get("spcname");
+            let relations = relation_rows
+                .iter()
+                .filter(|r| r.get::<String, _>("spcname") == name)
+                .map(|r| TablespaceRelation {
+                    schema: r.get("nspname"),
+                    name: r.get("relname"),
+                    kind: r.get("kind"),
+                })
+                .collect();
+            TablespaceInfo {
+                name,
+                owner: row.get("owner"),
+                location: row.get("location"),
+                size_bytes: row.get("size_bytes"),
+                is_database_default: row.get("is_database_default"),
+                relations,
+            }
+        })
+        .collect())
+}
+
+pub async fn table_storage(
+    pool: &PgPool,
+    table_schema: &str,
+    table_name: &str,
+) -> Result<TableStorage> {
+    let query = r#"
+        SELECT ts.spcname, COALESCE(c.reloptions, '{}') AS reloptions
+        FROM pg_class c
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        LEFT JOIN pg_tablespace ts ON ts.oid = c.reltablespace
+        WHERE n.nspname = $1 AND c.relname = $2
+    "#;
+    let row = sqlx::query(query)
+        .bind(table_schema)
+        .bind(table_name)
+        .fetch_one(pool)
+        .await?;
+    let reloptions: Vec<String> = row.get("reloptions");
+    Ok(TableStorage::from_reloptions(
+        row.get("spcname"),
+        &reloptions,
+    ))
+}
@@ -0,0 +1,280 @@
+//! Creating and altering Postgres roles.
+//!
+//! Passwords never appear in the generated SQL: they are turned into a
+//! SCRAM-SHA-256 verifier here, which Postgres stores as is. The script can
+//! then sit in the editor, the history or a change script without giving
+//! the password away, and nothing is saved to the keychain.
+
+use anyhow::{Result, anyhow};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+/// PBKDF2 rounds, the Postgres default for `scram_iterations`.
+const SCRAM_ITERATIONS: u32 = 4096;
+
+/// A role as listed or edited. Its password is never read back.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoleDefinition {
+    pub name: String,
+    pub login: bool,
+    /// A timestamp, e.g. `2027-01-01`; `None` never expires.
+    pub valid_until: Option<String>,
+    /// `None` is unlimited.
+    pub connection_limit: Option<i32>,
+    /// Roles this one is a member of.
+    pub member_of: Vec<String>,
+}
+
+/// A random salt for [`scram_sha256_verifier`].
+pub fn password_salt() -> [u8; 16] {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// The `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>` form
+/// Postgres keeps in `pg_authid`. The password is used as typed; Postgres
+/// only normalizes non-ASCII passwords differently (SASLprep).
+pub fn scram_sha256_verifier(password: &str, salt: &[u8]) -> String {
+    let hmac = |key: &[u8], data: &[u8]| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes any key length");
+        mac.update(data);
+        mac.finalize().into_bytes()
+    };
+
+    // PBKDF2 with a single output block, which is all SHA-256 needs.
+    let mut block = salt.to_vec();
+    block.extend_from_slice(&1u32.to_be_bytes());
+    let mut u = hmac(password.as_bytes(), &block);
+    let mut salted = u;
+    for _ in 1..SCRAM_ITERATIONS {
+        u = hmac(password.as_bytes(), &u);
+        salted.iter_mut().zip(u.iter()).for_each(|(s, u)| *s ^= u);
+    }
+
+    let client_key = hmac(&salted, b"Client Key");
+    let stored_key = Sha256::digest(client_key);
+    let server_key = hmac(&salted, b"Server Key");
+    format!(
+        "SCRAM-SHA-256${}:{}${}:{}",
+        SCRAM_ITERATIONS,
+        STANDARD.encode(salt),
+        STANDARD.encode(stored_key),
+        STANDARD.encode(server_key)
+    )
+}
+
+fn password_clause(password: &str, salt: &[u8]) -> String {
+    format!(
+        "PASSWORD {}",
+        DRIVER.quote_literal(&scram_sha256_verifier(password, salt))
+    )
+}
+
+fn validate(role: &RoleDefinition) -> Result<()> {
+    if role.name.trim().is_empty() {
+        return Err(anyhow!("Enter the role name"));
+    }
+    if role.connection_limit.is_some_and(|limit| limit < 0) {
+        return Err(anyhow!("The connection limit cannot be negative"));
+    }
+    Ok(())
+}
+
+fn grant_sql(role: &str, groups: &[&String]) -> String {
+    let groups: Vec<String> = groups.iter().map(|g| DRIVER.quote_ident(g)).collect();
+    format!(
+        "GRANT {} TO {};",
+        groups.join(", "),
+        DRIVER.quote_ident(role)
+    )
+}
+
+fn revoke_sql(role: &str, groups: &[&String]) -> String {
+    let groups: Vec<String> = groups.iter().map(|g| DRIVER.quote_ident(g)).collect();
+    format!(
+        "REVOKE {} FROM {};",
+        groups.join(", "),
+        DRIVER.quote_ident(role)
+    )
+}
+
+/// `CREATE ROLE` for `role`, then a `GRANT` for its memberships.
+pub fn create_role_sql(
+    role: &RoleDefinition,
+    password: Option<&str>,
+    salt: &[u8],
+) -> Result<Vec<String>> {
+    validate(role)?;
+    let mut options = vec![if role.login { "LOGIN" } else { "NOLOGIN" }.to_string()];
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        options.push(password_clause(password, salt));
+    }
+    if let Some(valid_until) = &role.valid_until {
+        options.push(format!("VALID UNTIL {}", DRIVER.quote_literal(valid_until)));
+    }
+    if let Some(limit) = role.connection_limit {
+        options.push(format!("CONNECTION LIMIT {}", limit));
+    }
+
+    let mut statements = vec![format!(
+        "CREATE ROLE {} {};",
+        DRIVER.quote_ident(&role.name),
+        options.join(" ")
+    )];
+    let groups: Vec<&String> = role.member_of.iter().collect();
+    if !groups.is_empty() {
+        statements.push(grant_sql(&role.name, &groups));
+    }
+    Ok(statements)
+}
+
+/// The statements turning `original` into `role`, with a new password
+/// when one is given.
+pub fn alter_role_sql(
+    original: &RoleDefinition,
+    role: &RoleDefinition,
+    password: Option<&str>,
+    salt: &[u8],
+) -> Result<Vec<String>> {
+    validate(role)?;
+    let mut statements = vec![];
+    if original.name != role.name {
+        statements.push(format!(
+            "ALTER ROLE {} RENAME TO {};",
+            DRIVER.quote_ident(&original.name),
+            DRIVER.quote_ident(&role.name)
+        ));
+    }
+
+    let mut options = vec![];
+    if original.login != role.login {
+        options.push(if role.login { "LOGIN" } else { "NOLOGIN" }.to_string());
+    }
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        options.push(password_clause(password, salt));
+    }
+    if original.valid_until != role.valid_until {
+        let valid_until = role.valid_until.as_deref().unwrap_or("infinity");
+        options.push(format!("VALID UNTIL {}", DRIVER.quote_literal(valid_until)));
+    }
+    if original.connection_limit != role.connection_limit {
+        options.push(format!(
+            "CONNECTION LIMIT {}",
+            role.connection_limit.unwrap_or(-1)
+        ));
+    }
+    if !options.is_empty() {
+        statements.push(format!(
+            "ALTER ROLE {} {};",
+            DRIVER.quote_ident(&role.name),
+            options.join(" ")
+        ));
+    }
+
+    let granted: Vec<&String> = role
+        .member_of
+        .iter()
+        .filter(|g| !original.member_of.contains(g))
+        .collect();
+    if !granted.is_empty() {
+        statements.push(grant_sql(&role.name, &granted));
+    }
+    let revoked: Vec<&String> = original
+        .member_of
+        .iter()
+        .filter(|g| !role.member_of.contains(g))
+        .collect();
+    if !revoked.is_empty() {
+        statements.push(revoke_sql(&role.name, &revoked));
+    }
+
+    if statements.is_empty() {
+        return Err(anyhow!("Nothing to change"));
+    }
+    Ok(statements)
+}
+
+/// `ALTER ROLE ... PASSWORD` setting a new password.
+pub fn reset_password_sql(role: &str, password: &str, salt: &[u8]) -> Result<String> {
+    if password.is_empty() {
+        return Err(anyhow!("Enter the new password"));
+    }
+    Ok(format!(
+        "ALTER ROLE {} {};",
+        DRIVER.quote_ident(role),
+        password_clause(password, salt)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+    const VERIFIER: &str = "SCRAM-SHA-256$4096:AAECAwQFBgcICQoLDA0ODw==$\
+                            yERwYmHlx0VrpoYfOn54hMTjBUKWnmK42IDjnWgQWkk=:\
+                            RQZr9F8QCYmmp35tAVzp0VB/kDwY283N+s24jDDliG4=";
+
+    fn role() -> RoleDefinition {
+        RoleDefinition {
+            name: "reporter".to_string(),
+            login: true,
+            valid_until: Some("2027-01-01".to_string()),
+            connection_limit: Some(5),
+            member_of: vec!["readers".to_string()],
+        }
+    }
+
+    #[test]
+    fn hashes_passwords_as_scram_verifiers() {
+        assert_eq!(scram_sha256_verifier("correct horse", &SALT), VERIFIER);
+        assert_eq!(
+            reset_password_sql("reporter", "correct horse", &SALT).unwrap(),
+            format!("ALTER ROLE \"reporter\" PASSWORD '{}';", VERIFIER)
+        );
+        assert!(reset_password_sql("reporter", "", &SALT).is_err());
+    }
+
+    #[test]
+    fn creates_roles() {
+        assert_eq!(
+            create_role_sql(&role(), Some("correct horse"), &SALT).unwrap(),
+            vec![
+                format!(
+                    "CREATE ROLE \"reporter\" LOGIN PASSWORD '{}' VALID UNTIL '2027-01-01' \
+                     CONNECTION LIMIT 5;",
+                    VERIFIER
+                ),
+                "GRANT \"readers\" TO \"reporter\";".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn alters_only_what_changed() {
+        let altered = RoleDefinition {
+            login: false,
+            valid_until: None,
+            connection_limit: None,
+            member_of: vec!["writers".to_string()],
+            ..role()
+        };
+        assert_eq!(
+            alter_role_sql(&role(), &altered, None, &SALT).unwrap(),
+            vec![
+                "ALTER ROLE \"reporter\" NOLOGIN VALID UNTIL 'infinity' CONNECTION LIMIT -1;",
+                "GRANT \"writers\" TO \"reporter\";",
+                "REVOKE \"readers\" FROM \"reporter\";",
+            ]
+        );
+        assert!(alter_role_sql(&role(), &role(), None, &SALT).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! The max-rows limit on editor queries, and spotting queries that would
+//! return a large result without one.
+
+use super::tagging::skip_leading_comments;
+
+/// Rows fetched for a SELECT without its own LIMIT, unless changed.
+pub const DEFAULT_MAX_ROWS: usize = 1_000;
+
+/// Estimated rows above which an unlimited query asks before running.
+pub const LARGE_RESULT_ROWS: u64 = 100_000;
+
+/// Whether `sql` already limits its rows with `LIMIT` or `FETCH FIRST`.
+pub fn has_row_limit(sql: &str) -> bool {
+    let words: Vec<String> = sql
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_lowercase())
+        .collect();
+    words.iter().any(|w| w == "limit")
+        || words
+            .windows(2)
+            .any(|pair| pair[0] == "fetch" && (pair[1] == "first" || pair[1] == "next"))
+}
+
+/// Whether `sql` is a query whose rows are fetched into the grid, and so
+/// subject to the max-rows limit.
+pub fn is_row_query(sql: &str) -> bool {
+    let trimmed = skip_leading_comments(sql).trim_start().to_lowercase();
+    trimmed.starts_with("select") || trimmed.starts_with("with")
+}
+
+/// `sql` with `LIMIT limit` appended, dropping a trailing semicolon.
+pub fn with_limit(sql: &str, limit: usize) -> String {
+    format!(
+        "{} LIMIT {}",
+        sql.trim_end().trim_end_matches(';').trim_end(),
+        limit
+    )
+}
+
+/// The statement to send for `sql` under `max_rows`, and the limit that
+/// applies to its rows; a query with its own LIMIT is left alone. One row
+/// more than the limit is asked for, so a result cut short can be told
+/// apart from one that fits exactly.
+pub fn limited_sql(sql: &str, max_rows: Option<usize>) -> (String, Option<usize>) {
+    match max_rows {
+        Some(max_rows) if !has_row_limit(sql) => (with_limit(sql, max_rows + 1), Some(max_rows)),
+        _ => (sql.to_string(), None),
+    }
+}
+
+/// Cut `rows` down to `max_rows`, returning the limit when rows were
+/// dropped.
+pub fn truncate_rows<T>(rows: &mut Vec<T>, max_rows: Option<usize>) -> Option<usize> {
+    let max_rows = max_rows?;
+    if rows.len() <= max_rows {
+        return None;
+    }
+    rows.truncate(max_rows);
+    Some(max_rows)
+}
+
+/// Planner row estimate from the first line of a Postgres text `EXPLAIN`,
+/// e.g. `Seq Scan on users  (cost=0.00..155.00 rows=10000 width=4)`.
+pub fn parse_plan_rows(line: &str) -> Option<u64> {
+    let rest = &line[line.find("rows=")? + "rows=".len()..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Planner total cost from the first line of a Postgres text `EXPLAIN`,
+/// e.g. `155.00` from `(cost=0.00..155.00 rows=10000 width=4)`.
+pub fn parse_plan_cost(line: &str) -> Option<f64> {
+    let rest = &line[line.find("cost=")? + "cost=".len()..];
+    let total = &rest[rest.find("..")? + "..".len()..];
+    total.split_whitespace().next()?.parse().ok()
+}
+
+/// What the server's query plan expects of a statement. Nothing is run.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PlanEstimate {
+    /// Total cost in the planner's units (Postgres only).
+    pub cost: Option<f64>,
+    pub rows: Option<u64>,
+}
+
+impl PlanEstimate {
+    /// E.g. `Est. cost 155.00 · ~10,000 rows`, or `None` when the plan gave
+    /// no estimate.
+    pub fn summary(&self) -> Option<String> {
+        let rows = self.rows.map(|rows| {
+            format!(
+                "~{} row{}",
+                format_row_count(rows),
+                if rows == 1 { "" } else { "s" }
+            )
+        });
+        match (self.cost, rows) {
+            (Some(cost), Some(rows)) => Some(format!("Est. cost {:.2} · {}", cost, rows)),
+            (Some(cost), None) => Some(format!("Est. cost {:.2}", cost)),
+            (None, Some(rows)) => Some(format!("Est. {}", rows)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// `1,234,567`
+pub fn format_row_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_existing_limits() {
+        assert!(has_row_limit("SELECT * FROM users LIMIT 10"));
+        assert!(has_row_limit("select *\nfrom users\nlimit\n10"));
+        assert!(has_row_limit("SELECT * FROM (SELECT * FROM t LIMIT(5)) s"));
+        assert!(has_row_limit("SELECT * FROM users FETCH FIRST 5 ROWS ONLY"));
+        assert!(!has_row_limit("SELECT * FROM users"));
+        assert!(!has_row_limit("SELECT limited FROM users"));
+    }
+
+    #[test]
+    fn limits_row_queries_by_one_extra_row() {
+        assert_eq!(
+            limited_sql("SELECT * FROM users;", Some(1_000)),
+            ("SELECT * FROM users LIMIT 1001".to_string(), Some(1_000))
+        );
+        assert_eq!(
+            limited_sql("SELECT * FROM users LIMIT 5000", Some(1_000)),
+            ("SELECT * FROM users LIMIT 5000".to_string(), None)
+        );
+        assert_eq!(
+            limited_sql("SELECT * FROM users", None),
+            ("SELECT * FROM users".to_string(), None)
+        );
+        assert!(is_row_query(
+            "-- report\nWITH t AS (SELECT 1) SELECT * FROM t"
+        ));
+        assert!(!is_row_query("UPDATE users SET name = 'x'"));
+    }
+
+    #[test]
+    fn truncates_only_past_the_limit() {
+        let mut rows = vec![1, 2, 3];
+        assert_eq!(truncate_rows(&mut rows, Some(3)), None);
+        assert_eq!(truncate_rows(&mut rows, None), None);
+        assert_eq!(truncate_rows(&mut rows, Some(2)), Some(2));
+        assert_eq!(rows, vec![1, 2]);
+    }
+
+    #[test]
+    fn reads_plan_estimates() {
+        assert_eq!(
+            parse_plan_rows("Seq Scan on users  (cost=0.00..155.00 rows=10000 width=4)"),
+            Some(10_000)
+        );
+        assert_eq!(
+            parse_plan_rows("Result  (cost=0.00..0.01 rows=1 width=4)"),
+            Some(1)
+        );
+        assert_eq!(parse_plan_rows("Planning Time: 0.1 ms"), None);
+        assert_eq!(
+            parse_plan_cost("Seq Scan on users  (cost=0.00..155.00 rows=10000 width=4)"),
+            Some(155.0)
+        );
+        assert_eq!(parse_plan_cost("Planning Time: 0.1 ms"), None);
+        assert_eq!(format_row_count(1_234_567), "1,234,567");
+        assert_eq!(format_row_count(999), "999");
+    }
+
+    #[test]
+    fn summarizes_plan_estimates() {
+        let estimate = PlanEstimate {
+            cost: Some(155.0),
+            rows: Some(10_000),
+        };
+        assert_eq!(
+            estimate.summary().as_deref(),
+            Some("Est. cost 155.00 · ~10,000 rows")
+        );
+        let estimate = PlanEstimate {
+            cost: None,
+            rows: Some(1),
+        };
+        assert_eq!(estimate.summary().as_deref(), Some("Est. ~1 row"));
+        assert_eq!(PlanEstimate::default().summary(), None);
+    }
+}
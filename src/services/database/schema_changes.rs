@@ -0,0 +1,348 @@
+//! Lightweight change tracking for databases without migrations: the
+//! tables and columns of a database are snapshotted on connecting, and
+//! consecutive snapshots are compared into a log of what was added,
+//! dropped and altered.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::types::DatabaseSchema;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotColumn {
+    pub name: String,
+    pub data_type: String,
+    pub is_nullable: bool,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotTable {
+    pub schema: String,
+    pub name: String,
+    pub table_type: String,
+    /// In column order.
+    pub columns: Vec<SnapshotColumn>,
+}
+
+impl SnapshotTable {
+    fn qualified_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
+    }
+}
+
+/// The tables and columns of a database at one moment, sorted so equal
+/// structures serialize the same.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaSnapshot {
+    pub tables: Vec<SnapshotTable>,
+}
+
+impl SchemaSnapshot {
+    pub fn from_schema(schema: &DatabaseSchema) -> Self {
+        let mut tables: Vec<SnapshotTable> = schema
+            .tables
+            .iter()
+            .map(|table| {
+                let mut columns = table.columns.clone();
+                columns.sort_by_key(|column| column.ordinal_position);
+                SnapshotTable {
+                    schema: table.table_schema.clone(),
+                    name: table.table_name.clone(),
+                    table_type: table.table_type.clone(),
+                    columns: columns
+                        .into_iter()
+                        .map(|column| SnapshotColumn {
+                            name: column.column_name,
+                            data_type: column.data_type,
+                            is_nullable: column.is_nullable,
+                            default: column.column_default,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        tables.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        Self { tables }
+    }
+}
+
+/// One difference between two snapshots. Tables are named with their
+/// schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    TableAdded {
+        table: String,
+        table_type: String,
+    },
+    TableDropped {
+        table: String,
+    },
+    ColumnAdded {
+        table: String,
+        column: String,
+        data_type: String,
+    },
+    ColumnDropped {
+        table: String,
+        column: String,
+    },
+    /// What changed, e.g. `integer → bigint`, `now NOT NULL`.
+    ColumnAltered {
+        table: String,
+        column: String,
+        details: Vec<String>,
+    },
+}
+
+/// Whether a change added, dropped or altered something, for marking it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaChangeKind {
+    Added,
+    Dropped,
+    Altered,
+}
+
+impl SchemaChange {
+    pub fn kind(&self) -> SchemaChangeKind {
+        match self {
+            Self::TableAdded { .. } | Self::ColumnAdded { .. } => SchemaChangeKind::Added,
+            Self::TableDropped { .. } | Self::ColumnDropped { .. } => SchemaChangeKind::Dropped,
+            Self::ColumnAltered { .. } => SchemaChangeKind::Altered,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Self::TableAdded { table, table_type } => {
+                format!("{} added ({})", table, table_type.to_lowercase())
+            }
+            Self::TableDropped { table } => format!("{} dropped", table),
+            Self::ColumnAdded {
+                table,
+                column,
+                data_type,
+            } => format!("{}.{} added ({})", table, column, data_type),
+            Self::ColumnDropped { table, column } => format!("{}.{} dropped", table, column),
+            Self::ColumnAltered {
+                table,
+                column,
+                details,
+            } => format!("{}.{} altered: {}", table, column, details.join(", ")),
+        }
+    }
+}
+
+fn column_details(before: &SnapshotColumn, after: &SnapshotColumn) -> Vec<String> {
+    let mut details = vec![];
+    if before.data_type != after.data_type {
+        details.push(format!("{} → {}", before.data_type, after.data_type));
+    }
+    if before.is_nullable != after.is_nullable {
+        details.push(
+            if after.is_nullable {
+                "now nullable"
+            } else {
+                "now NOT NULL"
+            }
+            .to_string(),
+        );
+    }
+    if before.default != after.default {
+        details.push(match &after.default {
+            Some(default) => format!("default {}", default),
+            None => "default removed".to_string(),
+        });
+    }
+    details
+}
+
+/// What changed from `before` to `after`: dropped tables first, then
+/// added ones, then column changes, each in table order.
+pub fn schema_changes(before: &SchemaSnapshot, after: &SchemaSnapshot) -> Vec<SchemaChange> {
+    let find = |snapshot: &'_ SchemaSnapshot, table: &SnapshotTable| {
+        snapshot
+            .tables
+            .iter()
+            .find(|t| t.schema == table.schema && t.name == table.name)
+            .cloned()
+    };
+    let mut changes: Vec<SchemaChange> = before
+        .tables
+        .iter()
+        .filter(|table| find(after, table).is_none())
+        .map(|table| SchemaChange::TableDropped {
+            table: table.qualified_name(),
+        })
+        .collect();
+    changes.extend(
+        after
+            .tables
+            .iter()
+            .filter(|table| find(before, table).is_none())
+            .map(|table| SchemaChange::TableAdded {
+                table: table.qualified_name(),
+                table_type: table.table_type.clone(),
+            }),
+    );
+
+    for table in &after.tables {
+        let Some(old) = find(before, table) else {
+            continue;
+        };
+        let name = table.qualified_name();
+        for column in &old.columns {
+            if !table.columns.iter().any(|c| c.name == column.name) {
+                changes.push(SchemaChange::ColumnDropped {
+                    table: name.clone(),
+                    column: column.name.clone(),
+                });
+            }
+        }
+        for column in &table.columns {
+            match old.columns.iter().find(|c| c.name == column.name) {
+                None => changes.push(SchemaChange::ColumnAdded {
+                    table: name.clone(),
+                    column: column.name.clone(),
+                    data_type: column.data_type.clone(),
+                }),
+                Some(old_column) => {
+                    let details = column_details(old_column, column);
+                    if !details.is_empty() {
+                        changes.push(SchemaChange::ColumnAltered {
+                            table: name.clone(),
+                            column: column.name.clone(),
+                            details,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// A snapshot and how it differs from the one before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChangeEntry {
+    pub taken_at: DateTime<Utc>,
+    /// The first snapshot, which everything after is compared with.
+    pub baseline: bool,
+    pub tables: usize,
+    pub changes: Vec<SchemaChange>,
+}
+
+/// The change log of `snapshots` (oldest first), latest entry first.
+/// Snapshots identical to the one before are left out.
+pub fn change_log(snapshots: &[(DateTime<Utc>, SchemaSnapshot)]) -> Vec<SchemaChangeEntry> {
+    let mut entries = vec![];
+    let mut previous: Option<&SchemaSnapshot> = None;
+    for (taken_at, snapshot) in snapshots {
+        let changes = previous.map(|before| schema_changes(before, snapshot));
+        if changes.as_ref().is_none_or(|changes| !changes.is_empty()) {
+            entries.push(SchemaChangeEntry {
+                taken_at: *taken_at,
+                baseline: changes.is_none(),
+                tables: snapshot.tables.len(),
+                changes: changes.unwrap_or_default(),
+            });
+        }
+        previous = Some(snapshot);
+    }
+    entries.reverse();
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn column(name: &str, data_type: &str) -> SnapshotColumn {
+        SnapshotColumn {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_nullable: true,
+            default: None,
+        }
+    }
+
+    fn table(name: &str, columns: Vec<SnapshotColumn>) -> SnapshotTable {
+        SnapshotTable {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            table_type: "BASE TABLE".to_string(),
+            columns,
+        }
+    }
+
+    #[test]
+    fn finds_added_dropped_and_altered_objects() {
+        let before = SchemaSnapshot {
+            tables: vec![
+                table("legacy", vec![column("id", "integer")]),
+                table(
+                    "users",
+                    vec![
+                        column("id", "integer"),
+                        column("fax", "text"),
+                        column("email", "text"),
+                    ],
+                ),
+            ],
+        };
+        let mut email = column("email", "character varying");
+        email.is_nullable = false;
+        let after = SchemaSnapshot {
+            tables: vec![
+                table("orders", vec![column("id", "bigint")]),
+                table(
+                    "users",
+                    vec![
+                        column("id", "integer"),
+                        email,
+                        column("created_at", "timestamp"),
+                    ],
+                ),
+            ],
+        };
+
+        let descriptions: Vec<String> = schema_changes(&before, &after)
+            .iter()
+            .map(|change| change.description())
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                "public.legacy dropped",
+                "public.orders added (base table)",
+                "public.users.fax dropped",
+                "public.users.email altered: text → character varying, now NOT NULL",
+                "public.users.created_at added (timestamp)",
+            ]
+        );
+        assert!(schema_changes(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn logs_only_snapshots_that_changed_latest_first() {
+        let at = |day: u32| Utc.with_ymd_and_hms(2024, 5, day, 9, 0, 0).unwrap();
+        let first = SchemaSnapshot {
+            tables: vec![table("users", vec![column("id", "integer")])],
+        };
+        let mut second = first.clone();
+        second.tables[0].columns.push(column("name", "text"));
+
+        let log = change_log(&[
+            (at(1), first.clone()),
+            (at(2), first),
+            (at(3), second.clone()),
+            (at(4), second),
+        ]);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].taken_at, at(3));
+        assert_eq!(log[0].changes.len(), 1);
+        assert!(log[1].baseline);
+        assert_eq!(log[1].tables, 1);
+    }
+}
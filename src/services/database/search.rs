@@ -0,0 +1,348 @@
+//! Fuzzy search over the objects of the connected database.
+//!
+//! Names come from what is already loaded (tables, the cached schema and
+//! routines); view and function bodies are only searched when their
+//! sources have been fetched.
+
+use std::collections::HashSet;
+
+use super::types::{DatabaseSchema, RoutineInfo, TableInfo};
+
+/// The body of a view or routine, for searching inside it.
+#[derive(Debug, Clone)]
+pub struct ObjectSource {
+    pub schema: String,
+    pub name: String,
+    /// Identity arguments of a routine, telling overloads apart. `None`
+    /// for views, and for routines of drivers without overloading.
+    pub arguments: Option<String>,
+    pub definition: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum ObjectHit {
+    Table(TableInfo),
+    Column { table: TableInfo, column: String },
+    Routine(RoutineInfo),
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectMatch {
+    pub hit: ObjectHit,
+    /// Matched inside the object's source rather than its name.
+    pub in_source: bool,
+    score: i64,
+}
+
+impl ObjectMatch {
+    /// Short kind label, in the style of the table tree.
+    pub fn kind(&self) -> &'static str {
+        match &self.hit {
+            ObjectHit::Table(table) => match table.table_type.as_str() {
+                "VIEW" => "VIEW",
+                "MATERIALIZED VIEW" => "MATV",
+                _ => "TABLE",
+            },
+            ObjectHit::Column { .. } => "COLUMN",
+            ObjectHit::Routine(routine) if routine.routine_type == "PROCEDURE" => "PROC",
+            ObjectHit::Routine(_) => "FUNC",
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match &self.hit {
+            ObjectHit::Table(table) => format!("{}.{}", table.table_schema, table.table_name),
+            ObjectHit::Column { table, column } => {
+                format!("{}.{}.{}", table.table_schema, table.table_name, column)
+            }
+            ObjectHit::Routine(routine) => routine.signature(),
+        }
+    }
+}
+
+/// Score `candidate` against `query` when all of the query's characters
+/// appear in it in order (case-insensitive). Consecutive characters,
+/// characters at word starts, prefixes and exact matches score higher;
+/// longer candidates slightly lower.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+    for (ix, c) in candidate.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if *c != query[matched] {
+            continue;
+        }
+        score += 10;
+        if ix > 0 && previous == Some(ix - 1) {
+            score += 15;
+        }
+        if ix == 0 || matches!(candidate[ix - 1], '_' | '.' | ' ' | '-') {
+            score += 10;
+        }
+        previous = Some(ix);
+        matched += 1;
+    }
+    if matched < query.len() {
+        return None;
+    }
+
+    if candidate == query {
+        score += 100;
+    } else if candidate.starts_with(&query) {
+        score += 30;
+    }
+    Some(score - candidate.len() as i64)
+}
+
+/// Objects matching `query`, best first, at most `limit`. Names of
+/// tables, views, columns and routines are matched fuzzily; `sources`
+/// are searched for the query as a plain substring.
+pub fn search_objects(
+    query: &str,
+    tables: &[TableInfo],
+    schema: Option<&DatabaseSchema>,
+    routines: &[RoutineInfo],
+    sources: &[ObjectSource],
+    limit: usize,
+) -> Vec<ObjectMatch> {
+    let query = query.trim();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut matches: Vec<ObjectMatch> = vec![];
+    let name_match = |hit: ObjectHit, name: &str| {
+        fuzzy_score(query, name).map(|score| ObjectMatch {
+            hit,
+            in_source: false,
+            score,
+        })
+    };
+
+    matches.extend(
+        tables
+            .iter()
+            .filter_map(|t| name_match(ObjectHit::Table(t.clone()), &t.table_name)),
+    );
+
+    for table_schema in schema.map(|s| s.tables.as_slice()).unwrap_or_default() {
+        let table = tables
+            .iter()
+            .find(|t| {
+                t.table_schema == table_schema.table_schema
+                    && t.table_name == table_schema.table_name
+            })
+            .cloned()
+            .unwrap_or_else(|| TableInfo {
+                table_name: table_schema.table_name.clone(),
+                table_schema: table_schema.table_schema.clone(),
+                table_type: table_schema.table_type.clone(),
+            });
+        matches.extend(table_schema.columns.iter().filter_map(|c| {
+            let hit = ObjectHit::Column {
+                table: table.clone(),
+                column: c.column_name.clone(),
+            };
+            // Columns are numerous; a table or routine of the same name
+            // ranks first.
+            name_match(hit, &c.column_name).map(|m| ObjectMatch {
+                score: m.score - 5,
+                ..m
+            })
+        }));
+    }
+
+    matches.extend(
+        routines
+            .iter()
+            .filter_map(|r| name_match(ObjectHit::Routine(r.clone()), &r.routine_name)),
+    );
+
+    let needle = query.to_lowercase();
+    let named: HashSet<String> = matches.iter().map(|m| m.title()).collect();
+    for source in sources {
+        if !source.definition.to_lowercase().contains(&needle) {
+            continue;
+        }
+        let views = tables
+            .iter()
+            .filter(|t| t.table_schema == source.schema && t.table_name == source.name)
+            .map(|t| ObjectHit::Table(t.clone()));
+        let routines = routines
+            .iter()
+            .filter(|r| {
+                r.routine_schema == source.schema
+                    && r.routine_name == source.name
+                    && source.arguments.as_ref().is_none_or(|a| *a == r.arguments)
+            })
+            .map(|r| ObjectHit::Routine(r.clone()));
+        for hit in views.chain(routines) {
+            let found = ObjectMatch {
+                hit,
+                in_source: true,
+                score: 0,
+            };
+            if !named.contains(&found.title()) {
+                matches.push(found);
+            }
+        }
+    }
+
+    // Source matches follow all name matches.
+    matches.sort_by(|a, b| {
+        a.in_source
+            .cmp(&b.in_source)
+            .then_with(|| b.score.cmp(&a.score))
+            .then_with(|| a.title().cmp(&b.title()))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::types::{ColumnDetail, TableSchema};
+
+    fn table(schema: &str, name: &str, table_type: &str) -> TableInfo {
+        TableInfo {
+            table_name: name.to_string(),
+            table_schema: schema.to_string(),
+            table_type: table_type.to_string(),
+        }
+    }
+
+    fn routine(name: &str, arguments: &str) -> RoutineInfo {
+        RoutineInfo {
+            routine_schema: "public".to_string(),
+            routine_name: name.to_string(),
+            routine_type: "FUNCTION".to_string(),
+            arguments: arguments.to_string(),
+            result_type: Some("integer".to_string()),
+        }
+    }
+
+    fn schema_with_column(table: &TableInfo, column: &str) -> DatabaseSchema {
+        DatabaseSchema {
+            tables: vec![TableSchema {
+                table_name: table.table_name.clone(),
+                table_schema: table.table_schema.clone(),
+                table_type: table.table_type.clone(),
+                columns: vec![ColumnDetail {
+                    column_name: column.to_string(),
+                    data_type: "integer".to_string(),
+                    is_nullable: false,
+                    column_default: None,
+                    ordinal_position: 1,
+                    character_maximum_length: None,
+                    numeric_precision: None,
+                    numeric_scale: None,
+                    full_type: None,
+                    collation: None,
+                    extra: None,
+                    description: None,
+                }],
+                primary_keys: vec![],
+                foreign_keys: vec![],
+                indexes: vec![],
+                constraints: vec![],
+                triggers: vec![],
+                description: None,
+            }],
+            total_tables: 1,
+        }
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_exact_prefix_and_word_starts() {
+        assert_eq!(fuzzy_score("xyz", "orders"), None);
+        assert_eq!(fuzzy_score("", "orders"), None);
+
+        let exact = fuzzy_score("orders", "orders").unwrap();
+        let prefix = fuzzy_score("orders", "orders_archive").unwrap();
+        let word_starts = fuzzy_score("oi", "order_items").unwrap();
+        let scattered = fuzzy_score("oi", "location").unwrap();
+        assert!(exact > prefix);
+        assert!(word_starts > scattered);
+        assert!(fuzzy_score("ORD", "orders").is_some());
+    }
+
+    #[test]
+    fn matches_tables_columns_and_routines_by_name() {
+        let orders = table("sales", "orders", "BASE TABLE");
+        let tables = vec![orders.clone(), table("sales", "customers", "VIEW")];
+        let schema = schema_with_column(&orders, "order_total");
+        let routines = vec![routine("order_count", "since date")];
+
+        let found = search_objects("order", &tables, Some(&schema), &routines, &[], 10);
+        let titles: Vec<String> = found.iter().map(|m| m.title()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "sales.orders",
+                "public.order_count(since date)",
+                "sales.orders.order_total",
+            ]
+        );
+        assert_eq!(found[0].kind(), "TABLE");
+        assert_eq!(found[2].kind(), "COLUMN");
+
+        assert_eq!(
+            search_objects("cust", &tables, None, &[], &[], 10)[0].kind(),
+            "VIEW"
+        );
+        assert_eq!(search_objects("order", &tables, None, &[], &[], 1).len(), 1);
+        assert!(search_objects("  ", &tables, None, &[], &[], 10).is_empty());
+    }
+
+    #[test]
+    fn source_matches_pick_the_right_overload_once() {
+        let tables = vec![table("public", "active_users", "VIEW")];
+        let routines = vec![routine("touch", "id integer"), routine("touch", "")];
+        let sources = vec![
+            ObjectSource {
+                schema: "public".to_string(),
+                name: "active_users".to_string(),
+                arguments: None,
+                definition: "SELECT * FROM users WHERE deleted_at IS NULL".to_string(),
+            },
+            ObjectSource {
+                schema: "public".to_string(),
+                name: "touch".to_string(),
+                arguments: Some("id integer".to_string()),
+                definition: "UPDATE users SET deleted_at = now()".to_string(),
+            },
+        ];
+
+        let found = search_objects("deleted_at", &tables, None, &routines, &sources, 10);
+        let titles: Vec<String> = found.iter().map(|m| m.title()).collect();
+        assert_eq!(
+            titles,
+            vec!["public.active_users", "public.touch(id integer)"]
+        );
+        assert!(found.iter().all(|m| m.in_source));
+
+        // A name match is not repeated as a source match.
+        let found = search_objects("users", &tables, None, &routines, &sources, 10);
+        let titles: Vec<String> = found.iter().map(|m| m.title()).collect();
+        assert_eq!(
+            titles,
+            vec!["public.active_users", "public.touch(id integer)"]
+        );
+        assert!(!found[0].in_source);
+        assert!(found[1].in_source);
+    }
+}
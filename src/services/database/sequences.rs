@@ -0,0 +1,214 @@
+//! Sequence and identity column maintenance.
+//!
+//! On Postgres these are real sequences, including the ones backing
+//! `serial` and identity columns. MySQL has no sequences, so every table
+//! with an AUTO_INCREMENT column is listed as one, named after the table.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::storage::DatabaseDriver;
+
+/// The column a sequence feeds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceOwner {
+    pub table_schema: String,
+    pub table_name: String,
+    pub column_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceInfo {
+    pub sequence_schema: String,
+    pub sequence_name: String,
+    /// Last value handed out; `None` if the sequence has never been used.
+    pub last_value: Option<i64>,
+    pub start_value: i64,
+    pub increment_by: i64,
+    /// Owning column for `serial`, identity and AUTO_INCREMENT columns.
+    pub owner: Option<SequenceOwner>,
+    pub is_identity: bool,
+    /// Current `MAX()` of the owning column, when it could be read.
+    pub column_max: Option<i64>,
+}
+
+impl SequenceInfo {
+    pub fn qualified_name(&self, driver: &DatabaseDriver) -> String {
+        format!(
+            "{}.{}",
+            driver.quote_ident(&self.sequence_schema),
+            driver.quote_ident(&self.sequence_name)
+        )
+    }
+
+    /// Value the next insert will receive; `None` once the sequence has
+    /// run out of bigint values.
+    pub fn next_value(&self) -> Option<i64> {
+        match self.last_value {
+            Some(last) => last.checked_add(self.increment_by),
+            None => Some(self.start_value),
+        }
+    }
+
+    /// The next value would collide with existing rows, typically after a
+    /// bulk load or restore that wrote explicit ids. Only ascending
+    /// sequences are checked.
+    pub fn is_out_of_sync(&self) -> bool {
+        match self.column_max {
+            Some(max) if self.increment_by > 0 => self.next_value().is_some_and(|next| next <= max),
+            _ => false,
+        }
+    }
+}
+
+/// Restart `seq` at `value`, or at its start value when `None`.
+pub fn restart_sequence_sql(
+    driver: &DatabaseDriver,
+    seq: &SequenceInfo,
+    value: Option<i64>,
+) -> String {
+    let restart = match value {
+        Some(value) => format!("RESTART WITH {}", value),
+        None => "RESTART".to_string(),
+    };
+
+    match (driver, &seq.owner) {
+        (DatabaseDriver::Postgres, Some(owner)) if seq.is_identity => format!(
+            "ALTER TABLE {}.{} ALTER COLUMN {} {};",
+            driver.quote_ident(&owner.table_schema),
+            driver.quote_ident(&owner.table_name),
+            driver.quote_ident(&owner.column_name),
+            restart
+        ),
+        (DatabaseDriver::Postgres, _) => {
+            format!("ALTER SEQUENCE {} {};", seq.qualified_name(driver), restart)
+        }
+        (DatabaseDriver::MySql, _) => format!(
+            "ALTER TABLE {} AUTO_INCREMENT = {};",
+            seq.qualified_name(driver),
+            value.unwrap_or(seq.start_value)
+        ),
+    }
+}
+
+/// Move `seq` past the largest value already in its owning column.
+pub fn sync_sequence_sql(driver: &DatabaseDriver, seq: &SequenceInfo) -> Result<String> {
+    let owner = seq.owner.as_ref().ok_or_else(|| {
+        anyhow!(
+            "{}.{} is not owned by a column",
+            seq.sequence_schema,
+            seq.sequence_name
+        )
+    })?;
+
+    Ok(match driver {
+        DatabaseDriver::Postgres => format!(
+            // An empty table starts over at the start value, which MINVALUE
+            // may hold above 1
+            "SELECT setval({}, GREATEST(COALESCE(MAX({}), 0) + 1, {}), false) FROM {}.{};",
+            driver.quote_literal(&seq.qualified_name(driver)),
+            driver.quote_ident(&owner.column_name),
+            seq.start_value,
+            driver.quote_ident(&owner.table_schema),
+            driver.quote_ident(&owner.table_name)
+        ),
+        // ALTER TABLE does not accept a subquery here; InnoDB raises a
+        // value below MAX() + 1 to MAX() + 1 anyway.
+        DatabaseDriver::MySql => format!(
+            "ALTER TABLE {} AUTO_INCREMENT = {};",
+            seq.qualified_name(driver),
+            seq.column_max.map_or(1, |max| max + 1)
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orders_seq(is_identity: bool) -> SequenceInfo {
+        SequenceInfo {
+            sequence_schema: "public".to_string(),
+            sequence_name: "orders_id_seq".to_string(),
+            last_value: Some(10),
+            start_value: 1,
+            increment_by: 1,
+            owner: Some(SequenceOwner {
+                table_schema: "public".to_string(),
+                table_name: "orders".to_string(),
+                column_name: "id".to_string(),
+            }),
+            is_identity,
+            column_max: Some(42),
+        }
+    }
+
+    #[test]
+    fn detects_sequence_behind_column_max() {
+        let mut seq = orders_seq(false);
+        assert!(seq.is_out_of_sync());
+        seq.last_value = Some(42);
+        assert!(!seq.is_out_of_sync());
+        seq.last_value = None;
+        seq.start_value = 43;
+        assert!(!seq.is_out_of_sync());
+    }
+
+    #[test]
+    fn exhausted_sequence_has_no_next_value() {
+        let mut seq = orders_seq(false);
+        seq.last_value = Some(i64::MAX);
+        assert_eq!(seq.next_value(), None);
+        seq.column_max = Some(i64::MAX);
+        assert!(!seq.is_out_of_sync());
+
+        seq.last_value = Some(i64::MAX - 1);
+        assert_eq!(seq.next_value(), Some(i64::MAX));
+        assert!(seq.is_out_of_sync());
+    }
+
+    #[test]
+    fn restart_uses_alter_column_for_identity() {
+        assert_eq!(
+            restart_sequence_sql(&DatabaseDriver::Postgres, &orders_seq(true), Some(100)),
+            "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"id\" RESTART WITH 100;"
+        );
+        assert_eq!(
+            restart_sequence_sql(&DatabaseDriver::Postgres, &orders_seq(false), None),
+            "ALTER SEQUENCE \"public\".\"orders_id_seq\" RESTART;"
+        );
+    }
+
+    #[test]
+    fn sync_sets_next_value_from_column_max() {
+        assert_eq!(
+            sync_sequence_sql(&DatabaseDriver::Postgres, &orders_seq(false)).unwrap(),
+            "SELECT setval('\"public\".\"orders_id_seq\"', \
+             GREATEST(COALESCE(MAX(\"id\"), 0) + 1, 1), false) FROM \"public\".\"orders\";"
+        );
+
+        let mut seq = orders_seq(false);
+        seq.start_value = 1000;
+        assert!(
+            sync_sequence_sql(&DatabaseDriver::Postgres, &seq)
+                .unwrap()
+                .contains("GREATEST(COALESCE(MAX(\"id\"), 0) + 1, 1000)")
+        );
+    }
+
+    #[test]
+    fn sync_on_mysql_sets_auto_increment() {
+        let mut seq = orders_seq(false);
+        seq.sequence_name = "orders".to_string();
+        assert_eq!(
+            sync_sequence_sql(&DatabaseDriver::MySql, &seq).unwrap(),
+            "ALTER TABLE `public`.`orders` AUTO_INCREMENT = 43;"
+        );
+    }
+
+    #[test]
+    fn sync_requires_owner() {
+        let mut seq = orders_seq(false);
+        seq.owner = None;
+        assert!(sync_sequence_sql(&DatabaseDriver::Postgres, &seq).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! The Postgres server's configuration from `pg_settings`: values in
+//! readable units, grouped by category, and how they differ from the
+//! built-in defaults.
+
+/// Parameters most often changed from their defaults when tuning a server.
+pub const COMMONLY_TUNED: &[&str] = &[
+    "autovacuum_analyze_scale_factor",
+    "autovacuum_max_workers",
+    "autovacuum_naptime",
+    "autovacuum_vacuum_cost_limit",
+    "autovacuum_vacuum_scale_factor",
+    "checkpoint_completion_target",
+    "checkpoint_timeout",
+    "default_statistics_target",
+    "effective_cache_size",
+    "effective_io_concurrency",
+    "huge_pages",
+    "idle_in_transaction_session_timeout",
+    "log_min_duration_statement",
+    "maintenance_work_mem",
+    "max_connections",
+    "max_parallel_maintenance_workers",
+    "max_parallel_workers",
+    "max_parallel_workers_per_gather",
+    "max_wal_size",
+    "max_worker_processes",
+    "min_wal_size",
+    "random_page_cost",
+    "shared_buffers",
+    "shared_preload_libraries",
+    "statement_timeout",
+    "synchronous_commit",
+    "wal_buffers",
+    "wal_compression",
+    "wal_level",
+    "work_mem",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSetting {
+    pub name: String,
+    /// E.g. `Resource Usage / Memory`.
+    pub category: String,
+    /// The current value, in `unit`s.
+    pub setting: String,
+    /// `8kB`, `ms` and the like; `None` for unitless settings.
+    pub unit: Option<String>,
+    /// The value built into the server, in `unit`s.
+    pub boot_val: Option<String>,
+    /// Where the value comes from: `default`, `configuration file`, ...
+    pub source: String,
+    /// When a change takes effect: `postmaster` needs a restart.
+    pub context: String,
+    pub short_desc: String,
+    /// Changed in the configuration file, but not until a restart.
+    pub pending_restart: bool,
+}
+
+impl ServerSetting {
+    /// The current value as `SHOW` gives it, e.g. `128MB` rather than
+    /// `16384` blocks of `8kB`.
+    pub fn value(&self) -> String {
+        normalize_value(&self.setting, self.unit.as_deref())
+    }
+
+    /// The built-in default, in the same form as `value`.
+    pub fn default_value(&self) -> Option<String> {
+        let boot_val = self.boot_val.as_deref()?;
+        Some(normalize_value(boot_val, self.unit.as_deref()))
+    }
+
+    /// Whether the server runs with a value other than its built-in one.
+    pub fn is_changed(&self) -> bool {
+        self.source != "default" && self.boot_val.as_ref() != Some(&self.setting)
+    }
+
+    pub fn is_commonly_tuned(&self) -> bool {
+        COMMONLY_TUNED.contains(&self.name.as_str())
+    }
+}
+
+/// Which settings to list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsFilter {
+    /// Matched against names, descriptions and categories, ignoring case.
+    pub text: String,
+    pub commonly_tuned: bool,
+    pub changed_only: bool,
+}
+
+impl SettingsFilter {
+    pub fn matches(&self, setting: &ServerSetting) -> bool {
+        let text = self.text.trim().to_lowercase();
+        let text_matches = text.is_empty()
+            || [&setting.name, &setting.short_desc, &setting.category]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&text));
+        text_matches
+            && (!self.commonly_tuned || setting.is_commonly_tuned())
+            && (!self.changed_only || setting.is_changed())
+    }
+}
+
+/// `settings` passing `filter`, grouped by category in order of first
+/// appearance.
+pub fn group_settings<'a>(
+    settings: &'a [ServerSetting],
+    filter: &SettingsFilter,
+) -> Vec<(&'a str, Vec<&'a ServerSetting>)> {
+    let mut groups: Vec<(&str, Vec<&ServerSetting>)> = vec![];
+    for setting in settings.iter().filter(|setting| filter.matches(setting)) {
+        match groups
+            .iter_mut()
+            .find(|(category, _)| *category == setting.category)
+        {
+            Some((_, group)) => group.push(setting),
+            None => groups.push((&setting.category, vec![setting])),
+        }
+    }
+    groups
+}
+
+/// `value` in `unit`s written with the largest unit it is a whole number
+/// of, as `SHOW` does, so a fractional `0.5` ms becomes `500us`. Values
+/// that are not positive numbers, such as `-1` or `on`, are returned as
+/// they are.
+pub fn normalize_value(value: &str, unit: Option<&str>) -> String {
+    let Some(unit) = unit else {
+        return value.to_string();
+    };
+    let Ok(amount) = value.parse::<f64>() else {
+        return value.to_string();
+    };
+    if amount <= 0.0 {
+        return value.to_string();
+    }
+
+    const MEMORY: &[(&str, u64)] = &[
+        ("TB", 1 << 40),
+        ("GB", 1 << 30),
+        ("MB", 1 << 20),
+        ("kB", 1 << 10),
+        ("B", 1),
+    ];
+    const TIME: &[(&str, u64)] = &[
+        ("d", 86_400_000_000),
+        ("h", 3_600_000_000),
+        ("min", 60_000_000),
+        ("s", 1_000_000),
+        ("ms", 1_000),
+        ("us", 1),
+    ];
+    // Memory in bytes, time in microseconds
+    let (base, scale, units) = match unit {
+        "B" => (1, 1, MEMORY),
+        "kB" => (1 << 10, 1, MEMORY),
+        "8kB" => (8 << 10, 1, MEMORY),
+        "16MB" => (16 << 20, 1, MEMORY),
+        "MB" => (1 << 20, 1, MEMORY),
+        "us" => (1, 1, TIME),
+        "ms" => (1, 1_000, TIME),
+        "s" => (1, 1_000_000, TIME),
+        "min" => (1, 60_000_000, TIME),
+        _ => return format!("{}{}", value, unit),
+    };
+    // Whole in the smallest unit, or kept in the unit it came in
+    let total = amount * (base * scale) as f64;
+    if (total - total.round()).abs() > 1e-6 || total.round() >= u64::MAX as f64 {
+        return format!("{}{}", value, unit);
+    }
+    let total = total.round() as u64;
+    units
+        .iter()
+        .find(|(_, size)| total.is_multiple_of(*size))
+        .map(|(name, size)| format!("{}{}", total / size, name))
+        .unwrap_or_else(|| format!("{}{}", value, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(name: &str, category: &str, setting: &str, boot_val: &str) -> ServerSetting {
+        ServerSetting {
+            name: name.to_string(),
+            category: category.to_string(),
+            setting: setting.to_string(),
+            unit: None,
+            boot_val: Some(boot_val.to_string()),
+            source: if setting == boot_val {
+                "default".to_string()
+            } else {
+                "configuration file".to_string()
+            },
+            context: "user".to_string(),
+            short_desc: String::new(),
+            pending_restart: false,
+        }
+    }
+
+    #[test]
+    fn normalizes_memory_and_time_units() {
+        assert_eq!(normalize_value("16384", Some("8kB")), "128MB");
+        assert_eq!(normalize_value("4096", Some("kB")), "4MB");
+        assert_eq!(normalize_value("4097", Some("kB")), "4097kB");
+        assert_eq!(normalize_value("1048576", Some("kB")), "1GB");
+        assert_eq!(normalize_value("300", Some("s")), "5min");
+        assert_eq!(normalize_value("86400", Some("s")), "1d");
+        assert_eq!(normalize_value("200", Some("ms")), "200ms");
+        assert_eq!(normalize_value("1500", Some("ms")), "1500ms");
+    }
+
+    #[test]
+    fn keeps_the_unit_of_fractional_values() {
+        assert_eq!(normalize_value("0.5", Some("ms")), "500us");
+        assert_eq!(normalize_value("2.5", Some("s")), "2500ms");
+        assert_eq!(normalize_value("0.0001", Some("ms")), "0.0001ms");
+        assert_eq!(normalize_value("1.5", Some("kB")), "1536B");
+    }
+
+    #[test]
+    fn leaves_special_and_unitless_values() {
+        assert_eq!(normalize_value("-1", Some("ms")), "-1");
+        assert_eq!(normalize_value("0", Some("ms")), "0");
+        assert_eq!(normalize_value("0.9", None), "0.9");
+        assert_eq!(normalize_value("on", None), "on");
+        assert_eq!(normalize_value("3", Some("widgets")), "3widgets");
+    }
+
+    #[test]
+    fn filters_and_groups_by_category() {
+        let settings = vec![
+            setting("shared_buffers", "Resource Usage / Memory", "32768", "1024"),
+            setting("temp_buffers", "Resource Usage / Memory", "1024", "1024"),
+            setting("autovacuum", "Autovacuum", "on", "on"),
+            setting("work_mem", "Resource Usage / Memory", "4096", "4096"),
+        ];
+
+        let all = group_settings(&settings, &SettingsFilter::default());
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].0, "Resource Usage / Memory");
+        assert_eq!(all[0].1.len(), 3);
+
+        let changed = SettingsFilter {
+            changed_only: true,
+            ..Default::default()
+        };
+        let groups = group_settings(&settings, &changed);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].1[0].name, "shared_buffers");
+
+        let tuned = SettingsFilter {
+            commonly_tuned: true,
+            text: "MEM".to_string(),
+            ..Default::default()
+        };
+        let names: Vec<&str> = group_settings(&settings, &tuned)
+            .into_iter()
+            .flat_map(|(_, group)| group)
+            .map(|setting| setting.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["shared_buffers", "work_mem"]);
+    }
+
+    #[test]
+    fn compares_with_the_built_in_default() {
+        let mut shared_buffers = setting("shared_buffers", "Memory", "32768", "1024");
+        shared_buffers.unit = Some("8kB".to_string());
+        assert!(shared_buffers.is_changed());
+        assert_eq!(shared_buffers.value(), "256MB");
+        assert_eq!(shared_buffers.default_value().as_deref(), Some("8MB"));
+
+        // Set in a file, but to the default
+        let mut work_mem = setting("work_mem", "Memory", "4096", "4096");
+        work_mem.source = "configuration file".to_string();
+        assert!(!work_mem.is_changed());
+    }
+}
@@ -0,0 +1,455 @@
+//! Editing a table's columns: the column definitions as shown in the
+//! structure editor, and the `ALTER TABLE` statements that turn the
+//! current columns into the edited ones.
+//!
+//! Each change becomes statements of its own, so they can be reviewed one
+//! by one and a failing one stops the rest. On MySQL a change of type or
+//! nullability restates the whole column with `MODIFY COLUMN`, carrying
+//! the default, comment, collation and attributes such as `AUTO_INCREMENT`
+//! along.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::storage::DatabaseDriver;
+
+use super::types::ColumnDetail;
+
+/// A column as edited: its name, full type, nullability and default
+/// expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    /// A SQL expression, e.g. `0`, `'draft'` or `now()`.
+    pub default: Option<String>,
+    pub comment: Option<String>,
+    /// MySQL only, kept when the column is restated.
+    pub collation: Option<String>,
+    /// MySQL only, kept when the column is restated: e.g. `AUTO_INCREMENT`
+    /// or `ON UPDATE CURRENT_TIMESTAMP`.
+    pub attributes: Option<String>,
+}
+
+impl ColumnDefinition {
+    pub fn from_detail(driver: &DatabaseDriver, detail: &ColumnDetail) -> Self {
+        let mysql = *driver == DatabaseDriver::MySql;
+        Self {
+            name: detail.column_name.clone(),
+            data_type: detail
+                .full_type
+                .clone()
+                .unwrap_or_else(|| column_type(detail)),
+            nullable: detail.is_nullable,
+            default: detail
+                .column_default
+                .as_deref()
+                .map(|default| default_expression(driver, default)),
+            comment: detail.description.clone().filter(|c| !c.is_empty()),
+            collation: detail.collation.clone().filter(|_| mysql),
+            attributes: detail
+                .extra
+                .as_deref()
+                .filter(|_| mysql)
+                .and_then(mysql_attributes),
+        }
+    }
+
+    fn sql(&self, driver: &DatabaseDriver) -> String {
+        let mut sql = format!("{} {}", driver.quote_ident(&self.name), self.data_type);
+        let mysql = *driver == DatabaseDriver::MySql;
+        if mysql && let Some(collation) = &self.collation {
+            sql.push_str(&format!(" COLLATE {}", collation));
+        }
+        sql.push_str(if self.nullable { " NULL" } else { " NOT NULL" });
+        if let Some(default) = &self.default {
+            sql.push_str(&format!(" DEFAULT {}", default));
+        }
+        if mysql && let Some(attributes) = &self.attributes {
+            sql.push_str(&format!(" {}", attributes));
+        }
+        if mysql && let Some(comment) = &self.comment {
+            sql.push_str(&format!(" COMMENT {}", driver.quote_literal(comment)));
+        }
+        sql
+    }
+}
+
+/// The part of a MySQL EXTRA that is written in a column definition:
+/// `auto_increment` and `on update ...`, without the `DEFAULT_GENERATED`
+/// marker MySQL adds to expression defaults.
+fn mysql_attributes(extra: &str) -> Option<String> {
+    let mut attributes = vec![];
+    let lower = extra.to_lowercase();
+    if lower.contains("auto_increment") {
+        attributes.push("AUTO_INCREMENT".to_string());
+    }
+    if let Some(at) = lower.find("on update ") {
+        attributes.push(format!(
+            "ON UPDATE {}",
+            extra[at + "on update ".len()..].trim()
+        ));
+    }
+    (!attributes.is_empty()).then(|| attributes.join(" "))
+}
+
+/// One edit of the table's columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnChange {
+    Add(ColumnDefinition),
+    /// Rename, retype, or change the nullability or default of a column.
+    Alter {
+        original: ColumnDefinition,
+        column: ColumnDefinition,
+    },
+    Drop(String),
+}
+
+/// The type of a column with its length, or precision and scale, e.g.
+/// `character varying(255)` or `decimal(10,2)`.
+pub fn column_type(detail: &ColumnDetail) -> String {
+    let data_type = detail.data_type.as_str();
+    match data_type.to_lowercase().as_str() {
+        "character varying" | "character" | "bit varying" | "bit" | "varchar" | "char"
+        | "varbinary" | "binary" => match detail.character_maximum_length {
+            Some(length) => format!("{}({})", data_type, length),
+            None => data_type.to_string(),
+        },
+        "numeric" | "decimal" => match (detail.numeric_precision, detail.numeric_scale) {
+            (Some(precision), Some(scale)) => format!("{}({},{})", data_type, precision, scale),
+            (Some(precision), None) => format!("{}({})", data_type, precision),
+            _ => data_type.to_string(),
+        },
+        _ => data_type.to_string(),
+    }
+}
+
+/// Postgres reports defaults as expressions already; MySQL reports literal
+/// defaults unquoted, so those are quoted back.
+fn default_expression(driver: &DatabaseDriver, default: &str) -> String {
+    match driver {
+        DatabaseDriver::Postgres => default.to_string(),
+        DatabaseDriver::MySql => {
+            let upper = default.to_uppercase();
+            let is_expression = default.parse::<f64>().is_ok()
+                || upper == "NULL"
+                || upper.starts_with("CURRENT_TIMESTAMP")
+                || upper.starts_with("NOW(")
+                || default.starts_with('(');
+            if is_expression {
+                default.to_string()
+            } else {
+                driver.quote_literal(default)
+            }
+        }
+    }
+}
+
+/// The statements applying `changes` to `table_schema.table_name`, in order.
+pub fn alter_table_sql(
+    driver: &DatabaseDriver,
+    table_schema: &str,
+    table_name: &str,
+    changes: &[ColumnChange],
+) -> Result<Vec<String>> {
+    let table = format!(
+        "{}.{}",
+        driver.quote_ident(table_schema),
+        driver.quote_ident(table_name)
+    );
+    let alter = |action: String| format!("ALTER TABLE {} {};", table, action);
+
+    let mut statements = vec![];
+    for change in changes {
+        match change {
+            ColumnChange::Add(column) => {
+                validate(column)?;
+                statements.push(alter(format!("ADD COLUMN {}", column.sql(driver))));
+            }
+            ColumnChange::Drop(name) => {
+                statements.push(alter(format!("DROP COLUMN {}", driver.quote_ident(name))));
+            }
+            ColumnChange::Alter { original, column } => {
+                validate(column)?;
+                if original.name != column.name {
+                    statements.push(alter(format!(
+                        "RENAME COLUMN {} TO {}",
+                        driver.quote_ident(&original.name),
+                        driver.quote_ident(&column.name)
+                    )));
+                }
+                let name = driver.quote_ident(&column.name);
+                let type_changed = original.data_type != column.data_type;
+                let nullable_changed = original.nullable != column.nullable;
+                let default_changed = original.default != column.default;
+                let set_default = match &column.default {
+                    Some(default) => format!("ALTER COLUMN {} SET DEFAULT {}", name, default),
+                    None => format!("ALTER COLUMN {} DROP DEFAULT", name),
+                };
+
+                match driver {
+                    DatabaseDriver::Postgres => {
+                        if type_changed {
+                            statements.push(alter(format!(
+                                "ALTER COLUMN {} TYPE {}",
+                                name, column.data_type
+                            )));
+                        }
+                        if default_changed {
+                            statements.push(alter(set_default));
+                        }
+                        if nullable_changed {
+                            statements.push(alter(format!(
+                                "ALTER COLUMN {} {} NOT NULL",
+                                name,
+                                if column.nullable { "DROP" } else { "SET" }
+                            )));
+                        }
+                    }
+                    DatabaseDriver::MySql => {
+                        if type_changed || nullable_changed {
+                            statements.push(alter(format!("MODIFY COLUMN {}", column.sql(driver))));
+                        } else if default_changed {
+                            statements.push(alter(set_default));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if statements.is_empty() {
+        return Err(anyhow!("Nothing to change"));
+    }
+    Ok(statements)
+}
+
+fn validate(column: &ColumnDefinition) -> Result<()> {
+    if column.name.trim().is_empty() {
+        return Err(anyhow!("Every column needs a name"));
+    }
+    if column.data_type.trim().is_empty() {
+        return Err(anyhow!("Column {} needs a type", column.name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(
+        name: &str,
+        data_type: &str,
+        nullable: bool,
+        default: Option<&str>,
+    ) -> ColumnDefinition {
+        ColumnDefinition {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            default: default.map(str::to_string),
+            comment: None,
+            collation: None,
+            attributes: None,
+        }
+    }
+
+    #[test]
+    fn describes_existing_columns() {
+        let detail = ColumnDetail {
+            column_name: "price".to_string(),
+            data_type: "numeric".to_string(),
+            is_nullable: false,
+            column_default: Some("0".to_string()),
+            ordinal_position: 2,
+            character_maximum_length: None,
+            numeric_precision: Some(10),
+            numeric_scale: Some(2),
+            full_type: None,
+            collation: None,
+            extra: None,
+            description: Some(String::new()),
+        };
+        assert_eq!(
+            ColumnDefinition::from_detail(&DatabaseDriver::Postgres, &detail),
+            column("price", "numeric(10,2)", false, Some("0"))
+        );
+
+        // The modifiers information_schema leaves out come with format_type
+        let created_at = ColumnDetail {
+            column_name: "created_at".to_string(),
+            data_type: "timestamp with time zone".to_string(),
+            column_default: None,
+            full_type: Some("timestamp(3) with time zone".to_string()),
+            ..detail.clone()
+        };
+        assert_eq!(
+            ColumnDefinition::from_detail(&DatabaseDriver::Postgres, &created_at),
+            column("created_at", "timestamp(3) with time zone", false, None)
+        );
+
+        let status = ColumnDetail {
+            column_name: "status".to_string(),
+            data_type: "varchar".to_string(),
+            is_nullable: true,
+            column_default: Some("draft".to_string()),
+            character_maximum_length: Some(20),
+            numeric_precision: None,
+            numeric_scale: None,
+            ..detail
+        };
+        assert_eq!(
+            ColumnDefinition::from_detail(&DatabaseDriver::MySql, &status),
+            column("status", "varchar(20)", true, Some("'draft'"))
+        );
+    }
+
+    #[test]
+    fn alters_postgres_columns() {
+        let statements = alter_table_sql(
+            &DatabaseDriver::Postgres,
+            "public",
+            "orders",
+            &[
+                ColumnChange::Add(column("note", "text", true, None)),
+                ColumnChange::Alter {
+                    original: column("total", "integer", true, None),
+                    column: column("amount", "bigint", false, Some("0")),
+                },
+                ColumnChange::Drop("legacy".to_string()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                "ALTER TABLE \"public\".\"orders\" ADD COLUMN \"note\" text NULL;",
+                "ALTER TABLE \"public\".\"orders\" RENAME COLUMN \"total\" TO \"amount\";",
+                "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"amount\" TYPE bigint;",
+                "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"amount\" SET DEFAULT 0;",
+                "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"amount\" SET NOT NULL;",
+                "ALTER TABLE \"public\".\"orders\" DROP COLUMN \"legacy\";",
+            ]
+        );
+    }
+
+    #[test]
+    fn alters_mysql_columns() {
+        let mut commented = column("total", "int", true, Some("0"));
+        commented.comment = Some("in cents".to_string());
+        let statements = alter_table_sql(
+            &DatabaseDriver::MySql,
+            "shop",
+            "orders",
+            &[
+                ColumnChange::Alter {
+                    original: commented.clone(),
+                    column: ColumnDefinition {
+                        nullable: false,
+                        ..commented.clone()
+                    },
+                },
+                ColumnChange::Alter {
+                    original: column("status", "varchar(20)", true, None),
+                    column: column("status", "varchar(20)", true, Some("'new'")),
+                },
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                "ALTER TABLE `shop`.`orders` MODIFY COLUMN `total` int NOT NULL DEFAULT 0 \
+                 COMMENT 'in cents';",
+                "ALTER TABLE `shop`.`orders` ALTER COLUMN `status` SET DEFAULT 'new';",
+            ]
+        );
+    }
+
+    #[test]
+    fn restates_mysql_column_attributes() {
+        let id = ColumnDetail {
+            column_name: "id".to_string(),
+            data_type: "int".to_string(),
+            is_nullable: true,
+            column_default: None,
+            ordinal_position: 1,
+            character_maximum_length: None,
+            numeric_precision: Some(10),
+            numeric_scale: Some(0),
+            full_type: Some("int unsigned".to_string()),
+            collation: None,
+            extra: Some("auto_increment".to_string()),
+            description: None,
+        };
+        let code = ColumnDetail {
+            column_name: "code".to_string(),
+            data_type: "varchar".to_string(),
+            character_maximum_length: Some(8),
+            full_type: Some("varchar(8)".to_string()),
+            collation: Some("utf8mb4_bin".to_string()),
+            extra: None,
+            ..id.clone()
+        };
+        let updated_at = ColumnDetail {
+            column_name: "updated_at".to_string(),
+            data_type: "timestamp".to_string(),
+            column_default: Some("CURRENT_TIMESTAMP".to_string()),
+            full_type: Some("timestamp".to_string()),
+            extra: Some("DEFAULT_GENERATED on update CURRENT_TIMESTAMP".to_string()),
+            ..id.clone()
+        };
+
+        let changes: Vec<ColumnChange> = [id, code, updated_at]
+            .iter()
+            .map(|detail| {
+                let original = ColumnDefinition::from_detail(&DatabaseDriver::MySql, detail);
+                ColumnChange::Alter {
+                    column: ColumnDefinition {
+                        nullable: false,
+                        ..original.clone()
+                    },
+                    original,
+                }
+            })
+            .collect();
+        assert_eq!(
+            alter_table_sql(&DatabaseDriver::MySql, "shop", "orders", &changes).unwrap(),
+            vec![
+                "ALTER TABLE `shop`.`orders` MODIFY COLUMN `id` int unsigned NOT NULL \
+                 AUTO_INCREMENT;",
+                "ALTER TABLE `shop`.`orders` MODIFY COLUMN `code` varchar(8) COLLATE utf8mb4_bin \
+                 NOT NULL;",
+                "ALTER TABLE `shop`.`orders` MODIFY COLUMN `updated_at` timestamp NOT NULL \
+                 DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP;",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_empty_changes() {
+        let unchanged = column("id", "integer", false, None);
+        assert!(
+            alter_table_sql(
+                &DatabaseDriver::Postgres,
+                "public",
+                "orders",
+                &[ColumnChange::Alter {
+                    original: unchanged.clone(),
+                    column: unchanged,
+                }],
+            )
+            .is_err()
+        );
+        assert!(
+            alter_table_sql(
+                &DatabaseDriver::Postgres,
+                "public",
+                "orders",
+                &[ColumnChange::Add(column(" ", "text", true, None))],
+            )
+            .is_err()
+        );
+    }
+}
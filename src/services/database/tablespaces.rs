@@ -0,0 +1,194 @@
+//! Postgres tablespaces, and a table's storage parameters such as
+//! `fillfactor`.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+/// A table, index or materialized view placed in a tablespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablespaceRelation {
+    pub schema: String,
+    pub name: String,
+    /// `table`, `index` or `materialized view`.
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablespaceInfo {
+    pub name: String,
+    pub owner: String,
+    /// Empty for `pg_default` and `pg_global`, which live in the data
+    /// directory.
+    pub location: String,
+    /// `None` without the privilege to read it.
+    pub size_bytes: Option<i64>,
+    /// The current database's default, holding everything not placed
+    /// elsewhere.
+    pub is_database_default: bool,
+    /// Relations of the current database explicitly placed here.
+    pub relations: Vec<TablespaceRelation>,
+}
+
+/// Where a table is stored and its `WITH (...)` storage parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableStorage {
+    /// `None` when it is in the database's default tablespace.
+    pub tablespace: Option<String>,
+    /// Name and value pairs, e.g. `("fillfactor", "70")`.
+    pub parameters: Vec<(String, String)>,
+}
+
+impl TableStorage {
+    /// From `pg_class.reloptions`, e.g. `{fillfactor=70}`.
+    pub fn from_reloptions(tablespace: Option<String>, reloptions: &[String]) -> Self {
+        Self {
+            tablespace,
+            parameters: reloptions
+                .iter()
+                .filter_map(|option| option.split_once('='))
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// `fillfactor=70, autovacuum_enabled=false`, as edited.
+    pub fn parameters_text(&self) -> String {
+        self.parameters
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Parse `name=value` pairs separated by commas.
+pub fn parse_storage_parameters(text: &str) -> Result<Vec<(String, String)>> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Write {} as name=value", pair))?;
+            let name = name.trim().to_lowercase();
+            let value = value.trim();
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            if !valid_name {
+                return Err(anyhow!("{} is not a storage parameter name", name));
+            }
+            if value.is_empty() {
+                return Err(anyhow!("Give {} a value", name));
+            }
+            Ok((name, value.to_string()))
+        })
+        .collect()
+}
+
+/// Numbers and words such as `true` or `off` are written as is, anything
+/// else as a string literal.
+fn parameter_value(value: &str) -> String {
+    let bare = value.parse::<f64>().is_ok()
+        || value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if bare {
+        value.to_string()
+    } else {
+        DRIVER.quote_literal(value)
+    }
+}
+
+/// `ALTER TABLE ... SET (...)` for new or changed parameters and
+/// `RESET (...)` for removed ones; empty when nothing changed.
+pub fn storage_parameters_sql(
+    table_schema: &str,
+    table_name: &str,
+    original: &[(String, String)],
+    edited: &[(String, String)],
+) -> Vec<String> {
+    let table = format!(
+        "{}.{}",
+        DRIVER.quote_ident(table_schema),
+        DRIVER.quote_ident(table_name)
+    );
+    let set: Vec<String> = edited
+        .iter()
+        .filter(|parameter| !original.contains(parameter))
+        .map(|(name, value)| format!("{} = {}", name, parameter_value(value)))
+        .collect();
+    let reset: Vec<&str> = original
+        .iter()
+        .filter(|(name, _)| !edited.iter().any(|(edited, _)| edited == name))
+        .map(|(name, _)| name.as_str())
+        .collect();
+
+    let mut statements = vec![];
+    if !set.is_empty() {
+        statements.push(format!("ALTER TABLE {} SET ({});", table, set.join(", ")));
+    }
+    if !reset.is_empty() {
+        statements.push(format!(
+            "ALTER TABLE {} RESET ({});",
+            table,
+            reset.join(", ")
+        ));
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn parses_storage_parameters() {
+        let storage = TableStorage::from_reloptions(
+            None,
+            &[
+                "fillfactor=70".to_string(),
+                "autovacuum_enabled=false".to_string(),
+            ],
+        );
+        assert_eq!(
+            storage.parameters_text(),
+            "fillfactor=70, autovacuum_enabled=false"
+        );
+        assert_eq!(
+            parse_storage_parameters(&storage.parameters_text()).unwrap(),
+            storage.parameters
+        );
+        assert_eq!(parse_storage_parameters(" ").unwrap(), vec![]);
+        assert!(parse_storage_parameters("fillfactor").is_err());
+        assert!(parse_storage_parameters("fill factor=70").is_err());
+        assert!(parse_storage_parameters("fillfactor=").is_err());
+    }
+
+    #[test]
+    fn sets_and_resets_changed_parameters() {
+        let original = vec![
+            pair("fillfactor", "100"),
+            pair("autovacuum_enabled", "false"),
+        ];
+        let edited = vec![
+            pair("fillfactor", "70"),
+            pair("toast.autovacuum_vacuum_scale_factor", "0.05"),
+        ];
+        assert_eq!(
+            storage_parameters_sql("public", "orders", &original, &edited),
+            vec![
+                "ALTER TABLE \"public\".\"orders\" SET (fillfactor = 70, \
+                 toast.autovacuum_vacuum_scale_factor = 0.05);",
+                "ALTER TABLE \"public\".\"orders\" RESET (autovacuum_enabled);",
+            ]
+        );
+        assert!(storage_parameters_sql("public", "orders", &original, &original).is_empty());
+    }
+}
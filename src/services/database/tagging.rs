@@ -0,0 +1,126 @@
+//! Source tags for executed statements.
+//!
+//! When a connection has `tag_queries` enabled, statements run from the
+//! editor are prefixed with a comment such as
+//! `/* pgui:alice@db.internal tab:2 */` so DBAs can trace them in
+//! `pg_stat_activity` or the server logs. `tab` numbers the window they
+//! came from, as each window has its own connection.
+//!
+//! Background jobs also carry a `/* pgui:job:<id> */` tag, which is how a
+//! job's statement is found again to cancel it.
+
+use uuid::Uuid;
+
+use crate::services::storage::ConnectionInfo;
+
+/// The comment identifying statements sent by this connection, from
+/// window `tab` when given.
+pub fn query_tag(info: &ConnectionInfo, tab: Option<usize>) -> String {
+    let mut source = format!("pgui:{}@{}", info.username, info.hostname);
+    if let Some(tab) = tab {
+        source.push_str(&format!(" tab:{}", tab));
+    }
+    // A stray `*/` in a user or host name would end the comment early.
+    format!("/* {} */", source.replace("*/", "* /"))
+}
+
+/// The comment identifying the statement of a background job.
+pub fn job_tag(job_id: &Uuid) -> String {
+    format!("/* pgui:job:{} */", job_id)
+}
+
+/// Prefix `sql` with `tag`. Blank statements are returned unchanged so the
+/// backends still report them as empty.
+pub fn tag_sql(tag: Option<&str>, sql: &str) -> String {
+    match tag {
+        Some(tag) if !sql.trim().is_empty() => format!("{} {}", tag, sql.trim_start()),
+        _ => sql.to_string(),
+    }
+}
+
+/// `sql` with leading whitespace and comments removed, for classifying a
+/// statement by its first keyword.
+pub fn skip_leading_comments(sql: &str) -> &str {
+    let mut rest = sql.trim_start();
+    loop {
+        if let Some(after) = rest.strip_prefix("/*") {
+            match after.find("*/") {
+                Some(end) => rest = after[end + 2..].trim_start(),
+                None => return "",
+            }
+        } else if let Some(after) = rest.strip_prefix("--") {
+            match after.find('\n') {
+                Some(end) => rest = after[end + 1..].trim_start(),
+                None => return "",
+            }
+        } else {
+            return rest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(username: &str, hostname: &str) -> ConnectionInfo {
+        let mut info = ConnectionInfo::default();
+        info.username = username.to_string();
+        info.hostname = hostname.to_string();
+        info
+    }
+
+    #[test]
+    fn tag_names_user_host_and_tab() {
+        assert_eq!(
+            query_tag(&info("alice", "db.internal"), Some(3)),
+            "/* pgui:alice@db.internal tab:3 */"
+        );
+        assert_eq!(
+            query_tag(&info("alice", "db.internal"), None),
+            "/* pgui:alice@db.internal */"
+        );
+    }
+
+    #[test]
+    fn tag_cannot_close_comment_early() {
+        assert_eq!(query_tag(&info("a*/b", "h"), None), "/* pgui:a* /b@h */");
+    }
+
+    #[test]
+    fn tag_sql_prefixes_statement() {
+        assert_eq!(
+            tag_sql(Some("/* pgui:a@h */"), "\n  SELECT 1"),
+            "/* pgui:a@h */ SELECT 1"
+        );
+        assert_eq!(tag_sql(None, "SELECT 1"), "SELECT 1");
+        assert_eq!(tag_sql(Some("/* pgui:a@h */"), "  "), "  ");
+    }
+
+    #[test]
+    fn job_tag_leads_a_tagged_statement() {
+        let id = Uuid::nil();
+        let sql = tag_sql(
+            Some(&job_tag(&id)),
+            &tag_sql(Some("/* pgui:a@h */"), "SELECT 1"),
+        );
+        assert_eq!(
+            sql,
+            "/* pgui:job:00000000-0000-0000-0000-000000000000 */ /* pgui:a@h */ SELECT 1"
+        );
+        assert_eq!(skip_leading_comments(&sql), "SELECT 1");
+    }
+
+    #[test]
+    fn skips_block_and_line_comments() {
+        assert_eq!(
+            skip_leading_comments("/* pgui:a@h */ -- note\n  select 1"),
+            "select 1"
+        );
+        assert_eq!(skip_leading_comments("/* unterminated"), "");
+        assert_eq!(
+            skip_leading_comments("update t set a = 1"),
+            "update t set a = 1"
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Enabling and disabling table triggers.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::storage::DatabaseDriver;
+
+/// `ALTER TABLE ... ENABLE/DISABLE TRIGGER` for one trigger. MySQL cannot
+/// disable a trigger short of dropping it, so this is Postgres-only.
+pub fn set_trigger_enabled_sql(
+    driver: &DatabaseDriver,
+    table_schema: &str,
+    table_name: &str,
+    trigger_name: &str,
+    enabled: bool,
+) -> Result<String> {
+    match driver {
+        DatabaseDriver::Postgres => Ok(format!(
+            "ALTER TABLE {}.{} {} TRIGGER {};",
+            driver.quote_ident(table_schema),
+            driver.quote_ident(table_name),
+            if enabled { "ENABLE" } else { "DISABLE" },
+            driver.quote_ident(trigger_name)
+        )),
+        DatabaseDriver::MySql => Err(anyhow!("MySQL triggers cannot be disabled")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_postgres_trigger() {
+        assert_eq!(
+            set_trigger_enabled_sql(
+                &DatabaseDriver::Postgres,
+                "public",
+                "orders",
+                "audit",
+                false
+            )
+            .unwrap(),
+            "ALTER TABLE \"public\".\"orders\" DISABLE TRIGGER \"audit\";"
+        );
+        assert_eq!(
+            set_trigger_enabled_sql(&DatabaseDriver::Postgres, "public", "orders", "audit", true)
+                .unwrap(),
+            "ALTER TABLE \"public\".\"orders\" ENABLE TRIGGER \"audit\";"
+        );
+    }
+
+    #[test]
+    fn mysql_triggers_cannot_be_toggled() {
+        assert!(
+            set_trigger_enabled_sql(&DatabaseDriver::MySql, "app", "orders", "audit", false)
+                .is_err()
+        );
+    }
+}
@@ -1,12 +1,117 @@
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TableInfo {
     pub table_name: String,
     pub table_schema: String,
+    /// `BASE TABLE`, `VIEW`, `MATERIALIZED VIEW`, `FOREIGN` (FDW),
+    /// `LOCAL TEMPORARY`, or `INHERITED TABLE` for Postgres inheritance
+    /// children.
     pub table_type: String,
 }
 
+impl TableInfo {
+    /// A regular table that stores its own rows, as opposed to a view or
+    /// a foreign table.
+    pub fn is_table(&self) -> bool {
+        matches!(
+            self.table_type.as_str(),
+            "BASE TABLE" | "INHERITED TABLE" | "LOCAL TEMPORARY"
+        )
+    }
+
+    /// How to refer to the table from a session with `search_path`: the
+    /// bare name when its schema is on the path, else schema-qualified.
+    pub fn name_on_search_path(&self, search_path: &[String]) -> String {
+        if search_path.is_empty() || search_path.contains(&self.table_schema) {
+            self.table_name.clone()
+        } else {
+            format!("{}.{}", self.table_schema, self.table_name)
+        }
+    }
+}
+
+/// `search_path` with `schema` moved to the front, so unqualified names
+/// resolve there first while the rest of the path stays reachable.
+pub fn with_active_schema(search_path: &[String], schema: &str) -> Vec<String> {
+    std::iter::once(schema.to_string())
+        .chain(search_path.iter().filter(|s| *s != schema).cloned())
+        .collect()
+}
+
+/// The foreign server behind a foreign table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignTableInfo {
+    pub server_name: String,
+    pub wrapper: String,
+    /// `key=value` pairs from `CREATE SERVER ... OPTIONS`.
+    pub server_options: Vec<String>,
+    /// `key=value` pairs from `CREATE FOREIGN TABLE ... OPTIONS`.
+    pub table_options: Vec<String>,
+}
+
+/// Where a table's rows come from, beyond its columns.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TableDetails {
+    pub temporary: bool,
+    pub foreign: Option<ForeignTableInfo>,
+    /// Tables this one inherits from, schema-qualified.
+    pub parents: Vec<String>,
+    /// Tables inheriting from this one, schema-qualified.
+    pub children: Vec<String>,
+}
+
+impl TableDetails {
+    /// Label/value pairs for the details dialog.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![];
+        if self.temporary {
+            rows.push(("Persistence", "temporary (this session only)".to_string()));
+        }
+        if let Some(foreign) = &self.foreign {
+            rows.push(("Foreign server", foreign.server_name.clone()));
+            rows.push(("Wrapper", foreign.wrapper.clone()));
+            if !foreign.server_options.is_empty() {
+                rows.push(("Server options", foreign.server_options.join(", ")));
+            }
+            if !foreign.table_options.is_empty() {
+                rows.push(("Table options", foreign.table_options.join(", ")));
+            }
+        }
+        if !self.parents.is_empty() {
+            rows.push(("Inherits from", self.parents.join(", ")));
+        }
+        if !self.children.is_empty() {
+            rows.push(("Inherited by", self.children.join(", ")));
+        }
+        rows
+    }
+}
+
+/// A function or stored procedure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutineInfo {
+    pub routine_schema: String,
+    pub routine_name: String,
+    /// `FUNCTION` or `PROCEDURE`.
+    pub routine_type: String,
+    /// Argument list, e.g. `customer_id integer, since date`.
+    pub arguments: String,
+    /// Return type; `None` for procedures.
+    pub result_type: Option<String>,
+}
+
+impl RoutineInfo {
+    pub fn signature(&self) -> String {
+        format!(
+            "{}.{}({})",
+            self.routine_schema, self.routine_name, self.arguments
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableSchema {
     pub table_name: String,
@@ -17,6 +122,8 @@ pub struct TableSchema {
     pub foreign_keys: Vec<ForeignKeyInfo>,
     pub indexes: Vec<IndexInfo>,
     pub constraints: Vec<ConstraintInfo>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerInfo>,
     pub description: Option<String>,
 }
 
@@ -30,6 +137,18 @@ pub struct ColumnDetail {
     pub character_maximum_length: Option<i32>,
     pub numeric_precision: Option<i32>,
     pub numeric_scale: Option<i32>,
+    /// The type as the server spells it, modifiers included: `format_type`
+    /// on Postgres, e.g. `timestamp(3) with time zone` or `integer[]`, and
+    /// COLUMN_TYPE on MySQL, e.g. `int unsigned` or `enum('a','b')`.
+    #[serde(default)]
+    pub full_type: Option<String>,
+    /// MySQL only: the collation of a text column.
+    #[serde(default)]
+    pub collation: Option<String>,
+    /// MySQL only: EXTRA, e.g. `auto_increment` or
+    /// `DEFAULT_GENERATED on update CURRENT_TIMESTAMP`.
+    #[serde(default)]
+    pub extra: Option<String>,
     pub description: Option<String>,
 }
 
@@ -42,6 +161,17 @@ pub struct ForeignKeyInfo {
     pub foreign_column_name: String,
 }
 
+/// A foreign key column of another table that points at this table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferencingKey {
+    pub constraint_name: String,
+    pub table_schema: String,
+    pub table_name: String,
+    pub column_name: String,
+    /// Column of this table the key points at.
+    pub referenced_column: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexInfo {
     pub index_name: String,
@@ -59,6 +189,18 @@ pub struct ConstraintInfo {
     pub check_clause: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerInfo {
+    pub trigger_name: String,
+    /// `BEFORE`, `AFTER` or `INSTEAD OF`.
+    pub timing: String,
+    /// Firing events, e.g. `INSERT OR UPDATE`.
+    pub event: String,
+    /// Trigger function. MySQL triggers have an inline body instead.
+    pub function_name: Option<String>,
+    pub enabled: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseSchema {
     pub tables: Vec<TableSchema>,
@@ -79,6 +221,71 @@ pub struct ResultColumnMetadata {
     pub table_name: Option<String>,
     /// Whether the column allows NULL values
     pub is_nullable: Option<bool>,
+    /// The column of `table_name` it was read from, which may differ from
+    /// `name` when aliased
+    #[serde(default)]
+    pub source_column: Option<String>,
+}
+
+impl ResultColumnMetadata {
+    /// Where the column comes from, for the result header's tooltip.
+    /// Columns without a source table are expressions, aggregates or
+    /// literals computed by the query.
+    pub fn lineage(&self) -> String {
+        let Some(table) = &self.table_name else {
+            return format!(
+                "{} · {}\nComputed: no source table (expression, aggregate or literal)",
+                self.name, self.type_name
+            );
+        };
+        let column = self.source_column.as_deref().unwrap_or(&self.name);
+        let nullability = match self.is_nullable {
+            Some(true) => "NULL",
+            Some(false) => "NOT NULL",
+            None => "nullability unknown",
+        };
+        format!("{}.{}\n{} · {}", table, column, self.type_name, nullability)
+    }
+}
+
+/// A cell as read from its row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellValue {
+    Text(String),
+    Null,
+    /// A value of a type none of the decoders read, known only by its type
+    /// name. The grid shows it as `<type>`, which is not the value, so it
+    /// is never exported or written into a statement.
+    Undecoded(String),
+}
+
+impl CellValue {
+    /// The text the grid shows and whether it is NULL.
+    pub fn into_text(self) -> (String, bool) {
+        match self {
+            Self::Text(value) => (value, false),
+            Self::Null => ("NULL".to_string(), true),
+            Self::Undecoded(type_name) => (format!("<{}>", type_name), false),
+        }
+    }
+
+    /// The value to write out, `None` for NULL. An undecoded value has no
+    /// text that could stand for it, so it is an error naming `column`.
+    pub fn into_value(self, column: &ResultColumnMetadata) -> Result<Option<String>> {
+        match self {
+            Self::Text(value) => Ok(Some(value)),
+            Self::Null => Ok(None),
+            Self::Undecoded(type_name) => Err(undecoded_error(column, &type_name)),
+        }
+    }
+}
+
+fn undecoded_error(column: &ResultColumnMetadata, type_name: &str) -> anyhow::Error {
+    anyhow!(
+        "Column \"{}\" holds {} values that pgui cannot read",
+        column.name,
+        type_name
+    )
 }
 
 /// A cell value with its metadata
@@ -88,24 +295,216 @@ pub struct ResultCell {
     pub value: String,
     /// Whether the value is NULL
     pub is_null: bool,
+    /// Whether the value could not be decoded, leaving `value` as only its
+    /// `<type>` placeholder
+    #[serde(default)]
+    pub undecoded: bool,
     /// Column metadata for this cell
     pub column_metadata: ResultColumnMetadata,
 }
 
+impl ResultCell {
+    /// The value to write out, `None` for NULL; an error when it could
+    /// not be decoded.
+    pub fn written_value(&self) -> Result<Option<String>> {
+        if self.undecoded {
+            let type_name = self.column_metadata.type_name.to_lowercase();
+            return Err(undecoded_error(&self.column_metadata, &type_name));
+        }
+        Ok((!self.is_null).then(|| self.value.clone()))
+    }
+}
+
 /// A row with full metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResultRow {
     pub cells: Vec<ResultCell>,
 }
 
+/// Backing storage for the rows of a result, decoded one cell at a time.
+pub trait RowSource: Send + Sync {
+    fn len(&self) -> usize;
+
+    fn cell(&self, row: usize, col: usize) -> CellValue;
+}
+
+/// Rows of a query result. Drivers keep their rows in wire format and
+/// decode cells as they are read, so showing a large result only decodes
+/// the cells in view. Cloning shares the rows.
+#[derive(Clone)]
+pub struct ResultRows(Arc<dyn RowSource>);
+
+impl ResultRows {
+    pub fn new(source: impl RowSource + 'static) -> Self {
+        Self(Arc::new(source))
+    }
+
+    /// Rows already decoded to text, `None` for NULL.
+    pub fn from_text(rows: Vec<Vec<Option<String>>>) -> Self {
+        Self::new(TextRows(
+            rows.into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|value| value.map_or(CellValue::Null, CellValue::Text))
+                        .collect()
+                })
+                .collect(),
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> CellValue {
+        self.0.cell(row, col)
+    }
+
+    /// Text of the cell and whether it is NULL.
+    pub fn cell_text(&self, row: usize, col: usize) -> (String, bool) {
+        self.cell(row, col).into_text()
+    }
+}
+
+impl Default for ResultRows {
+    fn default() -> Self {
+        Self::from_text(vec![])
+    }
+}
+
+impl std::fmt::Debug for ResultRows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResultRows")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl From<Vec<ResultRow>> for ResultRows {
+    fn from(rows: Vec<ResultRow>) -> Self {
+        Self::new(TextRows(
+            rows.into_iter()
+                .map(|row| {
+                    row.cells
+                        .into_iter()
+                        .map(|cell| {
+                            if cell.is_null {
+                                CellValue::Null
+                            } else if cell.undecoded {
+                                CellValue::Undecoded(cell.column_metadata.type_name.to_lowercase())
+                            } else {
+                                CellValue::Text(cell.value)
+                            }
+                        })
+                        .collect()
+                })
+                .collect(),
+        ))
+    }
+}
+
+struct TextRows(Vec<Vec<CellValue>>);
+
+impl RowSource for TextRows {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn cell(&self, row: usize, col: usize) -> CellValue {
+        self.0
+            .get(row)
+            .and_then(|r| r.get(col))
+            .cloned()
+            .unwrap_or(CellValue::Null)
+    }
+}
+
 /// Enhanced query result with full metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "SerializedQueryResult", from = "SerializedQueryResult")]
 pub struct QueryResult {
     pub columns: Vec<ResultColumnMetadata>,
-    pub rows: Vec<ResultRow>,
+    pub rows: ResultRows,
     pub row_count: usize,
     pub execution_time_ms: u128,
     pub original_query: String,
+    /// The max-rows limit, when the query had more rows than it.
+    pub truncated_at: Option<usize>,
+}
+
+impl QueryResult {
+    /// Decode the row at `ix` with its column metadata.
+    pub fn row(&self, ix: usize) -> Option<ResultRow> {
+        if ix >= self.rows.len() {
+            return None;
+        }
+
+        let cells = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(col, column_metadata)| {
+                let cell = self.rows.cell(ix, col);
+                let undecoded = matches!(cell, CellValue::Undecoded(_));
+                let (value, is_null) = cell.into_text();
+                ResultCell {
+                    value,
+                    is_null,
+                    undecoded,
+                    column_metadata: column_metadata.clone(),
+                }
+            })
+            .collect();
+        Some(ResultRow { cells })
+    }
+
+    /// Decode every row in order, one at a time.
+    pub fn iter_rows(&self) -> impl Iterator<Item = ResultRow> + '_ {
+        (0..self.rows.len()).filter_map(|ix| self.row(ix))
+    }
+}
+
+/// Fully decoded form of `QueryResult`, kept in the shape the result had
+/// before rows were decoded lazily.
+#[derive(Serialize, Deserialize)]
+struct SerializedQueryResult {
+    columns: Vec<ResultColumnMetadata>,
+    rows: Vec<ResultRow>,
+    row_count: usize,
+    execution_time_ms: u128,
+    original_query: String,
+    #[serde(default)]
+    truncated_at: Option<usize>,
+}
+
+impl From<QueryResult> for SerializedQueryResult {
+    fn from(result: QueryResult) -> Self {
+        Self {
+            rows: result.iter_rows().collect(),
+            columns: result.columns,
+            row_count: result.row_count,
+            execution_time_ms: result.execution_time_ms,
+            original_query: result.original_query,
+            truncated_at: result.truncated_at,
+        }
+    }
+}
+
+impl From<SerializedQueryResult> for QueryResult {
+    fn from(result: SerializedQueryResult) -> Self {
+        Self {
+            columns: result.columns,
+            rows: result.rows.into(),
+            row_count: result.row_count,
+            execution_time_ms: result.execution_time_ms,
+            original_query: result.original_query,
+            truncated_at: result.truncated_at,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -132,3 +531,154 @@ pub enum QueryExecutionResult {
 pub struct DatabaseInfo {
     pub datname: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result() -> QueryResult {
+        QueryResult {
+            columns: vec![ResultColumnMetadata {
+                name: "email".to_string(),
+                type_name: "TEXT".to_string(),
+                ordinal: 0,
+                table_name: None,
+                is_nullable: Some(true),
+                source_column: None,
+            }],
+            rows: ResultRows::from_text(vec![vec![Some("a@example.com".to_string())], vec![None]]),
+            row_count: 2,
+            execution_time_ms: 3,
+            original_query: "SELECT email FROM users".to_string(),
+            truncated_at: None,
+        }
+    }
+
+    #[test]
+    fn table_details_list_foreign_server_and_inheritance() {
+        let details = TableDetails {
+            temporary: false,
+            foreign: Some(ForeignTableInfo {
+                server_name: "analytics".to_string(),
+                wrapper: "postgres_fdw".to_string(),
+                server_options: vec!["host=db".to_string(), "dbname=analytics".to_string()],
+                table_options: vec![],
+            }),
+            parents: vec!["public.events".to_string()],
+            children: vec![],
+        };
+
+        assert_eq!(
+            details.rows(),
+            vec![
+                ("Foreign server", "analytics".to_string()),
+                ("Wrapper", "postgres_fdw".to_string()),
+                ("Server options", "host=db, dbname=analytics".to_string()),
+                ("Inherits from", "public.events".to_string()),
+            ]
+        );
+        assert!(TableDetails::default().rows().is_empty());
+    }
+
+    #[test]
+    fn lineage_names_source_column_or_flags_computed() {
+        let mut column = ResultColumnMetadata {
+            name: "contact".to_string(),
+            type_name: "TEXT".to_string(),
+            ordinal: 0,
+            table_name: Some("public.users".to_string()),
+            is_nullable: Some(false),
+            source_column: Some("email".to_string()),
+        };
+        assert_eq!(column.lineage(), "public.users.email\nTEXT · NOT NULL");
+
+        column.source_column = None;
+        column.is_nullable = None;
+        assert_eq!(
+            column.lineage(),
+            "public.users.contact\nTEXT · nullability unknown"
+        );
+
+        column.table_name = None;
+        assert_eq!(
+            column.lineage(),
+            "contact · TEXT\nComputed: no source table (expression, aggregate or literal)"
+        );
+    }
+
+    #[test]
+    fn tables_off_the_search_path_are_qualified() {
+        let table = TableInfo {
+            table_name: "orders".to_string(),
+            table_schema: "sales".to_string(),
+            table_type: "BASE TABLE".to_string(),
+        };
+        let path = |schemas: &[&str]| schemas.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            table.name_on_search_path(&path(&["sales", "public"])),
+            "orders"
+        );
+        assert_eq!(
+            table.name_on_search_path(&path(&["public"])),
+            "sales.orders"
+        );
+        assert_eq!(table.name_on_search_path(&[]), "orders");
+    }
+
+    #[test]
+    fn active_schema_moves_to_the_front_of_the_path() {
+        let path = vec!["public".to_string(), "sales".to_string()];
+        assert_eq!(with_active_schema(&path, "sales"), vec!["sales", "public"]);
+        assert_eq!(
+            with_active_schema(&path, "audit"),
+            vec!["audit", "public", "sales"]
+        );
+        assert_eq!(with_active_schema(&[], "public"), vec!["public"]);
+    }
+
+    #[test]
+    fn rows_decode_with_their_column_metadata() {
+        let result = result();
+        let rows = result.iter_rows().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].cells[0].value, "a@example.com");
+        assert_eq!(rows[0].cells[0].column_metadata.name, "email");
+        assert!(rows[1].cells[0].is_null);
+        assert!(result.row(2).is_none());
+    }
+
+    #[test]
+    fn serializes_decoded_rows() {
+        let json = serde_json::to_value(result()).unwrap();
+        assert_eq!(json["rows"][0]["cells"][0]["value"], "a@example.com");
+        assert_eq!(json["rows"][1]["cells"][0]["is_null"], true);
+
+        let restored: QueryResult = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.rows.len(), 2);
+        assert_eq!(restored.rows.cell_text(1, 0), ("NULL".to_string(), true));
+    }
+
+    #[test]
+    fn undecoded_values_are_shown_but_never_written_out() {
+        let column = result().columns[0].clone();
+        let cell = CellValue::Undecoded("jsonpath".to_string());
+
+        assert_eq!(cell.clone().into_text(), ("<jsonpath>".to_string(), false));
+        assert!(cell.into_value(&column).is_err());
+        assert_eq!(CellValue::Null.into_value(&column).unwrap(), None);
+
+        let row = ResultRow {
+            cells: vec![ResultCell {
+                value: "<jsonpath>".to_string(),
+                is_null: false,
+                undecoded: true,
+                column_metadata: column,
+            }],
+        };
+        assert!(row.cells[0].written_value().is_err());
+        let rows = ResultRows::from(vec![row]);
+        assert!(matches!(rows.cell(0, 0), CellValue::Undecoded(_)));
+    }
+}
@@ -0,0 +1,134 @@
+//! User-defined Postgres types: enums, composite types and domains, and
+//! the DDL that adds a label to an enum.
+
+use crate::services::storage::DatabaseDriver;
+
+const DRIVER: DatabaseDriver = DatabaseDriver::Postgres;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserTypeKind {
+    Enum,
+    Composite,
+    Domain,
+}
+
+impl UserTypeKind {
+    /// From `pg_type.typtype`.
+    pub fn from_typtype(typtype: &str) -> Option<Self> {
+        match typtype {
+            "e" => Some(UserTypeKind::Enum),
+            "c" => Some(UserTypeKind::Composite),
+            "d" => Some(UserTypeKind::Domain),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UserTypeKind::Enum => "ENUM",
+            UserTypeKind::Composite => "COMPOSITE",
+            UserTypeKind::Domain => "DOMAIN",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserTypeInfo {
+    pub type_schema: String,
+    pub type_name: String,
+    pub kind: UserTypeKind,
+    /// Enum labels in sort order; empty for other kinds.
+    pub labels: Vec<String>,
+    /// Attributes of a composite type (`id integer, name text`), or the
+    /// base type and constraints of a domain.
+    pub definition: Option<String>,
+    /// Names of the table and view columns of this type.
+    pub column_names: Vec<String>,
+}
+
+impl UserTypeInfo {
+    pub fn qualified_name(&self) -> String {
+        format!(
+            "{}.{}",
+            DRIVER.quote_ident(&self.type_schema),
+            DRIVER.quote_ident(&self.type_name)
+        )
+    }
+}
+
+/// Where `ALTER TYPE ... ADD VALUE` puts the new label.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnumValuePosition {
+    Last,
+    Before(String),
+    After(String),
+}
+
+/// `ALTER TYPE ... ADD VALUE` adding `label` to an enum. Before Postgres
+/// 12 this cannot run inside a transaction block.
+pub fn add_enum_value_sql(
+    user_type: &UserTypeInfo,
+    label: &str,
+    position: &EnumValuePosition,
+) -> String {
+    let placement = match position {
+        EnumValuePosition::Last => String::new(),
+        EnumValuePosition::Before(existing) => {
+            format!(" BEFORE {}", DRIVER.quote_literal(existing))
+        }
+        EnumValuePosition::After(existing) => format!(" AFTER {}", DRIVER.quote_literal(existing)),
+    };
+    format!(
+        "ALTER TYPE {} ADD VALUE IF NOT EXISTS {}{};",
+        user_type.qualified_name(),
+        DRIVER.quote_literal(label),
+        placement
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mood() -> UserTypeInfo {
+        UserTypeInfo {
+            type_schema: "public".to_string(),
+            type_name: "mood".to_string(),
+            kind: UserTypeKind::Enum,
+            labels: vec!["sad".to_string(), "happy".to_string()],
+            definition: None,
+            column_names: vec!["current_mood".to_string()],
+        }
+    }
+
+    #[test]
+    fn kinds_from_typtype() {
+        assert_eq!(UserTypeKind::from_typtype("e"), Some(UserTypeKind::Enum));
+        assert_eq!(UserTypeKind::from_typtype("d"), Some(UserTypeKind::Domain));
+        assert_eq!(UserTypeKind::from_typtype("b"), None);
+    }
+
+    #[test]
+    fn add_value_ddl() {
+        assert_eq!(
+            add_enum_value_sql(&mood(), "ok", &EnumValuePosition::Last),
+            "ALTER TYPE \"public\".\"mood\" ADD VALUE IF NOT EXISTS 'ok';"
+        );
+        assert_eq!(
+            add_enum_value_sql(
+                &mood(),
+                "it's fine",
+                &EnumValuePosition::After("sad".to_string())
+            ),
+            "ALTER TYPE \"public\".\"mood\" ADD VALUE IF NOT EXISTS 'it''s fine' AFTER 'sad';"
+        );
+        assert_eq!(
+            add_enum_value_sql(
+                &mood(),
+                "meh",
+                &EnumValuePosition::Before("happy".to_string())
+            ),
+            "ALTER TYPE \"public\".\"mood\" ADD VALUE IF NOT EXISTS 'meh' BEFORE 'happy';"
+        );
+    }
+}
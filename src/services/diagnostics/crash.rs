@@ -0,0 +1,80 @@
+//! Panic hook writing a crash report next to the logs.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+
+use super::logs_dir;
+
+const CRASH_PREFIX: &str = "crash-";
+
+/// Write a `crash-<timestamp>.log` report for every panic, then run the
+/// default hook so the panic still reaches stderr.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_crash_report(info) {
+            Ok(path) => tracing::error!("pgui panicked; crash report at {}", path.display()),
+            Err(e) => tracing::error!("pgui panicked; failed to write crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+fn write_crash_report(info: &PanicHookInfo<'_>) -> anyhow::Result<PathBuf> {
+    let dir = logs_dir()?;
+    fs::create_dir_all(&dir)?;
+    let now = chrono::Utc::now();
+    let path = dir.join(format!(
+        "{}{}.log",
+        CRASH_PREFIX,
+        now.format("%Y%m%d-%H%M%S%.3f")
+    ));
+
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string());
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let thread = std::thread::current();
+
+    let report = format!(
+        "pgui v{} crashed at {}\nOS: {} {}\nThread: {}\nPanic: {}\nLocation: {}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        now.to_rfc3339(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread.name().unwrap_or("<unnamed>"),
+        message,
+        location,
+        Backtrace::force_capture()
+    );
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Crash reports in `dir`, newest first.
+pub fn crash_reports(dir: &Path) -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with(CRASH_PREFIX))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // The timestamp in the name sorts chronologically.
+    reports.sort();
+    reports.reverse();
+    reports
+}
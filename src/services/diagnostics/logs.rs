@@ -0,0 +1,218 @@
+//! The rotating log file written by the tracing subscriber, and parsing
+//! it back for the log viewer.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead as _, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+/// Size at which `pgui.log` is rotated to `pgui.1.log`.
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept besides the current one.
+const MAX_ROTATED_LOGS: usize = 4;
+
+const LOG_FILE: &str = "pgui.log";
+
+fn log_path(dir: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        dir.join(LOG_FILE)
+    } else {
+        dir.join(format!("pgui.{}.log", index))
+    }
+}
+
+struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    /// `pgui.log` becomes `pgui.1.log`, `pgui.1.log` becomes `pgui.2.log`
+    /// and so on; the oldest is dropped.
+    fn rotate(&mut self) -> io::Result<()> {
+        for index in (1..=MAX_ROTATED_LOGS).rev() {
+            let from = log_path(&self.dir, index - 1);
+            if from.exists() {
+                fs::rename(&from, log_path(&self.dir, index))?;
+            }
+        }
+        self.file = File::create(log_path(&self.dir, 0))?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// `io::Write` target for the file log layer. Clones share the same file.
+#[derive(Clone)]
+pub struct RotatingLog {
+    inner: Arc<Mutex<LogFile>>,
+}
+
+impl RotatingLog {
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = log_path(dir, 0);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            inner: Arc::new(Mutex::new(LogFile {
+                dir: dir.to_path_buf(),
+                file,
+                size,
+            })),
+        })
+    }
+}
+
+impl Write for RotatingLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if log.size > 0 && log.size + buf.len() as u64 > MAX_LOG_SIZE {
+            log.rotate()?;
+        }
+        let written = log.file.write(buf)?;
+        log.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        log.file.flush()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.label() == s)
+    }
+}
+
+/// One event from the log file. Continuation lines (multi-line messages)
+/// are folded into the event above them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+}
+
+/// Parse a line written by the fmt layer:
+/// `2024-05-01T13:45:00.123456Z  WARN pgui::state: message`.
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let timestamp = parts.next()?;
+    let rest = parts.next()?.trim_start();
+    let mut parts = rest.splitn(2, char::is_whitespace);
+    let level = LogLevel::parse(parts.next()?)?;
+    let rest = parts.next().unwrap_or_default().trim_start();
+    let (target, message) = rest.split_once(": ").unwrap_or(("", rest));
+    Some(LogEntry {
+        timestamp: timestamp.to_string(),
+        level,
+        target: target.to_string(),
+        message: message.to_string(),
+    })
+}
+
+fn parse_entries(reader: impl BufRead, entries: &mut Vec<LogEntry>) {
+    for line in reader.lines().map_while(io::Result::ok) {
+        match parse_line(&line) {
+            Some(entry) => entries.push(entry),
+            None => {
+                if let Some(last) = entries.last_mut() {
+                    last.message.push('\n');
+                    last.message.push_str(&line);
+                }
+            }
+        }
+    }
+}
+
+/// The last `limit` entries across the current and rotated log files,
+/// oldest first.
+pub fn read_log_entries(dir: &Path, limit: usize) -> Result<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    for index in (0..=MAX_ROTATED_LOGS).rev() {
+        if let Ok(file) = File::open(log_path(dir, index)) {
+            parse_entries(BufReader::new(file), &mut entries);
+        }
+    }
+    let skip = entries.len().saturating_sub(limit);
+    Ok(entries.split_off(skip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fmt_lines() {
+        let mut entries = Vec::new();
+        let log = "2024-05-01T13:45:00.1Z  WARN pgui::state::actions: Failed to connect: refused\n\
+                   \tcaused by: timeout\n\
+                   2024-05-01T13:45:01.2Z ERROR pgui: boom\n\
+                   not a log line\n";
+        parse_entries(log.as_bytes(), &mut entries);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].level, LogLevel::Warn);
+        assert_eq!(entries[0].target, "pgui::state::actions");
+        assert_eq!(
+            entries[0].message,
+            "Failed to connect: refused\n\tcaused by: timeout"
+        );
+        assert_eq!(entries[1].level, LogLevel::Error);
+        assert_eq!(entries[1].message, "boom\nnot a log line");
+    }
+
+    #[test]
+    fn rotates_and_reads_across_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RotatingLog::open(dir.path()).unwrap();
+        let line = format!("2024-05-01T13:45:00Z  INFO pgui: {}\n", "x".repeat(1000));
+        let lines = MAX_LOG_SIZE as usize / line.len() + 10;
+        for _ in 0..lines {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+        log.flush().unwrap();
+
+        assert!(log_path(dir.path(), 1).exists());
+        assert!(fs::metadata(log_path(dir.path(), 0)).unwrap().len() < MAX_LOG_SIZE);
+        assert_eq!(
+            read_log_entries(dir.path(), usize::MAX).unwrap().len(),
+            lines
+        );
+        assert_eq!(read_log_entries(dir.path(), 3).unwrap().len(), 3);
+    }
+}
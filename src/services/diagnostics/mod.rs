@@ -0,0 +1,19 @@
+//! Log files, crash reports and the diagnostics bundle attached to bug
+//! reports. Everything lives under `~/.pgui/logs`.
+
+mod bundle;
+mod crash;
+mod logs;
+
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow};
+
+pub use bundle::{diagnostics_bundle, redact_secrets};
+pub use crash::{crash_reports, install_panic_hook};
+pub use logs::{LogEntry, LogLevel, RotatingLog, read_log_entries};
+
+pub fn logs_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".pgui").join("logs"))
+}
@@ -0,0 +1,241 @@
+//! Connection profile files for sharing a vetted set of connections.
+//!
+//! Profiles are written without passwords unless a passphrase is given,
+//! in which case the passwords are encrypted together with AES-256-GCM
+//! under a key derived from the passphrase with Argon2id. SSH key
+//! passphrases are never exported.
+
+use std::collections::HashMap;
+
+use crate::services::storage::ConnectionInfo;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{Context, Result, anyhow, bail};
+use argon2::Argon2;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const PROFILES_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConnectionProfiles {
+    version: u32,
+    /// Connections with their passwords removed.
+    connections: Vec<ConnectionInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    passwords: Option<EncryptedPasswords>,
+}
+
+/// Passwords by connection id, as encrypted JSON. All values are hex.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedPasswords {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Connections read from a profiles file.
+#[derive(Debug)]
+pub struct ImportedConnections {
+    /// Fresh ids; passwords filled in when they were decrypted.
+    pub connections: Vec<ConnectionInfo>,
+    /// The file holds encrypted passwords but no passphrase was given.
+    pub passwords_skipped: bool,
+}
+
+/// Serialize `connections` for sharing. Passwords are included, encrypted,
+/// only when `passphrase` is given; the connections must have their
+/// passwords loaded for that.
+pub fn write_connection_profiles(
+    connections: &[ConnectionInfo],
+    passphrase: Option<&str>,
+) -> Result<String> {
+    let passwords = match passphrase {
+        Some(passphrase) => {
+            let passwords: HashMap<Uuid, &str> = connections
+                .iter()
+                .filter(|c| !c.password.is_empty())
+                .map(|c| (c.id, c.password.as_str()))
+                .collect();
+            Some(encrypt(&serde_json::to_vec(&passwords)?, passphrase)?)
+        }
+        None => None,
+    };
+
+    let connections = connections
+        .iter()
+        .map(|c| {
+            let mut shared = c.clone();
+            shared.password.clear();
+            shared
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&ConnectionProfiles {
+        version: PROFILES_VERSION,
+        connections,
+        passwords,
+    })?)
+}
+
+/// Parse a profiles file. A wrong passphrase is an error; no passphrase
+/// imports the connections without their passwords.
+pub fn read_connection_profiles(
+    contents: &str,
+    passphrase: Option<&str>,
+) -> Result<ImportedConnections> {
+    let profiles: ConnectionProfiles =
+        serde_json::from_str(contents).context("Not a pgui connections file")?;
+    if profiles.version > PROFILES_VERSION {
+        bail!(
+            "Connections file version {} is newer than this pgui supports",
+            profiles.version
+        );
+    }
+
+    let mut passwords_skipped = false;
+    let passwords: HashMap<Uuid, String> = match (&profiles.passwords, passphrase) {
+        (Some(encrypted), Some(passphrase)) => {
+            serde_json::from_slice(&decrypt(encrypted, passphrase)?)?
+        }
+        (Some(_), None) => {
+            passwords_skipped = true;
+            HashMap::new()
+        }
+        (None, _) => HashMap::new(),
+    };
+
+    let connections = profiles
+        .connections
+        .iter()
+        .map(|c| {
+            let mut imported = c.clone();
+            imported.password = passwords.get(&c.id).cloned().unwrap_or_default();
+            // Ids are per machine; a shared profile is a new connection.
+            imported.id = Uuid::new_v4();
+            imported
+        })
+        .collect();
+
+    Ok(ImportedConnections {
+        connections,
+        passwords_skipped,
+    })
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive key: {}", e))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| anyhow!("Invalid key: {}", e))
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<EncryptedPasswords> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = derive_key(passphrase, &salt)?
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| anyhow!("Failed to encrypt passwords"))?;
+
+    Ok(EncryptedPasswords {
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn decrypt(encrypted: &EncryptedPasswords, passphrase: &str) -> Result<Vec<u8>> {
+    let salt = hex::decode(&encrypted.salt).context("Corrupt connections file")?;
+    let nonce = hex::decode(&encrypted.nonce).context("Corrupt connections file")?;
+    let ciphertext = hex::decode(&encrypted.ciphertext).context("Corrupt connections file")?;
+    if nonce.len() != NONCE_LEN {
+        bail!("Corrupt connections file");
+    }
+
+    derive_key(passphrase, &salt)?
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| anyhow!("Wrong passphrase"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ssh::{SshAuth, SshConfig};
+
+    fn connections() -> Vec<ConnectionInfo> {
+        let mut direct = ConnectionInfo::default();
+        direct.name = "orders".to_string();
+        direct.password = "s3cret".to_string();
+
+        let mut tunneled = ConnectionInfo::default();
+        tunneled.name = "analytics".to_string();
+        tunneled.password = String::new();
+        tunneled.ssh = Some(SshConfig {
+            host: "bastion.example.com".to_string(),
+            port: 22,
+            username: "deploy".to_string(),
+            auth: SshAuth::Agent,
+        });
+
+        vec![direct, tunneled]
+    }
+
+    #[test]
+    fn without_passphrase_no_password_is_written() {
+        let contents = write_connection_profiles(&connections(), None).unwrap();
+        assert!(!contents.contains("s3cret"));
+        assert!(!contents.contains("passwords"));
+
+        let imported = read_connection_profiles(&contents, Some("unused")).unwrap();
+        assert!(!imported.passwords_skipped);
+        assert_eq!(imported.connections.len(), 2);
+        assert!(imported.connections.iter().all(|c| c.password.is_empty()));
+        assert_eq!(
+            imported.connections[1]
+                .ssh
+                .as_ref()
+                .map(|s| s.host.as_str()),
+            Some("bastion.example.com")
+        );
+    }
+
+    #[test]
+    fn passwords_round_trip_with_passphrase() {
+        let original = connections();
+        let contents = write_connection_profiles(&original, Some("team passphrase")).unwrap();
+        assert!(!contents.contains("s3cret"));
+
+        let imported = read_connection_profiles(&contents, Some("team passphrase")).unwrap();
+        assert_eq!(imported.connections[0].name, "orders");
+        assert_eq!(imported.connections[0].password, "s3cret");
+        assert_eq!(imported.connections[1].password, "");
+        // Imported connections get their own ids.
+        assert_ne!(imported.connections[0].id, original[0].id);
+    }
+
+    #[test]
+    fn wrong_or_missing_passphrase() {
+        let contents = write_connection_profiles(&connections(), Some("right")).unwrap();
+
+        let err = read_connection_profiles(&contents, Some("wrong")).unwrap_err();
+        assert_eq!(err.to_string(), "Wrong passphrase");
+
+        let imported = read_connection_profiles(&contents, None).unwrap();
+        assert!(imported.passwords_skipped);
+        assert!(imported.connections.iter().all(|c| c.password.is_empty()));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(read_connection_profiles("[]", None).is_err());
+        assert!(read_connection_profiles("{\"version\": 99, \"connections\": []}", None).is_err());
+    }
+}
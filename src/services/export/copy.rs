@@ -0,0 +1,153 @@
+//! The fast path for CSV exports on Postgres: the server formats the rows
+//! itself with `COPY (...) TO STDOUT`, and they are written to the file as
+//! they arrive, never decoded into a result.
+
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::services::is_row_query;
+
+/// Rows written between progress reports.
+const PROGRESS_EVERY_ROWS: u64 = 10_000;
+
+/// How far a `COPY` export has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopyProgress {
+    pub rows: u64,
+    pub bytes: u64,
+}
+
+/// `sql` wrapped as a `COPY` writing CSV with a header row. `None` for
+/// anything but a single query returning rows, which `COPY` cannot wrap.
+pub fn copy_to_csv_statement(sql: &str) -> Option<String> {
+    let query = sql.trim().trim_end_matches(';').trim_end();
+    // Any semicolon left may separate statements
+    if !is_row_query(query) || query.contains(';') {
+        return None;
+    }
+    Some(format!(
+        "COPY ({}\n) TO STDOUT WITH (FORMAT csv, HEADER)",
+        query
+    ))
+}
+
+/// Write the output of a `COPY ... TO STDOUT` to `output_path`. Postgres
+/// sends each row as a chunk of its own, the header first, so chunks are
+/// counted as rows. `progress` is called every few thousand rows.
+///
+/// Stops when `cancelled` is set, removing the partial file, and returns
+/// `None`. The caller cancels the statement on the server; the stream
+/// failing after that is not reported as an error.
+pub async fn copy_to_csv<S, B>(
+    mut chunks: S,
+    output_path: &Path,
+    cancelled: &AtomicBool,
+    progress: impl Fn(CopyProgress),
+) -> Result<Option<CopyProgress>>
+where
+    S: Stream<Item = Result<B, sqlx::Error>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    let file = std::fs::File::create(output_path)?;
+    let mut writer = std::io::BufWriter::with_capacity(256 * 1024, file);
+    let mut done = CopyProgress::default();
+    let mut header = true;
+
+    let outcome: Result<()> = async {
+        while let Some(chunk) = chunks.next().await {
+            if cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+            let chunk = chunk?;
+            writer.write_all(chunk.as_ref())?;
+            done.bytes += chunk.as_ref().len() as u64;
+            if std::mem::take(&mut header) {
+                continue;
+            }
+            done.rows += 1;
+            if done.rows.is_multiple_of(PROGRESS_EVERY_ROWS) {
+                progress(done);
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    if cancelled.load(Ordering::Relaxed) {
+        drop(writer);
+        let _ = std::fs::remove_file(output_path);
+        return Ok(None);
+    }
+    outcome?;
+    writer.flush()?;
+    progress(done);
+    Ok(Some(done))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(rows: &[&'static str]) -> impl Stream<Item = Result<&'static [u8], sqlx::Error>> {
+        futures::stream::iter(
+            rows.iter()
+                .map(|row| Ok(row.as_bytes()))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn wraps_single_queries() {
+        assert_eq!(
+            copy_to_csv_statement("SELECT * FROM users;\n").as_deref(),
+            Some("COPY (SELECT * FROM users\n) TO STDOUT WITH (FORMAT csv, HEADER)")
+        );
+        // A trailing line comment stays behind the closing parenthesis
+        assert!(
+            copy_to_csv_statement("-- all\nWITH t AS (SELECT 1) SELECT * FROM t -- done")
+                .is_some_and(
+                    |copy| copy.ends_with("-- done\n) TO STDOUT WITH (FORMAT csv, HEADER)")
+                )
+        );
+        assert!(copy_to_csv_statement("UPDATE users SET a = 1").is_none());
+        assert!(copy_to_csv_statement("SELECT 1; SELECT 2").is_none());
+    }
+
+    #[test]
+    fn writes_rows_and_counts_them() {
+        let path = std::env::temp_dir().join(format!("pgui-copy-{}.csv", uuid::Uuid::new_v4()));
+        let reports = std::cell::RefCell::new(vec![]);
+        let done = futures::executor::block_on(copy_to_csv(
+            chunks(&["id,name\n", "1,alice\n", "2,\"b\nob\"\n"]),
+            &path,
+            &AtomicBool::new(false),
+            |progress| reports.borrow_mut().push(progress),
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(done.rows, 2);
+        assert_eq!(done.bytes, 25);
+        assert_eq!(reports.into_inner(), vec![done]);
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, "id,name\n1,alice\n2,\"b\nob\"\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cancelling_removes_the_partial_file() {
+        let path = std::env::temp_dir().join(format!("pgui-copy-{}.csv", uuid::Uuid::new_v4()));
+        let done = futures::executor::block_on(copy_to_csv(
+            chunks(&["id\n", "1\n"]),
+            &path,
+            &AtomicBool::new(true),
+            |_| {},
+        ))
+        .unwrap();
+        assert_eq!(done, None);
+        assert!(!path.exists());
+    }
+}
@@ -74,8 +74,12 @@ pub fn export_to_csv(result: &QueryResult) -> Result<String> {
     wtr.write_record(&headers)?;
 
     // Data rows
-    for row in &result.rows {
-        let values: Vec<&str> = row.cells.iter().map(|c| c.value.as_str()).collect();
+    for row in result.iter_rows() {
+        let values = row
+            .cells
+            .iter()
+            .map(|cell| Ok(cell.written_value()?.unwrap_or_else(|| cell.value.clone())))
+            .collect::<Result<Vec<_>>>()?;
         wtr.write_record(&values)?;
     }
 
@@ -0,0 +1,181 @@
+//! Query history files for moving history between machines.
+//!
+//! Both formats carry every field of [`QueryHistoryEntry`], ids included,
+//! so importing the same file twice does not duplicate entries.
+
+use crate::services::storage::QueryHistoryEntry;
+use anyhow::{Context, Result};
+use std::path::Path;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistoryFormat {
+    Json,
+    Csv,
+}
+
+impl HistoryFormat {
+    /// The format of a history file, judged by its extension.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => HistoryFormat::Csv,
+            _ => HistoryFormat::Json,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            HistoryFormat::Json => "json",
+            HistoryFormat::Csv => "csv",
+        }
+    }
+}
+
+pub fn write_history(entries: &[QueryHistoryEntry], format: HistoryFormat) -> Result<String> {
+    match format {
+        HistoryFormat::Json => Ok(serde_json::to_string_pretty(entries)?),
+        HistoryFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(vec![]);
+            for entry in entries {
+                wtr.serialize(entry)?;
+            }
+            Ok(String::from_utf8(wtr.into_inner()?)?)
+        }
+    }
+}
+
+pub fn read_history(contents: &str, format: HistoryFormat) -> Result<Vec<QueryHistoryEntry>> {
+    match format {
+        HistoryFormat::Json => {
+            serde_json::from_str(contents).context("Not a query history JSON file")
+        }
+        HistoryFormat::Csv => csv::Reader::from_reader(contents.as_bytes())
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .context("Not a query history CSV file"),
+    }
+}
+
+/// Point entries whose connection is not among `known` at `fallback`.
+/// Connection ids differ between machines, so history from another
+/// machine is attached to the active connection instead.
+pub fn reassign_unknown_connections(
+    entries: &mut [QueryHistoryEntry],
+    known: &[Uuid],
+    fallback: Uuid,
+) {
+    for entry in entries {
+        if !known.contains(&entry.connection_id) {
+            entry.connection_id = fallback;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn entries() -> Vec<QueryHistoryEntry> {
+        let connection_id = Uuid::new_v4();
+        vec![
+            QueryHistoryEntry {
+                id: Uuid::new_v4(),
+                connection_id,
+                sql: "SELECT *\nFROM \"users\", 'a,b'".to_string(),
+                execution_time_ms: 12,
+                rows_affected: Some(3),
+                success: true,
+                error_message: None,
+                executed_at: Utc.with_ymd_and_hms(2025, 3, 1, 9, 30, 0).unwrap(),
+                run_id: Some(Uuid::new_v4()),
+                statement_index: Some(1),
+                result_columns: Some(r#"[["id","INT4"]]"#.to_string()),
+            },
+            QueryHistoryEntry {
+                id: Uuid::new_v4(),
+                connection_id,
+                sql: "SELEC 1".to_string(),
+                execution_time_ms: 1,
+                rows_affected: None,
+                success: false,
+                error_message: Some("syntax error at or near \"SELEC\"".to_string()),
+                executed_at: Utc.with_ymd_and_hms(2025, 3, 1, 9, 31, 0).unwrap(),
+                run_id: None,
+                statement_index: None,
+                result_columns: None,
+            },
+        ]
+    }
+
+    fn assert_same(read: &[QueryHistoryEntry], written: &[QueryHistoryEntry]) {
+        assert_eq!(read.len(), written.len());
+        for (r, w) in read.iter().zip(written) {
+            assert_eq!(r.id, w.id);
+            assert_eq!(r.connection_id, w.connection_id);
+            assert_eq!(r.sql, w.sql);
+            assert_eq!(r.execution_time_ms, w.execution_time_ms);
+            assert_eq!(r.rows_affected, w.rows_affected);
+            assert_eq!(r.success, w.success);
+            assert_eq!(r.error_message, w.error_message);
+            assert_eq!(r.executed_at, w.executed_at);
+            assert_eq!(r.run_id, w.run_id);
+            assert_eq!(r.statement_index, w.statement_index);
+            assert_eq!(r.result_columns, w.result_columns);
+        }
+    }
+
+    #[test]
+    fn json_and_csv_round_trip() {
+        let written = entries();
+        for format in [HistoryFormat::Json, HistoryFormat::Csv] {
+            let contents = write_history(&written, format).unwrap();
+            assert_same(&read_history(&contents, format).unwrap(), &written);
+        }
+    }
+
+    #[test]
+    fn reads_files_written_before_statements_were_linked() {
+        let csv = "id,connection_id,sql,execution_time_ms,rows_affected,success,error_message,executed_at\n\
+                   7f1c5ba2-3c1e-4d6a-9a4e-2b7c1d0e9f10,0c6f7a52-8d3b-4a39-9a4a-6c1b2f3e4d5a,SELECT 1,2,,true,,2025-03-01T09:30:00Z\n";
+        let read = read_history(csv, HistoryFormat::Csv).unwrap();
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].run_id, None);
+        assert_eq!(read[0].statement_index, None);
+        assert!(read[0].columns().is_empty());
+    }
+
+    #[test]
+    fn format_follows_file_extension() {
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("history.CSV")),
+            HistoryFormat::Csv
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("history.json")),
+            HistoryFormat::Json
+        );
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("history")),
+            HistoryFormat::Json
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(read_history("{\"rows\": []}", HistoryFormat::Json).is_err());
+        assert!(read_history("a,b\n1,2\n", HistoryFormat::Csv).is_err());
+    }
+
+    #[test]
+    fn unknown_connections_are_reassigned() {
+        let mut imported = entries();
+        let known = Uuid::new_v4();
+        imported[1].connection_id = known;
+        let fallback = Uuid::new_v4();
+
+        reassign_unknown_connections(&mut imported, &[known], fallback);
+        assert_eq!(imported[0].connection_id, fallback);
+        assert_eq!(imported[1].connection_id, known);
+    }
+}
@@ -69,27 +69,25 @@ fn extract_json_value(row: &PgRow, index: usize, _col: &sqlx::postgres::PgColumn
 }
 
 pub fn export_to_json(result: &QueryResult) -> Result<String> {
-    let rows: Vec<Value> = result
-        .rows
-        .iter()
+    let rows = result
+        .iter_rows()
         .map(|row| {
             let mut obj = Map::new();
             for cell in &row.cells {
-                let value = if cell.is_null {
-                    Value::Null
-                } else {
+                let value = match cell.written_value()? {
+                    None => Value::Null,
                     // Try to parse as number, otherwise keep as string
-                    cell.value
+                    Some(text) => text
                         .parse::<i64>()
                         .map(Value::from)
-                        .or_else(|_| cell.value.parse::<f64>().map(Value::from))
-                        .unwrap_or_else(|_| Value::String(cell.value.clone()))
+                        .or_else(|_| text.parse::<f64>().map(Value::from))
+                        .unwrap_or(Value::String(text)),
                 };
                 obj.insert(cell.column_metadata.name.clone(), value);
             }
-            Value::Object(obj)
+            Ok(Value::Object(obj))
         })
-        .collect();
+        .collect::<Result<Vec<Value>>>()?;
 
     Ok(serde_json::to_string_pretty(&rows)?)
 }
@@ -1,5 +1,17 @@
+mod connections;
+mod copy;
 mod csv;
+mod history;
 mod json;
+mod scheduled;
+mod snapshot;
+mod template;
 
+pub use connections::*;
+pub use copy::*;
 pub use csv::*;
+pub use history::*;
 pub use json::*;
+pub use scheduled::*;
+pub use snapshot::*;
+pub use template::*;
@@ -0,0 +1,61 @@
+//! One run of a scheduled query: connect on its own pool, run the SQL and
+//! write the rows to a new file.
+
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow, bail};
+use chrono::Local;
+
+use super::{export_to_csv, export_to_json};
+use crate::services::{
+    ConnectionInfo, ConnectionsRepository, DatabaseManager, QueryExecutionResult, ScheduleFormat,
+    ScheduledQuery, storage::vault_locked,
+};
+
+/// What a successful run produced.
+#[derive(Debug, Clone)]
+pub enum ScheduledRunOutput {
+    /// Rows written to `path`.
+    Rows { path: PathBuf, row_count: usize },
+    /// The statement changed rows rather than returning them.
+    Modified { rows_affected: u64 },
+}
+
+/// Run `query` against `connection`, independently of the connection open
+/// in the window. Fails while the saved password is in a locked vault.
+pub async fn run_scheduled_query(
+    query: &ScheduledQuery,
+    mut connection: ConnectionInfo,
+) -> Result<ScheduledRunOutput> {
+    if vault_locked() {
+        bail!("The password vault is locked; unlock it to run scheduled queries");
+    }
+    connection.password =
+        ConnectionsRepository::get_connection_password(&connection.id).unwrap_or_default();
+
+    let db_manager = DatabaseManager::new();
+    db_manager.set_max_rows(None).await;
+    db_manager.connect(&connection).await?;
+    let result = db_manager.execute_query_enhanced(&query.sql).await;
+    let _ = db_manager.disconnect().await;
+
+    match result {
+        QueryExecutionResult::Select(result) => {
+            let contents = match query.format {
+                ScheduleFormat::Csv => export_to_csv(&result)?,
+                ScheduleFormat::Json => export_to_json(&result)?,
+            };
+            let path = query.output_path(Local::now());
+            async_fs::create_dir_all(&query.output_dir).await?;
+            async_fs::write(&path, contents).await?;
+            Ok(ScheduledRunOutput::Rows {
+                path,
+                row_count: result.row_count,
+            })
+        }
+        QueryExecutionResult::Modified(modified) => Ok(ScheduledRunOutput::Modified {
+            rows_affected: modified.rows_affected,
+        }),
+        QueryExecutionResult::Error(error) => Err(anyhow!(error.message)),
+    }
+}
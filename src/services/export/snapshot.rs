@@ -0,0 +1,278 @@
+//! Result snapshots saved under `~/.pgui/snapshots`.
+//!
+//! A snapshot is two files: `<name>.json` with the query and column
+//! metadata, and `<name>.rows.zst` with the zstd-compressed cell values.
+//! Keeping the columns out of the rows avoids repeating them per cell.
+//! Snapshots open without a connection, from the Snapshots dialog or any
+//! folder.
+
+use crate::services::{QueryResult, ResultColumnMetadata, ResultRows};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_VERSION: u32 = 1;
+const ZSTD_LEVEL: i32 = 3;
+const ROWS_EXTENSION: &str = "rows.zst";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMeta {
+    pub version: u32,
+    /// Empty for snapshots saved before they were named.
+    #[serde(default)]
+    pub name: String,
+    pub created_at: String,
+    pub original_query: String,
+    pub execution_time_ms: u128,
+    pub row_count: usize,
+    pub columns: Vec<ResultColumnMetadata>,
+    #[serde(default)]
+    pub truncated_at: Option<usize>,
+}
+
+/// Default directory for snapshots, created on first use.
+pub fn snapshots_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    let dir = home.join(".pgui").join("snapshots");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// A snapshot found in a directory, by its metadata file.
+#[derive(Debug, Clone)]
+pub struct SnapshotEntry {
+    pub path: PathBuf,
+    pub meta: SnapshotMeta,
+}
+
+impl SnapshotEntry {
+    /// The snapshot's name, or its file name when it has none.
+    pub fn name(&self) -> String {
+        if self.meta.name.trim().is_empty() {
+            self.path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        } else {
+            self.meta.name.clone()
+        }
+    }
+}
+
+/// Write `result` into `dir` under `name` and return the path of its
+/// metadata file.
+pub fn write_snapshot(result: &QueryResult, name: &str, dir: &Path) -> Result<PathBuf> {
+    let now = chrono::Local::now();
+    let file_name = format!("snapshot_{}", now.format("%Y%m%d_%H%M%S_%3f"));
+    let meta_path = dir.join(format!("{}.json", file_name));
+
+    let meta = SnapshotMeta {
+        version: SNAPSHOT_VERSION,
+        name: name.trim().to_string(),
+        created_at: now.to_rfc3339(),
+        original_query: result.original_query.clone(),
+        execution_time_ms: result.execution_time_ms,
+        row_count: result.rows.len(),
+        columns: result.columns.clone(),
+        truncated_at: result.truncated_at,
+    };
+
+    let values = (0..result.rows.len())
+        .map(|row| {
+            result
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(col, column)| result.rows.cell(row, col).into_value(column))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let file = File::create(rows_path(&meta_path))?;
+    let mut encoder = zstd::Encoder::new(BufWriter::new(file), ZSTD_LEVEL)?;
+    serde_json::to_writer(&mut encoder, &values)?;
+    encoder.finish()?.flush()?;
+
+    // Written last so a metadata file always has its rows next to it.
+    let mut meta_file = BufWriter::new(File::create(&meta_path)?);
+    serde_json::to_writer_pretty(&mut meta_file, &meta)?;
+    meta_file.flush()?;
+
+    Ok(meta_path)
+}
+
+/// Read a snapshot back from its metadata file, decompressing the rows.
+pub fn read_snapshot(meta_path: &Path) -> Result<QueryResult> {
+    let meta = read_meta(meta_path)?;
+    if meta.version > SNAPSHOT_VERSION {
+        return Err(anyhow!(
+            "Snapshot version {} is newer than this version of pgui supports",
+            meta.version
+        ));
+    }
+
+    let decoder = zstd::Decoder::new(File::open(rows_path(meta_path))?)?;
+    let values: Vec<Vec<Option<String>>> = serde_json::from_reader(decoder)?;
+
+    Ok(QueryResult {
+        row_count: values.len(),
+        columns: meta.columns,
+        rows: ResultRows::from_text(values),
+        execution_time_ms: meta.execution_time_ms,
+        original_query: meta.original_query,
+        truncated_at: meta.truncated_at,
+    })
+}
+
+fn read_meta(meta_path: &Path) -> Result<SnapshotMeta> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(
+        meta_path,
+    )?))?)
+}
+
+/// Snapshots in `dir`, newest first. Metadata files that cannot be read,
+/// or whose rows are missing, are skipped.
+pub fn list_snapshots(dir: &Path) -> Result<Vec<SnapshotEntry>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") || !rows_path(&path).exists() {
+            continue;
+        }
+        match read_meta(&path) {
+            Ok(meta) => entries.push(SnapshotEntry { path, meta }),
+            Err(e) => tracing::debug!("Skipping snapshot {}: {}", path.display(), e),
+        }
+    }
+    entries.sort_by(|a, b| b.meta.created_at.cmp(&a.meta.created_at));
+    Ok(entries)
+}
+
+/// Remove a snapshot's metadata and rows.
+pub fn delete_snapshot(meta_path: &Path) -> Result<()> {
+    // A snapshot missing its rows can still be removed.
+    std::fs::remove_file(rows_path(meta_path)).or_else(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => Ok(()),
+        _ => Err(e),
+    })?;
+    std::fs::remove_file(meta_path)?;
+    Ok(())
+}
+
+/// `<name>.rows.zst` next to `<name>.json`.
+fn rows_path(meta_path: &Path) -> PathBuf {
+    meta_path.with_extension(ROWS_EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{ResultCell, ResultRow};
+
+    fn column(name: &str, ordinal: usize) -> ResultColumnMetadata {
+        ResultColumnMetadata {
+            name: name.to_string(),
+            type_name: "TEXT".to_string(),
+            ordinal,
+            table_name: Some("public.users".to_string()),
+            is_nullable: Some(true),
+            source_column: None,
+        }
+    }
+
+    fn sample(rows: usize) -> QueryResult {
+        let columns = vec![column("id", 0), column("email", 1)];
+        let rows = (0..rows)
+            .map(|i| ResultRow {
+                cells: vec![
+                    ResultCell {
+                        value: i.to_string(),
+                        is_null: false,
+                        undecoded: false,
+                        column_metadata: columns[0].clone(),
+                    },
+                    ResultCell {
+                        value: "NULL".to_string(),
+                        is_null: i % 2 == 0,
+                        undecoded: false,
+                        column_metadata: columns[1].clone(),
+                    },
+                ],
+            })
+            .collect::<Vec<_>>();
+
+        QueryResult {
+            row_count: rows.len(),
+            columns,
+            rows: rows.into(),
+            execution_time_ms: 12,
+            original_query: "SELECT id, email FROM users".to_string(),
+            truncated_at: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_rows_and_nulls() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = sample(3);
+
+        let meta_path = write_snapshot(&result, "Users", dir.path()).unwrap();
+        let restored = read_snapshot(&meta_path).unwrap();
+        let rows = restored.iter_rows().collect::<Vec<_>>();
+
+        assert_eq!(restored.original_query, result.original_query);
+        assert_eq!(restored.row_count, 3);
+        assert_eq!(restored.columns.len(), 2);
+        assert_eq!(rows[1].cells[0].value, "1");
+        assert!(rows[0].cells[1].is_null);
+        assert!(!rows[1].cells[1].is_null);
+        assert_eq!(
+            rows[2].cells[1].column_metadata.table_name.as_deref(),
+            Some("public.users")
+        );
+    }
+
+    #[test]
+    fn compressed_rows_are_smaller_than_the_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = sample(1_000);
+
+        let meta_path = write_snapshot(&result, "Users", dir.path()).unwrap();
+        let compressed = std::fs::metadata(rows_path(&meta_path)).unwrap().len() as usize;
+        let uncompressed = serde_json::to_vec(&result).unwrap().len();
+
+        assert!(compressed * 10 < uncompressed);
+    }
+
+    #[test]
+    fn missing_rows_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let meta_path = write_snapshot(&sample(1), "One", dir.path()).unwrap();
+        std::fs::remove_file(rows_path(&meta_path)).unwrap();
+
+        assert!(read_snapshot(&meta_path).is_err());
+    }
+
+    #[test]
+    fn lists_named_snapshots_newest_first_and_deletes_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_snapshot(&sample(1), "  First  ", dir.path()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = write_snapshot(&sample(2), "", dir.path()).unwrap();
+        std::fs::write(dir.path().join("notes.json"), "{}").unwrap();
+
+        let entries = list_snapshots(dir.path()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, second);
+        assert_eq!(entries[0].meta.row_count, 2);
+        assert!(entries[0].name().starts_with("snapshot_"));
+        assert_eq!(entries[1].name(), "First");
+
+        delete_snapshot(&first).unwrap();
+        assert!(!first.exists());
+        assert!(!rows_path(&first).exists());
+        assert_eq!(list_snapshots(dir.path()).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,289 @@
+//! Export templates: which columns of a result to export, under which
+//! headers, and how dates and NULLs are written. Templates are saved by
+//! name for each connection, so a recurring export is set up once.
+
+use anyhow::Result;
+use csv::Writer;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::services::{DateTimeDisplay, QueryResult, display_value};
+
+/// Workspace state key holding a connection's export templates.
+pub fn export_templates_key(connection_id: &Uuid) -> String {
+    format!("export_templates:{}", connection_id)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TemplateFormat {
+    #[default]
+    Csv,
+    /// An array of objects keyed by header.
+    Json,
+}
+
+/// A result column as it is exported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportColumn {
+    /// The column's name in the result.
+    pub source: String,
+    pub header: String,
+    pub included: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub format: TemplateFormat,
+    /// In export order.
+    pub columns: Vec<ExportColumn>,
+    /// Dates and times as the grid can show them; raw keeps them as
+    /// decoded.
+    #[serde(default)]
+    pub date_time: DateTimeDisplay,
+    /// Written for NULL in CSV. JSON always writes `null`.
+    #[serde(default)]
+    pub null_text: String,
+}
+
+impl ExportTemplate {
+    /// Every column of `result` under its own name.
+    pub fn for_result(result: &QueryResult) -> Self {
+        Self {
+            columns: result
+                .columns
+                .iter()
+                .map(|column| ExportColumn {
+                    source: column.name.clone(),
+                    header: column.name.clone(),
+                    included: true,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// This template applied to `result`: its columns keep their header,
+    /// order and whether they are included. Columns the template does not
+    /// know are added at the end, included; those missing from `result`
+    /// are left out.
+    pub fn fitted(&self, result: &QueryResult) -> Self {
+        let mut unmatched: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        let mut columns: Vec<ExportColumn> = vec![];
+        for column in &self.columns {
+            if let Some(ix) = unmatched.iter().position(|name| *name == column.source) {
+                unmatched.remove(ix);
+                columns.push(column.clone());
+            }
+        }
+        columns.extend(unmatched.into_iter().map(|name| ExportColumn {
+            source: name.to_string(),
+            header: name.to_string(),
+            included: true,
+        }));
+        Self {
+            columns,
+            ..self.clone()
+        }
+    }
+
+    /// Result column index and header of each included column, in export
+    /// order. Columns sharing a name are matched in turn.
+    fn included(&self, result: &QueryResult) -> Vec<(usize, String)> {
+        let mut used = vec![false; result.columns.len()];
+        let mut included = vec![];
+        for column in &self.columns {
+            let Some(ix) = result
+                .columns
+                .iter()
+                .enumerate()
+                .position(|(ix, c)| !used[ix] && c.name == column.source)
+            else {
+                continue;
+            };
+            used[ix] = true;
+            if column.included {
+                let header = match column.header.trim() {
+                    "" => column.source.clone(),
+                    header => header.to_string(),
+                };
+                included.push((ix, header));
+            }
+        }
+        included
+    }
+
+    /// The text written for a cell, `None` for NULL.
+    fn cell_text(&self, result: &QueryResult, row: usize, col: usize) -> Result<Option<String>> {
+        let column = &result.columns[col];
+        let Some(value) = result.rows.cell(row, col).into_value(column)? else {
+            return Ok(None);
+        };
+        Ok(Some(
+            display_value(&value, &column.type_name, &self.date_time).unwrap_or(value),
+        ))
+    }
+
+    pub fn export(&self, result: &QueryResult) -> Result<String> {
+        let columns = self.included(result);
+        match self.format {
+            TemplateFormat::Csv => {
+                let mut wtr = Writer::from_writer(vec![]);
+                wtr.write_record(columns.iter().map(|(_, header)| header))?;
+                for row in 0..result.rows.len() {
+                    let values = columns
+                        .iter()
+                        .map(|(col, _)| {
+                            Ok(self
+                                .cell_text(result, row, *col)?
+                                .unwrap_or_else(|| self.null_text.clone()))
+                        })
+                        .collect::<Result<Vec<String>>>()?;
+                    wtr.write_record(&values)?;
+                }
+                Ok(String::from_utf8(wtr.into_inner()?)?)
+            }
+            TemplateFormat::Json => {
+                let rows = (0..result.rows.len())
+                    .map(|row| {
+                        let mut obj = Map::new();
+                        for (col, header) in &columns {
+                            let value = match self.cell_text(result, row, *col)? {
+                                None => Value::Null,
+                                Some(text) => text
+                                    .parse::<i64>()
+                                    .map(Value::from)
+                                    .or_else(|_| text.parse::<f64>().map(Value::from))
+                                    .unwrap_or(Value::String(text)),
+                            };
+                            obj.insert(header.clone(), value);
+                        }
+                        Ok(Value::Object(obj))
+                    })
+                    .collect::<Result<Vec<Value>>>()?;
+                Ok(serde_json::to_string_pretty(&rows)?)
+            }
+        }
+    }
+}
+
+/// Add `template` to `templates`, replacing one of the same name.
+pub fn save_template(templates: &mut Vec<ExportTemplate>, template: ExportTemplate) {
+    match templates.iter_mut().find(|t| t.name == template.name) {
+        Some(existing) => *existing = template,
+        None => {
+            templates.push(template);
+            templates.sort_by_key(|t| t.name.to_lowercase());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{ResultColumnMetadata, ResultRows, TimeZoneDisplay};
+
+    fn result() -> QueryResult {
+        let column = |ordinal: usize, name: &str, type_name: &str| ResultColumnMetadata {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            ordinal,
+            table_name: None,
+            is_nullable: None,
+            source_column: None,
+        };
+        QueryResult {
+            columns: vec![
+                column(0, "id", "INT4"),
+                column(1, "email", "TEXT"),
+                column(2, "created_at", "TIMESTAMPTZ"),
+            ],
+            rows: ResultRows::from_text(vec![
+                vec![
+                    Some("1".to_string()),
+                    Some("a@example.com".to_string()),
+                    Some("2024-05-01 13:45:00 UTC".to_string()),
+                ],
+                vec![Some("2".to_string()), None, None],
+            ]),
+            row_count: 2,
+            execution_time_ms: 0,
+            original_query: "SELECT * FROM users".to_string(),
+            truncated_at: None,
+        }
+    }
+
+    #[test]
+    fn exports_chosen_columns_under_their_headers() {
+        let result = result();
+        let mut template = ExportTemplate::for_result(&result);
+        template.columns[0].included = false;
+        template.columns[1].header = "Email".to_string();
+        template.columns.swap(1, 2);
+        template.null_text = "\\N".to_string();
+        template.date_time.zone = TimeZoneDisplay::Utc;
+
+        assert_eq!(
+            template.export(&result).unwrap(),
+            "created_at,Email\n2024-05-01T13:45:00Z,a@example.com\n\\N,\\N\n"
+        );
+
+        template.format = TemplateFormat::Json;
+        let json: Value = serde_json::from_str(&template.export(&result).unwrap()).unwrap();
+        assert_eq!(json[0]["Email"], "a@example.com");
+        assert_eq!(json[1]["created_at"], Value::Null);
+        assert!(json[0].get("id").is_none());
+    }
+
+    #[test]
+    fn fits_a_saved_template_to_another_result() {
+        let result = result();
+        let template = ExportTemplate {
+            name: "Users".to_string(),
+            columns: vec![
+                ExportColumn {
+                    source: "email".to_string(),
+                    header: "Email".to_string(),
+                    included: true,
+                },
+                ExportColumn {
+                    source: "deleted_at".to_string(),
+                    header: "Deleted".to_string(),
+                    included: true,
+                },
+                ExportColumn {
+                    source: "id".to_string(),
+                    header: "id".to_string(),
+                    included: false,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let fitted = template.fitted(&result);
+        let sources: Vec<&str> = fitted.columns.iter().map(|c| c.source.as_str()).collect();
+        assert_eq!(sources, vec!["email", "id", "created_at"]);
+        assert_eq!(fitted.columns[0].header, "Email");
+        assert!(!fitted.columns[1].included);
+        assert!(fitted.columns[2].included);
+        assert_eq!(fitted.name, "Users");
+    }
+
+    #[test]
+    fn saving_replaces_by_name() {
+        let mut templates = vec![];
+        let named = |name: &str, null_text: &str| ExportTemplate {
+            name: name.to_string(),
+            null_text: null_text.to_string(),
+            ..Default::default()
+        };
+        save_template(&mut templates, named("weekly", ""));
+        save_template(&mut templates, named("Audit", ""));
+        save_template(&mut templates, named("weekly", "NULL"));
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["Audit", "weekly"]);
+        assert_eq!(templates[1].null_text, "NULL");
+    }
+}
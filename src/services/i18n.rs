@@ -0,0 +1,202 @@
+//! Translated user-facing strings.
+//!
+//! Messages are Fluent files under `locales/<locale>/pgui.ftl`, compiled
+//! into the binary. The locale is `PGUI_LOCALE` when set, otherwise the
+//! system locale, falling back to English. A message missing from the
+//! chosen locale is taken from English, so a partial translation still
+//! shows every string.
+//!
+//! Adding a locale means adding its `.ftl` file and an entry to
+//! [`LOCALES`]; render code only refers to message ids through [`tr`] and
+//! [`tr_args`].
+
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+const DEFAULT_LOCALE: &str = "en-US";
+
+/// Bundled locales and their messages.
+const LOCALES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../../locales/en-US/pgui.ftl")),
+    ("de", include_str!("../../locales/de/pgui.ftl")),
+];
+
+struct Localizer {
+    bundle: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+/// Pick the locale and load its messages. Called once at startup; strings
+/// looked up before then come from the default locale.
+pub fn init() {
+    let requested = std::env::var("PGUI_LOCALE")
+        .ok()
+        .filter(|locale| !locale.is_empty())
+        .or_else(sys_locale::get_locale);
+    let locale = requested
+        .as_deref()
+        .and_then(negotiate)
+        .unwrap_or(DEFAULT_LOCALE);
+    if LOCALIZER.set(Localizer::new(locale)).is_err() {
+        tracing::warn!("Translations were already loaded");
+    }
+    tracing::info!("Using the {} locale", locale);
+}
+
+/// The bundled locale best matching a BCP 47 tag such as `de-AT` or
+/// `de_AT.UTF-8`: the exact locale, else one of the same language.
+pub fn negotiate(requested: &str) -> Option<&'static str> {
+    let tag = requested.split(['.', '@']).next()?.replace('_', "-");
+    let requested: LanguageIdentifier = tag.parse().ok()?;
+    let available = LOCALES.iter().filter_map(|(locale, _)| {
+        locale
+            .parse::<LanguageIdentifier>()
+            .ok()
+            .map(|id| (*locale, id))
+    });
+
+    let mut same_language = None;
+    for (locale, id) in available {
+        if id == requested {
+            return Some(locale);
+        }
+        if same_language.is_none() && id.language == requested.language {
+            same_language = Some(locale);
+        }
+    }
+    same_language
+}
+
+/// The message `id` in the current locale.
+pub fn tr(id: &str) -> String {
+    localizer().format(id, None)
+}
+
+/// The message `id` with its `{ $name }` placeholders filled in.
+pub fn tr_args<'a>(id: &str, args: &[(&'a str, FluentValue<'a>)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    localizer().format(id, Some(&fluent_args))
+}
+
+fn localizer() -> &'static Localizer {
+    LOCALIZER.get_or_init(|| Localizer::new(DEFAULT_LOCALE))
+}
+
+impl Localizer {
+    fn new(locale: &str) -> Self {
+        Self {
+            bundle: load_bundle(locale),
+            fallback: load_bundle(DEFAULT_LOCALE),
+        }
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        format_message(&self.bundle, id, args)
+            .or_else(|| format_message(&self.fallback, id, args))
+            .unwrap_or_else(|| {
+                tracing::warn!("Missing translation for {}", id);
+                id.to_string()
+            })
+    }
+}
+
+fn load_bundle(locale: &str) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = locale.parse().unwrap_or_default();
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Directional isolation marks show up as boxes in some fonts.
+    bundle.set_use_isolating(false);
+
+    let source = LOCALES
+        .iter()
+        .find(|(name, _)| *name == locale)
+        .map_or("", |(_, source)| source);
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, e)| {
+        tracing::warn!("Errors in the {} translations: {:?}", locale, e);
+        resource
+    });
+    if let Err(e) = bundle.add_resource(resource) {
+        tracing::warn!("Duplicate messages in the {} translations: {:?}", locale, e);
+    }
+    bundle
+}
+
+fn format_message(
+    bundle: &FluentBundle<FluentResource>,
+    id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = vec![];
+    let text = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        tracing::warn!("Failed to format {}: {:?}", id, errors);
+    }
+    Some(text.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ids of the messages defined in a Fluent file.
+    fn message_ids(source: &str) -> Vec<&str> {
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| line.split_once('=').map(|(id, _)| id.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn translations_parse_and_only_use_english_ids() {
+        for (locale, source) in LOCALES {
+            if let Err((_, e)) = FluentResource::try_new(source.to_string()) {
+                panic!("{} has invalid Fluent: {:?}", locale, e);
+            }
+        }
+        let english = message_ids(LOCALES[0].1);
+        assert!(!english.is_empty());
+        for (locale, source) in &LOCALES[1..] {
+            for id in message_ids(source) {
+                assert!(
+                    english.contains(&id),
+                    "{} has unknown message {}",
+                    locale,
+                    id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn negotiates_the_closest_bundled_locale() {
+        assert_eq!(negotiate("en-US"), Some("en-US"));
+        assert_eq!(negotiate("en_GB.UTF-8"), Some("en-US"));
+        assert_eq!(negotiate("de-AT"), Some("de"));
+        assert_eq!(negotiate("fr-FR"), None);
+    }
+
+    #[test]
+    fn formats_arguments_and_shows_unknown_ids_as_is() {
+        let localizer = Localizer::new("de");
+        let mut args = FluentArgs::new();
+        args.set("version", "1.2.0");
+        assert_eq!(
+            localizer.format("panel-move-right", None),
+            "Ins rechte Dock verschieben"
+        );
+        assert_eq!(
+            localizer.format("header-update-available", Some(&args)),
+            "v1.2.0 verfügbar!"
+        );
+        assert_eq!(localizer.format("no-such-message", None), "no-such-message");
+    }
+}
@@ -1,6 +1,9 @@
 pub mod agent;
+pub mod cli;
 pub mod database;
+pub mod diagnostics;
 pub mod export;
+pub mod i18n;
 pub mod sql;
 pub mod ssh;
 pub mod storage;
@@ -12,7 +15,5 @@ pub use sql::SqlCompletionProvider;
 #[allow(unused_imports)]
 pub use storage::{
     AppStore, ConnectionInfo, ConnectionsRepository, DatabaseDriver, QueryHistoryRepository,
-    SslMode,
+    ScheduleFormat, ScheduledQuery, SslMode,
 };
-
-pub use updates::check_for_update;
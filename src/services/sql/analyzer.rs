@@ -1,5 +1,9 @@
 use tree_sitter::{Parser, Tree};
 
+use crate::services::storage::DatabaseDriver;
+
+use super::statements::statement_ranges;
+
 /// Represents a detected SQL query with position information
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -35,12 +39,18 @@ impl SqlQueryAnalyzer {
             None => return Vec::new(),
         };
 
+        // DO blocks, PL/pgSQL bodies and psql meta-commands are beyond the
+        // grammar, and its error recovery would cut them into pieces.
+        if tree.root_node().has_error() {
+            return self.split_lexically(sql_content);
+        }
+
         let mut queries = Vec::new();
         self.walk_tree(&tree, sql_content, &mut queries);
 
-        // If tree-sitter didn't find structured queries, try semicolon splitting
+        // If tree-sitter didn't find structured queries, split on semicolons
         if queries.is_empty() {
-            self.fallback_semicolon_split(sql_content, &mut queries);
+            return self.split_lexically(sql_content);
         }
 
         queries
@@ -87,6 +97,8 @@ impl SqlQueryAnalyzer {
                     end_char: byte_to_char_offset(source, node.end_byte()),
                     query_text,
                 });
+                // Statements nested in a function body belong to this one
+                return;
             }
         }
 
@@ -97,52 +109,21 @@ impl SqlQueryAnalyzer {
         }
     }
 
-    fn fallback_semicolon_split(&self, source: &str, queries: &mut Vec<SqlQuery>) {
-        let mut current_query = String::new();
-        let mut query_start_line = 0;
-        let mut query_start_char = 0;
-        let mut current_char_offset = 0;
-
-        for (line_idx, line) in source.lines().enumerate() {
-            if current_query.is_empty() {
-                query_start_line = line_idx;
-                query_start_char = current_char_offset;
-            }
-
-            current_query.push_str(line);
-            current_query.push('\n');
-
-            if line.trim().ends_with(';') && !line.trim().starts_with("--") {
-                let query_text = current_query.trim().to_string();
-                if !query_text.is_empty() {
-                    queries.push(SqlQuery {
-                        start_byte: query_start_char,
-                        end_byte: current_char_offset + line.len(),
-                        start_line: query_start_line,
-                        end_line: line_idx,
-                        start_char: query_start_char,
-                        end_char: current_char_offset + line.len(),
-                        query_text,
-                    });
-                }
-                current_query.clear();
-            }
-
-            current_char_offset += line.len() + 1; // +1 for newline
-        }
-
-        // Handle case where last query doesn't end with semicolon
-        if !current_query.trim().is_empty() {
-            queries.push(SqlQuery {
-                start_byte: query_start_char,
-                end_byte: current_char_offset,
-                start_line: query_start_line,
-                end_line: source.lines().count().saturating_sub(1),
-                start_char: query_start_char,
-                end_char: current_char_offset,
-                query_text: current_query.trim().to_string(),
-            });
-        }
+    /// Statements split on semicolons outside strings, comments and
+    /// dollar-quoted bodies, skipping psql meta-commands.
+    fn split_lexically(&self, source: &str) -> Vec<SqlQuery> {
+        statement_ranges(&DatabaseDriver::Postgres, source)
+            .into_iter()
+            .map(|range| SqlQuery {
+                start_byte: range.start,
+                end_byte: range.end,
+                start_line: source[..range.start].matches('\n').count(),
+                end_line: source[..range.end].matches('\n').count(),
+                start_char: byte_to_char_offset(source, range.start),
+                end_char: byte_to_char_offset(source, range.end),
+                query_text: source[range].to_string(),
+            })
+            .collect()
     }
 }
 
@@ -152,3 +133,21 @@ fn byte_to_char_offset(text: &str, byte_offset: usize) -> usize {
         .position(|(i, _)| i >= byte_offset)
         .unwrap_or(text.chars().count())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn do_blocks_and_meta_commands_split_whole() {
+        let sql = "\\timing on\nDO $$\nBEGIN\n  PERFORM 1;\nEND\n$$;\nSELECT 2;";
+        let queries = SqlQueryAnalyzer::new().detect_queries(sql);
+        let texts: Vec<&str> = queries.iter().map(|q| q.query_text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["DO $$\nBEGIN\n  PERFORM 1;\nEND\n$$", "SELECT 2"]
+        );
+        assert_eq!(queries[0].start_line, 1);
+        assert_eq!(queries[1].start_line, 6);
+    }
+}
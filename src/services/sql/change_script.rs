@@ -0,0 +1,578 @@
+//! Recording the DDL run through pgui into a change script that can be
+//! saved as a migration.
+//!
+//! Each recorded statement gets a rollback statement when one follows
+//! from the statement alone: `CREATE TABLE t` is undone by `DROP TABLE t`,
+//! `ALTER TABLE t ADD COLUMN c` by dropping `c`, a rename by the reverse
+//! rename, `GRANT` by `REVOKE`. Anything that would need the state before
+//! the change (a `DROP`, a type change, `CREATE OR REPLACE`) is left for
+//! the author of the migration, marked in the down script.
+
+use crate::services::storage::DatabaseDriver;
+
+use super::params::{is_ident_byte, skip_line, skip_quoted};
+
+/// A statement of the change script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedChange {
+    pub sql: String,
+    pub rollback: Option<String>,
+}
+
+/// The DDL recorded so far, in the order it ran.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeScript {
+    pub changes: Vec<RecordedChange>,
+}
+
+impl ChangeScript {
+    /// Append `sql` when it is DDL. Returns whether it was recorded.
+    pub fn record(&mut self, driver: &DatabaseDriver, sql: &str) -> bool {
+        if !is_ddl(driver, sql) {
+            return false;
+        }
+        let sql = statement_text(sql);
+        self.changes.push(RecordedChange {
+            rollback: rollback_sql(driver, &sql),
+            sql,
+        });
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The recorded statements, ready to run again.
+    pub fn up_script(&self) -> String {
+        self.changes
+            .iter()
+            .map(|change| format!("{};\n", change.sql))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The rollbacks in reverse order, with a commented-out note for each
+    /// statement that has none.
+    pub fn down_script(&self) -> String {
+        self.changes
+            .iter()
+            .rev()
+            .map(|change| match &change.rollback {
+                Some(rollback) => format!("{};\n", rollback),
+                None => {
+                    let original = change
+                        .sql
+                        .lines()
+                        .map(|line| format!("-- {}", line))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    format!("-- TODO: no rollback could be derived for:\n{}\n", original)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Whether `sql` changes the schema or privileges.
+pub fn is_ddl(driver: &DatabaseDriver, sql: &str) -> bool {
+    let tokens = tokenize(driver, sql);
+    tokens.first().is_some_and(|first| {
+        [
+            "CREATE", "ALTER", "DROP", "COMMENT", "RENAME", "GRANT", "REVOKE",
+        ]
+        .iter()
+        .any(|kw| first.is(kw))
+    })
+}
+
+/// The statement that undoes `sql`, when it follows from `sql` alone.
+pub fn rollback_sql(driver: &DatabaseDriver, sql: &str) -> Option<String> {
+    let tokens = tokenize(driver, sql);
+    let mut p = Parser {
+        sql,
+        tokens: &tokens,
+        pos: 0,
+    };
+    if p.eat("CREATE") {
+        create_rollback(driver, &mut p)
+    } else if p.eat("ALTER") && p.eat("TABLE") {
+        alter_table_rollback(&mut p)
+    } else if p.eat("GRANT") {
+        let to = p.find_keyword("TO")?;
+        let grantees = p.text_from(to + 1);
+        let grantees = strip_suffix_ci(grantees, "WITH GRANT OPTION").trim_end();
+        Some(format!(
+            "REVOKE {} FROM {}",
+            p.text_between(1, to),
+            grantees
+        ))
+    } else if p.eat("REVOKE") {
+        if p.peek_is("GRANT") {
+            return None;
+        }
+        let from = p.find_keyword("FROM")?;
+        let grantees = p.text_from(from + 1);
+        let grantees = strip_suffix_ci(strip_suffix_ci(grantees, "CASCADE"), "RESTRICT").trim_end();
+        Some(format!("GRANT {} TO {}", p.text_between(1, from), grantees))
+    } else {
+        None
+    }
+}
+
+/// `20241016093000_add_orders_index.up.sql` and its `.down.sql`, named
+/// the way sqlx and golang-migrate expect.
+pub fn migration_file_names(version: &str, name: &str) -> (String, String) {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_matches('_');
+    let slug = if slug.is_empty() {
+        "change_script"
+    } else {
+        slug
+    };
+    (
+        format!("{}_{}.up.sql", version, slug),
+        format!("{}_{}.down.sql", version, slug),
+    )
+}
+
+fn create_rollback(driver: &DatabaseDriver, p: &mut Parser) -> Option<String> {
+    if p.eat("OR") {
+        // The definition being replaced is unknown
+        return None;
+    }
+    let _ = p.eat("UNIQUE");
+    let _ = p.eat("TEMP") || p.eat("TEMPORARY") || p.eat("UNLOGGED");
+
+    if p.eat("INDEX") {
+        let _ = p.eat("CONCURRENTLY");
+        if p.eat_if_not_exists() {
+            return None;
+        }
+        let (index, _) = p.name()?;
+        if !p.eat("ON") {
+            return None;
+        }
+        let _ = p.eat("ONLY");
+        let (table, table_parts) = p.name()?;
+        return Some(match driver {
+            DatabaseDriver::MySql => format!("DROP INDEX {} ON {}", index, table),
+            // Postgres indexes live in the schema of their table
+            DatabaseDriver::Postgres if !index.contains('.') && table_parts > 1 => {
+                let schema = &table[..table.len() - last_part_len(&table)];
+                format!("DROP INDEX {}{}", schema, index)
+            }
+            DatabaseDriver::Postgres => format!("DROP INDEX {}", index),
+        });
+    }
+
+    let kind = if p.eat("MATERIALIZED") {
+        if !p.eat("VIEW") {
+            return None;
+        }
+        "MATERIALIZED VIEW"
+    } else {
+        [
+            "TABLE",
+            "VIEW",
+            "SCHEMA",
+            "SEQUENCE",
+            "TYPE",
+            "EXTENSION",
+            "DATABASE",
+        ]
+        .into_iter()
+        .find(|kind| p.eat(kind))?
+    };
+    if p.eat_if_not_exists() {
+        return None;
+    }
+    let (name, _) = p.name()?;
+    Some(format!("DROP {} {}", kind, name))
+}
+
+fn alter_table_rollback(p: &mut Parser) -> Option<String> {
+    if p.eat("IF") && !(p.eat("EXISTS")) {
+        return None;
+    }
+    let _ = p.eat("ONLY");
+    let (table, _) = p.name()?;
+    // Several actions in one statement are not undone
+    if p.has_top_level_comma() {
+        return None;
+    }
+
+    if p.eat("ADD") {
+        if p.eat("CONSTRAINT") {
+            let (constraint, _) = p.name()?;
+            return Some(format!(
+                "ALTER TABLE {} DROP CONSTRAINT {}",
+                table, constraint
+            ));
+        }
+        if ["PRIMARY", "UNIQUE", "FOREIGN", "CHECK", "INDEX", "KEY"]
+            .iter()
+            .any(|kw| p.peek_is(kw))
+        {
+            return None;
+        }
+        let _ = p.eat("COLUMN");
+        if p.eat_if_not_exists() {
+            return None;
+        }
+        let (column, parts) = p.name()?;
+        if parts > 1 {
+            return None;
+        }
+        return Some(format!("ALTER TABLE {} DROP COLUMN {}", table, column));
+    }
+
+    if p.eat("RENAME") {
+        if p.eat("TO") {
+            let (new_name, _) = p.name()?;
+            let schema = &table[..table.len() - last_part_len(&table)];
+            let old = &table[schema.len()..];
+            return Some(format!(
+                "ALTER TABLE {}{} RENAME TO {}",
+                schema, new_name, old
+            ));
+        }
+        if p.eat("CONSTRAINT") {
+            let (old, _) = p.name()?;
+            if !p.eat("TO") {
+                return None;
+            }
+            let (new, _) = p.name()?;
+            return Some(format!(
+                "ALTER TABLE {} RENAME CONSTRAINT {} TO {}",
+                table, new, old
+            ));
+        }
+        let _ = p.eat("COLUMN");
+        let (old, _) = p.name()?;
+        if !p.eat("TO") {
+            return None;
+        }
+        let (new, _) = p.name()?;
+        return Some(format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {}",
+            table, new, old
+        ));
+    }
+    None
+}
+
+/// `sql` trimmed, without its `;`.
+fn statement_text(sql: &str) -> String {
+    sql.trim().trim_end_matches(';').trim_end().to_string()
+}
+
+fn strip_suffix_ci<'a>(text: &'a str, suffix: &str) -> &'a str {
+    let text = text.trim_end();
+    match text.len().checked_sub(suffix.len()) {
+        Some(at) if text.is_char_boundary(at) && text[at..].eq_ignore_ascii_case(suffix) => {
+            &text[..at]
+        }
+        _ => text,
+    }
+}
+
+/// Length of the last part of a dotted name.
+fn last_part_len(name: &str) -> usize {
+    let b = name.as_bytes();
+    let mut i = 0;
+    let mut last = 0;
+    while i < b.len() {
+        match b[i] {
+            b'"' | b'`' => i = skip_quoted(b, i, false),
+            b'.' => {
+                i += 1;
+                last = i;
+            }
+            _ => i += 1,
+        }
+    }
+    name.len() - last
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl Token<'_> {
+    fn is(&self, keyword: &str) -> bool {
+        self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn is_name(&self) -> bool {
+        let first = self.text.as_bytes()[0];
+        first == b'"' || first == b'`' || first.is_ascii_alphabetic() || first == b'_'
+    }
+}
+
+/// Words, quoted names, strings and single punctuation characters of
+/// `sql`, skipping whitespace and comments.
+fn tokenize<'a>(driver: &DatabaseDriver, sql: &'a str) -> Vec<Token<'a>> {
+    let b = sql.as_bytes();
+    let mysql = *driver == DatabaseDriver::MySql;
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < b.len() {
+        let start = i;
+        match b[i] {
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if b.get(i + 1) == Some(&b'-') => {
+                i = skip_line(b, i);
+                continue;
+            }
+            b'#' if mysql => {
+                i = skip_line(b, i);
+                continue;
+            }
+            b'/' if b.get(i + 1) == Some(&b'*') => {
+                i = match sql[i + 2..].find("*/") {
+                    Some(end) => i + 2 + end + 2,
+                    None => b.len(),
+                };
+                continue;
+            }
+            b'\'' => i = skip_quoted(b, i, mysql),
+            b'"' | b'`' => i = skip_quoted(b, i, false),
+            c if is_ident_byte(c) || c == b'$' || !c.is_ascii() => {
+                while i < b.len() && (is_ident_byte(b[i]) || b[i] == b'$' || !b[i].is_ascii()) {
+                    i += 1;
+                }
+            }
+            _ => i += 1,
+        }
+        tokens.push(Token {
+            text: &sql[start..i],
+            start,
+            end: i,
+        });
+    }
+    tokens
+}
+
+struct Parser<'a, 't> {
+    sql: &'a str,
+    tokens: &'t [Token<'a>],
+    pos: usize,
+}
+
+impl Parser<'_, '_> {
+    fn peek_is(&self, keyword: &str) -> bool {
+        self.tokens.get(self.pos).is_some_and(|t| t.is(keyword))
+    }
+
+    fn eat(&mut self, keyword: &str) -> bool {
+        let matched = self.peek_is(keyword);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    /// Skip `IF NOT EXISTS`, telling whether it was there. Such a
+    /// statement may have created nothing, so it is not undone.
+    fn eat_if_not_exists(&mut self) -> bool {
+        let at = self.pos;
+        if self.eat("IF") && self.eat("NOT") && self.eat("EXISTS") {
+            return true;
+        }
+        self.pos = at;
+        false
+    }
+
+    /// A possibly dotted name as written, and how many parts it has.
+    fn name(&mut self) -> Option<(String, usize)> {
+        let first = self.tokens.get(self.pos).filter(|t| t.is_name())?;
+        let start = first.start;
+        let mut end = first.end;
+        let mut parts = 1;
+        self.pos += 1;
+        while self.tokens.get(self.pos).is_some_and(|t| t.text == ".")
+            && self.tokens.get(self.pos + 1).is_some_and(|t| t.is_name())
+        {
+            end = self.tokens[self.pos + 1].end;
+            parts += 1;
+            self.pos += 2;
+        }
+        Some((self.sql[start..end].to_string(), parts))
+    }
+
+    /// Index of the first `keyword` from here on outside parentheses.
+    fn find_keyword(&self, keyword: &str) -> Option<usize> {
+        let mut depth = 0;
+        for (ix, token) in self.tokens.iter().enumerate().skip(self.pos) {
+            match token.text {
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                _ if depth == 0 && token.is(keyword) => return Some(ix),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn has_top_level_comma(&self) -> bool {
+        let mut depth = 0;
+        for token in &self.tokens[self.pos..] {
+            match token.text {
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                "," if depth == 0 => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// The source text of tokens `from..to`.
+    fn text_between(&self, from: usize, to: usize) -> &str {
+        self.sql[self.tokens[from].start..self.tokens[to - 1].end].trim()
+    }
+
+    /// The source text from token `from` to the end, without `;`.
+    fn text_from(&self, from: usize) -> &str {
+        match self.tokens.get(from) {
+            Some(token) => self.sql[token.start..].trim().trim_end_matches(';'),
+            None => "",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PG: DatabaseDriver = DatabaseDriver::Postgres;
+    const MY: DatabaseDriver = DatabaseDriver::MySql;
+
+    fn rollback(sql: &str) -> Option<String> {
+        rollback_sql(&PG, sql)
+    }
+
+    #[test]
+    fn recognizes_ddl() {
+        assert!(is_ddl(&PG, "-- add it\nCREATE TABLE t (id int)"));
+        assert!(is_ddl(&PG, "grant select on t to app"));
+        assert!(is_ddl(&MY, "RENAME TABLE a TO b"));
+        assert!(!is_ddl(&PG, "SELECT 'CREATE TABLE t'"));
+        assert!(!is_ddl(&PG, "UPDATE t SET x = 1"));
+    }
+
+    #[test]
+    fn derives_rollbacks_for_creates() {
+        assert_eq!(
+            rollback("CREATE TABLE app.\"Orders\" (id int)").as_deref(),
+            Some("DROP TABLE app.\"Orders\"")
+        );
+        // The object may have been there already
+        assert_eq!(rollback("CREATE TABLE IF NOT EXISTS orders (id int)"), None);
+        assert_eq!(
+            rollback("CREATE INDEX IF NOT EXISTS orders_idx ON orders (id)"),
+            None
+        );
+        assert_eq!(
+            rollback("create unique index concurrently orders_idx on app.orders (id)").as_deref(),
+            Some("DROP INDEX app.orders_idx")
+        );
+        assert_eq!(
+            rollback_sql(&MY, "CREATE INDEX orders_idx ON orders (id)").as_deref(),
+            Some("DROP INDEX orders_idx ON orders")
+        );
+        assert_eq!(
+            rollback("CREATE MATERIALIZED VIEW totals AS SELECT 1").as_deref(),
+            Some("DROP MATERIALIZED VIEW totals")
+        );
+        assert_eq!(rollback("CREATE OR REPLACE VIEW v AS SELECT 1"), None);
+        assert_eq!(
+            rollback("CREATE FUNCTION f() RETURNS int AS $$ SELECT 1 $$"),
+            None
+        );
+    }
+
+    #[test]
+    fn derives_rollbacks_for_alter_table() {
+        assert_eq!(
+            rollback("ALTER TABLE orders ADD COLUMN total numeric(10, 2) NOT NULL DEFAULT 0")
+                .as_deref(),
+            Some("ALTER TABLE orders DROP COLUMN total")
+        );
+        assert_eq!(
+            rollback("ALTER TABLE orders ADD CONSTRAINT orders_total_check CHECK (total >= 0)")
+                .as_deref(),
+            Some("ALTER TABLE orders DROP CONSTRAINT orders_total_check")
+        );
+        assert_eq!(
+            rollback("ALTER TABLE app.orders RENAME TO purchases").as_deref(),
+            Some("ALTER TABLE app.purchases RENAME TO orders")
+        );
+        assert_eq!(
+            rollback("ALTER TABLE orders RENAME COLUMN total TO amount").as_deref(),
+            Some("ALTER TABLE orders RENAME COLUMN amount TO total")
+        );
+        assert_eq!(rollback("ALTER TABLE orders ADD a int, ADD b int"), None);
+        assert_eq!(
+            rollback("ALTER TABLE orders ADD COLUMN IF NOT EXISTS total numeric"),
+            None
+        );
+        assert_eq!(
+            rollback("ALTER TABLE orders ALTER COLUMN total TYPE bigint"),
+            None
+        );
+        assert_eq!(rollback("DROP TABLE orders"), None);
+    }
+
+    #[test]
+    fn derives_rollbacks_for_privileges() {
+        assert_eq!(
+            rollback("GRANT SELECT, INSERT ON orders TO app WITH GRANT OPTION;").as_deref(),
+            Some("REVOKE SELECT, INSERT ON orders FROM app")
+        );
+        assert_eq!(
+            rollback("REVOKE UPDATE ON orders FROM app CASCADE").as_deref(),
+            Some("GRANT UPDATE ON orders TO app")
+        );
+    }
+
+    #[test]
+    fn builds_up_and_down_scripts() {
+        let mut script = ChangeScript::default();
+        assert!(script.record(&PG, "CREATE TABLE t (id int);"));
+        assert!(!script.record(&PG, "INSERT INTO t VALUES (1)"));
+        assert!(script.record(&PG, "DROP TABLE old"));
+        assert_eq!(
+            script.up_script(),
+            "CREATE TABLE t (id int);\n\nDROP TABLE old;\n"
+        );
+        assert_eq!(
+            script.down_script(),
+            "-- TODO: no rollback could be derived for:\n-- DROP TABLE old\n\nDROP TABLE t;\n"
+        );
+
+        assert_eq!(
+            migration_file_names("20241016093000", "Add orders index!"),
+            (
+                "20241016093000_add_orders_index.up.sql".to_string(),
+                "20241016093000_add_orders_index.down.sql".to_string()
+            )
+        );
+        assert_eq!(migration_file_names("1", " ").0, "1_change_script.up.sql");
+    }
+}
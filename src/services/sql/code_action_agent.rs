@@ -2,6 +2,9 @@
 //!
 //! Unlike inline completions which are automatic and need to be fast,
 //! code actions are user-initiated and can be more comprehensive.
+//!
+//! Refactorings of a SELECT into a view, function or prepared statement are
+//! offered too; they are generated locally and opened for review.
 
 use std::ops::Range;
 use std::sync::{Arc, RwLock};
@@ -11,8 +14,15 @@ use gpui::{App, BorrowAppContext as _, Entity, SharedString, Task, Window};
 use gpui_component::input::{CodeActionProvider, InputState, RopeExt};
 use lsp_types::{CodeAction, CodeActionKind, TextEdit};
 
-use crate::services::agent::{Agent, AgentResponse, ContentBlock};
-use crate::state::EditorCodeActions;
+use super::params::parse_params;
+use super::refactor::{Refactoring, create_function, create_view, is_select, prepare_statement};
+use super::statements::statement_ranges;
+use crate::services::agent::{Agent, AgentPrivacy, AgentResponse, ContentBlock, SchemaContext};
+use crate::services::{DatabaseDriver, DatabaseManager, QueryExecutionResult};
+use crate::state::{
+    EditorAgentContext, EditorCodeActions, EditorRefactorings, PendingRefactoring,
+    WindowConnection, WindowScoped, window_id,
+};
 
 /// System prompt for SQL code actions
 const CODE_ACTION_SYSTEM_PROMPT: &str = r#"You are a SQL assistant. The user has explicitly requested your help with their SQL query.
@@ -63,7 +73,10 @@ impl ActionType {
 #[derive(Clone)]
 pub struct SqlCodeActionProvider {
     agent: Option<Agent>,
-    schema: Arc<RwLock<Option<String>>>,
+    /// Indexed schema, of which the tables relevant to a request are sent.
+    schema: Arc<RwLock<SchemaContext>>,
+    /// How literals are redacted before the agent sees the query.
+    privacy: Arc<RwLock<AgentPrivacy>>,
 }
 
 impl SqlCodeActionProvider {
@@ -71,18 +84,24 @@ impl SqlCodeActionProvider {
         let agent = build_code_action_agent();
         Self {
             agent,
-            schema: Arc::new(RwLock::new(None)),
+            schema: Arc::new(RwLock::new(SchemaContext::default())),
+            privacy: Arc::new(RwLock::new(AgentPrivacy::default())),
         }
     }
 
-    pub fn set_schema(&self, schema: String) {
+    pub fn set_schema_context(&self, schema: SchemaContext) {
         let mut guard = self.schema.write().unwrap();
-        *guard = Some(schema);
+        *guard = schema;
     }
 
-    fn get_schema(&self) -> Option<String> {
+    fn get_schema(&self) -> SchemaContext {
         self.schema.read().unwrap().clone()
     }
+
+    /// Redact queries sent for code actions as `privacy` says.
+    pub fn set_privacy(&self, privacy: AgentPrivacy) {
+        *self.privacy.write().unwrap() = privacy;
+    }
 }
 
 fn build_code_action_agent() -> Option<Agent> {
@@ -136,23 +155,45 @@ impl CodeActionProvider for SqlCodeActionProvider {
         &self,
         state: Entity<InputState>,
         range: Range<usize>,
-        _window: &mut Window,
+        window: &mut Window,
         cx: &mut App,
     ) -> Task<Result<Vec<CodeAction>>> {
+        let text = state.read(cx).text().to_string();
+        let mut actions = vec![];
+
+        // Refactorings need no agent, only a Postgres connection
+        let is_postgres = WindowConnection::of(window_id(window), cx)
+            .active_connection
+            .as_ref()
+            .is_some_and(|c| c.driver == DatabaseDriver::Postgres);
+        if let Some(source) = is_postgres
+            .then(|| refactor_source(&text, range.clone()))
+            .flatten()
+        {
+            for refactoring in Refactoring::ALL {
+                actions.push(CodeAction {
+                    title: refactoring.title().into(),
+                    kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+                    edit: None,
+                    data: Some(serde_json::json!({
+                        "refactor": refactoring.as_str(),
+                        "range_start": source.start,
+                        "range_end": source.end
+                    })),
+                    ..Default::default()
+                });
+            }
+        }
+
         if self.agent.is_none() {
-            return Task::ready(Ok(vec![]));
+            return Task::ready(Ok(actions));
         }
 
         // Check if there's any SQL content to work with
-        let has_content = {
-            let input = state.read(cx);
-            !input.text().to_string().trim().is_empty()
-        };
+        let has_content = !text.trim().is_empty();
 
         let has_selection = range.start != range.end;
 
-        let mut actions = vec![];
-
         // Always offer Complete (works at cursor)
         actions.push(CodeAction {
             title: ActionType::Complete.title().into(),
@@ -209,6 +250,25 @@ impl CodeActionProvider for SqlCodeActionProvider {
             None => return Task::ready(Ok(())),
         };
 
+        if let Some(refactoring) = data
+            .get("refactor")
+            .and_then(|t| t.as_str())
+            .and_then(Refactoring::from_name)
+        {
+            let start = data.get("range_start").and_then(|v| v.as_u64());
+            let end = data.get("range_end").and_then(|v| v.as_u64());
+            let (Some(start), Some(end)) = (start, end) else {
+                return Task::ready(Ok(()));
+            };
+            return perform_refactoring(
+                state,
+                refactoring,
+                start as usize..end as usize,
+                window,
+                cx,
+            );
+        }
+
         let action_type = data
             .get("type")
             .and_then(|t| t.as_str())
@@ -235,6 +295,7 @@ impl CodeActionProvider for SqlCodeActionProvider {
         });
 
         let schema = self.get_schema();
+        let privacy = self.privacy.read().unwrap().clone();
         let state_weak = state.downgrade();
 
         // Spawn async task - do ALL state reading inside update_in
@@ -251,19 +312,26 @@ impl CodeActionProvider for SqlCodeActionProvider {
                     text.to_string()
                 };
 
+                // Each side is redacted on its own so the marker stays put
                 let sql_for_prompt = if action_type == ActionType::Complete {
-                    let before = text.slice(0..cursor).to_string();
-                    let after = text.slice(cursor..text_len).to_string();
+                    let before = privacy.redact_sql(&text.slice(0..cursor).to_string());
+                    let after = privacy.redact_sql(&text.slice(cursor..text_len).to_string());
                     format!("{}[CURSOR]{}", before, after)
                 } else {
-                    sql_content.clone()
+                    privacy.redact_sql(&sql_content)
                 };
 
-                (sql_for_prompt, cursor, text_len)
+                // Only the tables the query is about are described
+                let selection = schema.select(&sql_content);
+
+                (sql_for_prompt, selection, cursor, text_len)
             })?;
 
-            let (sql_for_prompt, cursor_offset, _text_len) = prompt_data;
-            let prompt = build_prompt(action_type, &sql_for_prompt, &schema);
+            let (sql_for_prompt, selection, cursor_offset, _text_len) = prompt_data;
+            let prompt = build_prompt(action_type, &sql_for_prompt, &selection.text);
+            let _ = cx.update_global::<EditorAgentContext, _>(|context, _win, _cx| {
+                context.selection = (selection.total_tables > 0).then_some(selection);
+            });
 
             // Call the AI
             let result = match agent
@@ -363,6 +431,85 @@ impl CodeActionProvider for SqlCodeActionProvider {
     }
 }
 
+/// The SELECT a refactoring applies to: the selection, or else the
+/// statement at the cursor. Queries with placeholders are left out.
+fn refactor_source(text: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let source = if range.start != range.end {
+        let selected = text.get(range.clone())?;
+        let start = range.start + selected.len() - selected.trim_start().len();
+        start..start + selected.trim().len()
+    } else {
+        // A cursor right after the `;` still belongs to the statement
+        statement_ranges(&DatabaseDriver::Postgres, text)
+            .into_iter()
+            .find(|r| r.start <= range.start && range.start <= r.end + 1)?
+    };
+    let sql = &text[source.clone()];
+    (is_select(sql) && parse_params(&DatabaseDriver::Postgres, sql).is_none()).then_some(source)
+}
+
+/// Generate `refactoring` of the statement at `source` and hand it to the
+/// editor for review.
+fn perform_refactoring(
+    state: Entity<InputState>,
+    refactoring: Refactoring,
+    source: Range<usize>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    let text = state.read(cx).text().to_string();
+    let Some(sql) = text.get(source.clone()).map(str::to_string) else {
+        return Task::ready(Ok(()));
+    };
+    // Below the statement and its terminator
+    let rest = &text[source.end..];
+    let insert_at = match rest.trim_start().strip_prefix(';') {
+        Some(after) => text.len() - after.len(),
+        None => source.end,
+    };
+    let window_id = window_id(window);
+    let db_manager = WindowConnection::of(window_id, cx).db_manager.clone();
+
+    window.spawn(cx, async move |cx| {
+        let generated = match refactoring {
+            Refactoring::View => create_view(&sql, "new_view"),
+            Refactoring::Function => {
+                let columns = output_columns(&db_manager, &sql).await;
+                create_function(&sql, "new_function", columns.as_deref())
+            }
+            Refactoring::Prepared => prepare_statement(&sql, "new_statement"),
+        };
+
+        let _ = cx.update(|_window, cx| {
+            EditorRefactorings::update(window_id, cx, |refactorings, _cx| {
+                refactorings.pending = Some(PendingRefactoring {
+                    title: refactoring.title().to_string(),
+                    sql: generated,
+                    insert_at,
+                });
+            })
+        });
+        Ok(())
+    })
+}
+
+/// Names and types of the columns `sql` returns, from running it for no
+/// rows. A subquery cannot modify data, so this only reads.
+async fn output_columns(db_manager: &DatabaseManager, sql: &str) -> Option<Vec<(String, String)>> {
+    let body = sql.trim().trim_end_matches(';');
+    let probe = format!("SELECT * FROM (\n{}\n) AS q LIMIT 0", body);
+    match db_manager.execute_query_enhanced(&probe).await {
+        QueryExecutionResult::Select(result) => Some(
+            result
+                .columns
+                .iter()
+                .map(|c| (c.name.clone(), c.type_name.to_ascii_lowercase()))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
 /// Strip markdown code fences from AI response
 fn strip_code_fences(text: &str) -> String {
     let trimmed = text.trim();
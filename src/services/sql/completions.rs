@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     sync::{
         Arc, RwLock,
         atomic::{AtomicBool, AtomicU64, Ordering},
@@ -15,26 +16,48 @@ use lsp_types::{
 };
 
 use crate::services::{
-    agent::Agent,
+    UserTypeInfo, UserTypeKind,
+    agent::{Agent, AgentPrivacy, SchemaContext},
     sql::completion_agent::{build_completion_agent, build_completion_prompt, get_completion},
+    sql::history_suggestions::history_suggestion,
+    sql::snippets::{Snippet, builtin_snippets, expand_snippet},
+    sql::value_context::compared_column,
 };
-use crate::{services::agent::InlineCompletionRequest, state::EditorInlineCompletions};
+use crate::{
+    services::agent::InlineCompletionRequest,
+    state::{EditorAgentContext, EditorInlineCompletions},
+};
+
+/// Past queries kept for history suggestions.
+pub const HISTORY_SUGGESTION_LIMIT: u32 = 500;
 
 /// Default debounce duration for inline completions.
 const DEFAULT_INLINE_COMPLETION_DEBOUNCE: Duration = Duration::from_millis(600);
 
-/// SQL completion provider that implements LSP-style completions
-/// with optional agent-powered inline completions
+/// SQL completion provider that implements LSP-style completions, inline
+/// suggestions from the query history and optional agent-powered inline
+/// completions
 #[derive(Clone)]
 pub struct SqlCompletionProvider {
     completions: Arc<RwLock<Vec<CompletionItem>>>,
+    /// Table names of the connected database, replaced on every reload.
+    schema_completions: Arc<RwLock<Vec<CompletionItem>>>,
+    /// Enum labels by lowercased name of the columns of that enum type.
+    enum_labels: Arc<RwLock<HashMap<String, Vec<(String, String)>>>>,
     agent: Option<Agent>,
-    schema: Arc<RwLock<Option<String>>>,
+    /// Indexed schema, of which the tables relevant to a request are sent.
+    schema: Arc<RwLock<SchemaContext>>,
     /// Counter for generating unique request IDs
     request_counter: Arc<AtomicU64>,
     /// Track the latest request ID to ignore stale responses
     latest_request_id: Arc<AtomicU64>,
     inline_completions_enabled: Arc<AtomicBool>,
+    /// The connection's successful queries, most recent first.
+    history_queries: Arc<RwLock<Vec<String>>>,
+    /// How literals are redacted before the agent sees the query.
+    privacy: Arc<RwLock<AgentPrivacy>>,
+    /// Snippets offered by prefix, the user's before the built-in ones.
+    snippets: Arc<RwLock<Vec<Snippet>>>,
 }
 
 impl SqlCompletionProvider {
@@ -47,17 +70,23 @@ impl SqlCompletionProvider {
 
         Self {
             agent,
-            schema: Arc::new(RwLock::new(None)),
+            schema: Arc::new(RwLock::new(SchemaContext::default())),
             completions: Arc::new(RwLock::new(completions)),
+            schema_completions: Arc::new(RwLock::new(vec![])),
+            enum_labels: Arc::new(RwLock::new(HashMap::new())),
             request_counter: Arc::new(AtomicU64::new(0)),
             latest_request_id: Arc::new(AtomicU64::new(0)),
             inline_completions_enabled: Arc::new(AtomicBool::new(false)),
+            history_queries: Arc::new(RwLock::new(vec![])),
+            privacy: Arc::new(RwLock::new(AgentPrivacy::default())),
+            snippets: Arc::new(RwLock::new(builtin_snippets())),
         }
     }
 
     fn get_completions(&self) -> Vec<CompletionItem> {
         let guard = self.completions.read().unwrap();
-        guard.clone()
+        let schema_guard = self.schema_completions.read().unwrap();
+        guard.iter().chain(schema_guard.iter()).cloned().collect()
     }
 
     pub fn toggle_inline_completions(&self, enabled: bool) {
@@ -70,22 +99,146 @@ impl SqlCompletionProvider {
         guard.clone()
     }
 
-    /// Adds schema-derived completions (table names, column names, etc.)
-    pub fn add_schema_completions(&self, completions: Vec<CompletionItem>) {
-        let mut guard = self.completions.write().unwrap();
-        guard.extend(completions);
+    /// Replaces the schema-derived completions (table names, column names,
+    /// etc.)
+    pub fn set_schema_completions(&self, completions: Vec<CompletionItem>) {
+        let mut guard = self.schema_completions.write().unwrap();
+        *guard = completions;
+    }
+
+    /// Replaces the enum labels offered inside string literals compared to
+    /// a column of an enum type.
+    pub fn set_enum_types(&self, user_types: &[UserTypeInfo]) {
+        let mut labels: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for user_type in user_types.iter().filter(|t| t.kind == UserTypeKind::Enum) {
+            for column in &user_type.column_names {
+                labels.entry(column.to_lowercase()).or_default().extend(
+                    user_type
+                        .labels
+                        .iter()
+                        .map(|label| (label.clone(), user_type.type_name.clone())),
+                );
+            }
+        }
+        let mut guard = self.enum_labels.write().unwrap();
+        *guard = labels;
+    }
+
+    /// Labels for a string literal opened at `offset`, the position right
+    /// after its quote. The closing quote is added unless the editor has
+    /// closed the literal already.
+    fn enum_label_completions(&self, rope: &Rope, offset: usize) -> Vec<CompletionItem> {
+        let before = rope.slice(0..offset.saturating_sub(1)).to_string();
+        let Some(column) = compared_column(&before) else {
+            return vec![];
+        };
+        let closed = offset < rope.len() && rope.slice(offset..offset + 1).to_string() == "'";
+        let guard = self.enum_labels.read().unwrap();
+        guard
+            .get(&column.to_lowercase())
+            .map(|labels| {
+                labels
+                    .iter()
+                    .map(|(label, type_name)| CompletionItem {
+                        label: label.clone(),
+                        kind: Some(lsp_types::CompletionItemKind::ENUM_MEMBER),
+                        detail: Some(type_name.clone()),
+                        insert_text: Some(if closed {
+                            label.clone()
+                        } else {
+                            format!("{}'", label)
+                        }),
+                        ..Default::default()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
-    pub fn add_schema(&self, schema: String) {
+    pub fn set_schema_context(&self, schema: SchemaContext) {
         let mut guard = self.schema.write().unwrap();
-        *guard = Some(schema);
+        *guard = schema;
     }
 
-    fn get_schema(&self) -> Option<String> {
+    fn get_schema(&self) -> SchemaContext {
         let guard = self.schema.read().unwrap();
         guard.clone()
     }
 
+    /// Redact what is sent for agent completions as `privacy` says.
+    pub fn set_privacy(&self, privacy: AgentPrivacy) {
+        *self.privacy.write().unwrap() = privacy;
+    }
+
+    /// Replace the queries suggested from history.
+    pub fn set_history_queries(&self, queries: Vec<String>) {
+        *self.history_queries.write().unwrap() = queries;
+    }
+
+    /// Replace the snippets offered.
+    pub fn set_snippets(&self, snippets: Vec<Snippet>) {
+        *self.snippets.write().unwrap() = snippets;
+    }
+
+    /// Snippets whose prefix starts with `typed`, the word before
+    /// `offset`, replacing it with their expansion. None after a `.`,
+    /// where a column is being named.
+    fn snippet_completions(&self, rope: &Rope, offset: usize, typed: &str) -> Vec<CompletionItem> {
+        let start = offset.saturating_sub(typed.len());
+        if start > 0 && rope.slice(start - 1..start).to_string() == "." {
+            return vec![];
+        }
+        let range = lsp_types::Range::new(
+            rope.offset_to_position(start),
+            rope.offset_to_position(offset),
+        );
+        self.snippets
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|snippet| snippet.prefix.starts_with(typed))
+            .map(|snippet| {
+                let text = expand_snippet(&snippet.body).text;
+                CompletionItem {
+                    label: snippet.prefix.clone(),
+                    kind: Some(lsp_types::CompletionItemKind::SNIPPET),
+                    detail: Some(snippet.description.clone()),
+                    documentation: Some(lsp_types::Documentation::String(text.clone())),
+                    text_edit: Some(CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+                        new_text: text,
+                        insert: range,
+                        replace: range,
+                    })),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Suggest `sql` first from now on, after it ran successfully.
+    pub fn remember_query(&self, sql: &str) {
+        let sql = sql.trim();
+        if sql.is_empty() {
+            return;
+        }
+        let mut queries = self.history_queries.write().unwrap();
+        queries.retain(|query| query != sql);
+        queries.insert(0, sql.to_string());
+        queries.truncate(HISTORY_SUGGESTION_LIMIT as usize);
+    }
+
+    /// The rest of a past query starting with what is typed before
+    /// `offset`, when nothing follows the cursor on its line.
+    pub fn history_completion(&self, rope: &Rope, offset: usize) -> Option<String> {
+        let point = rope.offset_to_point(offset);
+        let line_end = rope.line_end_offset(point.row);
+        if !rope.slice(offset..line_end).to_string().trim().is_empty() {
+            return None;
+        }
+        let before_cursor = rope.slice(0..offset).to_string();
+        history_suggestion(&self.history_queries.read().unwrap(), &before_cursor)
+    }
+
     /// Generate a new unique request ID
     fn next_request_id(&self) -> u64 {
         self.request_counter.fetch_add(1, Ordering::SeqCst)
@@ -129,6 +282,12 @@ impl CompletionProvider for SqlCompletionProvider {
             });
         }
 
+        // An opened string literal compared to an enum column
+        if trigger_character == "'" {
+            let items = self.enum_label_completions(rope, offset);
+            return Task::ready(Ok(CompletionResponse::Array(items)));
+        }
+
         // For regular completions, only trigger at word boundaries
         // offset points to after the trigger character, so we check offset - 2
         // to see what character was before the trigger
@@ -145,6 +304,7 @@ impl CompletionProvider for SqlCompletionProvider {
             }
         }
 
+        let snippets = self.snippet_completions(rope, offset, &trigger_character);
         let items = self.get_completions();
         cx.background_spawn(async move {
             let items = items
@@ -155,8 +315,8 @@ impl CompletionProvider for SqlCompletionProvider {
                     let mut item = item.clone();
                     item.insert_text = Some(item.label.replace(&trigger_character, ""));
                     item
-                })
-                .collect::<Vec<_>>();
+                });
+            let items = snippets.into_iter().chain(items).collect::<Vec<_>>();
 
             Ok(CompletionResponse::Array(items))
         })
@@ -175,6 +335,10 @@ impl CompletionProvider for SqlCompletionProvider {
         _window: &mut Window,
         cx: &mut Context<InputState>,
     ) -> Task<Result<InlineCompletionResponse>> {
+        // Past queries first: instant, and nothing leaves the machine
+        if let Some(suggestion) = self.history_completion(rope, offset) {
+            return Task::ready(Ok(suggestion_response(suggestion)));
+        }
         if !self.get_inline_completions_enabled() {
             return Task::ready(Ok(InlineCompletionResponse::Array(vec![])));
         }
@@ -191,45 +355,53 @@ impl CompletionProvider for SqlCompletionProvider {
         let _latest_request_id = self.latest_request_id.clone();
 
         let mut agent = self.agent.clone().unwrap();
-        let schema = self.get_schema().clone();
+        let schema = self.get_schema();
+        let privacy = self.privacy.read().unwrap().clone();
 
         let task = cx.spawn(async move |_this, cx| {
-            let res = cx
+            let (response, selection) = cx
                 .background_spawn(async move {
                     let point = rope.offset_to_point(offset);
                     let line_start = rope.line_start_offset(point.row);
                     let line_end = rope.line_end_offset(point.row);
                     let _current_line = rope.slice(line_start..offset).to_string();
 
-                    let prefix = rope.slice(line_start..offset).to_string();
-                    let suffix = rope.slice(offset..line_end).to_string();
+                    let prefix = privacy.redact_sql(&rope.slice(line_start..offset).to_string());
+                    let suffix = privacy.redact_sql(&rope.slice(offset..line_end).to_string());
 
                     // Include up to 10 previous lines as context
+                    let ctx_start = rope.line_start_offset(point.row.saturating_sub(10));
                     let context = (point.row > 0).then(|| {
-                        let ctx_start = rope.line_start_offset(point.row.saturating_sub(10));
-                        rope.slice(ctx_start..line_start).to_string()
+                        privacy.redact_sql(&rope.slice(ctx_start..line_start).to_string())
                     });
 
+                    // Only the tables these lines are about are described
+                    let selection = schema.select(&rope.slice(ctx_start..line_end).to_string());
+
                     let request = InlineCompletionRequest {
                         request_id,
                         prefix: prefix,
                         suffix: suffix,
                         context: context,
                     };
-                    let prompt = build_completion_prompt(&request, &schema);
+                    let prompt = build_completion_prompt(&request, &selection.text);
                     let suggestion = get_completion(&mut agent, prompt).await;
 
-                    Ok(suggestion
+                    let response = suggestion
                         .map(suggestion_response)
-                        .unwrap_or_else(empty_response))
+                        .unwrap_or_else(empty_response);
+                    (response, selection)
                 })
                 .await;
 
             let _ = cx.update_global::<EditorInlineCompletions, _>(|eic, _cx| {
                 eic.loading = false;
             });
+            let _ = cx.update_global::<EditorAgentContext, _>(|context, _cx| {
+                context.selection = (selection.total_tables > 0).then_some(selection);
+            });
 
-            res
+            Ok(response)
         });
 
         task
@@ -245,8 +417,9 @@ impl CompletionProvider for SqlCompletionProvider {
             return false;
         };
 
-        // Only trigger for word-starting characters or slash commands
-        ch.is_ascii_alphabetic() || ch == '_' || ch == '/'
+        // Only trigger for word-starting characters, slash commands or an
+        // opening quote (enum labels)
+        ch.is_ascii_alphabetic() || ch == '_' || ch == '/' || ch == '\''
     }
 }
 
@@ -287,3 +460,50 @@ fn completion_item(range: &lsp_types::Range, label: &str, text: &str, doc: &str)
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> SqlCompletionProvider {
+        let provider = SqlCompletionProvider::new();
+        provider.set_enum_types(&[UserTypeInfo {
+            type_schema: "public".to_string(),
+            type_name: "order_status".to_string(),
+            kind: UserTypeKind::Enum,
+            labels: vec!["draft".to_string(), "paid".to_string()],
+            definition: None,
+            column_names: vec!["status".to_string()],
+        }]);
+        provider
+    }
+
+    /// The text inserted for each label, typing after the first quote.
+    fn inserted(provider: &SqlCompletionProvider, sql: &str) -> Vec<String> {
+        let offset = sql.find('\'').unwrap() + 1;
+        provider
+            .enum_label_completions(&Rope::from(sql), offset)
+            .into_iter()
+            .filter_map(|item| item.insert_text)
+            .collect()
+    }
+
+    #[test]
+    fn closes_enum_labels_unless_already_closed() {
+        let provider = provider();
+        assert_eq!(
+            inserted(&provider, "SELECT * FROM orders WHERE status = '"),
+            vec!["draft'", "paid'"]
+        );
+        // The editor closed the quote as it was typed
+        assert_eq!(
+            inserted(&provider, "SELECT * FROM orders WHERE status = ''"),
+            vec!["draft", "paid"]
+        );
+        assert_eq!(
+            inserted(&provider, "SELECT * FROM orders WHERE status = ''\nLIMIT 1"),
+            vec!["draft", "paid"]
+        );
+        assert!(inserted(&provider, "SELECT * FROM orders WHERE note = ''").is_empty());
+    }
+}
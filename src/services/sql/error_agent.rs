@@ -0,0 +1,111 @@
+//! Agent-powered explanations of failed queries.
+//!
+//! The failed SQL, the database's error and the tables the two mention are
+//! sent to the model, which answers with what went wrong and, when it can,
+//! a corrected query that can be applied to the editor.
+
+use anyhow::{Result, anyhow};
+
+use crate::services::agent::{Agent, AgentResponse, ContentBlock};
+
+/// System prompt for error explanations
+const ERROR_SYSTEM_PROMPT: &str = r#"You are a SQL assistant. A query the user ran failed.
+
+RULES:
+1. Explain the cause of the error in one or two short paragraphs of plain English
+2. Use the provided schema to suggest correct table and column names
+3. If the query can be fixed, end with the complete corrected query in one ```sql code block
+4. Keep the user's style (uppercase/lowercase keywords, indentation) and change nothing else
+"#;
+
+/// What the model made of a failed query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorExplanation {
+    /// Markdown explanation, without the corrected query.
+    pub explanation: String,
+    /// The corrected query, when the model offered one.
+    pub fix: Option<String>,
+}
+
+/// Ask the model why `sql` failed with `error`, describing `schema`.
+pub async fn explain_error(
+    sql: &str,
+    error: &str,
+    schema: Option<&str>,
+) -> Result<ErrorExplanation> {
+    let mut agent = Agent::builder()
+        .system_prompt(ERROR_SYSTEM_PROMPT.to_string())
+        .model("claude-haiku-4-5-20251001".to_string())
+        .max_tokens(2048)
+        .build(vec![])?;
+
+    let mut prompt = format!("Query:\n{}\n\nError:\n{}", sql, error);
+    if let Some(schema) = schema {
+        prompt.push_str(&format!("\n\nDatabase schema:\n{}", schema));
+    }
+
+    match agent
+        .chat_step(vec![ContentBlock::Text { text: prompt }])
+        .await?
+    {
+        AgentResponse::TextResponse { text, .. } => Ok(parse_explanation(&text)),
+        AgentResponse::Error(e) => Err(anyhow!(e)),
+        AgentResponse::ToolCallRequest { .. } => Err(anyhow!("Unexpected tool call")),
+    }
+}
+
+/// Split a response into the explanation and the last ```sql block.
+fn parse_explanation(text: &str) -> ErrorExplanation {
+    let fence = text.rfind("```sql").and_then(|start| {
+        let body = start + "```sql".len();
+        let end = body + text[body..].find("```")?;
+        Some((start, body, end))
+    });
+
+    match fence {
+        Some((start, body, end)) => {
+            let fix = text[body..end].trim();
+            let explanation = format!("{}{}", &text[..start], &text[end + 3..]);
+            ErrorExplanation {
+                explanation: explanation.trim().to_string(),
+                fix: (!fix.is_empty()).then(|| fix.to_string()),
+            }
+        }
+        None => ErrorExplanation {
+            explanation: text.trim().to_string(),
+            fix: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_the_fix_from_the_explanation() {
+        let text =
+            "The column is called `email`, not `mail`.\n\n```sql\nSELECT email FROM users;\n```\n";
+        assert_eq!(
+            parse_explanation(text),
+            ErrorExplanation {
+                explanation: "The column is called `email`, not `mail`.".to_string(),
+                fix: Some("SELECT email FROM users;".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_explanations_without_a_fix() {
+        let text = "  The server closed the connection.\n";
+        assert_eq!(
+            parse_explanation(text),
+            ErrorExplanation {
+                explanation: "The server closed the connection.".to_string(),
+                fix: None,
+            }
+        );
+        // An unterminated block is not a fix
+        assert_eq!(parse_explanation("Try:\n```sql\nSELECT 1").fix, None);
+    }
+}
@@ -0,0 +1,110 @@
+//! `.sql` files opened in the editor: titles, recent files and paths
+//! passed on the command line.
+
+use std::path::{Path, PathBuf};
+
+/// Recent files remembered for "Open Recent".
+pub const MAX_RECENT_FILES: usize = 10;
+
+/// Move `path` to the front of `recent`, dropping duplicates and the
+/// oldest entries beyond [`MAX_RECENT_FILES`].
+pub fn push_recent(recent: &mut Vec<PathBuf>, path: PathBuf) {
+    recent.retain(|p| p != &path);
+    recent.insert(0, path);
+    recent.truncate(MAX_RECENT_FILES);
+}
+
+/// Title shown for the editor contents, with a `•` when there are unsaved
+/// changes.
+pub fn file_title(path: Option<&Path>, dirty: bool) -> String {
+    let name = path
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Untitled".to_string());
+    if dirty { format!("{} •", name) } else { name }
+}
+
+/// `path` with a `.sql` extension added when it has none.
+pub fn with_sql_extension(path: PathBuf) -> PathBuf {
+    if path.extension().is_some() {
+        path
+    } else {
+        path.with_extension("sql")
+    }
+}
+
+/// `.sql` files among command line arguments, as passed by the desktop
+/// when the app is the handler for the file type.
+pub fn sql_file_args(args: impl IntoIterator<Item = String>) -> Vec<PathBuf> {
+    args.into_iter()
+        .filter(|arg| !arg.starts_with('-'))
+        .map(PathBuf::from)
+        .filter(|path| is_sql_file(path))
+        .collect()
+}
+
+/// Path of a `file://` URL the OS asked us to open, if it is a `.sql` file.
+pub fn sql_file_from_url(url: &str) -> Option<PathBuf> {
+    let path = url::Url::parse(url).ok()?.to_file_path().ok()?;
+    is_sql_file(&path).then_some(path)
+}
+
+fn is_sql_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("sql"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_files_are_most_recent_first_without_duplicates() {
+        let mut recent = vec![PathBuf::from("a.sql"), PathBuf::from("b.sql")];
+        push_recent(&mut recent, PathBuf::from("b.sql"));
+        assert_eq!(recent, vec![PathBuf::from("b.sql"), PathBuf::from("a.sql")]);
+
+        for i in 0..MAX_RECENT_FILES {
+            push_recent(&mut recent, PathBuf::from(format!("{}.sql", i)));
+        }
+        assert_eq!(recent.len(), MAX_RECENT_FILES);
+        assert_eq!(recent[0], PathBuf::from("9.sql"));
+        assert!(!recent.contains(&PathBuf::from("a.sql")));
+    }
+
+    #[test]
+    fn title_marks_unsaved_changes() {
+        let path = PathBuf::from("/tmp/reports/orders.sql");
+        assert_eq!(file_title(Some(&path), false), "orders.sql");
+        assert_eq!(file_title(Some(&path), true), "orders.sql •");
+        assert_eq!(file_title(None, true), "Untitled •");
+    }
+
+    #[test]
+    fn save_as_adds_missing_extension() {
+        assert_eq!(
+            with_sql_extension(PathBuf::from("report")),
+            PathBuf::from("report.sql")
+        );
+        assert_eq!(
+            with_sql_extension(PathBuf::from("report.txt")),
+            PathBuf::from("report.txt")
+        );
+    }
+
+    #[test]
+    fn picks_sql_files_from_arguments_and_urls() {
+        let args = ["--debug", "/tmp/a.sql", "/tmp/b.txt", "/tmp/C.SQL"].map(String::from);
+        assert_eq!(
+            sql_file_args(args),
+            vec![PathBuf::from("/tmp/a.sql"), PathBuf::from("/tmp/C.SQL")]
+        );
+
+        assert_eq!(
+            sql_file_from_url("file:///tmp/orders.sql"),
+            Some(PathBuf::from("/tmp/orders.sql"))
+        );
+        assert_eq!(sql_file_from_url("file:///tmp/orders.csv"), None);
+        assert_eq!(sql_file_from_url("https://example.com/a.sql"), None);
+    }
+}
@@ -0,0 +1,57 @@
+//! Editor highlighting for Postgres scripts.
+//!
+//! The built-in SQL language shows a dollar-quoted body (`DO $$ ... $$`,
+//! a PL/pgSQL function) as one long string. The `pgsql` language uses the
+//! same grammar and highlights, and injects SQL into those bodies so their
+//! statements and PL/pgSQL keywords are highlighted too.
+
+use std::sync::OnceLock;
+
+use gpui_component::highlighter::{LanguageConfig, LanguageRegistry};
+
+const PGSQL_LANGUAGE: &str = "pgsql";
+
+/// Ways a dollar-quoted body can appear in the grammar's tree. Patterns
+/// naming nodes the grammar does not have are left out.
+const BODY_INJECTIONS: &[&str] = &[
+    r#"((dollar_quoted_string) @injection.content
+  (#set! injection.language "sql"))"#,
+    r#"((literal) @injection.content
+  (#match? @injection.content "^\$[A-Za-z0-9_]*\$")
+  (#set! injection.language "sql"))"#,
+];
+
+/// The language for SQL editors: `pgsql` once registered, or the built-in
+/// `sql` when the grammar offers nothing to inject into.
+pub fn sql_language() -> &'static str {
+    static LANGUAGE: OnceLock<&'static str> = OnceLock::new();
+    LANGUAGE.get_or_init(register_pgsql)
+}
+
+fn register_pgsql() -> &'static str {
+    let registry = LanguageRegistry::singleton();
+    let Some(sql) = registry.language("sql") else {
+        return "sql";
+    };
+
+    let injections: Vec<&str> = BODY_INJECTIONS
+        .iter()
+        .copied()
+        .filter(|pattern| tree_sitter::Query::new(&sql.language, pattern).is_ok())
+        .collect();
+    if injections.is_empty() {
+        tracing::warn!("No dollar-quoted bodies to highlight in the SQL grammar");
+        return "sql";
+    }
+
+    let config = LanguageConfig::new(
+        PGSQL_LANGUAGE,
+        sql.language.clone(),
+        vec!["sql".into()],
+        &sql.highlights,
+        &injections.join("\n"),
+        &sql.locals,
+    );
+    registry.register(PGSQL_LANGUAGE, &config);
+    PGSQL_LANGUAGE
+}
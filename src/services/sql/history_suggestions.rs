@@ -0,0 +1,90 @@
+//! Ghost-text suggestions from the query history.
+//!
+//! Typing the start of a query that ran successfully before on the same
+//! connection suggests the rest of it. Suggestions come from the local
+//! history database only, so they work offline and nothing typed leaves
+//! the machine.
+
+use crate::services::storage::DatabaseDriver;
+
+use super::statements::statement_ranges;
+
+/// Typed characters needed before history is searched.
+const MIN_TYPED_CHARS: usize = 3;
+
+/// The rest of the first query in `history` (most recent first) that
+/// starts with the statement being typed at the end of `before_cursor`,
+/// ignoring ASCII case.
+pub fn history_suggestion(history: &[String], before_cursor: &str) -> Option<String> {
+    let typed = before_cursor[current_statement_start(before_cursor)..].trim_start();
+    if typed.trim_end().chars().count() < MIN_TYPED_CHARS {
+        return None;
+    }
+
+    history.iter().find_map(|sql| {
+        let head = sql.get(..typed.len())?;
+        let rest = sql[typed.len()..].trim_end();
+        (head.eq_ignore_ascii_case(typed) && !rest.trim().is_empty()).then(|| rest.to_string())
+    })
+}
+
+/// Where the statement running up to the end of `sql` starts, or the end
+/// of `sql` when the last statement is already closed.
+fn current_statement_start(sql: &str) -> usize {
+    match statement_ranges(&DatabaseDriver::Postgres, sql).last() {
+        Some(range) if sql[range.end..].trim().is_empty() => range.start,
+        _ => sql.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> Vec<String> {
+        vec![
+            "select count(*) from orders".to_string(),
+            "SELECT * FROM orders WHERE status = 'open'".to_string(),
+            "SELECT * FROM customers".to_string(),
+        ]
+    }
+
+    #[test]
+    fn completes_the_most_recent_match_ignoring_case() {
+        assert_eq!(
+            history_suggestion(&history(), "SELECT * FROM "),
+            Some("orders WHERE status = 'open'".to_string())
+        );
+        assert_eq!(
+            history_suggestion(&history(), "SELECT COUNT"),
+            Some("(*) from orders".to_string())
+        );
+        assert_eq!(
+            history_suggestion(&history(), "SELECT * FROM c"),
+            Some("ustomers".to_string())
+        );
+    }
+
+    #[test]
+    fn only_completes_the_statement_being_typed() {
+        assert_eq!(
+            history_suggestion(&history(), "SELECT 1;\n\nSELECT * FROM cu"),
+            Some("stomers".to_string())
+        );
+        assert_eq!(
+            history_suggestion(&history(), "SELECT * FROM customers;"),
+            None
+        );
+        assert_eq!(history_suggestion(&history(), "-- SELECT * FROM"), None);
+    }
+
+    #[test]
+    fn needs_a_few_characters_and_something_left_to_add() {
+        assert_eq!(history_suggestion(&history(), "se"), None);
+        assert_eq!(
+            history_suggestion(&history(), "SELECT * FROM customers"),
+            None
+        );
+        assert_eq!(history_suggestion(&[], "SELECT"), None);
+    }
+}
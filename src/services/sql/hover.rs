@@ -0,0 +1,519 @@
+//! Hover documentation for the editor: the signature and a short doc of
+//! built-in functions and keywords, from bundled catalogs, and the
+//! comments of tables and columns in the schema cache. The function
+//! catalog is Postgres's, so MySQL connections get no function hovers.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::Result;
+use gpui::{App, Task, Window};
+use gpui_component::input::{HoverProvider, Rope, RopeExt};
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
+use serde::Deserialize;
+
+use super::pairs::in_literal;
+use crate::services::{ColumnDetail, DatabaseDriver, TableSchema};
+use crate::state::{EditorState, WindowConnection, WindowScoped, window_id};
+
+/// Columns listed when hovering a table.
+const HOVER_COLUMNS: usize = 12;
+
+/// A built-in Postgres function of the bundled catalog.
+#[derive(Debug, Clone, Deserialize)]
+struct FunctionDoc {
+    name: String,
+    /// One line per form, e.g. `count(*) → bigint`.
+    signature: String,
+    doc: String,
+}
+
+/// A keyword or phrase, as in the keyword completions.
+#[derive(Debug, Clone, Deserialize)]
+struct KeywordDoc {
+    label: String,
+    detail: Option<String>,
+    documentation: Option<String>,
+}
+
+/// The bundled function and keyword catalogs.
+pub struct SqlDocs {
+    functions: Vec<FunctionDoc>,
+    keywords: Vec<KeywordDoc>,
+}
+
+/// The identifier under the cursor.
+struct HoveredWord<'a> {
+    range: Range<usize>,
+    name: &'a str,
+    /// What precedes it and a `.`: a schema, table or alias.
+    qualifier: Option<&'a str>,
+    /// Followed by `(`.
+    called: bool,
+    /// Inside `"..."`, or backquotes for MySQL.
+    quoted: bool,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Where the word ending at `end` starts.
+fn word_start(sql: &str, end: usize) -> Option<usize> {
+    sql[..end]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map(|(i, _)| i)
+}
+
+fn hovered_word(sql: &str, offset: usize) -> Option<HoveredWord<'_>> {
+    let end = offset
+        + sql
+            .get(offset..)?
+            .chars()
+            .take_while(|c| is_word(*c))
+            .map(char::len_utf8)
+            .sum::<usize>();
+    if end == offset {
+        return None;
+    }
+    let start = word_start(sql, end)?;
+    let name = &sql[start..end];
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let quote = sql[..start]
+        .chars()
+        .next_back()
+        .filter(|c| *c == '"' || *c == '`');
+    let quoted = quote.is_some_and(|quote| sql[end..].starts_with(quote));
+    let (before, after) = match quoted {
+        true => (start - 1, end + 1),
+        false => (start, end),
+    };
+    let qualifier = sql[..before].strip_suffix('.').and_then(|head| {
+        let bare = head.trim_end_matches(['"', '`']);
+        let start = word_start(bare, bare.len())?;
+        Some(&bare[start..])
+    });
+    Some(HoveredWord {
+        range: start..end,
+        name,
+        qualifier,
+        called: sql[after..].trim_start().starts_with('('),
+        quoted,
+    })
+}
+
+/// The words of `sql`, with qualified names kept whole and quotes dropped,
+/// for finding the tables a statement uses and their aliases.
+fn name_tokens(sql: &str) -> Vec<&str> {
+    sql.split(|c: char| !is_word(c) && c != '.' && c != '"' && c != '`')
+        .map(|token| token.trim_matches(['"', '`']))
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// Whether `table` is called `name`, which may be schema-qualified.
+fn is_named(table: &TableSchema, name: &str) -> bool {
+    match name.rsplit_once('.') {
+        Some((schema, name)) => {
+            table.table_schema.eq_ignore_ascii_case(schema)
+                && table.table_name.eq_ignore_ascii_case(name)
+        }
+        None => table.table_name.eq_ignore_ascii_case(name),
+    }
+}
+
+fn table_named<'a>(tables: &'a [TableSchema], name: &str) -> Option<&'a TableSchema> {
+    tables.iter().find(|table| is_named(table, name))
+}
+
+/// The table `qualifier` stands for in `sql`: a table of that name, else
+/// the one it is an alias of, as in `FROM users u` or `JOIN users AS u`.
+fn qualified_table<'a>(
+    tables: &'a [TableSchema],
+    sql: &str,
+    qualifier: &str,
+) -> Option<&'a TableSchema> {
+    table_named(tables, qualifier).or_else(|| {
+        let tokens = name_tokens(sql);
+        tokens.iter().enumerate().find_map(|(ix, token)| {
+            if ix == 0 || !token.eq_ignore_ascii_case(qualifier) {
+                return None;
+            }
+            let mut table = tokens[ix - 1];
+            if table.eq_ignore_ascii_case("AS") && ix > 1 {
+                table = tokens[ix - 2];
+            }
+            table_named(tables, table)
+        })
+    })
+}
+
+/// A comment, or a note that there is none.
+fn push_comment(doc: &mut String, comment: Option<&str>) {
+    match comment.map(str::trim).filter(|comment| !comment.is_empty()) {
+        Some(comment) => doc.push_str(&format!("\n\n{}", comment)),
+        None => doc.push_str("\n\n_No comment_"),
+    }
+}
+
+fn table_doc(table: &TableSchema) -> String {
+    let kind = match table.table_type.as_str() {
+        "BASE TABLE" => "TABLE",
+        kind => kind,
+    };
+    let mut doc = format!(
+        "```sql\n{} {}.{}\n```",
+        kind, table.table_schema, table.table_name
+    );
+    push_comment(&mut doc, table.description.as_deref());
+    doc.push('\n');
+    for column in table.columns.iter().take(HOVER_COLUMNS) {
+        doc.push_str(&format!(
+            "\n- `{}` {}",
+            column.column_name, column.data_type
+        ));
+    }
+    if table.columns.len() > HOVER_COLUMNS {
+        doc.push_str(&format!(
+            "\n- … {} more",
+            table.columns.len() - HOVER_COLUMNS
+        ));
+    }
+    doc
+}
+
+fn column_doc(table: &TableSchema, column: &ColumnDetail) -> String {
+    let mut signature = format!(
+        "{}.{} {}",
+        table.table_name, column.column_name, column.data_type
+    );
+    if table.primary_keys.contains(&column.column_name) {
+        signature.push_str(" PRIMARY KEY");
+    } else if !column.is_nullable {
+        signature.push_str(" NOT NULL");
+    }
+    let mut doc = format!("```sql\n{}\n```", signature);
+    push_comment(&mut doc, column.description.as_deref());
+    doc
+}
+
+impl SqlDocs {
+    pub fn bundled() -> Self {
+        Self {
+            functions: serde_json::from_slice(include_bytes!("./functions.json")).unwrap(),
+            keywords: serde_json::from_slice(include_bytes!("./completions.json")).unwrap(),
+        }
+    }
+
+    /// The catalog entry of `name`, for Postgres only: MySQL functions of
+    /// the same name often take other arguments or are missing.
+    fn function_doc(&self, driver: &DatabaseDriver, name: &str) -> Option<String> {
+        if *driver != DatabaseDriver::Postgres {
+            return None;
+        }
+        let function = self
+            .functions
+            .iter()
+            .find(|function| function.name.eq_ignore_ascii_case(name))?;
+        Some(format!(
+            "```sql\n{}\n```\n\n{}",
+            function.signature, function.doc
+        ))
+    }
+
+    /// The longest keyword phrase of the catalog the word at `range` is
+    /// part of, as `BY` in `GROUP BY`.
+    fn keyword_doc(&self, sql: &str, range: &Range<usize>) -> Option<String> {
+        let mut words: Vec<(usize, &str)> = vec![];
+        let mut start = None;
+        for (i, c) in sql.char_indices().chain([(sql.len(), ' ')]) {
+            if is_word(c) {
+                start.get_or_insert(i);
+            } else if let Some(start) = start.take() {
+                words.push((start, &sql[start..i]));
+            }
+        }
+        let at = words.iter().position(|(start, _)| *start == range.start)?;
+
+        let keyword = self
+            .keywords
+            .iter()
+            .filter(|keyword| {
+                let phrase: Vec<&str> = keyword.label.split_whitespace().collect();
+                (0..phrase.len()).any(|ix| {
+                    at.checked_sub(ix)
+                        .and_then(|first| words.get(first..first + phrase.len()))
+                        .is_some_and(|found| {
+                            found
+                                .iter()
+                                .zip(&phrase)
+                                .all(|((_, word), part)| word.eq_ignore_ascii_case(part))
+                        })
+                })
+            })
+            .max_by_key(|keyword| keyword.label.split_whitespace().count())?;
+
+        let mut doc = format!(
+            "```sql\n{}\n```",
+            keyword.detail.as_deref().unwrap_or(&keyword.label)
+        );
+        if let Some(documentation) = &keyword.documentation {
+            doc.push_str(&format!("\n\n{}", documentation));
+        }
+        Some(doc)
+    }
+
+    /// Markdown describing what is at `offset` of `sql`, and the range of
+    /// the word it describes. Names of `tables` come first, then keywords
+    /// and functions, functions first when called; nothing inside strings
+    /// or comments.
+    pub fn hover(
+        &self,
+        driver: &DatabaseDriver,
+        tables: &[TableSchema],
+        sql: &str,
+        offset: usize,
+    ) -> Option<(Range<usize>, String)> {
+        let word = hovered_word(sql, offset)?;
+        if !word.quoted && in_literal(driver, sql, word.range.start) {
+            return None;
+        }
+
+        let doc = match word.qualifier {
+            Some(qualifier) => {
+                let schema_table = tables.iter().find(|table| {
+                    table.table_schema.eq_ignore_ascii_case(qualifier)
+                        && table.table_name.eq_ignore_ascii_case(word.name)
+                });
+                match schema_table {
+                    Some(table) => Some(table_doc(table)),
+                    None => qualified_table(tables, sql, qualifier).and_then(|table| {
+                        let column = table
+                            .columns
+                            .iter()
+                            .find(|column| column.column_name.eq_ignore_ascii_case(word.name))?;
+                        Some(column_doc(table, column))
+                    }),
+                }
+                .or_else(|| {
+                    word.called
+                        .then(|| self.function_doc(driver, word.name))
+                        .flatten()
+                })
+            }
+            None if word.called => self.function_doc(driver, word.name),
+            None => None,
+        };
+        if word.qualifier.is_some() || doc.is_some() {
+            return doc.map(|doc| (word.range, doc));
+        }
+
+        // Tables, then columns of the tables the script names
+        let doc = table_named(tables, word.name)
+            .map(table_doc)
+            .or_else(|| {
+                let tokens = name_tokens(sql);
+                let columns = tables
+                    .iter()
+                    .filter(|table| tokens.iter().any(|token| is_named(table, token)))
+                    .flat_map(|table| {
+                        table
+                            .columns
+                            .iter()
+                            .filter(|column| column.column_name.eq_ignore_ascii_case(word.name))
+                            .map(move |column| column_doc(table, column))
+                    })
+                    .collect::<Vec<_>>();
+                (!columns.is_empty()).then(|| columns.join("\n\n---\n\n"))
+            })
+            .or_else(|| self.keyword_doc(sql, &word.range))
+            .or_else(|| self.function_doc(driver, word.name))?;
+        Some((word.range, doc))
+    }
+}
+
+/// Hover cards for the SQL editor.
+#[derive(Clone)]
+pub struct SqlHoverProvider {
+    docs: Arc<SqlDocs>,
+}
+
+impl SqlHoverProvider {
+    pub fn new() -> Self {
+        Self {
+            docs: Arc::new(SqlDocs::bundled()),
+        }
+    }
+}
+
+impl HoverProvider for SqlHoverProvider {
+    fn hover(
+        &self,
+        text: &Rope,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Option<Hover>>> {
+        let window = window_id(window);
+        let driver = WindowConnection::of(window, cx)
+            .active_connection
+            .as_ref()
+            .map_or(DatabaseDriver::Postgres, |connection| connection.driver);
+        let tables = EditorState::of(window, cx)
+            .schema
+            .as_ref()
+            .map_or(&[][..], |schema| &schema.tables);
+
+        let hover = self
+            .docs
+            .hover(&driver, tables, &text.to_string(), offset)
+            .map(|(range, doc)| Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: doc,
+                }),
+                range: Some(lsp_types::Range::new(
+                    text.offset_to_position(range.start),
+                    text.offset_to_position(range.end),
+                )),
+            });
+        Task::ready(Ok(hover))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PG: DatabaseDriver = DatabaseDriver::Postgres;
+
+    fn column(name: &str, description: Option<&str>) -> ColumnDetail {
+        ColumnDetail {
+            column_name: name.to_string(),
+            data_type: "text".to_string(),
+            is_nullable: true,
+            column_default: None,
+            ordinal_position: 1,
+            character_maximum_length: None,
+            numeric_precision: None,
+            numeric_scale: None,
+            full_type: None,
+            collation: None,
+            extra: None,
+            description: description.map(str::to_string),
+        }
+    }
+
+    fn tables() -> Vec<TableSchema> {
+        vec![TableSchema {
+            table_name: "users".to_string(),
+            table_schema: "public".to_string(),
+            table_type: "BASE TABLE".to_string(),
+            columns: vec![
+                column("id", None),
+                column("email", Some("Where receipts are sent")),
+            ],
+            primary_keys: vec!["id".to_string()],
+            foreign_keys: vec![],
+            indexes: vec![],
+            constraints: vec![],
+            triggers: vec![],
+            description: Some("People who can sign in".to_string()),
+        }]
+    }
+
+    /// The hover at the `|` in `sql` on `driver`.
+    fn hover_on(driver: &DatabaseDriver, sql: &str) -> Option<String> {
+        let offset = sql.find('|').unwrap();
+        let sql = sql.replace('|', "");
+        SqlDocs::bundled()
+            .hover(driver, &tables(), &sql, offset)
+            .map(|(_, doc)| doc)
+    }
+
+    /// The hover at the `|` in `sql` on Postgres.
+    fn hover(sql: &str) -> Option<String> {
+        hover_on(&PG, sql)
+    }
+
+    #[test]
+    fn documents_functions_and_keywords() {
+        let doc = hover("SELECT |coalesce(a, b)").unwrap();
+        assert!(doc.starts_with("```sql\ncoalesce(value [, ...])"));
+        assert!(
+            hover("SELECT date_tr|unc ('day', now())")
+                .unwrap()
+                .contains("date_trunc(")
+        );
+
+        let doc = hover("SELECT 1 GROUP |BY 1").unwrap();
+        assert!(doc.contains("Group"), "{}", doc);
+        assert!(
+            hover("FROM t |LEFT JOIN u")
+                .unwrap()
+                .contains("LEFT JOIN table")
+        );
+        assert!(
+            hover("SELECT |left(name, 1)")
+                .unwrap()
+                .contains("left(string text")
+        );
+        assert!(
+            hover("SELECT 1 |FROM t")
+                .unwrap()
+                .contains("FROM table_name")
+        );
+    }
+
+    #[test]
+    fn documents_tables_and_columns_with_their_comments() {
+        let doc = hover("SELECT * FROM public.us|ers").unwrap();
+        assert!(doc.contains("TABLE public.users"));
+        assert!(doc.contains("People who can sign in"));
+        assert!(doc.contains("- `email` text"));
+
+        let doc = hover("SELECT u.|email FROM users AS u").unwrap();
+        assert!(doc.contains("users.email text"));
+        assert!(doc.contains("Where receipts are sent"));
+        assert!(
+            hover("SELECT |id FROM users")
+                .unwrap()
+                .contains("PRIMARY KEY")
+        );
+        assert!(hover("SELECT \"em|ail\" FROM users").is_some());
+        assert!(
+            hover("SELECT |id FROM orders").is_none(),
+            "columns of tables the script does not use"
+        );
+    }
+
+    #[test]
+    fn documents_no_functions_on_mysql() {
+        let mysql = DatabaseDriver::MySql;
+        assert!(hover("SELECT |generate_series(1, 3)").is_some());
+        assert!(hover_on(&mysql, "SELECT |generate_series(1, 3)").is_none());
+        assert!(hover_on(&mysql, "SELECT |jsonb_set(doc, '{a}', '1')").is_none());
+
+        // Keywords, tables and columns are the same on both
+        assert!(
+            hover_on(&mysql, "SELECT 1 |FROM t")
+                .unwrap()
+                .contains("FROM table_name")
+        );
+        let doc = hover_on(&mysql, "SELECT `em|ail` FROM users").unwrap();
+        assert!(doc.contains("Where receipts are sent"));
+    }
+
+    #[test]
+    fn ignores_strings_and_comments() {
+        assert!(hover("SELECT 'co|unt(*)'").is_none());
+        assert!(hover("-- |count rows\nSELECT 1").is_none());
+        assert!(hover("SELECT |1").is_none());
+    }
+}
@@ -2,15 +2,66 @@
 //!
 //! This module provides:
 //! - `analyzer` - SQL query detection and parsing with tree-sitter
+//! - `params` - Bind placeholder detection (`$1`, `:name`, `?`)
+//! - `statements` - Splitting scripts into statements, skipping psql meta-commands
+//! - `variables` - `{{name}}` template variables and their environments
+//! - `change_script` - DDL recorded into a migration, with derived rollbacks
+//! - `highlight` - The `pgsql` editor language, highlighting dollar-quoted bodies
+//! - `value_context` - The column a string literal is compared to
+//! - `paste` - Pasted lists of values turned into `IN (...)` items
+//! - `pairs` - Auto-closing brackets and quotes, bracket matching and indenting inside parentheses
+//! - `files` - `.sql` files opened in the editor and recent files
 //! - `completions` - LSP-style completion provider for SQL
+//! - `hover` - Hover documentation for functions, keywords, tables and columns
+//! - `history_suggestions` - Inline suggestions from past queries
+//! - `statement_runs` - Per-statement history entries and the last run of each statement
+//! - `outline` - The statement outline of a script
+//! - `snippets` - Snippets with tab stops, built in and user-defined
 //! - `completion_agent` - Agent-powered inline completions
 //! - `code_action_agent` - Agent-powered code actions (Complete, Explain, Optimize)
+//! - `error_agent` - Agent-powered explanations and fixes of failed queries
+//! - `refactor` - Turning a query into a view, function or prepared statement
 
 mod analyzer;
+mod change_script;
 mod code_action_agent;
 mod completion_agent;
 mod completions;
+mod error_agent;
+mod files;
+mod highlight;
+mod history_suggestions;
+mod hover;
+mod outline;
+mod pairs;
+mod params;
+mod paste;
+mod refactor;
+mod snippets;
+mod statement_runs;
+mod statements;
+mod value_context;
+mod variables;
 
 pub use analyzer::{SqlQuery, SqlQueryAnalyzer};
+pub use change_script::{ChangeScript, migration_file_names};
 pub use code_action_agent::SqlCodeActionProvider;
-pub use completions::SqlCompletionProvider;
+pub use completions::{HISTORY_SUGGESTION_LIMIT, SqlCompletionProvider};
+pub use error_agent::{ErrorExplanation, explain_error};
+pub use files::{file_title, push_recent, sql_file_args, sql_file_from_url, with_sql_extension};
+pub use highlight::sql_language;
+pub use hover::SqlHoverProvider;
+pub use outline::{OutlineEntry, StatementCategory, statement_outline};
+pub use pairs::{PairEdit, deleted_pair, in_literal, matching_bracket, newline_indent, typed_pair};
+pub use params::{BoundParams, ParamQuery, parse_params, query_hash};
+pub use paste::in_list_items;
+pub use snippets::{
+    Snippet, SnippetSession, builtin_snippets, edit_between, expand_snippet, inserted_snippet,
+    snippet_at,
+};
+pub use statement_runs::{
+    STATEMENT_RUN_HISTORY_LIMIT, StatementRun, StatementRuns, captured_result, history_entry,
+};
+pub use statements::split_statements;
+pub use value_context::opens_in_list;
+pub use variables::{QueryVariable, QueryVariables, VariableEnvironment, template_variables};
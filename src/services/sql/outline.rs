@@ -0,0 +1,181 @@
+//! The outline of a script: one entry per statement, named by what it
+//! does and its first line, for finding your way around long scripts.
+
+use super::analyzer::SqlQuery;
+use super::refactor::strip_leading_comments;
+
+/// Longest first line shown in the outline.
+const SUMMARY_CHARS: usize = 80;
+
+/// Words between `CREATE` and the kind of object created.
+const CREATE_MODIFIERS: &[&str] = &[
+    "OR",
+    "REPLACE",
+    "UNIQUE",
+    "TEMP",
+    "TEMPORARY",
+    "UNLOGGED",
+    "GLOBAL",
+    "LOCAL",
+    "RECURSIVE",
+    "DEFAULT",
+    "TRUSTED",
+    "PROCEDURAL",
+];
+
+/// What a statement does, for colouring it in the outline and minimap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementCategory {
+    /// Reads data: `SELECT`, `WITH`, `EXPLAIN`, ...
+    Query,
+    /// Changes rows: `INSERT`, `UPDATE`, `DELETE`, ...
+    Modification,
+    /// Changes structure or privileges: `CREATE`, `ALTER`, `DROP`, ...
+    Definition,
+    /// `BEGIN`, `COMMIT`, `ROLLBACK`, ...
+    Transaction,
+    Other,
+}
+
+impl StatementCategory {
+    fn of(keyword: &str) -> Self {
+        match keyword {
+            "SELECT" | "WITH" | "VALUES" | "TABLE" | "SHOW" | "EXPLAIN" | "DESCRIBE" => Self::Query,
+            "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "UPSERT" | "REPLACE" | "COPY" => {
+                Self::Modification
+            }
+            "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "COMMENT" | "GRANT" | "REVOKE"
+            | "RENAME" => Self::Definition,
+            "BEGIN" | "START" | "COMMIT" | "END" | "ROLLBACK" | "ABORT" | "SAVEPOINT"
+            | "RELEASE" => Self::Transaction,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// One statement of a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// E.g. `SELECT`, `CREATE TABLE`, `DROP INDEX`.
+    pub kind: String,
+    pub category: StatementCategory,
+    /// The statement's first line, shortened.
+    pub summary: String,
+    /// Where the statement starts, past leading comments.
+    pub start_byte: usize,
+    /// Zero-based lines the statement spans.
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// The outline of the statements found by the analyzer, in script order.
+pub fn statement_outline(queries: &[SqlQuery]) -> Vec<OutlineEntry> {
+    queries
+        .iter()
+        .map(|query| {
+            let text = strip_leading_comments(&query.query_text);
+            let skipped = &query.query_text[..query.query_text.len() - text.len()];
+            let (kind, category) = statement_kind(text);
+            let first_line = text.lines().next().unwrap_or_default().trim();
+            let summary = match first_line.char_indices().nth(SUMMARY_CHARS) {
+                Some((end, _)) => format!("{}…", &first_line[..end]),
+                None => first_line.to_string(),
+            };
+            OutlineEntry {
+                kind,
+                category,
+                summary,
+                start_byte: query.start_byte + skipped.len(),
+                start_line: query.start_line + skipped.matches('\n').count(),
+                end_line: query.end_line,
+            }
+        })
+        .collect()
+}
+
+/// The kind of `sql`, from its leading keywords: the verb, plus the kind
+/// of object for `CREATE`, `ALTER` and `DROP`.
+fn statement_kind(sql: &str) -> (String, StatementCategory) {
+    let mut words = sql
+        .split(|c: char| !c.is_ascii_alphabetic() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_uppercase());
+    let Some(verb) = words.next() else {
+        return ("Statement".to_string(), StatementCategory::Other);
+    };
+    let category = StatementCategory::of(&verb);
+    if !matches!(verb.as_str(), "CREATE" | "ALTER" | "DROP") {
+        return (verb, category);
+    }
+
+    let mut object = words.skip_while(|word| CREATE_MODIFIERS.contains(&word.as_str()));
+    let kind = match object.next() {
+        Some(first) if first == "MATERIALIZED" || first == "FOREIGN" || first == "EVENT" => {
+            match object.next() {
+                Some(second) => format!("{} {} {}", verb, first, second),
+                None => format!("{} {}", verb, first),
+            }
+        }
+        Some(first) => format!("{} {}", verb, first),
+        None => verb,
+    };
+    (kind, category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::analyzer::SqlQueryAnalyzer;
+    use super::*;
+
+    #[test]
+    fn names_statements_by_kind_and_first_line() {
+        let sql = "CREATE OR REPLACE VIEW active AS\n  SELECT * FROM users;\n\
+                   create unique index users_email on users (email);\n\
+                   UPDATE users SET active = true;";
+        let outline = statement_outline(&SqlQueryAnalyzer::new().detect_queries(sql));
+
+        let kinds: Vec<&str> = outline.iter().map(|entry| entry.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["CREATE VIEW", "CREATE INDEX", "UPDATE"]);
+        assert_eq!(outline[0].summary, "CREATE OR REPLACE VIEW active AS");
+        assert_eq!((outline[0].start_line, outline[0].end_line), (0, 1));
+        assert_eq!(outline[1].start_line, 2);
+        assert_eq!(outline[0].category, StatementCategory::Definition);
+        assert_eq!(outline[2].category, StatementCategory::Modification);
+    }
+
+    #[test]
+    fn starts_past_leading_comments() {
+        let sql = "\n-- totals\nSELECT 1";
+        let outline = statement_outline(&[SqlQuery {
+            start_byte: 1,
+            end_byte: sql.len(),
+            start_line: 1,
+            end_line: 2,
+            start_char: 1,
+            end_char: sql.len(),
+            query_text: sql[1..].to_string(),
+        }]);
+        assert_eq!(&sql[outline[0].start_byte..], "SELECT 1");
+        assert_eq!(outline[0].start_line, 2);
+        assert_eq!(outline[0].kind, "SELECT");
+    }
+
+    #[test]
+    fn kinds_and_long_first_lines() {
+        assert_eq!(
+            statement_kind("DROP MATERIALIZED VIEW IF EXISTS totals"),
+            (
+                "DROP MATERIALIZED VIEW".to_string(),
+                StatementCategory::Definition
+            )
+        );
+        assert_eq!(statement_kind("begin").1, StatementCategory::Transaction);
+        assert_eq!(statement_kind("  ").0, "Statement");
+
+        let long = format!("SELECT {} 1", "1 +".repeat(40));
+        let outline = statement_outline(&SqlQueryAnalyzer::new().detect_queries(&long));
+        assert_eq!(outline[0].summary.chars().count(), SUMMARY_CHARS + 1);
+        assert!(outline[0].summary.ends_with('…'));
+        assert_eq!(outline[0].category, StatementCategory::Query);
+    }
+}
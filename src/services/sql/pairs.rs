@@ -0,0 +1,469 @@
+//! Bracket and quote aware editing: auto-closing pairs, matching brackets
+//! and indenting new lines inside parentheses.
+//!
+//! Brackets and quotes inside strings, quoted identifiers, comments and
+//! dollar-quoted bodies are text, so they are neither closed nor matched.
+//! A `'` typed inside a string doubles into the escaped `''`, and typing
+//! the second `$` of `$$` or `$tag$` closes the body.
+
+use std::ops::Range;
+
+use crate::services::storage::DatabaseDriver;
+
+use super::params::is_ident_byte;
+
+/// The end of a literal that runs to the end of the text, so that a
+/// cursor at the very end is still inside it.
+const UNTERMINATED: usize = usize::MAX;
+
+/// An edit replacing `range` with `text`, leaving the cursor at `cursor`
+/// in the edited text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairEdit {
+    pub range: Range<usize>,
+    pub text: String,
+    pub cursor: usize,
+}
+
+impl PairEdit {
+    fn insert(offset: usize, text: impl Into<String>, cursor: usize) -> Self {
+        Self {
+            range: offset..offset,
+            text: text.into(),
+            cursor,
+        }
+    }
+}
+
+/// A bracket next to the cursor and the one it pairs with, by byte offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketMatch {
+    pub bracket: usize,
+    /// `None` when the bracket is not closed, or closes nothing.
+    pub matching: Option<usize>,
+}
+
+/// A string, quoted identifier, comment or dollar-quoted body, from its
+/// first byte to past its last.
+#[derive(Debug, Clone, Copy)]
+struct Literal {
+    start: usize,
+    end: usize,
+    quote: Option<u8>,
+}
+
+fn literals(driver: &DatabaseDriver, sql: &str) -> Vec<Literal> {
+    let b = sql.as_bytes();
+    let mysql = *driver == DatabaseDriver::MySql;
+    let mut literals = vec![];
+    let mut i = 0;
+    while i < b.len() {
+        let (end, quote) = match b[i] {
+            b'-' if b.get(i + 1) == Some(&b'-') => (line_end(b, i), None),
+            b'#' if mysql => (line_end(b, i), None),
+            b'/' if b.get(i + 1) == Some(&b'*') => {
+                let end = sql[i + 2..]
+                    .find("*/")
+                    .map_or(UNTERMINATED, |e| i + 2 + e + 2);
+                (end, None)
+            }
+            q @ (b'\'' | b'"') => (quoted_end(b, i, mysql), Some(q)),
+            b'`' if mysql => (quoted_end(b, i, false), Some(b'`')),
+            b'$' if !mysql && (i == 0 || !is_ident_byte(b[i - 1])) => {
+                match dollar_quote_end(sql, i) {
+                    Some(end) => (end, None),
+                    None => {
+                        i += 1;
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        literals.push(Literal {
+            start: i,
+            end,
+            quote,
+        });
+        i = end;
+    }
+    literals
+}
+
+fn line_end(b: &[u8], start: usize) -> usize {
+    b[start..]
+        .iter()
+        .position(|c| *c == b'\n')
+        .map_or(UNTERMINATED, |p| start + p + 1)
+}
+
+fn quoted_end(b: &[u8], start: usize, backslash_escapes: bool) -> usize {
+    let quote = b[start];
+    let mut i = start + 1;
+    while i < b.len() {
+        if backslash_escapes && b[i] == b'\\' {
+            i += 2;
+        } else if b[i] == quote {
+            if b.get(i + 1) == Some(&quote) {
+                i += 2;
+            } else {
+                return i + 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    UNTERMINATED
+}
+
+/// The end of the `$tag$ ... $tag$` body opening at `start`, or `None`
+/// when the `$` opens none, as in `$1`.
+fn dollar_quote_end(sql: &str, start: usize) -> Option<usize> {
+    let b = sql.as_bytes();
+    let tag_len = b[start + 1..]
+        .iter()
+        .take_while(|c| is_ident_byte(**c))
+        .count();
+    let close = start + 1 + tag_len;
+    if b.get(close) != Some(&b'$') || b.get(start + 1).is_some_and(u8::is_ascii_digit) {
+        return None;
+    }
+    let tag = &sql[start..=close];
+    Some(
+        sql[close + 1..]
+            .find(tag)
+            .map_or(UNTERMINATED, |end| close + 1 + end + tag.len()),
+    )
+}
+
+/// The literal the cursor at `offset` is inside of, past its opening.
+fn literal_at(literals: &[Literal], offset: usize) -> Option<&Literal> {
+    literals
+        .iter()
+        .find(|literal| literal.start < offset && offset < literal.end)
+}
+
+/// Whether the byte at `index` is code rather than part of a literal.
+fn is_code(literals: &[Literal], index: usize) -> bool {
+    !literals
+        .iter()
+        .any(|literal| literal.start <= index && index < literal.end)
+}
+
+/// Whether a closing character may be inserted before `next`.
+fn closes_before(next: Option<char>) -> bool {
+    next.is_none_or(|c| c.is_whitespace() || ")],;".contains(c))
+}
+
+/// What typing `typed` at `offset` should do instead of inserting it, if
+/// anything: insert its closing pair, step over a closing character
+/// already there, double a quote inside a string or close a dollar quote.
+pub fn typed_pair(
+    driver: &DatabaseDriver,
+    sql: &str,
+    offset: usize,
+    typed: char,
+) -> Option<PairEdit> {
+    let literals = literals(driver, sql);
+    let next = sql[offset..].chars().next();
+    let prev = sql[..offset].chars().next_back();
+
+    match (literal_at(&literals, offset), typed) {
+        (None, '(' | '[') if closes_before(next) => {
+            let close = if typed == '(' { ')' } else { ']' };
+            Some(PairEdit::insert(
+                offset,
+                format!("{}{}", typed, close),
+                offset + 1,
+            ))
+        }
+        (None, ')' | ']') if next == Some(typed) => Some(PairEdit::insert(offset, "", offset + 1)),
+        (None, '\'' | '"') => {
+            // Quotes after a word are apostrophes or closing, except for
+            // the prefixes of E'...', B'...', X'...' and N'...'
+            let before = &sql[..offset];
+            let prefixed = prev.is_some_and(|c| "EeBbXxNn".contains(c))
+                && !before[..before.len() - 1]
+                    .bytes()
+                    .next_back()
+                    .is_some_and(is_ident_byte);
+            let after_word = prev.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            (closes_before(next) && (!after_word || (typed == '\'' && prefixed)))
+                .then(|| PairEdit::insert(offset, format!("{}{}", typed, typed), offset + 1))
+        }
+        (Some(literal), '\'' | '"') if literal.quote == Some(typed as u8) => {
+            if literal.end == offset + 1 {
+                // The closing quote, typed over
+                Some(PairEdit::insert(offset, "", offset + 1))
+            } else if typed == '\'' && literal.end != UNTERMINATED && next != Some('\'') {
+                Some(PairEdit::insert(offset, "''", offset + 2))
+            } else {
+                None
+            }
+        }
+        (None, '$') if *driver != DatabaseDriver::MySql => {
+            let b = sql.as_bytes();
+            let tag_len = b[..offset]
+                .iter()
+                .rev()
+                .take_while(|c| is_ident_byte(**c))
+                .count();
+            let dollar = offset.checked_sub(tag_len + 1)?;
+            let opens = b[dollar] == b'$'
+                && !(dollar > 0 && (is_ident_byte(b[dollar - 1]) || b[dollar - 1] == b'$'))
+                && !b.get(dollar + 1).is_some_and(u8::is_ascii_digit);
+            let delimiter = format!("{}$", &sql[dollar..offset]);
+            (opens && !sql[offset..].contains(&delimiter))
+                .then(|| PairEdit::insert(offset, format!("${}", delimiter), offset + 1))
+        }
+        _ => None,
+    }
+}
+
+/// The empty pair around `offset` that a backspace there should delete as
+/// a whole, as in `(|)` or `'|'`.
+pub fn deleted_pair(driver: &DatabaseDriver, sql: &str, offset: usize) -> Option<Range<usize>> {
+    let prev = sql[..offset].chars().next_back()?;
+    let next = sql[offset..].chars().next()?;
+    if !matches!(
+        (prev, next),
+        ('(', ')') | ('[', ']') | ('\'', '\'') | ('"', '"')
+    ) {
+        return None;
+    }
+    let literals = literals(driver, sql);
+    let empty = if prev == '(' || prev == '[' {
+        is_code(&literals, offset - 1) && is_code(&literals, offset)
+    } else {
+        literals
+            .iter()
+            .any(|literal| literal.start == offset - 1 && literal.end == offset + 1)
+    };
+    empty.then_some(offset - 1..offset + 1)
+}
+
+/// Whether the cursor at `offset` is inside a string, quoted identifier,
+/// comment or dollar-quoted body.
+pub fn in_literal(driver: &DatabaseDriver, sql: &str, offset: usize) -> bool {
+    literal_at(&literals(driver, sql), offset).is_some()
+}
+
+/// The code brackets of `sql` and what each pairs with.
+fn bracket_matches(driver: &DatabaseDriver, sql: &str) -> Vec<BracketMatch> {
+    let literals = literals(driver, sql);
+    let mut matches: Vec<BracketMatch> = vec![];
+    // Indexes into `matches` of the brackets still open
+    let mut open: Vec<usize> = vec![];
+    let mut literal = literals.iter().peekable();
+    let mut i = 0;
+    let b = sql.as_bytes();
+    while i < b.len() {
+        if let Some(next) = literal.peek()
+            && next.start == i
+        {
+            i = next.end;
+            literal.next();
+            continue;
+        }
+        match b[i] {
+            b'(' | b'[' => {
+                open.push(matches.len());
+                matches.push(BracketMatch {
+                    bracket: i,
+                    matching: None,
+                });
+            }
+            c @ (b')' | b']') => {
+                let opener = if c == b')' { b'(' } else { b'[' };
+                let matching = open
+                    .last()
+                    .copied()
+                    .filter(|&ix| b[matches[ix].bracket] == opener);
+                if let Some(ix) = matching {
+                    open.pop();
+                    matches[ix].matching = Some(i);
+                }
+                matches.push(BracketMatch {
+                    bracket: i,
+                    matching: matching.map(|ix| matches[ix].bracket),
+                });
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    matches
+}
+
+/// The bracket just before the cursor at `offset`, else just after it,
+/// and the one it pairs with.
+pub fn matching_bracket(driver: &DatabaseDriver, sql: &str, offset: usize) -> Option<BracketMatch> {
+    let matches = bracket_matches(driver, sql);
+    let at = |index: usize| matches.iter().find(|m| m.bracket == index).copied();
+    offset.checked_sub(1).and_then(at).or_else(|| at(offset))
+}
+
+/// How to break the line at `offset` inside parentheses: one `indent`
+/// deeper than the line that opened them, with the closing parenthesis on
+/// a line of its own when the cursor is right before it. `None` outside
+/// parentheses.
+pub fn newline_indent(
+    driver: &DatabaseDriver,
+    sql: &str,
+    offset: usize,
+    indent: &str,
+) -> Option<PairEdit> {
+    let matches = bracket_matches(driver, sql);
+    if literal_at(&literals(driver, sql), offset).is_some() {
+        return None;
+    }
+    let b = sql.as_bytes();
+    let open = matches
+        .iter()
+        .rev()
+        .filter(|m| b[m.bracket] == b'(' && m.bracket < offset)
+        .find(|m| m.matching.is_none_or(|close| close >= offset))?;
+
+    let line_start = sql[..open.bracket].rfind('\n').map_or(0, |n| n + 1);
+    let base: String = sql[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+    let inner = format!("{}{}", base, indent);
+
+    let rest = &sql[offset..];
+    let spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    let before_close = open.matching == Some(offset + spaces);
+    let after_open = sql[..offset].trim_end_matches([' ', '\t']).len() == open.bracket + 1;
+
+    let range = offset..offset + if before_close { spaces } else { 0 };
+    let edit = if before_close && after_open {
+        PairEdit {
+            range,
+            text: format!("\n{}\n{}", inner, base),
+            cursor: offset + 1 + inner.len(),
+        }
+    } else if before_close {
+        PairEdit {
+            range,
+            text: format!("\n{}", base),
+            cursor: offset + 1 + base.len(),
+        }
+    } else {
+        PairEdit::insert(offset, format!("\n{}", inner), offset + 1 + inner.len())
+    };
+    Some(edit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PG: DatabaseDriver = DatabaseDriver::Postgres;
+
+    /// `sql` with the `|` marking the cursor, and the result of typing
+    /// `typed` there, with the cursor marked the same way.
+    fn typing(sql: &str, typed: char) -> String {
+        let offset = sql.find('|').unwrap();
+        let sql = sql.replace('|', "");
+        let edit = typed_pair(&PG, &sql, offset, typed).unwrap_or_else(|| {
+            PairEdit::insert(offset, typed.to_string(), offset + typed.len_utf8())
+        });
+        let mut out = sql.clone();
+        out.replace_range(edit.range.clone(), &edit.text);
+        out.insert(edit.cursor, '|');
+        out
+    }
+
+    #[test]
+    fn closes_brackets_and_quotes_in_code_only() {
+        assert_eq!(typing("SELECT count|", '('), "SELECT count(|)");
+        assert_eq!(typing("SELECT count(*|)", ')'), "SELECT count(*)|");
+        assert_eq!(typing("SELECT |x", '('), "SELECT (|x");
+        assert_eq!(typing("WHERE name = |", '\''), "WHERE name = '|'");
+        assert_eq!(typing("WHERE name = E|", '\''), "WHERE name = E'|'");
+        assert_eq!(typing("WHERE name = 'ab|'", '\''), "WHERE name = 'ab'|");
+        assert_eq!(typing("-- don|", '\''), "-- don'|");
+        assert_eq!(typing("SELECT '(|'", '('), "SELECT '((|'");
+        assert_eq!(typing("SELECT \"|", '"'), "SELECT \"\"|");
+    }
+
+    #[test]
+    fn doubles_quotes_inside_strings() {
+        assert_eq!(typing("SELECT 'O|Brien'", '\''), "SELECT 'O''|Brien'");
+        // Unterminated, so the quote closes it
+        assert_eq!(typing("SELECT 'abc|", '\''), "SELECT 'abc'|");
+    }
+
+    #[test]
+    fn closes_dollar_quotes() {
+        assert_eq!(typing("AS $|", '$'), "AS $$|$$");
+        assert_eq!(typing("DO $body|", '$'), "DO $body$|$body$");
+        assert_eq!(typing("SELECT $1|", '$'), "SELECT $1$|");
+        assert_eq!(typing("AS $|\nBEGIN\n$$", '$'), "AS $$|\nBEGIN\n$$");
+        assert_eq!(typing("AS $$ x = $|$", '$'), "AS $$ x = $$|$");
+    }
+
+    #[test]
+    fn deletes_empty_pairs() {
+        assert_eq!(deleted_pair(&PG, "count()", 6), Some(5..7));
+        assert_eq!(deleted_pair(&PG, "x = ''", 5), Some(4..6));
+        assert_eq!(deleted_pair(&PG, "x = 'a''b'", 7), None);
+        assert_eq!(deleted_pair(&PG, "'()'", 2), None);
+    }
+
+    #[test]
+    fn finds_literals_around_the_cursor() {
+        assert!(in_literal(&PG, "SELECT 'sel", 11));
+        assert!(in_literal(&PG, "-- sel\nSELECT", 6));
+        assert!(!in_literal(&PG, "-- sel\nSELECT", 13));
+        assert!(!in_literal(&PG, "SELECT 'a' sel", 14));
+    }
+
+    #[test]
+    fn matches_brackets_outside_literals() {
+        let sql = "SELECT (a + ')') * (b";
+        assert_eq!(
+            matching_bracket(&PG, sql, 8),
+            Some(BracketMatch {
+                bracket: 7,
+                matching: Some(15),
+            })
+        );
+        assert_eq!(matching_bracket(&PG, sql, 16).unwrap().matching, Some(7));
+        assert_eq!(matching_bracket(&PG, sql, 19).unwrap().matching, None);
+        assert_eq!(matching_bracket(&PG, sql, 13), None);
+        assert_eq!(matching_bracket(&PG, "a)", 2).unwrap().matching, None);
+    }
+
+    #[test]
+    fn indents_new_lines_inside_parentheses() {
+        let enter = |sql: &str| {
+            let offset = sql.find('|').unwrap();
+            let sql = sql.replace('|', "");
+            newline_indent(&PG, &sql, offset, "  ").map(|edit| {
+                let mut out = sql.clone();
+                out.replace_range(edit.range.clone(), &edit.text);
+                out.insert(edit.cursor, '|');
+                out
+            })
+        };
+        assert_eq!(
+            enter("  WHERE id IN (|)"),
+            Some("  WHERE id IN (\n    |\n  )".to_string())
+        );
+        assert_eq!(
+            enter("INSERT INTO t (a,|"),
+            Some("INSERT INTO t (a,\n  |".to_string())
+        );
+        assert_eq!(
+            enter("VALUES (\n  1,\n  2| )"),
+            Some("VALUES (\n  1,\n  2\n|)".to_string())
+        );
+        assert_eq!(enter("SELECT 1|"), None);
+        assert_eq!(enter("SELECT '(|'"), None);
+    }
+}
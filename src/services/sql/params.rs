@@ -0,0 +1,421 @@
+//! Bind placeholders in editor queries.
+//!
+//! Postgres queries may use `$1` or `:name`; MySQL queries may also use
+//! `?`. Placeholders inside strings, quoted identifiers, comments and
+//! dollar-quoted bodies are ignored, as are `::` casts and the bounds of
+//! array slices like `tags[1:n]`.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::services::storage::DatabaseDriver;
+
+/// A query whose placeholders were rewritten to the driver's native form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamQuery {
+    pub driver: DatabaseDriver,
+    /// SQL with `$n` placeholders on Postgres and `?` on MySQL.
+    pub sql: String,
+    /// Distinct parameters as written: `$1`, `:user_id`, `?2`, ...
+    pub labels: Vec<String>,
+    /// For each bind, in order, the index into `labels` of its value.
+    pub bind_order: Vec<usize>,
+}
+
+/// A parameterized query ready to run: type hints from the server and one
+/// value per label (`None` binds NULL).
+#[derive(Debug, Clone)]
+pub struct BoundParams {
+    pub query: ParamQuery,
+    pub types: Vec<Option<String>>,
+    pub values: Vec<Option<String>>,
+}
+
+enum Placeholder<'a> {
+    Positional(usize),
+    Named(&'a str),
+    Anonymous,
+}
+
+/// Detect the placeholders of `sql`. `None` when there are none.
+pub fn parse_params(driver: &DatabaseDriver, sql: &str) -> Option<ParamQuery> {
+    let mut found = Vec::new();
+    scan(driver, sql, |range, placeholder| {
+        found.push((range, placeholder))
+    });
+    if found.is_empty() {
+        return None;
+    }
+
+    let mut labels: Vec<String> = Vec::new();
+    let mut bind_order = Vec::new();
+    let mut rewritten = String::with_capacity(sql.len());
+    let mut last = 0;
+
+    match driver {
+        DatabaseDriver::Postgres => {
+            // `$n` keep their numbers; named parameters follow them.
+            let max_positional = found
+                .iter()
+                .filter_map(|(_, p)| match p {
+                    Placeholder::Positional(n) => Some(*n),
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0);
+            labels.extend((1..=max_positional).map(|n| format!("${}", n)));
+
+            for (range, placeholder) in &found {
+                rewritten.push_str(&sql[last..range.start]);
+                let number = match placeholder {
+                    Placeholder::Positional(n) => *n,
+                    Placeholder::Named(name) => label_index(&mut labels, format!(":{}", name)) + 1,
+                    Placeholder::Anonymous => unreachable!("`?` is not a Postgres placeholder"),
+                };
+                rewritten.push_str(&format!("${}", number));
+                last = range.end;
+            }
+            bind_order.extend(0..labels.len());
+        }
+        DatabaseDriver::MySql => {
+            let mut anonymous = 0;
+            for (range, placeholder) in &found {
+                rewritten.push_str(&sql[last..range.start]);
+                rewritten.push('?');
+                let label = match placeholder {
+                    Placeholder::Positional(n) => format!("${}", n),
+                    Placeholder::Named(name) => format!(":{}", name),
+                    Placeholder::Anonymous => {
+                        anonymous += 1;
+                        format!("?{}", anonymous)
+                    }
+                };
+                bind_order.push(label_index(&mut labels, label));
+                last = range.end;
+            }
+        }
+    }
+    rewritten.push_str(&sql[last..]);
+
+    Some(ParamQuery {
+        driver: *driver,
+        sql: rewritten,
+        labels,
+        bind_order,
+    })
+}
+
+impl ParamQuery {
+    /// The SQL to run. On Postgres values are bound as text, so each
+    /// parameter with a known type is wrapped in `CAST($n AS type)`. The
+    /// types are named without a modifier, so the cast doesn't truncate.
+    pub fn typed_sql(&self, types: &[Option<String>]) -> String {
+        if self.driver != DatabaseDriver::Postgres {
+            return self.sql.clone();
+        }
+
+        let mut sql = String::with_capacity(self.sql.len());
+        let mut last = 0;
+        scan(&self.driver, &self.sql, |range, placeholder| {
+            if let Placeholder::Positional(n) = placeholder {
+                if let Some(Some(ty)) = types.get(n - 1) {
+                    sql.push_str(&self.sql[last..range.start]);
+                    sql.push_str(&format!("CAST(${} AS {})", n, ty));
+                    last = range.end;
+                }
+            }
+        });
+        sql.push_str(&self.sql[last..]);
+        sql
+    }
+
+    /// Values in bind order, from one value per label.
+    pub fn bind_values(&self, values: &[Option<String>]) -> Vec<Option<String>> {
+        self.bind_order
+            .iter()
+            .map(|&ix| values.get(ix).cloned().flatten())
+            .collect()
+    }
+
+    /// Values by label, for remembering them between runs.
+    pub fn values_by_label(&self, values: &[Option<String>]) -> HashMap<String, Option<String>> {
+        self.labels
+            .iter()
+            .cloned()
+            .zip(values.iter().cloned())
+            .collect()
+    }
+}
+
+/// Stable key for a query, ignoring differences in whitespace. Used to
+/// remember parameter values per query.
+pub fn query_hash(sql: &str) -> String {
+    // FNV-1a, so the key stays the same across builds.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let normalized = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    for byte in normalized.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn label_index(labels: &mut Vec<String>, label: String) -> usize {
+    match labels.iter().position(|l| *l == label) {
+        Some(ix) => ix,
+        None => {
+            labels.push(label);
+            labels.len() - 1
+        }
+    }
+}
+
+pub(super) fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Call `f` with the byte range of every placeholder outside strings,
+/// quoted identifiers and comments.
+fn scan<'a>(
+    driver: &DatabaseDriver,
+    sql: &'a str,
+    mut f: impl FnMut(Range<usize>, Placeholder<'a>),
+) {
+    let b = sql.as_bytes();
+    let mysql = *driver == DatabaseDriver::MySql;
+    // Inside `[...]`, where `:` separates slice bounds
+    let mut brackets = 0usize;
+    let mut i = 0;
+
+    while i < b.len() {
+        match b[i] {
+            b'[' => {
+                brackets += 1;
+                i += 1;
+            }
+            b']' => {
+                brackets = brackets.saturating_sub(1);
+                i += 1;
+            }
+            b'\'' => i = skip_quoted(b, i, mysql || is_escape_string(b, i)),
+            b'"' => i = skip_quoted(b, i, mysql),
+            b'`' if mysql => i = skip_quoted(b, i, false),
+            b'-' if b.get(i + 1) == Some(&b'-') => i = skip_line(b, i),
+            b'#' if mysql => i = skip_line(b, i),
+            b'/' if b.get(i + 1) == Some(&b'*') => {
+                i = match sql[i + 2..].find("*/") {
+                    Some(end) => i + 2 + end + 2,
+                    None => b.len(),
+                };
+            }
+            b'$' => {
+                let digits = b[i + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+                let after_ident = i > 0 && is_ident_byte(b[i - 1]);
+                if digits > 0 && !after_ident {
+                    let end = i + 1 + digits;
+                    if let Ok(n) = sql[i + 1..end].parse::<usize>() {
+                        if n > 0 {
+                            f(i..end, Placeholder::Positional(n));
+                        }
+                    }
+                    i = end;
+                } else if !mysql && !after_ident {
+                    i = skip_dollar_quoted(sql, i);
+                } else {
+                    i += 1;
+                }
+            }
+            b':' => {
+                if b.get(i + 1) == Some(&b':') {
+                    // `::` cast
+                    i += 2;
+                    continue;
+                }
+                let start = i + 1;
+                let starts_ident = brackets == 0
+                    && b.get(start)
+                        .is_some_and(|c| c.is_ascii_alphabetic() || *c == b'_');
+                if starts_ident {
+                    let len = b[start..].iter().take_while(|c| is_ident_byte(**c)).count();
+                    f(i..start + len, Placeholder::Named(&sql[start..start + len]));
+                    i = start + len;
+                } else {
+                    i += 1;
+                }
+            }
+            b'?' if mysql => {
+                f(i..i + 1, Placeholder::Anonymous);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// Whether the `'` at `quote` opens a Postgres `E'...'` string, in which
+/// backslashes escape.
+pub(super) fn is_escape_string(b: &[u8], quote: usize) -> bool {
+    quote
+        .checked_sub(1)
+        .is_some_and(|e| b[e].eq_ignore_ascii_case(&b'e') && (e == 0 || !is_ident_byte(b[e - 1])))
+}
+
+/// Index just past the quoted string or identifier opening at `start`.
+pub(super) fn skip_quoted(b: &[u8], start: usize, backslash_escapes: bool) -> usize {
+    let quote = b[start];
+    let mut i = start + 1;
+    while i < b.len() {
+        if backslash_escapes && b[i] == b'\\' {
+            i += 2;
+        } else if b[i] == quote {
+            if b.get(i + 1) == Some(&quote) {
+                i += 2;
+            } else {
+                return i + 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    b.len()
+}
+
+pub(super) fn skip_line(b: &[u8], start: usize) -> usize {
+    b[start..]
+        .iter()
+        .position(|c| *c == b'\n')
+        .map_or(b.len(), |p| start + p + 1)
+}
+
+/// Skip a `$tag$ ... $tag$` body opening at `start`, or just the `$` when
+/// it does not open one.
+pub(super) fn skip_dollar_quoted(sql: &str, start: usize) -> usize {
+    let b = sql.as_bytes();
+    let tag_len = b[start + 1..]
+        .iter()
+        .take_while(|c| is_ident_byte(**c))
+        .count();
+    let close = start + 1 + tag_len;
+    if b.get(close) != Some(&b'$') {
+        return start + 1;
+    }
+
+    let tag = &sql[start..=close];
+    match sql[close + 1..].find(tag) {
+        Some(end) => close + 1 + end + tag.len(),
+        None => b.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_placeholders() {
+        assert!(parse_params(&DatabaseDriver::Postgres, "SELECT 1").is_none());
+        assert!(
+            parse_params(
+                &DatabaseDriver::Postgres,
+                "SELECT '$1', ':name', now()::date -- $2\n/* :x */ FROM t WHERE data ? 'k'"
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn postgres_positional_and_named() {
+        let query = parse_params(
+            &DatabaseDriver::Postgres,
+            "SELECT * FROM users WHERE id = $2 AND org = :org AND (owner = :org OR $1)",
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "SELECT * FROM users WHERE id = $2 AND org = $3 AND (owner = $3 OR $1)"
+        );
+        assert_eq!(query.labels, vec!["$1", "$2", ":org"]);
+        assert_eq!(query.bind_order, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn postgres_skips_array_slice_bounds() {
+        let query = parse_params(
+            &DatabaseDriver::Postgres,
+            "SELECT tags[lo:hi], grid[1:n][:m] FROM t WHERE id = :id AND tags[$1] = 'x'",
+        )
+        .unwrap();
+        assert_eq!(query.labels, vec!["$1", ":id"]);
+        assert_eq!(
+            query.sql,
+            "SELECT tags[lo:hi], grid[1:n][:m] FROM t WHERE id = $2 AND tags[$1] = 'x'"
+        );
+    }
+
+    #[test]
+    fn postgres_skips_escape_strings() {
+        let query = parse_params(
+            &DatabaseDriver::Postgres,
+            "SELECT * FROM t WHERE a = E'x\\'' AND b = $1 AND c = '\\' AND d = :d",
+        )
+        .unwrap();
+        assert_eq!(query.labels, vec!["$1", ":d"]);
+    }
+
+    #[test]
+    fn postgres_skips_dollar_quoted_bodies() {
+        let sql = "DO $body$ BEGIN RAISE NOTICE '%', $1; END $body$; SELECT $1";
+        let query = parse_params(&DatabaseDriver::Postgres, sql).unwrap();
+        assert_eq!(query.labels, vec!["$1"]);
+        assert_eq!(query.sql, sql);
+    }
+
+    #[test]
+    fn mysql_anonymous_and_named() {
+        let query = parse_params(
+            &DatabaseDriver::MySql,
+            "SELECT * FROM t WHERE a = ? AND b = :b AND c = ? AND d = :b AND e = 'x?'",
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "SELECT * FROM t WHERE a = ? AND b = ? AND c = ? AND d = ? AND e = 'x?'"
+        );
+        assert_eq!(query.labels, vec!["?1", ":b", "?2"]);
+        assert_eq!(query.bind_order, vec![0, 1, 2, 1]);
+
+        let values = vec![Some("1".to_string()), None, Some("3".to_string())];
+        assert_eq!(
+            query.bind_values(&values),
+            vec![Some("1".to_string()), None, Some("3".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn casts_typed_postgres_parameters() {
+        let query = parse_params(
+            &DatabaseDriver::Postgres,
+            "SELECT * FROM t WHERE id = $1 AND n = $2",
+        )
+        .unwrap();
+        assert_eq!(
+            query.typed_sql(&[Some("integer".to_string()), None]),
+            "SELECT * FROM t WHERE id = CAST($1 AS integer) AND n = $2"
+        );
+        assert_eq!(
+            query.typed_sql(&[Some("bpchar".to_string()), Some("numeric".to_string())]),
+            "SELECT * FROM t WHERE id = CAST($1 AS bpchar) AND n = CAST($2 AS numeric)"
+        );
+    }
+
+    #[test]
+    fn hash_ignores_whitespace() {
+        assert_eq!(
+            query_hash("SELECT *\n  FROM t WHERE id = $1"),
+            query_hash("SELECT * FROM t WHERE id = $1")
+        );
+        assert_ne!(query_hash("SELECT $1"), query_hash("SELECT $2"));
+    }
+}
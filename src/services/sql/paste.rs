@@ -0,0 +1,87 @@
+//! Turning a pasted column of values into the items of an `IN (...)` list.
+//!
+//! Values copied from a spreadsheet or another result come one per line.
+//! Pasted into `WHERE id IN (`, they need commas and, unless they are all
+//! numbers, quotes.
+
+/// The lines of `pasted` as a comma-separated list: numbers as they are,
+/// anything else as quoted literals. `None` when there are fewer than two
+/// values, as a single value needs no converting.
+pub fn in_list_items(pasted: &str) -> Option<String> {
+    let values: Vec<&str> = pasted
+        .lines()
+        // Lists copied from SQL or CSV keep their separators
+        .map(|line| line.trim().trim_end_matches(',').trim_end())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if values.len() < 2 {
+        return None;
+    }
+
+    let items: Vec<String> = if values.iter().all(|value| is_number(value)) {
+        values.iter().map(|value| value.to_string()).collect()
+    } else {
+        values.iter().map(|value| quote(value)).collect()
+    };
+    Some(items.join(", "))
+}
+
+/// Whether `value` reads as a number. Leading zeros mark codes, such as
+/// ZIP codes, which are compared as text.
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    !leading_zero
+        && digits.starts_with(|c: char| c.is_ascii_digit())
+        && value.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// `value` as a string literal. Values already quoted keep their quotes.
+fn quote(value: &str) -> String {
+    let quoted = value.len() > 1 && value.starts_with('\'') && value.ends_with('\'');
+    if quoted {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_stay_bare() {
+        assert_eq!(in_list_items("1\n2\n3\n").as_deref(), Some("1, 2, 3"));
+        assert_eq!(
+            in_list_items("  -4.5\r\n1e3\r\n\r\n0\n").as_deref(),
+            Some("-4.5, 1e3, 0")
+        );
+    }
+
+    #[test]
+    fn text_is_quoted() {
+        assert_eq!(
+            in_list_items("alice\nO'Brien\n42").as_deref(),
+            Some("'alice', 'O''Brien', '42'")
+        );
+        // Leading zeros are codes, not numbers
+        assert_eq!(
+            in_list_items("02134\n10001").as_deref(),
+            Some("'02134', '10001'")
+        );
+        assert_eq!(in_list_items("NaN\ninf").as_deref(), Some("'NaN', 'inf'"));
+    }
+
+    #[test]
+    fn separators_and_quotes_are_kept_once() {
+        assert_eq!(in_list_items("'a',\n'b',\n").as_deref(), Some("'a', 'b'"));
+        assert_eq!(in_list_items("7,\n8").as_deref(), Some("7, 8"));
+    }
+
+    #[test]
+    fn single_values_are_left_alone() {
+        assert_eq!(in_list_items("42"), None);
+        assert_eq!(in_list_items("\n  alice \n\n"), None);
+    }
+}
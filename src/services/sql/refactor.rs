@@ -0,0 +1,501 @@
+//! Refactorings of a Postgres query into reusable database objects.
+//!
+//! A SELECT can be wrapped into a view, into a SQL function whose
+//! parameters are the query's literal values, or into a `PREPARE` /
+//! `EXECUTE` pair taking those values. Everything is generated locally for
+//! the user to review before running it.
+
+use std::ops::Range;
+
+use super::params::{is_escape_string, is_ident_byte, skip_dollar_quoted, skip_line, skip_quoted};
+
+/// Types whose literals can be written as `type 'value'`.
+const TYPE_KEYWORDS: &[&str] = &[
+    "date",
+    "time",
+    "timestamp",
+    "timestamptz",
+    "interval",
+    "uuid",
+    "json",
+    "jsonb",
+    "inet",
+];
+
+/// Refactorings offered for a SELECT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refactoring {
+    View,
+    Function,
+    Prepared,
+}
+
+impl Refactoring {
+    pub const ALL: [Refactoring; 3] = [
+        Refactoring::View,
+        Refactoring::Function,
+        Refactoring::Prepared,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Refactoring::View => "Convert to View",
+            Refactoring::Function => "Convert to Function",
+            Refactoring::Prepared => "Convert to Prepared Statement",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Refactoring::View => "view",
+            Refactoring::Function => "function",
+            Refactoring::Prepared => "prepared",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == name)
+    }
+}
+
+/// A literal value of a query, turned into a parameter.
+#[derive(Debug, Clone, PartialEq)]
+struct Literal {
+    /// The literal and any `::type` cast after it.
+    range: Range<usize>,
+    /// The value as written, without the cast.
+    value: String,
+    type_name: String,
+}
+
+/// Whether `sql` is a single query that returns rows.
+pub fn is_select(sql: &str) -> bool {
+    let body = strip_leading_comments(sql);
+    let keyword: String = body
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    matches!(keyword.as_str(), "SELECT" | "WITH" | "VALUES" | "TABLE")
+}
+
+/// `sql` as a `CREATE VIEW`.
+pub fn create_view(sql: &str, name: &str) -> String {
+    format!("CREATE VIEW {} AS\n{};\n", name, query_body(sql))
+}
+
+/// `sql` as a SQL function taking its literals as parameters, with a call
+/// using the current values. `columns` are the query's output names and
+/// types, when known; otherwise the function returns `SETOF record`.
+pub fn create_function(sql: &str, name: &str, columns: Option<&[(String, String)]>) -> String {
+    let (body, params) = parameterize(sql);
+    let returns = match columns {
+        Some(columns) if !columns.is_empty() => format!(
+            "TABLE ({})",
+            columns
+                .iter()
+                .map(|(name, type_name)| format!("{} {}", quote_ident(name), type_name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => "SETOF record".to_string(),
+    };
+
+    format!(
+        "CREATE OR REPLACE FUNCTION {}({})\nRETURNS {}\nLANGUAGE sql STABLE\nAS $function$\n{}\n$function$;\n\nSELECT * FROM {}({});\n",
+        name,
+        param_types(&params),
+        returns,
+        body,
+        name,
+        param_values(&params)
+    )
+}
+
+/// `sql` as a prepared statement taking its literals as parameters, and
+/// an `EXECUTE` of it with the current values.
+pub fn prepare_statement(sql: &str, name: &str) -> String {
+    let (body, params) = parameterize(sql);
+    let (types, execute) = if params.is_empty() {
+        (String::new(), String::new())
+    } else {
+        (
+            format!("({})", param_types(&params)),
+            format!("({})", param_values(&params)),
+        )
+    };
+    format!(
+        "PREPARE {}{} AS\n{};\n\nEXECUTE {}{};\n",
+        name, types, body, name, execute
+    )
+}
+
+fn param_types(params: &[Literal]) -> String {
+    params
+        .iter()
+        .map(|p| p.type_name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn param_values(params: &[Literal]) -> String {
+    params
+        .iter()
+        .map(|p| p.value.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The query without a trailing semicolon or surrounding whitespace.
+fn query_body(sql: &str) -> &str {
+    sql.trim().trim_end_matches(';').trim_end()
+}
+
+/// The query body with each distinct literal replaced by `$n`, and the
+/// literals in parameter order. The query must not have placeholders of
+/// its own.
+fn parameterize(sql: &str) -> (String, Vec<Literal>) {
+    let body = query_body(sql);
+    let mut params: Vec<Literal> = Vec::new();
+    let mut out = String::with_capacity(body.len());
+    let mut last = 0;
+
+    for literal in literals(body) {
+        let n = match params
+            .iter()
+            .position(|p| p.value == literal.value && p.type_name == literal.type_name)
+        {
+            Some(ix) => ix + 1,
+            None => {
+                params.push(literal.clone());
+                params.len()
+            }
+        };
+        out.push_str(&body[last..literal.range.start]);
+        out.push_str(&format!("${}", n));
+        last = literal.range.end;
+    }
+    out.push_str(&body[last..]);
+    (out, params)
+}
+
+/// String, number and boolean literals outside comments, quoted
+/// identifiers and dollar-quoted bodies, typed from a `::` cast after them
+/// or from their look. Column positions in `ORDER BY` and `GROUP BY`
+/// lists are not literals.
+fn literals(sql: &str) -> Vec<Literal> {
+    let b = sql.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+    // Parenthesis depth, the depth of the `ORDER BY` or `GROUP BY` list
+    // being read, and the word before
+    let mut depth: usize = 0;
+    let mut by_list: Option<usize> = None;
+    let mut prev_word = String::new();
+
+    while i < b.len() {
+        let after_ident = i > 0 && is_ident_byte(b[i - 1]);
+        match b[i] {
+            b'\'' => {
+                // `E'...'` strings take their prefix along
+                let start = if is_escape_string(b, i) { i - 1 } else { i };
+                let end = skip_quoted(b, i, start != i);
+                let value = &sql[start..end];
+                let literal = match type_keyword_before(sql, start) {
+                    // `date '2024-01-01'` takes its type from the keyword
+                    Some((keyword_start, type_name)) => Literal {
+                        range: keyword_start..end,
+                        value: value.to_string(),
+                        type_name,
+                    },
+                    None => typed(sql, start..end, value, guess_string_type(value)),
+                };
+                i = literal.range.end;
+                found.push(literal);
+            }
+            b'"' => i = skip_quoted(b, i, false),
+            b'-' if b.get(i + 1) == Some(&b'-') => i = skip_line(b, i),
+            b'/' if b.get(i + 1) == Some(&b'*') => {
+                i = match sql[i + 2..].find("*/") {
+                    Some(end) => i + 2 + end + 2,
+                    None => b.len(),
+                };
+            }
+            b'$' => {
+                let digits = b[i + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+                i = if digits > 0 || after_ident {
+                    i + 1 + digits
+                } else {
+                    skip_dollar_quoted(sql, i)
+                };
+            }
+            c if c.is_ascii_digit() && !after_ident => {
+                let len = b[i..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit() || **c == b'.')
+                    .count();
+                let end = i + len;
+                if b.get(end).is_some_and(|c| is_ident_byte(*c)) {
+                    // `1e5`, `2d`: not a plain number
+                    i = end + 1;
+                    continue;
+                }
+                let value = &sql[i..end];
+                let before = sql[..i].trim_end();
+                let position = by_list == Some(depth)
+                    && !value.contains('.')
+                    && !sql[end..].starts_with("::")
+                    && (before.ends_with(',') || before.to_ascii_lowercase().ends_with("by"));
+                if position {
+                    i = end;
+                    continue;
+                }
+                let type_name = if value.contains('.') {
+                    "numeric"
+                } else if value.parse::<i32>().is_ok() {
+                    "integer"
+                } else {
+                    "bigint"
+                };
+                let literal = typed(sql, i..end, value, type_name);
+                i = literal.range.end;
+                found.push(literal);
+            }
+            c if c.is_ascii_alphabetic() && !after_ident => {
+                let len = b[i..].iter().take_while(|c| is_ident_byte(**c)).count();
+                let word = &sql[i..i + len];
+                if word.eq_ignore_ascii_case("true") || word.eq_ignore_ascii_case("false") {
+                    let literal = typed(sql, i..i + len, word, "boolean");
+                    i = literal.range.end;
+                    found.push(literal);
+                } else {
+                    let lower = word.to_ascii_lowercase();
+                    match lower.as_str() {
+                        "by" if matches!(prev_word.as_str(), "order" | "group") => {
+                            by_list = Some(depth);
+                        }
+                        "limit" | "offset" | "having" | "window" | "fetch" | "for" | "union"
+                        | "intersect" | "except" | "returning" | "into"
+                            if by_list == Some(depth) =>
+                        {
+                            by_list = None;
+                        }
+                        _ => {}
+                    }
+                    prev_word = lower;
+                    i += len;
+                }
+            }
+            b'(' => {
+                depth += 1;
+                i += 1;
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                if by_list.is_some_and(|list| list > depth) {
+                    by_list = None;
+                }
+                i += 1;
+            }
+            b';' => {
+                by_list = None;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    found
+}
+
+/// The literal at `range`, widened over a `::type` cast after it, which
+/// then gives its type.
+fn typed(sql: &str, range: Range<usize>, value: &str, guessed: &str) -> Literal {
+    let rest = &sql[range.end..];
+    let cast = rest.strip_prefix("::").map(|after| {
+        let mut len = after
+            .bytes()
+            .take_while(|c| is_ident_byte(*c) || *c == b'[' || *c == b']')
+            .count();
+        // Type modifiers such as `varchar(20)`
+        if len > 0 && after[len..].starts_with('(') {
+            len += after[len..].find(')').map_or(0, |n| n + 1);
+        }
+        &after[..len]
+    });
+    match cast {
+        Some(type_name) if !type_name.is_empty() => Literal {
+            range: range.start..range.end + 2 + type_name.len(),
+            value: value.to_string(),
+            type_name: type_name.to_ascii_lowercase(),
+        },
+        _ => Literal {
+            range,
+            value: value.to_string(),
+            type_name: guessed.to_string(),
+        },
+    }
+}
+
+/// The start and lowercased name of a type keyword written before the
+/// string literal at `start`, as in `interval '1 day'`.
+fn type_keyword_before(sql: &str, start: usize) -> Option<(usize, String)> {
+    let before = sql[..start].trim_end();
+    let word_start = before
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(0, |n| n + 1);
+    let word = before[word_start..].to_ascii_lowercase();
+    TYPE_KEYWORDS
+        .contains(&word.as_str())
+        .then_some((word_start, word))
+}
+
+/// `date` or `timestamp` for strings that look like one, else `text`.
+fn guess_string_type(quoted: &str) -> &'static str {
+    let inner = quoted.trim_start_matches(['E', 'e']).trim_matches('\'');
+    let is_date = |s: &str| {
+        let b = s.as_bytes();
+        b.len() == 10
+            && b.iter().enumerate().all(|(ix, c)| {
+                if ix == 4 || ix == 7 {
+                    *c == b'-'
+                } else {
+                    c.is_ascii_digit()
+                }
+            })
+    };
+    match inner.len() {
+        10 if is_date(inner) => "date",
+        n if n > 10 && is_date(&inner[..10]) && matches!(inner.as_bytes()[10], b' ' | b'T') => {
+            "timestamp"
+        }
+        _ => "text",
+    }
+}
+
+pub(super) fn strip_leading_comments(sql: &str) -> &str {
+    let mut rest = sql.trim_start();
+    loop {
+        if rest.starts_with("--") {
+            rest = rest.find('\n').map_or("", |n| &rest[n + 1..]).trim_start();
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after
+                .find("*/")
+                .map_or("", |n| &after[n + 2..])
+                .trim_start();
+        } else {
+            return rest;
+        }
+    }
+}
+
+/// `name` as written when it is a plain lowercase identifier, else quoted.
+fn quote_ident(name: &str) -> String {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_queries_returning_rows() {
+        assert!(is_select("-- recent\nSELECT 1"));
+        assert!(is_select("/* c */ with x as (select 1) select * from x"));
+        assert!(!is_select("UPDATE t SET a = 1"));
+        assert!(!is_select(""));
+    }
+
+    #[test]
+    fn wraps_a_query_into_a_view() {
+        assert_eq!(
+            create_view("  SELECT * FROM orders;\n", "new_view"),
+            "CREATE VIEW new_view AS\nSELECT * FROM orders;\n"
+        );
+    }
+
+    #[test]
+    fn turns_literals_into_typed_parameters() {
+        let sql = "SELECT id, \"Total\" FROM orders -- status 'x'\n\
+                   WHERE status = 'open' AND total > 10.5 AND customer_id = 42 \
+                   AND placed_at >= '2024-01-01' AND note <> 'open' AND paid = true \
+                   AND code = '7'::char(1) AND tag = t2.tag;";
+        assert_eq!(
+            prepare_statement(sql, "new_statement"),
+            "PREPARE new_statement(text, numeric, integer, date, boolean, char(1)) AS\n\
+             SELECT id, \"Total\" FROM orders -- status 'x'\n\
+             WHERE status = $1 AND total > $2 AND customer_id = $3 \
+             AND placed_at >= $4 AND note <> $1 AND paid = $5 \
+             AND code = $6 AND tag = t2.tag;\n\n\
+             EXECUTE new_statement('open', 10.5, 42, '2024-01-01', true, '7');\n"
+        );
+    }
+
+    #[test]
+    fn builds_a_function_returning_the_query_columns() {
+        let columns = vec![
+            ("id".to_string(), "int4".to_string()),
+            ("Total".to_string(), "numeric".to_string()),
+        ];
+        assert_eq!(
+            create_function(
+                "SELECT id, total AS \"Total\" FROM orders WHERE id = 3000000000",
+                "new_function",
+                Some(&columns)
+            ),
+            "CREATE OR REPLACE FUNCTION new_function(bigint)\n\
+             RETURNS TABLE (id int4, \"Total\" numeric)\n\
+             LANGUAGE sql STABLE\n\
+             AS $function$\n\
+             SELECT id, total AS \"Total\" FROM orders WHERE id = $1\n\
+             $function$;\n\n\
+             SELECT * FROM new_function(3000000000);\n"
+        );
+        assert!(create_function("SELECT 1", "f", None).contains("RETURNS SETOF record"));
+    }
+
+    #[test]
+    fn leaves_bodies_and_identifiers_alone() {
+        assert_eq!(
+            prepare_statement("SELECT $$ 'x' $$, x1, E'a\\'b', date '2024-01-01'", "s"),
+            "PREPARE s(text, date) AS\nSELECT $$ 'x' $$, x1, $1, $2;\n\nEXECUTE s(E'a\\'b', '2024-01-01');\n"
+        );
+        assert_eq!(
+            prepare_statement("SELECT 1;", "s"),
+            "PREPARE s(integer) AS\nSELECT $1;\n\nEXECUTE s(1);\n"
+        );
+        assert_eq!(
+            prepare_statement("SELECT now()", "s"),
+            "PREPARE s AS\nSELECT now();\n\nEXECUTE s;\n"
+        );
+    }
+
+    #[test]
+    fn keeps_column_positions_in_order_and_group_by() {
+        assert_eq!(
+            prepare_statement(
+                "SELECT status, count(*) FROM orders WHERE total > 100 \
+                 GROUP BY 1 HAVING count(*) > 2 ORDER BY 2 DESC, 1 LIMIT 5",
+                "s"
+            ),
+            "PREPARE s(integer, integer, integer) AS\n\
+             SELECT status, count(*) FROM orders WHERE total > $1 \
+             GROUP BY 1 HAVING count(*) > $2 ORDER BY 2 DESC, 1 LIMIT $3;\n\n\
+             EXECUTE s(100, 2, 5);\n"
+        );
+        assert_eq!(
+            prepare_statement("SELECT a FROM t ORDER BY round(a, 2), 1", "s"),
+            "PREPARE s(integer) AS\nSELECT a FROM t ORDER BY round(a, $1), 1;\n\nEXECUTE s(2);\n"
+        );
+    }
+}
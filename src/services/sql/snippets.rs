@@ -0,0 +1,314 @@
+//! Snippets: a short prefix that Tab, or picking it from completions,
+//! expands into a statement with tab stops.
+//!
+//! In a snippet body `$1`, `$2`, ... mark where Tab moves the cursor next,
+//! in order, and `$0` where it ends up last (the end of the body when
+//! there is none). `\$` is a literal `$`, so bind parameters are written
+//! `\$1`; a `$` not followed by a digit, as in `$$`, needs no escaping.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// A prefix and the text it expands to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub prefix: String,
+    pub description: String,
+    pub body: String,
+}
+
+impl Snippet {
+    fn new(prefix: &str, description: &str, body: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            description: description.to_string(),
+            body: body.to_string(),
+        }
+    }
+}
+
+/// The snippets every editor has.
+pub fn builtin_snippets() -> Vec<Snippet> {
+    vec![
+        Snippet::new(
+            "sel",
+            "SELECT ... FROM ... WHERE",
+            "SELECT $1 FROM $2 WHERE $3;$0",
+        ),
+        Snippet::new("selc", "SELECT count(*)", "SELECT count(*) FROM $1;$0"),
+        Snippet::new(
+            "ins",
+            "INSERT INTO ... VALUES",
+            "INSERT INTO $1 ($2) VALUES ($3);$0",
+        ),
+        Snippet::new(
+            "upd",
+            "UPDATE ... SET ... WHERE",
+            "UPDATE $1 SET $2 WHERE $3;$0",
+        ),
+        Snippet::new("del", "DELETE FROM ... WHERE", "DELETE FROM $1 WHERE $2;$0"),
+        Snippet::new("join", "JOIN ... ON", "JOIN $1 ON $2$0"),
+        Snippet::new(
+            "case",
+            "CASE WHEN ... END",
+            "CASE WHEN $1 THEN $2 ELSE $3 END$0",
+        ),
+        Snippet::new(
+            "cte",
+            "WITH ... AS (...) SELECT",
+            "WITH $1 AS (\n  $2\n)\nSELECT $3;$0",
+        ),
+        Snippet::new("ct", "CREATE TABLE", "CREATE TABLE $1 (\n  $2\n);$0"),
+        Snippet::new("ci", "CREATE INDEX", "CREATE INDEX ON $1 ($2);$0"),
+        Snippet::new("tx", "BEGIN ... COMMIT", "BEGIN;\n$1\nCOMMIT;$0"),
+        Snippet::new("do", "DO block", "DO $$\nBEGIN\n  $1\nEND\n$$;$0"),
+        Snippet::new(
+            "fn",
+            "CREATE FUNCTION (PL/pgSQL)",
+            "CREATE OR REPLACE FUNCTION $1($2)\nRETURNS $3\nLANGUAGE plpgsql AS $$\nBEGIN\n  $4\nEND\n$$;$0",
+        ),
+    ]
+}
+
+/// A snippet body with its tab stops taken out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetExpansion {
+    pub text: String,
+    /// Byte offsets into `text` in the order Tab visits them, the final
+    /// one last.
+    pub stops: Vec<usize>,
+}
+
+/// Take the tab stops out of `body`.
+pub fn expand_snippet(body: &str) -> SnippetExpansion {
+    let mut text = String::with_capacity(body.len());
+    let mut numbered: Vec<(u32, usize)> = vec![];
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'$') => text.push(chars.next().unwrap_or('$')),
+            '$' if chars.peek().is_some_and(char::is_ascii_digit) => {
+                let mut number = 0;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    number = number * 10 + digit;
+                    chars.next();
+                }
+                numbered.push((number, text.len()));
+            }
+            c => text.push(c),
+        }
+    }
+
+    // `$0` is visited last; each other number once, at its first place
+    let end = numbered
+        .iter()
+        .find(|(number, _)| *number == 0)
+        .map_or(text.len(), |(_, offset)| *offset);
+    numbered.retain(|(number, _)| *number != 0);
+    numbered.sort_by_key(|(number, _)| *number);
+    numbered.dedup_by_key(|(number, _)| *number);
+    let mut stops: Vec<usize> = numbered.into_iter().map(|(_, offset)| offset).collect();
+    stops.push(end);
+    SnippetExpansion { text, stops }
+}
+
+/// The snippet whose prefix is the word ending at `offset`, and where that
+/// word starts. The word must not run on past the cursor.
+pub fn snippet_at<'a>(
+    snippets: &'a [Snippet],
+    text: &str,
+    offset: usize,
+) -> Option<(usize, &'a Snippet)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    if text[offset..].chars().next().is_some_and(is_word) {
+        return None;
+    }
+    let before = &text[..offset];
+    let start = before
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map(|(i, _)| i)?;
+    if before[..start].ends_with('.') {
+        return None;
+    }
+    let word = &before[start..];
+    snippets
+        .iter()
+        .find(|snippet| snippet.prefix == word)
+        .map(|snippet| (start, snippet))
+}
+
+/// What changed from `before` to `after`: the range of `before` that was
+/// replaced, and how many bytes replaced it.
+pub fn edit_between(before: &str, after: &str) -> (Range<usize>, usize) {
+    let prefix = before
+        .char_indices()
+        .zip(after.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(before.len().min(after.len()), |((i, _), _)| i);
+    let suffix = before[prefix..]
+        .chars()
+        .rev()
+        .zip(after[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum::<usize>();
+    (prefix..before.len() - suffix, after.len() - suffix - prefix)
+}
+
+/// The snippet a completion just expanded in place of its prefix, or the
+/// start of it, going from `before` to `after`: where the expansion starts
+/// and its tab stops.
+pub fn inserted_snippet(
+    snippets: &[Snippet],
+    before: &str,
+    after: &str,
+) -> Option<(usize, SnippetExpansion)> {
+    let (range, inserted) = edit_between(before, after);
+    let end = range.start + inserted;
+    snippets.iter().find_map(|snippet| {
+        let expansion = expand_snippet(&snippet.body);
+        let start = end.checked_sub(expansion.text.len())?;
+        let word = before
+            .get(start..range.end)
+            .filter(|word| !word.is_empty())?;
+        (start <= range.start
+            && snippet.prefix.starts_with(word)
+            && after.get(start..end) == Some(expansion.text.as_str()))
+        .then_some((start, expansion))
+    })
+}
+
+/// Tab stops of an expanded snippet, followed through later edits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetSession {
+    stops: Vec<usize>,
+    current: usize,
+}
+
+impl SnippetSession {
+    /// Stops of `expansion` inserted at `at`, at the first one.
+    pub fn new(at: usize, expansion: &SnippetExpansion) -> Self {
+        Self {
+            stops: expansion.stops.iter().map(|stop| at + stop).collect(),
+            current: 0,
+        }
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.stops[self.current]
+    }
+
+    /// Whether `offset` lies within the expanded snippet.
+    pub fn contains(&self, offset: usize) -> bool {
+        let first = self.stops.iter().min().copied().unwrap_or_default();
+        let last = self.stops.iter().max().copied().unwrap_or_default();
+        (first..=last).contains(&offset)
+    }
+
+    /// Whether the cursor is at the final stop, where the session ends.
+    pub fn is_done(&self) -> bool {
+        self.current + 1 >= self.stops.len()
+    }
+
+    /// Move to the next stop.
+    pub fn next_stop(&mut self) -> Option<usize> {
+        (!self.is_done()).then(|| {
+            self.current += 1;
+            self.cursor()
+        })
+    }
+
+    /// Move back to the previous stop.
+    pub fn previous_stop(&mut self) -> Option<usize> {
+        (self.current > 0).then(|| {
+            self.current -= 1;
+            self.cursor()
+        })
+    }
+
+    /// Follow an edit replacing `range` of the text with `inserted` bytes.
+    /// Stops after it move along; text typed at a stop goes before the
+    /// stops still ahead. `false` when the edit falls outside the snippet,
+    /// which ends the session.
+    pub fn edited(&mut self, range: Range<usize>, inserted: usize) -> bool {
+        if !self.contains(range.start) || !self.contains(range.end) {
+            return false;
+        }
+        for (ix, stop) in self.stops.iter_mut().enumerate() {
+            if *stop > range.start || (*stop == range.start && ix > self.current) {
+                *stop = stop.saturating_sub(range.len()).max(range.start) + inserted;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_out_tab_stops() {
+        let expansion = expand_snippet("SELECT $1 FROM $2 WHERE $3;$0");
+        assert_eq!(expansion.text, "SELECT  FROM  WHERE ;");
+        assert_eq!(expansion.stops, vec![7, 13, 20, 21]);
+
+        let expansion = expand_snippet("DO $$ BEGIN $2 END $$; SELECT \\$1, $1");
+        assert_eq!(expansion.text, "DO $$ BEGIN  END $$; SELECT $1, ");
+        assert_eq!(expansion.stops, vec![32, 12, 32]);
+    }
+
+    #[test]
+    fn finds_the_prefix_before_the_cursor() {
+        let snippets = builtin_snippets();
+        let text = "sel\nfoo.sel selx";
+        assert_eq!(snippet_at(&snippets, text, 3).map(|(at, _)| at), Some(0));
+        assert!(snippet_at(&snippets, text, 11).is_none());
+        assert!(snippet_at(&snippets, text, 14).is_none());
+        assert!(snippet_at(&snippets, text, 2).is_none());
+    }
+
+    #[test]
+    fn finds_what_an_edit_changed() {
+        assert_eq!(edit_between("SELECT  FROM", "SELECT id FROM"), (7..7, 2));
+        assert_eq!(edit_between("a, b, c", "a, c"), (3..6, 0));
+        assert_eq!(edit_between("aa", "aaa"), (2..2, 1));
+        assert_eq!(edit_between("é", "e"), (0..2, 1));
+    }
+
+    #[test]
+    fn recognizes_expansions_picked_from_completions() {
+        let snippets = builtin_snippets();
+        let before = "SELECT 1;\nse";
+        let after = "SELECT 1;\nSELECT  FROM  WHERE ;";
+        let (start, expansion) = inserted_snippet(&snippets, before, after).unwrap();
+        assert_eq!(start, 10);
+        assert_eq!(expansion.stops[0], 7);
+
+        // Typed rather than completed
+        assert!(inserted_snippet(&snippets, "SELECT", "SELECT ").is_none());
+        assert!(inserted_snippet(&snippets, "x", "x SELECT  FROM  WHERE ;").is_none());
+    }
+
+    #[test]
+    fn stops_follow_what_is_typed() {
+        let expansion = expand_snippet("SELECT $1 FROM $2;$0");
+        let mut session = SnippetSession::new(10, &expansion);
+        assert_eq!(session.cursor(), 17);
+
+        // `id, name` typed at the first stop
+        assert!(session.edited(17..17, 8));
+        assert_eq!(session.next_stop(), Some(31));
+        assert!(session.edited(31..31, 5));
+        assert_eq!(session.next_stop(), Some(37));
+        assert!(session.is_done());
+        assert_eq!(session.previous_stop(), Some(31));
+
+        // An edit before the snippet ends it
+        assert!(!session.edited(0..1, 0));
+    }
+}
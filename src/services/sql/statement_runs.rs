@@ -0,0 +1,352 @@
+//! The last run of each statement, from query history.
+//!
+//! The editor annotates the statement at the cursor with how it went the
+//! last time it ran on the connection. Statements are matched on their
+//! text with whitespace collapsed and the terminator dropped, so
+//! reformatting a query keeps its annotation.
+//!
+//! Runs are recorded one entry per statement, tagged with the job that ran
+//! them, so a history entry can be reopened with the result it produced.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::services::storage::{QueryHistoryEntry, relative_time};
+use crate::services::{
+    ErrorResult, ModifiedResult, QueryExecutionResult, QueryResult, ResultColumnMetadata,
+    ResultRows,
+};
+
+/// History entries indexed per connection for statement annotations.
+pub const STATEMENT_RUN_HISTORY_LIMIT: u32 = 500;
+
+/// How a statement went the last time it ran.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementRun {
+    pub execution_time_ms: i64,
+    /// Rows returned or affected, when known.
+    pub rows: Option<i64>,
+    pub success: bool,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Last runs by normalized statement text.
+#[derive(Debug, Clone, Default)]
+pub struct StatementRuns {
+    runs: HashMap<String, StatementRun>,
+}
+
+impl StatementRuns {
+    /// Index `entries`, most recent first as history loads them.
+    pub fn new(entries: &[QueryHistoryEntry]) -> Self {
+        let mut runs = HashMap::new();
+        for entry in entries {
+            runs.entry(normalize_statement(&entry.sql))
+                .or_insert_with(|| StatementRun {
+                    execution_time_ms: entry.execution_time_ms,
+                    rows: entry.rows_affected,
+                    success: entry.success,
+                    executed_at: entry.executed_at,
+                });
+        }
+        Self { runs }
+    }
+
+    /// Note a run that just finished.
+    pub fn record(&mut self, sql: &str, run: StatementRun) {
+        self.runs.insert(normalize_statement(sql), run);
+    }
+
+    pub fn last_run(&self, sql: &str) -> Option<&StatementRun> {
+        self.runs.get(&normalize_statement(sql))
+    }
+}
+
+impl StatementRun {
+    /// The run that produced `result`, finishing at `executed_at`.
+    pub fn from_result(result: &QueryExecutionResult, executed_at: DateTime<Utc>) -> Self {
+        let (execution_time_ms, rows, success) = match result {
+            QueryExecutionResult::Select(r) => {
+                (r.execution_time_ms, Some(r.row_count as i64), true)
+            }
+            QueryExecutionResult::Modified(m) => {
+                (m.execution_time_ms, Some(m.rows_affected as i64), true)
+            }
+            QueryExecutionResult::Error(e) => (e.execution_time_ms, None, false),
+        };
+        Self {
+            execution_time_ms: execution_time_ms as i64,
+            rows,
+            success,
+            executed_at,
+        }
+    }
+
+    /// One-line summary relative to `now`, e.g. `Ran 5 mins ago · 12 ms · 40 rows`.
+    pub fn summary(&self, now: DateTime<Utc>) -> String {
+        let when = relative_time(self.executed_at, now);
+        let mut summary = if self.success {
+            format!("Ran {} · {} ms", when, self.execution_time_ms)
+        } else {
+            format!("Failed {} after {} ms", when, self.execution_time_ms)
+        };
+        if let Some(rows) = self.rows.filter(|_| self.success) {
+            summary.push_str(&format!(
+                " · {} row{}",
+                rows,
+                if rows == 1 { "" } else { "s" }
+            ));
+        }
+        summary
+    }
+}
+
+/// The history entry for statement `statement_index` of run `run_id`, or
+/// for the whole run when it is a lone statement.
+pub fn history_entry(
+    connection_id: Uuid,
+    run_id: Uuid,
+    statement_index: Option<i64>,
+    sql: &str,
+    result: &QueryExecutionResult,
+    executed_at: DateTime<Utc>,
+) -> QueryHistoryEntry {
+    let run = StatementRun::from_result(result, executed_at);
+    let (error_message, result_columns) = match result {
+        QueryExecutionResult::Select(r) => {
+            let columns: Vec<(&str, &str)> = r
+                .columns
+                .iter()
+                .map(|c| (c.name.as_str(), c.type_name.as_str()))
+                .collect();
+            (None, serde_json::to_string(&columns).ok())
+        }
+        QueryExecutionResult::Modified(_) => (None, None),
+        QueryExecutionResult::Error(e) => (Some(e.message.clone()), None),
+    };
+    QueryHistoryEntry {
+        id: Uuid::new_v4(),
+        connection_id,
+        sql: sql.to_string(),
+        execution_time_ms: run.execution_time_ms,
+        rows_affected: run.rows,
+        success: run.success,
+        error_message,
+        executed_at,
+        run_id: Some(run_id),
+        statement_index,
+        result_columns,
+    }
+}
+
+/// What history kept of an entry's result: the columns it returned without
+/// their rows, the rows it changed, or its error.
+pub fn captured_result(entry: &QueryHistoryEntry) -> QueryExecutionResult {
+    let execution_time_ms = entry.execution_time_ms.max(0) as u128;
+    if !entry.success {
+        return QueryExecutionResult::Error(ErrorResult {
+            message: entry.error_message.clone().unwrap_or_default(),
+            execution_time_ms,
+        });
+    }
+    let columns = entry.columns();
+    if columns.is_empty() {
+        return QueryExecutionResult::Modified(ModifiedResult {
+            rows_affected: entry.rows_affected.unwrap_or(0).max(0) as u64,
+            execution_time_ms,
+        });
+    }
+    QueryExecutionResult::Select(QueryResult {
+        columns: columns
+            .into_iter()
+            .enumerate()
+            .map(|(ordinal, (name, type_name))| ResultColumnMetadata {
+                name,
+                type_name,
+                ordinal,
+                table_name: None,
+                is_nullable: None,
+                source_column: None,
+            })
+            .collect(),
+        rows: ResultRows::from_text(vec![]),
+        row_count: 0,
+        execution_time_ms,
+        original_query: entry.sql.clone(),
+        truncated_at: None,
+    })
+}
+
+/// `sql` with runs of whitespace collapsed and the trailing `;` dropped.
+fn normalize_statement(sql: &str) -> String {
+    let collapsed = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed.trim_end_matches(';').trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn entry(
+        sql: &str,
+        ms: i64,
+        rows: Option<i64>,
+        success: bool,
+        at: DateTime<Utc>,
+    ) -> QueryHistoryEntry {
+        QueryHistoryEntry {
+            id: Uuid::new_v4(),
+            connection_id: Uuid::nil(),
+            sql: sql.to_string(),
+            execution_time_ms: ms,
+            rows_affected: rows,
+            success,
+            error_message: None,
+            executed_at: at,
+            run_id: None,
+            statement_index: None,
+            result_columns: None,
+        }
+    }
+
+    #[test]
+    fn matches_statements_ignoring_layout() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let runs = StatementRuns::new(&[
+            entry(
+                "SELECT *\n  FROM users;",
+                12,
+                Some(40),
+                true,
+                now - Duration::minutes(5),
+            ),
+            entry(
+                "SELECT * FROM users",
+                900,
+                Some(1),
+                true,
+                now - Duration::days(2),
+            ),
+        ]);
+
+        let run = runs.last_run("SELECT * FROM users").unwrap();
+        assert_eq!(run.execution_time_ms, 12);
+        assert_eq!(run.summary(now), "Ran 5 mins ago · 12 ms · 40 rows");
+        assert!(runs.last_run("SELECT * FROM orders").is_none());
+    }
+
+    #[test]
+    fn summarizes_failures_and_new_runs() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut runs = StatementRuns::default();
+        runs.record(
+            "DELETE FROM users",
+            StatementRun {
+                execution_time_ms: 3,
+                rows: None,
+                success: false,
+                executed_at: now - Duration::days(9),
+            },
+        );
+        assert_eq!(
+            runs.last_run("DELETE FROM users;").unwrap().summary(now),
+            "Failed Apr 22, 2024 after 3 ms"
+        );
+
+        runs.record(
+            "DELETE FROM users;",
+            StatementRun {
+                execution_time_ms: 4,
+                rows: Some(1),
+                success: true,
+                executed_at: now,
+            },
+        );
+        assert_eq!(
+            runs.last_run("DELETE FROM users").unwrap().summary(now),
+            "Ran just now · 4 ms · 1 row"
+        );
+    }
+
+    #[test]
+    fn captures_results_per_statement() {
+        let at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let run_id = Uuid::new_v4();
+        let select = QueryExecutionResult::Select(QueryResult {
+            columns: vec![ResultColumnMetadata {
+                name: "id".to_string(),
+                type_name: "int4".to_string(),
+                ordinal: 0,
+                table_name: Some("users".to_string()),
+                is_nullable: Some(false),
+                source_column: None,
+            }],
+            rows: ResultRows::from_text(vec![vec![Some("1".to_string())]]),
+            row_count: 1,
+            execution_time_ms: 7,
+            original_query: "SELECT id FROM users".to_string(),
+            truncated_at: None,
+        });
+        let entry = history_entry(
+            Uuid::nil(),
+            run_id,
+            Some(1),
+            "SELECT id FROM users",
+            &select,
+            at,
+        );
+        assert_eq!(entry.run_id, Some(run_id));
+        assert_eq!(entry.statement_index, Some(1));
+        assert_eq!(entry.rows_affected, Some(1));
+        match captured_result(&entry) {
+            QueryExecutionResult::Select(r) => {
+                assert_eq!(r.columns.len(), 1);
+                assert_eq!(r.columns[0].name, "id");
+                assert_eq!(r.columns[0].type_name, "int4");
+                assert_eq!(r.execution_time_ms, 7);
+                assert!(r.rows.is_empty());
+            }
+            other => panic!("expected a select, got {:?}", other),
+        }
+
+        let error = QueryExecutionResult::Error(ErrorResult {
+            message: "relation \"nope\" does not exist".to_string(),
+            execution_time_ms: 2,
+        });
+        let entry = history_entry(
+            Uuid::nil(),
+            run_id,
+            Some(2),
+            "SELECT * FROM nope",
+            &error,
+            at,
+        );
+        assert!(!entry.success);
+        match captured_result(&entry) {
+            QueryExecutionResult::Error(e) => {
+                assert_eq!(e.message, "relation \"nope\" does not exist")
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+
+        let modified = QueryExecutionResult::Modified(ModifiedResult {
+            rows_affected: 3,
+            execution_time_ms: 4,
+        });
+        let entry = history_entry(
+            Uuid::nil(),
+            run_id,
+            None,
+            "DELETE FROM users",
+            &modified,
+            at,
+        );
+        match captured_result(&entry) {
+            QueryExecutionResult::Modified(m) => assert_eq!(m.rows_affected, 3),
+            other => panic!("expected a modification, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Splitting a script into its statements.
+//!
+//! Semicolons inside strings, quoted identifiers, comments and (on
+//! Postgres) dollar-quoted bodies do not end a statement, so `DO` blocks
+//! and PL/pgSQL function bodies stay whole.
+//!
+//! On Postgres, psql meta-commands (`\set`, `\c`, `\timing`, ...) on
+//! lines of their own are skipped, so scripts written for psql can be
+//! pasted as they are. A meta-command ends the statement before it, like
+//! `\g` does in psql.
+
+use std::ops::Range;
+
+use crate::services::storage::DatabaseDriver;
+
+use super::params::{is_escape_string, is_ident_byte, skip_dollar_quoted, skip_line, skip_quoted};
+
+/// The statements of `sql`, trimmed and without their `;`. Chunks holding
+/// only whitespace and comments are dropped.
+pub fn split_statements(driver: &DatabaseDriver, sql: &str) -> Vec<String> {
+    statement_ranges(driver, sql)
+        .into_iter()
+        .map(|range| sql[range].to_string())
+        .collect()
+}
+
+/// Byte ranges of the statements of `sql`, as split by
+/// [`split_statements`].
+pub fn statement_ranges(driver: &DatabaseDriver, sql: &str) -> Vec<Range<usize>> {
+    let b = sql.as_bytes();
+    let mysql = *driver == DatabaseDriver::MySql;
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut has_code = false;
+    let mut i = 0;
+
+    let mut push = |start: usize, end: usize| {
+        let text = &sql[start..end];
+        let trimmed = text.trim_start();
+        let from = start + text.len() - trimmed.len();
+        ranges.push(from..from + trimmed.trim_end().len());
+    };
+
+    while i < b.len() {
+        match b[i] {
+            b'-' if b.get(i + 1) == Some(&b'-') => i = skip_line(b, i),
+            b'#' if mysql => i = skip_line(b, i),
+            b'\\' if !mysql && at_line_start(b, i) => {
+                if has_code {
+                    push(start, i);
+                }
+                has_code = false;
+                i = skip_line(b, i);
+                start = i;
+            }
+            b'/' if b.get(i + 1) == Some(&b'*') => {
+                i = match sql[i + 2..].find("*/") {
+                    Some(end) => i + 2 + end + 2,
+                    None => b.len(),
+                };
+            }
+            b';' => {
+                if has_code {
+                    push(start, i);
+                }
+                has_code = false;
+                i += 1;
+                start = i;
+            }
+            c => {
+                has_code |= !c.is_ascii_whitespace();
+                i = match c {
+                    b'\'' => skip_quoted(b, i, mysql || is_escape_string(b, i)),
+                    b'"' => skip_quoted(b, i, mysql),
+                    b'`' if mysql => skip_quoted(b, i, false),
+                    b'$' if !mysql && !(i > 0 && is_ident_byte(b[i - 1])) => {
+                        skip_dollar_quoted(sql, i)
+                    }
+                    _ => i + 1,
+                };
+            }
+        }
+    }
+    if has_code {
+        push(start, b.len());
+    }
+    ranges
+}
+
+/// Whether only spaces and tabs come before `i` on its line.
+fn at_line_start(b: &[u8], i: usize) -> bool {
+    b[..i]
+        .iter()
+        .rev()
+        .take_while(|c| **c != b'\n')
+        .all(|c| *c == b' ' || *c == b'\t')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_semicolons() {
+        let sql = "SELECT 1;\n\nSELECT 2 ;  -- trailing comment\n/* only a comment */;";
+        assert_eq!(
+            split_statements(&DatabaseDriver::Postgres, sql),
+            vec!["SELECT 1", "SELECT 2"]
+        );
+        assert_eq!(
+            split_statements(&DatabaseDriver::Postgres, "SELECT 1"),
+            vec!["SELECT 1"]
+        );
+        assert!(split_statements(&DatabaseDriver::Postgres, " ; -- nothing").is_empty());
+    }
+
+    #[test]
+    fn ignores_semicolons_in_strings_comments_and_bodies() {
+        let sql = "SELECT ';', \"a;b\" FROM t -- x;y\n; \
+                   CREATE FUNCTION f() RETURNS int AS $body$ BEGIN RETURN 1; END $body$ LANGUAGE plpgsql;\
+                   SELECT $1::int";
+        let statements = split_statements(&DatabaseDriver::Postgres, sql);
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], "SELECT ';', \"a;b\" FROM t -- x;y");
+        assert!(statements[1].ends_with("LANGUAGE plpgsql"));
+        assert_eq!(statements[2], "SELECT $1::int");
+    }
+
+    #[test]
+    fn backslashes_escape_only_in_e_strings() {
+        let sql = "SELECT E'it\\'s; here' AS x; SELECT 'a\\'; SELECT 2";
+        assert_eq!(
+            split_statements(&DatabaseDriver::Postgres, sql),
+            vec!["SELECT E'it\\'s; here' AS x", "SELECT 'a\\'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn keeps_do_blocks_whole() {
+        let sql = "DO $$\nDECLARE n int;\nBEGIN\n  SELECT 1 INTO n;\n  RAISE NOTICE '%', n;\nEND\n$$;\nSELECT 2;";
+        let statements = split_statements(&DatabaseDriver::Postgres, sql);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].starts_with("DO $$") && statements[0].ends_with("$$"));
+        assert_eq!(statements[1], "SELECT 2");
+    }
+
+    #[test]
+    fn skips_psql_meta_commands() {
+        let sql =
+            "\\set ON_ERROR_STOP on\n\\c analytics\nSELECT 1\n\\gx\n  \\timing\nSELECT '\\x';";
+        assert_eq!(
+            split_statements(&DatabaseDriver::Postgres, sql),
+            vec!["SELECT 1", "SELECT '\\x'"]
+        );
+        let ranges = statement_ranges(&DatabaseDriver::Postgres, sql);
+        assert_eq!(&sql[ranges[0].clone()], "SELECT 1");
+    }
+
+    #[test]
+    fn mysql_quoting() {
+        let sql = "SELECT `a;b`, 'it\\'s;' FROM t # x;y\n; SELECT 2";
+        assert_eq!(
+            split_statements(&DatabaseDriver::MySql, sql),
+            vec!["SELECT `a;b`, 'it\\'s;' FROM t # x;y", "SELECT 2"]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Which column a string literal being typed is compared to.
+//!
+//! Used to complete enum labels in `WHERE status = '` and
+//! `WHERE status IN ('a', '`, and to tell when a paste lands in an empty
+//! `IN (` list.
+
+/// The column compared to a string literal opened at the end of `before`
+/// (the text up to, not including, the opening quote), when the statement
+/// has a `WHERE` clause. Qualifiers and identifier quotes are dropped:
+/// `o."status"` gives `status`.
+pub fn compared_column(before: &str) -> Option<String> {
+    let statement = before.rsplit(';').next().unwrap_or(before);
+    if !has_keyword(statement, "where") {
+        return None;
+    }
+
+    let text = statement.trim_end();
+    let operand = if let Some(rest) = strip_operator(text) {
+        rest
+    } else {
+        // Inside `IN (`, possibly after earlier literals.
+        let open = text.rfind('(')?;
+        let list = &text[open + 1..];
+        if !is_literal_list(list) {
+            return None;
+        }
+        let rest = text[..open].trim_end();
+        let rest = strip_keyword_suffix(rest, "in")?;
+        let rest = rest.trim_end();
+        strip_keyword_suffix(rest, "not").unwrap_or(rest)
+    };
+
+    let identifier = trailing_identifier(operand.trim_end())?;
+    let column = identifier.rsplit('.').next()?.trim_matches(['"', '`']);
+    (!column.is_empty()).then(|| column.to_string())
+}
+
+/// Whether `before` (the text up to the cursor) ends inside the empty
+/// parentheses of an `IN` or `NOT IN` list.
+pub fn opens_in_list(before: &str) -> bool {
+    let statement = before.rsplit(';').next().unwrap_or(before);
+    statement
+        .trim_end()
+        .strip_suffix('(')
+        .and_then(|rest| strip_keyword_suffix(rest.trim_end(), "in"))
+        .is_some()
+}
+
+fn strip_operator(text: &str) -> Option<&str> {
+    ["<>", "!=", "="]
+        .iter()
+        .find_map(|op| text.strip_suffix(op))
+}
+
+/// Whether `list` is empty or a series of closed string literals each
+/// followed by a comma.
+fn is_literal_list(list: &str) -> bool {
+    let mut in_literal = false;
+    let mut expect_comma = false;
+    for c in list.chars() {
+        match (in_literal, c) {
+            (true, '\'') => {
+                in_literal = false;
+                expect_comma = true;
+            }
+            (true, _) => {}
+            (false, '\'') if !expect_comma => in_literal = true,
+            (false, ',') if expect_comma => expect_comma = false,
+            (false, c) if c.is_whitespace() => {}
+            _ => return false,
+        }
+    }
+    !in_literal && !expect_comma
+}
+
+fn strip_keyword_suffix<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let split = text.len().checked_sub(keyword.len())?;
+    let (rest, word) = text.split_at_checked(split)?;
+    let bounded = rest
+        .chars()
+        .next_back()
+        .is_none_or(|c| !is_identifier_char(c));
+    (word.eq_ignore_ascii_case(keyword) && bounded).then_some(rest)
+}
+
+fn trailing_identifier(text: &str) -> Option<&str> {
+    let start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_identifier_char(*c) || matches!(c, '.' | '"' | '`'))
+        .last()
+        .map(|(i, _)| i)?;
+    Some(&text[start..])
+}
+
+fn has_keyword(text: &str, keyword: &str) -> bool {
+    text.split(|c: char| !is_identifier_char(c))
+        .any(|word| word.eq_ignore_ascii_case(keyword))
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparisons_in_where() {
+        assert_eq!(
+            compared_column("SELECT * FROM orders WHERE status = ").as_deref(),
+            Some("status")
+        );
+        assert_eq!(
+            compared_column("select * from orders o where o.\"status\"<>").as_deref(),
+            Some("status")
+        );
+        assert_eq!(
+            compared_column("SELECT 1; DELETE FROM t WHERE a = 1 AND mood != ").as_deref(),
+            Some("mood")
+        );
+    }
+
+    #[test]
+    fn in_lists() {
+        assert_eq!(
+            compared_column("SELECT * FROM t WHERE mood IN (").as_deref(),
+            Some("mood")
+        );
+        assert_eq!(
+            compared_column("SELECT * FROM t WHERE mood not in ('sad', ").as_deref(),
+            Some("mood")
+        );
+        // Still inside the first literal.
+        assert_eq!(
+            compared_column("SELECT * FROM t WHERE mood IN ('sad").as_deref(),
+            None
+        );
+        assert_eq!(compared_column("SELECT * FROM t WHERE coalesce("), None);
+    }
+
+    #[test]
+    fn empty_in_lists() {
+        assert!(opens_in_list("SELECT * FROM t WHERE id IN ("));
+        assert!(opens_in_list("select * from t where id not in (\n  "));
+        assert!(opens_in_list("SELECT * FROM t JOIN u ON u.id in("));
+        assert!(!opens_in_list("SELECT * FROM t WHERE id IN (1, "));
+        assert!(!opens_in_list("SELECT * FROM t WHERE login("));
+        assert!(!opens_in_list("SELECT count("));
+    }
+
+    #[test]
+    fn needs_a_where_clause() {
+        assert_eq!(compared_column("UPDATE t SET mood = "), None);
+        assert_eq!(
+            compared_column("SELECT * FROM t WHERE a = 1; SELECT x = "),
+            None
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! `{{name}}` template variables in editor queries.
+//!
+//! Variables are substituted as plain text before a query runs, from the
+//! active environment (`dev`, `staging`, `prod`, ...). Only variables in
+//! the code are: inside strings, quoted identifiers, comments and
+//! dollar-quoted bodies `{{name}}` is text, so a function body or a JSON
+//! value keeps its braces. A value that is a string carries its quotes,
+//! e.g. `'acme'` for `WHERE tenant = {{tenant}}`. Braces around anything
+//! but a name are left alone.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::services::storage::DatabaseDriver;
+
+use super::pairs::in_literal;
+use super::params::is_ident_byte;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct QueryVariable {
+    pub name: String,
+    pub value: String,
+}
+
+/// A named set of variable values, e.g. `staging`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VariableEnvironment {
+    pub name: String,
+    pub values: Vec<QueryVariable>,
+}
+
+impl VariableEnvironment {
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|v| v.name == name)
+            .map(|v| v.value.as_str())
+    }
+}
+
+/// The variable environments of a workspace and the one queries use.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryVariables {
+    pub environments: Vec<VariableEnvironment>,
+    /// Name of the active environment.
+    pub active: Option<String>,
+}
+
+impl QueryVariables {
+    pub fn active_environment(&self) -> Option<&VariableEnvironment> {
+        let active = self.active.as_deref()?;
+        self.environments.iter().find(|env| env.name == active)
+    }
+
+    /// `sql` with its variables replaced by the active environment's
+    /// values. Fails, naming every variable concerned, when one is not
+    /// defined there.
+    pub fn substitute(&self, driver: &DatabaseDriver, sql: &str) -> Result<String> {
+        let names = template_variables(driver, sql);
+        if names.is_empty() {
+            return Ok(sql.to_string());
+        }
+        let Some(env) = self.active_environment() else {
+            return Err(anyhow!(
+                "This query uses {}, but no variable environment is selected. \
+                 Pick or create one under Variables in the editor toolbar.",
+                list(&names)
+            ));
+        };
+
+        let undefined: Vec<String> = names
+            .into_iter()
+            .filter(|name| env.value(name).is_none())
+            .collect();
+        if !undefined.is_empty() {
+            return Err(anyhow!(
+                "Undefined variable{} {} in environment \"{}\". \
+                 Define {} under Variables in the editor toolbar.",
+                if undefined.len() == 1 { "" } else { "s" },
+                list(&undefined),
+                env.name,
+                if undefined.len() == 1 { "it" } else { "them" }
+            ));
+        }
+
+        let mut substituted = String::with_capacity(sql.len());
+        let mut last = 0;
+        scan(driver, sql, |start, end, name| {
+            substituted.push_str(&sql[last..start]);
+            substituted.push_str(env.value(name).unwrap_or_default());
+            last = end;
+        });
+        substituted.push_str(&sql[last..]);
+        Ok(substituted)
+    }
+}
+
+/// Distinct variables of `sql`, in order of first use.
+pub fn template_variables(driver: &DatabaseDriver, sql: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    scan(driver, sql, |_, _, name| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    });
+    names
+}
+
+fn list(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| format!("{{{{{}}}}}", name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Call `f` with the byte range and name of each `{{name}}` in the code,
+/// not in a string, quoted identifier, comment or dollar-quoted body.
+fn scan<'a>(driver: &DatabaseDriver, sql: &'a str, mut f: impl FnMut(usize, usize, &'a str)) {
+    let mut i = 0;
+    while let Some(found) = sql[i..].find("{{") {
+        let start = i + found;
+        match variable_at(sql, start).filter(|_| !in_literal(driver, sql, start)) {
+            Some((end, name)) => {
+                f(start, end, name);
+                i = end;
+            }
+            None => i = start + 1,
+        }
+    }
+}
+
+/// The end and name of a `{{ name }}` opening at `start`.
+fn variable_at(sql: &str, start: usize) -> Option<(usize, &str)> {
+    let close = start + 2 + sql[start + 2..].find("}}")?;
+    let name = sql[start + 2..close].trim();
+    let valid = name
+        .bytes()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == b'_')
+        && name.bytes().all(is_ident_byte);
+    valid.then_some((close + 2, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(values: &[(&str, &str)]) -> QueryVariables {
+        QueryVariables {
+            environments: vec![VariableEnvironment {
+                name: "dev".to_string(),
+                values: values
+                    .iter()
+                    .map(|(name, value)| QueryVariable {
+                        name: name.to_string(),
+                        value: value.to_string(),
+                    })
+                    .collect(),
+            }],
+            active: Some("dev".to_string()),
+        }
+    }
+
+    #[test]
+    fn finds_variables_in_code() {
+        let pg = DatabaseDriver::Postgres;
+        let sql = "SELECT * FROM {{schema}}.orders -- {{ignored}}\n\
+                   WHERE tenant = {{ tenant }} /* {{also_ignored}} */ \
+                   AND tags = '{{1,2},{3,4}}' AND region = {{tenant}}";
+        assert_eq!(template_variables(&pg, sql), vec!["schema", "tenant"]);
+        assert!(template_variables(&pg, "SELECT {{}}, {{1}}").is_empty());
+    }
+
+    #[test]
+    fn skips_strings_and_dollar_quoted_bodies() {
+        let pg = DatabaseDriver::Postgres;
+        let sql = "SELECT '{{in_string}}', \"{{in_identifier}}\", {{used}};\n\
+                   CREATE FUNCTION f() RETURNS text AS $body$\n\
+                   SELECT '{{in_body}}' || {{also_in_body}}\n\
+                   $body$ LANGUAGE sql";
+        assert_eq!(template_variables(&pg, sql), vec!["used"]);
+
+        let vars = variables(&[("used", "1")]);
+        assert_eq!(
+            vars.substitute(&pg, "SELECT {{used}}, '{{used}}', $${{used}}$$")
+                .unwrap(),
+            "SELECT 1, '{{used}}', $${{used}}$$"
+        );
+
+        // MySQL has no dollar quoting, and backticks quote identifiers
+        let mysql = DatabaseDriver::MySql;
+        assert_eq!(
+            template_variables(&mysql, "SELECT `{{a}}`, '{{b}}', $${{c}}$$"),
+            vec!["c"]
+        );
+    }
+
+    #[test]
+    fn substitutes_the_active_environment() {
+        let pg = DatabaseDriver::Postgres;
+        let vars = variables(&[("schema", "app"), ("tenant", "'acme'")]);
+        assert_eq!(
+            vars.substitute(
+                &pg,
+                "SELECT * FROM {{schema}}.t WHERE tenant = {{ tenant }}"
+            )
+            .unwrap(),
+            "SELECT * FROM app.t WHERE tenant = 'acme'"
+        );
+        assert_eq!(vars.substitute(&pg, "SELECT 1").unwrap(), "SELECT 1");
+    }
+
+    #[test]
+    fn names_undefined_variables() {
+        let pg = DatabaseDriver::Postgres;
+        let vars = variables(&[("schema", "app")]);
+        let err = vars
+            .substitute(&pg, "SELECT {{a}}, {{schema}}, {{b}}, {{a}}")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("Undefined variables {{a}}, {{b}} in environment \"dev\"."),
+            "{}",
+            err
+        );
+
+        let none = QueryVariables::default();
+        let err = none
+            .substitute(&pg, "SELECT {{a}}")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("no variable environment is selected"),
+            "{}",
+            err
+        );
+    }
+}
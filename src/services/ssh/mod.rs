@@ -8,8 +8,11 @@
 //! Authentication is key-based:
 //! - private key file (optional passphrase)
 //! - SSH agent (via `SSH_AUTH_SOCK`, when available on the platform)
+//!
+//! Connections through the same SSH server share one session.
 
 mod config;
+mod sessions;
 mod tunnel;
 
 pub use config::{SshAuth, SshConfig};
@@ -0,0 +1,189 @@
+//! SSH sessions shared between tunnels.
+//!
+//! Connections that go through the same SSH server (host, port and user)
+//! share one authenticated session, each with its own local forward, so
+//! the key or agent is only asked once. The registry only holds weak
+//! references: a session closes when the last tunnel using it is dropped.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use anyhow::Result;
+
+use super::config::SshConfig;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SessionKey {
+    host: String,
+    port: u16,
+    username: String,
+}
+
+impl SessionKey {
+    pub(crate) fn for_config(cfg: &SshConfig) -> Self {
+        Self {
+            host: cfg.host.to_lowercase(),
+            port: cfg.port,
+            username: cfg.username.clone(),
+        }
+    }
+}
+
+pub(crate) struct SessionRegistry<T> {
+    sessions: Mutex<HashMap<SessionKey, Weak<T>>>,
+}
+
+impl<T> SessionRegistry<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The live session for `key`, or a new one from `open`. A session
+    /// `is_alive` rejects is replaced. `is_alive` runs without the lock, so
+    /// a slow server does not hold up lookups for the others; the lock is
+    /// held while opening so two tunnels to the same server never both
+    /// authenticate.
+    pub(crate) fn get_or_open(
+        &self,
+        key: SessionKey,
+        is_alive: impl Fn(&T) -> bool,
+        open: impl FnOnce() -> Result<T>,
+    ) -> Result<Arc<T>> {
+        let existing = {
+            let mut sessions = self.lock();
+            sessions.retain(|_, session| session.strong_count() > 0);
+            sessions.get(&key).and_then(Weak::upgrade)
+        };
+        if let Some(session) = &existing
+            && is_alive(session)
+        {
+            tracing::debug!("ssh: reusing session to {}:{}", key.host, key.port);
+            return Ok(session.clone());
+        }
+
+        let mut sessions = self.lock();
+        // Another tunnel may have opened a session meanwhile
+        let opened = sessions
+            .get(&key)
+            .and_then(Weak::upgrade)
+            .filter(|session| {
+                !existing
+                    .as_ref()
+                    .is_some_and(|dead| Arc::ptr_eq(dead, session))
+            });
+        if let Some(session) = opened {
+            return Ok(session);
+        }
+
+        let session = Arc::new(open()?);
+        sessions.insert(key, Arc::downgrade(&session));
+        Ok(session)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<SessionKey, Weak<T>>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        let sessions = self.sessions.lock().unwrap();
+        sessions.values().filter(|s| s.strong_count() > 0).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn key(host: &str, user: &str) -> SessionKey {
+        SessionKey::for_config(&SshConfig {
+            host: host.to_string(),
+            port: 22,
+            username: user.to_string(),
+            ..SshConfig::default()
+        })
+    }
+
+    #[test]
+    fn same_server_shares_one_session() {
+        let registry = SessionRegistry::new();
+        let opened = Cell::new(0);
+        let open = || {
+            opened.set(opened.get() + 1);
+            Ok(opened.get())
+        };
+
+        let a = registry
+            .get_or_open(key("bastion", "deploy"), |_| true, open)
+            .unwrap();
+        let b = registry
+            .get_or_open(key("BASTION", "deploy"), |_| true, open)
+            .unwrap();
+        let c = registry
+            .get_or_open(key("bastion", "admin"), |_| true, open)
+            .unwrap();
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(opened.get(), 2);
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn session_closes_with_its_last_tunnel() {
+        let registry = SessionRegistry::new();
+        let first = registry
+            .get_or_open(key("bastion", "deploy"), |_| true, || Ok(1))
+            .unwrap();
+        drop(first);
+        assert_eq!(registry.len(), 0);
+
+        let second = registry
+            .get_or_open(key("bastion", "deploy"), |_| true, || Ok(2))
+            .unwrap();
+        assert_eq!(*second, 2);
+    }
+
+    #[test]
+    fn dead_session_is_replaced() {
+        let registry = SessionRegistry::new();
+        let _stale = registry
+            .get_or_open(key("bastion", "deploy"), |_| true, || Ok(1))
+            .unwrap();
+        let fresh = registry
+            .get_or_open(key("bastion", "deploy"), |s| *s != 1, || Ok(2))
+            .unwrap();
+        assert_eq!(*fresh, 2);
+    }
+
+    #[test]
+    fn checks_liveness_without_the_lock() {
+        let registry = SessionRegistry::new();
+        let _first = registry
+            .get_or_open(key("bastion", "deploy"), |_| true, || Ok(1))
+            .unwrap();
+        // Would deadlock if the registry were still locked
+        let again = registry
+            .get_or_open(key("bastion", "deploy"), |_| registry.len() == 1, || Ok(2))
+            .unwrap();
+        assert_eq!(*again, 1);
+    }
+
+    #[test]
+    fn failed_open_is_not_cached() {
+        let registry: SessionRegistry<u32> = SessionRegistry::new();
+        assert!(
+            registry
+                .get_or_open(
+                    key("bastion", "deploy"),
+                    |_| true,
+                    || Err(anyhow::anyhow!("auth failed"))
+                )
+                .is_err()
+        );
+        assert_eq!(registry.len(), 0);
+    }
+}
@@ -9,12 +9,14 @@
 //!   bytes between the local socket and the channel.
 //! - Dropping the [`SshTunnel`] signals the worker thread to exit and
 //!   tears down all resources.
+//! - Tunnels through the same SSH server share one session (see
+//!   [`super::sessions`]), which closes with the last of them.
 
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
@@ -22,6 +24,16 @@ use anyhow::{anyhow, Context, Result};
 use ssh2::Session;
 
 use super::config::{SshAuth, SshConfig};
+use super::sessions::{SessionKey, SessionRegistry};
+
+/// Seconds between keepalives on idle sessions. Also how a shared session
+/// is found to be dead before it is reused.
+const KEEPALIVE_INTERVAL: u32 = 30;
+
+fn sessions() -> &'static SessionRegistry<Session> {
+    static SESSIONS: OnceLock<SessionRegistry<Session>> = OnceLock::new();
+    SESSIONS.get_or_init(SessionRegistry::new)
+}
 
 /// A live SSH tunnel.
 ///
@@ -32,6 +44,8 @@ pub struct SshTunnel {
     local_port: u16,
     shutdown: Arc<AtomicBool>,
     worker: Option<JoinHandle<()>>,
+    /// Shared with other tunnels through the same SSH server.
+    session: Arc<Session>,
 }
 
 impl SshTunnel {
@@ -40,7 +54,16 @@ impl SshTunnel {
         self.local_port
     }
 
-    /// Establish a new SSH session and start forwarding.
+    /// Whether the forwarding thread is running and the SSH session still
+    /// answers a keepalive.
+    pub fn is_alive(&self) -> bool {
+        self.worker.as_ref().is_some_and(|w| !w.is_finished())
+            && self.session.authenticated()
+            && self.session.keepalive_send().is_ok()
+    }
+
+    /// Start forwarding over the session to `cfg`'s server, opening and
+    /// authenticating one unless another tunnel already has it open.
     ///
     /// `remote_host`/`remote_port` is the target as seen from the SSH
     /// server (typically the database host on its private network).
@@ -53,7 +76,11 @@ impl SshTunnel {
     ) -> Result<Self> {
         // Open and authenticate the SSH session synchronously so that
         // connection failures surface immediately to the caller.
-        let session = open_session(cfg, passphrase.as_deref())?;
+        let session = sessions().get_or_open(
+            SessionKey::for_config(cfg),
+            |session| session.authenticated() && session.keepalive_send().is_ok(),
+            || open_session(cfg, passphrase.as_deref()),
+        )?;
 
         // Bind a local listener on an ephemeral port.
         let listener = TcpListener::bind("127.0.0.1:0")
@@ -70,11 +97,12 @@ impl SshTunnel {
         let shutdown = Arc::new(AtomicBool::new(false));
         let shutdown_for_worker = shutdown.clone();
         let remote = (remote_host, remote_port);
+        let session_for_worker = session.clone();
 
         let worker = thread::Builder::new()
             .name(format!("ssh-tunnel:{}", local_port))
             .spawn(move || {
-                run_tunnel(listener, session, remote, shutdown_for_worker);
+                run_tunnel(listener, &session_for_worker, remote, shutdown_for_worker);
             })
             .context("Failed to spawn SSH tunnel worker thread")?;
 
@@ -82,6 +110,7 @@ impl SshTunnel {
             local_port,
             shutdown,
             worker: Some(worker),
+            session,
         })
     }
 }
@@ -165,13 +194,14 @@ fn open_session(cfg: &SshConfig, passphrase: Option<&str>) -> Result<Session> {
     if !session.authenticated() {
         return Err(anyhow!("SSH authentication did not complete"));
     }
+    session.set_keepalive(false, KEEPALIVE_INTERVAL);
 
     Ok(session)
 }
 
 fn run_tunnel(
     listener: TcpListener,
-    session: Session,
+    session: &Session,
     remote: (String, u16),
     shutdown: Arc<AtomicBool>,
 ) {
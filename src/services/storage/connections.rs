@@ -1,24 +1,24 @@
-//! Connection repository using SQLite and system keyring.
+//! Connection repository using SQLite and the active credential store.
 //!
-//! Layout of secrets in the system keyring (service `pgui`):
+//! Layout of secrets in the system keyring (service `pgui`) or the vault:
 //! - `<connection-id>`              -> database password
 //! - `<connection-id>:ssh-keypass`  -> SSH private-key passphrase (optional)
 
 use anyhow::{Context, Result};
-use keyring::Entry;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use super::secrets::{self, CredentialStore};
 use super::types::{ConnectionInfo, DatabaseDriver, SslMode};
 use crate::services::ssh::{SshAuth, SshConfig};
 
-const KEYRING_SERVICE: &str = "pgui";
 const SSH_KEYPASS_SUFFIX: &str = ":ssh-keypass";
 
 /// Repository for connection CRUD operations.
 ///
-/// Passwords are stored securely in the system keyring, while connection
-/// metadata (host, port, username, etc.) is stored in SQLite.
+/// Passwords are stored in the system keyring or the encrypted vault,
+/// while connection metadata (host, port, username, etc.) is stored in
+/// SQLite.
 #[derive(Debug, Clone)]
 pub struct ConnectionsRepository {
     pool: SqlitePool,
@@ -41,39 +41,33 @@ type ConnRow = (
     Option<String>, // ssh_username
     Option<String>, // ssh_auth_type
     Option<String>, // ssh_key_path
+    i64,            // tag_queries
+    Option<String>, // startup_sql
 );
 
 const SELECT_COLS: &str = "id, name, driver, hostname, username, database, port, ssl_mode, \
-     ssh_enabled, ssh_host, ssh_port, ssh_username, ssh_auth_type, ssh_key_path";
+     ssh_enabled, ssh_host, ssh_port, ssh_username, ssh_auth_type, ssh_key_path, tag_queries, \
+     startup_sql";
 
 impl ConnectionsRepository {
     pub(crate) fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
 
-    // ========== Keyring Methods ==========
-
-    fn keyring_entry(key: &str) -> Result<Entry> {
-        Entry::new(KEYRING_SERVICE, key).context("Failed to create keyring entry")
-    }
+    // ========== Secret Methods ==========
 
     fn store_password(connection_id: &Uuid, password: &str) -> Result<()> {
-        let entry = Self::keyring_entry(&connection_id.to_string())?;
-        entry
-            .set_password(password)
-            .context("Failed to store password in keyring")
+        secrets::set(&connection_id.to_string(), password).context("Failed to store password")
     }
 
     fn get_password(connection_id: &Uuid) -> Result<String> {
-        let entry = Self::keyring_entry(&connection_id.to_string())?;
-        entry
-            .get_password()
-            .context("Failed to retrieve password from keyring")
+        secrets::get(&connection_id.to_string())
+            .context("Failed to retrieve password")?
+            .context("No password stored for this connection")
     }
 
     fn delete_password(connection_id: &Uuid) -> Result<()> {
-        let entry = Self::keyring_entry(&connection_id.to_string())?;
-        let _ = entry.delete_credential();
+        let _ = secrets::delete(&connection_id.to_string());
         Ok(())
     }
 
@@ -84,27 +78,40 @@ impl ConnectionsRepository {
     /// Store an SSH key passphrase for a connection. Pass an empty string
     /// to clear it.
     pub fn store_ssh_key_passphrase(connection_id: &Uuid, passphrase: &str) -> Result<()> {
-        let entry = Self::keyring_entry(&Self::ssh_keypass_key(connection_id))?;
+        let key = Self::ssh_keypass_key(connection_id);
         if passphrase.is_empty() {
-            let _ = entry.delete_credential();
+            let _ = secrets::delete(&key);
             Ok(())
         } else {
-            entry
-                .set_password(passphrase)
-                .context("Failed to store SSH key passphrase in keyring")
+            secrets::set(&key, passphrase).context("Failed to store SSH key passphrase")
         }
     }
 
     /// Retrieve an SSH key passphrase for a connection, if one is stored.
     pub fn get_ssh_key_passphrase(connection_id: &Uuid) -> Option<String> {
-        let entry = Self::keyring_entry(&Self::ssh_keypass_key(connection_id)).ok()?;
-        entry.get_password().ok()
+        secrets::get(&Self::ssh_keypass_key(connection_id))
+            .ok()
+            .flatten()
     }
 
     fn delete_ssh_key_passphrase(connection_id: &Uuid) {
-        if let Ok(entry) = Self::keyring_entry(&Self::ssh_keypass_key(connection_id)) {
-            let _ = entry.delete_credential();
-        }
+        let _ = secrets::delete(&Self::ssh_keypass_key(connection_id));
+    }
+
+    /// Move every saved password and SSH key passphrase into `to` and make
+    /// it the store used from now on.
+    pub async fn move_secrets(&self, to: CredentialStore) -> Result<()> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM connections")
+            .fetch_all(&self.pool)
+            .await?;
+        let keys = ids
+            .into_iter()
+            .flat_map(|id| {
+                let keypass = format!("{}{}", id, SSH_KEYPASS_SUFFIX);
+                [id, keypass]
+            })
+            .collect::<Vec<_>>();
+        secrets::move_to(to, &keys)
     }
 
     // ========== Mapping Helpers ==========
@@ -125,6 +132,8 @@ impl ConnectionsRepository {
             ssh_username,
             ssh_auth_type,
             ssh_key_path,
+            tag_queries,
+            startup_sql,
         ) = row;
 
         let id = Uuid::parse_str(&id_str).context("Invalid UUID in database")?;
@@ -157,6 +166,9 @@ impl ConnectionsRepository {
             port: port as usize,
             ssl_mode: SslMode::from_db_str(&ssl_mode_str),
             ssh,
+            tag_queries: tag_queries != 0,
+            startup_sql: startup_sql.filter(|sql| !sql.trim().is_empty()),
+            search_path: None,
         })
     }
 
@@ -227,9 +239,9 @@ impl ConnectionsRepository {
             INSERT INTO connections (
                 id, name, driver, hostname, username, database, port, ssl_mode,
                 ssh_enabled, ssh_host, ssh_port, ssh_username, ssh_auth_type, ssh_key_path,
-                updated_at
+                tag_queries, startup_sql, updated_at
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, CURRENT_TIMESTAMP)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, CURRENT_TIMESTAMP)
             "#,
         )
         .bind(connection.id.to_string())
@@ -246,6 +258,8 @@ impl ConnectionsRepository {
         .bind(ssh_user)
         .bind(ssh_auth_type)
         .bind(ssh_key_path)
+        .bind(connection.tag_queries as i64)
+        .bind(&connection.startup_sql)
         .execute(&self.pool)
         .await?;
 
@@ -289,7 +303,7 @@ impl ConnectionsRepository {
                 port = ?7, ssl_mode = ?8,
                 ssh_enabled = ?9, ssh_host = ?10, ssh_port = ?11,
                 ssh_username = ?12, ssh_auth_type = ?13, ssh_key_path = ?14,
-                updated_at = CURRENT_TIMESTAMP
+                tag_queries = ?15, startup_sql = ?16, updated_at = CURRENT_TIMESTAMP
             WHERE id = ?1
             "#,
         )
@@ -307,6 +321,8 @@ impl ConnectionsRepository {
         .bind(ssh_user)
         .bind(ssh_auth_type)
         .bind(ssh_key_path)
+        .bind(connection.tag_queries as i64)
+        .bind(&connection.startup_sql)
         .execute(&self.pool)
         .await?;
 
@@ -325,7 +341,6 @@ impl ConnectionsRepository {
     }
 
     /// Get a single connection by ID
-    #[allow(dead_code)]
     pub async fn get(&self, id: &Uuid) -> Result<Option<ConnectionInfo>> {
         let sql = format!(
             "SELECT {} FROM connections WHERE id = ?1",
@@ -342,11 +357,83 @@ impl ConnectionsRepository {
         }
     }
 
-    /// Get password for a connection from keyring (on-demand)
+    /// Get password for a connection from the credential store (on-demand)
     pub fn get_connection_password(connection_id: &Uuid) -> Result<String> {
         Self::get_password(connection_id)
     }
 
+    /// Replace a connection's stored database password, e.g. after it was
+    /// rotated on the server.
+    pub fn update_connection_password(connection_id: &Uuid, password: &str) -> Result<()> {
+        Self::store_password(connection_id, password)
+    }
+
+    // ========== Usage Methods ==========
+
+    /// Record that a connection was just opened.
+    pub async fn mark_used(&self, id: &Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE connections SET last_used_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = ?1",
+        )
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// IDs of the connections opened most recently, newest first.
+    pub async fn recently_used(&self, limit: usize) -> Result<Vec<Uuid>> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM connections WHERE last_used_at IS NOT NULL ORDER BY last_used_at DESC LIMIT ?1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).context("Invalid UUID in database"))
+            .collect()
+    }
+
+    /// The connection opened when pgui starts, if any.
+    pub async fn auto_connect(&self) -> Result<Option<Uuid>> {
+        let id: Option<String> =
+            sqlx::query_scalar("SELECT id FROM connections WHERE auto_connect = 1 LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        id.map(|id| Uuid::parse_str(&id).context("Invalid UUID in database"))
+            .transpose()
+    }
+
+    /// Open `id` when pgui starts, instead of any other connection; `None`
+    /// turns auto-connect off.
+    pub async fn set_auto_connect(&self, id: Option<&Uuid>) -> Result<()> {
+        sqlx::query("UPDATE connections SET auto_connect = CASE WHEN id = ?1 THEN 1 ELSE 0 END")
+            .bind(id.map(|id| id.to_string()))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// IDs of the connections marked as production databases.
+    pub async fn production_connections(&self) -> Result<Vec<Uuid>> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM connections WHERE production = 1")
+            .fetch_all(&self.pool)
+            .await?;
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).context("Invalid UUID in database"))
+            .collect()
+    }
+
+    /// Mark `id` as a production database, shown with the danger accent.
+    pub async fn set_production(&self, id: &Uuid, production: bool) -> Result<()> {
+        sqlx::query("UPDATE connections SET production = ?2 WHERE id = ?1")
+            .bind(id.to_string())
+            .bind(production)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     /// Check if a connection with the given name exists
     pub async fn exists_by_name(&self, name: &str) -> Result<bool> {
         let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM connections WHERE name = ?1")
@@ -1,10 +1,178 @@
 use anyhow::{Context, Result};
-use chrono::{NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use super::types::QueryHistoryEntry;
 
+/// How SQLite's `datetime('now')` stores `executed_at`.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Recent entries searched for runs of the same query.
+const TIMELINE_SCAN_LIMIT: u32 = 5000;
+
+/// When a query ran, relative to `now`: `just now`, `5 mins ago`, ... and
+/// the date after a week.
+pub fn relative_time(executed_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let duration = now.signed_duration_since(executed_at);
+
+    if duration.num_seconds() < 60 {
+        "just now".to_string()
+    } else if duration.num_minutes() < 60 {
+        let mins = duration.num_minutes();
+        format!("{} min{} ago", mins, if mins == 1 { "" } else { "s" })
+    } else if duration.num_hours() < 24 {
+        let hours = duration.num_hours();
+        format!("{} hour{} ago", hours, if hours == 1 { "" } else { "s" })
+    } else if duration.num_days() < 7 {
+        let days = duration.num_days();
+        format!("{} day{} ago", days, if days == 1 { "" } else { "s" })
+    } else {
+        executed_at.format("%b %d, %Y").to_string()
+    }
+}
+
+/// `sql` reduced to its shape, so runs with different values compare
+/// equal: string, dollar-quoted and numeric literals become `?`, lists of
+/// them collapse to one, comments are dropped, whitespace is collapsed and
+/// everything outside quoted identifiers is lowercased.
+pub fn normalize_query(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let push_space = |out: &mut String| {
+        if !out.is_empty() && !out.ends_with(' ') {
+            out.push(' ');
+        }
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            push_space(&mut out);
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            push_space(&mut out);
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            push_space(&mut out);
+        } else if c == '\'' {
+            // '' inside a string is an escaped quote
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '\'' {
+                    if chars.get(i + 1) == Some(&'\'') {
+                        i += 2;
+                        continue;
+                    }
+                    break;
+                }
+                i += 1;
+            }
+            i += 1;
+            out.push('?');
+        } else if c == '"' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&c| c == '"')
+                .map_or(chars.len(), |p| i + 2 + p);
+            out.extend(&chars[i..end]);
+            i = end;
+        } else if c == '$' && !out.chars().last().is_some_and(is_ident) {
+            // $tag$ ... $tag$, as opposed to a $1 placeholder
+            let tag_end = chars[i + 1..]
+                .iter()
+                .position(|&c| !is_ident(c))
+                .map(|p| i + 1 + p);
+            match tag_end.filter(|&end| chars[end] == '$' && !chars[i + 1].is_ascii_digit()) {
+                Some(end) => {
+                    let tag: String = chars[i..=end].iter().collect();
+                    let body: String = chars[end + 1..].iter().collect();
+                    i = match body.find(&tag) {
+                        Some(p) => end + 1 + body[..p].chars().count() + tag.chars().count(),
+                        None => chars.len(),
+                    };
+                    out.push('?');
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            }
+        } else if c.is_ascii_digit() && !out.chars().last().is_some_and(|c| is_ident(c) || c == '$')
+        {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            out.push('?');
+        } else {
+            out.extend(c.to_lowercase());
+            i += 1;
+        }
+    }
+
+    let mut normalized = out.trim().trim_end_matches(';').trim_end().to_string();
+    for list in ["?, ?", "?,?", "? ,?", "? , ?"] {
+        while normalized.contains(list) {
+            normalized = normalized.replace(list, "?");
+        }
+    }
+    normalized
+}
+
+type HistoryRow = (
+    String,
+    String,
+    String,
+    i64,
+    Option<i64>,
+    bool,
+    Option<String>,
+    String,
+    Option<String>,
+    Option<i64>,
+    Option<String>,
+);
+
+fn entry_from_row(row: HistoryRow) -> Result<QueryHistoryEntry> {
+    let (
+        id,
+        conn_id,
+        sql,
+        exec_time,
+        rows,
+        success,
+        err,
+        executed_at,
+        run_id,
+        statement_index,
+        result_columns,
+    ) = row;
+    Ok(QueryHistoryEntry {
+        id: Uuid::parse_str(&id).context("Invalid UUID")?,
+        connection_id: Uuid::parse_str(&conn_id).context("Invalid connection UUID")?,
+        sql,
+        execution_time_ms: exec_time,
+        rows_affected: rows,
+        success,
+        error_message: err,
+        executed_at: NaiveDateTime::parse_from_str(&executed_at, TIMESTAMP_FORMAT)
+            .map(|dt| dt.and_utc())
+            .unwrap_or_else(|_| Utc::now()),
+        run_id: run_id.and_then(|id| Uuid::parse_str(&id).ok()),
+        statement_index,
+        result_columns,
+    })
+}
+
 /// Repository for query history operations.
 #[derive(Debug, Clone)]
 pub struct QueryHistoryRepository {
@@ -53,12 +221,38 @@ impl QueryHistoryRepository {
         connection_id: &Uuid,
         limit: u32,
     ) -> Result<Vec<QueryHistoryEntry>> {
-        let rows = sqlx::query_as::<_, (String, String, String, i64, Option<i64>, bool, Option<String>, String)>(
+        let rows = sqlx::query_as::<_, HistoryRow>(
             r#"
-            SELECT id, connection_id, sql, execution_time_ms, rows_affected, success, error_message, executed_at
+            SELECT id, connection_id, sql, execution_time_ms, rows_affected, success, error_message, executed_at,
+                   run_id, statement_index, result_columns
             FROM query_history
             WHERE connection_id = ?
-            ORDER BY executed_at DESC
+            ORDER BY executed_at DESC, rowid DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(connection_id.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(entry_from_row).collect()
+    }
+
+    /// Distinct SQL of a connection's successful queries, most recently run
+    /// first.
+    pub async fn successful_queries(
+        &self,
+        connection_id: &Uuid,
+        limit: u32,
+    ) -> Result<Vec<String>> {
+        let queries: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT sql
+            FROM query_history
+            WHERE connection_id = ? AND success = 1
+            GROUP BY sql
+            ORDER BY MAX(executed_at) DESC, MAX(rowid) DESC
             LIMIT ?
             "#,
         )
@@ -67,28 +261,92 @@ impl QueryHistoryRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        rows.into_iter()
-            .map(
-                |(id, conn_id, sql, exec_time, rows, success, err, executed_at)| {
-                    Ok(QueryHistoryEntry {
-                        id: Uuid::parse_str(&id).context("Invalid UUID")?,
-                        connection_id: Uuid::parse_str(&conn_id)
-                            .context("Invalid connection UUID")?,
-                        sql,
-                        execution_time_ms: exec_time,
-                        rows_affected: rows,
-                        success,
-                        error_message: err,
-                        executed_at: NaiveDateTime::parse_from_str(
-                            &executed_at,
-                            "%Y-%m-%d %H:%M:%S",
-                        )
-                        .map(|dt| dt.and_utc())
-                        .unwrap_or_else(|_| Utc::now()),
-                    })
-                },
+        Ok(queries)
+    }
+
+    /// Successful runs of `sql` and of queries that differ from it only in
+    /// literal values, oldest first, at most `limit` of the latest.
+    pub async fn timeline(
+        &self,
+        connection_id: &Uuid,
+        sql: &str,
+        limit: usize,
+    ) -> Result<Vec<QueryHistoryEntry>> {
+        let normalized = normalize_query(sql);
+        let recent = self
+            .load_for_connection(connection_id, TIMELINE_SCAN_LIMIT)
+            .await?;
+        let mut runs: Vec<QueryHistoryEntry> = recent
+            .into_iter()
+            .filter(|entry| entry.success && normalize_query(&entry.sql) == normalized)
+            .take(limit)
+            .collect();
+        runs.reverse();
+        Ok(runs)
+    }
+
+    /// All history, or one connection's, oldest first.
+    pub async fn export(&self, connection_id: Option<&Uuid>) -> Result<Vec<QueryHistoryEntry>> {
+        let rows = sqlx::query_as::<_, HistoryRow>(
+            r#"
+            SELECT id, connection_id, sql, execution_time_ms, rows_affected, success, error_message, executed_at,
+                   run_id, statement_index, result_columns
+            FROM query_history
+            WHERE ? IS NULL OR connection_id = ?
+            ORDER BY executed_at ASC, rowid ASC
+            "#,
+        )
+        .bind(connection_id.map(|id| id.to_string()))
+        .bind(connection_id.map(|id| id.to_string()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(entry_from_row).collect()
+    }
+
+    /// Record the statements of one run, each as its own entry linked to
+    /// the run. Entries keep their ids and timestamps.
+    pub async fn record_entries(&self, entries: &[QueryHistoryEntry]) -> Result<()> {
+        self.insert(entries).await?;
+        Ok(())
+    }
+
+    /// Insert exported entries, keeping their ids and timestamps. Entries
+    /// already present are skipped, so importing a file twice is harmless.
+    /// Returns the number of entries added.
+    pub async fn import(&self, entries: &[QueryHistoryEntry]) -> Result<u64> {
+        self.insert(entries).await
+    }
+
+    async fn insert(&self, entries: &[QueryHistoryEntry]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut imported = 0;
+        for entry in entries {
+            let result = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO query_history
+                    (id, connection_id, sql, execution_time_ms, rows_affected, success, error_message, executed_at,
+                     run_id, statement_index, result_columns)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
             )
-            .collect()
+            .bind(entry.id.to_string())
+            .bind(entry.connection_id.to_string())
+            .bind(&entry.sql)
+            .bind(entry.execution_time_ms)
+            .bind(entry.rows_affected)
+            .bind(entry.success)
+            .bind(&entry.error_message)
+            .bind(entry.executed_at.format(TIMESTAMP_FORMAT).to_string())
+            .bind(entry.run_id.map(|id| id.to_string()))
+            .bind(entry.statement_index)
+            .bind(&entry.result_columns)
+            .execute(&mut *tx)
+            .await?;
+            imported += result.rows_affected();
+        }
+        tx.commit().await?;
+        Ok(imported)
     }
 
     /// Clear history for a connection
@@ -34,6 +34,10 @@
 //! - Renames / deletes / `exists_by_name` semantics.
 //! - Updating a connection through CRUD.
 //! - SSH key passphrase keyring helpers.
+//! - Workspace state key/value round-trips.
+//! - Scheduled queries, including the recorded last run.
+//! - Schema snapshots, skipping ones equal to the latest.
+//! - Recently used connections and the one opened at startup.
 //!
 //! What we deliberately don't cover here:
 //! - Live database connections (PG, MySQL) — that requires Docker and
@@ -53,7 +57,10 @@ use tempfile::TempDir;
 use uuid::Uuid;
 
 use super::connections::ConnectionsRepository;
-use super::types::{ConnectionInfo, DatabaseDriver, SslMode};
+use super::history::normalize_query;
+use super::scheduled_queries::{ScheduleFormat, ScheduledQuery};
+use super::table_sizes::{SAMPLE_RETENTION_DAYS, TableSizeSample, table_growth};
+use super::types::{ConnectionInfo, DatabaseDriver, QueryHistoryEntry, SslMode};
 use super::AppStore;
 use crate::services::ssh::{SshAuth, SshConfig};
 
@@ -183,6 +190,11 @@ fn fresh_database_has_all_columns() {
             "ssh_username",
             "ssh_auth_type",
             "ssh_key_path",
+            "tag_queries",
+            "startup_sql",
+            "auto_connect",
+            "last_used_at",
+            "production",
         ] {
             let sql = format!("SELECT {} FROM connections LIMIT 1", col);
             sqlx::query(&sql)
@@ -252,6 +264,11 @@ fn migration_from_legacy_schema_adds_all_columns() {
             "ssh_username",
             "ssh_auth_type",
             "ssh_key_path",
+            "tag_queries",
+            "startup_sql",
+            "auto_connect",
+            "last_used_at",
+            "production",
         ] {
             let sql = format!("SELECT {} FROM connections LIMIT 1", col);
             sqlx::query(&sql)
@@ -267,6 +284,8 @@ fn migration_from_legacy_schema_adds_all_columns() {
         assert_eq!(c.name, "legacy-pg");
         assert_eq!(c.driver, DatabaseDriver::Postgres, "driver default");
         assert!(c.ssh.is_none(), "legacy row should have no SSH");
+        assert!(!c.tag_queries, "legacy row should not tag queries");
+        assert!(c.startup_sql.is_none(), "legacy row has no startup SQL");
         assert_eq!(c.port, 5432);
     });
 }
@@ -306,6 +325,9 @@ fn create_load_postgres_no_ssh_roundtrip() {
             port: 5432,
             ssl_mode: SslMode::Require,
             ssh: None,
+            tag_queries: false,
+            startup_sql: None,
+            search_path: None,
         };
         repo.create(&info).await.unwrap();
 
@@ -351,6 +373,9 @@ fn create_load_mysql_with_ssh_keyfile_roundtrip() {
                     path: "/Users/me/.ssh/id_ed25519".to_string(),
                 },
             }),
+            tag_queries: false,
+            startup_sql: None,
+            search_path: None,
         };
         repo.create(&info).await.unwrap();
 
@@ -392,6 +417,9 @@ fn create_load_mysql_with_ssh_agent() {
                 username: "ops".to_string(),
                 auth: SshAuth::Agent,
             }),
+            tag_queries: false,
+            startup_sql: None,
+            search_path: None,
         };
         repo.create(&info).await.unwrap();
 
@@ -442,6 +470,9 @@ fn update_changes_driver_and_ssh_fields() {
             port: 5432,
             ssl_mode: SslMode::Prefer,
             ssh: None,
+            tag_queries: false,
+            startup_sql: None,
+            search_path: None,
         };
         repo.create(&info).await.unwrap();
 
@@ -454,12 +485,19 @@ fn update_changes_driver_and_ssh_fields() {
             username: "me".to_string(),
             auth: SshAuth::Agent,
         });
+        info.tag_queries = true;
+        info.startup_sql = Some("SET search_path TO app, public".to_string());
         repo.update(&info).await.unwrap();
 
         let loaded = repo.load_all().await.unwrap();
         let l = &loaded[0];
         assert_eq!(l.driver, DatabaseDriver::MySql);
         assert_eq!(l.port, 3306);
+        assert!(l.tag_queries);
+        assert_eq!(
+            l.startup_sql.as_deref(),
+            Some("SET search_path TO app, public")
+        );
         let ssh = l.ssh.as_ref().unwrap();
         assert_eq!(ssh.host, "ssh.example");
         assert!(matches!(ssh.auth, SshAuth::Agent));
@@ -537,3 +575,459 @@ fn exists_by_name_is_case_sensitive_and_scoped() {
         assert!(!repo.exists_by_name("Staging").await.unwrap());
     });
 }
+
+#[test]
+fn workspace_state_roundtrip_and_overwrite() {
+    smol::block_on(async {
+        let (_dir, store) = fresh_store().await;
+        let repo = store.workspace_state();
+
+        assert_eq!(repo.get("window").await.unwrap(), None);
+
+        repo.set("window", "first").await.unwrap();
+        assert_eq!(repo.get("window").await.unwrap().as_deref(), Some("first"));
+
+        repo.set("window", "second").await.unwrap();
+        assert_eq!(repo.get("window").await.unwrap().as_deref(), Some("second"));
+
+        repo.set_json("numbers", &vec![1, 2, 3]).await.unwrap();
+        assert_eq!(
+            repo.get_json::<Vec<i32>>("numbers").await.unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        // Unparseable JSON reads back as missing rather than an error.
+        assert_eq!(repo.get_json::<Vec<i32>>("window").await.unwrap(), None);
+
+        repo.delete("window").await.unwrap();
+        assert_eq!(repo.get("window").await.unwrap(), None);
+    });
+}
+
+#[test]
+fn saved_workspaces_upsert_by_name_and_delete() {
+    smol::block_on(async {
+        let (_dir, store) = fresh_store().await;
+        let repo = store.workspaces();
+
+        assert!(repo.load_all().await.unwrap().is_empty());
+
+        repo.save("Reporting", &vec!["first"]).await.unwrap();
+        repo.save("Billing", &vec!["other"]).await.unwrap();
+        repo.save("Reporting", &vec!["second"]).await.unwrap();
+
+        let saved = repo.load_all().await.unwrap();
+        let names: Vec<&str> = saved.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["Billing", "Reporting"]);
+        assert_eq!(
+            saved[1].snapshot::<Vec<String>>(),
+            Some(vec!["second".to_string()])
+        );
+        // A snapshot in an older shape reads back as missing.
+        assert_eq!(saved[1].snapshot::<u32>(), None);
+
+        repo.delete(&saved[0].id).await.unwrap();
+        let saved = repo.load_all().await.unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name, "Reporting");
+    });
+}
+
+#[test]
+fn history_export_import_roundtrip_skips_existing_entries() {
+    smol::block_on(async {
+        let (_dir, source) = fresh_store().await;
+        let mut info = ConnectionInfo::default();
+        info.name = "history-source".to_string();
+        source.connections().create(&info).await.unwrap();
+
+        let history = source.history();
+        history
+            .record(&info.id, "SELECT 1", 3, Some(1), true, None)
+            .await
+            .unwrap();
+        history
+            .record(&info.id, "SELEC 2", 1, None, false, Some("syntax error"))
+            .await
+            .unwrap();
+
+        let exported = history.export(Some(&info.id)).await.unwrap();
+        assert_eq!(exported.len(), 2);
+        let other = history.export(Some(&Uuid::new_v4())).await.unwrap();
+        assert!(other.is_empty());
+        assert_eq!(history.export(None).await.unwrap().len(), 2);
+
+        let (_dir2, target) = fresh_store().await;
+        target.connections().create(&info).await.unwrap();
+        let imported_history = target.history();
+        assert_eq!(imported_history.import(&exported).await.unwrap(), 2);
+        // Importing the same file again adds nothing.
+        assert_eq!(imported_history.import(&exported).await.unwrap(), 0);
+
+        let mut loaded = imported_history
+            .load_for_connection(&info.id, 10)
+            .await
+            .unwrap();
+        loaded.sort_by_key(|e| e.sql.clone());
+        let mut expected = exported.clone();
+        expected.sort_by_key(|e| e.sql.clone());
+        assert_eq!(loaded.len(), 2);
+        for (l, e) in loaded.iter().zip(&expected) {
+            assert_eq!(l.id, e.id);
+            assert_eq!(l.sql, e.sql);
+            assert_eq!(l.success, e.success);
+            assert_eq!(l.error_message, e.error_message);
+            assert_eq!(l.executed_at, e.executed_at);
+        }
+    });
+}
+
+#[test]
+fn successful_queries_are_distinct_and_most_recent_first() {
+    smol::block_on(async {
+        let (_dir, store) = fresh_store().await;
+        let mut info = ConnectionInfo::default();
+        info.name = "history-suggestions".to_string();
+        store.connections().create(&info).await.unwrap();
+
+        let history = store.history();
+        for (sql, success) in [
+            ("SELECT a", true),
+            ("SELECT b", true),
+            ("SELEC c", false),
+            ("SELECT a", true),
+        ] {
+            let error = (!success).then_some("syntax error");
+            history
+                .record(&info.id, sql, 1, None, success, error)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            history.successful_queries(&info.id, 10).await.unwrap(),
+            vec!["SELECT a", "SELECT b"]
+        );
+        assert_eq!(
+            history.successful_queries(&info.id, 1).await.unwrap(),
+            vec!["SELECT a"]
+        );
+        assert!(
+            history
+                .successful_queries(&Uuid::new_v4(), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    });
+}
+
+#[test]
+fn scheduled_queries_roundtrip_and_last_run() {
+    smol::block_on(async {
+        use chrono::Timelike as _;
+
+        let (_dir, store) = fresh_store().await;
+        let info = ConnectionInfo {
+            name: format!("scheduled-{}", Uuid::new_v4()),
+            ..ConnectionInfo::default()
+        };
+        store.connections().create(&info).await.unwrap();
+        let repo = store.scheduled_queries();
+        assert!(repo.load_all().await.unwrap().is_empty());
+
+        let mut query = ScheduledQuery {
+            id: Uuid::new_v4(),
+            name: "Signups".to_string(),
+            connection_id: info.id,
+            sql: "select count(*) from users".to_string(),
+            interval_minutes: 60,
+            format: ScheduleFormat::Json,
+            output_dir: std::path::PathBuf::from("/tmp/reports"),
+            enabled: true,
+            last_run_at: None,
+        };
+        repo.save(&query).await.unwrap();
+        assert_eq!(repo.load_all().await.unwrap(), vec![query.clone()]);
+
+        let ran_at = chrono::Utc::now()
+            .with_nanosecond(0)
+            .expect("valid timestamp");
+        repo.set_last_run(&query.id, ran_at).await.unwrap();
+        query.interval_minutes = 15;
+        query.enabled = false;
+        // Editing keeps the recorded last run.
+        repo.save(&query).await.unwrap();
+        let loaded = repo.load_all().await.unwrap();
+        assert_eq!(loaded[0].interval_minutes, 15);
+        assert!(!loaded[0].enabled);
+        assert_eq!(loaded[0].last_run_at, Some(ran_at));
+
+        repo.delete(&query.id).await.unwrap();
+        assert!(repo.load_all().await.unwrap().is_empty());
+    });
+}
+
+#[test]
+fn table_size_samples_roundtrip_per_database() {
+    smol::block_on(async {
+        use chrono::Timelike as _;
+
+        let (_dir, store) = fresh_store().await;
+        let info = ConnectionInfo {
+            name: format!("sizes-{}", Uuid::new_v4()),
+            ..ConnectionInfo::default()
+        };
+        store.connections().create(&info).await.unwrap();
+        let repo = store.table_sizes();
+        assert_eq!(repo.last_sampled_at(&info.id, "app").await.unwrap(), None);
+
+        let now = chrono::Utc::now()
+            .with_nanosecond(0)
+            .expect("valid timestamp");
+        let sample = |days_ago: i64, total_bytes: i64| TableSizeSample {
+            table_schema: "public".to_string(),
+            table_name: "events".to_string(),
+            total_bytes,
+            sampled_at: now - chrono::Duration::days(days_ago),
+        };
+        let expired = sample(SAMPLE_RETENTION_DAYS + 1, 500);
+        repo.record(&info.id, "app", &[expired]).await.unwrap();
+        repo.record(&info.id, "app", &[sample(2, 1_000)]).await.unwrap();
+        repo.record(&info.id, "app", &[sample(0, 3_000)]).await.unwrap();
+        repo.record(&info.id, "other", &[sample(1, 9_000)]).await.unwrap();
+
+        assert_eq!(repo.last_sampled_at(&info.id, "app").await.unwrap(), Some(now));
+        let samples = repo.load(&info.id, "app").await.unwrap();
+        assert_eq!(samples, vec![sample(2, 1_000), sample(0, 3_000)]);
+        assert_eq!(table_growth(&samples)[0].bytes_per_day, 1_000.);
+
+        // Samples go with their connection
+        store.connections().delete(&info.id).await.unwrap();
+        assert!(repo.load(&info.id, "app").await.unwrap().is_empty());
+    });
+}
+
+#[test]
+fn schema_snapshots_skip_unchanged() {
+    smol::block_on(async {
+        let (_dir, store) = fresh_store().await;
+        let info = ConnectionInfo {
+            name: format!("snapshots-{}", Uuid::new_v4()),
+            ..ConnectionInfo::default()
+        };
+        store.connections().create(&info).await.unwrap();
+        let repo = store.schema_snapshots();
+
+        assert!(repo.record(&info.id, "app", r#"{"tables":[]}"#).await.unwrap());
+        assert!(!repo.record(&info.id, "app", r#"{"tables":[]}"#).await.unwrap());
+        assert!(repo.record(&info.id, "app", r#"{"tables":[1]}"#).await.unwrap());
+        assert!(repo.record(&info.id, "other", r#"{"tables":[]}"#).await.unwrap());
+
+        let snapshots: Vec<String> = repo
+            .load(&info.id, "app")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, snapshot)| snapshot)
+            .collect();
+        assert_eq!(snapshots, vec![r#"{"tables":[]}"#, r#"{"tables":[1]}"#]);
+
+        // Snapshots go with their connection
+        store.connections().delete(&info.id).await.unwrap();
+        assert!(repo.load(&info.id, "app").await.unwrap().is_empty());
+    });
+}
+
+#[test]
+fn recently_used_and_auto_connect() {
+    smol::block_on(async {
+        let (_dir, store) = fresh_store().await;
+        let repo = store.connections();
+        let mut ids = vec![];
+        for name in ["first", "second", "never-used"] {
+            let info = ConnectionInfo {
+                name: format!("{}-{}", name, Uuid::new_v4()),
+                ..ConnectionInfo::default()
+            };
+            repo.create(&info).await.unwrap();
+            ids.push(info.id);
+        }
+        assert!(repo.recently_used(5).await.unwrap().is_empty());
+
+        repo.mark_used(&ids[0]).await.unwrap();
+        smol::Timer::after(std::time::Duration::from_millis(5)).await;
+        repo.mark_used(&ids[1]).await.unwrap();
+        assert_eq!(repo.recently_used(5).await.unwrap(), vec![ids[1], ids[0]]);
+        assert_eq!(repo.recently_used(1).await.unwrap(), vec![ids[1]]);
+
+        assert_eq!(repo.auto_connect().await.unwrap(), None);
+        repo.set_auto_connect(Some(&ids[0])).await.unwrap();
+        repo.set_auto_connect(Some(&ids[2])).await.unwrap();
+        // Only one connection opens at startup.
+        assert_eq!(repo.auto_connect().await.unwrap(), Some(ids[2]));
+        repo.set_auto_connect(None).await.unwrap();
+        assert_eq!(repo.auto_connect().await.unwrap(), None);
+    });
+}
+
+#[test]
+fn production_connections() {
+    smol::block_on(async {
+        let (_dir, store) = fresh_store().await;
+        let repo = store.connections();
+        let mut ids = vec![];
+        for name in ["prod", "staging"] {
+            let info = ConnectionInfo {
+                name: format!("{}-{}", name, Uuid::new_v4()),
+                ..ConnectionInfo::default()
+            };
+            repo.create(&info).await.unwrap();
+            ids.push(info.id);
+        }
+        assert!(repo.production_connections().await.unwrap().is_empty());
+
+        repo.set_production(&ids[0], true).await.unwrap();
+        assert_eq!(repo.production_connections().await.unwrap(), vec![ids[0]]);
+        repo.set_production(&ids[0], false).await.unwrap();
+        assert!(repo.production_connections().await.unwrap().is_empty());
+    });
+}
+
+#[test]
+fn legacy_history_gains_run_columns_and_links_statements() {
+    smol::block_on(async {
+        init_keyring_mock();
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("legacy-history.db");
+        let connection_id = "00000000-0000-0000-0000-000000000002";
+
+        // History as it was before statements were linked to their run.
+        {
+            let pool = raw_pool(&db_path).await;
+            sqlx::query(
+                r#"
+                CREATE TABLE query_history (
+                    id TEXT PRIMARY KEY,
+                    connection_id TEXT NOT NULL,
+                    sql TEXT NOT NULL,
+                    execution_time_ms INTEGER NOT NULL,
+                    rows_affected INTEGER,
+                    success INTEGER NOT NULL,
+                    error_message TEXT,
+                    executed_at TIMESTAMP NOT NULL
+                )
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                r#"INSERT INTO query_history
+                   (id, connection_id, sql, execution_time_ms, rows_affected, success, error_message, executed_at)
+                   VALUES (?1, ?2, 'SELECT 1', 2, NULL, 1, NULL, '2024-01-01 10:00:00')"#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(connection_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool.close().await;
+        }
+
+        let store = AppStore::from_path(db_path).await.unwrap();
+        let info = ConnectionInfo {
+            id: Uuid::parse_str(connection_id).unwrap(),
+            name: "legacy-history".to_string(),
+            ..ConnectionInfo::default()
+        };
+        store.connections().create(&info).await.unwrap();
+
+        let history = store.history();
+        let legacy = history.load_for_connection(&info.id, 10).await.unwrap();
+        assert_eq!(legacy.len(), 1);
+        assert_eq!(legacy[0].run_id, None);
+        assert!(legacy[0].columns().is_empty());
+
+        // A two-statement script, recorded statement by statement.
+        let run_id = Uuid::new_v4();
+        let entry = |ix: i64, sql: &str, columns: Option<&str>| QueryHistoryEntry {
+            id: Uuid::new_v4(),
+            connection_id: info.id,
+            sql: sql.to_string(),
+            execution_time_ms: 5,
+            rows_affected: Some(1),
+            success: true,
+            error_message: None,
+            executed_at: chrono::Utc::now(),
+            run_id: Some(run_id),
+            statement_index: Some(ix),
+            result_columns: columns.map(str::to_string),
+        };
+        history
+            .record_entries(&[
+                entry(0, "UPDATE t SET a = 1", None),
+                entry(1, "SELECT a FROM t", Some(r#"[["a","INT4"]]"#)),
+            ])
+            .await
+            .unwrap();
+
+        let loaded = history.load_for_connection(&info.id, 10).await.unwrap();
+        assert_eq!(loaded.len(), 3);
+        // Same second: the later statement of the run comes first.
+        assert_eq!(loaded[0].sql, "SELECT a FROM t");
+        assert_eq!(loaded[0].run_id, Some(run_id));
+        assert_eq!(loaded[0].statement_index, Some(1));
+        assert_eq!(loaded[0].columns(), vec![("a".to_string(), "INT4".to_string())]);
+        assert_eq!(loaded[1].statement_index, Some(0));
+    });
+}
+
+#[test]
+fn timeline_groups_runs_that_differ_only_in_literals() {
+    smol::block_on(async {
+        let (_dir, store) = fresh_store().await;
+        let mut info = ConnectionInfo::default();
+        info.name = "query-timeline".to_string();
+        store.connections().create(&info).await.unwrap();
+
+        let history = store.history();
+        for (sql, ms, success) in [
+            ("SELECT * FROM orders WHERE day = '2024-05-01'", 100, true),
+            ("SELECT * FROM customers", 5, true),
+            ("select *\n  from orders where day = '2024-05-02';", 150, true),
+            ("SELECT * FROM orders WHERE day = 'oops", 1, false),
+            ("SELECT * FROM orders WHERE day = '2024-05-03'", 220, true),
+        ] {
+            let error = (!success).then_some("syntax error");
+            history
+                .record(&info.id, sql, ms, None, success, error)
+                .await
+                .unwrap();
+        }
+
+        let runs = history
+            .timeline(&info.id, "SELECT * FROM orders WHERE day = '2024-06-01'", 10)
+            .await
+            .unwrap();
+        let times: Vec<i64> = runs.iter().map(|r| r.execution_time_ms).collect();
+        assert_eq!(times, vec![100, 150, 220]);
+
+        let latest = history
+            .timeline(&info.id, "SELECT * FROM orders WHERE day = '2024-06-01'", 2)
+            .await
+            .unwrap();
+        let times: Vec<i64> = latest.iter().map(|r| r.execution_time_ms).collect();
+        assert_eq!(times, vec![150, 220]);
+
+        assert_eq!(
+            normalize_query(
+                "SELECT id FROM t WHERE id IN (1, 2, 3) -- ids\n AND note = 'O''Brien';"
+            ),
+            "select id from t where id in (?) and note = ?"
+        );
+        assert_eq!(
+            normalize_query("SELECT $1, $$ body $$, \"Col 1\" FROM t2"),
+            "select $1, ?, \"Col 1\" from t2"
+        );
+    });
+}
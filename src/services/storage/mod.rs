@@ -4,12 +4,31 @@ mod connections;
 mod history;
 #[cfg(test)]
 mod migration_tests;
+mod scheduled_queries;
+mod schema_snapshots;
+mod secrets;
+mod table_sizes;
 mod types;
+mod vault;
+mod workspace_state;
+mod workspaces;
 
 pub use connections::ConnectionsRepository;
-pub use history::QueryHistoryRepository;
+pub use history::{QueryHistoryRepository, normalize_query, relative_time};
+pub use scheduled_queries::{ScheduleFormat, ScheduledQueriesRepository, ScheduledQuery};
+pub use schema_snapshots::SchemaSnapshotsRepository;
+pub use secrets::{
+    CREDENTIAL_STORE_KEY, CredentialStore, open_vault, use_store, vault_locked, vault_unlocked,
+};
+pub use table_sizes::{
+    PROJECTION_DAYS, SAMPLE_INTERVAL_HOURS, TableGrowth, TableSizeSample, TableSizesRepository,
+    table_growth,
+};
 #[allow(unused_imports)]
 pub use types::*;
+pub use vault::{MIN_MASTER_PASSWORD_LEN, Vault};
+pub use workspace_state::WorkspaceStateRepository;
+pub use workspaces::WorkspacesRepository;
 
 use anyhow::Result;
 use async_lock::OnceCell;
@@ -38,7 +57,7 @@ impl AppStore {
         Self::from_path(db_path).await
     }
 
-    async fn from_path(db_path: PathBuf) -> Result<Self> {
+    pub(crate) async fn from_path(db_path: PathBuf) -> Result<Self> {
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -74,6 +93,31 @@ impl AppStore {
         QueryHistoryRepository::new(self.pool.clone())
     }
 
+    /// Get a workspace state repository
+    pub fn workspace_state(&self) -> WorkspaceStateRepository {
+        WorkspaceStateRepository::new(self.pool.clone())
+    }
+
+    /// Get a named workspaces repository
+    pub fn workspaces(&self) -> WorkspacesRepository {
+        WorkspacesRepository::new(self.pool.clone())
+    }
+
+    /// Get a scheduled queries repository
+    pub fn scheduled_queries(&self) -> ScheduledQueriesRepository {
+        ScheduledQueriesRepository::new(self.pool.clone())
+    }
+
+    /// Get a table size samples repository
+    pub fn table_sizes(&self) -> TableSizesRepository {
+        TableSizesRepository::new(self.pool.clone())
+    }
+
+    /// Get a schema snapshots repository
+    pub fn schema_snapshots(&self) -> SchemaSnapshotsRepository {
+        SchemaSnapshotsRepository::new(self.pool.clone())
+    }
+
     /// Initialize the database schema
     async fn initialize_schema(&self) -> Result<()> {
         sqlx::query(
@@ -93,6 +137,11 @@ impl AppStore {
                     ssh_username TEXT,
                     ssh_auth_type TEXT,
                     ssh_key_path TEXT,
+                    tag_queries INTEGER NOT NULL DEFAULT 0,
+                    startup_sql TEXT,
+                    auto_connect INTEGER NOT NULL DEFAULT 0,
+                    last_used_at TIMESTAMP,
+                    production INTEGER NOT NULL DEFAULT 0,
                     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
                     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
                 )
@@ -118,6 +167,9 @@ impl AppStore {
                     success INTEGER NOT NULL,
                     error_message TEXT,
                     executed_at TIMESTAMP NOT NULL,
+                    run_id TEXT,
+                    statement_index INTEGER,
+                    result_columns TEXT,
                     FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
                 )
                 "#,
@@ -132,6 +184,97 @@ impl AppStore {
             .execute(&self.pool)
             .await?;
 
+        // Persisted UI state (window placement, layout, ...)
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS workspace_state (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                )
+                "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Named workspaces; the snapshot is JSON owned by the UI
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS workspaces (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE,
+                    snapshot TEXT NOT NULL,
+                    updated_at TIMESTAMP NOT NULL
+                )
+                "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Queries run on an interval, writing results to files
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS scheduled_queries (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    connection_id TEXT NOT NULL,
+                    sql TEXT NOT NULL,
+                    interval_minutes INTEGER NOT NULL,
+                    format TEXT NOT NULL DEFAULT 'csv',
+                    output_dir TEXT NOT NULL,
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    last_run_at TIMESTAMP,
+                    FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
+                )
+                "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Table sizes sampled over time, for growth forecasts
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS table_size_samples (
+                    connection_id TEXT NOT NULL,
+                    database TEXT NOT NULL,
+                    table_schema TEXT NOT NULL,
+                    table_name TEXT NOT NULL,
+                    total_bytes INTEGER NOT NULL,
+                    sampled_at TIMESTAMP NOT NULL,
+                    FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
+                )
+                "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_table_size_samples ON table_size_samples(connection_id, database, sampled_at)"
+            )
+            .execute(&self.pool)
+            .await?;
+
+        // Schema snapshots taken on connecting, for the change log
+        sqlx::query(
+            r#"
+                CREATE TABLE IF NOT EXISTS schema_snapshots (
+                    connection_id TEXT NOT NULL,
+                    database TEXT NOT NULL,
+                    taken_at TIMESTAMP NOT NULL,
+                    snapshot TEXT NOT NULL,
+                    FOREIGN KEY (connection_id) REFERENCES connections(id) ON DELETE CASCADE
+                )
+                "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_schema_snapshots ON schema_snapshots(connection_id, database, taken_at)"
+            )
+            .execute(&self.pool)
+            .await?;
+
         Ok(())
     }
 
@@ -140,19 +283,27 @@ impl AppStore {
     /// Each ALTER TABLE is attempted independently. SQLite returns an
     /// error when a column already exists, which we treat as a no-op.
     async fn migrate_schema(&self) -> Result<()> {
-        let migrations: &[(&str, &str)] = &[
-            ("ssl_mode", "ALTER TABLE connections ADD COLUMN ssl_mode TEXT NOT NULL DEFAULT 'prefer'"),
-            ("driver", "ALTER TABLE connections ADD COLUMN driver TEXT NOT NULL DEFAULT 'postgres'"),
-            ("ssh_enabled", "ALTER TABLE connections ADD COLUMN ssh_enabled INTEGER NOT NULL DEFAULT 0"),
-            ("ssh_host", "ALTER TABLE connections ADD COLUMN ssh_host TEXT"),
-            ("ssh_port", "ALTER TABLE connections ADD COLUMN ssh_port INTEGER"),
-            ("ssh_username", "ALTER TABLE connections ADD COLUMN ssh_username TEXT"),
-            ("ssh_auth_type", "ALTER TABLE connections ADD COLUMN ssh_auth_type TEXT"),
-            ("ssh_key_path", "ALTER TABLE connections ADD COLUMN ssh_key_path TEXT"),
+        let migrations: &[(&str, &str, &str)] = &[
+            ("connections", "ssl_mode", "ALTER TABLE connections ADD COLUMN ssl_mode TEXT NOT NULL DEFAULT 'prefer'"),
+            ("connections", "driver", "ALTER TABLE connections ADD COLUMN driver TEXT NOT NULL DEFAULT 'postgres'"),
+            ("connections", "ssh_enabled", "ALTER TABLE connections ADD COLUMN ssh_enabled INTEGER NOT NULL DEFAULT 0"),
+            ("connections", "ssh_host", "ALTER TABLE connections ADD COLUMN ssh_host TEXT"),
+            ("connections", "ssh_port", "ALTER TABLE connections ADD COLUMN ssh_port INTEGER"),
+            ("connections", "ssh_username", "ALTER TABLE connections ADD COLUMN ssh_username TEXT"),
+            ("connections", "ssh_auth_type", "ALTER TABLE connections ADD COLUMN ssh_auth_type TEXT"),
+            ("connections", "ssh_key_path", "ALTER TABLE connections ADD COLUMN ssh_key_path TEXT"),
+            ("connections", "tag_queries", "ALTER TABLE connections ADD COLUMN tag_queries INTEGER NOT NULL DEFAULT 0"),
+            ("connections", "startup_sql", "ALTER TABLE connections ADD COLUMN startup_sql TEXT"),
+            ("connections", "auto_connect", "ALTER TABLE connections ADD COLUMN auto_connect INTEGER NOT NULL DEFAULT 0"),
+            ("connections", "last_used_at", "ALTER TABLE connections ADD COLUMN last_used_at TIMESTAMP"),
+            ("connections", "production", "ALTER TABLE connections ADD COLUMN production INTEGER NOT NULL DEFAULT 0"),
+            ("query_history", "run_id", "ALTER TABLE query_history ADD COLUMN run_id TEXT"),
+            ("query_history", "statement_index", "ALTER TABLE query_history ADD COLUMN statement_index INTEGER"),
+            ("query_history", "result_columns", "ALTER TABLE query_history ADD COLUMN result_columns TEXT"),
         ];
 
-        for (col, ddl) in migrations {
-            let probe = format!("SELECT {} FROM connections LIMIT 1", col);
+        for (table, col, ddl) in migrations {
+            let probe = format!("SELECT {} FROM {} LIMIT 1", col, table);
             let exists = sqlx::query(&probe)
                 .fetch_optional(&self.pool)
                 .await
@@ -0,0 +1,265 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// File format a scheduled query writes its rows in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScheduleFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ScheduleFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleFormat::Csv => "csv",
+            ScheduleFormat::Json => "json",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "json" => ScheduleFormat::Json,
+            _ => ScheduleFormat::Csv,
+        }
+    }
+}
+
+/// A query run every `interval_minutes` against a saved connection, each
+/// run writing its rows to a new file in `output_dir`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledQuery {
+    pub id: Uuid,
+    pub name: String,
+    pub connection_id: Uuid,
+    pub sql: String,
+    pub interval_minutes: u32,
+    pub format: ScheduleFormat,
+    pub output_dir: PathBuf,
+    pub enabled: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+impl ScheduledQuery {
+    /// When the query runs next; a query that never ran is due now.
+    /// `None` while disabled.
+    pub fn next_run_at(&self) -> Option<DateTime<Utc>> {
+        if !self.enabled {
+            return None;
+        }
+        Some(match self.last_run_at {
+            Some(last) => last + chrono::Duration::minutes(i64::from(self.interval_minutes.max(1))),
+            None => DateTime::<Utc>::MIN_UTC,
+        })
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_run_at().is_some_and(|next| next <= now)
+    }
+
+    /// `every 15 min`, `every 2 h`, `every 1 d`.
+    pub fn interval_label(&self) -> String {
+        let minutes = self.interval_minutes;
+        if minutes >= 24 * 60 && minutes % (24 * 60) == 0 {
+            format!("every {} d", minutes / (24 * 60))
+        } else if minutes >= 60 && minutes % 60 == 0 {
+            format!("every {} h", minutes / 60)
+        } else {
+            format!("every {} min", minutes)
+        }
+    }
+
+    /// File for a run started at `at`: `<name>-20240501-134500.csv`, with
+    /// characters unsafe in file names replaced.
+    pub fn output_path(&self, at: DateTime<chrono::Local>) -> PathBuf {
+        let stem: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.output_dir.join(format!(
+            "{}-{}.{}",
+            stem,
+            at.format("%Y%m%d-%H%M%S"),
+            self.format.as_str()
+        ))
+    }
+}
+
+/// Repository for scheduled queries.
+#[derive(Debug, Clone)]
+pub struct ScheduledQueriesRepository {
+    pool: SqlitePool,
+}
+
+type ScheduledQueryRow = (
+    String,
+    String,
+    String,
+    String,
+    i64,
+    String,
+    String,
+    bool,
+    Option<String>,
+);
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+impl ScheduledQueriesRepository {
+    pub(crate) fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// All scheduled queries, by name.
+    pub async fn load_all(&self) -> Result<Vec<ScheduledQuery>> {
+        let rows = sqlx::query_as::<_, ScheduledQueryRow>(
+            r#"
+            SELECT id, name, connection_id, sql, interval_minutes, format, output_dir,
+                   enabled, last_run_at
+            FROM scheduled_queries
+            ORDER BY name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(
+                |(id, name, connection_id, sql, interval, format, output_dir, enabled, last)| {
+                    Ok(ScheduledQuery {
+                        id: Uuid::parse_str(&id).context("Invalid UUID")?,
+                        name,
+                        connection_id: Uuid::parse_str(&connection_id)
+                            .context("Invalid connection UUID")?,
+                        sql,
+                        interval_minutes: u32::try_from(interval).unwrap_or(1).max(1),
+                        format: ScheduleFormat::parse(&format),
+                        output_dir: PathBuf::from(output_dir),
+                        enabled,
+                        last_run_at: last.and_then(|last| {
+                            NaiveDateTime::parse_from_str(&last, TIMESTAMP_FORMAT)
+                                .ok()
+                                .map(|dt| dt.and_utc())
+                        }),
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Insert `query`, or update the one with its id.
+    pub async fn save(&self, query: &ScheduledQuery) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO scheduled_queries
+                (id, name, connection_id, sql, interval_minutes, format, output_dir,
+                 enabled, last_run_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                connection_id = excluded.connection_id,
+                sql = excluded.sql,
+                interval_minutes = excluded.interval_minutes,
+                format = excluded.format,
+                output_dir = excluded.output_dir,
+                enabled = excluded.enabled
+            "#,
+        )
+        .bind(query.id.to_string())
+        .bind(&query.name)
+        .bind(query.connection_id.to_string())
+        .bind(&query.sql)
+        .bind(i64::from(query.interval_minutes))
+        .bind(query.format.as_str())
+        .bind(query.output_dir.to_string_lossy().to_string())
+        .bind(query.enabled)
+        .bind(
+            query
+                .last_run_at
+                .map(|at| at.format(TIMESTAMP_FORMAT).to_string()),
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_last_run(&self, id: &Uuid, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("UPDATE scheduled_queries SET last_run_at = ? WHERE id = ?")
+            .bind(at.format(TIMESTAMP_FORMAT).to_string())
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM scheduled_queries WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn scheduled(interval_minutes: u32) -> ScheduledQuery {
+        ScheduledQuery {
+            id: Uuid::new_v4(),
+            name: "daily signups/region".to_string(),
+            connection_id: Uuid::new_v4(),
+            sql: "select 1".to_string(),
+            interval_minutes,
+            format: ScheduleFormat::Csv,
+            output_dir: PathBuf::from("/tmp/reports"),
+            enabled: true,
+            last_run_at: None,
+        }
+    }
+
+    #[test]
+    fn due_after_the_interval() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut query = scheduled(30);
+        assert!(query.is_due(now));
+
+        query.last_run_at = Some(now - chrono::Duration::minutes(29));
+        assert!(!query.is_due(now));
+        query.last_run_at = Some(now - chrono::Duration::minutes(30));
+        assert!(query.is_due(now));
+
+        query.enabled = false;
+        assert!(!query.is_due(now));
+        assert_eq!(query.next_run_at(), None);
+    }
+
+    #[test]
+    fn labels_and_file_names() {
+        assert_eq!(scheduled(15).interval_label(), "every 15 min");
+        assert_eq!(scheduled(120).interval_label(), "every 2 h");
+        assert_eq!(scheduled(90).interval_label(), "every 90 min");
+        assert_eq!(scheduled(2 * 24 * 60).interval_label(), "every 2 d");
+
+        let at = chrono::Local
+            .with_ymd_and_hms(2024, 5, 1, 13, 45, 0)
+            .unwrap();
+        assert_eq!(
+            scheduled(15).output_path(at),
+            PathBuf::from("/tmp/reports/daily_signups_region-20240501-134500.csv")
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Repository for schema snapshots taken on connecting, per connection
+/// and database. Snapshots are kept as the JSON they serialize to.
+#[derive(Debug, Clone)]
+pub struct SchemaSnapshotsRepository {
+    pool: SqlitePool,
+}
+
+impl SchemaSnapshotsRepository {
+    pub(crate) fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Store `snapshot` unless it equals the latest one of `database`.
+    /// Returns whether it was stored.
+    pub async fn record(
+        &self,
+        connection_id: &Uuid,
+        database: &str,
+        snapshot: &str,
+    ) -> Result<bool> {
+        let latest: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT snapshot FROM schema_snapshots
+            WHERE connection_id = ? AND database = ?
+            ORDER BY taken_at DESC, rowid DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(connection_id.to_string())
+        .bind(database)
+        .fetch_optional(&self.pool)
+        .await?;
+        if latest.as_deref() == Some(snapshot) {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO schema_snapshots (connection_id, database, taken_at, snapshot) VALUES (?, ?, ?, ?)",
+        )
+        .bind(connection_id.to_string())
+        .bind(database)
+        .bind(Utc::now().format(TIMESTAMP_FORMAT).to_string())
+        .bind(snapshot)
+        .execute(&self.pool)
+        .await?;
+        Ok(true)
+    }
+
+    /// Every snapshot of `database`, oldest first.
+    pub async fn load(
+        &self,
+        connection_id: &Uuid,
+        database: &str,
+    ) -> Result<Vec<(DateTime<Utc>, String)>> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT taken_at, snapshot
+            FROM schema_snapshots
+            WHERE connection_id = ? AND database = ?
+            ORDER BY taken_at, rowid
+            "#,
+        )
+        .bind(connection_id.to_string())
+        .bind(database)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(taken_at, snapshot)| {
+                let taken_at = NaiveDateTime::parse_from_str(&taken_at, TIMESTAMP_FORMAT)
+                    .context("Invalid snapshot timestamp")?
+                    .and_utc();
+                Ok((taken_at, snapshot))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,143 @@
+//! Where passwords and SSH key passphrases are kept: the system keyring,
+//! or the encrypted [`Vault`] for machines without one.
+//!
+//! The choice is process-wide. While the vault is the active store but
+//! has not been unlocked, reads and writes fail rather than falling back
+//! to the keyring.
+
+use std::sync::{Mutex, RwLock};
+
+use anyhow::{Context, Result, bail};
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+use super::vault::Vault;
+
+const KEYRING_SERVICE: &str = "pgui";
+
+/// Workspace state key for where passwords are stored.
+pub const CREDENTIAL_STORE_KEY: &str = "credential_store";
+
+/// Backend for stored secrets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CredentialStore {
+    /// macOS Keychain, Windows Credential Manager or the Secret Service.
+    #[default]
+    Keyring,
+    /// `~/.pgui/vault.json`, unlocked with a master password.
+    Vault,
+}
+
+impl CredentialStore {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CredentialStore::Keyring => "System Keychain",
+            CredentialStore::Vault => "Encrypted Vault",
+        }
+    }
+}
+
+static ACTIVE_STORE: RwLock<CredentialStore> = RwLock::new(CredentialStore::Keyring);
+static UNLOCKED_VAULT: Mutex<Option<Vault>> = Mutex::new(None);
+
+pub fn active_store() -> CredentialStore {
+    *ACTIVE_STORE.read().unwrap()
+}
+
+/// Make `store` the active store without moving any secrets into it.
+pub fn use_store(store: CredentialStore) {
+    *ACTIVE_STORE.write().unwrap() = store;
+}
+
+pub fn vault_unlocked() -> bool {
+    UNLOCKED_VAULT.lock().unwrap().is_some()
+}
+
+/// The vault is the active store but has not been unlocked, so no
+/// password can be read yet.
+pub fn vault_locked() -> bool {
+    active_store() == CredentialStore::Vault && !vault_unlocked()
+}
+
+/// Hand an unlocked vault to the store, replacing any open one.
+pub fn open_vault(vault: Vault) {
+    *UNLOCKED_VAULT.lock().unwrap() = Some(vault);
+}
+
+pub(crate) fn set(key: &str, secret: &str) -> Result<()> {
+    set_in(active_store(), key, secret)
+}
+
+/// The secret under `key`, or `None` when nothing is stored there.
+pub(crate) fn get(key: &str) -> Result<Option<String>> {
+    get_from(active_store(), key)
+}
+
+pub(crate) fn delete(key: &str) -> Result<()> {
+    delete_from(active_store(), key)
+}
+
+/// Copy the secrets under `keys` from the active store into `to`, make
+/// `to` active, then remove them from the previous store.
+pub(crate) fn move_to(to: CredentialStore, keys: &[String]) -> Result<()> {
+    let from = active_store();
+    if from == to {
+        return Ok(());
+    }
+
+    for key in keys {
+        if let Some(secret) = get_from(from, key)? {
+            set_in(to, key, &secret)?;
+        }
+    }
+    use_store(to);
+
+    for key in keys {
+        if let Err(e) = delete_from(from, key) {
+            tracing::warn!("Failed to remove {} from the {}: {}", key, from.label(), e);
+        }
+    }
+    Ok(())
+}
+
+fn set_in(store: CredentialStore, key: &str, secret: &str) -> Result<()> {
+    match store {
+        CredentialStore::Keyring => keyring_entry(key)?
+            .set_password(secret)
+            .context("Failed to store secret in keyring"),
+        CredentialStore::Vault => with_vault(|vault| vault.set(key, secret)),
+    }
+}
+
+fn get_from(store: CredentialStore, key: &str) -> Result<Option<String>> {
+    match store {
+        CredentialStore::Keyring => match keyring_entry(key)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context("Failed to retrieve secret from keyring"),
+        },
+        CredentialStore::Vault => with_vault(|vault| Ok(vault.get(key).map(str::to_string))),
+    }
+}
+
+fn delete_from(store: CredentialStore, key: &str) -> Result<()> {
+    match store {
+        CredentialStore::Keyring => {
+            let _ = keyring_entry(key)?.delete_credential();
+            Ok(())
+        }
+        CredentialStore::Vault => with_vault(|vault| vault.remove(key)),
+    }
+}
+
+fn keyring_entry(key: &str) -> Result<Entry> {
+    Entry::new(KEYRING_SERVICE, key).context("Failed to create keyring entry")
+}
+
+fn with_vault<T>(f: impl FnOnce(&mut Vault) -> Result<T>) -> Result<T> {
+    let mut vault = UNLOCKED_VAULT.lock().unwrap();
+    match vault.as_mut() {
+        Some(vault) => f(vault),
+        None => bail!("The password vault is locked"),
+    }
+}
@@ -0,0 +1,263 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Time after which connecting samples table sizes again.
+pub const SAMPLE_INTERVAL_HOURS: i64 = 6;
+
+/// Days a sample is kept before recording newer ones drops it.
+pub const SAMPLE_RETENTION_DAYS: i64 = 365;
+
+/// Days ahead sizes are projected.
+pub const PROJECTION_DAYS: [i64; 2] = [30, 90];
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The size of a table, with its indexes and TOAST data, at one moment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSizeSample {
+    pub table_schema: String,
+    pub table_name: String,
+    pub total_bytes: i64,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// How a table's size changed over its samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableGrowth {
+    pub table_schema: String,
+    pub table_name: String,
+    /// Sizes oldest first.
+    pub samples: Vec<(DateTime<Utc>, i64)>,
+    /// Slope of a least-squares line through the samples; zero with fewer
+    /// than two.
+    pub bytes_per_day: f64,
+}
+
+impl TableGrowth {
+    pub fn current_bytes(&self) -> i64 {
+        self.samples.last().map_or(0, |(_, bytes)| *bytes)
+    }
+
+    /// Size `days` after the latest sample if growth keeps its pace. A
+    /// shrinking table is not projected below zero.
+    pub fn projected_bytes(&self, days: i64) -> i64 {
+        let projected = self.current_bytes() as f64 + self.bytes_per_day * days as f64;
+        projected.max(0.) as i64
+    }
+
+    /// Days between the first and the latest sample.
+    pub fn days_sampled(&self) -> f64 {
+        match (self.samples.first(), self.samples.last()) {
+            (Some((first, _)), Some((last, _))) => (*last - *first).num_seconds() as f64 / 86_400.,
+            _ => 0.,
+        }
+    }
+}
+
+/// Samples grouped by table, fastest growing first.
+pub fn table_growth(samples: &[TableSizeSample]) -> Vec<TableGrowth> {
+    let mut tables: Vec<TableGrowth> = vec![];
+    for sample in samples {
+        let point = (sample.sampled_at, sample.total_bytes);
+        let table = tables.iter_mut().find(|table| {
+            table.table_schema == sample.table_schema && table.table_name == sample.table_name
+        });
+        match table {
+            Some(table) => table.samples.push(point),
+            None => tables.push(TableGrowth {
+                table_schema: sample.table_schema.clone(),
+                table_name: sample.table_name.clone(),
+                samples: vec![point],
+                bytes_per_day: 0.,
+            }),
+        }
+    }
+
+    for table in &mut tables {
+        table.samples.sort_by_key(|(at, _)| *at);
+        table.bytes_per_day = slope_per_day(&table.samples);
+    }
+    tables.sort_by(|a, b| {
+        b.bytes_per_day
+            .total_cmp(&a.bytes_per_day)
+            .then(b.current_bytes().cmp(&a.current_bytes()))
+    });
+    tables
+}
+
+fn slope_per_day(samples: &[(DateTime<Utc>, i64)]) -> f64 {
+    let Some((start, _)) = samples.first() else {
+        return 0.;
+    };
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|(at, bytes)| ((*at - *start).num_seconds() as f64 / 86_400., *bytes as f64))
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    // All samples at one moment give no direction
+    if variance > 0. {
+        covariance / variance
+    } else {
+        0.
+    }
+}
+
+/// Repository for sampled table sizes, per connection and database.
+#[derive(Debug, Clone)]
+pub struct TableSizesRepository {
+    pool: SqlitePool,
+}
+
+type SampleRow = (String, String, i64, String);
+
+impl TableSizesRepository {
+    pub(crate) fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Store `samples` of `database`, dropping its samples older than
+    /// [`SAMPLE_RETENTION_DAYS`].
+    pub async fn record(
+        &self,
+        connection_id: &Uuid,
+        database: &str,
+        samples: &[TableSizeSample],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for sample in samples {
+            sqlx::query(
+                r#"
+                INSERT INTO table_size_samples
+                    (connection_id, database, table_schema, table_name, total_bytes, sampled_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(connection_id.to_string())
+            .bind(database)
+            .bind(&sample.table_schema)
+            .bind(&sample.table_name)
+            .bind(sample.total_bytes)
+            .bind(sample.sampled_at.format(TIMESTAMP_FORMAT).to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+        let cutoff = Utc::now() - chrono::Duration::days(SAMPLE_RETENTION_DAYS);
+        sqlx::query(
+            r#"
+            DELETE FROM table_size_samples
+            WHERE connection_id = ? AND database = ? AND sampled_at < ?
+            "#,
+        )
+        .bind(connection_id.to_string())
+        .bind(database)
+        .bind(cutoff.format(TIMESTAMP_FORMAT).to_string())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// When sizes of `database` were last sampled.
+    pub async fn last_sampled_at(
+        &self,
+        connection_id: &Uuid,
+        database: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let last: Option<String> = sqlx::query_scalar(
+            "SELECT MAX(sampled_at) FROM table_size_samples WHERE connection_id = ? AND database = ?",
+        )
+        .bind(connection_id.to_string())
+        .bind(database)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(last.and_then(|last| {
+            NaiveDateTime::parse_from_str(&last, TIMESTAMP_FORMAT)
+                .ok()
+                .map(|dt| dt.and_utc())
+        }))
+    }
+
+    /// Every sample of `database`, oldest first.
+    pub async fn load(&self, connection_id: &Uuid, database: &str) -> Result<Vec<TableSizeSample>> {
+        let rows = sqlx::query_as::<_, SampleRow>(
+            r#"
+            SELECT table_schema, table_name, total_bytes, sampled_at
+            FROM table_size_samples
+            WHERE connection_id = ? AND database = ?
+            ORDER BY sampled_at, table_schema, table_name
+            "#,
+        )
+        .bind(connection_id.to_string())
+        .bind(database)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(table_schema, table_name, total_bytes, sampled_at)| {
+                Ok(TableSizeSample {
+                    table_schema,
+                    table_name,
+                    total_bytes,
+                    sampled_at: NaiveDateTime::parse_from_str(&sampled_at, TIMESTAMP_FORMAT)
+                        .context("Invalid sample timestamp")?
+                        .and_utc(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample(name: &str, day: u32, total_bytes: i64) -> TableSizeSample {
+        TableSizeSample {
+            table_schema: "public".to_string(),
+            table_name: name.to_string(),
+            total_bytes,
+            sampled_at: Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn fastest_growing_tables_first() {
+        let samples = vec![
+            sample("events", 1, 1_000),
+            sample("users", 1, 5_000),
+            sample("events", 2, 3_000),
+            sample("users", 2, 5_000),
+            sample("events", 3, 5_000),
+            sample("users", 3, 5_100),
+        ];
+        let growth = table_growth(&samples);
+        assert_eq!(growth.len(), 2);
+        assert_eq!(growth[0].table_name, "events");
+        assert_eq!(growth[0].bytes_per_day, 2_000.);
+        assert_eq!(growth[0].current_bytes(), 5_000);
+        assert_eq!(growth[0].projected_bytes(30), 65_000);
+        assert_eq!(growth[0].days_sampled(), 2.);
+        assert_eq!(growth[1].bytes_per_day, 50.);
+    }
+
+    #[test]
+    fn single_samples_and_shrinking_tables() {
+        let growth = table_growth(&[sample("logs", 1, 4_000)]);
+        assert_eq!(growth[0].bytes_per_day, 0.);
+        assert_eq!(growth[0].projected_bytes(90), 4_000);
+
+        let growth = table_growth(&[sample("logs", 1, 4_000), sample("logs", 3, 2_000)]);
+        assert_eq!(growth[0].bytes_per_day, -1_000.);
+        assert_eq!(growth[0].projected_bytes(30), 0);
+    }
+}
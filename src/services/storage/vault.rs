@@ -0,0 +1,196 @@
+//! Encrypted file of secrets, used in place of the system keyring where
+//! no Secret Service is available.
+//!
+//! The secrets are kept as one JSON object, encrypted with AES-256-GCM
+//! under a key derived from the master password with Argon2id. The key is
+//! derived once when the vault is unlocked; each write uses a new nonce.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{Context, Result, anyhow, bail};
+use argon2::Argon2;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Shortest master password accepted for a new vault.
+pub const MIN_MASTER_PASSWORD_LEN: usize = 8;
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// An unlocked vault. Changes are written to disk as they are made.
+pub struct Vault {
+    path: PathBuf,
+    salt: [u8; SALT_LEN],
+    cipher: Aes256Gcm,
+    secrets: BTreeMap<String, String>,
+}
+
+impl Vault {
+    /// `~/.pgui/vault.json`
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not find home directory")?;
+        Ok(home.join(".pgui").join("vault.json"))
+    }
+
+    /// Whether a vault has been created at the default path.
+    pub fn exists() -> bool {
+        Self::default_path().is_ok_and(|path| path.exists())
+    }
+
+    /// Create an empty vault at `path`, replacing any vault there.
+    pub fn create(path: &Path, master_password: &str) -> Result<Self> {
+        if master_password.chars().count() < MIN_MASTER_PASSWORD_LEN {
+            bail!(
+                "The master password needs at least {} characters",
+                MIN_MASTER_PASSWORD_LEN
+            );
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let vault = Self {
+            path: path.to_path_buf(),
+            salt,
+            cipher: derive_key(master_password, &salt)?,
+            secrets: BTreeMap::new(),
+        };
+        vault.save()?;
+        Ok(vault)
+    }
+
+    /// Open the vault at `path` with its master password.
+    pub fn unlock(path: &Path, master_password: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let file: VaultFile = serde_json::from_str(&contents).context("Corrupt vault file")?;
+        if file.version > VAULT_VERSION {
+            bail!("The vault was written by a newer version of pgui");
+        }
+
+        let salt: [u8; SALT_LEN] = hex::decode(&file.salt)
+            .ok()
+            .and_then(|salt| salt.try_into().ok())
+            .context("Corrupt vault file")?;
+        let nonce = hex::decode(&file.nonce).context("Corrupt vault file")?;
+        let ciphertext = hex::decode(&file.ciphertext).context("Corrupt vault file")?;
+        if nonce.len() != NONCE_LEN {
+            bail!("Corrupt vault file");
+        }
+
+        let cipher = derive_key(master_password, &salt)?;
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| anyhow!("Wrong master password"))?;
+        let secrets = serde_json::from_slice(&plaintext).context("Corrupt vault file")?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            salt,
+            cipher,
+            secrets,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.secrets.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: &str, secret: &str) -> Result<()> {
+        self.secrets.insert(key.to_string(), secret.to_string());
+        self.save()
+    }
+
+    pub fn remove(&mut self, key: &str) -> Result<()> {
+        if self.secrets.remove(key).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Encrypt the secrets under a new nonce and replace the file, so a
+    /// failed write leaves the previous vault intact.
+    fn save(&self) -> Result<()> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                serde_json::to_vec(&self.secrets)?.as_slice(),
+            )
+            .map_err(|_| anyhow!("Failed to encrypt the vault"))?;
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            salt: hex::encode(self.salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&file)?)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+fn derive_key(master_password: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(master_password.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive key: {}", e))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| anyhow!("Invalid key: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_survive_reopening_with_the_master_password() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.json");
+
+        let mut vault = Vault::create(&path, "correct horse").unwrap();
+        vault.set("conn-1", "s3cret").unwrap();
+        vault.set("conn-1:ssh-keypass", "phrase").unwrap();
+        vault.remove("conn-1:ssh-keypass").unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("s3cret"));
+
+        let reopened = Vault::unlock(&path, "correct horse").unwrap();
+        assert_eq!(reopened.get("conn-1"), Some("s3cret"));
+        assert_eq!(reopened.get("conn-1:ssh-keypass"), None);
+
+        let err = Vault::unlock(&path, "wrong horse").err().unwrap();
+        assert_eq!(err.to_string(), "Wrong master password");
+    }
+
+    #[test]
+    fn short_master_passwords_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Vault::create(&dir.path().join("vault.json"), "short").is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use serde::{Serialize, de::DeserializeOwned};
+use sqlx::SqlitePool;
+
+/// Key/value store for UI state that should survive restarts (window
+/// placement, panel layout, ...). Values are stored as JSON text.
+#[derive(Debug, Clone)]
+pub struct WorkspaceStateRepository {
+    pool: SqlitePool,
+}
+
+impl WorkspaceStateRepository {
+    pub(crate) fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Load the raw value stored under `key`.
+    pub async fn get(&self, key: &str) -> Result<Option<String>> {
+        let row = sqlx::query_as::<_, (String,)>("SELECT value FROM workspace_state WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|(value,)| value))
+    }
+
+    /// Insert or replace the raw value stored under `key`.
+    pub async fn set(&self, key: &str, value: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO workspace_state (key, value, updated_at)
+            VALUES (?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub async fn delete(&self, key: &str) -> Result<()> {
+        sqlx::query("DELETE FROM workspace_state WHERE key = ?")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Load and deserialize a JSON value. Values that no longer parse
+    /// (e.g. after a format change) are treated as missing.
+    pub async fn get_json<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        Ok(self
+            .get(key)
+            .await?
+            .and_then(|raw| serde_json::from_str(&raw).ok()))
+    }
+
+    pub async fn set_json<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let raw = serde_json::to_string(value)?;
+        self.set(key, &raw).await
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::types::SavedWorkspace;
+
+/// Repository for named workspaces: snapshots of the connection, editor
+/// contents and panel layout the user can switch back to.
+#[derive(Debug, Clone)]
+pub struct WorkspacesRepository {
+    pool: SqlitePool,
+}
+
+impl WorkspacesRepository {
+    pub(crate) fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// All saved workspaces, by name.
+    pub async fn load_all(&self) -> Result<Vec<SavedWorkspace>> {
+        let rows = sqlx::query_as::<_, (String, String, String, String)>(
+            "SELECT id, name, snapshot, updated_at FROM workspaces ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(id, name, snapshot, updated_at)| {
+                Ok(SavedWorkspace {
+                    id: Uuid::parse_str(&id).context("Invalid UUID")?,
+                    name,
+                    snapshot,
+                    updated_at: NaiveDateTime::parse_from_str(&updated_at, "%Y-%m-%d %H:%M:%S")
+                        .map(|dt| dt.and_utc())
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })
+            .collect()
+    }
+
+    /// Save `snapshot` as JSON under `name`, replacing a workspace of the
+    /// same name.
+    pub async fn save<T: Serialize>(&self, name: &str, snapshot: &T) -> Result<()> {
+        let snapshot = serde_json::to_string(snapshot)?;
+        sqlx::query(
+            r#"
+            INSERT INTO workspaces (id, name, snapshot, updated_at)
+            VALUES (?, ?, ?, datetime('now'))
+            ON CONFLICT(name) DO UPDATE SET
+                snapshot = excluded.snapshot,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(name)
+        .bind(snapshot)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM workspaces WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+//! Background download of a release build, verified against its minisign
+//! signature before it is offered for install.
+//!
+//! The file is fetched in ranges into `~/.pgui/updates/<name>.part`, so an
+//! interrupted download picks up where it stopped.
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+
+use super::update_checker::{ReleaseAsset, UpdateInfo};
+
+/// Bytes requested per range request; progress is reported after each.
+const CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Public key matching the secret key the release workflow signs builds
+/// with, provided at build time. Without it downloads cannot be verified.
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("PGUI_UPDATE_PUBLIC_KEY");
+
+/// Whether this build can verify, and therefore install, downloaded updates.
+pub fn can_verify_updates() -> bool {
+    UPDATE_PUBLIC_KEY.is_some()
+}
+
+fn updates_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    Ok(home.join(".pgui").join("updates"))
+}
+
+/// Download the update's build for this platform and verify its signature.
+/// `progress` gets the bytes downloaded so far and the total size.
+pub async fn download_update(
+    info: &UpdateInfo,
+    progress: impl Fn(u64, u64) + Send + 'static,
+) -> Result<PathBuf> {
+    let asset = info
+        .asset
+        .clone()
+        .ok_or_else(|| anyhow!("This release has no build for this platform"))?;
+    let signature = info
+        .signature
+        .clone()
+        .ok_or_else(|| anyhow!("This release's build is not signed"))?;
+    let public_key = UPDATE_PUBLIC_KEY
+        .ok_or_else(|| anyhow!("This build of pgui cannot verify updates"))?
+        .to_string();
+
+    smol::unblock(move || {
+        let dir = updates_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(&asset.name);
+        let partial = dir.join(format!("{}.part", asset.name));
+
+        let signature = String::from_utf8(fetch(&signature.browser_download_url, None)?)
+            .map_err(|_| anyhow!("The update signature is not valid text"))?;
+
+        // A previous run may have finished the download already.
+        if path.exists() {
+            if verify(&public_key, &std::fs::read(&path)?, &signature).is_ok() {
+                progress(asset.size, asset.size);
+                return Ok(path);
+            }
+            std::fs::remove_file(&path)?;
+        }
+
+        fetch_into(&asset, &partial, &progress)?;
+
+        let data = std::fs::read(&partial)?;
+        if let Err(e) = verify(&public_key, &data, &signature) {
+            // Start over next time rather than resuming a bad file.
+            let _ = std::fs::remove_file(&partial);
+            return Err(e);
+        }
+        std::fs::rename(&partial, &path)?;
+        Ok(path)
+    })
+    .await
+}
+
+/// Append the rest of `asset` to `partial`, one range at a time.
+fn fetch_into(asset: &ReleaseAsset, partial: &Path, progress: &impl Fn(u64, u64)) -> Result<()> {
+    let mut downloaded = std::fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    if downloaded > asset.size {
+        std::fs::remove_file(partial)?;
+        downloaded = 0;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(partial)?;
+    progress(downloaded, asset.size);
+
+    while let Some(range) = next_range(downloaded, asset.size) {
+        let chunk = fetch(&asset.browser_download_url, Some(range))?;
+        let expected = range.1 - range.0 + 1;
+        if chunk.len() as u64 == asset.size && downloaded > 0 {
+            // The server ignored the range and sent the whole file.
+            file.set_len(0)?;
+            file.write_all(&chunk)?;
+            downloaded = asset.size;
+        } else if chunk.len() as u64 == expected || chunk.len() as u64 == asset.size {
+            file.write_all(&chunk)?;
+            downloaded += chunk.len() as u64;
+        } else {
+            return Err(anyhow!(
+                "Download interrupted: expected {} bytes, got {}",
+                expected,
+                chunk.len()
+            ));
+        }
+        progress(downloaded, asset.size);
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// The inclusive byte range to request next, or `None` once `downloaded`
+/// reaches `size`.
+fn next_range(downloaded: u64, size: u64) -> Option<(u64, u64)> {
+    (downloaded < size).then(|| (downloaded, (downloaded + CHUNK_SIZE).min(size) - 1))
+}
+
+fn fetch(url: &str, range: Option<(u64, u64)>) -> Result<Vec<u8>> {
+    let mut headers = vec![("User-Agent".to_string(), "pgui-updater".to_string())];
+    if let Some((start, end)) = range {
+        headers.push(("Range".to_string(), format!("bytes={}-{}", start, end)));
+    }
+    let response = smolhttp::Client::new(url)
+        .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?
+        .get()
+        .headers(headers)
+        .send()
+        .map_err(|e| anyhow!("Failed to download update: {}", e))?;
+    Ok(response.binary())
+}
+
+fn verify(public_key: &str, data: &[u8], signature: &str) -> Result<()> {
+    let public_key = minisign_verify::PublicKey::from_base64(public_key.trim())
+        .map_err(|e| anyhow!("Invalid update public key: {}", e))?;
+    let signature = minisign_verify::Signature::decode(signature)
+        .map_err(|e| anyhow!("Invalid update signature: {}", e))?;
+    public_key
+        .verify(data, &signature, false)
+        .map_err(|_| anyhow!("The downloaded update failed signature verification"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_cover_the_file_in_chunks() {
+        assert_eq!(next_range(0, 10), Some((0, 9)));
+        assert_eq!(next_range(0, CHUNK_SIZE * 2), Some((0, CHUNK_SIZE - 1)));
+        assert_eq!(
+            next_range(CHUNK_SIZE, CHUNK_SIZE + 5),
+            Some((CHUNK_SIZE, CHUNK_SIZE + 4))
+        );
+        assert_eq!(next_range(10, 10), None);
+    }
+
+    #[test]
+    fn rejects_bad_signatures() {
+        let key = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+        assert!(verify(key, b"data", "not a signature").is_err());
+        assert!(verify("not a key", b"data", "not a signature").is_err());
+    }
+}
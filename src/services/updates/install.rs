@@ -0,0 +1,108 @@
+//! Installing a downloaded, verified update over the running app bundle.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context as _, Result, anyhow};
+
+/// The `.app` bundle the running binary lives in.
+fn current_bundle() -> Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    // PGUI.app/Contents/MacOS/pgui
+    exe.ancestors()
+        .nth(3)
+        .filter(|bundle| bundle.extension().is_some_and(|ext| ext == "app"))
+        .map(Path::to_path_buf)
+        .ok_or_else(|| anyhow!("pgui is not running from an app bundle"))
+}
+
+/// Whether an update can replace the running app in place.
+pub fn can_install_updates() -> bool {
+    cfg!(target_os = "macos") && current_bundle().is_ok()
+}
+
+/// Copy the app out of the downloaded disk image over the running bundle
+/// and return the bundle path to relaunch.
+pub async fn install_update(image: PathBuf) -> Result<PathBuf> {
+    smol::unblock(move || {
+        if !cfg!(target_os = "macos") {
+            return Err(anyhow!("Installing updates is only supported on macOS"));
+        }
+        let bundle = current_bundle()?;
+        let mount = image.with_extension("mount");
+
+        run(
+            "hdiutil",
+            &[
+                "attach".as_ref(),
+                "-nobrowse".as_ref(),
+                "-readonly".as_ref(),
+                "-mountpoint".as_ref(),
+                mount.as_os_str(),
+                image.as_os_str(),
+            ],
+        )?;
+        let source = mount.join("PGUI.app");
+        // ditto merges into an existing bundle, leaving files the new
+        // version dropped, so the app is copied beside the bundle and
+        // swapped in whole.
+        let staged = bundle.with_extension("app.update");
+        let copied = if source.exists() {
+            let _ = std::fs::remove_dir_all(&staged);
+            run("ditto", &[source.as_os_str(), staged.as_os_str()])
+        } else {
+            Err(anyhow!("The disk image does not contain PGUI.app"))
+        };
+        let detached = run("hdiutil", &["detach".as_ref(), mount.as_os_str()]);
+        if let Err(e) = copied
+            .and(detached)
+            .and_then(|_| replace_bundle(&bundle, &staged))
+        {
+            let _ = std::fs::remove_dir_all(&staged);
+            return Err(e);
+        }
+
+        let _ = std::fs::remove_file(&image);
+        Ok(bundle)
+    })
+    .await
+}
+
+/// Swap `staged` in for `bundle`, putting the old bundle back if the new
+/// one cannot be moved into place.
+fn replace_bundle(bundle: &Path, staged: &Path) -> Result<()> {
+    let old = bundle.with_extension("app.old");
+    let _ = std::fs::remove_dir_all(&old);
+    std::fs::rename(bundle, &old).context("Failed to move the old app aside")?;
+    if let Err(e) = std::fs::rename(staged, bundle) {
+        let _ = std::fs::rename(&old, bundle);
+        return Err(e).context("Failed to move the new app into place");
+    }
+    let _ = std::fs::remove_dir_all(&old);
+    Ok(())
+}
+
+/// Start a new instance of the bundle; the caller quits the current one.
+pub fn relaunch(bundle: &Path) -> Result<()> {
+    Command::new("open")
+        .arg("-n")
+        .arg(bundle)
+        .spawn()
+        .context("Failed to relaunch pgui")?;
+    Ok(())
+}
+
+fn run(cmd: &str, args: &[&std::ffi::OsStr]) -> Result<()> {
+    let output = Command::new(cmd)
+        .args(args)
+        .output()
+        .context(format!("Failed to run {}", cmd))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} failed: {}",
+            cmd,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
@@ -1,3 +1,7 @@
+mod download;
+mod install;
 mod update_checker;
 
-pub use update_checker::{UpdateInfo, check_for_update};
+pub use download::{can_verify_updates, download_update};
+pub use install::{can_install_updates, install_update, relaunch};
+pub use update_checker::{UpdateChannel, UpdateInfo, check_for_update};
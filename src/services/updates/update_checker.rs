@@ -1,23 +1,59 @@
 use anyhow::{Result, anyhow};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-const GITHUB_RELEASES_URL: &str = "https://api.github.com/repos/duanebester/pgui/releases/latest";
+const GITHUB_RELEASES_URL: &str = "https://api.github.com/repos/duanebester/pgui/releases";
+
+/// Which releases the app offers to update to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Pre-releases as well as stable releases.
+    Beta,
+}
+
+impl UpdateChannel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "Stable",
+            UpdateChannel::Beta => "Beta",
+        }
+    }
+}
+
+/// A downloadable file attached to a release.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub size: u64,
+    pub browser_download_url: String,
+}
 
 /// Represents the latest release info from GitHub
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct UpdateInfo {
     pub current_version: String,
     pub latest_version: String,
     pub release_url: String,
     pub release_notes: Option<String>,
+    pub prerelease: bool,
+    /// The build for this platform, if the release has one.
+    pub asset: Option<ReleaseAsset>,
+    /// The minisign signature of `asset`.
+    pub signature: Option<ReleaseAsset>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 struct GitHubRelease {
     tag_name: String,
     html_url: String,
     body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,33 +61,69 @@ struct GitHubError {
     message: String,
 }
 
-/// Check if a newer version is available
-pub async fn check_for_update() -> Result<Option<UpdateInfo>> {
-    let current_version = env!("CARGO_PKG_VERSION");
-
-    // Fetch latest release from GitHub
-    let release = fetch_latest_release().await?;
+/// Suffix of the release asset built for this platform; `None` where
+/// releases do not ship a build.
+fn platform_asset_suffix() -> Option<&'static str> {
+    if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some("-macos-arm64.dmg")
+    } else {
+        None
+    }
+}
 
-    // Parse versions (strip 'v' prefix if present)
-    let latest_tag = release.tag_name.trim_start_matches('v');
+/// Check if a newer version is available on `channel`
+pub async fn check_for_update(channel: UpdateChannel) -> Result<Option<UpdateInfo>> {
+    let current_version = env!("CARGO_PKG_VERSION");
     let current = semver::Version::parse(current_version)
         .map_err(|e| anyhow!("Failed to parse current version: {}", e))?;
-    let latest = semver::Version::parse(latest_tag)
-        .map_err(|e| anyhow!("Failed to parse latest version: {}", e))?;
-
-    if latest > current {
-        Ok(Some(UpdateInfo {
-            current_version: current_version.to_string(),
-            latest_version: latest_tag.to_string(),
-            release_url: release.html_url,
-            release_notes: release.body,
-        }))
-    } else {
-        Ok(None)
+
+    let releases = fetch_releases().await?;
+    if releases.is_empty() {
+        return Err(anyhow!("No releases found"));
     }
+
+    Ok(
+        newest_release(&releases, channel, &current).map(|(latest, release)| {
+            let asset = platform_asset_suffix()
+                .and_then(|suffix| release.assets.iter().find(|a| a.name.ends_with(suffix)))
+                .cloned();
+            let signature = asset.as_ref().and_then(|asset| {
+                let name = format!("{}.minisig", asset.name);
+                release.assets.iter().find(|a| a.name == name).cloned()
+            });
+            UpdateInfo {
+                current_version: current_version.to_string(),
+                latest_version: latest.to_string(),
+                release_url: release.html_url.clone(),
+                release_notes: release.body.clone(),
+                prerelease: release.prerelease,
+                asset,
+                signature,
+            }
+        }),
+    )
+}
+
+/// The highest published release newer than `current`. Pre-releases only
+/// count on the beta channel; tags that are not semver are ignored.
+fn newest_release<'a>(
+    releases: &'a [GitHubRelease],
+    channel: UpdateChannel,
+    current: &semver::Version,
+) -> Option<(semver::Version, &'a GitHubRelease)> {
+    releases
+        .iter()
+        .filter(|r| !r.draft)
+        .filter(|r| channel == UpdateChannel::Beta || !r.prerelease)
+        .filter_map(|r| {
+            let version = semver::Version::parse(r.tag_name.trim_start_matches('v')).ok()?;
+            Some((version, r))
+        })
+        .filter(|(version, _)| version > current)
+        .max_by(|(a, _), (b, _)| a.cmp(b))
 }
 
-async fn fetch_latest_release() -> Result<GitHubRelease> {
+async fn fetch_releases() -> Result<Vec<GitHubRelease>> {
     smol::unblock(|| {
         let response = smolhttp::Client::new(GITHUB_RELEASES_URL)
             .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?
@@ -64,15 +136,10 @@ async fn fetch_latest_release() -> Result<GitHubRelease> {
                 ),
             ])
             .send()
-            .map_err(|e| anyhow!("Failed to fetch release: {}", e))?;
+            .map_err(|e| anyhow!("Failed to fetch releases: {}", e))?;
 
         let body = response.text();
 
-        // Check if it's an error response
-        if body.contains("\"message\"") && body.contains("Not Found") {
-            return Err(anyhow!("No releases found"));
-        }
-
         // Try to parse as error first
         if let Ok(error) = serde_json::from_str::<GitHubError>(&body) {
             if error.message.contains("rate limit") {
@@ -86,3 +153,40 @@ async fn fetch_latest_release() -> Result<GitHubRelease> {
     })
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool, draft: bool) -> GitHubRelease {
+        GitHubRelease {
+            tag_name: tag.to_string(),
+            html_url: format!("https://example.com/{}", tag),
+            body: None,
+            draft,
+            prerelease,
+            assets: vec![],
+        }
+    }
+
+    #[test]
+    fn channels_pick_the_newest_release() {
+        let releases = vec![
+            release("v0.2.0-beta.1", true, false),
+            release("v0.1.23", false, false),
+            release("v0.3.0", false, true),
+            release("nightly", true, false),
+            release("v0.1.21", false, false),
+        ];
+        let current = semver::Version::parse("0.1.22").unwrap();
+
+        let (stable, _) = newest_release(&releases, UpdateChannel::Stable, &current).unwrap();
+        assert_eq!(stable.to_string(), "0.1.23");
+        let (beta, r) = newest_release(&releases, UpdateChannel::Beta, &current).unwrap();
+        assert_eq!(beta.to_string(), "0.2.0-beta.1");
+        assert!(r.prerelease);
+
+        let newest = semver::Version::parse("0.2.0").unwrap();
+        assert!(newest_release(&releases, UpdateChannel::Beta, &newest).is_none());
+    }
+}
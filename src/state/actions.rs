@@ -7,73 +7,317 @@ use std::time::Duration;
 
 use gpui::*;
 
-use crate::services::{AppStore, ConnectionInfo, ConnectionsRepository, DatabaseManager};
+use std::path::PathBuf;
 
-use super::connection::{ConnectionState, ConnectionStatus};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::services::{
+    AppStore, ConnectionInfo, ConnectionsRepository, DatabaseDriver, DatabaseManager,
+    SchemaSnapshot, TunnelStatus, is_authentication_error,
+    sql::push_recent,
+    storage::{SAMPLE_INTERVAL_HOURS, SavedWorkspace},
+    with_active_schema,
+};
+
+use super::activity::TransactionAlerts;
+use super::connection::{
+    ConnectionState, ConnectionStatus, RECENT_CONNECTIONS, WindowConnection,
+};
 use super::database::DatabaseState;
-use super::editor::EditorState;
+use super::display::DisplayState;
+use super::editor::{EditorRefactorings, EditorState};
+use super::files::{FilesState, RECENT_FILES_KEY};
+use super::health::HealthState;
+use super::jobs::JobsState;
+use super::notifications::{NotificationSource, NotificationsState};
+use super::vault::VaultState;
+use super::windows::WindowScoped;
+use super::workspaces::WorkspacesState;
+
+// =============================================================================
+// Window Lifecycle
+// =============================================================================
+
+/// Starts the state of a newly opened window: its own connection, not yet
+/// open, and the schema and health that come with it.
+pub fn open_window(window: WindowId, cx: &mut App) {
+    WindowConnection::open(window, cx);
+    EditorState::open(window, cx);
+    EditorRefactorings::open(window, cx);
+    DatabaseState::open(window, cx);
+    HealthState::open(window, cx);
+    JobsState::open(window, cx);
+    TransactionAlerts::open(window, cx);
+
+    let tab = (1..)
+        .find(|n| WindowConnection::all(cx).all(|(_, state)| state.tab != *n))
+        .unwrap_or(1);
+    let db_manager = WindowConnection::update(window, cx, |state, _cx| {
+        state.tab = tab;
+        state.db_manager.clone()
+    });
+    let max_rows = cx.global::<DisplayState>().max_rows;
+    cx.spawn(async move |_cx| {
+        db_manager.set_max_rows(max_rows).await;
+        db_manager.set_tab(tab).await;
+    })
+    .detach();
+}
+
+/// Closes the connection of a closed window and drops its state.
+pub fn close_window(window: WindowId, cx: &mut App) {
+    EditorState::remove(window, cx);
+    EditorRefactorings::remove(window, cx);
+    DatabaseState::remove(window, cx);
+    HealthState::remove(window, cx);
+    JobsState::remove(window, cx);
+    TransactionAlerts::remove(window, cx);
+    if let Some(connection) = WindowConnection::remove(window, cx) {
+        let db_manager = connection.db_manager;
+        cx.spawn(async move |_cx| {
+            if let Err(e) = db_manager.disconnect().await {
+                tracing::warn!("Failed to disconnect a closed window: {}", e);
+            }
+        })
+        .detach();
+    }
+}
 
 // =============================================================================
 // Connection Lifecycle
 // =============================================================================
 
-/// Initiates a connection to the database.
-/// Updates ConnectionState, EditorState, and DatabaseState on success.
-pub fn connect(connection_info: &ConnectionInfo, cx: &mut App) {
-    cx.update_global::<ConnectionState, _>(|state, _cx| {
+/// Time between health pings of the active connection.
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Failed pings in a row after which the connection counts as lost.
+const MAX_FAILED_PINGS: usize = 3;
+
+/// Initiates a connection to the database in `window`.
+/// Updates the window's WindowConnection, EditorState, and DatabaseState on
+/// success.
+pub fn connect(window: WindowId, connection_info: &ConnectionInfo, cx: &mut App) {
+    let db_manager = WindowConnection::update(window, cx, |state, _cx| {
         state.connection_state = ConnectionStatus::Connecting;
+        state.db_manager.clone()
     });
 
     let cic = connection_info.clone();
-    let db_manager = cx.global::<ConnectionState>().db_manager.clone();
 
-    cx.spawn(async move |cx| connect_async(cic, db_manager, cx).await)
+    cx.spawn(async move |cx| connect_async(window, cic, None, db_manager, cx).await)
         .detach();
 }
 
-/// Disconnects from the current database.
-/// Updates ConnectionState and LLMState.
-pub fn disconnect(cx: &mut App) {
-    let db_manager = cx.global::<ConnectionState>().db_manager.clone();
-    cx.spawn(async move |cx| disconnect_async(db_manager, cx).await)
+/// Connects with a password typed after the saved one was rejected. With
+/// `update_keychain`, the password replaces the saved one once the
+/// connection succeeds.
+pub fn connect_with_password(
+    window: WindowId,
+    connection_info: &ConnectionInfo,
+    password: String,
+    update_keychain: bool,
+    cx: &mut App,
+) {
+    let db_manager = WindowConnection::update(window, cx, |state, _cx| {
+        state.connection_state = ConnectionStatus::Connecting;
+        state.db_manager.clone()
+    });
+
+    let cic = connection_info.clone();
+    let typed = TypedPassword {
+        password,
+        update_keychain,
+    };
+
+    cx.spawn(async move |cx| connect_async(window, cic, Some(typed), db_manager, cx).await)
+        .detach();
+}
+
+/// Connects to a `postgres://` or `mysql://` URL without saving it. The
+/// connection is kept as `WindowConnection::quick_connection` so it can be
+/// saved afterwards.
+pub fn quick_connect(window: WindowId, url: &str, cx: &mut App) -> anyhow::Result<()> {
+    let connection = ConnectionInfo::from_url(url)?;
+    WindowConnection::update(window, cx, |state, _cx| {
+        state.quick_connection = Some(connection.clone());
+    });
+    let password = connection.password.clone();
+    connect_with_password(window, &connection, password, false, cx);
+    Ok(())
+}
+
+/// Connects `window` to the saved connection marked to open at startup,
+/// if any, once its password can be read from a locked vault.
+pub fn auto_connect(window: WindowId, cx: &mut App) {
+    cx.spawn(async move |cx| {
+        let Ok(store) = AppStore::singleton().await else {
+            return;
+        };
+        let repo = store.connections();
+        let connection = match repo.auto_connect().await {
+            Ok(Some(id)) => repo.get(&id).await,
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+        match connection {
+            Ok(Some(connection)) => {
+                let _ = cx.update(|cx| {
+                    VaultState::after_unlock(cx, move |cx| {
+                        let idle = WindowConnection::of(window, cx).connection_state
+                            == ConnectionStatus::Disconnected;
+                        if idle {
+                            connect(window, &connection, cx);
+                        }
+                    });
+                });
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load the startup connection: {}", e),
+        }
+    })
+    .detach();
+}
+
+/// Opens `connection_id` when pgui starts, in place of any other
+/// connection; `None` turns auto-connect off.
+pub fn set_auto_connect(connection_id: Option<Uuid>, cx: &mut App) {
+    cx.update_global::<ConnectionState, _>(|state, _cx| {
+        state.auto_connect = connection_id;
+    });
+    cx.spawn(async move |_cx| {
+        if let Ok(store) = AppStore::singleton().await {
+            if let Err(e) = store
+                .connections()
+                .set_auto_connect(connection_id.as_ref())
+                .await
+            {
+                tracing::warn!("Failed to save the startup connection: {}", e);
+            }
+        }
+    })
+    .detach();
+}
+
+/// Marks `connection_id` as a production database, or not.
+pub fn set_production(connection_id: Uuid, production: bool, cx: &mut App) {
+    cx.update_global::<ConnectionState, _>(|state, _cx| {
+        state.production_connections.retain(|id| *id != connection_id);
+        if production {
+            state.production_connections.push(connection_id);
+        }
+    });
+    cx.spawn(async move |_cx| {
+        if let Ok(store) = AppStore::singleton().await {
+            if let Err(e) = store
+                .connections()
+                .set_production(&connection_id, production)
+                .await
+            {
+                tracing::warn!("Failed to save the production flag: {}", e);
+            }
+        }
+    })
+    .detach();
+}
+
+/// Disconnects `window` from its database.
+/// Updates the window's WindowConnection.
+pub fn disconnect(window: WindowId, cx: &mut App) {
+    let db_manager = WindowConnection::of(window, cx).db_manager.clone();
+    cx.spawn(async move |cx| disconnect_async(window, db_manager, cx).await)
         .detach();
 }
 
 /// Changes to a different database on the same server.
 /// Disconnects from current database and reconnects to the new one.
-pub fn change_database(database_name: String, cx: &mut App) {
-    let current_connection = cx.global::<ConnectionState>().active_connection.clone();
+pub fn change_database(window: WindowId, database_name: String, cx: &mut App) {
+    let current_connection = WindowConnection::of(window, cx).active_connection.clone();
 
     if let Some(mut new_connection) = current_connection {
         new_connection.database = database_name;
+        // Schemas belong to the old database.
+        new_connection.search_path = None;
+        reconnect(window, new_connection, cx);
+    }
+}
 
-        let db_manager = cx.global::<ConnectionState>().db_manager.clone();
-        cx.spawn(async move |cx| {
-            disconnect_async(db_manager.clone(), cx).await;
-            // Wait a brief moment for cleanup
-            cx.background_executor()
-                .timer(Duration::from_millis(100))
-                .await;
-            // Connect to the new database
-            connect_async(new_connection, db_manager, cx).await;
-        })
-        .detach();
+/// Makes `schema` the first entry of the session's search path. Every
+/// pooled session needs it, so this reconnects with the new path.
+pub fn set_active_schema(window: WindowId, schema: String, cx: &mut App) {
+    let current_connection = WindowConnection::of(window, cx).active_connection.clone();
+
+    if let Some(mut new_connection) = current_connection {
+        let search_path = &EditorState::of(window, cx).search_path;
+        new_connection.search_path = Some(with_active_schema(search_path, &schema));
+        reconnect(window, new_connection, cx);
     }
 }
 
+/// Refreshes the database list and, on Postgres, the schema list after
+/// one was created or dropped.
+pub fn reload_databases(window: WindowId, cx: &mut App) {
+    let connection = WindowConnection::of(window, cx);
+    let db_manager = connection.db_manager.clone();
+    let driver = connection.active_connection.as_ref().map(|c| c.driver);
+    cx.spawn(async move |cx| {
+        if let Ok(databases) = db_manager.get_databases().await {
+            let _ = cx.update(|cx| {
+                DatabaseState::update(window, cx, |state, _cx| {
+                    state.databases = databases;
+                })
+            });
+        }
+        if driver == Some(DatabaseDriver::Postgres)
+            && let Ok(schemas) = db_manager.get_schemas().await
+        {
+            let _ = cx.update(|cx| {
+                EditorState::update(window, cx, |state, _cx| {
+                    state.schemas = schemas;
+                })
+            });
+        }
+    })
+    .detach();
+}
+
+/// Reconnects with the password the active connection was opened with,
+/// which stays in memory for the session: a quick connection has none
+/// saved, and a typed one may differ from the saved one.
+fn reconnect(window: WindowId, connection: ConnectionInfo, cx: &mut App) {
+    let db_manager = WindowConnection::of(window, cx).db_manager.clone();
+    let typed = TypedPassword {
+        password: connection.password.clone(),
+        update_keychain: false,
+    };
+    cx.spawn(async move |cx| {
+        disconnect_async(window, db_manager.clone(), cx).await;
+        // Wait a brief moment for cleanup
+        cx.background_executor()
+            .timer(Duration::from_millis(100))
+            .await;
+        connect_async(window, connection, Some(typed), db_manager, cx).await;
+    })
+    .detach();
+}
+
 // =============================================================================
 // Connection CRUD Operations
 // =============================================================================
 
 /// Adds a new connection to the saved connections store.
-pub fn add_connection(connection: ConnectionInfo, cx: &mut App) {
+pub fn add_connection(window: WindowId, connection: ConnectionInfo, cx: &mut App) {
     cx.spawn(async move |cx| {
         if let Ok(store) = AppStore::singleton().await {
             if let Ok(_) = store.connections().create(&connection).await {
                 if let Ok(connections) = store.connections().load_all().await {
-                    let _ = cx.update_global::<ConnectionState, _>(|app_state, _cx| {
-                        app_state.saved_connections = connections;
-                        app_state.active_connection = None;
+                    let _ = cx.update(|cx| {
+                        cx.update_global::<ConnectionState, _>(|app_state, _cx| {
+                            app_state.saved_connections = connections;
+                        });
+                        WindowConnection::update(window, cx, |state, _cx| {
+                            state.active_connection = None;
+                        });
                     });
                 }
             }
@@ -83,14 +327,18 @@ pub fn add_connection(connection: ConnectionInfo, cx: &mut App) {
 }
 
 /// Updates an existing connection in the saved connections store.
-pub fn update_connection(connection: ConnectionInfo, cx: &mut App) {
+pub fn update_connection(window: WindowId, connection: ConnectionInfo, cx: &mut App) {
     cx.spawn(async move |cx| {
         if let Ok(store) = AppStore::singleton().await {
             if let Ok(_) = store.connections().update(&connection).await {
                 if let Ok(connections) = store.connections().load_all().await {
-                    let _ = cx.update_global::<ConnectionState, _>(|app_state, _cx| {
-                        app_state.saved_connections = connections;
-                        app_state.active_connection = Some(connection);
+                    let _ = cx.update(|cx| {
+                        cx.update_global::<ConnectionState, _>(|app_state, _cx| {
+                            app_state.saved_connections = connections;
+                        });
+                        WindowConnection::update(window, cx, |state, _cx| {
+                            state.active_connection = Some(connection);
+                        });
                     });
                 }
             }
@@ -116,89 +364,370 @@ pub fn delete_connection(connection: ConnectionInfo, cx: &mut App) {
     .detach();
 }
 
+// =============================================================================
+// Named Workspaces
+// =============================================================================
+
+/// Saves a workspace snapshot under `name`, replacing one of the same name.
+pub fn save_workspace<T: Serialize + Send + 'static>(name: String, snapshot: T, cx: &mut App) {
+    cx.spawn(async move |cx| {
+        if let Ok(store) = AppStore::singleton().await {
+            if let Err(e) = store.workspaces().save(&name, &snapshot).await {
+                tracing::error!("Failed to save workspace: {}", e);
+                return;
+            }
+            reload_workspaces(store, cx).await;
+        }
+    })
+    .detach();
+}
+
+/// Deletes a saved workspace.
+pub fn delete_workspace(id: Uuid, cx: &mut App) {
+    cx.spawn(async move |cx| {
+        if let Ok(store) = AppStore::singleton().await {
+            if let Ok(_) = store.workspaces().delete(&id).await {
+                reload_workspaces(store, cx).await;
+            }
+        }
+    })
+    .detach();
+}
+
+/// Opens a saved workspace in `window`: connects to its connection, if it
+/// still exists, and hands the snapshot to the window's main view to
+/// restore the layout.
+pub fn open_workspace(
+    window: WindowId,
+    workspace: SavedWorkspace,
+    connection: Option<ConnectionInfo>,
+    cx: &mut App,
+) {
+    cx.update_global::<WorkspacesState, _>(|state, _cx| {
+        state.pending_restore = Some((window, workspace));
+    });
+
+    let Some(connection) = connection else {
+        return;
+    };
+    let state = WindowConnection::of(window, cx);
+    if state.active_connection.as_ref().map(|c| c.id) == Some(connection.id) {
+        return;
+    }
+
+    let connected = state.active_connection.is_some();
+    let db_manager = WindowConnection::update(window, cx, |state, _cx| {
+        state.connection_state = ConnectionStatus::Connecting;
+        state.db_manager.clone()
+    });
+    cx.spawn(async move |cx| {
+        if connected {
+            disconnect_async(window, db_manager.clone(), cx).await;
+        }
+        connect_async(window, connection, None, db_manager, cx).await;
+    })
+    .detach();
+}
+
+// =============================================================================
+// SQL Files
+// =============================================================================
+
+/// Asks the editor to open a `.sql` file.
+pub fn open_file(path: PathBuf, cx: &mut App) {
+    cx.update_global::<FilesState, _>(|state, _cx| {
+        state.pending_open = Some(path);
+    });
+}
+
+/// Records a file as recently opened or saved.
+pub fn add_recent_file(path: PathBuf, cx: &mut App) {
+    let recent = cx.update_global::<FilesState, _>(|state, _cx| {
+        push_recent(&mut state.recent, path);
+        state.recent.clone()
+    });
+
+    cx.spawn(async move |_cx| {
+        if let Ok(store) = AppStore::singleton().await {
+            if let Err(e) = store
+                .workspace_state()
+                .set_json(RECENT_FILES_KEY, &recent)
+                .await
+            {
+                tracing::warn!("Failed to save recent files: {}", e);
+            }
+        }
+    })
+    .detach();
+}
+
 // =============================================================================
 // Private Async Helpers
 // =============================================================================
 
-async fn connect_async(mut cic: ConnectionInfo, db_manager: DatabaseManager, cx: &mut AsyncApp) {
-    // Load password from keychain on-demand
-    if let Ok(password) = ConnectionsRepository::get_connection_password(&cic.id) {
-        cic.password = password;
-    } else {
-        let _ = cx.update_global::<ConnectionState, _>(|state, _cx| {
-            state.connection_state = ConnectionStatus::Disconnected;
+/// A password to use instead of the saved one: typed into the prompt shown
+/// after authentication failed, or the one a reconnect carries over.
+struct TypedPassword {
+    password: String,
+    update_keychain: bool,
+}
+
+async fn connect_async(
+    window: WindowId,
+    mut cic: ConnectionInfo,
+    typed: Option<TypedPassword>,
+    db_manager: DatabaseManager,
+    cx: &mut AsyncApp,
+) {
+    // Load password from keychain on-demand. A connection without a saved
+    // password is tried without one; the server asks for it if needed.
+    cic.password = match &typed {
+        Some(typed) => typed.password.clone(),
+        None => ConnectionsRepository::get_connection_password(&cic.id).unwrap_or_default(),
+    };
+
+    if let Err(e) = db_manager.connect(&cic).await {
+        tracing::warn!("Failed to connect to {}: {}", cic.name, e);
+        let auth_failed = is_authentication_error(&e);
+        cic.password.clear();
+        let _ = cx.update(|cx| {
+            WindowConnection::update(window, cx, |state, _cx| {
+                state.active_connection = None;
+                state.connection_state = ConnectionStatus::Disconnected;
+                if auth_failed {
+                    state.password_prompt = Some(cic);
+                }
+            })
         });
         return;
     }
 
-    if let Ok(_) = db_manager.connect(&cic).await {
-        if let Ok(tables) = db_manager.get_tables().await {
-            let _ = cx.update_global::<EditorState, _>(|state, _cx| {
-                state.tables = tables;
-            });
+    if let Some(typed) = typed.filter(|t| t.update_keychain) {
+        if let Err(e) = ConnectionsRepository::update_connection_password(&cic.id, &typed.password)
+        {
+            tracing::warn!("Failed to update the saved password: {}", e);
         }
+    }
 
-        if let Ok(schema) = db_manager.get_schema(None).await {
-            let _ = cx.update_global::<EditorState, _>(|state, _cx| {
+    if let Ok(tables) = db_manager.get_tables().await {
+        let _ = cx.update(|cx| {
+            EditorState::update(window, cx, |state, _cx| {
+                state.tables = tables;
+            })
+        });
+    }
+
+    let mut snapshot = None;
+    if let Ok(schema) = db_manager.get_schema(None).await {
+        snapshot = Some(SchemaSnapshot::from_schema(&schema));
+        let _ = cx.update(|cx| {
+            EditorState::update(window, cx, |state, _cx| {
                 state.schema = Some(schema);
+            })
+        });
+    }
+
+    // Only Postgres has a search path; MySQL leaves both lists empty.
+    let (schemas, search_path) = match cic.driver {
+        DatabaseDriver::Postgres => (
+            db_manager.get_schemas().await.unwrap_or_default(),
+            db_manager.get_search_path().await.unwrap_or_default(),
+        ),
+        DatabaseDriver::MySql => (vec![], vec![]),
+    };
+    let user_types = db_manager.get_user_types().await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load user-defined types: {}", e);
+        vec![]
+    });
+    let _ = cx.update(|cx| {
+        EditorState::update(window, cx, |state, _cx| {
+            state.schemas = schemas;
+            state.search_path = search_path;
+            state.user_types = user_types;
+        })
+    });
+
+    if let Ok(databases) = db_manager.get_databases().await {
+        let _ = cx.update(|cx| {
+            DatabaseState::update(window, cx, |state, _cx| {
+                state.databases = databases;
+            })
+        });
+    }
+
+    let connection_id = cic.id;
+    let database = cic.database.clone();
+    let connection_name = cic.name.clone();
+    let _ = cx.update(|cx| {
+        WindowConnection::update(window, cx, |state, _cx| {
+            state.active_connection = Some(cic);
+            state.connection_state = ConnectionStatus::Connected;
+            state.transaction_open = false;
+        })
+    });
+    if let Ok(store) = AppStore::singleton().await {
+        let repo = store.connections();
+        if let Err(e) = repo.mark_used(&connection_id).await {
+            tracing::warn!("Failed to record connection use: {}", e);
+        }
+        if let Ok(recent) = repo.recently_used(RECENT_CONNECTIONS).await {
+            let _ = cx.update_global::<ConnectionState, _>(|state, _cx| {
+                state.recent_connections = recent;
             });
         }
 
-        if let Ok(databases) = db_manager.get_databases().await {
-            let _ = cx.update_global::<DatabaseState, _>(|state, _cx| {
-                state.databases = databases;
+        // Sizing every table can take a while on a big database, so it
+        // runs alongside the monitoring loop instead of before it.
+        let table_sizes = store.table_sizes();
+        let sampling_manager = db_manager.clone();
+        let sampled_database = database.clone();
+        cx.background_spawn(async move {
+            let last_sampled = table_sizes
+                .last_sampled_at(&connection_id, &sampled_database)
+                .await
+                .ok()
+                .flatten();
+            let due = last_sampled.is_none_or(|last| {
+                chrono::Utc::now() - last >= chrono::Duration::hours(SAMPLE_INTERVAL_HOURS)
             });
+            if due
+                && let Err(e) =
+                    record_table_sizes(&sampling_manager, connection_id, &sampled_database).await
+            {
+                tracing::warn!("Failed to sample table sizes: {}", e);
+            }
+        })
+        .detach();
+        if let Some(snapshot) = snapshot
+            && let Err(e) = record_schema_snapshot(connection_id, &database, &snapshot).await
+        {
+            tracing::warn!("Failed to snapshot the schema: {}", e);
         }
+    }
+    let _ = cx.update(|cx| {
+        HealthState::update(window, cx, |state, _cx| {
+            *state = HealthState::default();
+        })
+    });
 
-        let _ = cx.update_global::<ConnectionState, _>(|state, _cx| {
-            state.active_connection = Some(cic);
-            state.connection_state = ConnectionStatus::Connected;
-        });
+    // Connection monitoring loop: ping for the health indicator, and give
+    // up on the connection after several failed pings in a row.
+    loop {
+        let latency = db_manager.ping().await.ok();
+        // The SSH keepalive blocks until the server answers
+        let tunnel = cx
+            .background_spawn({
+                let db_manager = db_manager.clone();
+                async move { db_manager.tunnel_status().await }
+            })
+            .await;
+        let (failures, previous_tunnel) = cx
+            .update(|cx| {
+                HealthState::update(window, cx, |state, _cx| {
+                    let previous = std::mem::replace(&mut state.tunnel, tunnel);
+                    state.health.record(latency);
+                    (state.health.consecutive_failures(), previous)
+                })
+            })
+            .unwrap_or((0, tunnel));
+
+        let tunnel_notice = match (previous_tunnel, tunnel) {
+            (TunnelStatus::Up, TunnelStatus::Down) => Some("SSH tunnel lost"),
+            (TunnelStatus::Down, TunnelStatus::Up) => Some("SSH tunnel reconnected"),
+            _ => None,
+        };
+        if let Some(notice) = tunnel_notice {
+            let message = format!("{} to {}", notice, connection_name);
+            let _ = cx.update(|cx| {
+                NotificationsState::push(NotificationSource::Tunnel, message, None, cx);
+            });
+        }
 
-        // Connection monitoring loop
-        loop {
-            let mut connected = db_manager.is_connected().await;
-            if !connected {
-                let _ = cx.update_global::<ConnectionState, _>(|state, _cx| {
+        let mut connected = failures < MAX_FAILED_PINGS;
+        if !connected {
+            let _ = cx.update(|cx| {
+                WindowConnection::update(window, cx, |state, _cx| {
                     state.active_connection = None;
                     state.connection_state = ConnectionStatus::Disconnected;
-                });
-                break;
-            }
-
-            let _ = cx.try_read_global::<ConnectionState, _>(|state, _cx| {
-                if state.active_connection.is_none() {
-                    connected = false;
-                }
+                })
             });
+            break;
+        }
 
-            if !connected {
-                break;
+        let _ = cx.update(|cx| {
+            if WindowConnection::of(window, cx).active_connection.is_none() {
+                connected = false;
             }
+        });
 
-            cx.background_executor()
-                .timer(Duration::from_millis(1000))
-                .await;
+        if !connected {
+            break;
         }
-    } else {
-        tracing::warn!("No Connect :(");
-        let _ = cx.update_global::<ConnectionState, _>(|state, _cx| {
-            state.active_connection = None;
-            state.connection_state = ConnectionStatus::Disconnected;
+
+        cx.background_executor().timer(PING_INTERVAL).await;
+    }
+}
+
+/// Record the size of every table of `database`, for growth forecasts.
+/// Returns the tables sampled; connections that are not saved keep no
+/// history.
+pub async fn record_table_sizes(
+    db_manager: &DatabaseManager,
+    connection_id: Uuid,
+    database: &str,
+) -> anyhow::Result<usize> {
+    let store = AppStore::singleton().await?;
+    if store.connections().get(&connection_id).await?.is_none() {
+        return Ok(0);
+    }
+    let sizes = db_manager.get_table_sizes().await?;
+    store
+        .table_sizes()
+        .record(&connection_id, database, &sizes)
+        .await?;
+    Ok(sizes.len())
+}
+
+/// Store `snapshot` of `database` for the schema change log. Returns
+/// whether it was stored: unchanged schemas and connections that are not
+/// saved keep no new snapshot.
+pub async fn record_schema_snapshot(
+    connection_id: Uuid,
+    database: &str,
+    snapshot: &SchemaSnapshot,
+) -> anyhow::Result<bool> {
+    let store = AppStore::singleton().await?;
+    if store.connections().get(&connection_id).await?.is_none() {
+        return Ok(false);
+    }
+    store
+        .schema_snapshots()
+        .record(&connection_id, database, &serde_json::to_string(snapshot)?)
+        .await
+}
+
+async fn reload_workspaces(store: &AppStore, cx: &mut AsyncApp) {
+    if let Ok(saved) = store.workspaces().load_all().await {
+        let _ = cx.update_global::<WorkspacesState, _>(|state, _cx| {
+            state.saved = saved;
         });
     }
 }
 
-async fn disconnect_async(db_manager: DatabaseManager, cx: &mut AsyncApp) {
-    let _ = cx.update_global::<ConnectionState, _>(|state, _cx| {
-        state.active_connection = None;
-        state.connection_state = ConnectionStatus::Disconnecting;
+async fn disconnect_async(window: WindowId, db_manager: DatabaseManager, cx: &mut AsyncApp) {
+    let _ = cx.update(|cx| {
+        WindowConnection::update(window, cx, |state, _cx| {
+            state.active_connection = None;
+            state.connection_state = ConnectionStatus::Disconnecting;
+        })
     });
 
     if let Ok(_) = db_manager.disconnect().await {
-        let _ = cx.update_global::<ConnectionState, _>(|state, _cx| {
-            state.active_connection = None;
-            state.connection_state = ConnectionStatus::Disconnected;
+        let _ = cx.update(|cx| {
+            WindowConnection::update(window, cx, |state, _cx| {
+                state.active_connection = None;
+                state.connection_state = ConnectionStatus::Disconnected;
+            })
         });
     }
 }
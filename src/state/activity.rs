@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use gpui::*;
+
+use crate::services::{
+    AppStore, DatabaseDriver, TransactionAlert, TransactionAlertKind, TransactionAlertSettings,
+    transaction_alerts,
+};
+
+use super::connection::{ConnectionStatus, WindowConnection};
+use super::windows::WindowScoped;
+
+/// Workspace state key for the transaction alert thresholds.
+const TRANSACTION_ALERTS_KEY: &str = "transaction_alerts";
+
+/// How often sessions are checked for transactions left open.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Thresholds for flagging sessions with transactions open too long, or
+/// idle in transaction. Each window's connection is checked in the
+/// background.
+pub struct ActivityState {
+    pub settings: TransactionAlertSettings,
+}
+
+impl Global for ActivityState {}
+
+/// Flagged sessions of a window's connection.
+#[derive(Default)]
+pub struct TransactionAlerts {
+    /// Flagged sessions as of the last check.
+    pub alerts: Vec<TransactionAlert>,
+    /// Alerts not yet shown as a notification.
+    pub pending: Vec<TransactionAlert>,
+    /// Alerts already notified, so a session is flagged once per episode.
+    notified: HashSet<(i32, TransactionAlertKind)>,
+}
+
+impl WindowScoped for TransactionAlerts {}
+
+impl ActivityState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(ActivityState {
+            settings: TransactionAlertSettings::default(),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Ok(Some(settings)) = store
+                    .workspace_state()
+                    .get_json::<TransactionAlertSettings>(TRANSACTION_ALERTS_KEY)
+                    .await
+                {
+                    let _ = cx.update_global::<ActivityState, _>(|state, _cx| {
+                        state.settings = settings;
+                    });
+                }
+            }
+
+            loop {
+                cx.background_executor().timer(CHECK_INTERVAL).await;
+                let Ok((settings, checks)) = cx.update(|cx| {
+                    let settings = cx.global::<ActivityState>().settings;
+                    let checks = WindowConnection::all(cx)
+                        .map(|(window, connection)| {
+                            let postgres = connection.connection_state
+                                == ConnectionStatus::Connected
+                                && connection
+                                    .active_connection
+                                    .as_ref()
+                                    .is_some_and(|c| c.driver == DatabaseDriver::Postgres);
+                            let check = (postgres && settings.is_enabled())
+                                .then(|| connection.db_manager.clone());
+                            (window, check)
+                        })
+                        .collect::<Vec<_>>();
+                    (settings, checks)
+                }) else {
+                    break;
+                };
+
+                for (window, check) in checks {
+                    let alerts = match check {
+                        Some(db_manager) => match db_manager.session_activity().await {
+                            Ok(sessions) => transaction_alerts(&sessions, &settings),
+                            Err(e) => {
+                                tracing::debug!("Failed to check session activity: {}", e);
+                                continue;
+                            }
+                        },
+                        None => vec![],
+                    };
+                    let _ = cx.update(|cx| {
+                        TransactionAlerts::update(window, cx, |state, _cx| {
+                            state.update_alerts(alerts);
+                        });
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Apply and persist new alert thresholds.
+    pub fn set_settings(settings: TransactionAlertSettings, cx: &mut App) {
+        cx.update_global::<ActivityState, _>(|state, _cx| {
+            state.settings = settings;
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(TRANSACTION_ALERTS_KEY, &settings)
+                    .await
+                {
+                    tracing::warn!("Failed to save transaction alert settings: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+}
+
+impl TransactionAlerts {
+    /// Replace the flagged sessions, queueing notifications for new ones.
+    fn update_alerts(&mut self, alerts: Vec<TransactionAlert>) {
+        let current: HashSet<_> = alerts.iter().map(|a| (a.pid, a.kind)).collect();
+        self.notified.retain(|key| current.contains(key));
+        for alert in &alerts {
+            if self.notified.insert((alert.pid, alert.kind)) {
+                self.pending.push(alert.clone());
+            }
+        }
+        self.alerts = alerts;
+    }
+
+    /// The alert for session `pid`, if it is flagged.
+    pub fn alert(&self, pid: i32) -> Option<&TransactionAlert> {
+        self.alerts.iter().find(|a| a.pid == pid)
+    }
+}
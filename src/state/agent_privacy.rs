@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use gpui::*;
+use uuid::Uuid;
+
+use crate::services::AppStore;
+use crate::services::agent::AgentPrivacy;
+
+use super::connection::WindowConnection;
+use super::windows::WindowScoped;
+
+/// Workspace state key for the agent privacy settings of every connection.
+const AGENT_PRIVACY_KEY: &str = "agent_privacy";
+
+/// What the agent features may send to the model, per saved connection.
+pub struct AgentPrivacyState {
+    settings: HashMap<Uuid, AgentPrivacy>,
+}
+
+impl Global for AgentPrivacyState {}
+
+impl AgentPrivacyState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(AgentPrivacyState {
+            settings: HashMap::new(),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                match store
+                    .workspace_state()
+                    .get_json::<HashMap<Uuid, AgentPrivacy>>(AGENT_PRIVACY_KEY)
+                    .await
+                {
+                    Ok(Some(settings)) => {
+                        let _ = cx.update_global::<AgentPrivacyState, _>(|state, _cx| {
+                            state.settings = settings;
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load agent privacy settings: {}", e),
+                }
+            }
+        })
+        .detach();
+    }
+
+    pub fn for_connection(&self, connection_id: Uuid) -> AgentPrivacy {
+        self.settings
+            .get(&connection_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The settings of the connection open in `window`.
+    pub fn active(window: WindowId, cx: &App) -> AgentPrivacy {
+        WindowConnection::of(window, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| cx.global::<AgentPrivacyState>().for_connection(c.id))
+            .unwrap_or_default()
+    }
+
+    pub fn set(connection_id: Uuid, privacy: AgentPrivacy, cx: &mut App) {
+        cx.update_global::<AgentPrivacyState, _>(|state, _cx| {
+            if privacy == AgentPrivacy::default() {
+                state.settings.remove(&connection_id);
+            } else {
+                state.settings.insert(connection_id, privacy);
+            }
+        });
+
+        let settings = cx.global::<AgentPrivacyState>().settings.clone();
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(AGENT_PRIVACY_KEY, &settings)
+                    .await
+                {
+                    tracing::warn!("Failed to save agent privacy settings: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+}
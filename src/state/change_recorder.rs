@@ -0,0 +1,61 @@
+use gpui::*;
+
+use crate::services::sql::ChangeScript;
+
+use super::connection::WindowConnection;
+use super::windows::WindowScoped;
+
+/// DDL run through pgui while recording is on, kept as a change script
+/// for the current session.
+pub struct ChangeRecorderState {
+    pub recording: bool,
+    pub script: ChangeScript,
+}
+
+impl Global for ChangeRecorderState {}
+
+impl ChangeRecorderState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(ChangeRecorderState {
+            recording: false,
+            script: ChangeScript::default(),
+        });
+    }
+
+    pub fn set_recording(recording: bool, cx: &mut App) {
+        cx.update_global::<ChangeRecorderState, _>(|state, _cx| {
+            state.recording = recording;
+        });
+    }
+
+    /// Add the DDL among `statements`, run on the connection open in
+    /// `window`, to the change script when recording.
+    pub fn record<'a>(
+        window: WindowId,
+        statements: impl IntoIterator<Item = &'a str>,
+        cx: &mut App,
+    ) {
+        if !cx.global::<ChangeRecorderState>().recording {
+            return;
+        }
+        let Some(driver) = WindowConnection::of(window, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| c.driver)
+        else {
+            return;
+        };
+        let statements: Vec<&str> = statements.into_iter().collect();
+        cx.update_global::<ChangeRecorderState, _>(|state, _cx| {
+            for sql in statements {
+                state.script.record(&driver, sql);
+            }
+        });
+    }
+
+    pub fn clear(cx: &mut App) {
+        cx.update_global::<ChangeRecorderState, _>(|state, _cx| {
+            state.script = ChangeScript::default();
+        });
+    }
+}
@@ -1,6 +1,12 @@
 use gpui::*;
+use uuid::Uuid;
 
-use crate::services::{AppStore, ConnectionInfo, DatabaseManager};
+use crate::services::{AppStore, ConnectionInfo, DatabaseDriver, DatabaseManager};
+
+use super::windows::WindowScoped;
+
+/// How many connections the "Recent" section of the list shows.
+pub const RECENT_CONNECTIONS: usize = 3;
 
 #[derive(Clone, PartialEq)]
 pub enum ConnectionStatus {
@@ -12,30 +18,42 @@ pub enum ConnectionStatus {
 
 pub struct ConnectionState {
     pub saved_connections: Vec<ConnectionInfo>,
-    pub active_connection: Option<ConnectionInfo>,
-    pub db_manager: DatabaseManager,
-    pub connection_state: ConnectionStatus,
+    /// Saved connections opened most recently, newest first.
+    pub recent_connections: Vec<Uuid>,
+    /// The saved connection opened when pgui starts.
+    pub auto_connect: Option<Uuid>,
+    /// Saved connections marked as production databases.
+    pub production_connections: Vec<Uuid>,
 }
 
 impl Global for ConnectionState {}
 
 impl ConnectionState {
     pub fn init(cx: &mut App) {
-        let db_manager = DatabaseManager::new();
         let this = ConnectionState {
             saved_connections: vec![],
-            active_connection: None,
-            db_manager,
-            connection_state: ConnectionStatus::Disconnected,
+            recent_connections: vec![],
+            auto_connect: None,
+            production_connections: vec![],
         };
         cx.set_global(this);
 
         // Load saved connections on startup
         cx.spawn(async move |cx| {
             if let Ok(store) = AppStore::singleton().await {
-                if let Ok(connections) = store.connections().load_all().await {
+                let repo = store.connections();
+                if let Ok(connections) = repo.load_all().await {
+                    let recent = repo
+                        .recently_used(RECENT_CONNECTIONS)
+                        .await
+                        .unwrap_or_default();
+                    let auto_connect = repo.auto_connect().await.unwrap_or_default();
+                    let production = repo.production_connections().await.unwrap_or_default();
                     let _ = cx.update_global::<ConnectionState, _>(|app_state, _cx| {
                         app_state.saved_connections = connections;
+                        app_state.recent_connections = recent;
+                        app_state.auto_connect = auto_connect;
+                        app_state.production_connections = production;
                     });
                 }
             }
@@ -43,3 +61,58 @@ impl ConnectionState {
         .detach();
     }
 }
+
+/// The connection open in one window. Each window connects on its own.
+pub struct WindowConnection {
+    pub active_connection: Option<ConnectionInfo>,
+    pub db_manager: DatabaseManager,
+    pub connection_state: ConnectionStatus,
+    /// Set when the server rejected a connection's password, so the UI can
+    /// ask for a new one. Taken by whoever shows the prompt.
+    pub password_prompt: Option<ConnectionInfo>,
+    /// The last connection opened from a URL, which is not saved. Kept so
+    /// it can be saved afterwards.
+    pub quick_connection: Option<ConnectionInfo>,
+    /// Whether the queries run on the connection left a transaction open.
+    pub transaction_open: bool,
+    /// The window's number, from 1, for telling windows apart in query
+    /// tags. Numbers of closed windows are reused.
+    pub tab: usize,
+}
+
+impl Default for WindowConnection {
+    fn default() -> Self {
+        WindowConnection {
+            active_connection: None,
+            db_manager: DatabaseManager::new(),
+            connection_state: ConnectionStatus::Disconnected,
+            password_prompt: None,
+            quick_connection: None,
+            transaction_open: false,
+            tab: 0,
+        }
+    }
+}
+
+impl WindowScoped for WindowConnection {}
+
+impl WindowConnection {
+    /// Whether the connection open in `window` is marked as a production
+    /// database.
+    pub fn is_production(window: WindowId, cx: &App) -> bool {
+        let production = &cx.global::<ConnectionState>().production_connections;
+        Self::of(window, cx)
+            .active_connection
+            .as_ref()
+            .is_some_and(|c| production.contains(&c.id))
+    }
+
+    /// The driver whose SQL dialect applies in `window`, Postgres until it
+    /// connects.
+    pub fn driver(window: WindowId, cx: &App) -> DatabaseDriver {
+        Self::of(window, cx)
+            .active_connection
+            .as_ref()
+            .map_or(DatabaseDriver::Postgres, |c| c.driver)
+    }
+}
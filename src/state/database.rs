@@ -1,16 +1,11 @@
-use gpui::*;
-
 use crate::services::DatabaseInfo;
 
+use super::windows::WindowScoped;
+
+/// The databases on the server a window is connected to.
+#[derive(Default)]
 pub struct DatabaseState {
     pub databases: Vec<DatabaseInfo>,
 }
 
-impl Global for DatabaseState {}
-
-impl DatabaseState {
-    pub fn init(cx: &mut App) {
-        let this = DatabaseState { databases: vec![] };
-        cx.set_global(this);
-    }
-}
+impl WindowScoped for DatabaseState {}
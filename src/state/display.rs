@@ -0,0 +1,142 @@
+use gpui::*;
+
+use crate::services::{AppStore, CellDisplay, DEFAULT_MAX_ROWS, DateTimeDisplay};
+
+use super::connection::WindowConnection;
+use super::windows::WindowScoped;
+
+/// Workspace state key for the date and time display settings.
+const DATE_TIME_DISPLAY_KEY: &str = "date_time_display";
+
+/// Workspace state key for the NULL and whitespace display settings.
+const CELL_DISPLAY_KEY: &str = "cell_display";
+
+/// Workspace state key for the max-rows limit.
+const MAX_ROWS_KEY: &str = "max_rows";
+
+/// How many rows the results grid fetches and how their values are shown.
+pub struct DisplayState {
+    pub date_time: DateTimeDisplay,
+    /// How NULL, empty and whitespace-only values are told apart.
+    pub cells: CellDisplay,
+    /// Rows fetched for a SELECT without its own LIMIT; `None` fetches
+    /// them all.
+    pub max_rows: Option<usize>,
+}
+
+impl Global for DisplayState {}
+
+impl DisplayState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(DisplayState {
+            date_time: DateTimeDisplay::default(),
+            cells: CellDisplay::default(),
+            max_rows: Some(DEFAULT_MAX_ROWS),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Ok(Some(date_time)) = store
+                    .workspace_state()
+                    .get_json::<DateTimeDisplay>(DATE_TIME_DISPLAY_KEY)
+                    .await
+                {
+                    let _ = cx.update_global::<DisplayState, _>(|state, _cx| {
+                        state.date_time = date_time;
+                    });
+                }
+                if let Ok(Some(cells)) = store
+                    .workspace_state()
+                    .get_json::<CellDisplay>(CELL_DISPLAY_KEY)
+                    .await
+                {
+                    let _ = cx.update_global::<DisplayState, _>(|state, _cx| {
+                        state.cells = cells;
+                    });
+                }
+                if let Ok(Some(max_rows)) = store
+                    .workspace_state()
+                    .get_json::<Option<usize>>(MAX_ROWS_KEY)
+                    .await
+                {
+                    let _ = cx.update(|cx| Self::apply_max_rows(max_rows, cx));
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Apply and persist new date and time display settings.
+    pub fn set_date_time(date_time: DateTimeDisplay, cx: &mut App) {
+        cx.update_global::<DisplayState, _>(|state, _cx| {
+            state.date_time = date_time;
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(DATE_TIME_DISPLAY_KEY, &date_time)
+                    .await
+                {
+                    tracing::warn!("Failed to save date and time display: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Apply and persist new NULL and whitespace display settings.
+    pub fn set_cells(cells: CellDisplay, cx: &mut App) {
+        cx.update_global::<DisplayState, _>(|state, _cx| {
+            state.cells = cells;
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(CELL_DISPLAY_KEY, &cells)
+                    .await
+                {
+                    tracing::warn!("Failed to save cell display: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Apply and persist a new max-rows limit.
+    pub fn set_max_rows(max_rows: Option<usize>, cx: &mut App) {
+        Self::apply_max_rows(max_rows, cx);
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(MAX_ROWS_KEY, &max_rows)
+                    .await
+                {
+                    tracing::warn!("Failed to save max rows: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    fn apply_max_rows(max_rows: Option<usize>, cx: &mut App) {
+        cx.update_global::<DisplayState, _>(|state, _cx| {
+            state.max_rows = max_rows;
+        });
+
+        let db_managers: Vec<_> = WindowConnection::all(cx)
+            .map(|(_, connection)| connection.db_manager.clone())
+            .collect();
+        cx.spawn(async move |_cx| {
+            for db_manager in db_managers {
+                db_manager.set_max_rows(max_rows).await;
+            }
+        })
+        .detach();
+    }
+}
@@ -1,23 +1,23 @@
 use gpui::*;
 
-use crate::services::{DatabaseSchema, TableInfo};
+use crate::services::{DatabaseSchema, TableInfo, UserTypeInfo, agent::SchemaSelection};
 
+use super::windows::WindowScoped;
+
+/// The schema of a window's connection, for completions and the tables tree.
+#[derive(Default)]
 pub struct EditorState {
     pub tables: Vec<TableInfo>,
     pub schema: Option<DatabaseSchema>,
+    /// Schemas that can go on the search path (Postgres only).
+    pub schemas: Vec<String>,
+    /// The session's effective `search_path`, empty when not applicable.
+    pub search_path: Vec<String>,
+    /// Enums, composite types and domains (Postgres only).
+    pub user_types: Vec<UserTypeInfo>,
 }
 
-impl Global for EditorState {}
-
-impl EditorState {
-    pub fn init(cx: &mut App) {
-        let this = EditorState {
-            tables: vec![],
-            schema: None,
-        };
-        cx.set_global(this);
-    }
-}
+impl WindowScoped for EditorState {}
 
 pub struct EditorCodeActions {
     pub loading: bool,
@@ -42,3 +42,34 @@ impl EditorInlineCompletions {
         cx.set_global(this);
     }
 }
+
+/// The tables described to the model for the editor's latest agent request.
+pub struct EditorAgentContext {
+    pub selection: Option<SchemaSelection>,
+}
+
+impl Global for EditorAgentContext {}
+impl EditorAgentContext {
+    pub fn init(cx: &mut App) {
+        let this = EditorAgentContext { selection: None };
+        cx.set_global(this);
+    }
+}
+
+/// Generated SQL waiting to be reviewed before it goes into the editor.
+#[derive(Debug, Clone)]
+pub struct PendingRefactoring {
+    pub title: String,
+    pub sql: String,
+    /// Offset in the editor the reviewed SQL is inserted at.
+    pub insert_at: usize,
+}
+
+/// Refactorings produced by code actions, taken by the window's editor to
+/// review.
+#[derive(Default)]
+pub struct EditorRefactorings {
+    pub pending: Option<PendingRefactoring>,
+}
+
+impl WindowScoped for EditorRefactorings {}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use gpui::*;
+
+use crate::services::AppStore;
+
+/// Workspace state key for the recently opened `.sql` files.
+pub(super) const RECENT_FILES_KEY: &str = "recent_sql_files";
+
+pub struct FilesState {
+    /// Recently opened or saved `.sql` files, most recent first.
+    pub recent: Vec<PathBuf>,
+    /// A file to open in the editor, e.g. one the OS asked us to open.
+    /// Taken by the editor.
+    pub pending_open: Option<PathBuf>,
+}
+
+impl Global for FilesState {}
+
+impl FilesState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(FilesState {
+            recent: vec![],
+            pending_open: None,
+        });
+
+        // Load recent files on startup
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Ok(Some(recent)) = store
+                    .workspace_state()
+                    .get_json::<Vec<PathBuf>>(RECENT_FILES_KEY)
+                    .await
+                {
+                    let _ = cx.update_global::<FilesState, _>(|state, _cx| {
+                        state.recent = recent;
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+}
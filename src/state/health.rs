@@ -0,0 +1,12 @@
+use crate::services::{ConnectionHealth, TunnelStatus};
+
+use super::windows::WindowScoped;
+
+/// Latency of a window's connection pings and the state of its tunnel.
+#[derive(Default)]
+pub struct HealthState {
+    pub health: ConnectionHealth,
+    pub tunnel: TunnelStatus,
+}
+
+impl WindowScoped for HealthState {}
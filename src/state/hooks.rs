@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use gpui::*;
+use uuid::Uuid;
+
+use crate::services::{AppStore, ExecutionHooks};
+
+use super::connection::WindowConnection;
+use super::windows::WindowScoped;
+
+/// Workspace state key for the execution hooks of every connection.
+const EXECUTION_HOOKS_KEY: &str = "execution_hooks";
+
+/// Commands run before and after each query, per saved connection.
+pub struct HooksState {
+    hooks: HashMap<Uuid, ExecutionHooks>,
+}
+
+impl Global for HooksState {}
+
+impl HooksState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(HooksState {
+            hooks: HashMap::new(),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                match store
+                    .workspace_state()
+                    .get_json::<HashMap<Uuid, ExecutionHooks>>(EXECUTION_HOOKS_KEY)
+                    .await
+                {
+                    Ok(Some(hooks)) => {
+                        let _ = cx.update_global::<HooksState, _>(|state, _cx| {
+                            state.hooks = hooks;
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load execution hooks: {}", e),
+                }
+            }
+        })
+        .detach();
+    }
+
+    pub fn for_connection(&self, connection_id: Uuid) -> ExecutionHooks {
+        self.hooks.get(&connection_id).cloned().unwrap_or_default()
+    }
+
+    /// The hooks of the connection open in `window`.
+    pub fn active(window: WindowId, cx: &App) -> ExecutionHooks {
+        WindowConnection::of(window, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| cx.global::<HooksState>().for_connection(c.id))
+            .unwrap_or_default()
+    }
+
+    pub fn set(connection_id: Uuid, hooks: ExecutionHooks, cx: &mut App) {
+        cx.update_global::<HooksState, _>(|state, _cx| {
+            if hooks.is_active() {
+                state.hooks.insert(connection_id, hooks);
+            } else {
+                state.hooks.remove(&connection_id);
+            }
+        });
+
+        let hooks = cx.global::<HooksState>().hooks.clone();
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(EXECUTION_HOOKS_KEY, &hooks)
+                    .await
+                {
+                    tracing::warn!("Failed to save execution hooks: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+}
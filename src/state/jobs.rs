@@ -0,0 +1,142 @@
+use chrono::{DateTime, Local};
+use uuid::Uuid;
+
+use crate::services::{QueryExecutionResult, ServerNotice};
+
+use super::windows::WindowScoped;
+
+/// Finished jobs kept in the Jobs panel.
+const MAX_FINISHED_JOBS: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// A query running, or run, in the background.
+pub struct Job {
+    pub id: Uuid,
+    pub sql: String,
+    pub connection_name: String,
+    pub started_at: DateTime<Local>,
+    pub finished_at: Option<DateTime<Local>>,
+    pub status: JobStatus,
+    pub execution_time_ms: Option<u128>,
+    pub result: Option<QueryExecutionResult>,
+    pub notices: Vec<ServerNotice>,
+    /// Each statement's result, for scripts.
+    pub statement_results: Vec<(String, QueryExecutionResult)>,
+    /// Set once the user asked to cancel, until the job finishes.
+    pub cancel_requested: bool,
+    /// Whether the user has seen the outcome; unseen ones are badged.
+    pub seen: bool,
+}
+
+/// The jobs of a window's connection.
+#[derive(Default)]
+pub struct JobsState {
+    /// Most recent first.
+    pub jobs: Vec<Job>,
+}
+
+impl WindowScoped for JobsState {}
+
+impl JobsState {
+    pub fn get(&self, id: Uuid) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: Uuid) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    /// Register a job as running.
+    pub fn start(&mut self, id: Uuid, sql: String, connection_name: String) {
+        self.jobs.insert(
+            0,
+            Job {
+                id,
+                sql,
+                connection_name,
+                started_at: Local::now(),
+                finished_at: None,
+                status: JobStatus::Running,
+                execution_time_ms: None,
+                result: None,
+                notices: vec![],
+                statement_results: vec![],
+                cancel_requested: false,
+                seen: false,
+            },
+        );
+    }
+
+    /// Record the outcome of a job and drop the oldest finished ones.
+    pub fn finish(
+        &mut self,
+        id: Uuid,
+        result: QueryExecutionResult,
+        notices: Vec<ServerNotice>,
+        seen: bool,
+    ) {
+        if let Some(job) = self.get_mut(id) {
+            let (status, execution_time_ms) = match &result {
+                QueryExecutionResult::Select(r) => (JobStatus::Succeeded, r.execution_time_ms),
+                QueryExecutionResult::Modified(m) => (JobStatus::Succeeded, m.execution_time_ms),
+                QueryExecutionResult::Error(e) if job.cancel_requested => {
+                    (JobStatus::Cancelled, e.execution_time_ms)
+                }
+                QueryExecutionResult::Error(e) => (JobStatus::Failed, e.execution_time_ms),
+            };
+            job.status = status;
+            job.finished_at = Some(Local::now());
+            job.execution_time_ms = Some(execution_time_ms);
+            job.result = Some(result);
+            job.notices = notices;
+            job.cancel_requested = false;
+            job.seen = seen;
+        }
+
+        let mut finished = 0;
+        self.jobs.retain(|job| {
+            if job.status == JobStatus::Running {
+                return true;
+            }
+            finished += 1;
+            finished <= MAX_FINISHED_JOBS
+        });
+    }
+
+    /// The job that finished last.
+    pub fn last_finished(&self) -> Option<&Job> {
+        self.jobs
+            .iter()
+            .filter(|job| job.status != JobStatus::Running)
+            .max_by_key(|job| job.finished_at)
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Running)
+            .count()
+    }
+
+    pub fn unseen_count(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status != JobStatus::Running && !job.seen)
+            .count()
+    }
+
+    pub fn mark_all_seen(&mut self) {
+        for job in &mut self.jobs {
+            if job.status != JobStatus::Running {
+                job.seen = true;
+            }
+        }
+    }
+}
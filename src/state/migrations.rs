@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use gpui::*;
+use uuid::Uuid;
+
+use crate::services::{AppStore, MigrationSettings};
+
+use super::connection::WindowConnection;
+use super::windows::WindowScoped;
+
+/// Workspace state key for the migration settings of every connection.
+const MIGRATION_SETTINGS_KEY: &str = "migration_settings";
+
+/// The migrations directory and tracking table, per saved connection.
+pub struct MigrationsState {
+    settings: HashMap<Uuid, MigrationSettings>,
+}
+
+impl Global for MigrationsState {}
+
+impl MigrationsState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(MigrationsState {
+            settings: HashMap::new(),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                match store
+                    .workspace_state()
+                    .get_json::<HashMap<Uuid, MigrationSettings>>(MIGRATION_SETTINGS_KEY)
+                    .await
+                {
+                    Ok(Some(settings)) => {
+                        let _ = cx.update_global::<MigrationsState, _>(|state, _cx| {
+                            state.settings = settings;
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load migration settings: {}", e),
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// The settings of the connection open in `window`.
+    pub fn active(window: WindowId, cx: &App) -> MigrationSettings {
+        WindowConnection::of(window, cx)
+            .active_connection
+            .as_ref()
+            .and_then(|c| cx.global::<MigrationsState>().settings.get(&c.id).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn set(connection_id: Uuid, settings: MigrationSettings, cx: &mut App) {
+        cx.update_global::<MigrationsState, _>(|state, _cx| {
+            if settings == MigrationSettings::default() {
+                state.settings.remove(&connection_id);
+            } else {
+                state.settings.insert(connection_id, settings);
+            }
+        });
+
+        let settings = cx.global::<MigrationsState>().settings.clone();
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(MIGRATION_SETTINGS_KEY, &settings)
+                    .await
+                {
+                    tracing::warn!("Failed to save migration settings: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+}
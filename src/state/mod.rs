@@ -5,33 +5,117 @@
 //!
 //! ## Structure
 //!
-//! - `connection` - Connection status and saved connections
-//! - `database` - Available databases on the connected server
-//! - `editor` - Editor-related state (tables for autocomplete, etc.)
+//! - `activity` - Sessions of each window's connection left in long or idle transactions
+//! - `agent_privacy` - What the agent may send to the model, per connection
+//! - `change_recorder` - DDL recorded into a change script while recording is on
+//! - `connection` - Saved connections and the connection open in each window
+//! - `database` - Available databases on each window's server
+//! - `display` - Rows fetched into the results grid and how values are shown
+//! - `editor` - Editor-related state (each window's tables for autocomplete, etc.)
+//! - `files` - Recent `.sql` files and files waiting to be opened
+//! - `health` - Latency of each window's connection and its SSH tunnel
+//! - `hooks` - SQL or shell commands run before and after each query
+//! - `jobs` - Queries running in the background and their outcomes
+//! - `migrations` - Migration directory and tracking table, per connection
+//! - `notifications` - Recent errors, finished jobs, tunnel and update notices
+//! - `quick_slots` - Queries bound to `cmd-1`..`cmd-9` per connection
+//! - `scheduler` - Queries run on an interval and their recent runs
+//! - `snippets` - Snippets added by the user
+//! - `updates` - The update channel and downloading a newer release
+//! - `variables` - `{{name}}` variable environments of the current workspace
+//! - `vault` - Where passwords are stored and unlocking the encrypted vault
+//! - `windows` - State kept separately for each open window
+//! - `workspaces` - Named workspaces and the one being opened
 //! - `actions` - Cross-cutting operations (connect, disconnect, etc.)
 
 mod actions;
+mod activity;
+mod agent_privacy;
+mod change_recorder;
 mod connection;
 mod database;
+mod display;
 mod editor;
+mod files;
+mod health;
+mod hooks;
+mod jobs;
+mod migrations;
+mod notifications;
+mod quick_slots;
+mod scheduler;
+mod snippets;
+mod updates;
+mod variables;
+mod vault;
+mod windows;
+mod workspaces;
 
 // Re-export state structs
-pub use connection::{ConnectionState, ConnectionStatus};
+pub use activity::{ActivityState, TransactionAlerts};
+pub use agent_privacy::AgentPrivacyState;
+pub use change_recorder::ChangeRecorderState;
+pub use connection::{ConnectionState, ConnectionStatus, WindowConnection};
 pub use database::DatabaseState;
-pub use editor::{EditorCodeActions, EditorInlineCompletions, EditorState};
+pub use display::DisplayState;
+pub use editor::{
+    EditorAgentContext, EditorCodeActions, EditorInlineCompletions, EditorRefactorings, EditorState,
+    PendingRefactoring,
+};
+pub use files::FilesState;
+pub use health::HealthState;
+pub use hooks::HooksState;
+pub use jobs::{Job, JobStatus, JobsState};
+pub use migrations::MigrationsState;
+pub use notifications::{
+    NotificationAction, NotificationEntry, NotificationSource, NotificationsState,
+};
+pub use quick_slots::{QUICK_SLOT_COUNT, QuickSlot, QuickSlotsState};
+pub use scheduler::{ScheduledRun, ScheduledRunStatus, SchedulerState};
+pub use snippets::SnippetsState;
+pub use updates::{UpdateState, UpdateStatus};
+pub use variables::VariablesState;
+pub use vault::VaultState;
+pub use windows::{PerWindow, WindowScoped, window_id};
+pub use workspaces::WorkspacesState;
 
 // Re-export actions for orchestration
 pub use actions::{
-    add_connection, change_database, connect, delete_connection, disconnect, update_connection,
+    add_connection, add_recent_file, auto_connect, change_database, close_window, connect,
+    connect_with_password, delete_connection, delete_workspace, disconnect, open_file,
+    open_window, open_workspace, quick_connect, record_schema_snapshot, record_table_sizes,
+    reload_databases, save_workspace, set_active_schema, set_auto_connect, set_production,
+    update_connection,
 };
 
 use gpui::App;
 
 /// Initialize all global state.
 pub fn init(cx: &mut App) {
+    ActivityState::init(cx);
+    TransactionAlerts::init(cx);
+    AgentPrivacyState::init(cx);
+    ChangeRecorderState::init(cx);
     ConnectionState::init(cx);
+    WindowConnection::init(cx);
     DatabaseState::init(cx);
+    DisplayState::init(cx);
     EditorState::init(cx);
     EditorCodeActions::init(cx);
     EditorInlineCompletions::init(cx);
+    EditorAgentContext::init(cx);
+    EditorRefactorings::init(cx);
+    FilesState::init(cx);
+    HealthState::init(cx);
+    HooksState::init(cx);
+    JobsState::init(cx);
+    MigrationsState::init(cx);
+    NotificationsState::init(cx);
+    QuickSlotsState::init(cx);
+    SchedulerState::init(cx);
+    SnippetsState::init(cx);
+    UpdateState::init(cx);
+    VariablesState::init(cx);
+    VaultState::init(cx);
+    WorkspacesState::init(cx);
 }
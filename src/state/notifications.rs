@@ -0,0 +1,103 @@
+use chrono::{DateTime, Local};
+use gpui::*;
+
+/// Notifications kept in the notification center.
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// What a notification is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationSource {
+    Error,
+    Job,
+    Tunnel,
+    Update,
+}
+
+/// Where a notification's button leads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationAction {
+    ShowJobs,
+    ShowUpdates,
+    ShowLogs,
+}
+
+/// A notification kept after its toast has gone.
+#[derive(Clone)]
+pub struct NotificationEntry {
+    pub id: usize,
+    pub at: DateTime<Local>,
+    pub source: NotificationSource,
+    pub message: String,
+    pub action: Option<NotificationAction>,
+    pub read: bool,
+}
+
+pub struct NotificationsState {
+    /// Most recent first.
+    pub entries: Vec<NotificationEntry>,
+    next_id: usize,
+}
+
+impl Global for NotificationsState {}
+
+impl NotificationsState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(NotificationsState {
+            entries: vec![],
+            next_id: 0,
+        });
+    }
+
+    /// Keep a notification, dropping the oldest past the limit.
+    pub fn record(
+        &mut self,
+        source: NotificationSource,
+        message: impl Into<String>,
+        action: Option<NotificationAction>,
+    ) {
+        self.entries.insert(
+            0,
+            NotificationEntry {
+                id: self.next_id,
+                at: Local::now(),
+                source,
+                message: message.into(),
+                action,
+                read: false,
+            },
+        );
+        self.next_id += 1;
+        self.entries.truncate(MAX_NOTIFICATIONS);
+    }
+
+    /// Record a notification from anywhere with the app.
+    pub fn push(
+        source: NotificationSource,
+        message: impl Into<String>,
+        action: Option<NotificationAction>,
+        cx: &mut App,
+    ) {
+        let message = message.into();
+        cx.update_global::<NotificationsState, _>(|state, _cx| {
+            state.record(source, message, action);
+        });
+    }
+
+    pub fn unread_count(&self) -> usize {
+        self.entries.iter().filter(|entry| !entry.read).count()
+    }
+
+    pub fn mark_all_read(&mut self) {
+        for entry in &mut self.entries {
+            entry.read = true;
+        }
+    }
+
+    pub fn dismiss(&mut self, id: usize) {
+        self.entries.retain(|entry| entry.id != id);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::{BTreeMap, HashMap};
+
+use gpui::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::AppStore;
+
+/// Workspace state key for the quick slots of every connection.
+const QUICK_SLOTS_KEY: &str = "quick_slots";
+
+/// Slots are numbered 1 to 9, one per `cmd-1`..`cmd-9`.
+pub const QUICK_SLOT_COUNT: u8 = 9;
+
+/// A query bound to a number key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickSlot {
+    pub name: String,
+    pub sql: String,
+}
+
+/// Queries bound to `cmd-1`..`cmd-9`, kept per saved connection.
+pub struct QuickSlotsState {
+    slots: HashMap<Uuid, BTreeMap<u8, QuickSlot>>,
+}
+
+impl Global for QuickSlotsState {}
+
+impl QuickSlotsState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(QuickSlotsState {
+            slots: HashMap::new(),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                match store
+                    .workspace_state()
+                    .get_json::<HashMap<Uuid, BTreeMap<u8, QuickSlot>>>(QUICK_SLOTS_KEY)
+                    .await
+                {
+                    Ok(Some(slots)) => {
+                        let _ = cx.update_global::<QuickSlotsState, _>(|state, _cx| {
+                            state.slots = slots;
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load quick slots: {}", e),
+                }
+            }
+        })
+        .detach();
+    }
+
+    pub fn slot(&self, connection_id: Uuid, slot: u8) -> Option<&QuickSlot> {
+        self.slots.get(&connection_id)?.get(&slot)
+    }
+
+    /// Bind `quick_slot` to `slot` for a connection, replacing what was there.
+    pub fn assign(connection_id: Uuid, slot: u8, quick_slot: QuickSlot, cx: &mut App) {
+        cx.update_global::<QuickSlotsState, _>(|state, _cx| {
+            state
+                .slots
+                .entry(connection_id)
+                .or_default()
+                .insert(slot, quick_slot);
+        });
+        Self::save(cx);
+    }
+
+    pub fn clear(connection_id: Uuid, slot: u8, cx: &mut App) {
+        cx.update_global::<QuickSlotsState, _>(|state, _cx| {
+            if let Some(slots) = state.slots.get_mut(&connection_id) {
+                slots.remove(&slot);
+                if slots.is_empty() {
+                    state.slots.remove(&connection_id);
+                }
+            }
+        });
+        Self::save(cx);
+    }
+
+    fn save(cx: &mut App) {
+        let slots = cx.global::<QuickSlotsState>().slots.clone();
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(QUICK_SLOTS_KEY, &slots)
+                    .await
+                {
+                    tracing::warn!("Failed to save quick slots: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+}
@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use gpui::*;
+use uuid::Uuid;
+
+use crate::services::export::{ScheduledRunOutput, run_scheduled_query};
+use crate::services::{AppStore, ScheduledQuery};
+
+use super::ConnectionState;
+
+/// How often due queries are looked for.
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Finished runs kept in the Scheduled tab.
+const MAX_FINISHED_RUNS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub enum ScheduledRunStatus {
+    Running,
+    Succeeded(ScheduledRunOutput),
+    Failed(String),
+}
+
+/// One run of a scheduled query.
+#[derive(Debug, Clone)]
+pub struct ScheduledRun {
+    pub query_id: Uuid,
+    pub query_name: String,
+    pub started_at: DateTime<Local>,
+    pub duration_ms: Option<i64>,
+    pub status: ScheduledRunStatus,
+}
+
+/// Scheduled queries and the outcomes of their recent runs.
+pub struct SchedulerState {
+    /// By name.
+    pub queries: Vec<ScheduledQuery>,
+    /// Most recent first.
+    pub runs: Vec<ScheduledRun>,
+}
+
+impl Global for SchedulerState {}
+
+impl SchedulerState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(SchedulerState {
+            queries: vec![],
+            runs: vec![],
+        });
+
+        cx.spawn(async move |cx| {
+            match AppStore::singleton().await {
+                Ok(store) => match store.scheduled_queries().load_all().await {
+                    Ok(queries) => {
+                        let _ = cx.update_global::<SchedulerState, _>(|state, _cx| {
+                            state.queries = queries;
+                        });
+                    }
+                    Err(e) => tracing::error!("Failed to load scheduled queries: {}", e),
+                },
+                Err(e) => tracing::error!("Failed to open store: {}", e),
+            }
+
+            loop {
+                if cx.update(Self::run_due).is_err() {
+                    break;
+                }
+                cx.background_executor().timer(TICK_INTERVAL).await;
+            }
+        })
+        .detach();
+    }
+
+    pub fn is_running(&self, query_id: Uuid) -> bool {
+        self.runs.iter().any(|run| {
+            run.query_id == query_id && matches!(run.status, ScheduledRunStatus::Running)
+        })
+    }
+
+    /// Start every enabled query whose interval has elapsed.
+    fn run_due(cx: &mut App) {
+        let now = Utc::now();
+        let state = cx.global::<SchedulerState>();
+        let due: Vec<Uuid> = state
+            .queries
+            .iter()
+            .filter(|query| query.is_due(now) && !state.is_running(query.id))
+            .map(|query| query.id)
+            .collect();
+        for id in due {
+            Self::run_now(id, cx);
+        }
+    }
+
+    /// Run a scheduled query now, whether or not it is due.
+    pub fn run_now(query_id: Uuid, cx: &mut App) {
+        let state = cx.global::<SchedulerState>();
+        let Some(query) = state.queries.iter().find(|q| q.id == query_id).cloned() else {
+            return;
+        };
+        if state.is_running(query_id) {
+            return;
+        }
+        let connection = cx
+            .global::<ConnectionState>()
+            .saved_connections
+            .iter()
+            .find(|c| c.id == query.connection_id)
+            .cloned();
+
+        // Recorded up front so a slow run is not started again.
+        let started = Utc::now();
+        let started_at = Local::now();
+        cx.update_global::<SchedulerState, _>(|state, _cx| {
+            if let Some(q) = state.queries.iter_mut().find(|q| q.id == query_id) {
+                q.last_run_at = Some(started);
+            }
+            state.runs.insert(
+                0,
+                ScheduledRun {
+                    query_id,
+                    query_name: query.name.clone(),
+                    started_at,
+                    duration_ms: None,
+                    status: ScheduledRunStatus::Running,
+                },
+            );
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .scheduled_queries()
+                    .set_last_run(&query_id, started)
+                    .await
+                {
+                    tracing::warn!("Failed to record scheduled run: {}", e);
+                }
+            }
+
+            let status = match connection {
+                Some(connection) => match run_scheduled_query(&query, connection).await {
+                    Ok(output) => ScheduledRunStatus::Succeeded(output),
+                    Err(e) => {
+                        tracing::warn!("Scheduled query '{}' failed: {}", query.name, e);
+                        ScheduledRunStatus::Failed(e.to_string())
+                    }
+                },
+                None => ScheduledRunStatus::Failed("The connection no longer exists".to_string()),
+            };
+            let duration_ms = (Local::now() - started_at).num_milliseconds();
+
+            let _ = cx.update_global::<SchedulerState, _>(|state, _cx| {
+                if let Some(run) = state.runs.iter_mut().find(|run| {
+                    run.query_id == query_id
+                        && run.started_at == started_at
+                        && matches!(run.status, ScheduledRunStatus::Running)
+                }) {
+                    run.status = status;
+                    run.duration_ms = Some(duration_ms);
+                }
+
+                let mut finished = 0;
+                state.runs.retain(|run| {
+                    if matches!(run.status, ScheduledRunStatus::Running) {
+                        return true;
+                    }
+                    finished += 1;
+                    finished <= MAX_FINISHED_RUNS
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// Add `query`, or replace the one with its id, and persist it.
+    pub fn save(query: ScheduledQuery, cx: &mut App) {
+        cx.update_global::<SchedulerState, _>(|state, _cx| {
+            match state.queries.iter_mut().find(|q| q.id == query.id) {
+                Some(existing) => {
+                    let last_run_at = existing.last_run_at;
+                    *existing = ScheduledQuery {
+                        last_run_at,
+                        ..query.clone()
+                    };
+                }
+                None => state.queries.push(query.clone()),
+            }
+            state
+                .queries
+                .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store.scheduled_queries().save(&query).await {
+                    tracing::error!("Failed to save scheduled query: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    pub fn set_enabled(query_id: Uuid, enabled: bool, cx: &mut App) {
+        let query = cx
+            .global::<SchedulerState>()
+            .queries
+            .iter()
+            .find(|q| q.id == query_id)
+            .cloned();
+        if let Some(query) = query {
+            Self::save(ScheduledQuery { enabled, ..query }, cx);
+        }
+    }
+
+    pub fn delete(query_id: Uuid, cx: &mut App) {
+        cx.update_global::<SchedulerState, _>(|state, _cx| {
+            state.queries.retain(|q| q.id != query_id);
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store.scheduled_queries().delete(&query_id).await {
+                    tracing::error!("Failed to delete scheduled query: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+}
@@ -0,0 +1,68 @@
+use gpui::*;
+
+use crate::services::AppStore;
+use crate::services::sql::{Snippet, builtin_snippets};
+
+/// Workspace state key for the user's snippets.
+const SQL_SNIPPETS_KEY: &str = "sql_snippets";
+
+/// Snippets added by the user, on top of the built-in ones.
+pub struct SnippetsState {
+    pub user: Vec<Snippet>,
+}
+
+impl Global for SnippetsState {}
+
+impl SnippetsState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(SnippetsState { user: vec![] });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                match store
+                    .workspace_state()
+                    .get_json::<Vec<Snippet>>(SQL_SNIPPETS_KEY)
+                    .await
+                {
+                    Ok(Some(user)) => {
+                        let _ = cx.update_global::<SnippetsState, _>(|state, _cx| {
+                            state.user = user;
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load snippets: {}", e),
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Replace the user's snippets and persist them.
+    pub fn set(user: Vec<Snippet>, cx: &mut App) {
+        cx.update_global::<SnippetsState, _>(|state, _cx| {
+            state.user = user.clone();
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(SQL_SNIPPETS_KEY, &user)
+                    .await
+                {
+                    tracing::warn!("Failed to save snippets: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Every snippet, the user's first. A user snippet replaces the
+    /// built-in one with the same prefix.
+    pub fn all(&self) -> Vec<Snippet> {
+        let builtin = builtin_snippets()
+            .into_iter()
+            .filter(|builtin| !self.user.iter().any(|user| user.prefix == builtin.prefix));
+        self.user.iter().cloned().chain(builtin).collect()
+    }
+}
@@ -0,0 +1,264 @@
+use std::path::PathBuf;
+
+use gpui::*;
+
+use crate::services::AppStore;
+use crate::services::updates::{
+    UpdateChannel, UpdateInfo, check_for_update, download_update, install_update, relaunch,
+};
+
+use super::notifications::{NotificationAction, NotificationSource, NotificationsState};
+
+/// Workspace state key for the update channel.
+const UPDATE_CHANNEL_KEY: &str = "update_channel";
+
+#[derive(Debug, Clone, Default)]
+pub enum UpdateStatus {
+    #[default]
+    Idle,
+    Checking,
+    UpToDate,
+    Available(UpdateInfo),
+    Downloading {
+        info: UpdateInfo,
+        downloaded: u64,
+        total: u64,
+    },
+    /// Downloaded and verified, waiting to be installed.
+    Ready {
+        info: UpdateInfo,
+        path: PathBuf,
+    },
+    Installing(UpdateInfo),
+    Failed {
+        info: Option<UpdateInfo>,
+        message: String,
+    },
+}
+
+impl UpdateStatus {
+    /// The newer release, once one has been found.
+    pub fn info(&self) -> Option<&UpdateInfo> {
+        match self {
+            UpdateStatus::Available(info)
+            | UpdateStatus::Downloading { info, .. }
+            | UpdateStatus::Ready { info, .. }
+            | UpdateStatus::Installing(info) => Some(info),
+            UpdateStatus::Failed { info, .. } => info.as_ref(),
+            UpdateStatus::Idle | UpdateStatus::Checking | UpdateStatus::UpToDate => None,
+        }
+    }
+}
+
+/// The update channel and where the current check or download stands.
+pub struct UpdateState {
+    pub channel: UpdateChannel,
+    pub status: UpdateStatus,
+}
+
+impl Global for UpdateState {}
+
+impl UpdateState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(UpdateState {
+            channel: UpdateChannel::default(),
+            status: UpdateStatus::Idle,
+        });
+
+        // Check for updates on startup, on the saved channel
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Ok(Some(channel)) = store
+                    .workspace_state()
+                    .get_json::<UpdateChannel>(UPDATE_CHANNEL_KEY)
+                    .await
+                {
+                    let _ = cx.update_global::<UpdateState, _>(|state, _cx| {
+                        state.channel = channel;
+                    });
+                }
+            }
+            let _ = cx.update(UpdateState::check);
+        })
+        .detach();
+    }
+
+    fn set_status(status: UpdateStatus, cx: &mut App) {
+        cx.update_global::<UpdateState, _>(|state, _cx| {
+            state.status = status;
+        });
+    }
+
+    /// Look for a newer release on the current channel.
+    pub fn check(cx: &mut App) {
+        let state = cx.global::<UpdateState>();
+        if matches!(
+            state.status,
+            UpdateStatus::Checking | UpdateStatus::Downloading { .. } | UpdateStatus::Installing(_)
+        ) {
+            return;
+        }
+        let channel = state.channel;
+        Self::set_status(UpdateStatus::Checking, cx);
+
+        cx.spawn(async move |cx| {
+            let status = match check_for_update(channel).await {
+                Ok(Some(info)) => {
+                    tracing::info!(
+                        "Update available: {} -> {}",
+                        info.current_version,
+                        info.latest_version
+                    );
+                    UpdateStatus::Available(info)
+                }
+                Ok(None) => {
+                    tracing::debug!("No update available");
+                    UpdateStatus::UpToDate
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to check for updates: {}", e);
+                    UpdateStatus::Failed {
+                        info: None,
+                        message: e.to_string(),
+                    }
+                }
+            };
+            let _ = cx.update(|cx| {
+                // The channel may have changed while checking.
+                if cx.global::<UpdateState>().channel == channel {
+                    if let UpdateStatus::Available(info) = &status {
+                        NotificationsState::push(
+                            NotificationSource::Update,
+                            format!("pgui {} is available", info.latest_version),
+                            Some(NotificationAction::ShowUpdates),
+                            cx,
+                        );
+                    }
+                    Self::set_status(status, cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Switch channels, persist the choice and check again.
+    pub fn set_channel(channel: UpdateChannel, cx: &mut App) {
+        if cx.global::<UpdateState>().channel == channel {
+            return;
+        }
+        cx.update_global::<UpdateState, _>(|state, _cx| {
+            state.channel = channel;
+            if !matches!(
+                state.status,
+                UpdateStatus::Downloading { .. } | UpdateStatus::Installing(_)
+            ) {
+                state.status = UpdateStatus::Idle;
+            }
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(UPDATE_CHANNEL_KEY, &channel)
+                    .await
+                {
+                    tracing::warn!("Failed to save update channel: {}", e);
+                }
+            }
+        })
+        .detach();
+
+        Self::check(cx);
+    }
+
+    /// Download and verify the available update in the background.
+    pub fn download(cx: &mut App) {
+        let info = match &cx.global::<UpdateState>().status {
+            UpdateStatus::Available(info)
+            | UpdateStatus::Failed {
+                info: Some(info), ..
+            } => info.clone(),
+            _ => return,
+        };
+        let total = info.asset.as_ref().map_or(0, |asset| asset.size);
+        Self::set_status(
+            UpdateStatus::Downloading {
+                info: info.clone(),
+                downloaded: 0,
+                total,
+            },
+            cx,
+        );
+
+        let (progress_tx, progress_rx) = async_channel::unbounded::<(u64, u64)>();
+        let progress_info = info.clone();
+        cx.spawn(async move |cx| {
+            while let Ok((downloaded, total)) = progress_rx.recv().await {
+                let info = progress_info.clone();
+                let _ = cx.update(|cx| {
+                    if matches!(
+                        cx.global::<UpdateState>().status,
+                        UpdateStatus::Downloading { .. }
+                    ) {
+                        Self::set_status(
+                            UpdateStatus::Downloading {
+                                info,
+                                downloaded,
+                                total,
+                            },
+                            cx,
+                        );
+                    }
+                });
+            }
+        })
+        .detach();
+
+        cx.spawn(async move |cx| {
+            let result = download_update(&info, move |downloaded, total| {
+                let _ = progress_tx.try_send((downloaded, total));
+            })
+            .await;
+            let status = match result {
+                Ok(path) => UpdateStatus::Ready { info, path },
+                Err(e) => {
+                    tracing::warn!("Failed to download update: {}", e);
+                    UpdateStatus::Failed {
+                        info: Some(info),
+                        message: e.to_string(),
+                    }
+                }
+            };
+            let _ = cx.update(|cx| Self::set_status(status, cx));
+        })
+        .detach();
+    }
+
+    /// Install the downloaded update, then quit and relaunch the new version.
+    pub fn install_and_relaunch(cx: &mut App) {
+        let (info, path) = match &cx.global::<UpdateState>().status {
+            UpdateStatus::Ready { info, path } => (info.clone(), path.clone()),
+            _ => return,
+        };
+        Self::set_status(UpdateStatus::Installing(info.clone()), cx);
+
+        cx.spawn(async move |cx| {
+            let result = install_update(path).await;
+            let _ = cx.update(|cx| match result.and_then(|bundle| relaunch(&bundle)) {
+                Ok(()) => cx.quit(),
+                Err(e) => {
+                    tracing::warn!("Failed to install update: {}", e);
+                    Self::set_status(
+                        UpdateStatus::Failed {
+                            info: Some(info),
+                            message: e.to_string(),
+                        },
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
+    }
+}
@@ -0,0 +1,69 @@
+use gpui::*;
+
+use crate::services::AppStore;
+use crate::services::sql::QueryVariables;
+
+/// Workspace state key for the current variable environments.
+const QUERY_VARIABLES_KEY: &str = "query_variables";
+
+/// `{{name}}` variable environments of the current workspace. Saved
+/// workspaces carry their own, restored when one is opened.
+pub struct VariablesState {
+    pub variables: QueryVariables,
+}
+
+impl Global for VariablesState {}
+
+impl VariablesState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(VariablesState {
+            variables: QueryVariables::default(),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                match store
+                    .workspace_state()
+                    .get_json::<QueryVariables>(QUERY_VARIABLES_KEY)
+                    .await
+                {
+                    Ok(Some(variables)) => {
+                        let _ = cx.update_global::<VariablesState, _>(|state, _cx| {
+                            state.variables = variables;
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load query variables: {}", e),
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Replace the environments and persist them.
+    pub fn set(variables: QueryVariables, cx: &mut App) {
+        cx.update_global::<VariablesState, _>(|state, _cx| {
+            state.variables = variables.clone();
+        });
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(QUERY_VARIABLES_KEY, &variables)
+                    .await
+                {
+                    tracing::warn!("Failed to save query variables: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Make the environment `name` the one queries use.
+    pub fn set_active(name: Option<String>, cx: &mut App) {
+        let mut variables = cx.global::<VariablesState>().variables.clone();
+        variables.active = name;
+        Self::set(variables, cx);
+    }
+}
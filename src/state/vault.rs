@@ -0,0 +1,137 @@
+use anyhow::{Context as _, Result};
+use gpui::*;
+
+use crate::services::AppStore;
+use crate::services::storage::{
+    CREDENTIAL_STORE_KEY, CredentialStore, Vault, open_vault, use_store, vault_locked,
+    vault_unlocked,
+};
+
+/// Which credential store is in use, and whether the vault still waits
+/// for its master password.
+pub struct VaultState {
+    pub store: CredentialStore,
+    /// The vault is the active store but has not been unlocked yet.
+    pub locked: bool,
+    /// Ask for the master password; cleared once the prompt is open.
+    pub prompt_unlock: bool,
+    /// The saved choice of store has been read.
+    loaded: bool,
+    /// Run once the vault is unlocked, such as the startup connection.
+    after_unlock: Vec<Box<dyn FnOnce(&mut App)>>,
+}
+
+impl Global for VaultState {}
+
+impl VaultState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(VaultState {
+            store: CredentialStore::default(),
+            locked: false,
+            prompt_unlock: false,
+            loaded: false,
+            after_unlock: Vec::new(),
+        });
+
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                match store
+                    .workspace_state()
+                    .get_json::<CredentialStore>(CREDENTIAL_STORE_KEY)
+                    .await
+                {
+                    Ok(Some(credential_store)) => {
+                        use_store(credential_store);
+                        let _ = cx.update_global::<VaultState, _>(|state, _cx| {
+                            state.store = credential_store;
+                            state.locked =
+                                credential_store == CredentialStore::Vault && !vault_unlocked();
+                            state.prompt_unlock = state.locked;
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load credential store: {}", e),
+                }
+            }
+            let _ = cx.update(|cx| {
+                cx.update_global::<VaultState, _>(|state, _cx| state.loaded = true);
+                if !vault_locked() {
+                    Self::unlocked(cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Open the vault with its master password.
+    pub fn unlock(master_password: String, cx: &mut App) -> Task<Result<()>> {
+        cx.spawn(async move |cx| {
+            let vault = cx
+                .background_spawn(async move {
+                    Vault::unlock(&Vault::default_path()?, &master_password)
+                })
+                .await?;
+            open_vault(vault);
+            cx.update(Self::unlocked)?;
+            Ok(())
+        })
+    }
+
+    /// Move every saved secret into `to` and keep using it from now on.
+    /// Choosing the vault opens it with `master_password`, creating it
+    /// when there is none yet, which also unlocks a vault already in use.
+    pub fn switch_store(
+        to: CredentialStore,
+        master_password: Option<String>,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        cx.spawn(async move |cx| {
+            if to == CredentialStore::Vault && !vault_unlocked() {
+                let master_password = master_password.context("Enter a master password")?;
+                let vault = cx
+                    .background_spawn(async move {
+                        let path = Vault::default_path()?;
+                        if path.exists() {
+                            Vault::unlock(&path, &master_password)
+                        } else {
+                            Vault::create(&path, &master_password)
+                        }
+                    })
+                    .await?;
+                open_vault(vault);
+            }
+
+            let store = AppStore::singleton().await?;
+            store.connections().move_secrets(to).await?;
+            store
+                .workspace_state()
+                .set_json(CREDENTIAL_STORE_KEY, &to)
+                .await?;
+            cx.update(|cx| {
+                cx.update_global::<VaultState, _>(|state, _cx| state.store = to);
+                Self::unlocked(cx);
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Run `f` once saved passwords can be read: now, or when the vault
+    /// is unlocked if it is still locked or not known to be yet.
+    pub fn after_unlock(cx: &mut App, f: impl FnOnce(&mut App) + 'static) {
+        if cx.global::<VaultState>().loaded && !vault_locked() {
+            f(cx);
+        } else {
+            cx.update_global::<VaultState, _>(|state, _cx| state.after_unlock.push(Box::new(f)));
+        }
+    }
+
+    fn unlocked(cx: &mut App) {
+        let pending = cx.update_global::<VaultState, _>(|state, _cx| {
+            state.locked = false;
+            std::mem::take(&mut state.after_unlock)
+        });
+        for f in pending {
+            f(cx);
+        }
+    }
+}
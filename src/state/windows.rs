@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use gpui::*;
+
+/// State kept separately for each open window, such as its connection and
+/// the schema loaded from it.
+pub struct PerWindow<T> {
+    by_window: HashMap<WindowId, T>,
+    /// Read for a window that has not stored anything yet.
+    fallback: T,
+}
+
+impl<T: 'static> Global for PerWindow<T> {}
+
+/// State of which every window has its own, read with [`WindowScoped::of`]
+/// and changed with [`WindowScoped::update`].
+pub trait WindowScoped: Default + 'static {
+    fn init(cx: &mut App) {
+        cx.set_global(PerWindow::<Self> {
+            by_window: HashMap::new(),
+            fallback: Self::default(),
+        });
+    }
+
+    /// The state of `window`.
+    fn of(window: WindowId, cx: &App) -> &Self {
+        let state = cx.global::<PerWindow<Self>>();
+        state.by_window.get(&window).unwrap_or(&state.fallback)
+    }
+
+    /// Start keeping state for a newly opened window.
+    fn open(window: WindowId, cx: &mut App) {
+        cx.update_global::<PerWindow<Self>, _>(|state, _cx| {
+            state.by_window.entry(window).or_default();
+        });
+    }
+
+    /// Change the state of `window`, notifying the observers of every
+    /// window's state. A window that was closed has no state left to
+    /// change, so `f` gets a throwaway default.
+    fn update<R>(window: WindowId, cx: &mut App, f: impl FnOnce(&mut Self, &mut App) -> R) -> R {
+        cx.update_global::<PerWindow<Self>, _>(|state, cx| match state.by_window.get_mut(&window) {
+            Some(state) => f(state, cx),
+            None => f(&mut Self::default(), cx),
+        })
+    }
+
+    /// Every window's state.
+    fn all(cx: &App) -> impl Iterator<Item = (WindowId, &Self)> {
+        cx.global::<PerWindow<Self>>()
+            .by_window
+            .iter()
+            .map(|(window, state)| (*window, state))
+    }
+
+    /// Drop the state of a closed window.
+    fn remove(window: WindowId, cx: &mut App) -> Option<Self> {
+        cx.update_global::<PerWindow<Self>, _>(|state, _cx| state.by_window.remove(&window))
+    }
+}
+
+/// The id the state of `window` is kept under.
+pub fn window_id(window: &Window) -> WindowId {
+    window.window_handle().window_id()
+}
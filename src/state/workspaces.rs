@@ -0,0 +1,34 @@
+use gpui::*;
+
+use crate::services::{AppStore, storage::SavedWorkspace};
+
+pub struct WorkspacesState {
+    /// Named workspaces, by name.
+    pub saved: Vec<SavedWorkspace>,
+    /// A workspace being opened and the window it opens in, applied by that
+    /// window's main view and then cleared.
+    pub pending_restore: Option<(WindowId, SavedWorkspace)>,
+}
+
+impl Global for WorkspacesState {}
+
+impl WorkspacesState {
+    pub fn init(cx: &mut App) {
+        cx.set_global(WorkspacesState {
+            saved: vec![],
+            pending_restore: None,
+        });
+
+        // Load saved workspaces on startup
+        cx.spawn(async move |cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Ok(saved) = store.workspaces().load_all().await {
+                    let _ = cx.update_global::<WorkspacesState, _>(|state, _cx| {
+                        state.saved = saved;
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+}
@@ -0,0 +1,90 @@
+use gpui::*;
+use gpui_component::{ActiveTheme as _, Icon, spinner::Spinner};
+use serde::{Deserialize, Serialize};
+
+use crate::services::AppStore;
+
+use super::change_color_mode;
+
+const APPEARANCE_KEY: &str = "appearance";
+
+/// Accessibility preferences applied on top of the light/dark mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Appearance {
+    /// Use the high-contrast variant of the current color mode.
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Show static indicators instead of spinners, and toasts without
+    /// sliding or fading them in and out.
+    #[serde(default)]
+    pub reduce_motion: bool,
+}
+
+impl Global for Appearance {}
+
+impl Appearance {
+    /// The active preferences, or the defaults before `init` has run.
+    pub fn global(cx: &App) -> Self {
+        cx.try_global::<Self>().copied().unwrap_or_default()
+    }
+
+    /// Install the defaults, then apply the saved preferences once the app
+    /// store has loaded them.
+    pub fn init(window: &mut Window, cx: &mut App) {
+        cx.set_global(Self::default());
+
+        window
+            .spawn(cx, async move |cx| {
+                let Ok(store) = AppStore::singleton().await else {
+                    return;
+                };
+                let saved = store
+                    .workspace_state()
+                    .get_json::<Appearance>(APPEARANCE_KEY)
+                    .await
+                    .ok()
+                    .flatten();
+                if let Some(appearance) = saved {
+                    let _ = cx.update(|window, cx| appearance.apply(window, cx));
+                }
+            })
+            .detach();
+    }
+
+    /// Make these the active preferences and persist them.
+    pub fn set(self, window: &mut Window, cx: &mut App) {
+        self.apply(window, cx);
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(APPEARANCE_KEY, &self)
+                    .await
+                {
+                    tracing::warn!("Failed to save appearance: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    fn apply(self, window: &mut Window, cx: &mut App) {
+        cx.set_global(self);
+        change_color_mode(cx.theme().mode, window, cx);
+        window.refresh();
+    }
+}
+
+/// Busy indicator that honors `reduce_motion`: a spinner, or a static
+/// loader icon when motion is reduced.
+pub fn busy_indicator(cx: &App) -> AnyElement {
+    if Appearance::global(cx).reduce_motion {
+        Icon::empty()
+            .path("icons/loader.svg")
+            .text_color(cx.theme().muted_foreground)
+            .into_any_element()
+    } else {
+        Spinner::new().into_any_element()
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use gpui::*;
+use gpui_component::{ActiveTheme as _, ThemeConfig, ThemeMode, ThemeSet};
+use serde::{Deserialize, Serialize};
+
+use crate::services::AppStore;
+
+use super::{THEMES, change_color_mode};
+
+const THEME_SETTINGS_KEY: &str = "theme_settings";
+
+/// Themes read from `~/.pgui/themes`, replacing built-in themes of the
+/// same name.
+static USER_THEMES: RwLock<Vec<ThemeConfig>> = RwLock::new(Vec::new());
+
+/// `~/.pgui/themes`
+pub fn user_themes_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".pgui").join("themes"))
+}
+
+/// The themes of every theme set file (`*.json`, in the gpui-component
+/// theme format) in `dir`. Files that fail to parse are skipped.
+fn read_theme_dir(dir: &Path) -> Vec<ThemeConfig> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut themes: Vec<ThemeConfig> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let source = std::fs::read_to_string(&path).ok()?;
+            match serde_json::from_str::<ThemeSet>(&source) {
+                Ok(set) => Some(set.themes),
+                Err(e) => {
+                    tracing::warn!("Skipping theme file {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .flatten()
+        .collect();
+    themes.sort_by(|a, b| a.name.cmp(&b.name));
+    themes
+}
+
+/// Read `~/.pgui/themes` again, returning how many themes it holds.
+pub fn reload_user_themes() -> usize {
+    let themes = user_themes_dir()
+        .map(|dir| read_theme_dir(&dir))
+        .unwrap_or_default();
+    let count = themes.len();
+    *USER_THEMES.write().unwrap() = themes;
+    count
+}
+
+/// The user or built-in theme called `name`.
+pub fn find_theme(name: &str) -> Option<ThemeConfig> {
+    USER_THEMES
+        .read()
+        .unwrap()
+        .iter()
+        .find(|theme| theme.name == name)
+        .or_else(|| THEMES.get(name))
+        .cloned()
+}
+
+/// Names of the themes for `mode`: built-in first, then the user's.
+pub fn theme_names(mode: ThemeMode) -> Vec<SharedString> {
+    let mut builtin: Vec<SharedString> = THEMES
+        .values()
+        .filter(|theme| theme.mode == mode)
+        .map(|theme| theme.name.clone())
+        .collect();
+    builtin.sort();
+    let user = USER_THEMES.read().unwrap();
+    builtin.retain(|name| !user.iter().any(|theme| theme.name == *name));
+    builtin.extend(
+        user.iter()
+            .filter(|theme| theme.mode == mode)
+            .map(|theme| theme.name.clone()),
+    );
+    builtin
+}
+
+/// A `#rrggbb` or `#rrggbbaa` color.
+pub fn parse_color(hex: &str) -> Option<Hsla> {
+    Rgba::try_from(hex.trim()).ok().map(Hsla::from)
+}
+
+/// Colors set on top of the chosen theme, as hex strings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeOverrides {
+    pub editor_background: Option<String>,
+    pub editor_foreground: Option<String>,
+    /// Background of every other row in the results grid.
+    pub grid_stripe: Option<String>,
+    /// Marks the window while connected to a production database; the
+    /// theme's danger color by default.
+    pub production_accent: Option<String>,
+}
+
+/// The themes picked for light and dark mode, and color overrides.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub light_theme: Option<SharedString>,
+    pub dark_theme: Option<SharedString>,
+    pub overrides: ThemeOverrides,
+}
+
+impl Global for ThemeSettings {}
+
+impl ThemeSettings {
+    /// The active settings, or the defaults before `init` has run.
+    pub fn global(cx: &App) -> Self {
+        cx.try_global::<Self>().cloned().unwrap_or_default()
+    }
+
+    /// Install the defaults, then read the user's themes and apply the
+    /// saved settings once the app store has loaded them.
+    pub fn init(window: &mut Window, cx: &mut App) {
+        cx.set_global(Self::default());
+
+        window
+            .spawn(cx, async move |cx| {
+                cx.background_spawn(async { reload_user_themes() }).await;
+                let Ok(store) = AppStore::singleton().await else {
+                    return;
+                };
+                let saved = store
+                    .workspace_state()
+                    .get_json::<ThemeSettings>(THEME_SETTINGS_KEY)
+                    .await
+                    .ok()
+                    .flatten();
+                let settings = saved.unwrap_or_default();
+                let _ = cx.update(|window, cx| settings.preview(window, cx));
+            })
+            .detach();
+    }
+
+    /// The theme picked for `mode`.
+    pub fn theme_for(&self, mode: ThemeMode) -> Option<&SharedString> {
+        match mode {
+            ThemeMode::Light => self.light_theme.as_ref(),
+            ThemeMode::Dark => self.dark_theme.as_ref(),
+        }
+    }
+
+    /// Apply these settings without saving them.
+    pub fn preview(self, window: &mut Window, cx: &mut App) {
+        cx.set_global(self);
+        change_color_mode(cx.theme().mode, window, cx);
+        window.refresh();
+    }
+
+    /// Apply these settings and persist them.
+    pub fn set(self, window: &mut Window, cx: &mut App) {
+        self.clone().preview(window, cx);
+
+        cx.spawn(async move |_cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(THEME_SETTINGS_KEY, &self)
+                    .await
+                {
+                    tracing::warn!("Failed to save theme settings: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+}
+
+/// Editor background and text colors, from the overrides or the theme.
+pub fn editor_colors(cx: &App) -> (Hsla, Hsla) {
+    let overrides = cx
+        .try_global::<ThemeSettings>()
+        .map(|settings| &settings.overrides);
+    let color = |hex: Option<&String>| hex.and_then(|hex| parse_color(hex));
+    (
+        color(overrides.and_then(|o| o.editor_background.as_ref()))
+            .unwrap_or(cx.theme().background),
+        color(overrides.and_then(|o| o.editor_foreground.as_ref()))
+            .unwrap_or(cx.theme().foreground),
+    )
+}
+
+/// Color marking a connection to a production database.
+pub fn production_accent(cx: &App) -> Hsla {
+    cx.try_global::<ThemeSettings>()
+        .and_then(|settings| settings.overrides.production_accent.as_deref())
+        .and_then(parse_color)
+        .unwrap_or(cx.theme().danger)
+}
@@ -1,2 +1,6 @@
+mod appearance;
+mod custom;
 mod themes;
+pub use appearance::*;
+pub use custom::*;
 pub use themes::*;
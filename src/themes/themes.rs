@@ -8,13 +8,18 @@ use gpui_component::ThemeConfig;
 use gpui_component::ThemeMode;
 use gpui_component::ThemeSet;
 
+use super::{Appearance, ThemeSettings, find_theme, parse_color};
+
 pub static THEMES: LazyLock<HashMap<SharedString, ThemeConfig>> = LazyLock::new(|| {
     fn parse_themes(source: &str) -> ThemeSet {
         serde_json::from_str(source).unwrap()
     }
 
     let mut themes = HashMap::new();
-    for source in [include_str!("./catppuccin.json")] {
+    for source in [
+        include_str!("./catppuccin.json"),
+        include_str!("./high_contrast.json"),
+    ] {
         let theme_set = parse_themes(source);
         for theme in theme_set.themes {
             themes.insert(theme.name.clone(), theme);
@@ -24,17 +29,37 @@ pub static THEMES: LazyLock<HashMap<SharedString, ThemeConfig>> = LazyLock::new(
     themes
 });
 
-// Apply a Catppuccin theme by color mode
+// Apply the theme picked for a color mode (Catppuccin by default), or the
+// high-contrast variant, then the user's color overrides
 pub fn change_color_mode(mode: ThemeMode, _win: &mut Window, cx: &mut App) {
-    let theme_name = match mode {
-        ThemeMode::Light => "Catppuccin Latte",
-        ThemeMode::Dark => "Catppuccin Macchiato",
+    let high_contrast = Appearance::global(cx).high_contrast;
+    let settings = ThemeSettings::global(cx);
+    let default_name = match (mode, high_contrast) {
+        (ThemeMode::Light, false) => "Catppuccin Latte",
+        (ThemeMode::Dark, false) => "Catppuccin Macchiato",
+        (ThemeMode::Light, true) => "High Contrast Light",
+        (ThemeMode::Dark, true) => "High Contrast Dark",
+    };
+    let theme_name = match settings.theme_for(mode) {
+        Some(name) if !high_contrast => name.as_ref(),
+        _ => default_name,
     };
 
-    if let Some(theme_config) = THEMES.get(theme_name) {
-        let theme_config = Rc::new(theme_config.clone());
+    if let Some(theme_config) = find_theme(theme_name) {
+        let theme_config = Rc::new(theme_config);
         let theme = Theme::global_mut(cx);
         theme.mode = theme_config.mode;
         theme.apply_config(&theme_config);
     }
+
+    if let Some(stripe) = settings
+        .overrides
+        .grid_stripe
+        .as_deref()
+        .and_then(parse_color)
+    {
+        let theme = Theme::global_mut(cx);
+        theme.table_even = stripe;
+        theme.list_even = stripe;
+    }
 }
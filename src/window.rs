@@ -1,16 +1,134 @@
 use gpui::*;
-use gpui_component::TitleBar;
-
-pub fn get_window_options(cx: &mut App) -> WindowOptions {
-    let mut window_size = size(px(1600.0), px(1200.0));
-    if let Some(display) = cx.primary_display() {
-        let display_size = display.bounds().size;
-        window_size.width = window_size.width.min(display_size.width * 0.85);
-        window_size.height = window_size.height.min(display_size.height * 0.85);
+use gpui_component::{Root, TitleBar};
+use serde::{Deserialize, Serialize};
+
+use crate::services::{AppStore, ConnectionInfo};
+use crate::workspace::Workspace;
+
+/// Title of a window with no connection open.
+const APP_TITLE: &str = "pgui";
+
+/// Window placement remembered for one display configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedWindowBounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub maximized: bool,
+}
+
+impl SavedWindowBounds {
+    pub fn from_window(window: &Window) -> Self {
+        let window_bounds = window.window_bounds();
+        let bounds = window_bounds.get_bounds();
+        Self {
+            x: f32::from(bounds.origin.x),
+            y: f32::from(bounds.origin.y),
+            width: f32::from(bounds.size.width),
+            height: f32::from(bounds.size.height),
+            maximized: matches!(window_bounds, WindowBounds::Maximized(_)),
+        }
+    }
+
+    fn bounds(&self) -> Bounds<Pixels> {
+        Bounds::new(
+            point(px(self.x), px(self.y)),
+            size(px(self.width), px(self.height)),
+        )
+    }
+}
+
+/// Title shown in the title bar and the OS task switcher, e.g.
+/// `pgui — staging/app_db` while connected.
+pub fn window_title(connection: Option<&ConnectionInfo>) -> String {
+    match connection {
+        Some(connection) => format!(
+            "{} — {}/{}",
+            APP_TITLE, connection.name, connection.database
+        ),
+        None => APP_TITLE.to_string(),
+    }
+}
+
+/// Open another workspace window. Each window has its own connection,
+/// editor and results, while saved connections, history and settings are
+/// shared by all of them.
+pub fn open_new_window(cx: &mut App) {
+    let window_options = get_window_options(cx, None);
+    if let Err(e) = cx.open_window(window_options, |win, cx| {
+        let workspace_view = Workspace::view(win, cx);
+        cx.new(|cx| Root::new(workspace_view, win, cx))
+    }) {
+        tracing::error!("Failed to open a new window: {}", e);
+    }
+}
+
+/// Identifies the current set of displays (size and arrangement), so a
+/// laptop-only setup and a docked multi-monitor setup each get their own
+/// remembered window placement.
+pub fn display_layout_key(cx: &App) -> String {
+    let mut displays: Vec<String> = cx
+        .displays()
+        .iter()
+        .map(|display| {
+            let bounds = display.bounds();
+            format!(
+                "{}x{}@{},{}",
+                f32::from(bounds.size.width) as i32,
+                f32::from(bounds.size.height) as i32,
+                f32::from(bounds.origin.x) as i32,
+                f32::from(bounds.origin.y) as i32,
+            )
+        })
+        .collect();
+    displays.sort();
+    format!("window_bounds:{}", displays.join(";"))
+}
+
+pub async fn load_window_bounds(layout_key: &str) -> Option<SavedWindowBounds> {
+    let store = AppStore::singleton().await.ok()?;
+    store
+        .workspace_state()
+        .get_json(layout_key)
+        .await
+        .ok()
+        .flatten()
+}
+
+pub async fn save_window_bounds(layout_key: &str, bounds: SavedWindowBounds) {
+    if let Ok(store) = AppStore::singleton().await {
+        if let Err(e) = store.workspace_state().set_json(layout_key, &bounds).await {
+            tracing::warn!("Failed to save window bounds: {}", e);
+        }
     }
-    let bounds = Bounds::centered(None, window_size, cx);
+}
+
+pub fn get_window_options(cx: &mut App, saved: Option<SavedWindowBounds>) -> WindowOptions {
+    // Only restore a placement that is still visible on one of the
+    // connected displays.
+    let restored = saved.filter(|saved| {
+        cx.displays()
+            .iter()
+            .any(|display| display.bounds().intersects(&saved.bounds()))
+    });
+
+    let window_bounds = match restored {
+        Some(saved) if saved.maximized => WindowBounds::Maximized(saved.bounds()),
+        Some(saved) => WindowBounds::Windowed(saved.bounds()),
+        None => {
+            let mut window_size = size(px(1600.0), px(1200.0));
+            if let Some(display) = cx.primary_display() {
+                let display_size = display.bounds().size;
+                window_size.width = window_size.width.min(display_size.width * 0.85);
+                window_size.height = window_size.height.min(display_size.height * 0.85);
+            }
+            WindowBounds::Windowed(Bounds::centered(None, window_size, cx))
+        }
+    };
+
     WindowOptions {
-        window_bounds: Some(WindowBounds::Windowed(bounds)),
+        window_bounds: Some(window_bounds),
         titlebar: Some(TitleBar::title_bar_options()),
         window_decorations: Some(WindowDecorations::Client),
         ..Default::default()
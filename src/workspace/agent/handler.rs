@@ -1,17 +1,77 @@
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+
 use async_channel::{Receiver, Sender};
-use gpui::{AppContext, AsyncApp, WeakEntity};
+use futures::FutureExt as _;
+use gpui::{AppContext, AsyncApp, WeakEntity, WindowId};
 
 use crate::{
     services::agent::{
-        Agent, AgentRequest, AgentResponse, ContentBlock, FileSource, UiMessage,
+        Agent, AgentRequest, AgentResponse, ContentBlock, FileSource, Message, UiMessage,
         create_get_schema_tool, create_get_table_columns_tool, create_get_tables_tool, upload_file,
     },
     workspace::agent::{panel::AgentPanel, tools::execute_tools},
 };
 
-pub async fn handle_outgoing(
+/// How many times a panicking agent task is restarted before giving up.
+const MAX_RESTARTS: usize = 3;
+
+/// Conversation state kept outside the agent task, so a restarted task
+/// picks up where the previous one stopped.
+#[derive(Debug, Default)]
+pub struct AgentSession {
+    conversation: Vec<Message>,
+    model: Option<String>,
+}
+
+pub type SharedSession = Arc<Mutex<AgentSession>>;
+
+/// Run `handle_outgoing`, restarting it if it panics. Each restart is
+/// reported in the transcript.
+pub async fn supervise_outgoing(
+    outgoing_rx: Receiver<AgentRequest>,
+    incoming_tx: Sender<AgentResponse>,
+    session: SharedSession,
+) {
+    for attempt in 0..=MAX_RESTARTS {
+        let task = handle_outgoing(outgoing_rx.clone(), incoming_tx.clone(), session.clone());
+        let Err(panic) = AssertUnwindSafe(task).catch_unwind().await else {
+            return;
+        };
+
+        let reason = panic_message(panic.as_ref());
+        tracing::error!("Agent task panicked: {}", reason);
+        let message = if attempt < MAX_RESTARTS {
+            format!(
+                "The agent stopped unexpectedly ({}) and was restarted. \
+                 The conversation was kept.",
+                reason
+            )
+        } else {
+            format!(
+                "The agent stopped unexpectedly ({}). Send a message to start it again.",
+                reason
+            )
+        };
+        let _ = incoming_tx.try_send(AgentResponse::Error(message));
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+async fn handle_outgoing(
     outgoing_rx: Receiver<AgentRequest>,
     incoming_tx: Sender<AgentResponse>,
+    session: SharedSession,
 ) {
     if let Some(mut agent) = Agent::builder()
         .system_prompt(
@@ -28,15 +88,34 @@ pub async fn handle_outgoing(
         ])
         .ok()
     {
+        // Resume the conversation of a previous (restarted) task
+        {
+            let session = session.lock().unwrap();
+            if let Some(model) = &session.model {
+                agent.set_model(model.clone());
+            }
+            agent.set_conversation(session.conversation.clone());
+        }
+
         // Get API key for file uploads
         let api_key = std::env::var("ANTHROPIC_API_KEY").unwrap_or_default();
 
         while let Ok(request) = outgoing_rx.recv().await {
             match request {
-                AgentRequest::Chat { content, files } => {
+                AgentRequest::Chat {
+                    content,
+                    files,
+                    schema_context,
+                } => {
                     // Start a new user message
                     let mut user_content = vec![ContentBlock::Text { text: content }];
 
+                    // The tables picked locally for this message, so the
+                    // whole schema need not be fetched
+                    if let Some(text) = schema_context {
+                        user_content.push(ContentBlock::Text { text });
+                    }
+
                     // Upload files and add to content
                     for path in files {
                         match smol::unblock({
@@ -90,11 +169,14 @@ pub async fn handle_outgoing(
                 AgentRequest::SetModel(model) => {
                     // Update the agent's model
                     tracing::debug!("Setting agent model to: {}", model);
+                    session.lock().unwrap().model = Some(model.clone());
                     agent.set_model(model);
                     // Clear conversation when model changes
                     agent.clear_conversation();
                 }
             }
+
+            session.lock().unwrap().conversation = agent.get_conversation().to_vec();
         }
     } else {
         tracing::error!("Failed to build agent");
@@ -105,6 +187,7 @@ pub async fn handle_outgoing(
 }
 
 pub async fn handle_incoming(
+    window: WindowId,
     this: WeakEntity<AgentPanel>,
     incoming_rx: Receiver<AgentResponse>,
     outgoing_tx: Sender<AgentRequest>,
@@ -133,7 +216,7 @@ pub async fn handle_incoming(
                         text, tool_calls, ..
                     } => {
                         // Execute tools with database access
-                        let results = execute_tools(tool_calls.clone(), &cx).await;
+                        let results = execute_tools(window, tool_calls.clone(), &cx).await;
 
                         if let Some(view) = this.upgrade() {
                             let _ = cx.update_entity(&view, |this, cx| {
@@ -178,7 +261,12 @@ pub async fn handle_incoming(
                 tracing::error!("Channel error: {}", e);
                 if let Some(view) = this.upgrade() {
                     let _ = cx.update_entity(&view, |this, cx| {
-                        // TODO: notify of error
+                        this.add_message(
+                            UiMessage::error(
+                                "The agent stopped responding. Send a message to restart it.",
+                            ),
+                            cx,
+                        );
                         this.set_loading(false, cx);
                     });
                 }
@@ -4,7 +4,7 @@ use async_channel::{Sender, unbounded};
 use gpui::{
     AnyElement, App, AppContext, ClickEvent, Context, Div, Entity, EventEmitter, IntoElement,
     ListAlignment, ListState, ParentElement, PathPromptOptions, Render, SharedString, Styled as _,
-    Window, div, list, prelude::FluentBuilder as _, px,
+    Window, WindowId, div, list, prelude::FluentBuilder as _, px,
 };
 use gpui_component::{
     ActiveTheme as _, Icon, IndexPath, Sizable as _, StyledExt as _,
@@ -20,8 +20,12 @@ use gpui_component::{
 };
 
 use crate::{
-    services::agent::{AgentRequest, AgentResponse, MessageRole, UiMessage},
-    workspace::agent::handler::{handle_incoming, handle_outgoing},
+    services::agent::{AgentRequest, AgentResponse, MessageRole, SchemaSelection, UiMessage},
+    state::{AgentPrivacyState, EditorState, WindowScoped, window_id},
+    workspace::agent::{
+        handler::{SharedSession, handle_incoming, supervise_outgoing},
+        tools::schema_context_for_llm,
+    },
 };
 
 /// Events emitted by the AgentPanel
@@ -45,10 +49,14 @@ pub struct MessageState {
 }
 
 pub struct AgentPanel {
+    /// The window whose connection the agent works with.
+    window_id: WindowId,
     textarea: Entity<InputState>,
     message_state: Entity<MessageState>,
     model_select: Entity<SelectState<Vec<SharedString>>>,
     outgoing_tx: Sender<AgentRequest>,
+    /// Conversation shared with the agent task; survives its restarts.
+    session: SharedSession,
     list_state: ListState,
     attached_files: Vec<PathBuf>,
     is_loading: bool,
@@ -162,23 +170,12 @@ impl AgentPanel {
         let model_select =
             cx.new(|cx| SelectState::new(model_names, Some(IndexPath::new(0)), window, cx));
 
-        let (incoming_tx, incoming_rx) = unbounded::<AgentResponse>();
-        let (outgoing_tx, outgoing_rx) = unbounded::<AgentRequest>();
-
         // Initialize state with empty messages
         let message_state = cx.new(|_cx| MessageState { messages: vec![] });
 
-        // Spawn the agent message handler
-        cx.background_executor()
-            .spawn(handle_outgoing(outgoing_rx, incoming_tx))
-            .detach();
-
-        // Spawn task to handle incoming responses from agent
-        let outgoing_tx_clone = outgoing_tx.clone();
-        cx.spawn(async move |this, cx| {
-            handle_incoming(this, incoming_rx, outgoing_tx_clone, cx).await;
-        })
-        .detach();
+        let window_id = window_id(window);
+        let session = SharedSession::default();
+        let outgoing_tx = Self::spawn_agent(window_id, session.clone(), cx);
 
         let list_state = ListState::new(4, ListAlignment::Bottom, px(200.));
 
@@ -190,18 +187,18 @@ impl AgentPanel {
         .detach();
 
         // Subscribe to model selection changes
-        let outgoing_tx_for_select = outgoing_tx.clone();
         cx.subscribe_in(
             &model_select,
             window,
-            move |_this, _entity, event: &SelectEvent<Vec<SharedString>>, _window, _cx| {
+            move |this, _entity, event: &SelectEvent<Vec<SharedString>>, _window, _cx| {
                 if let SelectEvent::Confirm(Some(selected_display_name)) = event {
                     // Find the model ID from the display name
                     if let Some((model_id, _)) = AVAILABLE_MODELS
                         .iter()
                         .find(|(_, display)| *display == selected_display_name.as_ref())
                     {
-                        let _ = outgoing_tx_for_select
+                        let _ = this
+                            .outgoing_tx
                             .try_send(AgentRequest::SetModel(model_id.to_string()));
                     }
                 }
@@ -210,10 +207,12 @@ impl AgentPanel {
         .detach();
 
         Self {
+            window_id,
             textarea,
             message_state,
             model_select,
             outgoing_tx,
+            session,
             list_state,
             attached_files: vec![],
             is_loading: false,
@@ -221,6 +220,31 @@ impl AgentPanel {
         }
     }
 
+    /// Start the agent task and the task relaying its responses, returning
+    /// the sender for requests. The conversation resumes from `session`.
+    fn spawn_agent(
+        window: WindowId,
+        session: SharedSession,
+        cx: &mut Context<Self>,
+    ) -> Sender<AgentRequest> {
+        let (incoming_tx, incoming_rx) = unbounded::<AgentResponse>();
+        let (outgoing_tx, outgoing_rx) = unbounded::<AgentRequest>();
+
+        // Spawn the agent message handler
+        cx.background_executor()
+            .spawn(supervise_outgoing(outgoing_rx, incoming_tx, session))
+            .detach();
+
+        // Spawn task to handle incoming responses from agent
+        let outgoing_tx_clone = outgoing_tx.clone();
+        cx.spawn(async move |this, cx| {
+            handle_incoming(window, this, incoming_rx, outgoing_tx_clone, cx).await;
+        })
+        .detach();
+
+        outgoing_tx
+    }
+
     pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
         cx.new(|cx| Self::new(window, cx))
     }
@@ -246,17 +270,35 @@ impl AgentPanel {
         // Take attached files (clears them from state)
         let files = std::mem::take(&mut self.attached_files);
 
-        // Send chat request to agent with files
+        // Restart the agent if its task has stopped
+        if self.outgoing_tx.is_closed() {
+            self.outgoing_tx = Self::spawn_agent(self.window_id, self.session.clone(), cx);
+        }
+
+        // Send chat request to agent with files and the relevant tables
+        let selection = Self::schema_selection(self.window_id, &text, cx);
         let result = self.outgoing_tx.try_send(AgentRequest::Chat {
             content: text.clone(),
             files,
+            schema_context: selection.as_ref().and_then(|s| s.text.clone()),
         });
 
         match result {
             Ok(_) => {
                 tracing::debug!("Message sent successfully");
-                // Add user message to display
+                // Add user message to display, and which tables went along
                 self.add_message(UiMessage::user(text), cx);
+                if let Some(selection) = selection.filter(|s| !s.tables.is_empty()) {
+                    self.add_message(
+                        UiMessage::system(format!(
+                            "_Schema context ({} of {} tables): {}_",
+                            selection.tables.len(),
+                            selection.total_tables,
+                            selection.tables.join(", ")
+                        )),
+                        cx,
+                    );
+                }
                 self.set_loading(true, cx);
             }
             Err(e) => {
@@ -273,6 +315,14 @@ impl AgentPanel {
         cx.notify();
     }
 
+    /// The tables of the connected database relevant to `text`, leaving
+    /// out what the connection's privacy settings exclude.
+    fn schema_selection(window: WindowId, text: &str, cx: &App) -> Option<SchemaSelection> {
+        let schema = EditorState::of(window, cx).schema.as_ref()?;
+        let privacy = AgentPrivacyState::active(window, cx);
+        Some(schema_context_for_llm(schema, &privacy).select(text))
+    }
+
     fn on_attach_file(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
         // Create the path prompt options - allow files, multiple selection
         let options = PathPromptOptions {
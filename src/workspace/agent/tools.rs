@@ -1,14 +1,22 @@
-use gpui::AsyncApp;
+use gpui::{AsyncApp, WindowId};
 
-use crate::services::{ColumnDetail, DatabaseSchema, QueryExecutionResult, TableSchema};
+use crate::services::{ColumnDetail, DatabaseSchema, QueryExecutionResult, ResultRow, TableSchema};
 use crate::{
-    services::agent::{ToolCallData, ToolResultData},
-    state::ConnectionState,
+    services::agent::{AgentPrivacy, SchemaContext, ToolCallData, ToolResultData},
+    state::{AgentPrivacyState, WindowConnection, WindowScoped},
 };
 
-/// Execute tools with access to context
-/// This is where you'll add database access, file system, etc.
-pub async fn execute_tools(tool_calls: Vec<ToolCallData>, cx: &AsyncApp) -> Vec<ToolResultData> {
+/// Execute tools with access to context, against the connection of
+/// `window`. This is where you'll add database access, file system, etc.
+pub async fn execute_tools(
+    window: WindowId,
+    tool_calls: Vec<ToolCallData>,
+    cx: &AsyncApp,
+) -> Vec<ToolResultData> {
+    // Tables and columns excluded for this connection are never described
+    let privacy = cx
+        .update(|cx| AgentPrivacyState::active(window, cx))
+        .unwrap_or_default();
     let mut results = Vec::new();
     for call in tool_calls {
         let result = match call.name.as_str() {
@@ -32,10 +40,10 @@ pub async fn execute_tools(tool_calls: Vec<ToolCallData>, cx: &AsyncApp) -> Vec<
                     is_error: true,
                 };
 
-                match cx.read_global::<ConnectionState, _>(|state, _cx| state.db_manager.clone()) {
+                match cx.update(|cx| WindowConnection::of(window, cx).db_manager.clone()) {
                     Ok(db) => match db.get_schema(filter_tables).await {
                         Ok(schema) => {
-                            let formatted = format_schema_for_llm(&schema);
+                            let formatted = format_schema_for_llm(&schema, &privacy);
                             ToolResultData {
                                 tool_use_id: call.id,
                                 content: formatted,
@@ -55,11 +63,12 @@ pub async fn execute_tools(tool_calls: Vec<ToolCallData>, cx: &AsyncApp) -> Vec<
                     is_error: true,
                 };
 
-                match cx.read_global::<ConnectionState, _>(|state, _cx| state.db_manager.clone()) {
+                match cx.update(|cx| WindowConnection::of(window, cx).db_manager.clone()) {
                     Ok(db) => match db.get_tables().await {
                         Ok(tables) => {
                             let formatted = tables
                                 .iter()
+                                .filter(|t| !privacy.excludes_table(&t.table_schema, &t.table_name))
                                 .map(|t| {
                                     format!(
                                         "- {}.{} ({})",
@@ -95,13 +104,17 @@ pub async fn execute_tools(tool_calls: Vec<ToolCallData>, cx: &AsyncApp) -> Vec<
                 };
 
                 match table_name {
+                    Some(name) if privacy.excludes_table(table_schema, name) => {
+                        error_result("This table is excluded by the agent privacy settings")
+                    }
                     Some(name) => {
-                        match cx.read_global::<ConnectionState, _>(|state, _cx| {
-                            state.db_manager.clone()
-                        }) {
+                        match cx.update(|cx| WindowConnection::of(window, cx).db_manager.clone()) {
                             Ok(db) => match db.get_table_columns(name, table_schema).await {
                                 Ok(result) => {
-                                    let formatted = format_query_result_as_markdown(result);
+                                    let formatted =
+                                        format_query_result_as_markdown(result, |column| {
+                                            privacy.excludes_column(table_schema, name, column)
+                                        });
                                     ToolResultData {
                                         tool_use_id: call.id,
                                         content: formatted,
@@ -130,8 +143,12 @@ pub async fn execute_tools(tool_calls: Vec<ToolCallData>, cx: &AsyncApp) -> Vec<
     results
 }
 
-/// Format a QueryExecutionResult as markdown
-fn format_query_result_as_markdown(result: QueryExecutionResult) -> String {
+/// Format a QueryExecutionResult as markdown, leaving out rows describing a
+/// column `hidden` returns true for
+fn format_query_result_as_markdown(
+    result: QueryExecutionResult,
+    hidden: impl Fn(&str) -> bool,
+) -> String {
     match result {
         QueryExecutionResult::Select(query_result) => {
             if query_result.columns.is_empty() {
@@ -165,7 +182,19 @@ fn format_query_result_as_markdown(result: QueryExecutionResult) -> String {
             md.push_str(" |\n");
 
             // Data rows
-            for row in &query_result.rows {
+            let name_ix = query_result
+                .columns
+                .iter()
+                .position(|c| c.name == "column_name");
+            let describes_hidden = |row: &ResultRow| {
+                name_ix
+                    .and_then(|ix| row.cells.get(ix))
+                    .is_some_and(|cell| hidden(&cell.value))
+            };
+            for row in query_result
+                .iter_rows()
+                .filter(|row| !describes_hidden(row))
+            {
                 md.push_str("| ");
                 let values: Vec<String> = row
                     .cells
@@ -199,8 +228,10 @@ fn format_query_result_as_markdown(result: QueryExecutionResult) -> String {
     }
 }
 
-/// Generates a human-readable schema description for LLM consumption
-pub fn format_schema_for_llm(schema: &DatabaseSchema) -> String {
+/// Generates a human-readable schema description for LLM consumption,
+/// without what `privacy` excludes
+pub fn format_schema_for_llm(schema: &DatabaseSchema, privacy: &AgentPrivacy) -> String {
+    let schema = &privacy.filter_schema(schema);
     let mut output = String::new();
 
     output.push_str(&format!(
@@ -215,6 +246,16 @@ pub fn format_schema_for_llm(schema: &DatabaseSchema) -> String {
     output
 }
 
+/// Indexes the tables of `schema` the model may see, each described as in
+/// [`format_schema_for_llm`], so a request only sends the relevant ones
+pub fn schema_context_for_llm(schema: &DatabaseSchema, privacy: &AgentPrivacy) -> SchemaContext {
+    SchemaContext::new(&privacy.filter_schema(schema), |table| {
+        let mut output = String::new();
+        format_table_for_llm(table, &mut output);
+        output
+    })
+}
+
 fn format_table_for_llm(table: &TableSchema, output: &mut String) {
     output.push_str(&format!(
         "## Table: {}.{}\n",
@@ -305,6 +346,25 @@ fn format_table_for_llm(table: &TableSchema, output: &mut String) {
         output.push('\n');
     }
 
+    // Triggers
+    if !table.triggers.is_empty() {
+        output.push_str("### Triggers:\n");
+        for trigger in &table.triggers {
+            output.push_str(&format!(
+                "- **{}**: {} {}",
+                trigger.trigger_name, trigger.timing, trigger.event
+            ));
+            if let Some(ref function) = trigger.function_name {
+                output.push_str(&format!(" EXECUTE {}", function));
+            }
+            if !trigger.enabled {
+                output.push_str(" (disabled)");
+            }
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
     output.push_str("---\n\n");
 }
 
@@ -0,0 +1,477 @@
+use std::collections::HashSet;
+
+use gpui::{
+    App, AppContext, ClipboardItem, Context, Entity, EventEmitter, InteractiveElement as _,
+    IntoElement, ParentElement, Render, StatefulInteractiveElement as _, Styled, Window, WindowId,
+    div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Sizable as _, StyledExt as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    dialog::DialogButtonProps,
+    h_flex,
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+use uuid::Uuid;
+
+use crate::services::sql::split_statements;
+use crate::services::{
+    BatchStatus, BatchTarget, BatchTargetResult, ConnectionInfo, ConnectionsRepository,
+    DatabaseManager, QueryExecutionResult, batch_report, batch_summary,
+};
+use crate::state::{ConnectionState, DatabaseState, WindowConnection, WindowScoped, window_id};
+use crate::workspace::notifications::Toasts as _;
+
+pub enum BatchRunEvent {
+    /// Show the results of one target, labelled, in the results panel.
+    ShowResults(String, Vec<(String, QueryExecutionResult)>),
+}
+
+/// Dialog body running the editor script against several saved
+/// connections, or every database of the open server, one after another,
+/// with a status line per target.
+pub struct BatchRunPanel {
+    window_id: WindowId,
+    script: String,
+    /// Statements of the script, as split for the open connection.
+    statement_count: usize,
+    connections: Vec<ConnectionInfo>,
+    production: HashSet<Uuid>,
+    selected: HashSet<Uuid>,
+    /// Also run on each database of the open connection.
+    all_databases: bool,
+    stop_on_failure: bool,
+    runs: Vec<(BatchTarget, BatchStatus)>,
+    running: bool,
+    stop_requested: bool,
+}
+
+impl EventEmitter<BatchRunEvent> for BatchRunPanel {}
+
+impl BatchRunPanel {
+    pub fn view(script: String, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(script, window, cx))
+    }
+
+    fn new(script: String, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let window_id = window_id(window);
+        let state = cx.global::<ConnectionState>();
+        let driver = WindowConnection::of(window_id, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| c.driver)
+            .unwrap_or_default();
+        Self {
+            window_id,
+            statement_count: split_statements(&driver, &script).len(),
+            script,
+            connections: state.saved_connections.clone(),
+            production: state.production_connections.iter().copied().collect(),
+            selected: HashSet::new(),
+            all_databases: false,
+            stop_on_failure: true,
+            runs: vec![],
+            running: false,
+            stop_requested: false,
+        }
+    }
+
+    /// The selected connections in their saved order, then the databases
+    /// of the open connection when asked for, each target once.
+    fn targets(&self, cx: &App) -> Vec<BatchTarget> {
+        let mut targets: Vec<BatchTarget> = self
+            .connections
+            .iter()
+            .filter(|c| self.selected.contains(&c.id))
+            .map(|c| BatchTarget::new(c.clone(), None))
+            .collect();
+
+        if self.all_databases {
+            if let Some(active) = &WindowConnection::of(self.window_id, cx).active_connection {
+                for db in &DatabaseState::of(self.window_id, cx).databases {
+                    let target = BatchTarget::new(active.clone(), Some(db.datname.clone()));
+                    if !targets.iter().any(|t| t.label() == target.label()) {
+                        targets.push(target);
+                    }
+                }
+            }
+        }
+        targets
+    }
+
+    /// Run on the selected targets, asking first when one of them is a
+    /// production connection.
+    fn confirm_run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let targets = self.targets(cx);
+        if targets.is_empty() {
+            window.push_toast(
+                (
+                    NotificationType::Warning,
+                    "Select the connections or databases to run on",
+                ),
+                cx,
+            );
+            return;
+        }
+
+        let production: Vec<String> = targets
+            .iter()
+            .filter(|t| self.production.contains(&t.connection.id))
+            .map(|t| t.label())
+            .collect();
+        if production.is_empty() {
+            self.run(targets, cx);
+            return;
+        }
+
+        let panel = cx.entity().downgrade();
+        let count = targets.len();
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let panel = panel.clone();
+            let targets = targets.clone();
+            dialog
+                .title("Run on Production")
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Run"))
+                .child(Label::new(format!(
+                    "The script runs on {} target{}, including production: {}",
+                    count,
+                    if count == 1 { "" } else { "s" },
+                    production.join(", ")
+                )))
+                .on_ok(move |_, _window, cx| {
+                    let targets = targets.clone();
+                    let _ = panel.update(cx, |this, cx| this.run(targets, cx));
+                    true
+                })
+        });
+    }
+
+    fn run(&mut self, targets: Vec<BatchTarget>, cx: &mut Context<Self>) {
+        if self.running {
+            return;
+        }
+        self.runs = targets
+            .into_iter()
+            .map(|target| (target, BatchStatus::Pending))
+            .collect();
+        self.running = true;
+        self.stop_requested = false;
+        cx.notify();
+
+        let script = self.script.clone();
+        let stop_on_failure = self.stop_on_failure;
+        let count = self.runs.len();
+
+        cx.spawn(async move |this, cx| {
+            for ix in 0..count {
+                let Ok(Some(target)) = this.update(cx, |this, cx| {
+                    if this.stop_requested {
+                        return None;
+                    }
+                    this.runs[ix].1 = BatchStatus::Running;
+                    cx.notify();
+                    Some(this.runs[ix].0.clone())
+                }) else {
+                    break;
+                };
+
+                let mut connection = target.connection;
+                let statements = split_statements(&connection.driver, &script);
+                let password = if connection.password.is_empty() {
+                    ConnectionsRepository::get_connection_password(&connection.id)
+                } else {
+                    Ok(connection.password.clone())
+                };
+                let result = match password {
+                    Ok(password) => {
+                        connection.password = password;
+                        DatabaseManager::run_on_target(&connection, &statements).await
+                    }
+                    Err(e) => Err(e),
+                };
+                let result = match result {
+                    Ok(results) => BatchTargetResult::from_results(statements, results),
+                    Err(e) => BatchTargetResult::connection_failed(statements, e.to_string()),
+                };
+                let failed = !result.succeeded();
+
+                let _ = this.update(cx, |this, cx| {
+                    this.runs[ix].1 = BatchStatus::Done(result);
+                    cx.notify();
+                });
+                if failed && stop_on_failure {
+                    break;
+                }
+            }
+
+            let _ = this.update(cx, |this, cx| {
+                for (_, status) in &mut this.runs {
+                    if matches!(status, BatchStatus::Pending) {
+                        *status = BatchStatus::Skipped;
+                    }
+                }
+                this.running = false;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn render_target_choices(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let active = WindowConnection::of(self.window_id, cx)
+            .active_connection
+            .clone();
+        let databases = DatabaseState::of(self.window_id, cx).databases.len();
+
+        let choices =
+            self.connections
+                .iter()
+                .enumerate()
+                .fold(v_flex().gap_1(), |list, (ix, connection)| {
+                    let id = connection.id;
+                    let production = self.production.contains(&id);
+                    list.child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(
+                                Checkbox::new(("batch-connection", ix))
+                                    .label(connection.name.clone())
+                                    .checked(self.selected.contains(&id))
+                                    .disabled(self.running)
+                                    .on_click(cx.listener(
+                                        move |this, checked: &bool, _win, cx| {
+                                            if *checked {
+                                                this.selected.insert(id);
+                                            } else {
+                                                this.selected.remove(&id);
+                                            }
+                                            cx.notify();
+                                        },
+                                    )),
+                            )
+                            .child(
+                                Label::new(format!(
+                                    "{}@{}/{}",
+                                    connection.username, connection.hostname, connection.database
+                                ))
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                            )
+                            .when(production, |row| {
+                                row.child(
+                                    Label::new("production")
+                                        .text_xs()
+                                        .text_color(cx.theme().danger),
+                                )
+                            }),
+                    )
+                });
+
+        v_flex()
+            .gap_2()
+            .child(
+                div()
+                    .id("batch-connections")
+                    .max_h(px(180.))
+                    .overflow_y_scroll()
+                    .child(choices),
+            )
+            .when_some(active.filter(|_| databases > 0), |d, active| {
+                d.child(
+                    Checkbox::new("batch-all-databases")
+                        .label(format!("Every database on {} ({})", active.name, databases))
+                        .checked(self.all_databases)
+                        .disabled(self.running)
+                        .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                            this.all_databases = *checked;
+                            cx.notify();
+                        })),
+                )
+            })
+            .child(
+                Checkbox::new("batch-stop-on-failure")
+                    .label("Stop at the first failing target")
+                    .checked(self.stop_on_failure)
+                    .disabled(self.running)
+                    .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                        this.stop_on_failure = *checked;
+                        cx.notify();
+                    })),
+            )
+    }
+
+    fn render_run(
+        &self,
+        ix: usize,
+        target: &BatchTarget,
+        status: &BatchStatus,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let status_color = match status {
+            BatchStatus::Done(result) if result.succeeded() => cx.theme().success,
+            BatchStatus::Done(_) => cx.theme().danger,
+            BatchStatus::Running => cx.theme().accent_foreground,
+            _ => cx.theme().muted_foreground,
+        };
+        let results = match status {
+            BatchStatus::Done(result) if !result.results.is_empty() => Some(result.results.clone()),
+            _ => None,
+        };
+        let summary = match status {
+            BatchStatus::Done(result) => Some(result.summary()),
+            _ => None,
+        };
+        let label = target.label();
+
+        h_flex()
+            .id(("batch-run", ix))
+            .w_full()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .when(status.failed(), |d| d.bg(cx.theme().danger.opacity(0.08)))
+            .child(
+                div().w(px(64.)).child(
+                    Label::new(status.label())
+                        .text_xs()
+                        .font_medium()
+                        .text_color(status_color),
+                ),
+            )
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .child(Label::new(label.clone()).text_sm())
+                    .when_some(summary, |d, summary| {
+                        d.child(
+                            Label::new(summary)
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    }),
+            )
+            .when_some(results, |d, results| {
+                d.child(
+                    Button::new(("batch-results", ix))
+                        .label("Results")
+                        .small()
+                        .ghost()
+                        .tooltip("Show this target's results")
+                        .on_click(cx.listener(move |_this, _, _window, cx| {
+                            cx.emit(BatchRunEvent::ShowResults(label.clone(), results.clone()));
+                        })),
+                )
+            })
+    }
+}
+
+impl Render for BatchRunPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let statements = self.statement_count;
+        let done = self
+            .runs
+            .iter()
+            .filter(|(_, status)| matches!(status, BatchStatus::Done(_)))
+            .count();
+        let summary = if self.running {
+            format!("Running… {} of {} targets done", done, self.runs.len())
+        } else if self.runs.is_empty() {
+            format!(
+                "{} statement{} to run on each target",
+                statements,
+                if statements == 1 { "" } else { "s" }
+            )
+        } else {
+            batch_summary(&self.runs)
+        };
+
+        let rows = self
+            .runs
+            .iter()
+            .enumerate()
+            .map(|(ix, (target, status))| {
+                self.render_run(ix, target, status, cx).into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_3()
+            .child(self.render_target_choices(cx))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .when(!self.runs.is_empty() && !self.running, |d| {
+                                d.child(
+                                    Button::new("copy-batch-report")
+                                        .icon(Icon::empty().path("icons/copy.svg"))
+                                        .label("Copy Report")
+                                        .small()
+                                        .ghost()
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            let report = batch_report(&this.script, &this.runs);
+                                            cx.write_to_clipboard(ClipboardItem::new_string(
+                                                report,
+                                            ));
+                                            window.push_toast(
+                                                (NotificationType::Success, "Report copied"),
+                                                cx,
+                                            );
+                                        })),
+                                )
+                            })
+                            .when(self.running, |d| {
+                                d.child(
+                                    Button::new("stop-batch")
+                                        .label("Stop")
+                                        .small()
+                                        .danger()
+                                        .disabled(self.stop_requested)
+                                        .tooltip("Skip the targets not started yet")
+                                        .on_click(cx.listener(|this, _, _window, cx| {
+                                            this.stop_requested = true;
+                                            cx.notify();
+                                        })),
+                                )
+                            })
+                            .child(
+                                Button::new("run-batch")
+                                    .icon(Icon::empty().path("icons/play.svg"))
+                                    .label("Run")
+                                    .small()
+                                    .primary()
+                                    .disabled(self.running)
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.confirm_run(window, cx);
+                                    })),
+                            ),
+                    ),
+            )
+            .when(!rows.is_empty(), |d| {
+                d.child(
+                    div()
+                        .id("batch-runs")
+                        .max_h(px(320.))
+                        .overflow_y_scroll()
+                        .border_1()
+                        .border_color(cx.theme().border)
+                        .rounded(cx.theme().radius)
+                        .children(rows),
+                )
+            })
+    }
+}
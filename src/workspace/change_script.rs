@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+
+use gpui::{
+    App, AppContext, ClipboardItem, Context, Entity, InteractiveElement as _, IntoElement,
+    ParentElement, PathPromptOptions, Render, SharedString, StatefulInteractiveElement as _,
+    Styled, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::services::sql::{ChangeScript, migration_file_names};
+use crate::state::{ChangeRecorderState, MigrationsState, window_id};
+use crate::workspace::notifications::{RecordedNotifications as _, Toasts as _};
+
+/// Dialog body for the recorded change script: the statements as run, the
+/// derived rollbacks, and where to save them as a migration.
+pub struct ChangeScriptForm {
+    script: ChangeScript,
+    name_input: Entity<InputState>,
+    /// Defaults to the migrations directory of the open connection.
+    directory: Option<PathBuf>,
+}
+
+impl ChangeScriptForm {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let script = cx.global::<ChangeRecorderState>().script.clone();
+        let directory = MigrationsState::active(window_id(window), cx).directory;
+        cx.new(|cx| {
+            let name_input = cx.new(|cx| {
+                InputState::new(window, cx).placeholder("Migration name, e.g. add_orders_index")
+            });
+            Self {
+                script,
+                name_input,
+                directory,
+            }
+        })
+    }
+
+    fn choose_directory(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("Save Here".into()),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = this.update(cx, |this, cx| {
+                this.directory = Some(path);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Write the `.up.sql` and `.down.sql` files. Returns whether the
+    /// dialog can close.
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let Some(directory) = self.directory.clone() else {
+            window.push_toast(
+                (
+                    NotificationType::Warning,
+                    "Choose where to save the migration",
+                ),
+                cx,
+            );
+            return false;
+        };
+        if self.script.is_empty() {
+            window.push_toast("No changes were recorded", cx);
+            return false;
+        }
+
+        let version = chrono::Local::now().format("%Y%m%d%H%M%S").to_string();
+        let name = self.name_input.read(cx).value().to_string();
+        let (up, down) = migration_file_names(&version, &name);
+        let written = std::fs::write(directory.join(&up), self.script.up_script())
+            .and_then(|_| std::fs::write(directory.join(&down), self.script.down_script()));
+
+        match written {
+            Ok(()) => {
+                ChangeRecorderState::clear(cx);
+                let message: SharedString = format!("Saved {} and {}", up, down).into();
+                window.push_toast((NotificationType::Success, message), cx);
+                true
+            }
+            Err(e) => {
+                let message: SharedString = format!("Failed to save migration: {}", e).into();
+                window.push_recorded_notification((NotificationType::Error, message), cx);
+                false
+            }
+        }
+    }
+
+    fn render_script(
+        &self,
+        id: &'static str,
+        title: &'static str,
+        sql: String,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let copied = sql.clone();
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(Label::new(title).text_sm())
+                    .child(
+                        Button::new(id)
+                            .icon(Icon::empty().path("icons/copy.svg"))
+                            .xsmall()
+                            .ghost()
+                            .tooltip("Copy")
+                            .on_click(move |_, _window, cx| {
+                                cx.write_to_clipboard(ClipboardItem::new_string(copied.clone()));
+                            }),
+                    ),
+            )
+            .child(
+                div()
+                    .id(SharedString::from(format!("{}-sql", id)))
+                    .p_2()
+                    .max_h(px(180.))
+                    .overflow_y_scroll()
+                    .rounded(cx.theme().radius)
+                    .bg(cx.theme().secondary)
+                    .font_family("monospace")
+                    .text_xs()
+                    .child(sql),
+            )
+    }
+}
+
+impl Render for ChangeScriptForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let count = self.script.changes.len();
+        let without_rollback = self
+            .script
+            .changes
+            .iter()
+            .filter(|change| change.rollback.is_none())
+            .count();
+
+        let mut summary = format!(
+            "{} change{} recorded",
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+        if without_rollback > 0 {
+            summary.push_str(&format!(" · {} without a rollback", without_rollback));
+        }
+
+        let directory = self
+            .directory
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or("No directory chosen".to_string());
+
+        v_flex()
+            .gap_3()
+            .child(
+                Label::new(summary)
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .child(self.render_script("copy-up-script", "Up", self.script.up_script(), cx))
+            .child(self.render_script("copy-down-script", "Down", self.script.down_script(), cx))
+            .child(Input::new(&self.name_input).small())
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        Label::new(directory)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .when(self.directory.is_none(), |label| {
+                                label.text_color(cx.theme().warning)
+                            }),
+                    )
+                    .child(
+                        Button::new("choose-change-script-directory")
+                            .label("Choose…")
+                            .xsmall()
+                            .ghost()
+                            .on_click(
+                                cx.listener(|this, _, window, cx| {
+                                    this.choose_directory(window, cx)
+                                }),
+                            ),
+                    ),
+            )
+    }
+}
+
+pub fn open_change_script_dialog(window: &mut Window, cx: &mut App) {
+    let form = ChangeScriptForm::view(window, cx);
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        dialog
+            .title("Change Script")
+            .width(px(640.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Save as Migration"))
+            .on_ok(move |_, window, cx| form.update(cx, |form, cx| form.save(window, cx)))
+    });
+}
@@ -0,0 +1,93 @@
+use gpui::{App, AppContext, Context, Entity, IntoElement, ParentElement, Render, Styled, Window};
+use gpui_component::{
+    Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputState},
+};
+
+use crate::services::agent::{AgentPrivacy, LiteralPolicy};
+
+/// Dialog body for what the agent features may send to the model for one
+/// connection: excluded schemas, tables and columns, and how literal values
+/// in queries are redacted.
+pub struct AgentPrivacyForm {
+    excluded_input: Entity<InputState>,
+    literals: LiteralPolicy,
+}
+
+impl AgentPrivacyForm {
+    pub fn view(privacy: AgentPrivacy, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let excluded = privacy.excluded.join(", ");
+            let excluded_input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder("e.g. audit, public.users.email, *.*.ssn")
+                    .default_value(excluded)
+            });
+            Self {
+                excluded_input,
+                literals: privacy.literals,
+            }
+        })
+    }
+
+    /// The settings as entered, with the comma-separated patterns split up.
+    pub fn privacy(&self, cx: &App) -> AgentPrivacy {
+        let excluded = self
+            .excluded_input
+            .read(cx)
+            .value()
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(String::from)
+            .collect();
+        AgentPrivacy {
+            excluded,
+            literals: self.literals,
+        }
+    }
+}
+
+impl Render for AgentPrivacyForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let policies = LiteralPolicy::ALL
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, policy| {
+                row.child(
+                    Button::new(policy.label())
+                        .label(policy.label())
+                        .small()
+                        .ghost()
+                        .selected(self.literals == policy)
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.literals = policy;
+                            cx.notify();
+                        })),
+                )
+            });
+
+        v_form()
+            .small()
+            .child(
+                field()
+                    .label("Excluded from the schema")
+                    .description(
+                        "Comma-separated schema, schema.table or schema.table.column \
+                         patterns; * matches anything",
+                    )
+                    .child(Input::new(&self.excluded_input)),
+            )
+            .child(
+                field()
+                    .label("Literal values in queries")
+                    .description(
+                        "Strip replaces strings and numbers with ?, Hash with a short digest",
+                    )
+                    .child(policies),
+            )
+            .w_full()
+    }
+}
@@ -0,0 +1,127 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement as _, Styled, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    checkbox::Checkbox,
+    form::{field, v_form},
+    input::{Input, InputState},
+    v_flex,
+};
+use uuid::Uuid;
+
+use crate::{services::ConnectionInfo, state::ConnectionState};
+
+/// Dialog body for exporting connection profiles: which connections to
+/// include, and optionally a passphrase to encrypt their passwords with.
+pub struct ConnectionExportForm {
+    connections: Vec<ConnectionInfo>,
+    selected: Vec<Uuid>,
+    include_passwords: bool,
+    passphrase_input: Entity<InputState>,
+}
+
+impl ConnectionExportForm {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let connections = cx.global::<ConnectionState>().saved_connections.clone();
+        let selected = connections.iter().map(|c| c.id).collect();
+        let passphrase_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .masked(true)
+                .placeholder("Shared with your team separately")
+        });
+
+        Self {
+            connections,
+            selected,
+            include_passwords: false,
+            passphrase_input,
+        }
+    }
+
+    fn toggle(&mut self, id: Uuid, checked: bool, cx: &mut Context<Self>) {
+        if checked {
+            if !self.selected.contains(&id) {
+                self.selected.push(id);
+            }
+        } else {
+            self.selected.retain(|s| *s != id);
+        }
+        cx.notify();
+    }
+
+    /// The checked connections, in list order.
+    pub fn selected_connections(&self) -> Vec<ConnectionInfo> {
+        self.connections
+            .iter()
+            .filter(|c| self.selected.contains(&c.id))
+            .cloned()
+            .collect()
+    }
+
+    /// `Some` when passwords should be exported, with the passphrase typed
+    /// (possibly empty).
+    pub fn passphrase(&self, cx: &App) -> Option<String> {
+        self.include_passwords
+            .then(|| self.passphrase_input.read(cx).value().to_string())
+    }
+}
+
+impl Render for ConnectionExportForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let connections =
+            self.connections
+                .iter()
+                .enumerate()
+                .fold(v_flex().gap_1(), |list, (ix, connection)| {
+                    let id = connection.id;
+                    list.child(
+                        Checkbox::new(("export-connection", ix))
+                            .label(connection.name.clone())
+                            .checked(self.selected.contains(&id))
+                            .on_click(cx.listener(move |this, checked: &bool, _win, cx| {
+                                this.toggle(id, *checked, cx);
+                            })),
+                    )
+                });
+
+        v_form()
+            .small()
+            .child(
+                field().label("Connections").child(
+                    div()
+                        .id("export-connections")
+                        .max_h(px(240.))
+                        .overflow_y_scroll()
+                        .child(connections),
+                ),
+            )
+            .child(
+                field()
+                    .label("Passwords")
+                    .description("SSH key passphrases are never exported")
+                    .child(
+                        Checkbox::new("export-passwords")
+                            .label("Include passwords, encrypted with a passphrase")
+                            .checked(self.include_passwords)
+                            .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                                this.include_passwords = *checked;
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when(self.include_passwords, |form| {
+                form.child(
+                    field()
+                        .label("Passphrase")
+                        .required(true)
+                        .child(Input::new(&self.passphrase_input)),
+                )
+            })
+            .w_full()
+    }
+}
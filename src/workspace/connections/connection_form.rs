@@ -14,7 +14,8 @@ use crate::{
         ssh::{SshAuth, SshConfig},
         ConnectionInfo, ConnectionsRepository, DatabaseDriver, DatabaseManager, SslMode,
     },
-    state::{add_connection, connect, delete_connection, update_connection},
+    state::{add_connection, connect, delete_connection, update_connection, window_id},
+    workspace::{connections::show_connection_report, notifications::Toasts as _},
 };
 
 #[allow(dead_code)]
@@ -41,6 +42,10 @@ pub struct ConnectionForm {
     port: Entity<InputState>,
     driver_select: Entity<SelectState<Vec<DatabaseDriver>>>,
     driver: DatabaseDriver,
+    /// Prepend a `/* pgui:user@host tab:N */` comment to executed statements.
+    tag_queries: bool,
+    /// SQL run on every new session, e.g. `SET search_path TO app, public`.
+    startup_sql: Entity<InputState>,
 
     // SSH state
     ssh_enabled: bool,
@@ -141,6 +146,11 @@ impl ConnectionForm {
                     .placeholder("Port")
                     .clean_on_escape()
             });
+            let startup_sql = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder("SET search_path TO app, public;")
+                    .clean_on_escape()
+            });
 
             // Driver selector
             let initial_driver = connection
@@ -221,6 +231,8 @@ impl ConnectionForm {
                 port,
                 driver_select,
                 driver: initial_driver,
+                tag_queries: false,
+                startup_sql,
                 ssh_enabled,
                 ssh_host,
                 ssh_port,
@@ -317,6 +329,14 @@ impl ConnectionForm {
         let _ = self.port.update(cx, |this, cx| {
             this.set_value(connection.port.to_string(), window, cx)
         });
+        self.tag_queries = connection.tag_queries;
+        let _ = self.startup_sql.update(cx, |this, cx| {
+            this.set_value(
+                connection.startup_sql.clone().unwrap_or_default(),
+                window,
+                cx,
+            )
+        });
 
         if let Some(ssh) = &connection.ssh {
             self.ssh_enabled = true;
@@ -348,6 +368,7 @@ impl ConnectionForm {
             &self.password,
             &self.database,
             &self.port,
+            &self.startup_sql,
             &self.ssh_host,
             &self.ssh_port,
             &self.ssh_username,
@@ -356,6 +377,7 @@ impl ConnectionForm {
         ] {
             let _ = input.update(cx, |this, cx| this.set_value("", window, cx));
         }
+        self.tag_queries = false;
         self.ssh_enabled = false;
         self.ssh_auth = SshAuth::Agent;
         self.ssh_passphrase_known = false;
@@ -384,11 +406,28 @@ impl ConnectionForm {
         cx.notify();
     }
 
+    /// Fill the form with `connection` to save it as a new connection.
+    pub fn prefill(
+        &mut self,
+        connection: ConnectionInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.clear(window, cx);
+        self.driver = connection.driver;
+        let driver_index = connection.driver.to_index();
+        self.driver_select.update(cx, |state, cx| {
+            state.set_selected_index(Some(IndexPath::new(driver_index)), window, cx);
+        });
+        self.populate_from(connection, window, cx);
+        cx.notify();
+    }
+
     fn connect(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if let Some(connection) = self.get_connection(window, cx) {
             // Persist any SSH key passphrase the user typed (when applicable).
             self.persist_ssh_passphrase_if_needed(&connection, cx);
-            connect(&connection, cx);
+            connect(window_id(window), &connection, cx);
             self.clear(window, cx);
             cx.notify();
         }
@@ -408,7 +447,7 @@ impl ConnectionForm {
         let port_str = self.ssh_port.read(cx).value().to_string();
 
         if host.is_empty() || user.is_empty() {
-            window.push_notification(
+            window.push_toast(
                 (
                     NotificationType::Error,
                     "SSH host and username are required when SSH is enabled.",
@@ -424,7 +463,7 @@ impl ConnectionForm {
             match port_str.parse() {
                 Ok(p) if (1..=65535).contains(&p) => p,
                 _ => {
-                    window.push_notification(
+                    window.push_toast(
                         (NotificationType::Error, "Invalid SSH port."),
                         cx,
                     );
@@ -438,7 +477,7 @@ impl ConnectionForm {
             SshAuth::KeyFile { .. } => {
                 let path = self.ssh_key_path.read(cx).value().to_string();
                 if path.is_empty() {
-                    window.push_notification(
+                    window.push_toast(
                         (
                             NotificationType::Error,
                             "Private key path is required for key-file authentication.",
@@ -513,7 +552,7 @@ impl ConnectionForm {
             || database.is_empty()
             || port.is_empty()
         {
-            window.push_notification(
+            window.push_toast(
                 (
                     NotificationType::Error,
                     "Not all fields have values. Please try again.",
@@ -526,7 +565,7 @@ impl ConnectionForm {
         let port_num: usize = match port.parse() {
             Ok(n) if (1..=65_535).contains(&n) => n,
             _ => {
-                window.push_notification((NotificationType::Error, "Invalid port number."), cx);
+                window.push_toast((NotificationType::Error, "Invalid port number."), cx);
                 return None;
             }
         };
@@ -538,6 +577,9 @@ impl ConnectionForm {
             return None;
         }
 
+        let startup_sql = self.startup_sql.read(cx).value().trim().to_string();
+        let startup_sql = (!startup_sql.is_empty()).then_some(startup_sql);
+
         let id = self
             .active_connection
             .as_ref()
@@ -555,13 +597,16 @@ impl ConnectionForm {
             port: port_num,
             ssl_mode: SslMode::Prefer,
             ssh,
+            tag_queries: self.tag_queries,
+            startup_sql,
+            search_path: None,
         })
     }
 
     fn save_connection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if let Some(connection) = self.get_connection(window, cx) {
             self.persist_ssh_passphrase_if_needed(&connection, cx);
-            add_connection(connection, cx);
+            add_connection(window_id(window), connection, cx);
             self.clear(window, cx);
         }
     }
@@ -569,7 +614,7 @@ impl ConnectionForm {
     fn update_connection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if let Some(connection) = self.get_connection(window, cx) {
             self.persist_ssh_passphrase_if_needed(&connection, cx);
-            update_connection(connection, cx);
+            update_connection(window_id(window), connection, cx);
         }
     }
 
@@ -597,23 +642,17 @@ impl ConnectionForm {
             let conn_for_test = connection.clone();
 
             cx.spawn_in(window, async move |_this, cx| {
-                let result = DatabaseManager::test_connection(&conn_for_test).await;
+                let report = DatabaseManager::test_connection(&conn_for_test).await;
+                if let Some(failure) = report.failure() {
+                    tracing::error!(
+                        "Connection test failed at {}: {}",
+                        failure.stage.label(),
+                        failure.detail
+                    );
+                }
 
                 let _ = cx.update(|window, cx| {
-                    match result {
-                        Ok(_) => {
-                            window.push_notification(
-                                (NotificationType::Success, "Connection successful!"),
-                                cx,
-                            );
-                        }
-                        Err(e) => {
-                            let error_msg: SharedString =
-                                format!("Connection failed: {}", e).into();
-                            tracing::error!("{}", error_msg.clone());
-                            window.push_notification((NotificationType::Error, error_msg), cx);
-                        }
-                    }
+                    show_connection_report(conn_for_test.name.clone(), report, window, cx);
 
                     cx.update_entity(&entity, |form, cx| {
                         form.is_testing = false;
@@ -765,6 +804,24 @@ impl Render for ConnectionForm {
                             .label("Database")
                             .required(true)
                             .child(Input::new(&self.database)),
+                    )
+                    .child(
+                        field()
+                            .col_span(2)
+                            .label("Startup SQL")
+                            .description("Runs on every new session, after connecting")
+                            .child(Input::new(&self.startup_sql)),
+                    )
+                    .child(
+                        field().col_span(2).label_indent(false).child(
+                            Switch::new("tag-queries")
+                                .checked(self.tag_queries)
+                                .label("Tag queries with a /* pgui:user@host tab:N */ comment")
+                                .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                                    this.tag_queries = *checked;
+                                    cx.notify();
+                                })),
+                        ),
                     ),
             )
             .child(
@@ -819,7 +876,7 @@ impl Render for ConnectionForm {
                                                             cx.notify();
                                                         },
                                                     );
-                                                    window.push_notification(
+                                                    window.push_toast(
                                                         (NotificationType::Success, "Deleted"),
                                                         cx,
                                                     );
@@ -1,22 +1,61 @@
 use gpui::*;
 use gpui_component::{
-    IndexPath,
+    ActiveTheme as _, IndexPath,
     list::{ListDelegate, ListState},
 };
+use uuid::Uuid;
 
 use crate::{services::*, workspace::connections::ConnectionListItem};
 
+/// Section listing the most recently opened connections, above the rest.
+const RECENT_SECTION: usize = 0;
+const ALL_SECTION: usize = 1;
+
 pub struct ConnectionListDelegate {
     connections: Vec<ConnectionInfo>,
-    pub matched_connections: Vec<ConnectionInfo>,
+    recent_connections: Vec<ConnectionInfo>,
+    matched_connections: Vec<ConnectionInfo>,
     selected_index: Option<IndexPath>,
 }
 
 impl ListDelegate for ConnectionListDelegate {
     type Item = ConnectionListItem;
 
-    fn items_count(&self, _section: usize, _app: &App) -> usize {
-        self.matched_connections.len()
+    fn sections_count(&self, _app: &App) -> usize {
+        2
+    }
+
+    fn items_count(&self, section: usize, _app: &App) -> usize {
+        if section == RECENT_SECTION {
+            self.recent_connections.len()
+        } else {
+            self.matched_connections.len()
+        }
+    }
+
+    fn render_section_header(
+        &mut self,
+        section: usize,
+        _window: &mut Window,
+        cx: &mut Context<ListState<Self>>,
+    ) -> Option<impl IntoElement> {
+        if self.recent_connections.is_empty() {
+            return None;
+        }
+        let title = if section == RECENT_SECTION {
+            "Recent"
+        } else {
+            "All Connections"
+        };
+        Some(
+            div()
+                .px_2()
+                .pt_2()
+                .pb_1()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .child(title),
+        )
     }
 
     fn confirm(
@@ -26,7 +65,7 @@ impl ListDelegate for ConnectionListDelegate {
         _cx: &mut Context<ListState<Self>>,
     ) {
         if let Some(selected) = self.selected_index {
-            if let Some(conn) = self.matched_connections.get(selected.row) {
+            if let Some(conn) = self.connection_at(selected) {
                 tracing::debug!("Selected connection: {}@{}", conn.username, conn.hostname);
             }
         }
@@ -49,7 +88,7 @@ impl ListDelegate for ConnectionListDelegate {
         _cx: &mut Context<ListState<Self>>,
     ) -> Option<Self::Item> {
         let selected = Some(ix) == self.selected_index;
-        if let Some(conn) = self.matched_connections.get(ix.row) {
+        if let Some(conn) = self.connection_at(ix) {
             return Some(ConnectionListItem::new(ix, conn.clone(), ix, selected));
         }
         None
@@ -60,16 +99,34 @@ impl ConnectionListDelegate {
     pub fn new() -> Self {
         Self {
             connections: vec![],
+            recent_connections: vec![],
             matched_connections: vec![],
             selected_index: None,
         }
     }
 
-    pub fn update_connections(&mut self, connections: Vec<ConnectionInfo>) {
+    /// The connection shown at `ix`, in either section.
+    pub fn connection_at(&self, ix: IndexPath) -> Option<&ConnectionInfo> {
+        if ix.section == RECENT_SECTION {
+            self.recent_connections.get(ix.row)
+        } else {
+            self.matched_connections.get(ix.row)
+        }
+    }
+
+    pub fn update_connections(&mut self, connections: Vec<ConnectionInfo>, recent: &[Uuid]) {
         self.connections = connections;
         self.matched_connections = self.connections.clone();
-        if !self.matched_connections.is_empty() && self.selected_index.is_none() {
-            self.selected_index = Some(IndexPath::default());
+        self.recent_connections = recent
+            .iter()
+            .filter_map(|id| self.connections.iter().find(|c| c.id == *id).cloned())
+            .collect();
+        if self.selected_index.is_none() {
+            if !self.recent_connections.is_empty() {
+                self.selected_index = Some(IndexPath::new(0).section(RECENT_SECTION));
+            } else if !self.matched_connections.is_empty() {
+                self.selected_index = Some(IndexPath::new(0).section(ALL_SECTION));
+            }
         }
     }
 }
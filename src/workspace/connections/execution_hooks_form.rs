@@ -0,0 +1,111 @@
+use gpui::{
+    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
+    Window,
+};
+use gpui_component::{
+    Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputState},
+};
+
+use crate::services::{ExecutionHook, ExecutionHooks, HookKind, HookStage};
+
+/// Dialog body for the commands run before and after each query of one
+/// connection, each either SQL or a shell command.
+pub struct ExecutionHooksForm {
+    before_input: Entity<InputState>,
+    before_kind: HookKind,
+    after_input: Entity<InputState>,
+    after_kind: HookKind,
+}
+
+impl ExecutionHooksForm {
+    pub fn view(hooks: ExecutionHooks, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let before_input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder("e.g. SET ROLE analyst;")
+                    .default_value(hooks.before.command.clone())
+            });
+            let after_input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder("e.g. logger -t pgui \"$PGUI_STATUS $PGUI_QUERY\"")
+                    .default_value(hooks.after.command.clone())
+            });
+            Self {
+                before_input,
+                before_kind: hooks.before.kind,
+                after_input,
+                after_kind: hooks.after.kind,
+            }
+        })
+    }
+
+    /// The hooks as entered; a blank command turns its hook off.
+    pub fn hooks(&self, cx: &App) -> ExecutionHooks {
+        let hook = |input: &Entity<InputState>, kind| ExecutionHook {
+            kind,
+            command: input.read(cx).value().trim().to_string(),
+        };
+        ExecutionHooks {
+            before: hook(&self.before_input, self.before_kind),
+            after: hook(&self.after_input, self.after_kind),
+        }
+    }
+
+    fn kind_buttons(&self, stage: HookStage, cx: &mut Context<Self>) -> impl IntoElement {
+        let current = match stage {
+            HookStage::Before => self.before_kind,
+            HookStage::After => self.after_kind,
+        };
+        HookKind::ALL
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, kind| {
+                row.child(
+                    Button::new(SharedString::from(format!(
+                        "{}-{}",
+                        stage.name(),
+                        kind.label()
+                    )))
+                    .label(kind.label())
+                    .small()
+                    .ghost()
+                    .selected(current == kind)
+                    .on_click(cx.listener(move |this, _, _win, cx| {
+                        match stage {
+                            HookStage::Before => this.before_kind = kind,
+                            HookStage::After => this.after_kind = kind,
+                        }
+                        cx.notify();
+                    })),
+                )
+            })
+    }
+}
+
+impl Render for ExecutionHooksForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_form()
+            .small()
+            .child(
+                field()
+                    .label("Before each query")
+                    .description("A failing hook stops the query from running")
+                    .child(self.kind_buttons(HookStage::Before, cx))
+                    .child(Input::new(&self.before_input)),
+            )
+            .child(
+                field()
+                    .label("After each query")
+                    .description(
+                        "Shell commands see PGUI_QUERY, PGUI_STATUS, PGUI_ROWS, \
+                         PGUI_DURATION_MS and PGUI_ERROR",
+                    )
+                    .child(self.kind_buttons(HookStage::After, cx))
+                    .child(Input::new(&self.after_input)),
+            )
+            .w_full()
+    }
+}
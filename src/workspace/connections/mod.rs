@@ -1,10 +1,22 @@
+mod agent_privacy_form;
+mod connection_export_form;
 mod connection_form;
 mod connection_list;
 mod connection_list_item;
 mod connection_manager;
+mod execution_hooks_form;
+mod password_prompt;
+mod password_storage;
+mod test_report;
 
+pub(crate) use agent_privacy_form::AgentPrivacyForm;
+pub(crate) use connection_export_form::*;
 pub(crate) use connection_form::*;
 pub(crate) use connection_list::*;
 pub(crate) use connection_list_item::*;
+pub(crate) use execution_hooks_form::ExecutionHooksForm;
 
 pub use connection_manager::ConnectionManager;
+pub(crate) use password_prompt::PasswordPrompt;
+pub(crate) use password_storage::{open_password_storage_dialog, open_unlock_vault_dialog};
+pub(crate) use test_report::show_connection_report;
@@ -0,0 +1,70 @@
+use gpui::{App, AppContext, Context, Entity, IntoElement, ParentElement, Render, Styled, Window};
+use gpui_component::{
+    checkbox::Checkbox,
+    input::{Input, InputState},
+    label::Label,
+    v_flex,
+};
+
+use crate::services::ConnectionInfo;
+
+/// Dialog body asking for a new password after the server rejected the
+/// saved one.
+pub struct PasswordPrompt {
+    connection: ConnectionInfo,
+    password_input: Entity<InputState>,
+    update_keychain: bool,
+}
+
+impl PasswordPrompt {
+    pub fn view(connection: ConnectionInfo, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let password_input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .masked(true)
+                    .placeholder("Password")
+            });
+            Self {
+                connection,
+                password_input,
+                update_keychain: true,
+            }
+        })
+    }
+
+    pub fn connection(&self) -> &ConnectionInfo {
+        &self.connection
+    }
+
+    pub fn password(&self, cx: &App) -> String {
+        self.password_input.read(cx).value().to_string()
+    }
+
+    pub fn update_keychain(&self) -> bool {
+        self.update_keychain
+    }
+}
+
+impl Render for PasswordPrompt {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_2()
+            .child(
+                Label::new(format!(
+                    "{} rejected the password for {}@{}. Enter the current password to retry.",
+                    self.connection.name, self.connection.username, self.connection.hostname
+                ))
+                .text_sm(),
+            )
+            .child(Input::new(&self.password_input))
+            .child(
+                Checkbox::new("update-keychain")
+                    .label("Update the password saved in the keychain")
+                    .checked(self.update_keychain)
+                    .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                        this.update_keychain = *checked;
+                        cx.notify();
+                    })),
+            )
+    }
+}
@@ -0,0 +1,235 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::services::storage::{CredentialStore, MIN_MASTER_PASSWORD_LEN, Vault, vault_unlocked};
+use crate::state::VaultState;
+use crate::workspace::notifications::{RecordedNotifications as _, Toasts as _};
+
+/// Ask for the master password of the vault opened at startup.
+pub fn open_unlock_vault_dialog(window: &mut Window, cx: &mut App) {
+    let password_input = cx.new(|cx| {
+        InputState::new(window, cx)
+            .masked(true)
+            .placeholder("Master password")
+    });
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let password_input = password_input.clone();
+        dialog
+            .title("Unlock Password Vault")
+            .width(px(420.))
+            .child(
+                v_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(
+                            "Saved passwords are kept in an encrypted vault. Enter its master \
+                             password to use them.",
+                        )
+                        .text_sm(),
+                    )
+                    .child(Input::new(&password_input)),
+            )
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Unlock"))
+            .on_ok(move |_, window, cx| {
+                let master_password = password_input.read(cx).value().to_string();
+                let task = VaultState::unlock(master_password, cx);
+                window
+                    .spawn(cx, async move |cx| {
+                        if let Err(e) = task.await {
+                            let _ = cx.update(|window, cx| {
+                                window.push_recorded_notification(
+                                    (
+                                        NotificationType::Error,
+                                        SharedString::from(format!(
+                                            "Failed to unlock the vault: {}",
+                                            e
+                                        )),
+                                    ),
+                                    cx,
+                                );
+                                cx.update_global::<VaultState, _>(|state, _cx| {
+                                    state.prompt_unlock = true;
+                                });
+                            });
+                        }
+                    })
+                    .detach();
+                true
+            })
+    });
+}
+
+/// Choose between the system keychain and the encrypted vault, moving the
+/// saved passwords over.
+pub fn open_password_storage_dialog(window: &mut Window, cx: &mut App) {
+    let form = PasswordStorageForm::view(window, cx);
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        dialog
+            .title("Password Storage")
+            .width(px(480.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Apply"))
+            .on_ok(move |_, window, cx| {
+                let form = form.read(cx);
+                let store = form.selected;
+                let master_password = form.master_password(cx);
+                if let Some(warning) = form.validate(cx) {
+                    window.push_toast((NotificationType::Warning, warning), cx);
+                    return false;
+                }
+
+                let task = VaultState::switch_store(store, master_password, cx);
+                window
+                    .spawn(cx, async move |cx| {
+                        let result = task.await;
+                        let _ = cx.update(|window, cx| {
+                            let (notification_type, message): (_, SharedString) = match result {
+                                Ok(()) => (
+                                    NotificationType::Success,
+                                    format!("Passwords are now kept in the {}", store.label())
+                                        .into(),
+                                ),
+                                Err(e) => (
+                                    NotificationType::Error,
+                                    format!("Failed to move passwords: {}", e).into(),
+                                ),
+                            };
+                            window.push_recorded_notification((notification_type, message), cx);
+                        });
+                    })
+                    .detach();
+                true
+            })
+    });
+}
+
+struct PasswordStorageForm {
+    selected: CredentialStore,
+    master_input: Entity<InputState>,
+    confirm_input: Entity<InputState>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PasswordStorageForm {
+    fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self {
+            selected: cx.global::<VaultState>().store,
+            master_input: cx.new(|cx| {
+                InputState::new(window, cx)
+                    .masked(true)
+                    .placeholder("Master password")
+            }),
+            confirm_input: cx.new(|cx| {
+                InputState::new(window, cx)
+                    .masked(true)
+                    .placeholder("Confirm master password")
+            }),
+            _subscriptions: vec![cx.observe_global::<VaultState>(|_, cx| cx.notify())],
+        })
+    }
+
+    /// The vault has to be opened, or created, before it can be used.
+    fn needs_master_password(&self) -> bool {
+        self.selected == CredentialStore::Vault && !vault_unlocked()
+    }
+
+    fn master_password(&self, cx: &App) -> Option<String> {
+        self.needs_master_password()
+            .then(|| self.master_input.read(cx).value().to_string())
+    }
+
+    fn validate(&self, cx: &App) -> Option<SharedString> {
+        if !self.needs_master_password() || Vault::exists() {
+            return None;
+        }
+        let master_password = self.master_input.read(cx).value();
+        if master_password.chars().count() < MIN_MASTER_PASSWORD_LEN {
+            Some(
+                format!(
+                    "The master password needs at least {} characters",
+                    MIN_MASTER_PASSWORD_LEN
+                )
+                .into(),
+            )
+        } else if master_password != self.confirm_input.read(cx).value() {
+            Some("The master passwords do not match".into())
+        } else {
+            None
+        }
+    }
+}
+
+impl Render for PasswordStorageForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let stores = [CredentialStore::Keyring, CredentialStore::Vault]
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, store| {
+                row.child(
+                    Button::new(store.label())
+                        .label(store.label())
+                        .small()
+                        .ghost()
+                        .selected(self.selected == store)
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.selected = store;
+                            cx.notify();
+                        })),
+                )
+            });
+
+        let description = match self.selected {
+            CredentialStore::Keyring => {
+                "Passwords are kept in the operating system's keychain or Secret Service."
+            }
+            CredentialStore::Vault => {
+                "Passwords are kept in ~/.pgui/vault.json, encrypted with a master password \
+                 asked for at startup. Use this where no keychain is available."
+            }
+        };
+        let creating = self.needs_master_password() && !Vault::exists();
+        let state = cx.global::<VaultState>();
+        let current = format!(
+            "Currently using the {}{}",
+            state.store.label(),
+            if state.locked { " (locked)" } else { "" }
+        );
+
+        v_flex()
+            .gap_2()
+            .child(stores)
+            .child(Label::new(description).text_sm())
+            .when(self.needs_master_password(), |col| {
+                col.child(Input::new(&self.master_input))
+            })
+            .when(creating, |col| {
+                col.child(Input::new(&self.confirm_input)).child(
+                    Label::new(
+                        "The master password cannot be recovered. Without it the saved \
+                         passwords have to be entered again.",
+                    )
+                    .text_xs()
+                    .text_color(cx.theme().warning),
+                )
+            })
+            .child(
+                Label::new(current)
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+    }
+}
@@ -0,0 +1,104 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Icon, IconName, StyledExt as _, WindowExt as _, h_flex, label::Label, v_flex,
+};
+
+use crate::services::{ConnectionReport, StageResult, StageStatus};
+
+/// Open a dialog listing each stage of a connection test with its outcome
+/// and, for a failure, what to try.
+pub fn show_connection_report(
+    connection_name: String,
+    report: ConnectionReport,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let title = if report.passed() {
+        format!("{} — Connection Successful", connection_name)
+    } else {
+        format!("{} — Connection Failed", connection_name)
+    };
+
+    window.open_dialog(cx, move |dialog, _win, cx| {
+        let rows = report
+            .stages
+            .iter()
+            .map(|stage| render_stage(stage, cx))
+            .collect::<Vec<_>>();
+        dialog
+            .title(title.clone())
+            .width(px(560.))
+            .child(v_flex().gap_2().children(rows))
+    });
+}
+
+fn render_stage(stage: &StageResult, cx: &App) -> AnyElement {
+    let (icon, detail_color) = match stage.status {
+        StageStatus::Passed => (
+            Icon::new(IconName::CircleCheck).text_color(cx.theme().success),
+            cx.theme().muted_foreground,
+        ),
+        StageStatus::Failed => (
+            Icon::new(IconName::CircleX).text_color(cx.theme().danger),
+            cx.theme().danger,
+        ),
+        StageStatus::Skipped => (
+            Icon::empty()
+                .path("icons/minus.svg")
+                .text_color(cx.theme().muted_foreground),
+            cx.theme().muted_foreground,
+        ),
+    };
+    let timing = stage
+        .duration_ms
+        .map_or_else(String::new, |ms| format!("{}ms", ms));
+
+    h_flex()
+        .gap_2()
+        .items_start()
+        .child(icon.size_4().mt_0p5())
+        .child(
+            v_flex()
+                .flex_1()
+                .gap_0p5()
+                .child(
+                    h_flex()
+                        .justify_between()
+                        .child(
+                            Label::new(stage.stage.label())
+                                .text_sm()
+                                .font_medium()
+                                .when(stage.status == StageStatus::Skipped, |l| {
+                                    l.text_color(cx.theme().muted_foreground)
+                                }),
+                        )
+                        .child(
+                            Label::new(timing)
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        ),
+                )
+                .child(
+                    Label::new(stage.detail.clone())
+                        .text_xs()
+                        .text_color(detail_color),
+                )
+                .when_some(stage.hint.clone(), |col, hint| {
+                    col.child(
+                        h_flex()
+                            .gap_1()
+                            .items_start()
+                            .child(
+                                Icon::empty()
+                                    .path("icons/info.svg")
+                                    .size_3()
+                                    .mt_0p5()
+                                    .text_color(cx.theme().muted_foreground),
+                            )
+                            .child(Label::new(hint).text_xs()),
+                    )
+                }),
+        )
+        .into_any_element()
+}
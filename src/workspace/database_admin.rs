@@ -0,0 +1,381 @@
+use anyhow::Result;
+use gpui::{
+    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
+    Window, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, IndexPath, Sizable as _, WindowExt as _,
+    checkbox::Checkbox,
+    dialog::DialogButtonProps,
+    input::{Input, InputState},
+    label::Label,
+    notification::NotificationType,
+    select::{Select, SelectState},
+    v_flex,
+};
+
+use crate::{
+    services::{DatabaseDriver, DatabaseManager, NewDatabase, NewSchema},
+    state::{
+        ChangeRecorderState, DatabaseState, EditorState, WindowConnection, WindowScoped,
+        reload_databases, window_id,
+    },
+    workspace::notifications::{RecordedNotifications as _, Toasts as _},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AdminTarget {
+    Database,
+    Schema,
+}
+
+impl AdminTarget {
+    fn noun(&self) -> &'static str {
+        match self {
+            AdminTarget::Database => "database",
+            AdminTarget::Schema => "schema",
+        }
+    }
+}
+
+fn active_driver(window: &Window, cx: &App) -> DatabaseDriver {
+    WindowConnection::of(window_id(window), cx)
+        .active_connection
+        .as_ref()
+        .map(|c| c.driver)
+        .unwrap_or(DatabaseDriver::Postgres)
+}
+
+fn field_label(label: &'static str) -> Label {
+    Label::new(label).text_xs()
+}
+
+/// Dialog body for `CREATE DATABASE` or `CREATE SCHEMA`. Owner, encoding
+/// and template only apply where the server supports them.
+pub struct CreateForm {
+    target: AdminTarget,
+    driver: DatabaseDriver,
+    name_input: Entity<InputState>,
+    owner_input: Entity<InputState>,
+    encoding_input: Entity<InputState>,
+    template_input: Entity<InputState>,
+}
+
+impl CreateForm {
+    fn view(target: AdminTarget, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let driver = active_driver(window, cx);
+        cx.new(|cx| {
+            let name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Name"));
+            let owner_input = cx.new(|cx| {
+                InputState::new(window, cx).placeholder("Leave empty for the current user")
+            });
+            let encoding_input = cx.new(|cx| {
+                InputState::new(window, cx).placeholder(match driver {
+                    DatabaseDriver::Postgres => "e.g. UTF8, leave empty for the default",
+                    DatabaseDriver::MySql => "e.g. utf8mb4, leave empty for the default",
+                })
+            });
+            let template_input = cx.new(|cx| {
+                InputState::new(window, cx).placeholder("e.g. template0, leave empty for template1")
+            });
+            Self {
+                target,
+                driver,
+                name_input,
+                owner_input,
+                encoding_input,
+                template_input,
+            }
+        })
+    }
+
+    fn value(input: &Entity<InputState>, cx: &App) -> String {
+        input.read(cx).value().trim().to_string()
+    }
+
+    fn operation(&self, cx: &App) -> AdminOperation {
+        let name = Self::value(&self.name_input, cx);
+        let owner = Self::value(&self.owner_input, cx);
+        match self.target {
+            AdminTarget::Database => AdminOperation::CreateDatabase(NewDatabase {
+                name,
+                owner,
+                encoding: Self::value(&self.encoding_input, cx),
+                template: Self::value(&self.template_input, cx),
+            }),
+            AdminTarget::Schema => AdminOperation::CreateSchema(NewSchema { name, owner }),
+        }
+    }
+}
+
+impl Render for CreateForm {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        let postgres = self.driver == DatabaseDriver::Postgres;
+        let is_database = self.target == AdminTarget::Database;
+
+        v_flex()
+            .gap_3()
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(field_label("Name"))
+                    .child(Input::new(&self.name_input).small()),
+            )
+            .when(postgres, |d| {
+                d.child(
+                    v_flex()
+                        .gap_1()
+                        .child(field_label("Owner"))
+                        .child(Input::new(&self.owner_input).small()),
+                )
+            })
+            .when(is_database, |d| {
+                d.child(
+                    v_flex()
+                        .gap_1()
+                        .child(field_label(if postgres {
+                            "Encoding"
+                        } else {
+                            "Character set"
+                        }))
+                        .child(Input::new(&self.encoding_input).small()),
+                )
+            })
+            .when(is_database && postgres, |d| {
+                d.child(
+                    v_flex()
+                        .gap_1()
+                        .child(field_label("Template"))
+                        .child(Input::new(&self.template_input).small()),
+                )
+            })
+    }
+}
+
+/// Dialog body for dropping a database or schema. The name has to be
+/// typed again before the drop runs.
+pub struct DropForm {
+    target: AdminTarget,
+    driver: DatabaseDriver,
+    select: Entity<SelectState<Vec<SharedString>>>,
+    confirm_input: Entity<InputState>,
+    /// `WITH (FORCE)` for a database, `CASCADE` for a schema.
+    force: bool,
+}
+
+impl DropForm {
+    fn view(
+        target: AdminTarget,
+        selected: Option<String>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        let driver = active_driver(window, cx);
+        let window_id = window_id(window);
+        // The connected database cannot be dropped from its own session.
+        let connected = WindowConnection::of(window_id, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| c.database.clone());
+        let names: Vec<SharedString> = match target {
+            AdminTarget::Database => DatabaseState::of(window_id, cx)
+                .databases
+                .iter()
+                .filter(|db| Some(&db.datname) != connected.as_ref())
+                .map(|db| db.datname.clone().into())
+                .collect(),
+            AdminTarget::Schema => EditorState::of(window_id, cx)
+                .schemas
+                .iter()
+                .map(|schema| schema.clone().into())
+                .collect(),
+        };
+        let selected_index = selected
+            .and_then(|name| names.iter().position(|n| n.as_ref() == name.as_str()))
+            .map(IndexPath::new);
+
+        cx.new(|cx| {
+            let select = cx.new(|cx| SelectState::new(names, selected_index, window, cx));
+            let confirm_input =
+                cx.new(|cx| InputState::new(window, cx).placeholder("Type the name to confirm"));
+            Self {
+                target,
+                driver,
+                select,
+                confirm_input,
+                force: false,
+            }
+        })
+    }
+
+    fn operation(&self, cx: &App) -> Result<AdminOperation> {
+        let Some(name) = self.select.read(cx).selected_value().cloned() else {
+            anyhow::bail!("Pick the {} to drop", self.target.noun());
+        };
+        if self.confirm_input.read(cx).value().trim() != name.as_ref() {
+            anyhow::bail!("Type {} to confirm dropping it", name);
+        }
+        let name = name.to_string();
+        Ok(match self.target {
+            AdminTarget::Database => AdminOperation::DropDatabase(name, self.force),
+            AdminTarget::Schema => AdminOperation::DropSchema(name, self.force),
+        })
+    }
+}
+
+impl Render for DropForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let force_label = match self.target {
+            AdminTarget::Database => "Disconnect other sessions first (WITH FORCE, Postgres 13+)",
+            AdminTarget::Schema => "Also drop everything in the schema (CASCADE)",
+        };
+
+        v_flex()
+            .gap_3()
+            .child(
+                Label::new(format!(
+                    "Dropping a {} deletes it and all of its data. This cannot be undone.",
+                    self.target.noun()
+                ))
+                .text_sm()
+                .text_color(cx.theme().danger),
+            )
+            .child(Select::new(&self.select).placeholder(match self.target {
+                AdminTarget::Database => "Pick a database",
+                AdminTarget::Schema => "Pick a schema",
+            }))
+            .child(Input::new(&self.confirm_input).small())
+            .when(self.driver == DatabaseDriver::Postgres, |d| {
+                d.child(
+                    Checkbox::new("drop-force")
+                        .label(force_label)
+                        .checked(self.force)
+                        .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                            this.force = *checked;
+                            cx.notify();
+                        })),
+                )
+            })
+    }
+}
+
+enum AdminOperation {
+    CreateDatabase(NewDatabase),
+    DropDatabase(String, bool),
+    CreateSchema(NewSchema),
+    DropSchema(String, bool),
+}
+
+impl AdminOperation {
+    async fn run(self, db_manager: &DatabaseManager) -> Result<String> {
+        match self {
+            AdminOperation::CreateDatabase(database) => db_manager.create_database(&database).await,
+            AdminOperation::DropDatabase(name, force) => {
+                db_manager.drop_database(&name, force).await
+            }
+            AdminOperation::CreateSchema(schema) => db_manager.create_schema(&schema).await,
+            AdminOperation::DropSchema(name, cascade) => {
+                db_manager.drop_schema(&name, cascade).await
+            }
+        }
+    }
+}
+
+/// Run `operation`, record the statement and refresh the database and
+/// schema lists.
+fn run_operation(operation: AdminOperation, window: &mut Window, cx: &mut App) {
+    let window_id = window_id(window);
+    let db_manager = WindowConnection::of(window_id, cx).db_manager.clone();
+    window
+        .spawn(cx, async move |cx| {
+            let result = operation.run(&db_manager).await;
+            let _ = cx.update(|window, cx| match result {
+                Ok(sql) => {
+                    ChangeRecorderState::record(window_id, [sql.as_str()], cx);
+                    reload_databases(window_id, cx);
+                    window.push_toast((NotificationType::Success, SharedString::from(sql)), cx);
+                }
+                Err(e) => {
+                    let message: SharedString = e.to_string().into();
+                    window.push_recorded_notification((NotificationType::Error, message), cx);
+                }
+            });
+        })
+        .detach();
+}
+
+fn open_create_dialog(target: AdminTarget, window: &mut Window, cx: &mut App) {
+    let form = CreateForm::view(target, window, cx);
+    let title = match target {
+        AdminTarget::Database => "New Database",
+        AdminTarget::Schema => "New Schema",
+    };
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        dialog
+            .title(title)
+            .width(px(480.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Create"))
+            .on_ok(move |_, window, cx| {
+                let operation = form.read(cx).operation(cx);
+                run_operation(operation, window, cx);
+                true
+            })
+    });
+}
+
+fn open_drop_dialog(
+    target: AdminTarget,
+    selected: Option<String>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let form = DropForm::view(target, selected, window, cx);
+    let title = match target {
+        AdminTarget::Database => "Drop Database",
+        AdminTarget::Schema => "Drop Schema",
+    };
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        dialog
+            .title(title)
+            .width(px(480.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Drop"))
+            .on_ok(move |_, window, cx| match form.read(cx).operation(cx) {
+                Ok(operation) => {
+                    run_operation(operation, window, cx);
+                    true
+                }
+                Err(e) => {
+                    window.push_toast(
+                        (NotificationType::Warning, SharedString::from(e.to_string())),
+                        cx,
+                    );
+                    false
+                }
+            })
+    });
+}
+
+pub fn open_create_database_dialog(window: &mut Window, cx: &mut App) {
+    open_create_dialog(AdminTarget::Database, window, cx);
+}
+
+pub fn open_drop_database_dialog(window: &mut Window, cx: &mut App) {
+    open_drop_dialog(AdminTarget::Database, None, window, cx);
+}
+
+pub fn open_create_schema_dialog(window: &mut Window, cx: &mut App) {
+    open_create_dialog(AdminTarget::Schema, window, cx);
+}
+
+/// Drop a schema, preselecting `schema` when opened from the tree.
+pub fn open_drop_schema_dialog(schema: Option<String>, window: &mut Window, cx: &mut App) {
+    open_drop_dialog(AdminTarget::Schema, schema, window, cx);
+}
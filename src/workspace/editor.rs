@@ -1,34 +1,91 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 
-use crate::services::sql::{SqlCodeActionProvider, SqlQuery, SqlQueryAnalyzer};
-use crate::state::{EditorCodeActions, EditorInlineCompletions};
-use crate::workspace::agent::format_schema_for_llm;
+use crate::services::sql::{
+    HISTORY_SUGGESTION_LIMIT, OutlineEntry, PairEdit, STATEMENT_RUN_HISTORY_LIMIT, Snippet,
+    SnippetSession, SqlCodeActionProvider, SqlHoverProvider, SqlQuery, SqlQueryAnalyzer,
+    StatementCategory, StatementRun, StatementRuns, deleted_pair, edit_between, expand_snippet,
+    file_title, in_list_items, in_literal, inserted_snippet, matching_bracket, newline_indent,
+    opens_in_list, snippet_at, sql_language, statement_outline, template_variables, typed_pair,
+    with_sql_extension,
+};
+use crate::state::{
+    AgentPrivacyState, ChangeRecorderState, EditorAgentContext, EditorCodeActions,
+    EditorInlineCompletions, EditorRefactorings, FilesState, HooksState, PendingRefactoring,
+    SnippetsState, VariablesState, add_recent_file, open_file,
+};
+use crate::themes::{busy_indicator, editor_colors, production_accent};
+use crate::workspace::agent::schema_context_for_llm;
+use crate::workspace::change_script::open_change_script_dialog;
+use crate::workspace::database_admin::{
+    open_create_database_dialog, open_create_schema_dialog, open_drop_database_dialog,
+    open_drop_schema_dialog,
+};
+use crate::workspace::notifications::{RecordedNotifications as _, Toasts as _};
+use crate::workspace::roles::{open_reset_password_dialog, open_role_dialog};
+use crate::workspace::schema_changes::open_schema_changes_dialog;
+use crate::workspace::server_settings::open_server_settings_dialog;
+use crate::workspace::snippets_form::open_snippets_dialog;
+use crate::workspace::table_growth::open_table_growth_dialog;
+use crate::workspace::tablespaces::open_tablespaces_dialog;
+use crate::workspace::variables_form::open_variables_dialog;
 use crate::{
-    services::{ConnectionInfo, SqlCompletionProvider},
-    state::{ConnectionState, DatabaseState, EditorState, change_database, disconnect},
+    services::{
+        AppStore, ConnectionInfo, DatabaseDriver, PlanEstimate, SqlCompletionProvider,
+        agent::SchemaSelection,
+    },
+    state::{
+        DatabaseState, EditorState, PerWindow, WindowConnection, WindowScoped, change_database,
+        disconnect, set_active_schema, window_id,
+    },
 };
 use gpui::{prelude::FluentBuilder as _, *};
-use gpui_component::spinner::Spinner;
 use gpui_component::{
-    ActiveTheme as _, Disableable as _, Icon, Sizable as _,
+    ActiveTheme as _, Disableable as _, Icon, Sizable as _, WindowExt as _,
     button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
     divider::Divider,
     h_flex,
-    input::{Input, InputState, TabSize},
+    input::{Input, InputState, RopeExt as _, TabSize},
+    label::Label,
+    menu::{DropdownMenu as _, PopupMenuItem},
+    notification::NotificationType,
     select::{Select, SelectEvent, SelectState},
+    tooltip::Tooltip,
     v_flex,
 };
 use gpui_component::{Selectable as _, input};
 use lsp_types::CompletionItem;
 use sqlformat::{FormatOptions, QueryParams, format};
 
+/// One level of indentation, as wide as the editor's tab.
+const INDENT: &str = "  ";
+
+actions!(
+    editor,
+    [
+        OpenFile,
+        OpenRecentFile,
+        SaveFile,
+        SaveFileAs,
+        RunStatement,
+        RunSelection,
+        RunScript
+    ]
+);
+
 pub enum EditorEvent {
     ExecuteQuery(String),
+    /// Run the script on several connections or databases in turn.
+    RunOnTargets(String),
 }
 
 impl EventEmitter<EditorEvent> for Editor {}
 
 pub struct Editor {
+    window_id: WindowId,
     input_state: Entity<InputState>,
     _subscriptions: Vec<Subscription>,
     completion_provider: Rc<SqlCompletionProvider>,
@@ -37,12 +94,54 @@ pub struct Editor {
     is_formatting: bool,
     active_connection: Option<ConnectionInfo>,
     db_select: Entity<SelectState<Vec<SharedString>>>,
+    /// Picks the first schema of the session's search path (Postgres).
+    schema_select: Entity<SelectState<Vec<SharedString>>>,
+    search_path: Vec<String>,
     analyzer: SqlQueryAnalyzer,
     parsed_queries: Vec<SqlQuery>,
     current_query_index: Option<usize>,
+    /// One entry per parsed query, for the outline and minimap.
+    outline: Vec<OutlineEntry>,
+    line_count: usize,
+    show_outline: bool,
+    show_minimap: bool,
+    /// Where the bracket at the cursor pairs, for the lens, and whether it
+    /// is unmatched.
+    bracket_hint: Option<(String, bool)>,
+    /// The contents and cursor when Enter was pressed, until the input has
+    /// broken the line.
+    pending_newline: Option<(String, usize)>,
+    /// Snippets Tab expands, the user's first.
+    snippets: Vec<Snippet>,
+    /// Tab stops of the snippet being filled in.
+    snippet: Option<SnippetSession>,
+    /// The contents as of the last change, to tell what the next one did.
+    last_text: String,
+    /// The selected text, which the execute button runs instead of the
+    /// statement at the cursor.
+    selection: Option<String>,
+    /// Last runs of statements on the active connection, for the lens above
+    /// the editor.
+    statement_runs: StatementRuns,
+    /// The planner's estimates by statement text, for the lenses; `None`
+    /// when the statement has no plan.
+    plan_estimates: HashMap<String, Option<PlanEstimate>>,
+    /// Waits for the cursor to settle before explaining its statement.
+    _estimate_task: Option<Task<()>>,
+    lens_scroll: ScrollHandle,
     inline_completions_enabled: bool,
     code_actions_loading: bool,
     inline_completions_loading: bool,
+    /// Tables described to the model for the latest agent request.
+    agent_context: Option<SchemaSelection>,
+    /// The `.sql` file the contents were opened from or saved to.
+    file_path: Option<PathBuf>,
+    /// Contents as last opened or saved, to tell whether there are unsaved
+    /// changes.
+    saved_text: String,
+    dirty: bool,
+    /// Zen mode: a larger font for presenting.
+    large_text: bool,
 }
 
 impl Editor {
@@ -53,8 +152,199 @@ impl Editor {
         });
     }
 
+    /// Replace the statement `failed` with `fix`. Returns false, leaving the
+    /// contents alone, when the statement is no longer in the editor.
+    pub fn apply_fix(
+        &mut self,
+        failed: &str,
+        fix: &str,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> bool {
+        let failed = failed.trim();
+        // Keep the statement's own terminator, or its lack of one
+        let fix = match failed.ends_with(';') {
+            true => fix.trim().to_string(),
+            false => fix.trim().trim_end_matches(';').to_string(),
+        };
+        cx.update_entity(&self.input_state, |input, cx| {
+            let Some(start) = input.value().find(failed).filter(|_| !failed.is_empty()) else {
+                return false;
+            };
+            let text = input.text();
+            let range = lsp_types::Range::new(
+                text.offset_to_position(start),
+                text.offset_to_position(start + failed.len()),
+            );
+            input.apply_lsp_edits(
+                &vec![lsp_types::TextEdit {
+                    range,
+                    new_text: fix,
+                    ..Default::default()
+                }],
+                window,
+                cx,
+            );
+            cx.notify();
+            true
+        })
+    }
+
+    /// Show SQL generated by a refactoring in an editable preview, and
+    /// insert it below its source statement once accepted.
+    fn review_refactoring(
+        &mut self,
+        pending: PendingRefactoring,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let preview = cx.new(|cx| {
+            InputState::new(window, cx)
+                .code_editor(sql_language())
+                .line_number(true)
+                .tab_size(TabSize {
+                    tab_size: 2,
+                    hard_tabs: false,
+                })
+                .default_value(pending.sql.clone())
+        });
+        let editor = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let preview = preview.clone();
+            let editor = editor.clone();
+            let insert_at = pending.insert_at;
+            dialog
+                .title(pending.title.clone())
+                .width(px(720.))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(div().h(px(360.)).child(Input::new(&preview).h_full()))
+                        .child(
+                            Label::new(
+                                "Review and rename the generated SQL; it is inserted below the query, not run.",
+                            )
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                        ),
+                )
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Insert into Editor"))
+                .on_ok(move |_, window, cx| {
+                    let sql = preview.read(cx).value().trim().to_string();
+                    let _ = editor.update(cx, |editor, cx| {
+                        editor.insert_below(insert_at, &sql, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    /// Insert `sql` at `offset` as a statement of its own.
+    fn insert_below(&mut self, offset: usize, sql: &str, window: &mut Window, cx: &mut App) {
+        self.insert_at(offset, format!("\n\n{}\n", sql), window, cx);
+    }
+
+    /// Insert `text` at `offset`.
+    fn insert_at(&mut self, offset: usize, text: String, window: &mut Window, cx: &mut App) {
+        cx.update_entity(&self.input_state, |input, cx| {
+            let rope = input.text();
+            let pos = rope.offset_to_position(offset.min(rope.len()));
+            input.apply_lsp_edits(
+                &vec![lsp_types::TextEdit {
+                    range: lsp_types::Range::new(pos, pos),
+                    new_text: text,
+                    ..Default::default()
+                }],
+                window,
+                cx,
+            );
+            cx.notify();
+        });
+    }
+
+    /// Offer to paste a list of values into an empty `IN (` as its items,
+    /// quoted unless they are all numbers. Other pastes go to the input.
+    fn on_paste(&mut self, _: &input::Paste, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(pasted) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        if self.selected_text(window, cx).is_some() {
+            return;
+        }
+        let input = self.input_state.read(cx);
+        let offset = input.cursor();
+        let before = input.value().get(..offset).unwrap_or_default().to_string();
+        if !opens_in_list(&before) {
+            return;
+        }
+        let Some(items) = in_list_items(&pasted) else {
+            return;
+        };
+        cx.stop_propagation();
+
+        let count = pasted
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        let editor = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let (on_ok, on_cancel) = (editor.clone(), editor.clone());
+            let (items, pasted) = (items.clone(), pasted.clone());
+            dialog
+                .title("Paste as IN List")
+                .width(px(560.))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(
+                            Label::new(format!(
+                                "The clipboard holds {} values, one per line.",
+                                count
+                            ))
+                            .text_sm(),
+                        )
+                        .child(
+                            div()
+                                .p_2()
+                                .max_h(px(240.))
+                                .overflow_hidden()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("Monaco")
+                                .text_xs()
+                                .child(items.clone()),
+                        ),
+                )
+                .confirm()
+                .button_props(
+                    DialogButtonProps::default()
+                        .ok_text("Paste as List")
+                        .cancel_text("Paste as Is"),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = on_ok.update(cx, |editor, cx| {
+                        editor.insert_at(offset, items.clone(), window, cx);
+                    });
+                    true
+                })
+                .on_cancel(move |_, window, cx| {
+                    let _ = on_cancel.update(cx, |editor, cx| {
+                        editor.insert_at(offset, pasted.clone(), window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    /// The full editor contents.
+    pub fn query_text(&self, cx: &App) -> String {
+        self.input_state.read(cx).value().to_string()
+    }
+
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let default_language = "sql".to_string();
+        let default_language = sql_language();
         let completion_provider = Rc::new(SqlCompletionProvider::new());
         let code_action_provider = Rc::new(SqlCodeActionProvider::new());
 
@@ -70,21 +360,27 @@ impl Editor {
                 .placeholder("Enter your SQL query here...");
             i.lsp.completion_provider = Some(completion_provider.clone());
             i.lsp.code_action_providers = vec![code_action_provider.clone()];
+            i.lsp.hover_provider = Some(Rc::new(SqlHoverProvider::new()));
             i
         });
 
         let db_select = cx.new(|cx| SelectState::new(Vec::<SharedString>::new(), None, window, cx));
+        let schema_select =
+            cx.new(|cx| SelectState::new(Vec::<SharedString>::new(), None, window, cx));
 
         let _subscriptions = vec![
-            cx.observe_global::<EditorState>(move |this, cx| {
-                let tables = cx.global::<EditorState>().tables.clone();
-                let schema = cx.global::<EditorState>().schema.clone();
+            cx.observe_global_in::<PerWindow<EditorState>>(window, move |this, win, cx| {
+                let state = EditorState::of(this.window_id, cx);
+                let tables = state.tables.clone();
+                let schemas = state.schemas.clone();
+                let search_path = state.search_path.clone();
+                // Tables outside the search path complete schema-qualified.
                 let completions = tables
                     .iter()
                     .map(|table| {
                         let table = table.clone();
                         CompletionItem {
-                            label: table.table_name.into(),
+                            label: table.name_on_search_path(&search_path).into(),
                             kind: Some(lsp_types::CompletionItemKind::CLASS), // Better kind for tables
                             detail: Some(
                                 format!("{}:{}", table.table_schema, table.table_type).into(),
@@ -93,19 +389,35 @@ impl Editor {
                         }
                     })
                     .collect::<Vec<_>>();
-                this.completion_provider.add_schema_completions(completions);
-                if let Some(schema) = schema {
-                    let formatted = format_schema_for_llm(&schema);
-                    this.completion_provider.add_schema(formatted.clone());
-                    this.code_action_provider.set_schema(formatted);
-                }
+                this.completion_provider.set_schema_completions(completions);
+                this.completion_provider
+                    .set_enum_types(&EditorState::of(this.window_id, cx).user_types);
+
+                let schemas: Vec<SharedString> = schemas.into_iter().map(Into::into).collect();
+                cx.update_entity(&this.schema_select.clone(), |select, cx| {
+                    select.set_items(schemas, win, cx);
+                    if let Some(active) = search_path.first() {
+                        select.set_selected_value(&active.clone().into(), win, cx);
+                    }
+                });
+                this.search_path = search_path;
+                this.update_agent_context(cx);
                 cx.notify();
             }),
-            cx.observe_global_in::<ConnectionState>(window, move |this, win, cx| {
-                let state = cx.global::<ConnectionState>();
+            cx.observe_global_in::<PerWindow<WindowConnection>>(window, move |this, win, cx| {
+                let state = WindowConnection::of(this.window_id, cx);
                 let active_connection = state.active_connection.clone();
 
+                let connection_changed = this.active_connection.as_ref().map(|c| c.id)
+                    != active_connection.as_ref().map(|c| c.id);
                 this.active_connection = active_connection.clone();
+                if connection_changed {
+                    this.load_history_suggestions(cx);
+                    this.load_statement_runs(cx);
+                    this.plan_estimates.clear();
+                    this.update_agent_context(cx);
+                    this.agent_context = None;
+                }
 
                 if let Some(conn) = active_connection.clone() {
                     cx.update_entity(&this.db_select.clone(), |select, cx| {
@@ -115,8 +427,8 @@ impl Editor {
 
                 cx.notify();
             }),
-            cx.observe_global_in::<DatabaseState>(window, move |this, win, cx| {
-                let state = cx.global::<DatabaseState>();
+            cx.observe_global_in::<PerWindow<DatabaseState>>(window, move |this, win, cx| {
+                let state = DatabaseState::of(this.window_id, cx);
                 let databases = state.databases.clone();
 
                 let databases: Vec<SharedString> = databases
@@ -130,8 +442,57 @@ impl Editor {
 
                 cx.notify();
             }),
+            cx.observe_in(&input_state, window, |this, _, window, cx| {
+                this.update_run_target(window, cx);
+            }),
+            cx.subscribe_in(&input_state, window, |this, _, event, window, cx| {
+                if let input::InputEvent::Change = event {
+                    this.indent_new_line(window, cx);
+                    if let Some((before, after)) = this.follow_snippet(cx) {
+                        this.start_completed_snippet(&before, &after, window, cx);
+                    }
+                }
+            }),
             cx.subscribe(&input_state, |this, _, _: &input::InputEvent, cx| {
                 this.reparse_queries(cx);
+                let dirty = *this.input_state.read(cx).value() != *this.saved_text;
+                if dirty != this.dirty {
+                    this.dirty = dirty;
+                    cx.notify();
+                }
+            }),
+            cx.observe_global_in::<FilesState>(window, move |this, win, cx| {
+                let Some(path) = cx.global::<FilesState>().pending_open.clone() else {
+                    return;
+                };
+                cx.update_global::<FilesState, _>(|state, _cx| {
+                    state.pending_open = None;
+                });
+                this.open_path(path, win, cx);
+            }),
+            cx.observe_global_in::<PerWindow<EditorRefactorings>>(window, move |this, win, cx| {
+                let Some(pending) = EditorRefactorings::of(this.window_id, cx).pending.clone()
+                else {
+                    return;
+                };
+                EditorRefactorings::update(this.window_id, cx, |state, _cx| {
+                    state.pending = None;
+                });
+                this.review_refactoring(pending, win, cx);
+            }),
+            cx.observe_global::<AgentPrivacyState>(move |this, cx| {
+                this.update_agent_context(cx);
+            }),
+            cx.observe_global::<HooksState>(|_, cx| cx.notify()),
+            cx.observe_global::<VariablesState>(|_, cx| cx.notify()),
+            cx.observe_global::<SnippetsState>(|this, cx| {
+                this.snippets = cx.global::<SnippetsState>().all();
+                this.completion_provider.set_snippets(this.snippets.clone());
+            }),
+            cx.observe_global::<ChangeRecorderState>(|_, cx| cx.notify()),
+            cx.observe_global::<EditorAgentContext>(move |this, cx| {
+                this.agent_context = cx.global::<EditorAgentContext>().selection.clone();
+                cx.notify();
             }),
             cx.observe_global::<EditorCodeActions>(move |this, cx| {
                 this.code_actions_loading = cx.global::<EditorCodeActions>().loading.clone();
@@ -143,10 +504,16 @@ impl Editor {
             }),
         ];
 
+        let snippets = cx.global::<SnippetsState>().all();
+        completion_provider.set_snippets(snippets.clone());
+
         cx.subscribe_in(&db_select, window, Self::on_select_database_event)
             .detach();
+        cx.subscribe_in(&schema_select, window, Self::on_select_schema_event)
+            .detach();
 
         Self {
+            window_id: window_id(window),
             input_state,
             completion_provider,
             code_action_provider,
@@ -154,14 +521,154 @@ impl Editor {
             is_formatting: false,
             active_connection: None,
             db_select,
+            schema_select,
+            search_path: vec![],
             _subscriptions,
             analyzer: SqlQueryAnalyzer::new(),
             parsed_queries: vec![],
             current_query_index: None,
+            outline: vec![],
+            line_count: 1,
+            show_outline: false,
+            show_minimap: false,
+            bracket_hint: None,
+            pending_newline: None,
+            snippets,
+            snippet: None,
+            last_text: String::new(),
+            selection: None,
+            statement_runs: StatementRuns::default(),
+            plan_estimates: HashMap::new(),
+            _estimate_task: None,
+            lens_scroll: ScrollHandle::new(),
             inline_completions_enabled: false,
             code_actions_loading: false,
             inline_completions_loading: false,
+            agent_context: None,
+            file_path: None,
+            saved_text: String::new(),
+            dirty: false,
+            large_text: false,
+        }
+    }
+
+    /// Give the agent providers the indexed schema and redaction settings of
+    /// the active connection, leaving out what its privacy settings exclude.
+    fn update_agent_context(&mut self, cx: &mut Context<Self>) {
+        let privacy = AgentPrivacyState::active(self.window_id, cx);
+        if let Some(schema) = EditorState::of(self.window_id, cx).schema.as_ref() {
+            let context = schema_context_for_llm(schema, &privacy);
+            self.completion_provider.set_schema_context(context.clone());
+            self.code_action_provider.set_schema_context(context);
         }
+        self.completion_provider.set_privacy(privacy.clone());
+        self.code_action_provider.set_privacy(privacy);
+    }
+
+    /// Suggest the active connection's past queries as ghost text.
+    fn load_history_suggestions(&mut self, cx: &mut Context<Self>) {
+        let provider = self.completion_provider.clone();
+        let Some(connection_id) = self.active_connection.as_ref().map(|c| c.id) else {
+            provider.set_history_queries(vec![]);
+            return;
+        };
+
+        cx.spawn(async move |_this, _cx| {
+            let result = async {
+                let store = AppStore::singleton().await?;
+                store
+                    .history()
+                    .successful_queries(&connection_id, HISTORY_SUGGESTION_LIMIT)
+                    .await
+            }
+            .await;
+            match result {
+                Ok(queries) => provider.set_history_queries(queries),
+                Err(e) => tracing::warn!("Failed to load history suggestions: {}", e),
+            }
+        })
+        .detach();
+    }
+
+    /// Index the active connection's history for the statement lens.
+    fn load_statement_runs(&mut self, cx: &mut Context<Self>) {
+        self.statement_runs = StatementRuns::default();
+        let Some(connection_id) = self.active_connection.as_ref().map(|c| c.id) else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                let store = AppStore::singleton().await?;
+                store
+                    .history()
+                    .load_for_connection(&connection_id, STATEMENT_RUN_HISTORY_LIMIT)
+                    .await
+            }
+            .await;
+            match result {
+                Ok(entries) => {
+                    let _ = this.update(cx, |this, cx| {
+                        this.statement_runs = StatementRuns::new(&entries);
+                        cx.notify();
+                    });
+                }
+                Err(e) => tracing::warn!("Failed to load statement runs: {}", e),
+            }
+        })
+        .detach();
+    }
+
+    /// Show how `sql` just went in the lens of its statement.
+    pub fn record_run(&mut self, sql: &str, run: StatementRun, cx: &mut Context<Self>) {
+        self.statement_runs.record(sql, run);
+        cx.notify();
+    }
+
+    /// Ask the server for the plan of the statement at the cursor once the
+    /// cursor settles there, for its lens. Only statements that read or
+    /// change rows are explained, and each text only once.
+    fn estimate_current_statement(&mut self, cx: &mut Context<Self>) {
+        let Some(ix) = self.current_query_index else {
+            return;
+        };
+        let explainable = self.outline.get(ix).is_some_and(|entry| {
+            matches!(
+                entry.kind.as_str(),
+                "SELECT" | "WITH" | "VALUES" | "TABLE" | "INSERT" | "UPDATE" | "DELETE" | "MERGE"
+            )
+        });
+        let sql = self.parsed_queries[ix].query_text.clone();
+        if !explainable || self.plan_estimates.contains_key(&sql) {
+            return;
+        }
+        let state = WindowConnection::of(self.window_id, cx);
+        if state.active_connection.is_none() {
+            return;
+        }
+        let db_manager = state.db_manager.clone();
+
+        self._estimate_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(Duration::from_millis(600))
+                .await;
+            let estimate = match db_manager.estimate_plan(&sql).await {
+                Ok(estimate) => Some(estimate),
+                Err(e) => {
+                    tracing::debug!("No plan for the statement at the cursor: {}", e);
+                    None
+                }
+            };
+            let _ = this.update(cx, |this, cx| {
+                this.plan_estimates.insert(sql, estimate);
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Offer `sql` as a suggestion after it ran successfully.
+    pub fn remember_query(&self, sql: &str) {
+        self.completion_provider.remember_query(sql);
     }
 
     fn find_query_at_cursor(&self, cursor_offset: usize) -> Option<usize> {
@@ -170,10 +677,313 @@ impl Editor {
             .position(|q| cursor_offset >= q.start_byte && cursor_offset <= q.end_byte)
     }
 
+    /// Track the selection and the statement under the cursor, which the
+    /// toolbar and the lens show as what will run.
+    fn update_run_target(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let input = self.input_state.read(cx);
+        let cursor = input.cursor();
+        let text = input.value().to_string();
+        let index = self.find_query_at_cursor(cursor);
+        let selection = self.selected_text(window, cx);
+
+        // The input can't highlight the matching bracket in place, so the
+        // lens says where it is
+        let bracket_hint = matching_bracket(&self.driver(), &text, cursor).map(|found| {
+            let line = |offset: usize| text[..offset].matches('\n').count() + 1;
+            let bracket = &text[found.bracket..=found.bracket];
+            match found.matching {
+                Some(other) => (
+                    format!(
+                        "{} line {} matches {} line {}",
+                        bracket,
+                        line(found.bracket),
+                        &text[other..=other],
+                        line(other)
+                    ),
+                    false,
+                ),
+                None => (
+                    format!("Unmatched {} on line {}", bracket, line(found.bracket)),
+                    true,
+                ),
+            }
+        });
+
+        if index != self.current_query_index
+            || selection != self.selection
+            || bracket_hint != self.bracket_hint
+        {
+            if index != self.current_query_index
+                && let Some(ix) = index
+            {
+                self.lens_scroll.scroll_to_item(ix);
+            }
+            self.current_query_index = index;
+            self.selection = selection;
+            self.bracket_hint = bracket_hint;
+            self.estimate_current_statement(cx);
+            cx.notify();
+        }
+    }
+
+    /// The driver whose quoting rules apply to the editor contents.
+    fn driver(&self) -> DatabaseDriver {
+        self.active_connection
+            .as_ref()
+            .map_or(DatabaseDriver::Postgres, |connection| connection.driver)
+    }
+
+    /// Replace `edit.range` and move the cursor where the edit leaves it.
+    fn apply_pair_edit(&mut self, edit: PairEdit, window: &mut Window, cx: &mut Context<Self>) {
+        self.input_state.update(cx, |input, cx| {
+            if !edit.range.is_empty() || !edit.text.is_empty() {
+                let text = input.text();
+                let range = lsp_types::Range::new(
+                    text.offset_to_position(edit.range.start),
+                    text.offset_to_position(edit.range.end),
+                );
+                input.apply_lsp_edits(
+                    &vec![lsp_types::TextEdit {
+                        range,
+                        new_text: edit.text,
+                        ..Default::default()
+                    }],
+                    window,
+                    cx,
+                );
+            }
+            let position = input.text().offset_to_position(edit.cursor);
+            input.set_cursor_position(position, window, cx);
+        });
+        self.follow_snippet(cx);
+    }
+
+    /// Move the active snippet's tab stops along with the latest change,
+    /// ending the snippet when the change is outside it. The contents
+    /// before and after, when they changed.
+    fn follow_snippet(&mut self, cx: &App) -> Option<(String, String)> {
+        let text = self.query_text(cx);
+        if text == self.last_text {
+            return None;
+        }
+        let before = std::mem::replace(&mut self.last_text, text.clone());
+        if let Some(session) = self.snippet.as_mut() {
+            let (range, inserted) = edit_between(&before, &text);
+            if !session.edited(range, inserted) {
+                self.snippet = None;
+            }
+        }
+        Some((before, text))
+    }
+
+    /// Go to the first tab stop of a snippet just picked from the
+    /// completions, which insert its text only.
+    fn start_completed_snippet(
+        &mut self,
+        before: &str,
+        after: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((start, expansion)) = inserted_snippet(&self.snippets, before, after) else {
+            return;
+        };
+        let session = SnippetSession::new(start, &expansion);
+        let cursor = session.cursor();
+        self.snippet = (!session.is_done()).then_some(session);
+        self.jump_to(cursor, window, cx);
+    }
+
+    /// Move to the next tab stop of the snippet being filled in, else
+    /// expand the snippet prefix before the cursor, unless Tab accepts a
+    /// history suggestion there. Other Tabs indent.
+    fn on_tab(&mut self, _: &input::IndentInline, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_text(window, cx).is_some() {
+            return;
+        }
+        let input = self.input_state.read(cx);
+        let (sql, offset) = (input.value().to_string(), input.cursor());
+        let suggested = self
+            .completion_provider
+            .history_completion(&input.text(), offset)
+            .is_some();
+        if let Some(session) = self.snippet.as_mut()
+            && session.contains(offset)
+        {
+            cx.stop_propagation();
+            let cursor = session.next_stop();
+            if session.is_done() {
+                self.snippet = None;
+            }
+            if let Some(cursor) = cursor {
+                self.jump_to(cursor, window, cx);
+            }
+            return;
+        }
+        self.snippet = None;
+
+        if suggested || in_literal(&self.driver(), &sql, offset) {
+            return;
+        }
+        let Some((start, snippet)) = snippet_at(&self.snippets, &sql, offset) else {
+            return;
+        };
+        cx.stop_propagation();
+        let expansion = expand_snippet(&snippet.body);
+        let session = SnippetSession::new(start, &expansion);
+        self.apply_pair_edit(
+            PairEdit {
+                range: start..offset,
+                text: expansion.text,
+                cursor: session.cursor(),
+            },
+            window,
+            cx,
+        );
+        self.snippet = (!session.is_done()).then_some(session);
+    }
+
+    /// Move back to the previous tab stop of the snippet being filled in.
+    fn on_shift_tab(
+        &mut self,
+        _: &input::OutdentInline,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let offset = self.input_state.read(cx).cursor();
+        if let Some(session) = self.snippet.as_mut()
+            && session.contains(offset)
+            && let Some(cursor) = session.previous_stop()
+        {
+            cx.stop_propagation();
+            self.jump_to(cursor, window, cx);
+        }
+    }
+
+    /// Close brackets and quotes as they are typed, step over closing ones
+    /// and double quotes inside strings. Other keys go to the input.
+    fn on_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.control || keystroke.modifiers.platform {
+            return;
+        }
+        let Some(typed) = keystroke.key_char.as_deref().and_then(|key| {
+            let mut chars = key.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        }) else {
+            return;
+        };
+        if !"()[]'\"$".contains(typed) || self.selected_text(window, cx).is_some() {
+            return;
+        }
+        let input = self.input_state.read(cx);
+        let (sql, offset) = (input.value().to_string(), input.cursor());
+        let Some(edit) = typed_pair(&self.driver(), &sql, offset, typed) else {
+            return;
+        };
+        // When the edit opens a pair, only its closing half goes in here.
+        // The input types the opening one itself, so that it still offers
+        // the completions that character triggers, like enum labels
+        if edit.range == (offset..offset)
+            && edit.cursor == offset + typed.len_utf8()
+            && let Some(closing) = edit.text.strip_prefix(typed).filter(|c| !c.is_empty())
+        {
+            let closing = PairEdit {
+                range: offset..offset,
+                text: closing.to_string(),
+                cursor: offset,
+            };
+            self.apply_pair_edit(closing, window, cx);
+            return;
+        }
+        cx.stop_propagation();
+        self.apply_pair_edit(edit, window, cx);
+    }
+
+    /// Delete an empty pair of brackets or quotes as a whole.
+    fn on_backspace(&mut self, _: &input::Backspace, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_text(window, cx).is_some() {
+            return;
+        }
+        let input = self.input_state.read(cx);
+        let (sql, offset) = (input.value().to_string(), input.cursor());
+        if let Some(range) = deleted_pair(&self.driver(), &sql, offset) {
+            cx.stop_propagation();
+            let cursor = range.start;
+            self.apply_pair_edit(
+                PairEdit {
+                    range,
+                    text: String::new(),
+                    cursor,
+                },
+                window,
+                cx,
+            );
+        }
+    }
+
+    /// Note where Enter was pressed, leaving the key to the input, which
+    /// may be accepting a completion rather than breaking the line.
+    fn on_enter(&mut self, _: &input::Enter, _window: &mut Window, cx: &mut Context<Self>) {
+        let input = self.input_state.read(cx);
+        self.pending_newline = Some((input.value().to_string(), input.cursor()));
+    }
+
+    /// Re-indent the line Enter just broke inside parentheses.
+    fn indent_new_line(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((before, offset)) = self.pending_newline.take() else {
+            return;
+        };
+        let after = self.input_state.read(cx).value().to_string();
+        let Some(inserted) = after
+            .strip_prefix(&before[..offset])
+            .and_then(|rest| rest.strip_suffix(&before[offset..]))
+        else {
+            return;
+        };
+        let plain_break = inserted
+            .strip_prefix('\n')
+            .is_some_and(|indent| indent.chars().all(|c| c == ' ' || c == '\t'));
+        if !plain_break {
+            return;
+        }
+        if let Some(edit) = newline_indent(&self.driver(), &before, offset, INDENT) {
+            // The same edit, over the line break and indentation already in
+            self.apply_pair_edit(
+                PairEdit {
+                    range: offset..edit.range.end + inserted.len(),
+                    text: edit.text,
+                    cursor: edit.cursor,
+                },
+                window,
+                cx,
+            );
+        }
+    }
+
+    /// The selected text, unless nothing but whitespace is selected.
+    fn selected_text(&self, window: &mut Window, cx: &mut App) -> Option<String> {
+        self.input_state
+            .update(cx, |input, cx| {
+                let selection = input.selected_text_range(false, window, cx)?;
+                if selection.range.is_empty() {
+                    return None;
+                }
+                input.text_for_range(selection.range, &mut None, window, cx)
+            })
+            .filter(|text| !text.trim().is_empty())
+    }
+
     fn reparse_queries(&mut self, cx: &mut Context<Self>) {
         let content = self.input_state.read(cx).value().to_string();
 
         self.parsed_queries = self.analyzer.detect_queries(&content);
+        self.outline = statement_outline(&self.parsed_queries);
+        self.line_count = content.lines().count().max(1);
+        let cursor = self.input_state.read(cx).cursor();
+        self.current_query_index = self.find_query_at_cursor(cursor);
+        self.estimate_current_statement(cx);
+        cx.notify();
 
         tracing::debug!(
             "Query {} of {}",
@@ -182,6 +992,16 @@ impl Editor {
         );
     }
 
+    /// Move the cursor to `offset` and focus the editor, scrolling there.
+    fn jump_to(&mut self, offset: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.input_state.update(cx, |input, cx| {
+            let position = input.text().offset_to_position(offset);
+            input.set_cursor_position(position, window, cx);
+            input.focus(window, cx);
+        });
+        self.update_run_target(window, cx);
+    }
+
     pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
         cx.new(|cx| Self::new(window, cx))
     }
@@ -196,7 +1016,27 @@ impl Editor {
         match event {
             SelectEvent::Confirm(value) => {
                 if let Some(database) = value {
-                    change_database(database.to_string(), cx)
+                    change_database(self.window_id, database.to_string(), cx)
+                }
+            }
+        }
+    }
+
+    fn on_select_schema_event(
+        &mut self,
+        _: &Entity<SelectState<Vec<SharedString>>>,
+        event: &SelectEvent<Vec<SharedString>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            SelectEvent::Confirm(value) => {
+                let Some(schema) = value else {
+                    return;
+                };
+                let schema = schema.to_string();
+                if self.search_path.first() != Some(&schema) {
+                    set_active_schema(self.window_id, schema, cx);
                 }
             }
         }
@@ -229,7 +1069,14 @@ impl Editor {
         })
     }
 
-    pub fn execute_query(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+    /// Run the selection when there is one, else the statement at the
+    /// cursor, else the whole script.
+    pub fn execute_query(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(selection) = self.selected_text(window, cx) {
+            cx.emit(EditorEvent::ExecuteQuery(selection));
+            return;
+        }
+
         let cursor = self.input_state.read(cx).cursor();
         self.current_query_index = self.find_query_at_cursor(cursor);
 
@@ -249,10 +1096,362 @@ impl Editor {
         }
     }
 
+    /// Run only the selected text.
+    pub fn run_selection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match self.selected_text(window, cx) {
+            Some(selection) => cx.emit(EditorEvent::ExecuteQuery(selection)),
+            None => window.push_toast("Select the SQL to run first", cx),
+        }
+    }
+
+    /// Run only the statement under the cursor, or the only statement.
+    pub fn run_statement(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let cursor = self.input_state.read(cx).cursor();
+        self.current_query_index = self.find_query_at_cursor(cursor);
+        let index = self
+            .current_query_index
+            .or((self.parsed_queries.len() == 1).then_some(0));
+        match index.and_then(|ix| self.parsed_queries.get(ix)) {
+            Some(query) => cx.emit(EditorEvent::ExecuteQuery(query.query_text.clone())),
+            None => window.push_toast("Place the cursor in a statement to run it", cx),
+        }
+    }
+
+    /// Run the whole editor contents, statement by statement.
+    pub fn run_script(&mut self, cx: &mut Context<Self>) {
+        let script = self.query_text(cx);
+        if !script.trim().is_empty() {
+            cx.emit(EditorEvent::ExecuteQuery(script));
+        }
+    }
+
+    /// Run the selection, or else the whole script, on other targets.
+    pub fn run_on_targets(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let script = self
+            .selected_text(window, cx)
+            .unwrap_or_else(|| self.query_text(cx));
+        if script.trim().is_empty() {
+            window.push_toast("Write the script to run first", cx);
+            return;
+        }
+        cx.emit(EditorEvent::RunOnTargets(script));
+    }
+
     pub fn set_executing(&mut self, executing: bool, cx: &mut Context<Self>) {
         self.is_executing = executing;
         cx.notify();
     }
+
+    pub fn set_large_text(&mut self, large_text: bool, cx: &mut Context<Self>) {
+        self.large_text = large_text;
+        cx.notify();
+    }
+
+    /// Pick a `.sql` file and open it in the editor.
+    pub fn open_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Open".into()),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = this.update_in(cx, |this, window, cx| this.open_path(path, window, cx));
+        })
+        .detach();
+    }
+
+    /// The `.sql` file being edited, if any.
+    pub fn file_path(&self) -> Option<&PathBuf> {
+        self.file_path.as_ref()
+    }
+
+    /// Show `sql` as the contents of `path`, as a restored workspace left
+    /// them: changes that were not saved to the file stay unsaved.
+    pub fn restore_file(
+        &mut self,
+        path: PathBuf,
+        sql: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn_in(window, async move |this, cx| {
+            let saved = async_fs::read_to_string(&path).await.unwrap_or_default();
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.dirty = sql != saved;
+                this.saved_text = saved;
+                this.file_path = Some(path);
+                this.set_query(sql, window, cx);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Open `path`, asking first when there are unsaved changes.
+    pub fn open_path(&mut self, path: PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.dirty {
+            self.load_file(path, window, cx);
+            return;
+        }
+
+        let editor = cx.entity().downgrade();
+        let title = file_title(self.file_path.as_deref(), false);
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let editor = editor.clone();
+            let path = path.clone();
+            dialog
+                .title("Unsaved Changes")
+                .child(format!("Discard the unsaved changes to {}?", title))
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Discard"))
+                .on_ok(move |_, window, cx| {
+                    let _ = editor.update(cx, |editor, cx| {
+                        editor.load_file(path.clone(), window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn load_file(&mut self, path: PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let contents = async_fs::read_to_string(&path).await;
+
+            let _ = this.update_in(cx, |this, window, cx| match contents {
+                Ok(contents) => {
+                    this.saved_text = contents.clone();
+                    this.file_path = Some(path.clone());
+                    this.dirty = false;
+                    this.set_query(contents, window, cx);
+                    add_recent_file(path, cx);
+                    cx.notify();
+                }
+                Err(e) => {
+                    let message: SharedString =
+                        format!("Failed to open {}: {}", path.display(), e).into();
+                    window.push_recorded_notification((NotificationType::Error, message), cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Save to the current file, or ask for one when there is none yet.
+    pub fn save_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match self.file_path.clone() {
+            Some(path) => self.write_file(path, window, cx),
+            None => self.save_file_as(window, cx),
+        }
+    }
+
+    pub fn save_file_as(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let directory = self
+            .file_path
+            .as_ref()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+            .or_else(dirs::home_dir)
+            .unwrap_or_default();
+        let suggested_name = self
+            .file_path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "query.sql".to_string());
+        let receiver = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+
+        cx.spawn_in(window, async move |this, cx| {
+            if let Ok(Ok(Some(path))) = receiver.await {
+                let _ = this.update_in(cx, |this, window, cx| {
+                    this.write_file(with_sql_extension(path), window, cx);
+                });
+            }
+        })
+        .detach();
+    }
+
+    fn write_file(&mut self, path: PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        let contents = self.query_text(cx);
+
+        cx.spawn_in(window, async move |this, cx| {
+            let written = async_fs::write(&path, contents.as_bytes()).await;
+
+            let _ = this.update_in(cx, |this, window, cx| match written {
+                Ok(()) => {
+                    this.dirty = this.query_text(cx) != contents;
+                    this.saved_text = contents;
+                    this.file_path = Some(path.clone());
+                    add_recent_file(path, cx);
+                    cx.notify();
+                }
+                Err(e) => {
+                    let message: SharedString =
+                        format!("Failed to save {}: {}", path.display(), e).into();
+                    window.push_recorded_notification((NotificationType::Error, message), cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// List the recently opened files to pick one.
+    pub fn open_recent_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let recent = cx.global::<FilesState>().recent.clone();
+        if recent.is_empty() {
+            window.push_toast((NotificationType::Info, "No recent files"), cx);
+            return;
+        }
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let items = recent.iter().enumerate().map(|(ix, path)| {
+                let open = path.clone();
+                Button::new(("recent-file", ix))
+                    .label(path.display().to_string())
+                    .ghost()
+                    .small()
+                    .w_full()
+                    .on_click(move |_, window, cx| {
+                        window.close_dialog(cx);
+                        open_file(open.clone(), cx);
+                    })
+            });
+            dialog
+                .title("Open Recent")
+                .width(px(560.))
+                .child(v_flex().gap_1().children(items))
+        });
+    }
+}
+
+fn category_color(category: StatementCategory, cx: &App) -> Hsla {
+    match category {
+        StatementCategory::Query => cx.theme().info,
+        StatementCategory::Modification => cx.theme().warning,
+        StatementCategory::Definition => cx.theme().success,
+        StatementCategory::Transaction => cx.theme().primary,
+        StatementCategory::Other => cx.theme().muted_foreground,
+    }
+}
+
+impl Editor {
+    /// The statements of the script, each jumping the editor to it.
+    fn render_outline(&self, cx: &Context<Self>) -> impl IntoElement {
+        let entries = self.outline.iter().enumerate().map(|(ix, entry)| {
+            let offset = entry.start_byte;
+            h_flex()
+                .id(("outline-entry", ix))
+                .w_full()
+                .gap_2()
+                .px_2()
+                .py_0p5()
+                .items_center()
+                .cursor_pointer()
+                .when(self.current_query_index == Some(ix), |d| {
+                    d.bg(cx.theme().list_active)
+                })
+                .hover(|s| s.bg(cx.theme().secondary))
+                .child(
+                    Label::new(entry.kind.clone())
+                        .text_xs()
+                        .flex_shrink_0()
+                        .text_color(category_color(entry.category, cx)),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w_0()
+                        .overflow_hidden()
+                        .whitespace_nowrap()
+                        .text_ellipsis()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(entry.summary.clone()),
+                )
+                .child(
+                    Label::new((entry.start_line + 1).to_string())
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                )
+                .tooltip({
+                    let summary = entry.summary.clone();
+                    move |window, cx| Tooltip::new(summary.clone()).build(window, cx)
+                })
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    this.jump_to(offset, window, cx);
+                }))
+        });
+
+        v_flex()
+            .w(px(260.))
+            .h_full()
+            .flex_shrink_0()
+            .border_l_1()
+            .border_color(cx.theme().border)
+            .child(
+                Label::new(match self.outline.len() {
+                    0 => "No statements".to_string(),
+                    1 => "1 statement".to_string(),
+                    n => format!("{} statements", n),
+                })
+                .px_2()
+                .py_1()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+            )
+            .child(
+                div()
+                    .id("statement-outline")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .children(entries),
+            )
+    }
+
+    /// A strip with a block per statement, placed by its lines within the
+    /// script, the one under the cursor drawn solid.
+    fn render_minimap(&self, cx: &Context<Self>) -> impl IntoElement {
+        let lines = self.line_count as f32;
+        let blocks = self.outline.iter().enumerate().map(|(ix, entry)| {
+            let offset = entry.start_byte;
+            let span = (entry.end_line.saturating_sub(entry.start_line) + 1) as f32;
+            let color = category_color(entry.category, cx);
+            div()
+                .id(("minimap-statement", ix))
+                .absolute()
+                .left_0()
+                .right_0()
+                .top(relative(entry.start_line as f32 / lines))
+                .h(relative(span / lines))
+                .min_h(px(2.))
+                .rounded_sm()
+                .cursor_pointer()
+                .bg(if self.current_query_index == Some(ix) {
+                    color
+                } else {
+                    color.opacity(0.35)
+                })
+                .hover(move |s| s.bg(color))
+                .on_click(cx.listener(move |this, _, window, cx| {
+                    this.jump_to(offset, window, cx);
+                }))
+        });
+
+        div()
+            .relative()
+            .w(px(10.))
+            .h_full()
+            .flex_shrink_0()
+            .ml_1()
+            .children(blocks)
+    }
 }
 
 impl Render for Editor {
@@ -268,21 +1467,232 @@ impl Render for Editor {
             .danger()
             .ghost()
             .tooltip("Disconnect")
-            .on_click(|_evt, _win, cx| disconnect(cx));
+            .on_click(|_evt, win, cx| disconnect(window_id(win), cx));
+
+        // What the execute button runs right now
+        let run_target = if let Some(selection) = &self.selection {
+            let lines = selection.trim().lines().count();
+            format!(
+                "Selection · {} line{}",
+                lines,
+                if lines == 1 { "" } else { "s" }
+            )
+        } else if let Some(ix) = self.current_query_index {
+            format!("Statement {} of {}", ix + 1, self.parsed_queries.len())
+        } else if self.parsed_queries.len() > 1 {
+            format!("Script · {} statements", self.parsed_queries.len())
+        } else {
+            "Statement".to_string()
+        };
+        let run_target_label =
+            Label::new(run_target.clone())
+                .text_xs()
+                .text_color(if self.selection.is_some() {
+                    cx.theme().accent_foreground
+                } else {
+                    cx.theme().muted_foreground
+                });
+
+        // Commands the open connection runs around each query
+        let hooks = HooksState::active(self.window_id, cx);
+        let hooks_label = hooks.summary().map(|summary| {
+            let commands: Vec<String> = [("Before", &hooks.before), ("After", &hooks.after)]
+                .into_iter()
+                .filter(|(_, hook)| hook.is_set())
+                .map(|(stage, hook)| format!("{} ({}): {}", stage, hook.kind.label(), hook.command))
+                .collect();
+            Button::new("execution-hooks")
+                .icon(Icon::empty().path("icons/square-terminal.svg"))
+                .label(summary)
+                .xsmall()
+                .ghost()
+                .tooltip(commands.join("\n"))
+        });
+
+        // Which `{{name}}` variable environment queries use
+        let variables = cx.global::<VariablesState>().variables.clone();
+        let used_variables = template_variables(&self.driver(), &self.query_text(cx));
+        let variables_menu = Button::new("query-variables")
+            .icon(Icon::empty().path("icons/file-braces.svg"))
+            .label(variables.active.clone().unwrap_or("Variables".to_string()))
+            .xsmall()
+            .ghost()
+            .tooltip("{{variable}} environment")
+            .dropdown_menu(move |menu, _window, _cx| {
+                let used_variables = used_variables.clone();
+                let menu = variables.environments.iter().fold(menu, |menu, env| {
+                    let name = env.name.clone();
+                    menu.item(
+                        PopupMenuItem::new(env.name.clone())
+                            .checked(variables.active.as_ref() == Some(&env.name))
+                            .on_click(move |_, _window, cx| {
+                                VariablesState::set_active(Some(name.clone()), cx);
+                            }),
+                    )
+                });
+                let menu = if variables.environments.is_empty() {
+                    menu
+                } else {
+                    menu.separator()
+                };
+                menu.item(
+                    PopupMenuItem::new("Edit Variables…").on_click(move |_, window, cx| {
+                        open_variables_dialog(used_variables.clone(), window, cx);
+                    }),
+                )
+            });
+
+        // Whether DDL run now is recorded into the change script
+        let recorder = cx.global::<ChangeRecorderState>();
+        let recording = recorder.recording;
+        let recorded = recorder.script.changes.len();
+        let record_menu = Button::new("record-changes")
+            .icon(Icon::empty().path("icons/circle-dot.svg"))
+            .when(recording || recorded > 0, |button| {
+                button.label(format!(
+                    "{} change{}",
+                    recorded,
+                    if recorded == 1 { "" } else { "s" }
+                ))
+            })
+            .xsmall()
+            .ghost()
+            .when(recording, |button| button.danger())
+            .tooltip(if recording {
+                "Recording DDL into the change script"
+            } else {
+                "Record changes"
+            })
+            .dropdown_menu(move |menu, _window, _cx| {
+                menu.item(
+                    PopupMenuItem::new(if recording {
+                        "Stop Recording"
+                    } else {
+                        "Start Recording"
+                    })
+                    .on_click(move |_, _window, cx| {
+                        ChangeRecorderState::set_recording(!recording, cx);
+                    }),
+                )
+                .separator()
+                .item(
+                    PopupMenuItem::new("Change Script…").on_click(|_, window, cx| {
+                        open_change_script_dialog(window, cx);
+                    }),
+                )
+                .item(
+                    PopupMenuItem::new("Clear Recorded Changes").on_click(|_, _window, cx| {
+                        ChangeRecorderState::clear(cx);
+                    }),
+                )
+            });
+
+        // Creating and dropping databases and how their tables grow; on
+        // Postgres also schemas, roles, tablespaces and the server
+        // configuration
+        let postgres = self
+            .active_connection
+            .as_ref()
+            .is_some_and(|c| c.driver == DatabaseDriver::Postgres);
+        let editor = cx.entity().downgrade();
+        let database_menu = Button::new("database-admin")
+            .icon(Icon::empty().path("icons/ellipsis.svg"))
+            .xsmall()
+            .ghost()
+            .tooltip(if postgres {
+                "Databases, Schemas, Roles, Tablespaces and Server"
+            } else {
+                "Databases"
+            })
+            .dropdown_menu(move |menu, _window, _cx| {
+                let editor = editor.clone();
+                let menu = menu
+                    .item(
+                        PopupMenuItem::new("New Database…").on_click(|_, window, cx| {
+                            open_create_database_dialog(window, cx);
+                        }),
+                    )
+                    .item(
+                        PopupMenuItem::new("Drop Database…").on_click(|_, window, cx| {
+                            open_drop_database_dialog(window, cx);
+                        }),
+                    )
+                    .item(
+                        PopupMenuItem::new("Table Growth…").on_click(|_, window, cx| {
+                            open_table_growth_dialog(window, cx);
+                        }),
+                    )
+                    .item(
+                        PopupMenuItem::new("Schema Changes…").on_click(|_, window, cx| {
+                            open_schema_changes_dialog(window, cx);
+                        }),
+                    );
+                if !postgres {
+                    return menu;
+                }
+                menu.separator()
+                    .item(PopupMenuItem::new("New Schema…").on_click(|_, window, cx| {
+                        open_create_schema_dialog(window, cx);
+                    }))
+                    .item(
+                        PopupMenuItem::new("Drop Schema…").on_click(|_, window, cx| {
+                            open_drop_schema_dialog(None, window, cx);
+                        }),
+                    )
+                    .separator()
+                    .item(PopupMenuItem::new("New Role…").on_click({
+                        let editor = editor.clone();
+                        move |_, window, cx| {
+                            open_role_dialog(false, editor.clone(), window, cx);
+                        }
+                    }))
+                    .item(
+                        PopupMenuItem::new("Alter Role…").on_click(move |_, window, cx| {
+                            open_role_dialog(true, editor.clone(), window, cx);
+                        }),
+                    )
+                    .item(
+                        PopupMenuItem::new("Reset Password…").on_click(|_, window, cx| {
+                            open_reset_password_dialog(window, cx);
+                        }),
+                    )
+                    .separator()
+                    .item(
+                        PopupMenuItem::new("Tablespaces…").on_click(|_, window, cx| {
+                            open_tablespaces_dialog(window, cx);
+                        }),
+                    )
+                    .item(
+                        PopupMenuItem::new("Server Configuration…").on_click(|_, window, cx| {
+                            open_server_settings_dialog(window, cx);
+                        }),
+                    )
+            });
 
+        // Queries run as background jobs, so another one can always start
         let execute_button = Button::new("execute-query")
             .tooltip(if self.is_executing {
-                "Executing..."
+                "Executing... (run again as a new job)".to_string()
             } else {
-                "Execute"
+                format!("Execute {}", run_target.to_lowercase())
             })
             .icon(Icon::empty().path("icons/play.svg"))
             .small()
             .primary()
             .ghost()
-            .disabled(self.is_executing)
             .on_click(cx.listener(Self::execute_query));
 
+        let run_on_targets_button = Button::new("run-on-targets")
+            .tooltip(if self.selection.is_some() {
+                "Run Selection on Several Targets…"
+            } else {
+                "Run Script on Several Targets…"
+            })
+            .icon(Icon::empty().path("icons/database-zap.svg"))
+            .small()
+            .ghost()
+            .on_click(cx.listener(|this, _, window, cx| this.run_on_targets(window, cx)));
+
         let format_button = Button::new("execute-format")
             .tooltip(if self.is_formatting {
                 "Formatting..."
@@ -296,6 +1706,73 @@ impl Render for Editor {
             .disabled(self.is_formatting)
             .on_click(cx.listener(Self::format_query));
 
+        let file_label = Label::new(file_title(self.file_path.as_deref(), self.dirty))
+            .text_xs()
+            .text_color(cx.theme().muted_foreground);
+
+        let open_button = Button::new("open-file")
+            .tooltip("Open File")
+            .icon(Icon::empty().path("icons/folder-open.svg"))
+            .small()
+            .ghost()
+            .on_click(cx.listener(|this, _, window, cx| this.open_file(window, cx)));
+
+        let recent_button = Button::new("open-recent-file")
+            .tooltip("Open Recent")
+            .icon(Icon::empty().path("icons/history.svg"))
+            .small()
+            .ghost()
+            .on_click(cx.listener(|this, _, window, cx| this.open_recent_file(window, cx)));
+
+        // The statement outline and minimap beside the editor
+        let outline_menu = Button::new("statement-outline-menu")
+            .icon(Icon::empty().path("icons/map.svg"))
+            .small()
+            .ghost()
+            .selected(self.show_outline || self.show_minimap)
+            .tooltip("Outline")
+            .dropdown_menu({
+                let editor = cx.entity().downgrade();
+                let (show_outline, show_minimap) = (self.show_outline, self.show_minimap);
+                move |menu, _window, _cx| {
+                    let (outline, minimap) = (editor.clone(), editor.clone());
+                    menu.item(
+                        PopupMenuItem::new("Statement Outline")
+                            .checked(show_outline)
+                            .on_click(move |_, _window, cx| {
+                                let _ = outline.update(cx, |editor, cx| {
+                                    editor.show_outline = !show_outline;
+                                    cx.notify();
+                                });
+                            }),
+                    )
+                    .item(
+                        PopupMenuItem::new("Minimap")
+                            .checked(show_minimap)
+                            .on_click(move |_, _window, cx| {
+                                let _ = minimap.update(cx, |editor, cx| {
+                                    editor.show_minimap = !show_minimap;
+                                    cx.notify();
+                                });
+                            }),
+                    )
+                }
+            });
+
+        let snippets_button = Button::new("sql-snippets")
+            .tooltip("Snippets")
+            .icon(Icon::empty().path("icons/zap.svg"))
+            .small()
+            .ghost()
+            .on_click(|_, window, cx| open_snippets_dialog(window, cx));
+
+        let save_button = Button::new("save-file")
+            .tooltip("Save")
+            .icon(Icon::empty().path("icons/save.svg"))
+            .small()
+            .ghost()
+            .on_click(cx.listener(|this, _, window, cx| this.save_file(window, cx)));
+
         let inline_completions_button = Button::new("inline-completions")
             .tooltip("Toggle inline assist")
             .icon(Icon::empty().path("icons/sparkles.svg"))
@@ -306,6 +1783,26 @@ impl Render for Editor {
             .disabled(self.is_formatting || self.is_executing)
             .on_click(cx.listener(Self::toggle_inline_completions));
 
+        // Which tables the latest completion or code action described
+        let agent_context_label = self.agent_context.as_ref().map(|selection| {
+            Button::new("agent-context")
+                .label(format!(
+                    "{}/{} tables",
+                    selection.tables.len(),
+                    selection.total_tables
+                ))
+                .tooltip(if selection.tables.is_empty() {
+                    "No tables matched the last AI request".to_string()
+                } else {
+                    format!(
+                        "Sent with the last AI request: {}",
+                        selection.tables.join(", ")
+                    )
+                })
+                .xsmall()
+                .ghost()
+        });
+
         let toolbar = h_flex()
             .id("editor-toolbar")
             .justify_between()
@@ -323,7 +1820,24 @@ impl Render for Editor {
                             Select::new(&self.db_select.clone())
                                 .appearance(false)
                                 .menu_width(px(200.)), // Keep menu width for longer db names
-                        ),
+                        )
+                        .child(database_menu)
+                        .when(!self.search_path.is_empty(), |el| {
+                            el.child(Icon::empty().path("icons/list-ordered.svg"))
+                                .child(
+                                    Select::new(&self.schema_select.clone())
+                                        .appearance(false)
+                                        .menu_width(px(200.)),
+                                )
+                                .child(
+                                    Label::new(format!(
+                                        "search_path: {}",
+                                        self.search_path.join(", ")
+                                    ))
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground),
+                                )
+                        }),
                 )
             })
             .when(connection_name.is_none(), |el| el.child(div()))
@@ -331,27 +1845,152 @@ impl Render for Editor {
                 h_flex()
                     .gap_1()
                     .items_center()
+                    .child(file_label)
+                    .child(open_button)
+                    .child(recent_button)
+                    .child(save_button)
+                    .child(outline_menu)
+                    .child(snippets_button)
+                    .child(Divider::vertical())
+                    .children(agent_context_label)
                     .child(inline_completions_button)
                     .child(format_button)
+                    .children(hooks_label)
+                    .child(variables_menu)
+                    .child(record_menu)
+                    .child(run_target_label)
                     .child(execute_button)
+                    .child(run_on_targets_button)
                     .child(Divider::vertical())
                     .child(disconnect_button),
             );
 
-        v_flex().size_full().child(toolbar).child(
-            div()
-                .id("editor-content")
-                .bg(cx.theme().background)
-                .w_full()
-                .flex_1()
-                .px_2()
-                .pb_2()
-                .font_family("Monaco")
-                .text_size(px(12.))
-                .child(Input::new(&self.input_state).h_full()) // Absolutely positioned loading indicator in top-right
-                .when(show_ai_loading, |d| {
-                    d.child(div().absolute().top_2().right_4().child(Spinner::new()))
-                }),
-        )
+        // CodeLens-style summary of each statement, the one under the
+        // cursor highlighted
+        let lenses = self
+            .parsed_queries
+            .iter()
+            .enumerate()
+            .map(|(ix, query)| {
+                let current = self.selection.is_none() && self.current_query_index == Some(ix);
+                let run = self.statement_runs.last_run(&query.query_text);
+                let summary = run
+                    .map(|run| run.summary(chrono::Utc::now()))
+                    .unwrap_or_else(|| "Not run yet".to_string());
+                let failed = run.is_some_and(|run| !run.success);
+                let estimate = self
+                    .plan_estimates
+                    .get(&query.query_text)
+                    .copied()
+                    .flatten()
+                    .and_then(|estimate| estimate.summary());
+                let sql = query.query_text.clone();
+                let explain = format!("EXPLAIN {}", query.query_text);
+
+                h_flex()
+                    .id(("statement-lens", ix))
+                    .px_4()
+                    .gap_2()
+                    .items_center()
+                    .when(current, |d| d.bg(cx.theme().secondary))
+                    .child(
+                        Label::new(format!("Line {}", query.start_line + 1))
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .child(Label::new(summary).text_color(if failed {
+                        cx.theme().danger
+                    } else {
+                        cx.theme().muted_foreground
+                    }))
+                    .children(estimate.map(|estimate| {
+                        Label::new(estimate).text_color(cx.theme().muted_foreground)
+                    }))
+                    .children(self.bracket_hint.clone().filter(|_| current).map(
+                        |(hint, unmatched)| {
+                            Label::new(hint).text_color(if unmatched {
+                                cx.theme().danger
+                            } else {
+                                cx.theme().muted_foreground
+                            })
+                        },
+                    ))
+                    .child(
+                        Button::new(("lens-run", ix))
+                            .label("Run")
+                            .xsmall()
+                            .ghost()
+                            .disabled(connection_name.is_none())
+                            .on_click(cx.listener(move |_, _, _, cx| {
+                                cx.emit(EditorEvent::ExecuteQuery(sql.clone()));
+                            })),
+                    )
+                    .child(
+                        Button::new(("lens-explain", ix))
+                            .label("Explain")
+                            .xsmall()
+                            .ghost()
+                            .disabled(connection_name.is_none())
+                            .on_click(cx.listener(move |_, _, _, cx| {
+                                cx.emit(EditorEvent::ExecuteQuery(explain.clone()));
+                            })),
+                    )
+            })
+            .collect::<Vec<_>>();
+        let statement_lenses = (!lenses.is_empty()).then(|| {
+            v_flex()
+                .id("statement-lenses")
+                .max_h(px(88.))
+                .overflow_y_scroll()
+                .track_scroll(&self.lens_scroll)
+                .text_xs()
+                .children(lenses)
+        });
+
+        // Theme overrides for the editor, and an accent frame while
+        // connected to a production database
+        let (editor_bg, editor_fg) = editor_colors(cx);
+        let production = WindowConnection::is_production(self.window_id, cx);
+
+        v_flex()
+            .size_full()
+            .child(toolbar)
+            .children(statement_lenses)
+            .child(
+                div()
+                    .id("editor-content")
+                    // Ahead of the input, which would paste the text as is
+                    .capture_action(cx.listener(Self::on_paste))
+                    .capture_action(cx.listener(Self::on_backspace))
+                    .capture_action(cx.listener(Self::on_enter))
+                    .capture_action(cx.listener(Self::on_tab))
+                    .capture_action(cx.listener(Self::on_shift_tab))
+                    .capture_key_down(cx.listener(Self::on_key_down))
+                    .bg(cx.theme().background)
+                    .w_full()
+                    .flex_1()
+                    .px_2()
+                    .pb_2()
+                    .font_family("Monaco")
+                    .text_size(px(if self.large_text { 16. } else { 12. }))
+                    .when(production, |d| {
+                        d.border_2().border_color(production_accent(cx))
+                    })
+                    .child(
+                        h_flex()
+                            .size_full()
+                            .child(
+                                Input::new(&self.input_state)
+                                    .h_full()
+                                    .flex_1()
+                                    .bg(editor_bg)
+                                    .text_color(editor_fg),
+                            )
+                            .when(self.show_minimap, |d| d.child(self.render_minimap(cx)))
+                            .when(self.show_outline, |d| d.child(self.render_outline(cx))),
+                    ) // Absolutely positioned loading indicator in top-right
+                    .when(show_ai_loading, |d| {
+                        d.child(div().absolute().top_2().right_4().child(busy_indicator(cx)))
+                    }),
+            )
     }
 }
@@ -0,0 +1,143 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{ActiveTheme as _, h_flex, label::Label, text::TextView, v_flex};
+
+use crate::services::agent::{LiteralPolicy, SchemaSelection};
+use crate::services::sql::{ErrorExplanation, explain_error};
+use crate::state::{AgentPrivacyState, EditorState, WindowScoped, window_id};
+use crate::themes::busy_indicator;
+use crate::workspace::agent::schema_context_for_llm;
+
+enum ExplanationState {
+    Loading,
+    Ready(ErrorExplanation),
+    Failed(String),
+}
+
+/// Dialog body asking the agent why a query failed, showing its answer and
+/// the suggested fix.
+pub struct ErrorExplanationView {
+    state: ExplanationState,
+    /// Tables described to the model along with the query.
+    selection: Option<SchemaSelection>,
+    redacted: bool,
+    _task: Task<()>,
+}
+
+impl ErrorExplanationView {
+    pub fn view(sql: String, error: String, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(sql, error, window, cx))
+    }
+
+    fn new(sql: String, error: String, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        // Only the tables the query and error mention, minus what the
+        // connection's privacy settings exclude
+        let window = window_id(window);
+        let privacy = AgentPrivacyState::active(window, cx);
+        let selection = EditorState::of(window, cx).schema.as_ref().map(|schema| {
+            schema_context_for_llm(schema, &privacy).select(&format!("{}\n{}", sql, error))
+        });
+        let schema = selection.as_ref().and_then(|s| s.text.clone());
+        let sql = privacy.redact_sql(&sql);
+        let error = privacy.redact_error(&error);
+
+        let _task = cx.spawn(async move |this, cx| {
+            let result = explain_error(&sql, &error, schema.as_deref()).await;
+            let _ = this.update(cx, |this, cx| {
+                this.state = match result {
+                    Ok(explanation) => ExplanationState::Ready(explanation),
+                    Err(e) => ExplanationState::Failed(e.to_string()),
+                };
+                cx.notify();
+            });
+        });
+
+        Self {
+            state: ExplanationState::Loading,
+            selection,
+            redacted: privacy.literals != LiteralPolicy::Keep,
+            _task,
+        }
+    }
+
+    /// The corrected query, once the agent offered one.
+    pub fn fix(&self) -> Option<String> {
+        match &self.state {
+            ExplanationState::Ready(explanation) => explanation.fix.clone(),
+            _ => None,
+        }
+    }
+}
+
+impl Render for ErrorExplanationView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let context = self
+            .selection
+            .as_ref()
+            .filter(|s| !s.tables.is_empty())
+            .map(|s| {
+                Label::new(format!(
+                    "Schema context ({} of {} tables): {}",
+                    s.tables.len(),
+                    s.total_tables,
+                    s.tables.join(", ")
+                ))
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+            });
+
+        let body = match &self.state {
+            ExplanationState::Loading => h_flex()
+                .gap_2()
+                .child(busy_indicator(cx))
+                .child(Label::new("Asking the agent...").text_sm())
+                .into_any_element(),
+            ExplanationState::Failed(message) => Label::new(format!("Error: {}", message))
+                .text_sm()
+                .text_color(cx.theme().danger)
+                .into_any_element(),
+            ExplanationState::Ready(explanation) => {
+                let fix = explanation
+                    .fix
+                    .as_ref()
+                    .map(|fix| format!("```sql\n{}\n```", fix));
+                v_flex()
+                    .gap_2()
+                    .child(TextView::markdown(
+                        "error-explanation",
+                        explanation.explanation.clone(),
+                        window,
+                        cx,
+                    ))
+                    .when_some(fix, |d, fix| {
+                        d.child(TextView::markdown("error-fix", fix, window, cx))
+                    })
+                    .when(explanation.fix.is_none(), |d| {
+                        d.child(
+                            Label::new("The agent did not suggest a fix.")
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    })
+                    .when(self.redacted && explanation.fix.is_some(), |d| {
+                        d.child(
+                            Label::new(
+                                "Literal values were redacted; check the fix before running it.",
+                            )
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                        )
+                    })
+                    .into_any_element()
+            }
+        };
+
+        v_flex()
+            .id("error-explanation-body")
+            .gap_2()
+            .max_h(px(480.))
+            .overflow_y_scroll()
+            .children(context)
+            .child(body)
+    }
+}
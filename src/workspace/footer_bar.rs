@@ -2,16 +2,55 @@ use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use gpui_component::button::{Button, ButtonVariants as _};
 use gpui_component::label::Label;
+use gpui_component::tooltip::Tooltip;
 use gpui_component::{ActiveTheme, Icon, IconName, Selectable as _, Sizable as _};
+use serde::{Deserialize, Serialize};
 
-use crate::services::ConnectionInfo;
-use crate::state::{ConnectionState, ConnectionStatus};
+use crate::services::i18n::tr;
+use crate::services::{
+    ConnectionInfo, HealthLevel, QueryExecutionResult, TunnelStatus, format_row_count,
+};
+use crate::state::{
+    ConnectionStatus, HealthState, JobsState, NotificationsState, PerWindow, UpdateState,
+    UpdateStatus, WindowConnection, WindowScoped, window_id,
+};
+
+/// Height of the latency sparkline's tallest bar.
+const SPARKLINE_HEIGHT: f32 = 12.;
+
+fn format_latency(latency: std::time::Duration) -> String {
+    format!("{} ms", latency.as_millis())
+}
+
+/// Where the results panel sits relative to the editor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResultsOrientation {
+    #[default]
+    Below,
+    Right,
+}
+
+impl ResultsOrientation {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Below => Self::Right,
+            Self::Right => Self::Below,
+        }
+    }
+}
 
 pub struct FooterBar {
+    window_id: WindowId,
     active_connection: Option<ConnectionInfo>,
     tables_active: bool,
+    results_orientation: ResultsOrientation,
     agent_active: bool,
     history_active: bool,
+    jobs_active: bool,
+    /// Running plus finished-but-unseen jobs, shown as a badge
+    jobs_badge: usize,
+    /// Notifications not yet seen in the notification center
+    unread_notifications: usize,
     is_connected: bool,
     _subscriptions: Vec<Subscription>,
 }
@@ -20,24 +59,52 @@ pub enum FooterBarEvent {
     ToggleTables(bool), // true = show
     ToggleAgent(bool),
     ToggleHistory(bool),
+    ToggleJobs(bool),
+    SetResultsOrientation(ResultsOrientation),
+    OpenPerformance,
+    OpenLocks,
+    OpenActivity,
+    OpenMigrations,
+    SaveWorkspace,
+    EnterZenMode,
+    OpenNotifications,
+    OpenUpdates,
 }
 
 impl EventEmitter<FooterBarEvent> for FooterBar {}
 
 impl FooterBar {
-    pub fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let _subscriptions = vec![cx.observe_global::<ConnectionState>(move |this, cx| {
-            let state = cx.global::<ConnectionState>();
-            this.is_connected = state.connection_state.clone() == ConnectionStatus::Connected;
-            this.active_connection = state.active_connection.clone();
-            cx.notify();
-        })];
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let _subscriptions = vec![
+            cx.observe_global::<PerWindow<WindowConnection>>(move |this, cx| {
+                let state = WindowConnection::of(this.window_id, cx);
+                this.is_connected = state.connection_state.clone() == ConnectionStatus::Connected;
+                this.active_connection = state.active_connection.clone();
+                cx.notify();
+            }),
+            cx.observe_global::<PerWindow<HealthState>>(|_, cx| cx.notify()),
+            cx.observe_global::<PerWindow<JobsState>>(move |this, cx| {
+                let state = JobsState::of(this.window_id, cx);
+                this.jobs_badge = state.running_count() + state.unseen_count();
+                cx.notify();
+            }),
+            cx.observe_global::<NotificationsState>(move |this, cx| {
+                this.unread_notifications = cx.global::<NotificationsState>().unread_count();
+                cx.notify();
+            }),
+            cx.observe_global::<UpdateState>(|_, cx| cx.notify()),
+        ];
 
         Self {
+            window_id: window_id(window),
             active_connection: None,
             tables_active: true,
+            results_orientation: ResultsOrientation::default(),
             agent_active: false,
             history_active: false,
+            jobs_active: false,
+            jobs_badge: 0,
+            unread_notifications: 0,
             is_connected: false,
             _subscriptions,
         }
@@ -45,6 +112,270 @@ impl FooterBar {
     pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
         cx.new(|cx| Self::new(window, cx))
     }
+
+    pub fn set_results_orientation(
+        &mut self,
+        orientation: ResultsOrientation,
+        cx: &mut Context<Self>,
+    ) {
+        self.results_orientation = orientation;
+        cx.notify();
+    }
+
+    /// Sync the toggle buttons with panels shown by the workspace itself,
+    /// e.g. when a named workspace is restored.
+    pub fn set_panels(
+        &mut self,
+        tables: bool,
+        agent: bool,
+        history: bool,
+        jobs: bool,
+        cx: &mut Context<Self>,
+    ) {
+        self.tables_active = tables;
+        self.agent_active = agent;
+        self.history_active = history;
+        self.jobs_active = jobs;
+        cx.notify();
+    }
+}
+
+/// A clickable piece of the status bar.
+fn status_segment(id: &'static str, cx: &Context<FooterBar>) -> Stateful<Div> {
+    let hover_bg = cx.theme().list_active;
+    div()
+        .id(id)
+        .flex()
+        .items_center()
+        .gap_1()
+        .px_1p5()
+        .rounded_sm()
+        .cursor_pointer()
+        .hover(move |s| s.bg(hover_bg))
+}
+
+impl FooterBar {
+    /// Show the jobs panel in place of the agent and history panels.
+    fn show_jobs(&mut self, cx: &mut Context<Self>) {
+        self.jobs_active = true;
+        cx.emit(FooterBarEvent::ToggleJobs(true));
+        self.agent_active = false;
+        cx.emit(FooterBarEvent::ToggleAgent(false));
+        self.history_active = false;
+        cx.emit(FooterBarEvent::ToggleHistory(false));
+        cx.notify();
+    }
+
+    /// Connected server and database, transaction state and the last
+    /// query's duration and row count, around the connection health.
+    fn render_status(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let muted = theme.muted_foreground;
+        let jobs = JobsState::of(self.window_id, cx);
+
+        let server = self.active_connection.as_ref().map(|connection| {
+            let details = format!(
+                "{}@{}:{}/{}",
+                connection.username, connection.hostname, connection.port, connection.database
+            );
+            status_segment("status-server", cx)
+                .child(
+                    Icon::empty()
+                        .path("icons/database.svg")
+                        .size_3()
+                        .text_color(muted),
+                )
+                .child(
+                    Label::new(format!("{} · {}", connection.name, connection.database)).text_xs(),
+                )
+                .tooltip(move |window, cx| Tooltip::new(details.clone()).build(window, cx))
+                .on_click(cx.listener(|this, _evt, _win, cx| {
+                    this.tables_active = true;
+                    cx.emit(FooterBarEvent::ToggleTables(true));
+                    cx.notify();
+                }))
+        });
+
+        let (transaction, transaction_color, transaction_details) =
+            if WindowConnection::of(self.window_id, cx).transaction_open {
+                (
+                    "Transaction open",
+                    theme.warning,
+                    "A BEGIN has not been committed or rolled back yet",
+                )
+            } else {
+                (
+                    "Autocommit",
+                    muted,
+                    "No transaction left open by your queries",
+                )
+            };
+        let transaction = status_segment("status-transaction", cx)
+            .child(
+                Label::new(transaction)
+                    .text_xs()
+                    .text_color(transaction_color),
+            )
+            .tooltip(move |window, cx| Tooltip::new(transaction_details).build(window, cx))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::OpenActivity);
+            }));
+
+        let last_query = jobs.last_finished().map(|job| {
+            let rows = match &job.result {
+                Some(QueryExecutionResult::Select(result)) => Some(format!(
+                    "{} rows",
+                    format_row_count(result.row_count as u64)
+                )),
+                Some(QueryExecutionResult::Modified(result)) => Some(format!(
+                    "{} affected",
+                    format_row_count(result.rows_affected)
+                )),
+                Some(QueryExecutionResult::Error(_)) => Some("failed".to_string()),
+                None => None,
+            };
+            let summary = job
+                .execution_time_ms
+                .map(|ms| format!("{}ms", ms))
+                .into_iter()
+                .chain(rows)
+                .collect::<Vec<_>>()
+                .join(" · ");
+            let details = format!("Last query: {}", job.sql.trim());
+            status_segment("status-last-query", cx)
+                .child(Label::new(summary).text_xs().text_color(muted))
+                .tooltip(move |window, cx| Tooltip::new(details.clone()).build(window, cx))
+                .on_click(cx.listener(|this, _evt, _win, cx| this.show_jobs(cx)))
+        });
+
+        div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap_2()
+            .children(server)
+            .child(self.render_health(cx))
+            .child(transaction)
+            .children(last_query)
+    }
+
+    /// The app update status, hidden while there is nothing to tell.
+    fn render_update_status(&self, cx: &Context<Self>) -> Option<impl IntoElement> {
+        let theme = cx.theme();
+        let (label, color) = match &cx.global::<UpdateState>().status {
+            UpdateStatus::Idle | UpdateStatus::UpToDate => return None,
+            UpdateStatus::Checking => ("Checking for updates…".to_string(), theme.muted_foreground),
+            UpdateStatus::Available(info) => {
+                (format!("Update {}", info.latest_version), theme.primary)
+            }
+            UpdateStatus::Downloading {
+                downloaded, total, ..
+            } => {
+                let percent = if *total > 0 {
+                    downloaded * 100 / total
+                } else {
+                    0
+                };
+                (format!("Downloading {}%", percent), theme.muted_foreground)
+            }
+            UpdateStatus::Ready { .. } => ("Restart to update".to_string(), theme.success),
+            UpdateStatus::Installing(_) => {
+                ("Installing update…".to_string(), theme.muted_foreground)
+            }
+            UpdateStatus::Failed { .. } => ("Update failed".to_string(), theme.danger),
+        };
+
+        Some(
+            status_segment("status-update", cx)
+                .child(
+                    Icon::empty()
+                        .path("icons/cloud-download.svg")
+                        .size_3()
+                        .text_color(color),
+                )
+                .child(Label::new(label).text_xs().text_color(color))
+                .on_click(cx.listener(|_this, _evt, _win, cx| {
+                    cx.emit(FooterBarEvent::OpenUpdates);
+                })),
+        )
+    }
+
+    /// Health dot, last ping latency, a sparkline of recent pings and the
+    /// SSH tunnel state, with the details in a tooltip.
+    fn render_health(&self, cx: &Context<Self>) -> impl IntoElement {
+        let state = HealthState::of(self.window_id, cx);
+        let health = &state.health;
+        let theme = cx.theme();
+        let level_color = match health.level() {
+            HealthLevel::Unknown => theme.muted_foreground,
+            HealthLevel::Good => theme.success,
+            HealthLevel::Degraded => theme.warning,
+            HealthLevel::Down => theme.danger,
+        };
+
+        let sparkline = health.sparkline().into_iter().fold(
+            div().flex().items_end().gap(px(1.)).h(px(SPARKLINE_HEIGHT)),
+            |line, sample| {
+                let bar = div().w(px(2.)).rounded_sm();
+                line.child(match sample {
+                    Some(fraction) => bar
+                        .h(px(1. + fraction * (SPARKLINE_HEIGHT - 1.)))
+                        .bg(theme.muted_foreground),
+                    None => bar.h(px(SPARKLINE_HEIGHT)).bg(theme.danger.opacity(0.6)),
+                })
+            },
+        );
+
+        let latest = match health.latest() {
+            Some(Some(latency)) => format_latency(latency),
+            Some(None) => "timeout".to_string(),
+            None => "…".to_string(),
+        };
+        let tunnel = match state.tunnel {
+            TunnelStatus::Direct => None,
+            TunnelStatus::Up => Some(("SSH tunnel up", theme.success)),
+            TunnelStatus::Down => Some(("SSH tunnel down", theme.danger)),
+        };
+
+        let mut details = vec![format!("Last ping: {}", latest)];
+        if let (Some(average), Some(max)) = (health.average(), health.max()) {
+            details.push(format!(
+                "Average {}, slowest {}",
+                format_latency(average),
+                format_latency(max)
+            ));
+        }
+        let failed = health.samples().filter(Option::is_none).count();
+        if failed > 0 {
+            details.push(format!(
+                "{} failed of the last {} pings",
+                failed,
+                health.samples().count()
+            ));
+        }
+        if let Some((label, _)) = tunnel {
+            details.push(label.to_string());
+        }
+        let details = details.join("\n");
+
+        status_segment("connection-health", cx)
+            .gap_2()
+            .child(div().size(px(6.)).rounded_full().bg(level_color))
+            .child(Label::new(latest).text_xs())
+            .child(sparkline)
+            .when_some(tunnel, |d, (_, color)| {
+                d.child(
+                    Icon::empty()
+                        .path("icons/cable.svg")
+                        .size_3()
+                        .text_color(color),
+                )
+            })
+            .tooltip(move |window, cx| Tooltip::new(details.clone()).build(window, cx))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::OpenNotifications);
+            }))
+    }
 }
 
 impl Render for FooterBar {
@@ -54,7 +385,7 @@ impl Render for FooterBar {
             .small()
             .ghost()
             .selected(self.tables_active.clone())
-            .tooltip("Toggle Tables Panel")
+            .tooltip(tr("footer-toggle-tables"))
             .on_click(cx.listener(|this, _evt, _win, cx| {
                 this.tables_active = !this.tables_active;
                 if this.tables_active {
@@ -65,18 +396,91 @@ impl Render for FooterBar {
                 cx.notify();
             }));
 
+        let results_icon = match self.results_orientation {
+            ResultsOrientation::Below => "icons/panel-bottom.svg",
+            ResultsOrientation::Right => "icons/panel-right.svg",
+        };
+        let results_button = Button::new("results_orientation_button")
+            .icon(Icon::empty().path(results_icon))
+            .small()
+            .ghost()
+            .tooltip(tr("footer-toggle-results-position"))
+            .on_click(cx.listener(|this, _evt, _win, cx| {
+                this.results_orientation = this.results_orientation.toggled();
+                cx.emit(FooterBarEvent::SetResultsOrientation(
+                    this.results_orientation,
+                ));
+                cx.notify();
+            }));
+
+        let performance_button = Button::new("performance_button")
+            .icon(Icon::empty().path("icons/chart-pie.svg"))
+            .small()
+            .ghost()
+            .tooltip(tr("footer-performance"))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::OpenPerformance);
+            }));
+
+        let locks_button = Button::new("locks_button")
+            .icon(Icon::empty().path("icons/circle-pause.svg"))
+            .small()
+            .ghost()
+            .tooltip(tr("footer-locks"))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::OpenLocks);
+            }));
+
+        let activity_button = Button::new("activity_button")
+            .icon(Icon::empty().path("icons/bell.svg"))
+            .small()
+            .ghost()
+            .tooltip(tr("footer-activity"))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::OpenActivity);
+            }));
+
+        let migrations_button = Button::new("migrations_button")
+            .icon(Icon::empty().path("icons/list-ordered.svg"))
+            .small()
+            .ghost()
+            .tooltip(tr("footer-migrations"))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::OpenMigrations);
+            }));
+
+        let save_workspace_button = Button::new("save_workspace_button")
+            .icon(Icon::empty().path("icons/layout-dashboard.svg"))
+            .small()
+            .ghost()
+            .tooltip(tr("footer-save-workspace"))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::SaveWorkspace);
+            }));
+
+        let zen_mode_button = Button::new("zen_mode_button")
+            .icon(Icon::empty().path("icons/maximize.svg"))
+            .small()
+            .ghost()
+            .tooltip(tr("footer-zen-mode"))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::EnterZenMode);
+            }));
+
         let agent_button = Button::new("agent_button")
             .icon(IconName::Bot)
             .small()
             .ghost()
             .selected(self.agent_active.clone())
-            .tooltip("Toggle Agent Panel")
+            .tooltip(tr("footer-toggle-agent"))
             .on_click(cx.listener(|this, _evt, _win, cx| {
                 this.agent_active = !this.agent_active;
                 if this.agent_active {
                     cx.emit(FooterBarEvent::ToggleAgent(true));
                     this.history_active = false;
                     cx.emit(FooterBarEvent::ToggleHistory(false));
+                    this.jobs_active = false;
+                    cx.emit(FooterBarEvent::ToggleJobs(false));
                 } else {
                     cx.emit(FooterBarEvent::ToggleAgent(false));
                 }
@@ -88,45 +492,52 @@ impl Render for FooterBar {
             .small()
             .ghost()
             .selected(self.history_active.clone())
-            .tooltip("Toggle History Panel")
+            .tooltip(tr("footer-toggle-history"))
             .on_click(cx.listener(|this, _evt, _win, cx| {
                 this.history_active = !this.history_active;
                 if this.history_active {
                     cx.emit(FooterBarEvent::ToggleHistory(true));
                     this.agent_active = false;
                     cx.emit(FooterBarEvent::ToggleAgent(false));
+                    this.jobs_active = false;
+                    cx.emit(FooterBarEvent::ToggleJobs(false));
                 } else {
                     cx.emit(FooterBarEvent::ToggleHistory(false));
                 }
                 cx.notify();
             }));
 
-        let connection_url = self
-            .active_connection
-            .clone()
-            .map(|x| format!("{}@{}:{}", x.username, x.hostname, x.port));
+        let jobs_button = Button::new("jobs_button")
+            .icon(Icon::empty().path("icons/inbox.svg"))
+            .small()
+            .ghost()
+            .selected(self.jobs_active)
+            .when(self.jobs_badge > 0, |b| {
+                b.label(self.jobs_badge.to_string())
+            })
+            .tooltip(tr("footer-toggle-jobs"))
+            .on_click(cx.listener(|this, _evt, _win, cx| {
+                if this.jobs_active {
+                    this.jobs_active = false;
+                    cx.emit(FooterBarEvent::ToggleJobs(false));
+                    cx.notify();
+                } else {
+                    this.show_jobs(cx);
+                }
+            }));
 
-        let _connection_status = div()
-            .flex()
-            .items_center()
-            .justify_center()
-            .gap_1()
-            .pr_1()
-            .when(connection_url.clone().is_some(), |d| {
-                d.child(
-                    Label::new(connection_url.clone().unwrap())
-                        .italic()
-                        .text_xs(),
-                )
-                .child(Icon::empty().path("icons/power.svg"))
-                .text_color(cx.theme().primary)
+        // Shown while disconnected too, since errors happen before connecting
+        let notifications_button = Button::new("notifications_button")
+            .icon(Icon::empty().path("icons/gallery-vertical-end.svg"))
+            .small()
+            .ghost()
+            .when(self.unread_notifications > 0, |b| {
+                b.label(self.unread_notifications.to_string())
             })
-            .when(connection_url.is_none(), |d| {
-                d.child(Label::new("Disconnected").italic().text_xs())
-                    .child(Icon::empty().path("icons/power.svg"))
-                    .text_color(cx.theme().foreground)
-                    .opacity(0.6)
-            });
+            .tooltip(tr("footer-notifications"))
+            .on_click(cx.listener(|_this, _evt, _win, cx| {
+                cx.emit(FooterBarEvent::OpenNotifications);
+            }));
 
         let left_controls = div()
             .flex()
@@ -135,7 +546,10 @@ impl Render for FooterBar {
             .items_center()
             .gap_1()
             .when(!self.is_connected.clone(), |d| d.invisible())
-            .child(tables_button);
+            .child(tables_button)
+            .child(results_button)
+            .child(save_workspace_button)
+            .child(zen_mode_button);
 
         let right_controls = div()
             .flex()
@@ -144,6 +558,11 @@ impl Render for FooterBar {
             .items_center()
             .gap_1()
             .when(!self.is_connected.clone(), |d| d.invisible())
+            .child(performance_button)
+            .child(locks_button)
+            .child(activity_button)
+            .child(migrations_button)
+            .child(jobs_button)
             .child(history_button)
             .child(agent_button);
 
@@ -160,7 +579,17 @@ impl Render for FooterBar {
             .justify_between()
             .items_center()
             .child(left_controls)
-            .child(right_controls);
+            .when(self.is_connected, |d| d.child(self.render_status(cx)))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap_1()
+                    .child(right_controls)
+                    .children(self.render_update_status(cx))
+                    .child(notifications_button),
+            );
 
         footer
     }
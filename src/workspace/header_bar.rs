@@ -1,50 +1,63 @@
 use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme as _, Icon, IconName, Sizable as _, ThemeMode, TitleBar,
+    ActiveTheme as _, Icon, IconName, Selectable as _, Sizable as _, ThemeMode, TitleBar,
+    WindowExt as _,
     button::{Button, ButtonVariants as _},
     h_flex,
     label::Label,
 };
 
+use super::theme_dialog::ThemeDialog;
+use super::update_dialog::open_update_dialog;
 use crate::{
-    services::{check_for_update, updates::UpdateInfo},
+    services::i18n::{tr, tr_args},
+    state::{ConnectionState, PerWindow, UpdateState, WindowConnection, WindowScoped, window_id},
     themes::*,
+    window::window_title,
 };
 
 pub struct HeaderBar {
-    update_available: Option<UpdateInfo>,
+    window_id: WindowId,
+    title: String,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl HeaderBar {
-    pub fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let this = Self {
-            update_available: None,
-        };
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        // Startup update checks run in UpdateState::init
+        let _subscriptions = vec![
+            cx.observe_global::<UpdateState>(|_, cx| cx.notify()),
+            // Each window titles itself, so the OS task switcher tells
+            // windows apart by connection and database. Also redraws the
+            // production badge.
+            cx.observe_global_in::<PerWindow<WindowConnection>>(window, |this, window, cx| {
+                this.update_title(window, cx);
+                cx.notify();
+            }),
+            cx.observe_global::<ConnectionState>(|_, cx| cx.notify()),
+        ];
 
-        // Check for updates on startup
-        cx.spawn(async move |this, cx| match check_for_update().await {
-            Ok(Some(update_info)) => {
-                tracing::info!(
-                    "Update available: {} -> {}",
-                    update_info.current_version,
-                    update_info.latest_version
-                );
-                let _ = this.update(cx, |this, cx| {
-                    this.update_available = Some(update_info);
-                    cx.notify();
-                });
-            }
-            Ok(None) => {
-                tracing::debug!("No update available");
-            }
-            Err(e) => {
-                tracing::warn!("Failed to check for updates: {}", e);
-            }
-        })
-        .detach();
-
-        this
+        let title = window_title(None);
+        window.set_window_title(&title);
+        Self {
+            window_id: window_id(window),
+            title,
+            _subscriptions,
+        }
+    }
+
+    fn update_title(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let title = window_title(
+            WindowConnection::of(self.window_id, cx)
+                .active_connection
+                .as_ref(),
+        );
+        if title != self.title {
+            window.set_window_title(&title);
+            self.title = title;
+            cx.notify();
+        }
     }
     pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
         cx.new(|cx| Self::new(window, cx))
@@ -59,10 +72,50 @@ impl HeaderBar {
         change_color_mode(new_mode, window, cx);
     }
 
-    fn open_release_page(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
-        if let Some(update_info) = &self.update_available {
-            cx.open_url(&update_info.release_url);
-        }
+    fn toggle_high_contrast(
+        &mut self,
+        _: &ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut appearance = Appearance::global(cx);
+        appearance.high_contrast = !appearance.high_contrast;
+        appearance.set(window, cx);
+    }
+
+    fn toggle_reduce_motion(
+        &mut self,
+        _: &ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut appearance = Appearance::global(cx);
+        appearance.reduce_motion = !appearance.reduce_motion;
+        appearance.set(window, cx);
+    }
+
+    fn open_updates(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        open_update_dialog(window, cx);
+    }
+
+    fn open_themes(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let view = ThemeDialog::view(window, cx);
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let (on_ok, on_cancel) = (view.clone(), view.clone());
+            dialog
+                .title(tr("theme-dialog-title"))
+                .width(px(520.))
+                .child(view.clone())
+                .confirm()
+                .on_ok(move |_, window, cx| {
+                    on_ok.read(cx).save(window, cx);
+                    true
+                })
+                .on_cancel(move |_, window, cx| {
+                    on_cancel.read(cx).revert(window, cx);
+                    true
+                })
+        });
     }
 }
 
@@ -80,40 +133,99 @@ impl Render for HeaderBar {
             .ghost()
             .on_click(cx.listener(Self::change_mode));
 
+        let appearance = Appearance::global(cx);
+        let contrast_toggle = Button::new("high-contrast")
+            .icon(Icon::empty().path("icons/contrast.svg"))
+            .small()
+            .ghost()
+            .selected(appearance.high_contrast)
+            .tooltip(tr("header-high-contrast"))
+            .on_click(cx.listener(Self::toggle_high_contrast));
+
+        let motion_toggle = Button::new("reduce-motion")
+            .icon(Icon::empty().path("icons/circle-pause.svg"))
+            .small()
+            .ghost()
+            .selected(appearance.reduce_motion)
+            .tooltip(tr("header-reduce-motion"))
+            .on_click(cx.listener(Self::toggle_reduce_motion));
+
+        let themes_button = Button::new("themes")
+            .icon(Icon::empty().path("icons/palette.svg"))
+            .small()
+            .ghost()
+            .tooltip(tr("header-themes"))
+            .on_click(cx.listener(Self::open_themes));
+
         let github_button = Button::new("github")
             .icon(IconName::GitHub)
             .small()
             .ghost()
             .on_click(|_, _, cx| cx.open_url("https://github.com/duanebester/pgui"));
 
-        // Update button - only show if update is available
-        let update_button = self.update_available.as_ref().map(|info| {
-            let label: SharedString = format!("v{} available!", info.latest_version).into();
-            Button::new("update-available")
-                .icon(Icon::empty().path("icons/cloud-download.svg"))
-                .small()
-                .tooltip(label)
-                .ghost()
-                .on_click(cx.listener(Self::open_release_page))
-        });
+        // Update button - highlighted once a newer release is found
+        let latest_version = cx
+            .global::<UpdateState>()
+            .status
+            .info()
+            .map(|info| info.latest_version.clone());
+        let update_tooltip: SharedString = match &latest_version {
+            Some(version) => tr_args(
+                "header-update-available",
+                &[("version", version.as_str().into())],
+            )
+            .into(),
+            None => tr("header-updates").into(),
+        };
+        let update_button = Button::new("update-available")
+            .icon(Icon::empty().path("icons/cloud-download.svg"))
+            .small()
+            .tooltip(update_tooltip)
+            .ghost()
+            .selected(latest_version.is_some())
+            .on_click(cx.listener(Self::open_updates));
+
+        // Connected to a production database: badge the title bar in the
+        // production accent.
+        let production = WindowConnection::is_production(self.window_id, cx);
+        let accent = production_accent(cx);
 
-        TitleBar::new().child(
-            h_flex()
-                .w_full()
-                .pr_2()
-                .justify_between()
-                .child(Label::new("PGUI").text_xs())
-                .child(
-                    div()
-                        .pr(px(5.0))
-                        .flex()
-                        .items_center()
-                        .when(self.update_available.is_some(), |d| {
-                            d.child(update_button.unwrap())
-                        })
-                        .child(theme_toggle)
-                        .child(github_button),
-                ),
-        )
+        TitleBar::new()
+            .when(production, |bar| bar.border_b_2().border_color(accent))
+            .child(
+                h_flex()
+                    .w_full()
+                    .pr_2()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(self.title.clone()).text_xs())
+                            .when(production, |row| {
+                                row.child(
+                                    div()
+                                        .px_1p5()
+                                        .rounded(cx.theme().radius)
+                                        .bg(accent)
+                                        .text_color(cx.theme().danger_foreground)
+                                        .text_xs()
+                                        .font_semibold()
+                                        .child(tr("header-production")),
+                                )
+                            }),
+                    )
+                    .child(
+                        div()
+                            .pr(px(5.0))
+                            .flex()
+                            .items_center()
+                            .child(update_button)
+                            .child(motion_toggle)
+                            .child(contrast_toggle)
+                            .child(themes_button)
+                            .child(theme_toggle)
+                            .child(github_button),
+                    ),
+            )
     }
 }
@@ -0,0 +1,91 @@
+use gpui::{App, AppContext, Context, Entity, IntoElement, ParentElement, Render, Styled, Window};
+use gpui_component::{
+    Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+};
+
+use crate::services::export::HistoryFormat;
+
+/// Dialog body choosing which history to export and in which format.
+pub struct HistoryExportForm {
+    connection_name: String,
+    all_connections: bool,
+    format: HistoryFormat,
+}
+
+impl HistoryExportForm {
+    pub fn view(connection_name: String, cx: &mut App) -> Entity<Self> {
+        cx.new(|_| Self {
+            connection_name,
+            all_connections: false,
+            format: HistoryFormat::Json,
+        })
+    }
+
+    pub fn all_connections(&self) -> bool {
+        self.all_connections
+    }
+
+    pub fn format(&self) -> HistoryFormat {
+        self.format
+    }
+}
+
+impl Render for HistoryExportForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let scope_toggle = h_flex()
+            .gap_1()
+            .child(
+                Button::new("export-this-connection")
+                    .label(self.connection_name.clone())
+                    .small()
+                    .ghost()
+                    .selected(!self.all_connections)
+                    .on_click(cx.listener(|this, _, _win, cx| {
+                        this.all_connections = false;
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Button::new("export-all-connections")
+                    .label("All connections")
+                    .small()
+                    .ghost()
+                    .selected(self.all_connections)
+                    .on_click(cx.listener(|this, _, _win, cx| {
+                        this.all_connections = true;
+                        cx.notify();
+                    })),
+            );
+
+        let format_toggle = [HistoryFormat::Json, HistoryFormat::Csv].into_iter().fold(
+            h_flex().gap_1(),
+            |row, format| {
+                row.child(
+                    Button::new(format.extension())
+                        .label(format.extension().to_uppercase())
+                        .small()
+                        .ghost()
+                        .selected(self.format == format)
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.format = format;
+                            cx.notify();
+                        })),
+                )
+            },
+        );
+
+        v_form()
+            .small()
+            .child(field().label("Queries from").child(scope_toggle))
+            .child(
+                field()
+                    .label("Format")
+                    .description("Both formats can be imported again on another machine")
+                    .child(format_toggle),
+            )
+            .w_full()
+    }
+}
@@ -1,4 +1,11 @@
+mod export_form;
 mod panel;
+mod quick_slots;
+mod timeline;
 
 pub use panel::HistoryEvent;
 pub use panel::HistoryPanel;
+pub use quick_slots::{
+    QuickSlot1, QuickSlot2, QuickSlot3, QuickSlot4, QuickSlot5, QuickSlot6, QuickSlot7,
+    QuickSlot8, QuickSlot9, quick_slot_shortcut,
+};
@@ -1,65 +1,90 @@
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use gpui::{
     AnyElement, App, AppContext, ClickEvent, Context, Entity, EventEmitter,
-    InteractiveElement as _, IntoElement, ListAlignment, ListState, ParentElement, Render,
-    StatefulInteractiveElement as _, Styled, Subscription, Window, div, list,
-    prelude::FluentBuilder as _, px,
+    InteractiveElement as _, IntoElement, ListAlignment, ListState, ParentElement,
+    PathPromptOptions, Render, SharedString, StatefulInteractiveElement as _, Styled, Subscription,
+    Window, WindowId, div, list, prelude::FluentBuilder as _, px,
 };
 use gpui_component::{
-    ActiveTheme as _, Disableable, Icon, IconName, Sizable as _, StyledExt as _,
+    ActiveTheme as _, Disableable, Icon, IconName, Sizable as _, StyledExt as _, WindowExt as _,
     button::{Button, ButtonVariants as _},
     h_flex,
     label::Label,
+    menu::{ContextMenuExt as _, PopupMenuItem},
+    notification::NotificationType,
     v_flex,
 };
 
+use super::export_form::HistoryExportForm;
+use super::quick_slots::{QuickSlotsEvent, QuickSlotsPanel, quick_slot_shortcut};
+use super::timeline::{QueryTimelineChart, TIMELINE_RUNS};
 use crate::{
-    services::{AppStore, ConnectionInfo, storage::QueryHistoryEntry},
-    state::ConnectionState,
+    services::{
+        AppStore, ConnectionInfo,
+        export::{HistoryFormat, read_history, reassign_unknown_connections, write_history},
+        storage::{QueryHistoryEntry, normalize_query, relative_time},
+    },
+    state::{
+        ConnectionState, PerWindow, QUICK_SLOT_COUNT, QuickSlot, QuickSlotsState, WindowConnection,
+        WindowScoped, window_id,
+    },
+    workspace::notifications::{RecordedNotifications as _, Toasts as _},
 };
 
 /// Event emitted when a history entry is selected
 pub enum HistoryEvent {
     /// User wants to load this SQL into the editor
     LoadQuery(String),
+    /// User wants to reopen this entry: its SQL and the result of its run
+    OpenEntry(QueryHistoryEntry),
 }
 
 impl EventEmitter<HistoryEvent> for HistoryPanel {}
 
 pub struct HistoryPanel {
+    window_id: WindowId,
     list_state: ListState,
     history_entries: Vec<QueryHistoryEntry>,
     filtered_entries: Vec<QueryHistoryEntry>,
     active_connection: Option<ConnectionInfo>,
     is_loading: bool,
+    /// Runs of the query whose timeline is shown.
+    timeline: Option<(String, Vec<QueryHistoryEntry>)>,
     _subscriptions: Vec<Subscription>,
 }
 
 #[allow(dead_code)]
 impl HistoryPanel {
-    pub fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let list_state = ListState::new(0, ListAlignment::Top, px(20.));
 
-        let _subscriptions = vec![cx.observe_global::<ConnectionState>(move |this, cx| {
-            let state = cx.global::<ConnectionState>();
-            let new_connection = state.active_connection.clone();
-
-            // Only reload if connection changed
-            if this.active_connection.as_ref().map(|c| &c.id)
-                != new_connection.as_ref().map(|c| &c.id)
-            {
-                this.active_connection = new_connection;
-                this.load_history(cx);
-            }
-            cx.notify();
-        })];
+        let _subscriptions =
+            vec![
+                cx.observe_global::<PerWindow<WindowConnection>>(move |this, cx| {
+                    let new_connection = WindowConnection::of(this.window_id, cx)
+                        .active_connection
+                        .clone();
+
+                    // Only reload if connection changed
+                    if this.active_connection.as_ref().map(|c| &c.id)
+                        != new_connection.as_ref().map(|c| &c.id)
+                    {
+                        this.active_connection = new_connection;
+                        this.timeline = None;
+                        this.load_history(cx);
+                    }
+                    cx.notify();
+                }),
+            ];
 
         Self {
+            window_id: window_id(window),
             list_state,
             history_entries: Vec::new(),
             filtered_entries: Vec::new(),
             active_connection: None,
             is_loading: false,
+            timeline: None,
             _subscriptions,
         }
     }
@@ -167,28 +192,253 @@ impl HistoryPanel {
         .detach();
     }
 
-    fn on_entry_click(&mut self, sql: String, _window: &mut Window, cx: &mut Context<Self>) {
-        cx.emit(HistoryEvent::LoadQuery(sql));
+    fn on_export(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(connection) = self.active_connection.clone() else {
+            return;
+        };
+
+        let form = HistoryExportForm::view(connection.name.clone(), cx);
+        let panel = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            let panel = panel.clone();
+            dialog
+                .title("Export History")
+                .child(form.clone())
+                .confirm()
+                .on_ok(move |_, window, cx| {
+                    let form = form.read(cx);
+                    let (all_connections, format) = (form.all_connections(), form.format());
+                    let _ = panel.update(cx, |this, cx| {
+                        this.export_history(all_connections, format, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    /// Write the history to a file picked by the user, for importing on
+    /// another machine.
+    fn export_history(
+        &mut self,
+        all_connections: bool,
+        format: HistoryFormat,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(connection) = self.active_connection.clone() else {
+            return;
+        };
+        let connection_id = (!all_connections).then_some(connection.id);
+
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let suggested_name = format!("query_history_{}.{}", timestamp, format.extension());
+        let home = dirs::home_dir().unwrap_or_default();
+        let receiver = cx.prompt_for_new_path(&home, Some(&suggested_name));
+
+        cx.spawn_in(window, async move |_this, cx| {
+            let Ok(Ok(Some(path))) = receiver.await else {
+                return;
+            };
+
+            let result: anyhow::Result<usize> = async {
+                let store = AppStore::singleton().await?;
+                let entries = store.history().export(connection_id.as_ref()).await?;
+                async_fs::write(&path, write_history(&entries, format)?).await?;
+                Ok(entries.len())
+            }
+            .await;
+
+            let _ = cx.update(|window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok(count) => (
+                        NotificationType::Success,
+                        format!("Exported {} queries to {}", count, path.display()).into(),
+                    ),
+                    Err(e) => {
+                        tracing::error!("History export failed: {}", e);
+                        (
+                            NotificationType::Error,
+                            format!("Failed to export history: {}", e).into(),
+                        )
+                    }
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Import a history file. Entries for connections that are not saved
+    /// here are attached to the active connection.
+    fn on_import(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(connection) = self.active_connection.clone() else {
+            return;
+        };
+        let known_connections: Vec<_> = cx
+            .global::<ConnectionState>()
+            .saved_connections
+            .iter()
+            .map(|c| c.id)
+            .collect();
+
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import history".into()),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+
+            let result: anyhow::Result<(u64, usize)> = async {
+                let contents = async_fs::read_to_string(&path).await?;
+                let mut entries = read_history(&contents, HistoryFormat::from_path(&path))?;
+                reassign_unknown_connections(&mut entries, &known_connections, connection.id);
+                let store = AppStore::singleton().await?;
+                let imported = store.history().import(&entries).await?;
+                Ok((imported, entries.len()))
+            }
+            .await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok((imported, total)) => (
+                        NotificationType::Success,
+                        format!(
+                            "Imported {} of {} queries ({} already in history)",
+                            imported,
+                            total,
+                            total as u64 - imported
+                        )
+                        .into(),
+                    ),
+                    Err(e) => (
+                        NotificationType::Error,
+                        format!("Failed to import history: {}", e).into(),
+                    ),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.load_history(cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Chart the execution time of `sql` and of queries that differ from it
+    /// only in literal values.
+    fn show_timeline(&mut self, sql: String, cx: &mut Context<Self>) {
+        let Some(connection) = self.active_connection.clone() else {
+            return;
+        };
+
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                let store = AppStore::singleton().await?;
+                store
+                    .history()
+                    .timeline(&connection.id, &sql, TIMELINE_RUNS)
+                    .await
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(runs) => this.timeline = Some((normalize_query(&sql), runs)),
+                    Err(e) => tracing::error!("Failed to load query timeline: {}", e),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
     }
 
-    fn format_relative_time(executed_at: DateTime<Utc>) -> String {
-        let now = Utc::now();
-        let duration = now.signed_duration_since(executed_at);
-
-        if duration.num_seconds() < 60 {
-            "just now".to_string()
-        } else if duration.num_minutes() < 60 {
-            let mins = duration.num_minutes();
-            format!("{} min{} ago", mins, if mins == 1 { "" } else { "s" })
-        } else if duration.num_hours() < 24 {
-            let hours = duration.num_hours();
-            format!("{} hour{} ago", hours, if hours == 1 { "" } else { "s" })
-        } else if duration.num_days() < 7 {
-            let days = duration.num_days();
-            format!("{} day{} ago", days, if days == 1 { "" } else { "s" })
+    fn render_timeline(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let (sql, runs) = self.timeline.clone()?;
+        let body = if runs.is_empty() {
+            Label::new("No successful runs of this query yet")
+                .text_xs()
+                .text_color(cx.theme().muted_foreground)
+                .into_any_element()
         } else {
-            executed_at.format("%b %d, %Y").to_string()
-        }
+            QueryTimelineChart::new(runs).into_any_element()
+        };
+
+        Some(
+            v_flex()
+                .gap_1()
+                .p_2()
+                .border_1()
+                .border_color(cx.theme().border)
+                .rounded(cx.theme().radius)
+                .child(
+                    h_flex()
+                        .justify_between()
+                        .items_center()
+                        .child(Label::new("Timeline").text_sm().font_medium())
+                        .child(
+                            Button::new("close-timeline")
+                                .icon(IconName::Close)
+                                .xsmall()
+                                .ghost()
+                                .tooltip("Close Timeline")
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.timeline = None;
+                                    cx.notify();
+                                })),
+                        ),
+                )
+                .child(
+                    Label::new(Self::truncate_sql(&sql, 60))
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                )
+                .child(body),
+        )
+    }
+
+    fn on_quick_slots(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(connection) = self.active_connection.clone() else {
+            return;
+        };
+
+        let slots = QuickSlotsPanel::view(connection.id, cx);
+        cx.subscribe_in(
+            &slots,
+            window,
+            |_, _, event: &QuickSlotsEvent, window, cx| match event {
+                QuickSlotsEvent::LoadQuery(sql) => {
+                    cx.emit(HistoryEvent::LoadQuery(sql.clone()));
+                    window.close_dialog(cx);
+                }
+            },
+        )
+        .detach();
+
+        let title = format!("Quick Slots — {}", connection.name);
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title(title.clone())
+                .width(px(560.))
+                .child(slots.clone())
+        });
+    }
+
+    fn on_entry_click(
+        &mut self,
+        entry: QueryHistoryEntry,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.emit(HistoryEvent::OpenEntry(entry));
     }
 
     fn truncate_sql(sql: &str, max_len: usize) -> String {
@@ -213,13 +463,16 @@ impl HistoryPanel {
 
         let sql = entry.sql.clone();
         let truncated_sql = Self::truncate_sql(&sql, 40);
-        let relative_time = Self::format_relative_time(entry.executed_at);
+        let relative_time = relative_time(entry.executed_at, Utc::now());
 
-        let execution_info = if let Some(rows) = entry.rows_affected {
+        let mut execution_info = if let Some(rows) = entry.rows_affected {
             format!("{}ms • {} rows", entry.execution_time_ms, rows)
         } else {
             format!("{}ms", entry.execution_time_ms)
         };
+        if let Some(index) = entry.statement_index {
+            execution_info = format!("Statement {} • {}", index + 1, execution_info);
+        }
 
         let status_icon = if entry.success {
             Icon::new(IconName::CircleCheck).text_color(cx.theme().success)
@@ -249,9 +502,74 @@ impl HistoryPanel {
                         s.bg(cx.theme().list_active)
                             .border_color(cx.theme().list_active_border)
                     })
-                    .on_click(cx.listener(move |this, _, window, cx| {
-                        this.on_entry_click(sql.clone(), window, cx);
-                    }))
+                    .on_click({
+                        let entry = entry.clone();
+                        cx.listener(move |this, _, window, cx| {
+                            this.on_entry_click(entry.clone(), window, cx);
+                        })
+                    })
+                    .context_menu({
+                        let sql = entry.sql.clone();
+                        let connection_id = entry.connection_id;
+                        let panel = cx.entity().downgrade();
+                        move |menu, _window, cx| {
+                            let menu = menu
+                                .item(PopupMenuItem::new("Show Timeline").on_click({
+                                    let sql = sql.clone();
+                                    let panel = panel.clone();
+                                    move |_, _, cx| {
+                                        let _ = panel.update(cx, |this, cx| {
+                                            this.show_timeline(sql.clone(), cx);
+                                        });
+                                    }
+                                }))
+                                .separator();
+                            let current: Vec<Option<String>> = (1..=QUICK_SLOT_COUNT)
+                                .map(|slot| {
+                                    cx.global::<QuickSlotsState>()
+                                        .slot(connection_id, slot)
+                                        .map(|quick_slot| quick_slot.name.clone())
+                                })
+                                .collect();
+                            (1..=QUICK_SLOT_COUNT).zip(current).fold(
+                                menu,
+                                |menu, (slot, current)| {
+                                    let label = match current {
+                                        Some(name) => format!(
+                                            "Assign to {} (replaces {})",
+                                            quick_slot_shortcut(slot),
+                                            name
+                                        ),
+                                        None => format!("Assign to {}", quick_slot_shortcut(slot)),
+                                    };
+                                    let sql = sql.clone();
+                                    menu.item(PopupMenuItem::new(label).on_click(
+                                        move |_, window, cx| {
+                                            let quick_slot = QuickSlot {
+                                                name: Self::truncate_sql(&sql, 40),
+                                                sql: sql.clone(),
+                                            };
+                                            QuickSlotsState::assign(
+                                                connection_id,
+                                                slot,
+                                                quick_slot,
+                                                cx,
+                                            );
+                                            let message: SharedString = format!(
+                                                "Assigned to {}",
+                                                quick_slot_shortcut(slot)
+                                            )
+                                            .into();
+                                            window.push_toast(
+                                                (NotificationType::Success, message),
+                                                cx,
+                                            );
+                                        },
+                                    ))
+                                },
+                            )
+                        }
+                    })
                     .child(
                         v_flex()
                             .gap_1()
@@ -331,11 +649,43 @@ impl Render for HistoryPanel {
             .disabled(!has_connection || self.history_entries.is_empty())
             .on_click(cx.listener(Self::on_clear_history));
 
+        let export_button = Button::new("export-history")
+            .icon(Icon::empty().path("icons/save.svg"))
+            .small()
+            .ghost()
+            .tooltip("Export History...")
+            .disabled(!has_connection)
+            .on_click(cx.listener(Self::on_export));
+
+        let quick_slots_button = Button::new("quick-slots")
+            .icon(Icon::empty().path("icons/star.svg"))
+            .small()
+            .ghost()
+            .tooltip("Quick Slots")
+            .disabled(!has_connection)
+            .on_click(cx.listener(Self::on_quick_slots));
+
+        let import_button = Button::new("import-history")
+            .icon(Icon::empty().path("icons/folder-open.svg"))
+            .small()
+            .ghost()
+            .tooltip("Import History...")
+            .disabled(!has_connection)
+            .on_click(cx.listener(Self::on_import));
+
         let header = h_flex()
             .justify_between()
             .items_center()
             .child(Label::new("History").font_bold().text_base())
-            .child(h_flex().gap_1().child(refresh_button).child(clear_button));
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(refresh_button)
+                    .child(quick_slots_button)
+                    .child(export_button)
+                    .child(import_button)
+                    .child(clear_button),
+            );
 
         let content = if !has_connection {
             div().flex_1().flex().items_center().justify_center().child(
@@ -395,6 +745,7 @@ impl Render for HistoryPanel {
                         if entry_count == 1 { "query" } else { "queries" }
                     )),
             )
+            .children(self.render_timeline(cx))
             .child(content)
     }
 }
@@ -0,0 +1,134 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, StyledExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    v_flex,
+};
+use uuid::Uuid;
+
+use crate::state::{QUICK_SLOT_COUNT, QuickSlotsState};
+
+actions!(
+    workspace,
+    [
+        QuickSlot1, QuickSlot2, QuickSlot3, QuickSlot4, QuickSlot5, QuickSlot6, QuickSlot7,
+        QuickSlot8, QuickSlot9
+    ]
+);
+
+/// How the shortcut of `slot` reads on this platform, e.g. `⌘3`.
+pub fn quick_slot_shortcut(slot: u8) -> String {
+    if cfg!(target_os = "macos") {
+        format!("⌘{}", slot)
+    } else {
+        format!("Ctrl+{}", slot)
+    }
+}
+
+pub enum QuickSlotsEvent {
+    /// Load the slot's query into the editor
+    LoadQuery(String),
+}
+
+impl EventEmitter<QuickSlotsEvent> for QuickSlotsPanel {}
+
+/// Dialog body listing the quick slots of a connection.
+pub struct QuickSlotsPanel {
+    connection_id: Uuid,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl QuickSlotsPanel {
+    pub fn view(connection_id: Uuid, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self {
+            connection_id,
+            _subscriptions: vec![cx.observe_global::<QuickSlotsState>(|_, cx| cx.notify())],
+        })
+    }
+
+    fn render_slot(&self, slot: u8, cx: &mut Context<Self>) -> AnyElement {
+        let connection_id = self.connection_id;
+        let quick_slot = cx
+            .global::<QuickSlotsState>()
+            .slot(connection_id, slot)
+            .cloned();
+
+        h_flex()
+            .id(("quick-slot", slot as usize))
+            .gap_3()
+            .p_2()
+            .rounded(cx.theme().radius)
+            .hover(|s| s.bg(cx.theme().list_active))
+            .child(
+                Label::new(quick_slot_shortcut(slot))
+                    .w(px(48.))
+                    .text_sm()
+                    .font_family("monospace")
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .child(match &quick_slot {
+                Some(quick_slot) => v_flex()
+                    .flex_1()
+                    .overflow_hidden()
+                    .gap_0p5()
+                    .child(Label::new(quick_slot.name.clone()).text_sm().font_medium())
+                    .child(
+                        div()
+                            .text_xs()
+                            .font_family("monospace")
+                            .text_color(cx.theme().muted_foreground)
+                            .whitespace_nowrap()
+                            .overflow_x_hidden()
+                            .child(
+                                quick_slot
+                                    .sql
+                                    .split_whitespace()
+                                    .collect::<Vec<_>>()
+                                    .join(" "),
+                            ),
+                    ),
+                None => v_flex().flex_1().child(
+                    Label::new("Empty")
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground),
+                ),
+            })
+            .when_some(quick_slot, |row, quick_slot| {
+                row.child(
+                    Button::new(("load-quick-slot", slot as usize))
+                        .label("Load")
+                        .xsmall()
+                        .on_click(cx.listener(move |_, _, _, cx| {
+                            cx.emit(QuickSlotsEvent::LoadQuery(quick_slot.sql.clone()));
+                        })),
+                )
+                .child(
+                    Button::new(("clear-quick-slot", slot as usize))
+                        .icon(Icon::empty().path("icons/trash.svg"))
+                        .xsmall()
+                        .ghost()
+                        .tooltip("Clear Slot")
+                        .on_click(move |_, _, cx| QuickSlotsState::clear(connection_id, slot, cx)),
+                )
+            })
+            .into_any_element()
+    }
+}
+
+impl Render for QuickSlotsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = (1..=QUICK_SLOT_COUNT)
+            .map(|slot| self.render_slot(slot, cx))
+            .collect::<Vec<_>>();
+
+        v_flex().gap_1().children(rows).child(
+            Label::new("Right-click a query in History to assign it to a slot.")
+                .pt_2()
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+        )
+    }
+}
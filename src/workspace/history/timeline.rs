@@ -0,0 +1,110 @@
+use gpui::{prelude::FluentBuilder as _, *};
+use gpui_component::{ActiveTheme as _, h_flex, label::Label, tooltip::Tooltip, v_flex};
+
+use crate::services::storage::QueryHistoryEntry;
+
+/// Runs charted in a timeline, the latest ones.
+pub const TIMELINE_RUNS: usize = 60;
+
+const CHART_HEIGHT: f32 = 64.;
+
+/// Execution time of each run of one query, oldest on the left.
+#[derive(IntoElement)]
+pub struct QueryTimelineChart {
+    runs: Vec<QueryHistoryEntry>,
+}
+
+impl QueryTimelineChart {
+    pub fn new(runs: Vec<QueryHistoryEntry>) -> Self {
+        Self { runs }
+    }
+
+    /// How the latest runs compare with the earliest, e.g.
+    /// `Recent runs average 340 ms, up 183% from 120 ms`.
+    fn trend(&self) -> String {
+        let times: Vec<i64> = self.runs.iter().map(|r| r.execution_time_ms).collect();
+        if times.len() < 2 {
+            return "Only one run so far".to_string();
+        }
+        let window = (times.len() / 2).min(5);
+        let mean = |times: &[i64]| times.iter().sum::<i64>() as f64 / times.len() as f64;
+        let before = mean(&times[..window]);
+        let recent = mean(&times[times.len() - window..]);
+        let change = if before > 0. {
+            (recent - before) / before * 100.
+        } else {
+            0.
+        };
+
+        if change.abs() < 10. {
+            format!(
+                "Recent runs average {:.0} ms, about the same as before",
+                recent
+            )
+        } else {
+            format!(
+                "Recent runs average {:.0} ms, {} {:.0}% from {:.0} ms",
+                recent,
+                if change > 0. { "up" } else { "down" },
+                change.abs(),
+                before
+            )
+        }
+    }
+}
+
+impl RenderOnce for QueryTimelineChart {
+    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        let times: Vec<i64> = self.runs.iter().map(|r| r.execution_time_ms).collect();
+        let max = times.iter().copied().max().unwrap_or(0).max(1);
+        let min = times.iter().copied().min().unwrap_or(0);
+        let mut sorted = times.clone();
+        sorted.sort_unstable();
+        let median = sorted.get(sorted.len() / 2).copied().unwrap_or(0);
+        let trend = self.trend();
+
+        let bars = self.runs.iter().enumerate().map(|(ix, run)| {
+            let height = (CHART_HEIGHT * run.execution_time_ms as f32 / max as f32).max(2.);
+            let details = format!(
+                "{} ms · {}",
+                run.execution_time_ms,
+                run.executed_at
+                    .with_timezone(&chrono::Local)
+                    .format("%b %d, %Y %H:%M")
+            );
+            div()
+                .id(("timeline-run", ix))
+                .flex_1()
+                .max_w(px(12.))
+                .h(px(height))
+                .rounded(px(2.))
+                .bg(cx.theme().primary)
+                .when(run.execution_time_ms == max, |d| d.bg(cx.theme().warning))
+                .tooltip(move |window, cx| Tooltip::new(details.clone()).build(window, cx))
+        });
+
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .h(px(CHART_HEIGHT))
+                    .items_end()
+                    .gap(px(1.))
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .children(bars),
+            )
+            .child(
+                Label::new(format!(
+                    "{} runs · min {} ms · median {} ms · max {} ms",
+                    times.len(),
+                    min,
+                    median,
+                    max
+                ))
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+            )
+            .child(Label::new(trend).text_xs())
+    }
+}
@@ -0,0 +1,6 @@
+mod panel;
+mod schedule_form;
+mod scheduled;
+
+pub use panel::JobsEvent;
+pub use panel::JobsPanel;
@@ -0,0 +1,339 @@
+use chrono::Local;
+use gpui::{
+    AnyElement, App, AppContext, Context, Entity, EventEmitter, InteractiveElement as _,
+    IntoElement, ListAlignment, ListState, ParentElement, Render, Styled, Subscription, Window,
+    WindowId, div, list, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable, Icon, IconName, Selectable as _, Sizable as _, StyledExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    v_flex,
+};
+use uuid::Uuid;
+
+use crate::{
+    state::{JobStatus, JobsState, PerWindow, WindowConnection, WindowScoped, window_id},
+    themes::busy_indicator,
+};
+
+use super::scheduled::ScheduledJobs;
+
+/// Event emitted from a job entry
+pub enum JobsEvent {
+    /// Show the job's result in the results panel
+    ShowResult(Uuid),
+    /// Load the job's SQL into the editor
+    LoadQuery(String),
+}
+
+impl EventEmitter<JobsEvent> for JobsPanel {}
+
+pub struct JobsPanel {
+    window_id: WindowId,
+    list_state: ListState,
+    job_count: usize,
+    scheduled: Entity<ScheduledJobs>,
+    show_scheduled: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl JobsPanel {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let _subscriptions = vec![cx.observe_global::<PerWindow<JobsState>>(move |this, cx| {
+            let job_count = JobsState::of(this.window_id, cx).jobs.len();
+            if job_count != this.job_count {
+                this.job_count = job_count;
+                this.list_state = ListState::new(job_count, ListAlignment::Top, px(20.));
+            }
+            cx.notify();
+        })];
+
+        Self {
+            window_id: window_id(window),
+            list_state: ListState::new(0, ListAlignment::Top, px(20.)),
+            job_count: 0,
+            scheduled: ScheduledJobs::view(window, cx),
+            show_scheduled: false,
+            _subscriptions,
+        }
+    }
+
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn cancel_job(&mut self, job_id: Uuid, cx: &mut Context<Self>) {
+        let window = self.window_id;
+        JobsState::update(window, cx, |state, _cx| {
+            if let Some(job) = state.get_mut(job_id) {
+                job.cancel_requested = true;
+            }
+        });
+
+        let db_manager = WindowConnection::of(window, cx).db_manager.clone();
+        cx.spawn(async move |_this, cx| {
+            let cancelled = db_manager.cancel_job(job_id).await;
+            if !matches!(cancelled, Ok(true)) {
+                if let Err(e) = &cancelled {
+                    tracing::error!("Failed to cancel job: {}", e);
+                }
+                // Nothing was cancelled, so the job will finish normally
+                let _ = cx.update(|cx| {
+                    JobsState::update(window, cx, |state, _cx| {
+                        if let Some(job) = state.get_mut(job_id) {
+                            job.cancel_requested = false;
+                        }
+                    })
+                });
+            }
+        })
+        .detach();
+    }
+
+    fn clear_finished(&mut self, cx: &mut Context<Self>) {
+        JobsState::update(self.window_id, cx, |state, _cx| {
+            state.jobs.retain(|job| job.status == JobStatus::Running);
+        });
+    }
+
+    fn truncate_sql(sql: &str, max_len: usize) -> String {
+        // Collapse whitespace (including newlines) into single spaces to show more of the query
+        let collapsed: String = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+        if collapsed.chars().count() > max_len {
+            format!("{}...", collapsed.chars().take(max_len).collect::<String>())
+        } else {
+            collapsed
+        }
+    }
+
+    fn render_entry(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let Some(job) = JobsState::of(self.window_id, cx).jobs.get(ix) else {
+            return div().into_any_element();
+        };
+
+        let job_id = job.id;
+        let sql = job.sql.clone();
+        let truncated_sql = Self::truncate_sql(&sql, 40);
+        let status = job.status;
+        let cancel_requested = job.cancel_requested;
+        let has_result = job.result.is_some();
+        let unseen = !job.seen && status != JobStatus::Running;
+
+        let timing = match job.execution_time_ms {
+            Some(ms) => format!("{}ms", ms),
+            None => {
+                let elapsed = Local::now().signed_duration_since(job.started_at);
+                format!("running for {}s", elapsed.num_seconds())
+            }
+        };
+        let details = format!(
+            "{} • {} • {}",
+            job.connection_name,
+            job.started_at.format("%H:%M:%S"),
+            timing
+        );
+
+        let status_icon = match status {
+            JobStatus::Running => busy_indicator(cx),
+            JobStatus::Succeeded => Icon::new(IconName::CircleCheck)
+                .size_4()
+                .text_color(cx.theme().success)
+                .into_any_element(),
+            JobStatus::Failed => Icon::new(IconName::CircleX)
+                .size_4()
+                .text_color(cx.theme().danger)
+                .into_any_element(),
+            JobStatus::Cancelled => Icon::empty()
+                .path("icons/circle-pause.svg")
+                .size_4()
+                .text_color(cx.theme().muted_foreground)
+                .into_any_element(),
+        };
+
+        let bg_color = if ix % 2 == 0 {
+            cx.theme().list
+        } else {
+            cx.theme().list_even
+        };
+
+        let actions = h_flex()
+            .gap_1()
+            .when(status == JobStatus::Running, |el| {
+                el.child(
+                    Button::new(("cancel-job", ix))
+                        .icon(IconName::Close)
+                        .xsmall()
+                        .ghost()
+                        .tooltip(if cancel_requested {
+                            "Cancelling..."
+                        } else {
+                            "Cancel"
+                        })
+                        .disabled(cancel_requested)
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.cancel_job(job_id, cx);
+                        })),
+                )
+            })
+            .when(has_result, |el| {
+                el.child(
+                    Button::new(("show-job-result", ix))
+                        .icon(Icon::empty().path("icons/table-properties.svg"))
+                        .xsmall()
+                        .ghost()
+                        .tooltip("Show Result")
+                        .on_click(cx.listener(move |_this, _, _, cx| {
+                            cx.emit(JobsEvent::ShowResult(job_id));
+                        })),
+                )
+            })
+            .child(
+                Button::new(("open-job-sql", ix))
+                    .icon(Icon::empty().path("icons/square-terminal.svg"))
+                    .xsmall()
+                    .ghost()
+                    .tooltip("Open in Editor")
+                    .on_click(cx.listener(move |_this, _, _, cx| {
+                        cx.emit(JobsEvent::LoadQuery(sql.clone()));
+                    })),
+            );
+
+        div()
+            .p_1()
+            .child(
+                div()
+                    .id(("job-entry", ix))
+                    .w_full()
+                    .p_2()
+                    .bg(bg_color)
+                    .border_1()
+                    .border_color(if unseen {
+                        cx.theme().primary
+                    } else {
+                        cx.theme().border
+                    })
+                    .rounded(cx.theme().radius)
+                    .child(
+                        v_flex()
+                            .gap_1()
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .items_center()
+                                    .child(status_icon)
+                                    .child(
+                                        Label::new(truncated_sql)
+                                            .text_sm()
+                                            .font_medium()
+                                            .line_height(px(18.))
+                                            .flex_1(),
+                                    )
+                                    .child(actions),
+                            )
+                            .child(
+                                h_flex().pl(px(24.)).child(
+                                    Label::new(details)
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground),
+                                ),
+                            ),
+                    ),
+            )
+            .into_any_element()
+    }
+}
+
+impl Render for JobsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = JobsState::of(self.window_id, cx);
+        let job_count = state.jobs.len();
+        let running = state.running_count();
+
+        let clear_button = Button::new("clear-jobs")
+            .icon(Icon::empty().path("icons/trash.svg"))
+            .small()
+            .ghost()
+            .tooltip("Clear Finished Jobs")
+            .disabled(job_count == running)
+            .on_click(cx.listener(|this, _, _, cx| this.clear_finished(cx)));
+
+        let tabs = [("Background", false), ("Scheduled", true)]
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, (label, scheduled)| {
+                row.child(
+                    Button::new(label)
+                        .label(label)
+                        .xsmall()
+                        .ghost()
+                        .selected(self.show_scheduled == scheduled)
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.show_scheduled = scheduled;
+                            cx.notify();
+                        })),
+                )
+            });
+
+        let header = h_flex()
+            .justify_between()
+            .items_center()
+            .child(Label::new("Jobs").font_bold().text_base())
+            .child(tabs)
+            .when(!self.show_scheduled, |row| row.child(clear_button));
+
+        if self.show_scheduled {
+            return v_flex()
+                .size_full()
+                .gap_2()
+                .p_2()
+                .child(header)
+                .child(self.scheduled.clone());
+        }
+
+        let content = if job_count == 0 {
+            div().flex_1().flex().items_center().justify_center().child(
+                v_flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        Icon::empty()
+                            .path("icons/inbox.svg")
+                            .size_8()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .child(
+                        Label::new("No jobs yet")
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+        } else {
+            div().flex_1().overflow_hidden().child(
+                list(
+                    self.list_state.clone(),
+                    cx.processor(|this, ix, window, cx| this.render_entry(ix, window, cx)),
+                )
+                .size_full(),
+            )
+        };
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .p_2()
+            .child(header)
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(format!("{} running, {} total", running, job_count)),
+            )
+            .child(content)
+    }
+}
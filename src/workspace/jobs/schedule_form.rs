@@ -0,0 +1,262 @@
+use std::path::PathBuf;
+
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, IndexPath, Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    select::{Select, SelectState},
+};
+use uuid::Uuid;
+
+use crate::services::sql::sql_language;
+use crate::services::{ConnectionInfo, ScheduleFormat, ScheduledQuery};
+use crate::state::{ConnectionState, WindowConnection, WindowScoped, window_id};
+
+const INTERVAL_UNITS: [(&str, u32); 3] = [("minutes", 1), ("hours", 60), ("days", 24 * 60)];
+
+/// Dialog body for creating or editing a scheduled query.
+pub struct ScheduleForm {
+    existing: Option<ScheduledQuery>,
+    connections: Vec<ConnectionInfo>,
+    name_input: Entity<InputState>,
+    connection_select: Entity<SelectState<Vec<SharedString>>>,
+    sql_input: Entity<InputState>,
+    interval_input: Entity<InputState>,
+    unit_select: Entity<SelectState<Vec<SharedString>>>,
+    format: ScheduleFormat,
+    output_dir: Option<PathBuf>,
+}
+
+impl ScheduleForm {
+    pub fn view(
+        existing: Option<ScheduledQuery>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        cx.new(|cx| Self::new(existing, window, cx))
+    }
+
+    fn new(existing: Option<ScheduledQuery>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let connections = cx.global::<ConnectionState>().saved_connections.clone();
+        let active_connection = &WindowConnection::of(window_id(window), cx).active_connection;
+        let default_connection = existing
+            .as_ref()
+            .map(|q| q.connection_id)
+            .or_else(|| active_connection.as_ref().map(|c| c.id));
+
+        let names: Vec<SharedString> = connections.iter().map(|c| c.name.clone().into()).collect();
+        let selected = connections
+            .iter()
+            .position(|c| Some(c.id) == default_connection)
+            .or((!connections.is_empty()).then_some(0))
+            .map(IndexPath::new);
+        let connection_select = cx.new(|cx| SelectState::new(names, selected, window, cx));
+
+        // Show the interval in the largest unit it divides evenly into.
+        let minutes = existing.as_ref().map_or(60, |q| q.interval_minutes.max(1));
+        let unit_ix = INTERVAL_UNITS
+            .iter()
+            .rposition(|(_, per)| minutes % per == 0)
+            .unwrap_or(0);
+        let interval = minutes / INTERVAL_UNITS[unit_ix].1;
+        let units: Vec<SharedString> = INTERVAL_UNITS.iter().map(|(l, _)| (*l).into()).collect();
+        let unit_select =
+            cx.new(|cx| SelectState::new(units, Some(IndexPath::new(unit_ix)), window, cx));
+
+        let name = existing
+            .as_ref()
+            .map(|q| q.name.clone())
+            .unwrap_or_default();
+        let sql = existing.as_ref().map(|q| q.sql.clone()).unwrap_or_default();
+        let name_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Nightly signups")
+                .default_value(name)
+        });
+        let sql_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .code_editor(sql_language())
+                .soft_wrap(true)
+                .placeholder("SELECT ...")
+                .default_value(sql)
+        });
+        let interval_input =
+            cx.new(|cx| InputState::new(window, cx).default_value(interval.to_string()));
+
+        Self {
+            format: existing
+                .as_ref()
+                .map_or_else(ScheduleFormat::default, |q| q.format),
+            output_dir: existing
+                .as_ref()
+                .map(|q| q.output_dir.clone())
+                .or_else(dirs::download_dir),
+            existing,
+            connections,
+            name_input,
+            connection_select,
+            sql_input,
+            interval_input,
+            unit_select,
+        }
+    }
+
+    fn choose_folder(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("Write results to".into()),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = this.update(cx, |this, cx| {
+                this.output_dir = Some(path);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// The schedule described by the form, or what is missing from it.
+    pub fn scheduled_query(&self, cx: &App) -> Result<ScheduledQuery, &'static str> {
+        let name = self.name_input.read(cx).value().trim().to_string();
+        if name.is_empty() {
+            return Err("Give the schedule a name");
+        }
+        let connection = self
+            .connection_select
+            .read(cx)
+            .selected_value()
+            .and_then(|name| {
+                self.connections
+                    .iter()
+                    .find(|c| c.name.as_str() == name.as_ref())
+            })
+            .ok_or("Select a connection")?;
+        let sql = self.sql_input.read(cx).value().trim().to_string();
+        if sql.is_empty() {
+            return Err("Enter the SQL to run");
+        }
+        let interval = self
+            .interval_input
+            .read(cx)
+            .value()
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or("The interval must be a whole number above zero")?;
+        let per = self
+            .unit_select
+            .read(cx)
+            .selected_value()
+            .and_then(|unit| INTERVAL_UNITS.iter().find(|(l, _)| *l == unit.as_ref()))
+            .map_or(1, |(_, per)| *per);
+        let output_dir = self
+            .output_dir
+            .clone()
+            .ok_or("Choose a folder for results")?;
+
+        Ok(ScheduledQuery {
+            id: self.existing.as_ref().map_or_else(Uuid::new_v4, |q| q.id),
+            name,
+            connection_id: connection.id,
+            sql,
+            interval_minutes: interval.saturating_mul(per),
+            format: self.format,
+            output_dir,
+            enabled: self.existing.as_ref().is_none_or(|q| q.enabled),
+            last_run_at: self.existing.as_ref().and_then(|q| q.last_run_at),
+        })
+    }
+}
+
+impl Render for ScheduleForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let formats = [ScheduleFormat::Csv, ScheduleFormat::Json]
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, format| {
+                row.child(
+                    Button::new(format.as_str())
+                        .label(format.as_str().to_uppercase())
+                        .small()
+                        .ghost()
+                        .selected(self.format == format)
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.format = format;
+                            cx.notify();
+                        })),
+                )
+            });
+
+        let folder = self
+            .output_dir
+            .as_ref()
+            .map(|dir| dir.display().to_string());
+
+        v_form()
+            .small()
+            .child(
+                field()
+                    .label("Name")
+                    .required(true)
+                    .child(Input::new(&self.name_input)),
+            )
+            .child(
+                field().label("Connection").required(true).child(
+                    Select::new(&self.connection_select).placeholder("No saved connections"),
+                ),
+            )
+            .child(
+                field().label("SQL").required(true).child(
+                    div()
+                        .h(px(120.))
+                        .child(Input::new(&self.sql_input).h_full()),
+                ),
+            )
+            .child(
+                field().label("Every").required(true).child(
+                    h_flex()
+                        .gap_2()
+                        .child(div().w(px(80.)).child(Input::new(&self.interval_input)))
+                        .child(div().w(px(120.)).child(Select::new(&self.unit_select))),
+                ),
+            )
+            .child(field().label("Format").child(formats))
+            .child(
+                field().label("Folder").required(true).child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Label::new(folder.clone().unwrap_or_else(|| "None".to_string()))
+                                .flex_1()
+                                .text_sm()
+                                .when(folder.is_none(), |l| {
+                                    l.text_color(cx.theme().muted_foreground)
+                                }),
+                        )
+                        .child(
+                            Button::new("choose-schedule-folder")
+                                .label("Choose…")
+                                .small()
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.choose_folder(window, cx)
+                                })),
+                        ),
+                ),
+            )
+            .w_full()
+    }
+}
@@ -0,0 +1,330 @@
+use chrono::{Local, Utc};
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Icon, IconName, Sizable as _, StyledExt as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    notification::NotificationType,
+    switch::Switch,
+    v_flex,
+};
+
+use crate::{
+    services::{ScheduledQuery, export::ScheduledRunOutput},
+    state::{ConnectionState, ScheduledRun, ScheduledRunStatus, SchedulerState},
+    themes::busy_indicator,
+    workspace::notifications::Toasts as _,
+};
+
+use super::schedule_form::ScheduleForm;
+
+/// Scheduled queries with their next run, and the log of recent runs.
+pub struct ScheduledJobs {
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ScheduledJobs {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let _subscriptions = vec![
+            cx.observe_global::<SchedulerState>(|_, cx| cx.notify()),
+            cx.observe_global::<ConnectionState>(|_, cx| cx.notify()),
+        ];
+        Self { _subscriptions }
+    }
+
+    fn open_form(
+        &mut self,
+        existing: Option<ScheduledQuery>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let title = if existing.is_some() {
+            "Edit Schedule"
+        } else {
+            "New Schedule"
+        };
+        let form = ScheduleForm::view(existing, window, cx);
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            dialog
+                .title(title)
+                .width(px(560.))
+                .child(form.clone())
+                .confirm()
+                .on_ok(
+                    move |_, window, cx| match form.read(cx).scheduled_query(cx) {
+                        Ok(query) => {
+                            SchedulerState::save(query, cx);
+                            true
+                        }
+                        Err(message) => {
+                            window.push_toast((NotificationType::Warning, message), cx);
+                            false
+                        }
+                    },
+                )
+        });
+    }
+
+    fn confirm_delete(
+        &mut self,
+        query: ScheduledQuery,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let query_id = query.id;
+            dialog
+                .title("Delete Schedule")
+                .child(format!("Stop running \"{}\" and delete it?", query.name))
+                .confirm()
+                .on_ok(move |_, _window, cx| {
+                    SchedulerState::delete(query_id, cx);
+                    true
+                })
+        });
+    }
+
+    fn render_query(
+        &self,
+        ix: usize,
+        query: &ScheduledQuery,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let query_id = query.id;
+        let enabled = query.enabled;
+        let running = cx.global::<SchedulerState>().is_running(query_id);
+        let connection_name = cx
+            .global::<ConnectionState>()
+            .saved_connections
+            .iter()
+            .find(|c| c.id == query.connection_id)
+            .map_or_else(|| "Missing connection".to_string(), |c| c.name.clone());
+
+        let next = match query.next_run_at() {
+            _ if running => "running now".to_string(),
+            None => "paused".to_string(),
+            Some(next) if next <= Utc::now() => "next run due".to_string(),
+            Some(next) => format!(
+                "next at {}",
+                next.with_timezone(&Local).format("%b %-d %H:%M")
+            ),
+        };
+        let details = format!(
+            "{} • {} • {} • {}",
+            connection_name,
+            query.interval_label(),
+            query.format.as_str().to_uppercase(),
+            next
+        );
+
+        let edit_query = query.clone();
+        let delete_query = query.clone();
+        let actions = h_flex()
+            .gap_1()
+            .child(
+                Switch::new(("schedule-enabled", ix))
+                    .checked(enabled)
+                    .xsmall()
+                    .tooltip(if enabled { "Pause" } else { "Resume" })
+                    .on_click(move |checked: &bool, _, cx| {
+                        SchedulerState::set_enabled(query_id, *checked, cx);
+                    }),
+            )
+            .child(
+                Button::new(("run-schedule", ix))
+                    .icon(Icon::empty().path("icons/play.svg"))
+                    .xsmall()
+                    .ghost()
+                    .tooltip("Run Now")
+                    .loading(running)
+                    .on_click(move |_, _, cx| SchedulerState::run_now(query_id, cx)),
+            )
+            .child(
+                Button::new(("edit-schedule", ix))
+                    .icon(Icon::empty().path("icons/pencil-line.svg"))
+                    .xsmall()
+                    .ghost()
+                    .tooltip("Edit")
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.open_form(Some(edit_query.clone()), window, cx);
+                    })),
+            )
+            .child(
+                Button::new(("delete-schedule", ix))
+                    .icon(Icon::empty().path("icons/trash.svg"))
+                    .xsmall()
+                    .ghost()
+                    .tooltip("Delete")
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.confirm_delete(delete_query.clone(), window, cx);
+                    })),
+            );
+
+        v_flex()
+            .id(("scheduled-query", ix))
+            .gap_1()
+            .p_2()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded(cx.theme().radius)
+            .bg(cx.theme().list)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        Label::new(query.name.clone())
+                            .text_sm()
+                            .font_medium()
+                            .flex_1()
+                            .when(!enabled, |l| l.text_color(cx.theme().muted_foreground)),
+                    )
+                    .child(actions),
+            )
+            .child(
+                Label::new(details)
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .into_any_element()
+    }
+
+    fn render_run(&self, ix: usize, run: &ScheduledRun, cx: &mut Context<Self>) -> AnyElement {
+        let icon = match &run.status {
+            ScheduledRunStatus::Running => busy_indicator(cx),
+            ScheduledRunStatus::Succeeded(_) => Icon::new(IconName::CircleCheck)
+                .size_4()
+                .text_color(cx.theme().success)
+                .into_any_element(),
+            ScheduledRunStatus::Failed(_) => Icon::new(IconName::CircleX)
+                .size_4()
+                .text_color(cx.theme().danger)
+                .into_any_element(),
+        };
+        let timing = run
+            .duration_ms
+            .map_or_else(String::new, |ms| format!(" • {}ms", ms));
+        let (outcome, path) = match &run.status {
+            ScheduledRunStatus::Running => ("Running…".to_string(), None),
+            ScheduledRunStatus::Succeeded(ScheduledRunOutput::Rows { path, row_count }) => (
+                format!(
+                    "{} rows to {}",
+                    row_count,
+                    path.file_name()
+                        .map_or_else(String::new, |n| n.to_string_lossy().to_string())
+                ),
+                Some(path.clone()),
+            ),
+            ScheduledRunStatus::Succeeded(ScheduledRunOutput::Modified { rows_affected }) => {
+                (format!("{} rows affected", rows_affected), None)
+            }
+            ScheduledRunStatus::Failed(message) => (message.clone(), None),
+        };
+        let failed = matches!(run.status, ScheduledRunStatus::Failed(_));
+
+        h_flex()
+            .id(("scheduled-run", ix))
+            .gap_2()
+            .items_start()
+            .px_1()
+            .py_0p5()
+            .child(icon)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(
+                        Label::new(format!(
+                            "{} • {}{}",
+                            run.query_name,
+                            run.started_at.format("%H:%M:%S"),
+                            timing
+                        ))
+                        .text_xs(),
+                    )
+                    .child(Label::new(outcome).text_xs().text_color(if failed {
+                        cx.theme().danger
+                    } else {
+                        cx.theme().muted_foreground
+                    })),
+            )
+            .when_some(path, |row, path| {
+                row.child(
+                    Button::new(("reveal-scheduled-run", ix))
+                        .icon(Icon::empty().path("icons/folder-open.svg"))
+                        .xsmall()
+                        .ghost()
+                        .tooltip("Show in Folder")
+                        .on_click(move |_, _, cx| cx.reveal_path(&path)),
+                )
+            })
+            .into_any_element()
+    }
+}
+
+impl Render for ScheduledJobs {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = cx.global::<SchedulerState>();
+        let queries = state.queries.clone();
+        let runs = state.runs.clone();
+
+        let query_rows = queries
+            .iter()
+            .enumerate()
+            .map(|(ix, query)| self.render_query(ix, query, cx))
+            .collect::<Vec<_>>();
+        let run_rows = runs
+            .iter()
+            .enumerate()
+            .map(|(ix, run)| self.render_run(ix, run, cx))
+            .collect::<Vec<_>>();
+
+        let muted = cx.theme().muted_foreground;
+        v_flex()
+            .id("scheduled-jobs")
+            .flex_1()
+            .gap_2()
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(muted)
+                            .child(format!("{} scheduled", queries.len())),
+                    )
+                    .child(
+                        Button::new("new-schedule")
+                            .icon(IconName::Plus)
+                            .label("New Schedule")
+                            .xsmall()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.open_form(None, window, cx);
+                            })),
+                    ),
+            )
+            .when(queries.is_empty(), |col| {
+                col.child(
+                    Label::new("Run a query every few minutes, hours or days and write the rows to a file.")
+                        .text_xs()
+                        .text_color(muted),
+                )
+            })
+            .children(query_rows)
+            .when(!runs.is_empty(), |col| {
+                col.child(Label::new("Recent runs").text_sm().font_medium().pt_2())
+                    .children(run_rows)
+            })
+    }
+}
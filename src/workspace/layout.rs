@@ -0,0 +1,184 @@
+use gpui::*;
+use gpui_component::{ActiveTheme as _, h_flex};
+use serde::{Deserialize, Serialize};
+
+use crate::services::i18n::tr;
+
+actions!(workspace, [ToggleZenMode]);
+
+/// Workspace state key for the side panel docks and widths.
+pub const PANEL_LAYOUT_KEY: &str = "panel_layout";
+
+pub const MIN_PANEL_WIDTH: f32 = 200.;
+pub const MAX_PANEL_WIDTH: f32 = 900.;
+
+/// Panels that can be docked beside the editor and results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SidePanel {
+    Tables,
+    Agent,
+    History,
+    Jobs,
+}
+
+impl SidePanel {
+    pub const ALL: [SidePanel; 4] = [
+        SidePanel::Tables,
+        SidePanel::Agent,
+        SidePanel::History,
+        SidePanel::Jobs,
+    ];
+
+    /// Stable name, used in element ids.
+    pub fn key(&self) -> &'static str {
+        match self {
+            SidePanel::Tables => "tables",
+            SidePanel::Agent => "agent",
+            SidePanel::History => "history",
+            SidePanel::Jobs => "jobs",
+        }
+    }
+
+    pub fn title(&self) -> String {
+        tr(&format!("panel-{}", self.key()))
+    }
+
+    fn default_placement(self) -> PanelPlacement {
+        let (dock, width) = match self {
+            SidePanel::Tables => (PanelDock::Left, 300.),
+            _ => (PanelDock::Right, 400.),
+        };
+        PanelPlacement {
+            panel: self,
+            dock,
+            width,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanelDock {
+    Left,
+    Right,
+}
+
+impl PanelDock {
+    pub fn other(&self) -> PanelDock {
+        match self {
+            PanelDock::Left => PanelDock::Right,
+            PanelDock::Right => PanelDock::Left,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PanelPlacement {
+    pub panel: SidePanel,
+    pub dock: PanelDock,
+    pub width: f32,
+}
+
+/// Which dock each side panel sits in, left to right, and how wide it is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanelLayout {
+    placements: Vec<PanelPlacement>,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            placements: SidePanel::ALL
+                .into_iter()
+                .map(SidePanel::default_placement)
+                .collect(),
+        }
+    }
+}
+
+impl PanelLayout {
+    /// Fill in panels missing from a saved layout and drop duplicates, so
+    /// every panel is placed exactly once.
+    pub fn normalized(mut self) -> Self {
+        let mut seen = Vec::new();
+        self.placements.retain(|placement| {
+            if seen.contains(&placement.panel) {
+                return false;
+            }
+            seen.push(placement.panel);
+            true
+        });
+        for panel in SidePanel::ALL {
+            if !seen.contains(&panel) {
+                self.placements.push(panel.default_placement());
+            }
+        }
+        for placement in &mut self.placements {
+            placement.width = placement.width.clamp(MIN_PANEL_WIDTH, MAX_PANEL_WIDTH);
+        }
+        self
+    }
+
+    /// Panels in `dock`, left to right.
+    pub fn docked(&self, dock: PanelDock) -> impl Iterator<Item = &PanelPlacement> {
+        self.placements.iter().filter(move |p| p.dock == dock)
+    }
+
+    pub fn placement(&self, panel: SidePanel) -> PanelPlacement {
+        self.placements
+            .iter()
+            .find(|p| p.panel == panel)
+            .copied()
+            .unwrap_or_else(|| panel.default_placement())
+    }
+
+    /// Move `panel` into `dock`, just before `before`, or at the end of the
+    /// dock when `before` is `None` or not in that dock.
+    pub fn move_panel(&mut self, panel: SidePanel, dock: PanelDock, before: Option<SidePanel>) {
+        if before == Some(panel) {
+            return;
+        }
+        let mut placement = self.placement(panel);
+        self.placements.retain(|p| p.panel != panel);
+        placement.dock = dock;
+
+        let at = before
+            .and_then(|before| {
+                self.placements
+                    .iter()
+                    .position(|p| p.panel == before && p.dock == dock)
+            })
+            .or_else(|| {
+                self.placements
+                    .iter()
+                    .rposition(|p| p.dock == dock)
+                    .map(|ix| ix + 1)
+            })
+            .unwrap_or(self.placements.len());
+        self.placements.insert(at, placement);
+    }
+
+    pub fn set_width(&mut self, panel: SidePanel, width: f32) {
+        if let Some(placement) = self.placements.iter_mut().find(|p| p.panel == panel) {
+            placement.width = width.clamp(MIN_PANEL_WIDTH, MAX_PANEL_WIDTH);
+        }
+    }
+}
+
+/// Drag payload for a side panel's header, shown as a small tag under the
+/// cursor.
+#[derive(Clone)]
+pub struct DraggedPanel(pub SidePanel);
+
+impl Render for DraggedPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .rounded(cx.theme().radius)
+            .border_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().popover)
+            .text_xs()
+            .child(self.0.title())
+    }
+}
@@ -0,0 +1,288 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Root, Selectable as _, Sizable as _, TitleBar,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::services::diagnostics::{
+    LogEntry, LogLevel, crash_reports, diagnostics_bundle, logs_dir, read_log_entries,
+};
+use crate::workspace::notifications::{Toasts as _, render_toast_layer};
+
+actions!(log_viewer, [ViewLogs]);
+
+/// Entries read from the log files.
+const MAX_ENTRIES: usize = 5_000;
+
+/// Matching entries shown, newest last.
+const MAX_ROWS: usize = 1_000;
+
+/// Open the log viewer in its own window.
+pub fn open_log_viewer(cx: &mut App) {
+    let options = WindowOptions {
+        window_bounds: Some(WindowBounds::Windowed(Bounds::centered(
+            None,
+            size(px(960.), px(640.)),
+            cx,
+        ))),
+        titlebar: Some(TitleBar::title_bar_options()),
+        window_decorations: Some(WindowDecorations::Client),
+        ..Default::default()
+    };
+    if let Err(e) = cx.open_window(options, |window, cx| {
+        let viewer = LogViewer::view(window, cx);
+        cx.new(|cx| Root::new(viewer, window, cx))
+    }) {
+        tracing::error!("Failed to open log viewer: {}", e);
+    }
+}
+
+/// Entries from `~/.pgui/logs`, filtered by minimum level and text, with a
+/// button copying the redacted diagnostics bundle for bug reports.
+pub struct LogViewer {
+    entries: Vec<LogEntry>,
+    crash_count: usize,
+    level: LogLevel,
+    filter_input: Entity<InputState>,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LogViewer {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let filter_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Filter by message or target"));
+        let _subscriptions = vec![cx.subscribe(&filter_input, |_, _, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                cx.notify();
+            }
+        })];
+
+        let mut this = Self {
+            entries: vec![],
+            crash_count: 0,
+            level: LogLevel::Info,
+            filter_input,
+            error: None,
+            _subscriptions,
+        };
+        this.reload(cx);
+        this
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let result = smol::unblock(|| {
+                let dir = logs_dir()?;
+                let entries = read_log_entries(&dir, MAX_ENTRIES)?;
+                anyhow::Ok((entries, crash_reports(&dir).len()))
+            })
+            .await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok((entries, crash_count)) => {
+                        this.entries = entries;
+                        this.crash_count = crash_count;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("Failed to read logs: {}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn copy_bundle(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Ok(dir) = logs_dir() else {
+            return;
+        };
+        cx.spawn_in(window, async move |_this, cx| {
+            let bundle = smol::unblock(move || diagnostics_bundle(&dir)).await;
+            let _ = cx.update(|window, cx| {
+                cx.write_to_clipboard(ClipboardItem::new_string(bundle));
+                window.push_toast(
+                    (
+                        NotificationType::Success,
+                        "Diagnostics bundle copied to the clipboard",
+                    ),
+                    cx,
+                );
+            });
+        })
+        .detach();
+    }
+
+    fn open_folder(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Ok(dir) = logs_dir() {
+            cx.reveal_path(&dir);
+        }
+    }
+
+    fn visible_entries(&self, cx: &App) -> Vec<&LogEntry> {
+        let filter = self.filter_input.read(cx).value().trim().to_lowercase();
+        let mut visible: Vec<&LogEntry> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.level <= self.level)
+            .filter(|entry| {
+                filter.is_empty()
+                    || entry.message.to_lowercase().contains(&filter)
+                    || entry.target.to_lowercase().contains(&filter)
+            })
+            .take(MAX_ROWS)
+            .collect();
+        visible.reverse();
+        visible
+    }
+}
+
+impl Render for LogViewer {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let levels = LogLevel::ALL
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, level| {
+                row.child(
+                    Button::new(level.label())
+                        .label(level.label())
+                        .xsmall()
+                        .ghost()
+                        .selected(self.level == level)
+                        .tooltip(format!("Show {} and more severe", level.label()))
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.level = level;
+                            cx.notify();
+                        })),
+                )
+            });
+
+        let theme = cx.theme();
+        let rows = self
+            .visible_entries(cx)
+            .into_iter()
+            .enumerate()
+            .map(|(ix, entry)| {
+                let color = match entry.level {
+                    LogLevel::Error => theme.danger,
+                    LogLevel::Warn => theme.warning,
+                    LogLevel::Info => theme.foreground,
+                    LogLevel::Debug | LogLevel::Trace => theme.muted_foreground,
+                };
+                h_flex()
+                    .id(("log-entry", ix))
+                    .items_start()
+                    .gap_2()
+                    .px_2()
+                    .py_0p5()
+                    .font_family("monospace")
+                    .text_xs()
+                    .child(
+                        div()
+                            .flex_none()
+                            .text_color(theme.muted_foreground)
+                            .child(entry.timestamp.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(44.))
+                            .text_color(color)
+                            .child(entry.level.label()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .text_color(theme.muted_foreground)
+                            .child(entry.target.clone()),
+                    )
+                    .child(div().flex_1().child(entry.message.clone()))
+            })
+            .collect::<Vec<_>>();
+        let empty = rows.is_empty();
+
+        let status = self.error.clone().or_else(|| {
+            (self.crash_count > 0).then(|| {
+                format!(
+                    "{} crash report{} in the logs folder",
+                    self.crash_count,
+                    if self.crash_count == 1 { "" } else { "s" }
+                )
+            })
+        });
+
+        v_flex()
+            .size_full()
+            .child(TitleBar::new().child(Label::new("PGUI Logs").text_xs()))
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(levels)
+                    .child(div().flex_1().child(Input::new(&self.filter_input).small()))
+                    .child(
+                        Button::new("reload-logs")
+                            .label("Refresh")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _win, cx| this.reload(cx))),
+                    )
+                    .child(
+                        Button::new("open-logs-folder")
+                            .label("Open Folder")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(Self::open_folder)),
+                    )
+                    .child(
+                        Button::new("copy-diagnostics")
+                            .label("Copy Diagnostics Bundle")
+                            .small()
+                            .primary()
+                            .tooltip("Recent logs and crash reports, with secrets redacted")
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.copy_bundle(window, cx)),
+                            ),
+                    ),
+            )
+            .when_some(status, |col, status| {
+                col.child(
+                    Label::new(status)
+                        .px_2()
+                        .pt_1()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .child(
+                div()
+                    .id("log-entries")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .py_1()
+                    .children(rows)
+                    .when(empty, |d| {
+                        d.child(
+                            Label::new("No matching log entries")
+                                .p_2()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    }),
+            )
+            .children(render_toast_layer(window, cx))
+    }
+}
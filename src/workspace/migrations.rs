@@ -0,0 +1,506 @@
+use std::path::PathBuf;
+
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement,
+    PathPromptOptions, Render, SharedString, StatefulInteractiveElement as _, Styled, Window,
+    WindowId, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Sizable as _, StyledExt as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    v_flex,
+};
+
+use crate::{
+    services::{
+        DEFAULT_TRACKING_TABLE, DatabaseManager, MigrationEntry, MigrationFile, MigrationSettings,
+        MigrationState, load_migrations, migration_plan,
+    },
+    state::{MigrationsState, WindowConnection, WindowScoped, window_id},
+};
+
+/// What applying one migration did, for the log under the list.
+struct MigrationLog {
+    file_name: String,
+    result: Result<u128, String>,
+}
+
+/// Dialog body listing the migrations of a local directory against the
+/// tracking table of the open connection, and applying the pending ones
+/// in order.
+pub struct MigrationsPanel {
+    window_id: WindowId,
+    db_manager: DatabaseManager,
+    directory: Option<PathBuf>,
+    table_input: Entity<InputState>,
+    entries: Vec<MigrationEntry>,
+    logs: Vec<MigrationLog>,
+    loading: bool,
+    applying: bool,
+    error: Option<String>,
+}
+
+impl MigrationsPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let window_id = window_id(window);
+        let db_manager = WindowConnection::of(window_id, cx).db_manager.clone();
+        let settings = MigrationsState::active(window_id, cx);
+        let table_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(DEFAULT_TRACKING_TABLE)
+                .default_value(settings.tracking_table.clone())
+        });
+
+        let mut this = Self {
+            window_id,
+            db_manager,
+            directory: settings.directory,
+            table_input,
+            entries: vec![],
+            logs: vec![],
+            loading: false,
+            applying: false,
+            error: None,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn settings(&self, cx: &App) -> MigrationSettings {
+        MigrationSettings {
+            directory: self.directory.clone(),
+            tracking_table: self.table_input.read(cx).value().trim().to_string(),
+        }
+    }
+
+    /// Remember the directory and tracking table for the open connection.
+    fn save_settings(&self, cx: &mut App) {
+        let settings = self.settings(cx);
+        let connection_id = WindowConnection::of(self.window_id, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| c.id);
+        if let Some(connection_id) = connection_id {
+            MigrationsState::set(connection_id, settings, cx);
+        }
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        let Some(directory) = self.directory.clone() else {
+            self.entries.clear();
+            return;
+        };
+        let table = match self.settings(cx).tracking_table() {
+            Ok(table) => table,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                cx.notify();
+                return;
+            }
+        };
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = match smol::unblock(move || load_migrations(&directory)).await {
+                Ok(files) => db_manager
+                    .applied_migrations(&table)
+                    .await
+                    .map(|applied| migration_plan(&files, &applied)),
+                Err(e) => Err(e),
+            };
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(entries) => {
+                        this.entries = entries;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn choose_directory(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("Use Migrations".into()),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = this.update(cx, |this, cx| {
+                this.directory = Some(path);
+                this.logs.clear();
+                this.save_settings(cx);
+                this.load(cx);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        self.save_settings(cx);
+        self.load(cx);
+        cx.notify();
+    }
+
+    fn pending(&self) -> Vec<MigrationFile> {
+        self.entries
+            .iter()
+            .filter(|e| e.state == MigrationState::Pending)
+            .filter_map(|e| e.file.clone())
+            .collect()
+    }
+
+    /// Ask for confirmation, then apply the pending migrations in order.
+    fn confirm_apply(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let pending = self.pending();
+        let database = WindowConnection::of(self.window_id, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| c.database.clone())
+            .unwrap_or_default();
+        let names = pending
+            .iter()
+            .map(|f| f.file_name.clone())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let panel = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            let pending = pending.clone();
+            dialog
+                .title("Apply Migrations")
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Apply"))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(
+                            Label::new(format!(
+                                "Apply {} to {}, each in its own transaction, stopping at \
+                                 the first that fails?",
+                                migrations(pending.len()),
+                                database
+                            ))
+                            .text_sm(),
+                        )
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(names.clone()),
+                        ),
+                )
+                .on_ok(move |_, _window, cx| {
+                    let pending = pending.clone();
+                    let _ = panel.update(cx, |this, cx| this.apply(pending, cx));
+                    true
+                })
+        });
+    }
+
+    fn apply(&mut self, pending: Vec<MigrationFile>, cx: &mut Context<Self>) {
+        if self.applying {
+            return;
+        }
+        let table = match self.settings(cx).tracking_table() {
+            Ok(table) => table,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                cx.notify();
+                return;
+            }
+        };
+        self.applying = true;
+        self.logs.clear();
+        self.save_settings(cx);
+        cx.notify();
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            for file in pending {
+                let result = db_manager
+                    .apply_migration(&table, &file)
+                    .await
+                    .map_err(|e| format!("{}", e));
+                let failed = result.is_err();
+                let _ = this.update(cx, |this, cx| {
+                    this.logs.push(MigrationLog {
+                        file_name: file.file_name.clone(),
+                        result,
+                    });
+                    cx.notify();
+                });
+                if failed {
+                    break;
+                }
+            }
+            let _ = this.update(cx, |this, cx| {
+                this.applying = false;
+                this.load(cx);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn render_entry(
+        &self,
+        ix: usize,
+        entry: &MigrationEntry,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let color = match entry.state {
+            MigrationState::Applied => cx.theme().success,
+            MigrationState::Pending => cx.theme().accent_foreground,
+            MigrationState::Changed | MigrationState::Missing => cx.theme().warning,
+        };
+        let details = match (&entry.file, &entry.applied_at) {
+            (Some(file), Some(applied_at)) => {
+                format!("{} · applied {}", file.file_name, applied_at)
+            }
+            (Some(file), None) => file.file_name.clone(),
+            (None, Some(applied_at)) => format!("file not found · applied {}", applied_at),
+            (None, None) => String::new(),
+        };
+        let note = match entry.state {
+            MigrationState::Changed => Some("edited since it was applied"),
+            _ => None,
+        };
+
+        h_flex()
+            .id(("migration", ix))
+            .w_full()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div().w(px(64.)).child(
+                    Label::new(entry.state.label())
+                        .text_xs()
+                        .font_medium()
+                        .text_color(color),
+                ),
+            )
+            .child(
+                div()
+                    .w(px(120.))
+                    .font_family("monospace")
+                    .text_sm()
+                    .child(entry.version.clone()),
+            )
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .child(Label::new(entry.description.clone()).text_sm())
+                    .child(
+                        Label::new(details)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .when_some(note, |d, note| {
+                        d.child(Label::new(note).text_xs().text_color(cx.theme().warning))
+                    }),
+            )
+    }
+}
+
+impl Render for MigrationsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let count =
+            |state: MigrationState| self.entries.iter().filter(|e| e.state == state).count();
+        let pending = count(MigrationState::Pending);
+        let mut summary = format!(
+            "{} applied · {} pending",
+            count(MigrationState::Applied),
+            pending
+        );
+        for state in [MigrationState::Changed, MigrationState::Missing] {
+            if count(state) > 0 {
+                summary.push_str(&format!(
+                    " · {} {}",
+                    count(state),
+                    state.label().to_lowercase()
+                ));
+            }
+        }
+        if self.applying {
+            summary = "Applying migrations...".to_string();
+        } else if self.loading && self.entries.is_empty() {
+            summary = "Loading migrations...".to_string();
+        }
+
+        let directory: SharedString = match &self.directory {
+            Some(directory) => directory.display().to_string().into(),
+            None => "No directory chosen".into(),
+        };
+
+        let rows = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| self.render_entry(ix, entry, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let logs = self
+            .logs
+            .iter()
+            .map(|log| {
+                let (text, color) = match &log.result {
+                    Ok(ms) => (
+                        format!("Applied {} in {} ms", log.file_name, ms),
+                        cx.theme().success,
+                    ),
+                    Err(e) => (
+                        format!("{} failed: {}", log.file_name, e),
+                        cx.theme().danger,
+                    ),
+                };
+                Label::new(text)
+                    .text_xs()
+                    .text_color(color)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(div().w(px(110.)).child(Label::new("Directory").text_sm()))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .text_sm()
+                            .font_family("monospace")
+                            .when(self.directory.is_none(), |d| {
+                                d.text_color(cx.theme().muted_foreground)
+                            })
+                            .child(directory),
+                    )
+                    .child(
+                        Button::new("choose-migrations-directory")
+                            .icon(Icon::empty().path("icons/folder-open.svg"))
+                            .label("Choose…")
+                            .small()
+                            .ghost()
+                            .disabled(self.applying)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.choose_directory(window, cx);
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(
+                        div()
+                            .w(px(110.))
+                            .child(Label::new("Tracking table").text_sm()),
+                    )
+                    .child(div().flex_1().child(Input::new(&self.table_input).small()))
+                    .child(
+                        Button::new("reload-migrations")
+                            .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip("Reload")
+                            .disabled(self.applying)
+                            .on_click(cx.listener(|this, _, _window, cx| this.reload(cx))),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        Button::new("apply-migrations")
+                            .icon(Icon::empty().path("icons/play.svg"))
+                            .label("Apply Pending")
+                            .small()
+                            .primary()
+                            .disabled(pending == 0 || self.applying || self.loading)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.confirm_apply(window, cx);
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("migration-rows")
+                    .max_h(px(360.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .when(rows.is_empty() && !self.loading, |d| {
+                        d.child(
+                            div().p_2().child(
+                                Label::new(if self.directory.is_some() {
+                                    "No migrations in this directory"
+                                } else {
+                                    "Choose a directory of versioned .sql migrations"
+                                })
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                            ),
+                        )
+                    })
+                    .children(rows),
+            )
+            .when(!logs.is_empty(), |d| {
+                d.child(
+                    v_flex()
+                        .gap_1()
+                        .child(Label::new("Log").text_sm())
+                        .children(logs),
+                )
+            })
+    }
+}
+
+fn migrations(count: usize) -> String {
+    format!("{} migration{}", count, if count == 1 { "" } else { "s" })
+}
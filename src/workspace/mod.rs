@@ -1,11 +1,46 @@
 mod agent;
+mod batch_run;
+mod change_script;
 mod connections;
+mod database_admin;
 mod editor;
+mod error_explanation;
 mod footer_bar;
 mod header_bar;
 mod history;
+mod jobs;
+mod layout;
+mod log_viewer;
+mod migrations;
+mod notifications;
+mod performance;
+mod query_params_form;
 mod results;
+mod roles;
+mod schema_changes;
+mod server_settings;
+mod snapshot;
+mod snippets_form;
+mod table_growth;
 mod tables;
+mod tablespaces;
+mod theme_dialog;
+mod update_dialog;
+mod variables_form;
 mod workspace;
 
+pub use editor::{
+    OpenFile, OpenRecentFile, RunScript, RunSelection, RunStatement, SaveFile, SaveFileAs,
+};
+pub use history::{
+    QuickSlot1, QuickSlot2, QuickSlot3, QuickSlot4, QuickSlot5, QuickSlot6, QuickSlot7,
+    QuickSlot8, QuickSlot9,
+};
+pub use layout::ToggleZenMode;
+pub use log_viewer::{ViewLogs, open_log_viewer};
+pub use results::{
+    CellDown, CellFirstRow, CellLastRow, CellLeft, CellPageDown, CellPageUp, CellRight,
+    CellRowEnd, CellRowStart, CellUp, InspectCell, ToggleRowDetail,
+};
+pub use tables::{SearchDatabase, SelectItem};
 pub use workspace::*;
@@ -0,0 +1,440 @@
+use std::{collections::HashMap, rc::Rc, time::Duration};
+
+use gpui::{
+    AnyElement, App, AppContext, ClickEvent, Context, Entity, EventEmitter, Global, Hsla,
+    InteractiveElement as _, IntoElement, ParentElement, Render, SharedString,
+    StatefulInteractiveElement as _, Styled, Subscription, Window, WindowId, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, IconName, Root, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    notification::{Notification, NotificationType},
+    v_flex,
+};
+
+use crate::state::{
+    NotificationAction, NotificationEntry, NotificationSource, NotificationsState, window_id,
+};
+use crate::themes::Appearance;
+
+/// How long a toast stays up when motion is reduced.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+type ToastClick = Rc<dyn Fn(&ClickEvent, &mut Window, &mut App)>;
+
+/// A toast: a message with an optional kind, title and click action.
+pub struct Toast {
+    kind: Option<NotificationType>,
+    title: Option<SharedString>,
+    message: SharedString,
+    on_click: Option<ToastClick>,
+}
+
+impl Toast {
+    pub fn new(kind: NotificationType, message: impl Into<SharedString>) -> Self {
+        Self {
+            kind: Some(kind),
+            title: None,
+            message: message.into(),
+            on_click: None,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<SharedString>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn on_click(
+        mut self,
+        on_click: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_click = Some(Rc::new(on_click));
+        self
+    }
+
+    fn into_notification(self) -> Notification {
+        let mut notification = Notification::new().message(self.message);
+        if let Some(kind) = self.kind {
+            notification = notification.with_type(kind);
+        }
+        if let Some(title) = self.title {
+            notification = notification.title(title);
+        }
+        if let Some(on_click) = self.on_click {
+            notification = notification.on_click(move |event, window, cx| {
+                on_click(event, window, cx);
+            });
+        }
+        notification
+    }
+}
+
+impl<S: Into<SharedString>> From<(NotificationType, S)> for Toast {
+    fn from((kind, message): (NotificationType, S)) -> Self {
+        Self::new(kind, message)
+    }
+}
+
+impl From<&'static str> for Toast {
+    fn from(message: &'static str) -> Self {
+        Self {
+            kind: None,
+            title: None,
+            message: message.into(),
+            on_click: None,
+        }
+    }
+}
+
+/// Toasts shown in place of the animated notification layer while motion
+/// is reduced, per window.
+#[derive(Default)]
+struct StillToasts {
+    by_window: HashMap<WindowId, Vec<(usize, Toast)>>,
+    next_id: usize,
+}
+
+impl Global for StillToasts {}
+
+impl StillToasts {
+    fn dismiss(window: WindowId, id: usize, cx: &mut App) {
+        if let Some(toasts) = cx
+            .default_global::<StillToasts>()
+            .by_window
+            .get_mut(&window)
+        {
+            toasts.retain(|(toast_id, _)| *toast_id != id);
+        }
+    }
+}
+
+/// Shows toasts: on the notification layer, which slides and fades them in
+/// and out, or without any motion when reduced motion is on.
+pub trait Toasts {
+    fn push_toast(&mut self, toast: impl Into<Toast>, cx: &mut App);
+}
+
+impl Toasts for Window {
+    fn push_toast(&mut self, toast: impl Into<Toast>, cx: &mut App) {
+        let toast = toast.into();
+        if !Appearance::global(cx).reduce_motion {
+            self.push_notification(toast.into_notification(), cx);
+            return;
+        }
+
+        let window = window_id(self);
+        let toasts = cx.default_global::<StillToasts>();
+        let id = toasts.next_id;
+        toasts.next_id += 1;
+        toasts
+            .by_window
+            .entry(window)
+            .or_default()
+            .push((id, toast));
+        self.refresh();
+
+        self.spawn(cx, async move |cx| {
+            cx.background_executor().timer(TOAST_DURATION).await;
+            let _ = cx.update(|window, cx| {
+                StillToasts::dismiss(window_id(window), id, cx);
+                window.refresh();
+            });
+        })
+        .detach();
+    }
+}
+
+/// The toasts of `window`: the notification layer, or the toasts pushed
+/// while motion is reduced, drawn in place.
+pub fn render_toast_layer(window: &mut Window, cx: &mut App) -> Option<AnyElement> {
+    if !Appearance::global(cx).reduce_motion {
+        return Root::render_notification_layer(window, cx).map(IntoElement::into_any_element);
+    }
+
+    let window = window_id(window);
+    let toasts = cx.try_global::<StillToasts>()?.by_window.get(&window)?;
+    if toasts.is_empty() {
+        return None;
+    }
+    let toasts = toasts
+        .iter()
+        .map(|(id, toast)| render_still_toast(*id, toast, cx))
+        .collect::<Vec<_>>();
+
+    Some(
+        v_flex()
+            .absolute()
+            .top_0()
+            .right_0()
+            .p_4()
+            .gap_2()
+            .w(px(420.))
+            .children(toasts)
+            .into_any_element(),
+    )
+}
+
+fn render_still_toast(id: usize, toast: &Toast, cx: &App) -> AnyElement {
+    let icon = toast.kind.as_ref().map(|kind| {
+        let (path, color) = match kind {
+            NotificationType::Info => ("icons/info.svg", cx.theme().info),
+            NotificationType::Success => ("icons/circle-check.svg", cx.theme().success),
+            NotificationType::Warning => ("icons/triangle-alert.svg", cx.theme().warning),
+            NotificationType::Error => ("icons/circle-x.svg", cx.theme().danger),
+        };
+        div()
+            .pt_0p5()
+            .child(Icon::empty().path(path).size_4().text_color(color))
+    });
+    let on_click = toast.on_click.clone();
+
+    h_flex()
+        .id(("still-toast", id))
+        .w_full()
+        .gap_2()
+        .p_3()
+        .items_start()
+        .rounded(cx.theme().radius)
+        .border_1()
+        .border_color(cx.theme().border)
+        .bg(cx.theme().popover)
+        .shadow_md()
+        .children(icon)
+        .child(
+            v_flex()
+                .flex_1()
+                .min_w_0()
+                .gap_0p5()
+                .when_some(toast.title.clone(), |d, title| {
+                    d.child(Label::new(title).text_sm().font_semibold())
+                })
+                .child(Label::new(toast.message.clone()).text_sm()),
+        )
+        .child(
+            Button::new(("dismiss-still-toast", id))
+                .icon(IconName::Close)
+                .xsmall()
+                .ghost()
+                .on_click(move |_, window, cx| {
+                    StillToasts::dismiss(window_id(window), id, cx);
+                    window.refresh();
+                }),
+        )
+        .when_some(on_click, |d, on_click| {
+            d.cursor_pointer().on_click(move |event, window, cx| {
+                on_click(event, window, cx);
+                StillToasts::dismiss(window_id(window), id, cx);
+                window.refresh();
+            })
+        })
+        .into_any_element()
+}
+
+/// Shows toasts while keeping the errors among them in the notification
+/// center, where they stay after the toast is gone.
+pub trait RecordedNotifications {
+    fn push_recorded_notification(
+        &mut self,
+        notification: (NotificationType, impl Into<SharedString>),
+        cx: &mut App,
+    );
+}
+
+impl RecordedNotifications for Window {
+    fn push_recorded_notification(
+        &mut self,
+        (kind, message): (NotificationType, impl Into<SharedString>),
+        cx: &mut App,
+    ) {
+        let message: SharedString = message.into();
+        if matches!(kind, NotificationType::Error) {
+            NotificationsState::push(
+                NotificationSource::Error,
+                message.to_string(),
+                Some(NotificationAction::ShowLogs),
+                cx,
+            );
+        }
+        self.push_toast((kind, message), cx);
+    }
+}
+
+/// Event emitted when a notification's button is clicked.
+pub enum NotificationsEvent {
+    Open(NotificationAction),
+}
+
+impl EventEmitter<NotificationsEvent> for NotificationsPanel {}
+
+/// Dialog body listing recent notifications, latest first.
+pub struct NotificationsPanel {
+    /// Notifications unread when the dialog was opened, still highlighted.
+    fresh: Vec<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl NotificationsPanel {
+    pub fn view(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self {
+            fresh: vec![],
+            _subscriptions: vec![cx.observe_global::<NotificationsState>(|_, cx| cx.notify())],
+        })
+    }
+
+    fn render_entry(&self, entry: &NotificationEntry, cx: &mut Context<Self>) -> impl IntoElement {
+        let (icon, color): (Icon, Hsla) = match entry.source {
+            NotificationSource::Error => (
+                Icon::empty().path("icons/triangle-alert.svg"),
+                cx.theme().danger,
+            ),
+            NotificationSource::Job => (Icon::empty().path("icons/inbox.svg"), cx.theme().primary),
+            NotificationSource::Tunnel => {
+                (Icon::empty().path("icons/cable.svg"), cx.theme().warning)
+            }
+            NotificationSource::Update => (
+                Icon::empty().path("icons/cloud-download.svg"),
+                cx.theme().success,
+            ),
+        };
+        let id = entry.id;
+        let action = entry.action.map(|action| {
+            let label = match action {
+                NotificationAction::ShowJobs => "Show Jobs",
+                NotificationAction::ShowUpdates => "Updates…",
+                NotificationAction::ShowLogs => "View Logs",
+            };
+            Button::new(("notification-action", id))
+                .label(label)
+                .xsmall()
+                .ghost()
+                .on_click(cx.listener(move |_this, _, _window, cx| {
+                    cx.emit(NotificationsEvent::Open(action));
+                }))
+        });
+
+        h_flex()
+            .w_full()
+            .gap_2()
+            .py_1p5()
+            .px_2()
+            .items_start()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .when(self.fresh.contains(&entry.id), |d| {
+                d.bg(cx.theme().accent.opacity(0.3))
+            })
+            .child(div().pt_0p5().child(icon.size_4().text_color(color)))
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_0p5()
+                    .child(Label::new(entry.message.clone()).text_sm())
+                    .child(
+                        Label::new(entry.at.format("%b %d %H:%M:%S").to_string())
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .children(action)
+            .child(
+                Button::new(("dismiss-notification", id))
+                    .icon(IconName::Close)
+                    .xsmall()
+                    .ghost()
+                    .tooltip("Dismiss")
+                    .on_click(cx.listener(move |_this, _, _window, cx| {
+                        cx.update_global::<NotificationsState, _>(|state, _cx| {
+                            state.dismiss(id);
+                        });
+                    })),
+            )
+    }
+}
+
+impl Render for NotificationsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entries = cx.global::<NotificationsState>().entries.clone();
+        let count = entries.len();
+        let entries = entries
+            .iter()
+            .map(|entry| self.render_entry(entry, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let summary = match count {
+            0 => "No notifications".to_string(),
+            1 => "1 notification".to_string(),
+            n => format!("{} notifications", n),
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        Button::new("clear-notifications")
+                            .label("Clear All")
+                            .small()
+                            .ghost()
+                            .disabled(count == 0)
+                            .on_click(cx.listener(|_this, _, _window, cx| {
+                                cx.update_global::<NotificationsState, _>(|state, _cx| {
+                                    state.clear();
+                                });
+                            })),
+                    ),
+            )
+            .child(
+                Label::new(
+                    "Errors, background jobs, SSH tunnel changes and updates since pgui started",
+                )
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+            )
+            .child(
+                div()
+                    .id("notification-entries")
+                    .max_h(px(480.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(entries),
+            )
+    }
+}
+
+/// Open the notification center, marking everything in it read.
+pub fn open_notifications_dialog(
+    panel: &Entity<NotificationsPanel>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let fresh = cx
+        .global::<NotificationsState>()
+        .entries
+        .iter()
+        .filter(|entry| !entry.read)
+        .map(|entry| entry.id)
+        .collect();
+    panel.update(cx, |panel, _cx| panel.fresh = fresh);
+    cx.update_global::<NotificationsState, _>(|state, _cx| {
+        state.mark_all_read();
+    });
+
+    let panel = panel.clone();
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        dialog
+            .title("Notifications")
+            .width(px(560.))
+            .child(panel.clone())
+    });
+}
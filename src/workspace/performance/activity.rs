@@ -0,0 +1,438 @@
+use std::time::Duration;
+
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Subscription, Task, Window, WindowId,
+    div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, StyledExt as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    label::Label,
+    menu::{DropdownMenu as _, PopupMenuItem},
+    notification::NotificationType,
+    v_flex,
+};
+
+use super::panel::truncate;
+use crate::{
+    services::{
+        BackendSignal, DatabaseManager, IDLE_IN_TRANSACTION_OPTIONS, LONG_TRANSACTION_OPTIONS,
+        SessionActivity, TransactionAlertSettings, format_duration,
+    },
+    state::{
+        ActivityState, PerWindow, TransactionAlerts, WindowConnection, WindowScoped, window_id,
+    },
+    workspace::notifications::RecordedNotifications as _,
+};
+
+/// How often the session list reloads while it is open.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Dialog body listing the sessions of the current database, with those
+/// past a transaction alert threshold highlighted and the thresholds
+/// themselves. Reloads every few seconds.
+pub struct ActivityPanel {
+    window_id: WindowId,
+    db_manager: DatabaseManager,
+    sessions: Vec<SessionActivity>,
+    /// The session an alert was opened for.
+    highlight: Option<i32>,
+    loading: bool,
+    error: Option<String>,
+    _refresh: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ActivityPanel {
+    pub fn view(highlight: Option<i32>, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(highlight, window, cx))
+    }
+
+    fn new(highlight: Option<i32>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let window_id = window_id(window);
+        let db_manager = WindowConnection::of(window_id, cx).db_manager.clone();
+
+        let _refresh = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(REFRESH_INTERVAL).await;
+                if this.update(cx, |this, cx| this.load(cx)).is_err() {
+                    break;
+                }
+            }
+        });
+        let _subscriptions = vec![
+            cx.observe_global::<ActivityState>(|_, cx| cx.notify()),
+            cx.observe_global::<PerWindow<TransactionAlerts>>(|_, cx| cx.notify()),
+        ];
+
+        let mut this = Self {
+            window_id,
+            db_manager,
+            sessions: vec![],
+            highlight,
+            loading: false,
+            error: None,
+            _refresh,
+            _subscriptions,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.session_activity().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(sessions) => {
+                        this.sessions = sessions;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Ask for confirmation, then send `signal` to the session `pid`.
+    fn confirm_signal(
+        &mut self,
+        pid: i32,
+        signal: BackendSignal,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let sql = signal.sql(pid);
+        let warning = match signal {
+            BackendSignal::Cancel => "Its current statement is cancelled.",
+            BackendSignal::Terminate => {
+                "Its connection is closed and its open transaction rolled back."
+            }
+        };
+
+        let panel = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            dialog
+                .title(signal.title())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text(signal.title()))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(Label::new(format!("Session {}: {}", pid, warning)).text_sm())
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(sql.clone()),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = panel.update(cx, |this, cx| {
+                        this.send_signal(pid, signal, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn send_signal(
+        &mut self,
+        pid: i32,
+        signal: BackendSignal,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.signal_backend(pid, signal).await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok(sql) => (NotificationType::Success, sql.into()),
+                    Err(e) => (NotificationType::Error, format!("{}", e).into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.load(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn render_thresholds(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = cx.global::<ActivityState>().settings;
+
+        let threshold_label = |name: &str, secs: Option<u64>| match secs {
+            Some(secs) => format!("{} after {}", name, format_duration(secs)),
+            None => format!("{} off", name),
+        };
+
+        let long_menu = Button::new("long-transaction-threshold")
+            .label(threshold_label(
+                "Long transactions",
+                settings.long_transaction_secs,
+            ))
+            .xsmall()
+            .ghost()
+            .tooltip("Alert when a transaction stays open this long")
+            .dropdown_menu(move |menu, _window, _cx| {
+                let item = |secs: Option<u64>| {
+                    let label = secs.map_or("Off".to_string(), format_duration);
+                    PopupMenuItem::new(label)
+                        .checked(settings.long_transaction_secs == secs)
+                        .on_click(move |_, _window, cx| {
+                            let settings = TransactionAlertSettings {
+                                long_transaction_secs: secs,
+                                ..settings
+                            };
+                            ActivityState::set_settings(settings, cx);
+                        })
+                };
+                LONG_TRANSACTION_OPTIONS
+                    .into_iter()
+                    .fold(menu, |menu, secs| menu.item(item(Some(secs))))
+                    .separator()
+                    .item(item(None))
+            });
+
+        let idle_menu = Button::new("idle-in-transaction-threshold")
+            .label(threshold_label(
+                "Idle in transaction",
+                settings.idle_in_transaction_secs,
+            ))
+            .xsmall()
+            .ghost()
+            .tooltip("Alert when a session sits idle in a transaction this long")
+            .dropdown_menu(move |menu, _window, _cx| {
+                let item = |secs: Option<u64>| {
+                    let label = secs.map_or("Off".to_string(), format_duration);
+                    PopupMenuItem::new(label)
+                        .checked(settings.idle_in_transaction_secs == secs)
+                        .on_click(move |_, _window, cx| {
+                            let settings = TransactionAlertSettings {
+                                idle_in_transaction_secs: secs,
+                                ..settings
+                            };
+                            ActivityState::set_settings(settings, cx);
+                        })
+                };
+                IDLE_IN_TRANSACTION_OPTIONS
+                    .into_iter()
+                    .fold(menu, |menu, secs| menu.item(item(Some(secs))))
+                    .separator()
+                    .item(item(None))
+            });
+
+        h_flex()
+            .gap_1()
+            .items_center()
+            .child(
+                Label::new("Alerts")
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .child(long_menu)
+            .child(idle_menu)
+    }
+
+    fn render_session(
+        &self,
+        ix: usize,
+        session: &SessionActivity,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let pid = session.pid;
+        let alert = TransactionAlerts::of(self.window_id, cx)
+            .alert(pid)
+            .cloned();
+        let highlighted = self.highlight == Some(pid);
+        let query = session
+            .query
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        h_flex()
+            .id(("activity-session", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .when(alert.is_some(), |d| d.bg(cx.theme().warning.opacity(0.1)))
+            .when(highlighted, |d| {
+                d.border_l_2().border_color(cx.theme().accent)
+            })
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_0p5()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(format!("pid {}", pid)).text_sm().font_medium())
+                            .when_some(alert, |d, alert| {
+                                d.child(
+                                    Label::new(alert.message())
+                                        .text_xs()
+                                        .text_color(cx.theme().warning),
+                                )
+                            }),
+                    )
+                    .child(
+                        Label::new(session_details(session))
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .when(!query.is_empty(), |d| {
+                        d.child(
+                            div()
+                                .text_xs()
+                                .font_family("monospace")
+                                .child(truncate(&query, 120)),
+                        )
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new(("cancel-session", ix))
+                            .label("Cancel")
+                            .small()
+                            .ghost()
+                            .tooltip("Cancel its current statement")
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.confirm_signal(pid, BackendSignal::Cancel, window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(("terminate-session", ix))
+                            .label("Terminate")
+                            .small()
+                            .ghost()
+                            .tooltip("Close its connection")
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.confirm_signal(pid, BackendSignal::Terminate, window, cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+impl Render for ActivityPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .sessions
+            .iter()
+            .enumerate()
+            .map(|(ix, session)| self.render_session(ix, session, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let flagged = TransactionAlerts::of(self.window_id, cx).alerts.len();
+        let summary = if self.sessions.is_empty() && self.loading {
+            "Loading sessions...".to_string()
+        } else {
+            format!(
+                "{} session{} · {} flagged",
+                self.sessions.len(),
+                if self.sessions.len() == 1 { "" } else { "s" },
+                flagged
+            )
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .items_center()
+                            .child(self.render_thresholds(cx))
+                            .child(
+                                Button::new("reload-activity")
+                                    .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                                    .small()
+                                    .ghost()
+                                    .tooltip("Reload")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.load(cx);
+                                        cx.notify();
+                                    })),
+                            ),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("activity-rows")
+                    .max_h(px(460.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .when(rows.is_empty() && !self.loading, |d| {
+                        d.child(
+                            div().p_2().child(
+                                Label::new("No other session is connected")
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground),
+                            ),
+                        )
+                    })
+                    .children(rows),
+            )
+    }
+}
+
+/// `user · application · client · state · in transaction 12s`, leaving out
+/// what is unknown.
+fn session_details(session: &SessionActivity) -> String {
+    let mut details: Vec<String> = [
+        session.username.clone(),
+        session.application_name.clone().filter(|a| !a.is_empty()),
+        session.client_addr.clone(),
+        session.state.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if let Some(secs) = session.transaction_secs {
+        details.push(format!(
+            "in transaction {}",
+            format_duration(secs.max(0.) as u64)
+        ));
+    }
+    details.join(" · ")
+}
@@ -0,0 +1,357 @@
+use std::time::Duration;
+
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Task, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, StyledExt as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use super::panel::truncate;
+use crate::{
+    services::{BackendSignal, BlockingNode, DatabaseManager, LockSession, blocking_tree},
+    state::{WindowConnection, WindowScoped, window_id},
+    workspace::notifications::RecordedNotifications as _,
+};
+
+/// How often the lock monitor reloads while it is open.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Dialog body showing who blocks whom, root blockers first, with actions
+/// to cancel or terminate a session. Reloads every few seconds.
+pub struct LocksPanel {
+    db_manager: DatabaseManager,
+    nodes: Vec<BlockingNode>,
+    loading: bool,
+    error: Option<String>,
+    _refresh: Task<()>,
+}
+
+impl LocksPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+
+        let _refresh = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(REFRESH_INTERVAL).await;
+                if this.update(cx, |this, cx| this.load(cx)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut this = Self {
+            db_manager,
+            nodes: vec![],
+            loading: false,
+            error: None,
+            _refresh,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.lock_sessions().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(sessions) => {
+                        this.nodes = blocking_tree(&sessions);
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Ask for confirmation, then send `signal` to the session `pid`.
+    fn confirm_signal(
+        &mut self,
+        pid: i32,
+        signal: BackendSignal,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let sql = signal.sql(pid);
+        let warning = match signal {
+            BackendSignal::Cancel => "Its current statement is cancelled.",
+            BackendSignal::Terminate => {
+                "Its connection is closed and its open transaction rolled back."
+            }
+        };
+
+        let panel = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            dialog
+                .title(signal.title())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text(signal.title()))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(Label::new(format!("Session {}: {}", pid, warning)).text_sm())
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(sql.clone()),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = panel.update(cx, |this, cx| {
+                        this.send_signal(pid, signal, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn send_signal(
+        &mut self,
+        pid: i32,
+        signal: BackendSignal,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.signal_backend(pid, signal).await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok(sql) => (NotificationType::Success, sql.into()),
+                    Err(e) => (NotificationType::Error, format!("{}", e).into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.load(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn render_node(
+        &self,
+        ix: usize,
+        node: &BlockingNode,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let session = &node.session;
+        let pid = session.pid;
+        let query = session
+            .query
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let role = if node.is_root {
+            format!("Root blocker · holds up {}", sessions(node.blocked_count))
+        } else if node.blocked_count > 0 {
+            format!("Waiting · holds up {}", sessions(node.blocked_count))
+        } else {
+            "Waiting".to_string()
+        };
+
+        h_flex()
+            .id(("lock-session", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .pr_2()
+            .pl(px(8. + 20. * node.depth as f32))
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .when(node.is_root, |d| d.bg(cx.theme().danger.opacity(0.08)))
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_0p5()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(format!("pid {}", pid)).text_sm().font_medium())
+                            .child(
+                                Label::new(role)
+                                    .text_xs()
+                                    .when(node.is_root, |l| l.text_color(cx.theme().danger))
+                                    .when(!node.is_root, |l| {
+                                        l.text_color(cx.theme().muted_foreground)
+                                    }),
+                            ),
+                    )
+                    .child(
+                        Label::new(session_details(session))
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .when(!query.is_empty(), |d| {
+                        d.child(
+                            div()
+                                .text_xs()
+                                .font_family("monospace")
+                                .child(truncate(&query, 120)),
+                        )
+                    })
+                    .when(!session.locks.is_empty(), |d| {
+                        d.child(
+                            Label::new(session.locks.join(", "))
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new(("cancel-backend", ix))
+                            .label("Cancel")
+                            .small()
+                            .ghost()
+                            .tooltip("Cancel its current statement")
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.confirm_signal(pid, BackendSignal::Cancel, window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(("terminate-backend", ix))
+                            .label("Terminate")
+                            .small()
+                            .when(node.is_root, |b| b.danger())
+                            .when(!node.is_root, |b| b.ghost())
+                            .tooltip("Close its connection")
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.confirm_signal(pid, BackendSignal::Terminate, window, cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+impl Render for LocksPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(ix, node)| self.render_node(ix, node, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let roots = self.nodes.iter().filter(|n| n.is_root).count();
+        let waiting = self
+            .nodes
+            .iter()
+            .filter(|n| !n.session.blocked_by.is_empty())
+            .map(|n| n.session.pid)
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        let summary = if self.nodes.is_empty() && self.loading {
+            "Loading locks...".to_string()
+        } else {
+            format!(
+                "{} waiting on {} root blocker{}",
+                sessions(waiting),
+                roots,
+                if roots == 1 { "" } else { "s" }
+            )
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        Button::new("reload-locks")
+                            .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip("Reload")
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.load(cx);
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("lock-rows")
+                    .max_h(px(460.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .when(rows.is_empty() && !self.loading, |d| {
+                        d.child(
+                            div().p_2().child(
+                                Label::new("No session is waiting on a lock")
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground),
+                            ),
+                        )
+                    })
+                    .children(rows),
+            )
+    }
+}
+
+fn sessions(count: usize) -> String {
+    format!("{} session{}", count, if count == 1 { "" } else { "s" })
+}
+
+/// `user · application · state · open 12s`, leaving out what is unknown.
+fn session_details(session: &LockSession) -> String {
+    let mut details: Vec<String> = [
+        session.username.clone(),
+        session.application_name.clone().filter(|a| !a.is_empty()),
+        session.state.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if let Some(secs) = session.duration_secs {
+        details.push(format!("open {}s", secs.max(0.) as i64));
+    }
+    details.join(" · ")
+}
@@ -0,0 +1,10 @@
+mod activity;
+mod locks;
+mod panel;
+mod statements;
+
+pub use activity::ActivityPanel;
+pub use locks::LocksPanel;
+pub use panel::PerformanceEvent;
+pub use panel::PerformancePanel;
+pub use statements::StatementStatsPanel;
@@ -0,0 +1,284 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, InteractiveElement as _, IntoElement,
+    ParentElement, Render, StatefulInteractiveElement as _, Styled, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Sizable as _, StyledExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    v_flex,
+};
+
+use crate::{
+    services::{DatabaseManager, IndexSuggestion, PerformanceReport, StatementStats},
+    state::{WindowConnection, WindowScoped, window_id},
+};
+
+pub enum PerformanceEvent {
+    /// Open a statement in the editor for review before running it.
+    ReviewStatement(String),
+    /// Open the full statement statistics.
+    OpenStatementStats,
+}
+
+/// Dialog body listing the most expensive statements from
+/// `pg_stat_statements`, followed by index suggestions.
+pub struct PerformancePanel {
+    db_manager: DatabaseManager,
+    report: PerformanceReport,
+    loading: bool,
+    error: Option<String>,
+}
+
+impl EventEmitter<PerformanceEvent> for PerformancePanel {}
+
+impl PerformancePanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+
+        let mut this = Self {
+            db_manager,
+            report: PerformanceReport::default(),
+            loading: false,
+            error: None,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.performance_report().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(report) => {
+                        this.report = report;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn render_statement(
+        &self,
+        ix: usize,
+        stmt: &StatementStats,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let query = stmt.query.split_whitespace().collect::<Vec<_>>().join(" ");
+        let query_to_review = stmt.query.clone();
+
+        h_flex()
+            .id(("statement", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_0p5()
+                    .child(Label::new(truncate(&query, 120)).text_sm())
+                    .child(
+                        Label::new(format!(
+                            "{} calls · {:.1} ms mean · {:.0} ms total · {} rows",
+                            stmt.calls, stmt.mean_ms, stmt.total_ms, stmt.rows
+                        ))
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(
+                Button::new(("open-statement", ix))
+                    .icon(Icon::empty().path("icons/square-terminal.svg"))
+                    .small()
+                    .ghost()
+                    .tooltip("Open in Editor")
+                    .on_click(cx.listener(move |_this, _, _window, cx| {
+                        cx.emit(PerformanceEvent::ReviewStatement(query_to_review.clone()));
+                    })),
+            )
+    }
+
+    fn render_suggestion(
+        &self,
+        ix: usize,
+        suggestion: &IndexSuggestion,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let sql = suggestion.create_sql();
+
+        h_flex()
+            .id(("suggestion", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(
+                        Label::new(format!(
+                            "{}.{} ({})",
+                            suggestion.table_schema, suggestion.table_name, suggestion.column
+                        ))
+                        .text_sm(),
+                    )
+                    .child(
+                        Label::new(format!(
+                            "{} · {:.0} ms in matching statements",
+                            suggestion.reason, suggestion.total_ms
+                        ))
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(
+                Button::new(("review-index", ix))
+                    .label("Review")
+                    .small()
+                    .ghost()
+                    .tooltip("Open CREATE INDEX CONCURRENTLY in the editor")
+                    .on_click(cx.listener(move |_this, _, _window, cx| {
+                        cx.emit(PerformanceEvent::ReviewStatement(sql.clone()));
+                    })),
+            )
+    }
+}
+
+impl Render for PerformancePanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let statements = self
+            .report
+            .statements
+            .iter()
+            .enumerate()
+            .map(|(ix, stmt)| self.render_statement(ix, stmt, cx).into_any_element())
+            .collect::<Vec<_>>();
+        let suggestions = self
+            .report
+            .suggestions
+            .iter()
+            .enumerate()
+            .map(|(ix, s)| self.render_suggestion(ix, s, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let summary = if self.loading {
+            "Loading statistics...".to_string()
+        } else {
+            format!(
+                "{} statements, {} index suggestions",
+                self.report.statements.len(),
+                self.report.suggestions.len()
+            )
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .items_center()
+                            .child(
+                                Button::new("open-statement-stats")
+                                    .label("All Statements")
+                                    .small()
+                                    .ghost()
+                                    .disabled(self.loading || self.error.is_some())
+                                    .tooltip("Every statement pg_stat_statements tracks")
+                                    .on_click(cx.listener(|_this, _, _window, cx| {
+                                        cx.emit(PerformanceEvent::OpenStatementStats);
+                                    })),
+                            )
+                            .child(
+                                Button::new("reload-performance")
+                                    .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                                    .small()
+                                    .ghost()
+                                    .tooltip("Reload")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.load(cx);
+                                        cx.notify();
+                                    })),
+                            ),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                Label::new("Most expensive statements")
+                    .text_sm()
+                    .font_semibold(),
+            )
+            .child(
+                div()
+                    .id("statement-rows")
+                    .max_h(px(300.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(statements),
+            )
+            .child(Label::new("Index suggestions").text_sm().font_semibold())
+            .child(
+                div()
+                    .id("suggestion-rows")
+                    .max_h(px(200.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .when(suggestions.is_empty() && !self.loading, |d| {
+                        d.child(
+                            div().p_2().child(
+                                Label::new("No large, mostly sequentially scanned tables found")
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground),
+                            ),
+                        )
+                    })
+                    .children(suggestions),
+            )
+    }
+}
+
+pub(super) fn truncate(s: &str, max_len: usize) -> String {
+    match s.char_indices().nth(max_len) {
+        Some((end, _)) => format!("{}...", &s[..end]),
+        None => s.to_string(),
+    }
+}
@@ -0,0 +1,321 @@
+use gpui::{
+    App, AppContext, ClipboardItem, Context, Entity, EventEmitter, InteractiveElement as _,
+    IntoElement, ParentElement, Render, SharedString, StatefulInteractiveElement as _, Styled,
+    Subscription, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use super::panel::{PerformanceEvent, truncate};
+use crate::{
+    services::{DatabaseManager, StatementSort, StatementStats, sort_statements},
+    state::{WindowConnection, WindowScoped, window_id},
+    workspace::notifications::{RecordedNotifications as _, Toasts as _},
+};
+
+/// Dialog body listing every statement `pg_stat_statements` tracks for the
+/// current database, sortable and filterable, with a reset of the
+/// statistics behind a confirmation.
+pub struct StatementStatsPanel {
+    db_manager: DatabaseManager,
+    statements: Vec<StatementStats>,
+    sort: StatementSort,
+    descending: bool,
+    filter_input: Entity<InputState>,
+    loading: bool,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<PerformanceEvent> for StatementStatsPanel {}
+
+impl StatementStatsPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        let filter_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Filter statements"));
+        let _subscriptions =
+            vec![cx.subscribe(&filter_input, |_, _, _: &InputEvent, cx| cx.notify())];
+
+        let mut this = Self {
+            db_manager,
+            statements: vec![],
+            sort: StatementSort::TotalTime,
+            descending: true,
+            filter_input,
+            loading: false,
+            error: None,
+            _subscriptions,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.statement_stats().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(statements) => {
+                        this.statements = statements;
+                        sort_statements(&mut this.statements, this.sort, this.descending);
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Sort by `sort`, flipping the direction when it is already the sort.
+    fn set_sort(&mut self, sort: StatementSort, cx: &mut Context<Self>) {
+        if self.sort == sort {
+            self.descending = !self.descending;
+        } else {
+            self.sort = sort;
+            self.descending = true;
+        }
+        sort_statements(&mut self.statements, self.sort, self.descending);
+        cx.notify();
+    }
+
+    fn confirm_reset(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let panel = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            dialog
+                .title("Reset Statement Statistics")
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Reset"))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(
+                            Label::new("Statistics of every database on the server are discarded.")
+                                .text_sm(),
+                        )
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child("SELECT pg_stat_statements_reset();"),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = panel.update(cx, |this, cx| this.reset(window, cx));
+                    true
+                })
+        });
+    }
+
+    fn reset(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.reset_statement_stats().await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok(()) => (
+                        NotificationType::Success,
+                        "Statement statistics reset".into(),
+                    ),
+                    Err(e) => (NotificationType::Error, format!("{}", e).into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.load(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn render_statement(
+        &self,
+        ix: usize,
+        stmt: &StatementStats,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let query = stmt.query.split_whitespace().collect::<Vec<_>>().join(" ");
+        let hit_ratio = stmt
+            .hit_ratio()
+            .map_or("n/a".to_string(), |r| format!("{:.1}%", r * 100.));
+        let to_copy = stmt.query.clone();
+        let to_explain = format!("EXPLAIN {}", stmt.query.trim().trim_end_matches(';'));
+
+        h_flex()
+            .id(("statement-stats", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_0p5()
+                    .child(Label::new(truncate(&query, 120)).text_sm())
+                    .child(
+                        Label::new(format!(
+                            "{} calls · {:.0} ms total · {:.1} ms mean · {} rows · {} hit",
+                            stmt.calls, stmt.total_ms, stmt.mean_ms, stmt.rows, hit_ratio
+                        ))
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new(("copy-statement", ix))
+                            .icon(Icon::empty().path("icons/copy.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip("Copy Query")
+                            .on_click(cx.listener(move |_this, _, window, cx| {
+                                cx.write_to_clipboard(ClipboardItem::new_string(to_copy.clone()));
+                                window.push_toast((NotificationType::Success, "Query copied"), cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(("explain-statement", ix))
+                            .label("Explain")
+                            .small()
+                            .ghost()
+                            .tooltip("Open its EXPLAIN in the editor")
+                            .on_click(cx.listener(move |_this, _, _window, cx| {
+                                cx.emit(PerformanceEvent::ReviewStatement(to_explain.clone()));
+                            })),
+                    ),
+            )
+    }
+}
+
+impl Render for StatementStatsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let filter = self.filter_input.read(cx).value().trim().to_lowercase();
+        let rows = self
+            .statements
+            .iter()
+            .enumerate()
+            .filter(|(_, stmt)| filter.is_empty() || stmt.query.to_lowercase().contains(&filter))
+            .map(|(ix, stmt)| self.render_statement(ix, stmt, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let summary = if self.loading {
+            "Loading statistics...".to_string()
+        } else if rows.len() == self.statements.len() {
+            format!("{} statements", self.statements.len())
+        } else {
+            format!("{} of {} statements", rows.len(), self.statements.len())
+        };
+
+        let sort_buttons = StatementSort::ALL.into_iter().map(|sort| {
+            let active = self.sort == sort;
+            let label = match (active, self.descending) {
+                (true, true) => format!("{} ↓", sort.label()),
+                (true, false) => format!("{} ↑", sort.label()),
+                (false, _) => sort.label().to_string(),
+            };
+            Button::new(SharedString::from(format!("sort-{}", sort.label())))
+                .label(label)
+                .xsmall()
+                .when(active, |b| b.primary())
+                .when(!active, |b| b.ghost())
+                .on_click(cx.listener(move |this, _, _window, cx| this.set_sort(sort, cx)))
+        });
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .items_center()
+                            .child(
+                                div()
+                                    .w(px(200.))
+                                    .child(Input::new(&self.filter_input).small()),
+                            )
+                            .child(
+                                Button::new("reload-statement-stats")
+                                    .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                                    .small()
+                                    .ghost()
+                                    .tooltip("Reload")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.load(cx);
+                                        cx.notify();
+                                    })),
+                            )
+                            .child(
+                                Button::new("reset-statement-stats")
+                                    .label("Reset")
+                                    .small()
+                                    .danger()
+                                    .tooltip("Reset pg_stat_statements")
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.confirm_reset(window, cx);
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(
+                        Label::new("Sort by")
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .children(sort_buttons),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("statement-stats-rows")
+                    .max_h(px(460.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(rows),
+            )
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement as _, Styled, Window, div, px,
+};
+use gpui_component::{
+    Disableable as _, Sizable as _,
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputState},
+    switch::Switch,
+};
+
+use crate::services::sql::{BoundParams, ParamQuery};
+
+struct ParamField {
+    input: Entity<InputState>,
+    is_null: bool,
+}
+
+/// Dialog body for the parameters of an editor query, one field per
+/// distinct placeholder with the server's type hint as its description.
+pub struct QueryParamsForm {
+    query: ParamQuery,
+    types: Vec<Option<String>>,
+    fields: Vec<ParamField>,
+}
+
+impl QueryParamsForm {
+    pub fn view(
+        query: ParamQuery,
+        types: Vec<Option<String>>,
+        saved: HashMap<String, Option<String>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        cx.new(|cx| Self::new(query, types, saved, window, cx))
+    }
+
+    fn new(
+        query: ParamQuery,
+        types: Vec<Option<String>>,
+        saved: HashMap<String, Option<String>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let fields = query
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(ix, label)| {
+                let placeholder = types
+                    .get(ix)
+                    .cloned()
+                    .flatten()
+                    .unwrap_or_else(|| "Value".to_string());
+                let previous = saved.get(label).cloned();
+                let input = cx.new(|cx| {
+                    let mut state = InputState::new(window, cx).placeholder(placeholder);
+                    if let Some(Some(value)) = &previous {
+                        state.set_value(value.clone(), window, cx);
+                    }
+                    state
+                });
+                ParamField {
+                    input,
+                    is_null: matches!(previous, Some(None)),
+                }
+            })
+            .collect();
+
+        Self {
+            query,
+            types,
+            fields,
+        }
+    }
+
+    /// The query with the entered values, one per label.
+    pub fn bound(&self, cx: &App) -> BoundParams {
+        let values = self
+            .fields
+            .iter()
+            .map(|field| (!field.is_null).then(|| field.input.read(cx).value().to_string()))
+            .collect();
+
+        BoundParams {
+            query: self.query.clone(),
+            types: self.types.clone(),
+            values,
+        }
+    }
+}
+
+impl Render for QueryParamsForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let form = self
+            .fields
+            .iter()
+            .enumerate()
+            .fold(v_form().small(), |form, (ix, f)| {
+                let description = self
+                    .types
+                    .get(ix)
+                    .cloned()
+                    .flatten()
+                    .unwrap_or_else(|| "Type not known; sent as text".to_string());
+
+                form.child(
+                    field()
+                        .label(self.query.labels[ix].clone())
+                        .description(description)
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .child(Input::new(&f.input).disabled(f.is_null)),
+                                )
+                                .child(
+                                    Switch::new(("param-null", ix))
+                                        .checked(f.is_null)
+                                        .label("NULL")
+                                        .small()
+                                        .on_click(cx.listener(
+                                            move |this, checked: &bool, _win, cx| {
+                                                this.fields[ix].is_null = *checked;
+                                                cx.notify();
+                                            },
+                                        )),
+                                ),
+                        ),
+                )
+            });
+
+        div()
+            .id("query-param-fields")
+            .max_h(px(480.))
+            .overflow_y_scroll()
+            .child(form)
+    }
+}
@@ -0,0 +1,81 @@
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _,
+    input::{Input, InputState, TabSize},
+    label::Label,
+    v_flex,
+};
+
+use crate::services::{ResultColumnMetadata, cell_language};
+
+/// Dialog body showing a long text, JSON or XML cell in a highlighted,
+/// word-wrapped editor.
+pub struct CellEditor {
+    input: Entity<InputState>,
+    original: String,
+    /// Whether the value can be saved back to its table.
+    editable: bool,
+}
+
+impl CellEditor {
+    pub fn view(
+        column: &ResultColumnMetadata,
+        value: String,
+        editable: bool,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        let language = cell_language(&column.type_name, &value);
+        // jsonb does not keep the original formatting anyway.
+        let text = match column.type_name.to_uppercase().as_str() {
+            "JSONB" => serde_json::from_str::<serde_json::Value>(&value)
+                .and_then(|json| serde_json::to_string_pretty(&json))
+                .unwrap_or_else(|_| value.clone()),
+            _ => value.clone(),
+        };
+
+        cx.new(|cx| {
+            let input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .code_editor(language)
+                    .line_number(true)
+                    .soft_wrap(true)
+                    .tab_size(TabSize {
+                        tab_size: 2,
+                        hard_tabs: false,
+                    })
+                    .default_value(text.clone())
+            });
+            Self {
+                input,
+                original: text,
+                editable,
+            }
+        })
+    }
+
+    /// The edited value, or `None` when it was not changed.
+    pub fn changed_value(&self, cx: &App) -> Option<String> {
+        let value = self.input.read(cx).value().to_string();
+        (value != self.original).then_some(value)
+    }
+}
+
+impl Render for CellEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let hint = if self.editable {
+            "Saving reviews an UPDATE of this row before it runs."
+        } else {
+            "Read only: the value is not from a single table with a primary key in the result."
+        };
+
+        v_flex()
+            .gap_2()
+            .child(div().h(px(420.)).child(Input::new(&self.input).h_full()))
+            .child(
+                Label::new(hint)
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+    }
+}
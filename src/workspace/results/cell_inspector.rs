@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::services::{
+    ImageKind, ResultColumnMetadata, decode_binary, format_byte_size, image_kind, is_binary_type,
+};
+use crate::workspace::notifications::RecordedNotifications as _;
+
+/// The bytes of a binary cell, and its image format when it is one.
+struct BinaryValue {
+    bytes: Arc<Vec<u8>>,
+    image: Option<(ImageKind, Arc<Image>)>,
+}
+
+impl BinaryValue {
+    fn of(column: &ResultColumnMetadata, value: &str) -> Option<Self> {
+        if !is_binary_type(&column.type_name) {
+            return None;
+        }
+        let bytes = decode_binary(value)?;
+        let image = image_kind(&bytes).map(|kind| {
+            let format = match kind {
+                ImageKind::Png => ImageFormat::Png,
+                ImageKind::Jpeg => ImageFormat::Jpeg,
+            };
+            (kind, Arc::new(Image::from_bytes(format, bytes.clone())))
+        });
+        Some(Self {
+            bytes: Arc::new(bytes),
+            image,
+        })
+    }
+
+    fn file_name(&self, column: &ResultColumnMetadata) -> String {
+        let extension = self
+            .image
+            .as_ref()
+            .map_or("bin", |(kind, _)| kind.extension());
+        format!("{}.{}", column.name, extension)
+    }
+}
+
+/// Prompt for a path and write the bytes of a binary cell to it.
+fn save_binary(bytes: Arc<Vec<u8>>, file_name: &str, window: &mut Window, cx: &mut App) {
+    let home = dirs::home_dir().unwrap_or_default();
+    let receiver = cx.prompt_for_new_path(&home, Some(file_name));
+
+    window
+        .spawn(cx, async move |cx| {
+            let Ok(Ok(Some(path))) = receiver.await else {
+                return;
+            };
+            let saved = async_fs::write(&path, bytes.as_slice()).await;
+
+            let _ = cx.update(|window, cx| {
+                let (notification_type, message): (_, SharedString) = match saved {
+                    Ok(()) => (
+                        NotificationType::Success,
+                        format!("Saved {}", path.display()).into(),
+                    ),
+                    Err(e) => {
+                        tracing::error!("Saving binary value failed: {}", e);
+                        (NotificationType::Error, "Failed to save file".into())
+                    }
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+            });
+        })
+        .detach();
+}
+
+/// Save the value of a binary cell to a file. Does nothing for cells that
+/// are not binary.
+pub(crate) fn save_cell_to_file(
+    column: &ResultColumnMetadata,
+    value: &str,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    if let Some(binary) = BinaryValue::of(column, value) {
+        let file_name = binary.file_name(column);
+        save_binary(binary.bytes, &file_name, window, cx);
+    }
+}
+
+/// Show one cell in a dialog: its full value, or for binary values their
+/// size, a preview when they are an image, and a way to save them.
+pub(crate) fn show_cell_inspector(
+    column: ResultColumnMetadata,
+    value: String,
+    is_null: bool,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let binary = if is_null {
+        None
+    } else {
+        BinaryValue::of(&column, &value).map(Arc::new)
+    };
+
+    window.open_dialog(cx, move |dialog, _win, cx| {
+        let title = format!("{} ({})", column.name, column.type_name);
+        let body = match (&binary, is_null) {
+            (_, true) => v_flex().child(
+                Label::new("NULL")
+                    .italic()
+                    .text_color(cx.theme().muted_foreground),
+            ),
+            (Some(binary), _) => {
+                let bytes = binary.bytes.clone();
+                let file_name = binary.file_name(&column);
+                v_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(format_byte_size(binary.bytes.len()))
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .when_some(binary.image.clone(), |d, (_, image)| {
+                        d.child(
+                            div()
+                                .flex()
+                                .justify_center()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .child(
+                                    img(image)
+                                        .max_w(px(480.))
+                                        .max_h(px(320.))
+                                        .object_fit(ObjectFit::Contain),
+                                ),
+                        )
+                    })
+                    .child(
+                        h_flex().child(
+                            Button::new("save-cell")
+                                .icon(Icon::empty().path("icons/save.svg"))
+                                .label("Save to File…")
+                                .small()
+                                .ghost()
+                                .on_click(move |_, window, cx| {
+                                    save_binary(bytes.clone(), &file_name, window, cx);
+                                }),
+                        ),
+                    )
+            }
+            (None, _) => v_flex().child(
+                div()
+                    .id("cell-value")
+                    .max_h(px(420.))
+                    .overflow_y_scroll()
+                    .p_2()
+                    .rounded(cx.theme().radius)
+                    .bg(cx.theme().secondary)
+                    .font_family("monospace")
+                    .text_sm()
+                    .child(value.clone()),
+            ),
+        };
+
+        dialog.title(title).width(px(560.)).child(body)
+    });
+}
@@ -0,0 +1,54 @@
+use gpui::*;
+use gpui_component::{ActiveTheme as _, WindowExt as _, h_flex, label::Label, v_flex};
+
+use crate::services::ColumnStats;
+
+fn stat_row(label: impl Into<SharedString>, value: impl Into<SharedString>, cx: &App) -> Div {
+    h_flex()
+        .justify_between()
+        .gap_4()
+        .child(
+            Label::new(label)
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+        )
+        .child(Label::new(value).text_sm())
+}
+
+/// Show the statistics of the result column `name` in a small dialog.
+pub(crate) fn show_column_stats(
+    name: String,
+    stats: ColumnStats,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    window.open_dialog(cx, move |dialog, _win, cx| {
+        let none = || "—".to_string();
+        let top_values = stats
+            .top_values
+            .iter()
+            .map(|(value, count)| stat_row(value.clone(), count.to_string(), cx));
+
+        dialog
+            .title(format!("Column stats: {}", name))
+            .width(px(360.))
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(stat_row("Rows", stats.rows.to_string(), cx))
+                    .child(stat_row("Nulls", stats.nulls.to_string(), cx))
+                    .child(stat_row("Distinct", stats.distinct.to_string(), cx))
+                    .child(stat_row("Min", stats.min.clone().unwrap_or_else(none), cx))
+                    .child(stat_row("Max", stats.max.clone().unwrap_or_else(none), cx))
+                    .child(
+                        div()
+                            .pt_2()
+                            .mt_1()
+                            .border_t_1()
+                            .border_color(cx.theme().border)
+                            .child(Label::new("Top values").text_sm()),
+                    )
+                    .children(top_values),
+            )
+    });
+}
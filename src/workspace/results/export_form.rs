@@ -0,0 +1,435 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    v_flex,
+};
+use uuid::Uuid;
+
+use crate::services::{
+    AppStore, DateTimeDisplay, DateTimeFormat, QueryResult, TimeZoneDisplay,
+    export::{ExportTemplate, TemplateFormat, export_templates_key, save_template},
+};
+
+/// Date and time choices, as labelled in the form.
+const DATE_TIME_CHOICES: [(&str, TimeZoneDisplay, DateTimeFormat); 4] = [
+    ("As Decoded", TimeZoneDisplay::Raw, DateTimeFormat::Iso),
+    ("ISO 8601 UTC", TimeZoneDisplay::Utc, DateTimeFormat::Iso),
+    (
+        "ISO 8601 Local",
+        TimeZoneDisplay::Local,
+        DateTimeFormat::Iso,
+    ),
+    (
+        "Locale Format",
+        TimeZoneDisplay::Local,
+        DateTimeFormat::Locale,
+    ),
+];
+
+/// Dialog body choosing the columns, headers and formatting of a result
+/// export, with templates saved by name for the connection.
+pub struct ResultsExportForm {
+    result: QueryResult,
+    /// Where templates are saved; none for results of no saved connection.
+    connection_id: Option<Uuid>,
+    templates: Vec<ExportTemplate>,
+    template: ExportTemplate,
+    /// One per column of `template`, in the same order.
+    header_inputs: Vec<Entity<InputState>>,
+    null_input: Entity<InputState>,
+    name_input: Entity<InputState>,
+    status: Option<String>,
+}
+
+impl ResultsExportForm {
+    pub fn view(
+        result: QueryResult,
+        connection_id: Option<Uuid>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            let template = ExportTemplate::for_result(&result);
+            let null_input = cx.new(|cx| InputState::new(window, cx).placeholder("Empty"));
+            let name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Template name"));
+            let mut this = Self {
+                result,
+                connection_id,
+                templates: vec![],
+                template: ExportTemplate::default(),
+                header_inputs: vec![],
+                null_input,
+                name_input,
+                status: None,
+            };
+            this.apply(template, window, cx);
+            this.load_templates(cx);
+            this
+        })
+    }
+
+    fn load_templates(&mut self, cx: &mut Context<Self>) {
+        let Some(connection_id) = self.connection_id else {
+            return;
+        };
+        cx.spawn(async move |this, cx| {
+            let templates = async {
+                let store = AppStore::singleton().await?;
+                store
+                    .workspace_state()
+                    .get_json::<Vec<ExportTemplate>>(&export_templates_key(&connection_id))
+                    .await
+            }
+            .await;
+            let _ = this.update(cx, |this, cx| {
+                match templates {
+                    Ok(templates) => this.templates = templates.unwrap_or_default(),
+                    Err(e) => this.status = Some(format!("Failed to load templates: {}", e)),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Fill the form from `template`, fitted to the result's columns.
+    fn apply(&mut self, template: ExportTemplate, window: &mut Window, cx: &mut Context<Self>) {
+        self.template = template.fitted(&self.result);
+        self.header_inputs = self
+            .template
+            .columns
+            .iter()
+            .map(|column| {
+                let header = column.header.clone();
+                cx.new(|cx| InputState::new(window, cx).default_value(header))
+            })
+            .collect();
+        let null_text = self.template.null_text.clone();
+        let name = self.template.name.clone();
+        self.null_input
+            .update(cx, |input, cx| input.set_value(null_text, window, cx));
+        self.name_input
+            .update(cx, |input, cx| input.set_value(name, window, cx));
+        cx.notify();
+    }
+
+    /// The export as set up in the form.
+    pub fn template(&self, cx: &App) -> ExportTemplate {
+        let mut template = self.template.clone();
+        for (column, input) in template.columns.iter_mut().zip(&self.header_inputs) {
+            column.header = input.read(cx).value().to_string();
+        }
+        template.null_text = self.null_input.read(cx).value().to_string();
+        template.name = self.name_input.read(cx).value().trim().to_string();
+        template
+    }
+
+    pub fn result(&self) -> &QueryResult {
+        &self.result
+    }
+
+    fn move_column(&mut self, ix: usize, up: bool, cx: &mut Context<Self>) {
+        let other = if up { ix.checked_sub(1) } else { Some(ix + 1) };
+        let Some(other) = other.filter(|other| *other < self.template.columns.len()) else {
+            return;
+        };
+        self.template.columns.swap(ix, other);
+        self.header_inputs.swap(ix, other);
+        cx.notify();
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let Some(connection_id) = self.connection_id else {
+            return;
+        };
+        let template = self.template(cx);
+        if template.name.is_empty() {
+            self.status = Some("Name the template to save it".to_string());
+            cx.notify();
+            return;
+        }
+
+        let name = template.name.clone();
+        save_template(&mut self.templates, template);
+        self.template.name = name.clone();
+        let templates = self.templates.clone();
+        cx.spawn(async move |this, cx| {
+            let saved = async {
+                let store = AppStore::singleton().await?;
+                store
+                    .workspace_state()
+                    .set_json(&export_templates_key(&connection_id), &templates)
+                    .await
+            }
+            .await;
+            let _ = this.update(cx, |this, cx| {
+                this.status = Some(match saved {
+                    Ok(()) => format!("Saved template \"{}\"", name),
+                    Err(e) => format!("Failed to save template: {}", e),
+                });
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn delete(&mut self, name: String, cx: &mut Context<Self>) {
+        let Some(connection_id) = self.connection_id else {
+            return;
+        };
+        self.templates.retain(|template| template.name != name);
+        let templates = self.templates.clone();
+        cx.spawn(async move |_, _| {
+            let deleted = async {
+                let store = AppStore::singleton().await?;
+                store
+                    .workspace_state()
+                    .set_json(&export_templates_key(&connection_id), &templates)
+                    .await
+            }
+            .await;
+            if let Err(e) = deleted {
+                tracing::warn!("Failed to delete export template: {}", e);
+            }
+        })
+        .detach();
+        cx.notify();
+    }
+
+    fn render_templates(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        if self.connection_id.is_none() {
+            return h_flex().child(
+                Label::new("Connect to save templates")
+                    .text_xs()
+                    .text_color(muted),
+            );
+        }
+        if self.templates.is_empty() {
+            return h_flex().child(
+                Label::new("No saved templates for this connection")
+                    .text_xs()
+                    .text_color(muted),
+            );
+        }
+
+        h_flex()
+            .gap_1()
+            .flex_wrap()
+            .children(self.templates.iter().enumerate().map(|(ix, template)| {
+                let apply = template.clone();
+                let name = template.name.clone();
+                h_flex()
+                    .child(
+                        Button::new(("export-template", ix))
+                            .label(template.name.clone())
+                            .small()
+                            .ghost()
+                            .selected(self.template.name == template.name)
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.status = None;
+                                this.apply(apply.clone(), window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(("delete-export-template", ix))
+                            .icon(Icon::empty().path("icons/trash.svg"))
+                            .xsmall()
+                            .ghost()
+                            .tooltip("Delete Template")
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.delete(name.clone(), cx);
+                            })),
+                    )
+            }))
+    }
+
+    fn render_columns(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let count = self.template.columns.len();
+        let rows = self
+            .template
+            .columns
+            .iter()
+            .zip(&self.header_inputs)
+            .enumerate()
+            .map(|(ix, (column, input))| {
+                let type_name = self
+                    .result
+                    .columns
+                    .iter()
+                    .find(|c| c.name == column.source)
+                    .map(|c| c.type_name.to_lowercase())
+                    .unwrap_or_default();
+                h_flex()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    .items_center()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(
+                        div().w(px(200.)).child(
+                            Checkbox::new(("export-column", ix))
+                                .label(column.source.clone())
+                                .checked(column.included)
+                                .on_click(cx.listener(move |this, checked: &bool, _win, cx| {
+                                    this.template.columns[ix].included = *checked;
+                                    cx.notify();
+                                })),
+                        ),
+                    )
+                    .child(
+                        Label::new(type_name)
+                            .w(px(90.))
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .child(Input::new(input).small().disabled(!column.included)),
+                    )
+                    .child(
+                        Button::new(("export-column-up", ix))
+                            .icon(Icon::empty().path("icons/chevron-up.svg"))
+                            .xsmall()
+                            .ghost()
+                            .disabled(ix == 0)
+                            .on_click(cx.listener(move |this, _, _win, cx| {
+                                this.move_column(ix, true, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(("export-column-down", ix))
+                            .icon(Icon::empty().path("icons/chevron-down.svg"))
+                            .xsmall()
+                            .ghost()
+                            .disabled(ix + 1 == count)
+                            .on_click(cx.listener(move |this, _, _win, cx| {
+                                this.move_column(ix, false, cx);
+                            })),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .id("export-columns")
+            .max_h(px(280.))
+            .overflow_y_scroll()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded(cx.theme().radius)
+            .children(rows)
+    }
+}
+
+impl Render for ResultsExportForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let formats = [("CSV", TemplateFormat::Csv), ("JSON", TemplateFormat::Json)]
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, (label, format)| {
+                row.child(
+                    Button::new(label)
+                        .label(label)
+                        .small()
+                        .ghost()
+                        .selected(self.template.format == format)
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.template.format = format;
+                            cx.notify();
+                        })),
+                )
+            });
+        let date_time = DATE_TIME_CHOICES.into_iter().enumerate().fold(
+            h_flex().gap_1(),
+            |row, (ix, (label, zone, format))| {
+                let display = DateTimeDisplay { zone, format };
+                let selected = self.template.date_time == display
+                    || (zone == TimeZoneDisplay::Raw
+                        && self.template.date_time.zone == TimeZoneDisplay::Raw);
+                row.child(
+                    Button::new(("export-date-time", ix))
+                        .label(label)
+                        .small()
+                        .ghost()
+                        .selected(selected)
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.template.date_time = display;
+                            cx.notify();
+                        })),
+                )
+            },
+        );
+        let included = self.template.columns.iter().filter(|c| c.included).count();
+        let csv = self.template.format == TemplateFormat::Csv;
+
+        v_flex()
+            .gap_2()
+            .child(Label::new("Templates").text_sm().font_medium())
+            .child(self.render_templates(cx))
+            .child(
+                h_flex()
+                    .gap_4()
+                    .child(
+                        v_flex()
+                            .gap_1()
+                            .child(Label::new("Format").text_sm().font_medium())
+                            .child(formats),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_1()
+                            .child(Label::new("Dates and times").text_sm().font_medium())
+                            .child(date_time),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(Label::new("NULL as").text_sm())
+                    .child(
+                        div()
+                            .w(px(120.))
+                            .child(Input::new(&self.null_input).small().disabled(!csv)),
+                    )
+                    .when(!csv, |d| {
+                        d.child(Label::new("JSON writes null").text_xs().text_color(muted))
+                    }),
+            )
+            .child(
+                Label::new(format!(
+                    "Columns · {} of {} included, {} rows",
+                    included,
+                    self.template.columns.len(),
+                    self.result.rows.len()
+                ))
+                .text_sm()
+                .font_medium(),
+            )
+            .child(self.render_columns(cx))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(div().flex_1().child(Input::new(&self.name_input).small()))
+                    .child(
+                        Button::new("save-export-template")
+                            .label("Save Template")
+                            .small()
+                            .disabled(self.connection_id.is_none())
+                            .on_click(cx.listener(|this, _, _window, cx| this.save(cx))),
+                    ),
+            )
+            .when_some(self.status.clone(), |d, status| {
+                d.child(Label::new(status).text_xs().text_color(muted))
+            })
+    }
+}
@@ -1,6 +1,15 @@
+mod cell_editor;
+mod cell_inspector;
+mod column_stats;
+mod export_form;
 mod panel;
+mod row_detail;
+mod snapshots;
 mod table_delegate;
 
 pub(crate) use table_delegate::*;
 
-pub use panel::ResultsPanel;
+pub use panel::{
+    CellDown, CellFirstRow, CellLastRow, CellLeft, CellPageDown, CellPageUp, CellRight,
+    CellRowEnd, CellRowStart, CellUp, InspectCell, ResultsEvent, ResultsPanel, ToggleRowDetail,
+};
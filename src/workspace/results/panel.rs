@@ -1,41 +1,189 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::{
     services::{
-        QueryExecutionResult,
-        export::{stream_to_csv, stream_to_ndjson},
-        export_to_csv, export_to_json,
+        AppStore, CellDisplay, ColumnLayout, DatabaseDriver, DateTimeDisplay, DateTimeFormat,
+        NullDisplay, QueryExecutionResult, QueryResult, ResultFilter, ServerNotice,
+        TimeZoneDisplay, delete_row_sql, duplicate_row_sql,
+        export::{
+            CopyProgress, ExportTemplate, TemplateFormat, copy_to_csv_statement, read_snapshot,
+            snapshots_dir, stream_to_csv, stream_to_ndjson, write_snapshot,
+        },
+        export_to_csv, export_to_json, format_byte_size, format_row_count, is_binary_type,
+        referenced_row_sql, referencing_rows_sql, update_cell_sql,
+    },
+    state::{DisplayState, WindowConnection, WindowScoped, window_id},
+    workspace::{
+        notifications::{RecordedNotifications as _, Toasts as _},
+        results::{
+            EnhancedResultsTableDelegate,
+            cell_editor::CellEditor,
+            cell_inspector::show_cell_inspector,
+            export_form::ResultsExportForm,
+            row_detail::render_fields,
+            snapshots::{SnapshotsEvent, SnapshotsPanel},
+        },
     },
-    state::ConnectionState,
-    workspace::results::EnhancedResultsTableDelegate,
 };
+use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    ActiveTheme as _, Icon, Selectable as _, Sizable as _, WindowExt as _,
     button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
     h_flex,
+    input::{Input, InputEvent, InputState},
     label::Label,
+    menu::{DropdownMenu as _, PopupMenuItem},
     notification::NotificationType,
-    table::{Table, TableState},
+    table::{Table, TableEvent, TableState},
     v_flex,
 };
 
+actions!(
+    results,
+    [
+        CellUp,
+        CellDown,
+        CellLeft,
+        CellRight,
+        CellRowStart,
+        CellRowEnd,
+        CellFirstRow,
+        CellLastRow,
+        CellPageUp,
+        CellPageDown,
+        InspectCell,
+        ToggleRowDetail
+    ]
+);
+
+/// Choices in the Max Rows menu, besides no limit.
+const MAX_ROWS_OPTIONS: [usize; 4] = [100, 1_000, 10_000, 100_000];
+
 pub enum ExportFormat {
     Csv,
     Json,
 }
 
+pub enum ResultsEvent {
+    /// Load a query into the editor and run it.
+    RunQuery(String),
+    /// Ask the agent why `sql` failed with `error`.
+    ExplainError { sql: String, error: String },
+}
+
+impl EventEmitter<ResultsEvent> for ResultsPanel {}
+
+/// Which way to follow a foreign key from a cell.
+#[derive(Clone, Copy)]
+pub(crate) enum FollowKey {
+    /// The row the cell's foreign key points at.
+    Referenced,
+    /// Rows of other tables whose foreign keys point at the cell.
+    Referencing,
+}
+
+/// A CSV export running through `COPY`.
+struct CopyExport {
+    cancelled: Arc<AtomicBool>,
+    progress: CopyProgress,
+}
+
+/// Generated statements for a row of a single-table result.
+#[derive(Clone)]
+enum RowAction {
+    Delete,
+    Duplicate,
+    Update {
+        column: String,
+        value: Option<String>,
+    },
+}
+
 pub struct ResultsPanel {
+    window_id: WindowId,
     current_result: Option<QueryExecutionResult>,
     table: Entity<TableState<EnhancedResultsTableDelegate>>,
+    /// Server notices sent while the current result's query ran.
+    messages: Vec<ServerNotice>,
+    show_messages: bool,
+    /// Results of a script's statements that have something to show,
+    /// labelled with their statement. Empty for a single statement.
+    result_sets: Vec<(String, QueryExecutionResult)>,
+    active_set: usize,
+    /// The statement a single result came from, when known.
+    query: Option<String>,
+    filter_input: Entity<InputState>,
+    filter_regex: bool,
+    show_column_filters: bool,
+    /// One input per result column, created when the filter row is shown.
+    column_filter_inputs: Vec<Entity<InputState>>,
+    filter_error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+    _column_filter_subscriptions: Vec<Subscription>,
+    /// Zen mode: a larger font for presenting.
+    large_text: bool,
+    /// Whether the cursor's row is shown transposed beside the grid.
+    show_row_detail: bool,
+    _save_column_layout_task: Option<Task<()>>,
+    copy_export: Option<CopyExport>,
 }
 
 impl ResultsPanel {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let delegate = EnhancedResultsTableDelegate::new();
+        let delegate = EnhancedResultsTableDelegate::new(cx.entity().downgrade());
         let table = cx.new(|cx| TableState::new(delegate, window, cx).sortable(false));
+        let filter_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Filter results..."));
+        let _subscriptions = vec![
+            cx.subscribe(&filter_input, Self::on_filter_event),
+            // Redraw the cells when the date and time display changes.
+            cx.observe_global::<DisplayState>(|this, cx| {
+                this.table.update(cx, |_, cx| cx.notify());
+            }),
+            // Clicking a row or moving with the table's own keys moves the
+            // cell cursor along with it.
+            cx.subscribe(&table, |this, table, event: &TableEvent, cx| match event {
+                TableEvent::SelectRow(row_ix) => {
+                    table.update(cx, |table, cx| {
+                        let col = table.delegate().cursor().map_or(0, |(_, col)| col);
+                        table.delegate_mut().set_cursor(Some((*row_ix, col)));
+                        cx.notify();
+                    });
+                }
+                // Resized and moved columns are remembered for the query
+                TableEvent::ColumnWidthsChanged(widths) => {
+                    table.update(cx, |table, _| table.delegate_mut().set_widths(widths));
+                    this.save_column_layout(cx);
+                }
+                TableEvent::MoveColumn(..) => this.save_column_layout(cx),
+                _ => {}
+            }),
+        ];
 
         Self {
+            window_id: window_id(window),
             current_result: None,
             table,
+            messages: vec![],
+            show_messages: false,
+            result_sets: vec![],
+            active_set: 0,
+            query: None,
+            filter_input,
+            filter_regex: false,
+            show_column_filters: false,
+            column_filter_inputs: vec![],
+            filter_error: None,
+            _subscriptions,
+            _column_filter_subscriptions: vec![],
+            large_text: false,
+            show_row_detail: false,
+            _save_column_layout_task: None,
+            copy_export: None,
         }
     }
 
@@ -44,13 +192,297 @@ impl ResultsPanel {
     }
 
     pub fn update_result(&mut self, result: QueryExecutionResult, cx: &mut Context<Self>) {
+        self.messages.clear();
+        self.show_messages = false;
+        self.result_sets.clear();
+        self.query = None;
+        self.show(result, cx);
+    }
+
+    /// Show the results of a script, one tab per result set, opening the
+    /// last one: the failed statement when the script stopped early.
+    pub fn update_results(
+        &mut self,
+        results: Vec<(String, QueryExecutionResult)>,
+        cx: &mut Context<Self>,
+    ) {
+        let last = results.last().map(|(_, result)| result.clone());
+        let sets: Vec<(String, QueryExecutionResult)> = results
+            .into_iter()
+            .filter(|(_, result)| !matches!(result, QueryExecutionResult::Modified(_)))
+            .collect();
+        let Some(last) = last else {
+            return;
+        };
+
+        self.messages.clear();
+        self.show_messages = false;
+        self.query = None;
+        if sets.len() > 1 {
+            self.active_set = sets.len() - 1;
+            let (_, active) = &sets[self.active_set];
+            let active = active.clone();
+            self.result_sets = sets;
+            self.show(active, cx);
+        } else {
+            // Nothing to switch between; show the outcome as a single result.
+            self.result_sets.clear();
+            let shown = sets.into_iter().next().map(|(_, result)| result);
+            self.show(shown.unwrap_or(last), cx);
+        }
+    }
+
+    /// Show the results of a script with the tab of its `statement`-th
+    /// statement open, when that statement returned rows.
+    pub fn update_results_at(
+        &mut self,
+        results: Vec<(String, QueryExecutionResult)>,
+        statement: usize,
+        cx: &mut Context<Self>,
+    ) {
+        // Statements that only changed rows have no tab
+        let is_set =
+            |result: &QueryExecutionResult| !matches!(result, QueryExecutionResult::Modified(_));
+        let tab = results
+            .iter()
+            .take(statement)
+            .filter(|(_, result)| is_set(result))
+            .count();
+        let has_tab = results
+            .get(statement)
+            .is_some_and(|(_, result)| is_set(result));
+
+        self.update_results(results, cx);
+        if has_tab {
+            self.select_result_set(tab, cx);
+        }
+    }
+
+    /// Remember the statement the shown result came from, so a failure can
+    /// be explained. Script results know theirs already.
+    pub fn set_query(&mut self, sql: String) {
+        self.query = Some(sql);
+    }
+
+    /// The statement of the shown result, when known.
+    fn shown_query(&self) -> Option<String> {
+        match self.result_sets.get(self.active_set) {
+            Some((sql, _)) => Some(sql.clone()),
+            None => self.query.clone(),
+        }
+    }
+
+    fn select_result_set(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if let Some((_, result)) = self.result_sets.get(ix) {
+            let result = result.clone();
+            self.active_set = ix;
+            self.show(result, cx);
+        }
+    }
+
+    fn show(&mut self, result: QueryExecutionResult, cx: &mut Context<Self>) {
+        let same_columns = match (&self.current_result, &result) {
+            (Some(QueryExecutionResult::Select(old)), QueryExecutionResult::Select(new)) => old
+                .columns
+                .iter()
+                .map(|c| &c.name)
+                .eq(new.columns.iter().map(|c| &c.name)),
+            _ => false,
+        };
+        if !same_columns {
+            self.column_filter_inputs.clear();
+            self._column_filter_subscriptions.clear();
+        }
+
         self.current_result = Some(result.clone());
         if let QueryExecutionResult::Select(x) = result {
+            let previous_key = self
+                .table
+                .read(cx)
+                .delegate()
+                .layout_key()
+                .map(str::to_string);
             self.table.update(cx, |table, cx| {
                 table.delegate_mut().update(x.clone());
                 table.refresh(cx);
             });
+            if self.table.read(cx).delegate().layout_key() != previous_key.as_deref() {
+                self.load_column_layout(cx);
+            }
+            // Keep the filter across re-runs of the query.
+            self.apply_filter(cx);
+        }
+        cx.notify();
+    }
+
+    /// Restore how the shown query's columns were last arranged.
+    fn load_column_layout(&mut self, cx: &mut Context<Self>) {
+        let Some((key, _)) = self.table.read(cx).delegate().layout() else {
+            return;
+        };
+        cx.spawn(async move |this, cx| {
+            let Ok(store) = AppStore::singleton().await else {
+                return;
+            };
+            let saved = store
+                .workspace_state()
+                .get_json::<ColumnLayout>(&key)
+                .await
+                .ok()
+                .flatten();
+            if let Some(layout) = saved {
+                let _ = this.update(cx, |this, cx| {
+                    this.table.update(cx, |table, cx| {
+                        table.delegate_mut().apply_layout(&key, layout);
+                        table.refresh(cx);
+                    });
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// Persist the shown query's column layout. Resizing reports widths
+    /// continuously, so writes are debounced.
+    pub(crate) fn save_column_layout(&mut self, cx: &mut Context<Self>) {
+        let Some((key, layout)) = self.table.read(cx).delegate().layout() else {
+            return;
+        };
+        self._save_column_layout_task = Some(cx.spawn(async move |_this, cx| {
+            cx.background_executor()
+                .timer(std::time::Duration::from_millis(500))
+                .await;
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store.workspace_state().set_json(&key, &layout).await {
+                    tracing::warn!("Failed to save column layout: {}", e);
+                }
+            }
+        }));
+    }
+
+    fn on_filter_event(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            self.apply_filter(cx);
         }
+    }
+
+    /// Filter the grid's rows by the filter box and the column filters.
+    /// An invalid regex leaves the grid as it was.
+    fn apply_filter(&mut self, cx: &mut Context<Self>) {
+        let text = self.filter_input.read(cx).value().to_string();
+        let column_filters: Vec<String> = self
+            .column_filter_inputs
+            .iter()
+            .map(|input| input.read(cx).value().to_string())
+            .collect();
+
+        match ResultFilter::new(&text, &column_filters, self.filter_regex) {
+            Ok(filter) => {
+                self.filter_error = None;
+                self.table.update(cx, |table, cx| {
+                    table.delegate_mut().set_filter(filter);
+                    table.refresh(cx);
+                });
+            }
+            Err(e) => self.filter_error = Some(e.to_string()),
+        }
+        cx.notify();
+    }
+
+    fn toggle_filter_regex(&mut self, _: &ClickEvent, _: &mut Window, cx: &mut Context<Self>) {
+        self.filter_regex = !self.filter_regex;
+        self.apply_filter(cx);
+    }
+
+    fn toggle_column_filters(
+        &mut self,
+        _: &ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.show_column_filters = !self.show_column_filters;
+        if self.show_column_filters {
+            self.ensure_column_filters(window, cx);
+        } else {
+            // Hidden column filters no longer apply.
+            self.column_filter_inputs.clear();
+            self._column_filter_subscriptions.clear();
+            self.apply_filter(cx);
+        }
+        cx.notify();
+    }
+
+    /// Create the column filter inputs for the current result's columns.
+    fn ensure_column_filters(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(QueryExecutionResult::Select(result)) = &self.current_result else {
+            return;
+        };
+        if self.column_filter_inputs.len() == result.columns.len() {
+            return;
+        }
+
+        let names: Vec<String> = result.columns.iter().map(|c| c.name.clone()).collect();
+        self.column_filter_inputs = names
+            .into_iter()
+            .map(|name| cx.new(|cx| InputState::new(window, cx).placeholder(name)))
+            .collect();
+        self._column_filter_subscriptions = self
+            .column_filter_inputs
+            .iter()
+            .map(|input| cx.subscribe(input, Self::on_filter_event))
+            .collect();
+    }
+
+    /// Attach the notices of the current result. They open in the Messages
+    /// tab straight away when there are no rows to show.
+    /// Move the cell cursor by `d_row` rows and `d_col` columns, selecting
+    /// its row so the table scrolls to it.
+    fn move_cursor(&mut self, d_row: isize, d_col: isize, cx: &mut Context<Self>) {
+        let Some((row, col)) = self.table.read(cx).delegate().moved_cursor(d_row, d_col) else {
+            return;
+        };
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().set_cursor(Some((row, col)));
+            table.set_selected_row(row, cx);
+            cx.notify();
+        });
+    }
+
+    fn move_cursor_by_page(&mut self, pages: isize, cx: &mut Context<Self>) {
+        let page_rows = self.table.read(cx).delegate().page_rows() as isize;
+        self.move_cursor(pages * page_rows, 0, cx);
+    }
+
+    fn inspect_cursor_cell(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some((row_ix, col_ix)) = self.table.read(cx).delegate().cursor() {
+            self.inspect_cell(row_ix, col_ix, window, cx);
+        }
+    }
+
+    /// Show or hide the row detail pane. It follows the cell cursor, which
+    /// starts on the first row when there is none.
+    fn toggle_row_detail(&mut self, cx: &mut Context<Self>) {
+        self.show_row_detail = !self.show_row_detail;
+        if self.show_row_detail && self.table.read(cx).delegate().cursor().is_none() {
+            self.move_cursor(0, 0, cx);
+        }
+        cx.notify();
+    }
+
+    pub fn set_large_text(&mut self, large_text: bool, cx: &mut Context<Self>) {
+        self.large_text = large_text;
+        cx.notify();
+    }
+
+    pub fn set_messages(&mut self, messages: Vec<ServerNotice>, cx: &mut Context<Self>) {
+        self.show_messages = !messages.is_empty()
+            && !matches!(self.current_result, Some(QueryExecutionResult::Select(_)));
+        self.messages = messages;
         cx.notify();
     }
 
@@ -76,8 +508,11 @@ impl ResultsPanel {
 
         cx.spawn_in(window, async move |_this, cx| {
             if let Ok(Ok(Some(path))) = receiver.await {
-                let db_manager_result =
-                    cx.read_global::<ConnectionState, _>(|state, _, _| state.db_manager.clone());
+                let db_manager_result = cx.update(|window, cx| {
+                    WindowConnection::of(window_id(window), cx)
+                        .db_manager
+                        .clone()
+                });
 
                 let result: anyhow::Result<u64> = if let Ok(db_manager) = db_manager_result {
                     cx.background_executor()
@@ -101,14 +536,13 @@ impl ResultsPanel {
                     Ok(count) => {
                         let _ = cx.update(|window, cx| {
                             let info: SharedString = format!("Exported {} rows", count).into();
-                            window.push_notification((NotificationType::Info, info), cx);
+                            window.push_toast((NotificationType::Info, info), cx);
                         });
                     }
                     Err(e) => {
                         tracing::error!("Stream export failed: {}", e);
                         let _ = cx.update(|window, cx| {
-                            window
-                                .push_notification((NotificationType::Error, "Export failed"), cx);
+                            window.push_toast((NotificationType::Error, "Export failed"), cx);
                         });
                     }
                 }
@@ -117,6 +551,196 @@ impl ResultsPanel {
         .detach();
     }
 
+    /// Export the current result's query to CSV with `COPY`, which writes
+    /// rows as the server formats them instead of decoding each one.
+    /// Progress shows in the toolbar, where the export can be cancelled.
+    fn copy_export_results(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(QueryExecutionResult::Select(result)) = &self.current_result else {
+            return;
+        };
+        if self.copy_export.is_some() {
+            return;
+        }
+
+        let sql = result.original_query.clone();
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let suggested_name = format!("export_{}.csv", timestamp);
+        let home = dirs::home_dir().unwrap_or_default();
+        let receiver = cx.prompt_for_new_path(&home, Some(&suggested_name));
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(path))) = receiver.await else {
+                return;
+            };
+            let Ok(db_manager) = cx.update(|window, cx| {
+                WindowConnection::of(window_id(window), cx)
+                    .db_manager
+                    .clone()
+            }) else {
+                return;
+            };
+
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let started = this.update(cx, |this, cx| {
+                this.copy_export = Some(CopyExport {
+                    cancelled: cancelled.clone(),
+                    progress: CopyProgress::default(),
+                });
+                cx.notify();
+            });
+            if started.is_err() {
+                return;
+            }
+
+            let (progress_tx, progress_rx) = async_channel::unbounded::<CopyProgress>();
+            let progress_panel = this.clone();
+            cx.spawn(async move |cx| {
+                while let Ok(progress) = progress_rx.recv().await {
+                    let _ = progress_panel.update(cx, |this, cx| {
+                        if let Some(export) = &mut this.copy_export {
+                            export.progress = progress;
+                            cx.notify();
+                        }
+                    });
+                }
+            })
+            .detach();
+
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    db_manager
+                        .copy_to_csv(&sql, &path, cancelled, move |progress| {
+                            let _ = progress_tx.try_send(progress);
+                        })
+                        .await
+                })
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.copy_export = None;
+                cx.notify();
+                match result {
+                    Ok(Some(done)) => {
+                        let info: SharedString = format!(
+                            "Exported {} rows ({})",
+                            format_row_count(done.rows),
+                            format_byte_size(done.bytes as usize)
+                        )
+                        .into();
+                        window.push_toast((NotificationType::Info, info), cx);
+                    }
+                    Ok(None) => {
+                        window.push_toast((NotificationType::Info, "Export cancelled"), cx);
+                    }
+                    Err(e) => {
+                        tracing::error!("COPY export failed: {}", e);
+                        let message: SharedString = format!("Export failed: {}", e).into();
+                        window.push_recorded_notification((NotificationType::Error, message), cx);
+                    }
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Ask which columns to export and how, optionally from a template
+    /// saved for the connection, then export the rows fetched.
+    fn open_export_form(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(QueryExecutionResult::Select(result)) = &self.current_result else {
+            return;
+        };
+        let connection_id = WindowConnection::of(self.window_id, cx)
+            .active_connection
+            .as_ref()
+            .map(|connection| connection.id);
+        let form = ResultsExportForm::view(result.clone(), connection_id, window, cx);
+        let panel = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            let panel = panel.clone();
+            dialog
+                .title("Export Results")
+                .width(px(680.))
+                .child(form.clone())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Export…"))
+                .on_ok(move |_, window, cx| {
+                    let form = form.read(cx);
+                    let template = form.template(cx);
+                    if !template.columns.iter().any(|column| column.included) {
+                        window.push_toast(
+                            (NotificationType::Warning, "Include at least one column"),
+                            cx,
+                        );
+                        return false;
+                    }
+                    let result = form.result().clone();
+                    let _ = panel.update(cx, |this, cx| {
+                        this.export_with_template(result, template, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn export_with_template(
+        &mut self,
+        result: QueryResult,
+        template: ExportTemplate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let stem = match template.name.as_str() {
+            "" => "export".to_string(),
+            name => name.replace(|c: char| !c.is_alphanumeric(), "_"),
+        };
+        let suggested_name = match template.format {
+            TemplateFormat::Csv => format!("{}_{}.csv", stem, timestamp),
+            TemplateFormat::Json => format!("{}_{}.json", stem, timestamp),
+        };
+        let home = dirs::home_dir().unwrap_or_default();
+        let receiver = cx.prompt_for_new_path(&home, Some(&suggested_name));
+
+        cx.spawn_in(window, async move |_this, cx| {
+            let Ok(Ok(Some(path))) = receiver.await else {
+                return;
+            };
+            let written: anyhow::Result<()> = async {
+                let content = template.export(&result)?;
+                async_fs::write(&path, content).await?;
+                Ok(())
+            }
+            .await;
+
+            let _ = cx.update(|window, cx| match written {
+                Ok(()) => {
+                    let info: SharedString = format!(
+                        "Exported {} rows",
+                        format_row_count(result.rows.len() as u64)
+                    )
+                    .into();
+                    window.push_toast((NotificationType::Info, info), cx);
+                }
+                Err(e) => {
+                    tracing::error!("Export failed: {}", e);
+                    window
+                        .push_recorded_notification((NotificationType::Error, "Export failed"), cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn cancel_copy_export(&mut self, cx: &mut Context<Self>) {
+        if let Some(export) = &self.copy_export {
+            export.cancelled.store(true, Ordering::Relaxed);
+            cx.notify();
+        }
+    }
+
     #[allow(dead_code)]
     fn export_results(
         &mut self,
@@ -155,7 +779,7 @@ impl ResultsPanel {
                 if let Err(e) = result {
                     tracing::error!("Export failed: {}", e);
                     let _ = cx.update(|window, cx| {
-                        window.push_notification(
+                        window.push_recorded_notification(
                             (
                                 NotificationType::Error,
                                 "Failed to save file. Please try again.",
@@ -165,10 +789,7 @@ impl ResultsPanel {
                     });
                 } else {
                     let _ = cx.update(|window, cx| {
-                        window.push_notification(
-                            (NotificationType::Info, "File saved successfully."),
-                            cx,
-                        );
+                        window.push_toast((NotificationType::Info, "File saved successfully."), cx);
                     });
                 }
             }
@@ -176,21 +797,747 @@ impl ResultsPanel {
         .detach();
     }
 
+    /// Ask for a name, then save the current result as a compressed
+    /// snapshot under `~/.pgui/snapshots`.
+    fn save_snapshot(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(QueryExecutionResult::Select(result)) = &self.current_result else {
+            return;
+        };
+        let result = result.clone();
+
+        // Default to the start of the query, which is usually what it's for.
+        let default_name: String = result
+            .original_query
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(60)
+            .collect();
+        let name_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Snapshot name")
+                .default_value(default_name)
+        });
+        let panel = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let name_input = name_input.clone();
+            let panel = panel.clone();
+            let result = result.clone();
+            dialog
+                .title("Save Snapshot")
+                .width(px(420.))
+                .child(Input::new(&name_input))
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Save"))
+                .on_ok(move |_, window, cx| {
+                    let name = name_input.read(cx).value().trim().to_string();
+                    let result = result.clone();
+                    let _ = panel.update(cx, |this, cx| {
+                        this.write_snapshot(result, name, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn write_snapshot(
+        &mut self,
+        result: QueryResult,
+        name: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn_in(window, async move |_this, cx| {
+            let saved = cx
+                .background_executor()
+                .spawn(async move { write_snapshot(&result, &name, &snapshots_dir()?) })
+                .await;
+
+            let _ = cx.update(|window, cx| {
+                let (notification_type, message): (_, SharedString) = match saved {
+                    Ok(path) => (
+                        NotificationType::Success,
+                        format!("Snapshot saved to {}", path.display()).into(),
+                    ),
+                    Err(e) => {
+                        tracing::error!("Saving snapshot failed: {}", e);
+                        (NotificationType::Error, "Failed to save snapshot".into())
+                    }
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+            });
+        })
+        .detach();
+    }
+
+    /// List the saved snapshots to reopen one.
+    fn open_snapshots(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let snapshots = SnapshotsPanel::view(window, cx);
+        cx.subscribe_in(
+            &snapshots,
+            window,
+            |this, _, event: &SnapshotsEvent, window, cx| {
+                window.close_dialog(cx);
+                match event {
+                    SnapshotsEvent::Open(path) => this.open_snapshot(path.clone(), window, cx),
+                    SnapshotsEvent::Browse => this.browse_snapshot(window, cx),
+                }
+            },
+        )
+        .detach();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title("Snapshots")
+                .width(px(640.))
+                .child(snapshots.clone())
+        });
+    }
+
+    /// Pick a snapshot's `.json` file from any folder.
+    fn browse_snapshot(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Open snapshot".into()),
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = this.update_in(cx, |this, window, cx| this.open_snapshot(path, window, cx));
+        })
+        .detach();
+    }
+
+    /// Show the rows of the snapshot at `path` in the grid.
+    fn open_snapshot(&mut self, path: PathBuf, window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let snapshot = cx
+                .background_executor()
+                .spawn(async move { read_snapshot(&path) })
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| match snapshot {
+                Ok(result) => this.update_result(QueryExecutionResult::Select(result), cx),
+                Err(e) => {
+                    let message: SharedString = format!("Failed to open snapshot: {}", e).into();
+                    window.push_recorded_notification((NotificationType::Error, message), cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Source table (`schema`, `name`) and the values of the row at
+    /// `row_ix`, by column name. Only columns that come from that table are
+    /// included. The values are an error when one of them could not be
+    /// decoded, as it cannot be written into a statement.
+    fn row_source(
+        &self,
+        row_ix: usize,
+        cx: &App,
+    ) -> Option<(
+        String,
+        String,
+        anyhow::Result<Vec<(String, Option<String>)>>,
+    )> {
+        let cells = self.table.read(cx).delegate().row(row_ix)?;
+        let source = cells
+            .iter()
+            .find_map(|cell| cell.column_metadata.table_name.clone())?;
+        let (schema, name) = source.split_once('.')?;
+
+        let values = cells
+            .iter()
+            .filter(|cell| cell.column_metadata.table_name.as_deref() == Some(source.as_str()))
+            .map(|cell| Ok((cell.column_metadata.name.clone(), cell.written_value()?)))
+            .collect();
+
+        Some((schema.to_string(), name.to_string(), values))
+    }
+
+    fn on_row_action(&mut self, action: RowAction, window: &mut Window, cx: &mut Context<Self>) {
+        match self.table.read(cx).selected_row() {
+            Some(row_ix) => self.row_action(row_ix, action, window, cx),
+            None => window.push_toast(
+                (
+                    NotificationType::Warning,
+                    "Select a row from a single-table query first",
+                ),
+                cx,
+            ),
+        }
+    }
+
+    fn row_action(
+        &mut self,
+        row_ix: usize,
+        action: RowAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((schema, table, values)) = self.row_source(row_ix, cx) else {
+            window.push_toast(
+                (
+                    NotificationType::Warning,
+                    "Select a row from a single-table query first",
+                ),
+                cx,
+            );
+            return;
+        };
+        let values = match values {
+            Ok(values) => values,
+            Err(e) => {
+                let message: SharedString =
+                    format!("{}, so the row cannot be written into a statement", e).into();
+                window.push_toast((NotificationType::Warning, message), cx);
+                return;
+            }
+        };
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let driver = db_manager.driver().await;
+            let info = db_manager.get_table_edit_info(&schema, &table).await;
+
+            let sql = match (driver, info) {
+                (Some(driver), Ok(info)) => match &action {
+                    RowAction::Delete => delete_row_sql(&driver, &info, &values),
+                    RowAction::Duplicate => Ok(duplicate_row_sql(&driver, &info, &values)),
+                    RowAction::Update { column, value } => {
+                        update_cell_sql(&driver, &info, &values, column, value)
+                    }
+                },
+                (None, _) => Err(anyhow::anyhow!("Database not connected")),
+                (_, Err(e)) => Err(e),
+            };
+
+            let _ = this.update_in(cx, |this, window, cx| match sql {
+                Ok(sql) => this.confirm_row_statement(sql, window, cx),
+                Err(e) => {
+                    let message: SharedString = format!("{}", e).into();
+                    window.push_recorded_notification((NotificationType::Error, message), cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Open the cell at `row_ix`, `col_ix`: text in an editor that can save
+    /// it back to its table, NULL, binary and undecoded values in the
+    /// inspector.
+    pub(crate) fn inspect_cell(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(cell) = self
+            .table
+            .read(cx)
+            .delegate()
+            .row(row_ix)
+            .and_then(|cells| cells.into_iter().nth(col_ix))
+        else {
+            return;
+        };
+        let column = cell.column_metadata;
+        if cell.is_null || cell.undecoded || is_binary_type(&column.type_name) {
+            show_cell_inspector(column, cell.value, cell.is_null, window, cx);
+            return;
+        }
+
+        // Only columns of the row's source table can be written back.
+        let source = self
+            .row_source(row_ix, cx)
+            .map(|(schema, name, _)| format!("{}.{}", schema, name));
+        let editable = source.is_some() && column.table_name == source;
+        let title = format!("{} ({})", column.name, column.type_name);
+        let column_name = column.name.clone();
+        let editor = CellEditor::view(&column, cell.value, editable, window, cx);
+        let panel = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let dialog = dialog
+                .title(title.clone())
+                .width(px(720.))
+                .child(editor.clone());
+            if !editable {
+                return dialog;
+            }
+
+            let (editor, panel, column_name) = (editor.clone(), panel.clone(), column_name.clone());
+            dialog
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Save"))
+                .on_ok(move |_, window, cx| {
+                    let Some(value) = editor.read(cx).changed_value(cx) else {
+                        return true;
+                    };
+                    let action = RowAction::Update {
+                        column: column_name.clone(),
+                        value: Some(value),
+                    };
+                    let _ = panel.update(cx, |this, cx| {
+                        this.row_action(row_ix, action, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    /// Run a query for the rows linked to the cell at `row_ix`, `col_ix` by
+    /// a foreign key. When several keys match, the user picks one.
+    pub(crate) fn follow_key(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        follow: FollowKey,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(cell) = self
+            .table
+            .read(cx)
+            .delegate()
+            .row(row_ix)
+            .and_then(|cells| cells.into_iter().nth(col_ix))
+        else {
+            return;
+        };
+        let column = cell.column_metadata;
+        let Some((schema, table)) = column
+            .table_name
+            .as_deref()
+            .and_then(|source| source.split_once('.'))
+            .map(|(schema, table)| (schema.to_string(), table.to_string()))
+        else {
+            window.push_toast(
+                (
+                    NotificationType::Warning,
+                    "This column does not come from a table",
+                ),
+                cx,
+            );
+            return;
+        };
+        let value = cell.value;
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let targets = match (db_manager.driver().await, follow) {
+                (None, _) => Err(anyhow::anyhow!("Database not connected")),
+                (Some(driver), FollowKey::Referenced) => db_manager
+                    .get_foreign_keys(&schema, &table)
+                    .await
+                    .map(|keys| {
+                        let mut targets: Vec<(String, String, String)> = keys
+                            .iter()
+                            .filter(|fk| fk.column_name == column.name)
+                            .map(|fk| {
+                                (
+                                    fk.constraint_name.clone(),
+                                    format!(
+                                        "{}.{}",
+                                        fk.foreign_table_schema, fk.foreign_table_name
+                                    ),
+                                    referenced_row_sql(&driver, fk, &value),
+                                )
+                            })
+                            .collect();
+                        targets.dedup_by(|a, b| a.0 == b.0);
+                        targets
+                    }),
+                (Some(driver), FollowKey::Referencing) => db_manager
+                    .get_referencing_keys(&schema, &table)
+                    .await
+                    .map(|keys| {
+                        keys.iter()
+                            .filter(|key| key.referenced_column == column.name)
+                            .map(|key| {
+                                (
+                                    key.constraint_name.clone(),
+                                    format!(
+                                        "{}.{} ({})",
+                                        key.table_schema, key.table_name, key.column_name
+                                    ),
+                                    referencing_rows_sql(&driver, key, &value),
+                                )
+                            })
+                            .collect()
+                    }),
+            };
+
+            let _ = this.update_in(cx, |this, window, cx| match targets {
+                Err(e) => {
+                    let message: SharedString = format!("{}", e).into();
+                    window.push_recorded_notification((NotificationType::Error, message), cx);
+                }
+                Ok(targets) if targets.is_empty() => {
+                    let message: SharedString = match follow {
+                        FollowKey::Referenced => {
+                            format!("{}.{} has no foreign key", table, column.name)
+                        }
+                        FollowKey::Referencing => {
+                            format!("No foreign keys point at {}.{}", table, column.name)
+                        }
+                    }
+                    .into();
+                    window.push_toast((NotificationType::Info, message), cx);
+                }
+                Ok(mut targets) if targets.len() == 1 => {
+                    let (_, _, sql) = targets.remove(0);
+                    cx.emit(ResultsEvent::RunQuery(sql));
+                }
+                Ok(targets) => this.choose_key_target(targets, window, cx),
+            });
+        })
+        .detach();
+    }
+
+    /// Let the user pick which table to open when a cell is linked to
+    /// several by foreign keys.
+    fn choose_key_target(
+        &mut self,
+        targets: Vec<(String, String, String)>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let rows = targets
+                .iter()
+                .enumerate()
+                .map(|(ix, (constraint, label, sql))| {
+                    let (panel, sql) = (panel.clone(), sql.clone());
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .child(
+                            v_flex()
+                                .flex_1()
+                                .child(Label::new(label.clone()).text_sm())
+                                .child(
+                                    Label::new(constraint.clone())
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground),
+                                ),
+                        )
+                        .child(
+                            Button::new(("open-key-target", ix))
+                                .label("Open")
+                                .xsmall()
+                                .on_click(move |_, window, cx| {
+                                    let _ = panel.update(cx, |_, cx| {
+                                        cx.emit(ResultsEvent::RunQuery(sql.clone()));
+                                    });
+                                    window.close_dialog(cx);
+                                }),
+                        )
+                })
+                .collect::<Vec<_>>();
+            dialog
+                .title("Choose Table")
+                .width(px(480.))
+                .child(v_flex().gap_2().children(rows))
+        });
+    }
+
+    /// Show the generated statement and run it once confirmed.
+    fn confirm_row_statement(&mut self, sql: String, window: &mut Window, cx: &mut Context<Self>) {
+        let panel = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            let sql_to_run = sql.clone();
+            dialog
+                .title("Review Statement")
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(Label::new("The following statement will be executed:").text_sm())
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(sql.clone()),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = panel.update(cx, |this, cx| {
+                        this.run_row_statement(sql_to_run.clone(), window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn run_row_statement(&mut self, sql: String, window: &mut Window, cx: &mut Context<Self>) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+        let refresh_query = match &self.current_result {
+            Some(QueryExecutionResult::Select(result)) => Some(result.original_query.clone()),
+            _ => None,
+        };
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.execute_query_enhanced(&sql).await;
+            let failed = match &result {
+                QueryExecutionResult::Error(e) => Some(e.message.clone()),
+                _ => None,
+            };
+
+            let _ = cx.update(|window, cx| {
+                let (notification_type, message): (_, SharedString) = match &failed {
+                    Some(message) => (NotificationType::Error, message.clone().into()),
+                    None => (NotificationType::Success, "Statement executed".into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+            });
+
+            // Re-run the browsing query so the grid reflects the change.
+            if let (None, Some(query)) = (failed, refresh_query) {
+                let refreshed = db_manager.execute_query_enhanced(&query).await;
+                let _ = this.update(cx, |this, cx| this.update_result(refreshed, cx));
+            }
+        })
+        .detach();
+    }
+
     fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let date_time = cx.global::<DisplayState>().date_time;
+        let date_time_menu = Button::new("date-time-display")
+            .icon(Icon::empty().path("icons/calendar.svg"))
+            .small()
+            .ghost()
+            .tooltip("Date and Time Display")
+            .dropdown_menu(move |menu, _window, _cx| {
+                let zone_item = |label: &'static str, zone: TimeZoneDisplay| {
+                    PopupMenuItem::new(label)
+                        .checked(date_time.zone == zone)
+                        .on_click(move |_, _window, cx| {
+                            let display = DateTimeDisplay { zone, ..date_time };
+                            DisplayState::set_date_time(display, cx);
+                        })
+                };
+                let format_item = |label: &'static str, format: DateTimeFormat| {
+                    PopupMenuItem::new(label)
+                        .checked(date_time.format == format)
+                        .on_click(move |_, _window, cx| {
+                            let display = DateTimeDisplay {
+                                format,
+                                ..date_time
+                            };
+                            DisplayState::set_date_time(display, cx);
+                        })
+                };
+                menu.item(zone_item("Raw Values", TimeZoneDisplay::Raw))
+                    .item(zone_item("UTC", TimeZoneDisplay::Utc))
+                    .item(zone_item("Local Time", TimeZoneDisplay::Local))
+                    .separator()
+                    .item(format_item("ISO 8601", DateTimeFormat::Iso))
+                    .item(format_item("Locale Format", DateTimeFormat::Locale))
+            });
+
+        let cells = cx.global::<DisplayState>().cells;
+        let cell_display_menu = Button::new("cell-display")
+            .icon(Icon::empty().path("icons/eye.svg"))
+            .small()
+            .ghost()
+            .tooltip("NULL and Whitespace Display")
+            .dropdown_menu(move |menu, _window, _cx| {
+                let null_item = |label: &'static str, null: NullDisplay| {
+                    PopupMenuItem::new(label)
+                        .checked(cells.null == null)
+                        .on_click(move |_, _window, cx| {
+                            DisplayState::set_cells(CellDisplay { null, ..cells }, cx);
+                        })
+                };
+                menu.item(null_item("NULL as Chip", NullDisplay::Chip))
+                    .item(null_item("NULL as Text", NullDisplay::Text))
+                    .separator()
+                    .item(
+                        PopupMenuItem::new("Show Whitespace Markers")
+                            .checked(cells.whitespace_markers)
+                            .on_click(move |_, _window, cx| {
+                                let display = CellDisplay {
+                                    whitespace_markers: !cells.whitespace_markers,
+                                    ..cells
+                                };
+                                DisplayState::set_cells(display, cx);
+                            }),
+                    )
+            });
+
+        // On Postgres a single query can also be exported with COPY, and
+        // the button shows its progress while it runs
+        let copyable = WindowConnection::of(self.window_id, cx)
+            .active_connection
+            .as_ref()
+            .is_some_and(|c| c.driver == DatabaseDriver::Postgres)
+            && matches!(
+                &self.current_result,
+                Some(QueryExecutionResult::Select(result))
+                    if copy_to_csv_statement(&result.original_query).is_some()
+            );
+        let panel = cx.entity().downgrade();
+        let export_csv = match &self.copy_export {
+            Some(export) => h_flex()
+                .gap_1()
+                .items_center()
+                .child(
+                    Label::new(format!(
+                        "Exporting {} rows · {}",
+                        format_row_count(export.progress.rows),
+                        format_byte_size(export.progress.bytes as usize)
+                    ))
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+                )
+                .child(
+                    Button::new("cancel-copy-export")
+                        .icon(Icon::empty().path("icons/circle-x.svg"))
+                        .small()
+                        .ghost()
+                        .tooltip("Cancel Export")
+                        .on_click(cx.listener(|this, _, _win, cx| {
+                            this.cancel_copy_export(cx);
+                        })),
+                )
+                .into_any_element(),
+            None => Button::new("export-csv")
+                .icon(Icon::empty().path("icons/file-spreadsheet.svg"))
+                .small()
+                .ghost()
+                .tooltip("Export CSV")
+                .dropdown_menu(move |menu, _window, _cx| {
+                    let stream_panel = panel.clone();
+                    let copy_panel = panel.clone();
+                    let form_panel = panel.clone();
+                    let menu = menu.item(PopupMenuItem::new("Export CSV").on_click(
+                        move |_, window, cx| {
+                            let _ = stream_panel.update(cx, |this, cx| {
+                                this.stream_export_results(ExportFormat::Csv, window, cx);
+                            });
+                        },
+                    ));
+                    let menu = if copyable {
+                        menu.item(PopupMenuItem::new("Fast Export with COPY").on_click(
+                            move |_, window, cx| {
+                                let _ = copy_panel.update(cx, |this, cx| {
+                                    this.copy_export_results(window, cx);
+                                });
+                            },
+                        ))
+                    } else {
+                        menu
+                    };
+                    menu.separator()
+                        .item(PopupMenuItem::new("Customize Export…").on_click(
+                            move |_, window, cx| {
+                                let _ = form_panel.update(cx, |this, cx| {
+                                    this.open_export_form(window, cx);
+                                });
+                            },
+                        ))
+                })
+                .into_any_element(),
+        };
+
+        let max_rows = cx.global::<DisplayState>().max_rows;
+        let max_rows_menu = Button::new("max-rows")
+            .icon(Icon::empty().path("icons/list-ordered.svg"))
+            .small()
+            .ghost()
+            .tooltip("Max Rows")
+            .dropdown_menu(move |menu, _window, _cx| {
+                let limit_item = |limit: Option<usize>| {
+                    let label = match limit {
+                        Some(limit) => format!("{} rows", format_row_count(limit as u64)),
+                        None => "No Limit".to_string(),
+                    };
+                    PopupMenuItem::new(label)
+                        .checked(max_rows == limit)
+                        .on_click(move |_, _window, cx| DisplayState::set_max_rows(limit, cx))
+                };
+                MAX_ROWS_OPTIONS
+                    .into_iter()
+                    .fold(menu, |menu, limit| menu.item(limit_item(Some(limit))))
+                    .separator()
+                    .item(limit_item(None))
+            });
+
         h_flex()
             .gap_1()
             .justify_end()
             .items_center()
+            .child(max_rows_menu)
+            .child(date_time_menu)
+            .child(cell_display_menu)
+            .child(
+                Button::new("row-detail")
+                    .icon(Icon::empty().path("icons/panel-right.svg"))
+                    .small()
+                    .ghost()
+                    .selected(self.show_row_detail)
+                    .tooltip("Row Detail (Space)")
+                    .on_click(cx.listener(|this, _, _, cx| this.toggle_row_detail(cx))),
+            )
+            .child(
+                Button::new("duplicate-row")
+                    .icon(Icon::empty().path("icons/copy.svg"))
+                    .small()
+                    .ghost()
+                    .tooltip("Duplicate Selected Row")
+                    .on_click(cx.listener(|this, _, win, cx| {
+                        this.on_row_action(RowAction::Duplicate, win, cx);
+                    })),
+            )
+            .child(
+                Button::new("delete-row")
+                    .icon(Icon::empty().path("icons/trash.svg"))
+                    .small()
+                    .ghost()
+                    .tooltip("Delete Selected Row")
+                    .on_click(cx.listener(|this, _, win, cx| {
+                        this.on_row_action(RowAction::Delete, win, cx);
+                    })),
+            )
+            .child(
+                Button::new("save-snapshot")
+                    .icon(Icon::empty().path("icons/archive.svg"))
+                    .small()
+                    .ghost()
+                    .tooltip("Save Snapshot")
+                    .on_click(cx.listener(|this, _, win, cx| {
+                        this.save_snapshot(win, cx);
+                    })),
+            )
             .child(
-                Button::new("export-csv")
-                    .icon(Icon::empty().path("icons/file-spreadsheet.svg"))
+                Button::new("open-snapshot")
+                    .icon(Icon::empty().path("icons/folder-open.svg"))
                     .small()
                     .ghost()
-                    .tooltip("Export CSV")
+                    .tooltip("Snapshots")
                     .on_click(cx.listener(|this, _, win, cx| {
-                        this.stream_export_results(ExportFormat::Csv, win, cx);
+                        this.open_snapshots(win, cx);
                     })),
             )
+            .child(export_csv)
             .child(
                 Button::new("export-json")
                     .icon(Icon::empty().path("icons/file-braces.svg"))
@@ -202,19 +1549,328 @@ impl ResultsPanel {
                     })),
             )
     }
-}
 
-impl Render for ResultsPanel {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render_filter_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let (shown, total) = self.table.read(cx).delegate().row_counts();
+        let status = match &self.filter_error {
+            Some(error) => Some((error.clone(), cx.theme().danger)),
+            None if shown != total => Some((
+                format!("{} of {} rows", shown, total),
+                cx.theme().muted_foreground,
+            )),
+            None => None,
+        };
+
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(
+                        div()
+                            .w(px(260.))
+                            .child(Input::new(&self.filter_input).small()),
+                    )
+                    .child(
+                        Button::new("filter-regex")
+                            .label(".*")
+                            .small()
+                            .ghost()
+                            .selected(self.filter_regex)
+                            .tooltip("Use Regular Expression")
+                            .on_click(cx.listener(Self::toggle_filter_regex)),
+                    )
+                    .child(
+                        Button::new("column-filters")
+                            .icon(Icon::empty().path("icons/table-properties.svg"))
+                            .small()
+                            .ghost()
+                            .selected(self.show_column_filters)
+                            .tooltip("Filter by Column")
+                            .on_click(cx.listener(Self::toggle_column_filters)),
+                    )
+                    .when_some(status, |d, (status, color)| {
+                        d.child(Label::new(status).text_xs().text_color(color))
+                    }),
+            )
+            .when(self.show_column_filters, |d| {
+                d.child(
+                    h_flex().gap_1().children(
+                        self.column_filter_inputs
+                            .iter()
+                            .map(|input| div().w(px(140.)).child(Input::new(input).small())),
+                    ),
+                )
+            })
+    }
+
+    fn render_result_set_tabs(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let tabs = self
+            .result_sets
+            .iter()
+            .enumerate()
+            .map(|(ix, (statement, result))| {
+                let mut label: String = statement.split_whitespace().collect::<Vec<_>>().join(" ");
+                if label.chars().count() > 40 {
+                    label = format!("{}…", label.chars().take(40).collect::<String>());
+                }
+                Button::new(("result-set", ix))
+                    .label(label)
+                    .small()
+                    .ghost()
+                    .selected(ix == self.active_set)
+                    .when(matches!(result, QueryExecutionResult::Error(_)), |b| {
+                        b.icon(Icon::empty().path("icons/triangle-alert.svg"))
+                    })
+                    .tooltip(statement.clone())
+                    .on_click(cx.listener(move |this, _, _win, cx| {
+                        this.select_result_set(ix, cx);
+                    }))
+            });
+
+        div()
+            .id("result-sets")
+            .px_2()
+            .pt_1()
+            .overflow_x_scroll()
+            .child(h_flex().gap_1().children(tabs))
+    }
+
+    fn render_tabs(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_1()
+            .px_2()
+            .pt_1()
+            .child(
+                Button::new("results-tab")
+                    .label("Results")
+                    .small()
+                    .ghost()
+                    .selected(!self.show_messages)
+                    .on_click(cx.listener(|this, _, _win, cx| {
+                        this.show_messages = false;
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Button::new("messages-tab")
+                    .label(format!("Messages ({})", self.messages.len()))
+                    .small()
+                    .ghost()
+                    .selected(self.show_messages)
+                    .on_click(cx.listener(|this, _, _win, cx| {
+                        this.show_messages = true;
+                        cx.notify();
+                    })),
+            )
+    }
+
+    fn render_messages(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self.messages.iter().map(|notice| {
+            let color = match notice.severity {
+                "WARNING" | "ERROR" => cx.theme().warning,
+                _ => cx.theme().foreground,
+            };
+            h_flex()
+                .gap_2()
+                .items_start()
+                .child(
+                    Label::new(notice.severity)
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                )
+                .child(
+                    Label::new(notice.message.clone())
+                        .text_sm()
+                        .text_color(color),
+                )
+        });
+
+        div()
+            .id("messages")
+            .size_full()
+            .p_2()
+            .overflow_y_scroll()
+            .font_family("monospace")
+            .child(v_flex().gap_1().children(rows))
+    }
+
+    /// The result table. Arrow keys, Home/End and PageUp/PageDown move a
+    /// cell cursor while it has focus, and Enter opens the cell.
+    fn render_grid(&self, window: &Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focused = self.table.focus_handle(cx).contains_focused(window, cx);
+        div()
+            .key_context("ResultsGrid")
+            .flex_1()
+            .min_w_0()
+            .h_full()
+            .border_1()
+            .rounded(cx.theme().radius)
+            .border_color(if focused {
+                cx.theme().ring
+            } else {
+                transparent_black()
+            })
+            .on_action(cx.listener(|this, _: &CellUp, _, cx| this.move_cursor(-1, 0, cx)))
+            .on_action(cx.listener(|this, _: &CellDown, _, cx| this.move_cursor(1, 0, cx)))
+            .on_action(cx.listener(|this, _: &CellLeft, _, cx| this.move_cursor(0, -1, cx)))
+            .on_action(cx.listener(|this, _: &CellRight, _, cx| this.move_cursor(0, 1, cx)))
+            .on_action(
+                cx.listener(|this, _: &CellRowStart, _, cx| this.move_cursor(0, isize::MIN, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &CellRowEnd, _, cx| this.move_cursor(0, isize::MAX, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &CellFirstRow, _, cx| this.move_cursor(isize::MIN, 0, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &CellLastRow, _, cx| this.move_cursor(isize::MAX, 0, cx)),
+            )
+            .on_action(cx.listener(|this, _: &CellPageUp, _, cx| this.move_cursor_by_page(-1, cx)))
+            .on_action(cx.listener(|this, _: &CellPageDown, _, cx| this.move_cursor_by_page(1, cx)))
+            .on_action(
+                cx.listener(|this, _: &InspectCell, window, cx| {
+                    this.inspect_cursor_cell(window, cx)
+                }),
+            )
+            .on_action(cx.listener(|this, _: &ToggleRowDetail, _, cx| this.toggle_row_detail(cx)))
+            .child(
+                Table::new(&self.table.clone())
+                    .stripe(true)
+                    .when(self.large_text, |table| table.large()),
+            )
+    }
+
+    /// The cursor's row transposed: one line per column, with full values,
+    /// including columns hidden from the grid.
+    fn render_row_detail(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let delegate = self.table.read(cx).delegate();
+        let (shown, _) = delegate.row_counts();
+        let row_ix = delegate.cursor().map(|(row, _)| row);
+        let cells = row_ix.and_then(|row| delegate.row(row)).unwrap_or_default();
+        let nav_button = |id: &'static str, icon: &'static str, tooltip: &'static str| {
+            Button::new(id)
+                .icon(Icon::empty().path(icon))
+                .xsmall()
+                .ghost()
+                .tooltip(tooltip)
+        };
+
+        v_flex()
+            .w(px(360.))
+            .h_full()
+            .flex_shrink_0()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded(cx.theme().radius)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(
+                        Label::new(match row_ix {
+                            Some(row) => format!("Row {} of {}", row + 1, shown),
+                            None => "No row selected".to_string(),
+                        })
+                        .text_sm(),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                nav_button(
+                                    "detail-previous",
+                                    "icons/chevron-up.svg",
+                                    "Previous Row",
+                                )
+                                .disabled(row_ix.is_none_or(|row| row == 0))
+                                .on_click(
+                                    cx.listener(|this, _, _, cx| this.move_cursor(-1, 0, cx)),
+                                ),
+                            )
+                            .child(
+                                nav_button("detail-next", "icons/chevron-down.svg", "Next Row")
+                                    .disabled(row_ix.is_none_or(|row| row + 1 >= shown))
+                                    .on_click(
+                                        cx.listener(|this, _, _, cx| this.move_cursor(1, 0, cx)),
+                                    ),
+                            )
+                            .child(
+                                nav_button("detail-close", "icons/close.svg", "Close (Space)")
+                                    .on_click(
+                                        cx.listener(|this, _, _, cx| this.toggle_row_detail(cx)),
+                                    ),
+                            ),
+                    ),
+            )
+            .child(
+                div()
+                    .id("row-detail-fields")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .child(render_fields(&cells, cx)),
+            )
+    }
+
+    fn render_result(&self, window: &Window, cx: &mut Context<Self>) -> Div {
         match &self.current_result {
-            Some(QueryExecutionResult::Select(_result)) => v_flex()
+            Some(QueryExecutionResult::Select(result)) => v_flex()
                 .size_full()
                 .p_2()
                 .flex()
                 .flex_col()
                 .gap_1()
-                .child(self.render_toolbar(cx))
-                .child(Table::new(&self.table.clone()).stripe(true)),
+                .child(
+                    h_flex()
+                        .justify_between()
+                        .items_start()
+                        .child(self.render_filter_bar(cx))
+                        .child(self.render_toolbar(cx)),
+                )
+                .when_some(result.truncated_at, |d, limit| {
+                    d.child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .px_2()
+                            .py_1()
+                            .rounded(cx.theme().radius)
+                            .bg(cx.theme().warning.opacity(0.15))
+                            .child(
+                                Icon::empty()
+                                    .path("icons/triangle-alert.svg")
+                                    .size_4()
+                                    .text_color(cx.theme().warning),
+                            )
+                            .child(
+                                Label::new(format!(
+                                    "Showing the first {} rows; the query returned more. \
+                                     Add a LIMIT or raise Max Rows to see the rest.",
+                                    format_row_count(limit as u64)
+                                ))
+                                .text_xs(),
+                            ),
+                    )
+                })
+                .when(self.large_text, |d| d.text_base())
+                .child(
+                    h_flex()
+                        .flex_1()
+                        .min_h_0()
+                        .w_full()
+                        .gap_1()
+                        .child(self.render_grid(window, cx))
+                        .when(self.show_row_detail, |d| {
+                            d.child(self.render_row_detail(cx))
+                        }),
+                ),
             Some(QueryExecutionResult::Modified(modified)) => {
                 h_flex().size_full().items_center().justify_center().child(
                     Label::new(format!(
@@ -225,24 +1881,84 @@ impl Render for ResultsPanel {
                     .text_color(cx.theme().accent_foreground),
                 )
             }
-            Some(QueryExecutionResult::Error(error)) => v_flex().size_full().p_4().child(
-                div()
-                    .p_4()
-                    .bg(cx.theme().danger)
-                    .border_1()
-                    .border_color(cx.theme().danger)
-                    .rounded(cx.theme().radius)
-                    .child(
-                        Label::new(format!("Error: {}", error.message))
-                            .text_sm()
-                            .text_color(cx.theme().danger_foreground),
-                    ),
-            ),
-            _ => h_flex().size_full().items_center().justify_center().child(
-                Label::new("Execute a query to see results here")
-                    .text_sm()
-                    .text_color(cx.theme().muted_foreground),
-            ),
+            Some(QueryExecutionResult::Error(error)) => {
+                let explain = self.shown_query().map(|sql| {
+                    let error = error.message.clone();
+                    Button::new("explain-error")
+                        .icon(Icon::empty().path("icons/sparkles.svg"))
+                        .label("Explain error")
+                        .small()
+                        .on_click(cx.listener(move |_this, _, _win, cx| {
+                            cx.emit(ResultsEvent::ExplainError {
+                                sql: sql.clone(),
+                                error: error.clone(),
+                            });
+                        }))
+                });
+                v_flex().size_full().p_4().child(
+                    h_flex()
+                        .p_4()
+                        .gap_2()
+                        .justify_between()
+                        .bg(cx.theme().danger)
+                        .border_1()
+                        .border_color(cx.theme().danger)
+                        .rounded(cx.theme().radius)
+                        .child(
+                            Label::new(format!("Error: {}", error.message))
+                                .text_sm()
+                                .text_color(cx.theme().danger_foreground),
+                        )
+                        .children(explain),
+                )
+            }
+            _ => v_flex()
+                .size_full()
+                .gap_2()
+                .items_center()
+                .justify_center()
+                .child(
+                    Label::new("Execute a query to see results here")
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground),
+                )
+                .child(
+                    Button::new("open-snapshot-empty")
+                        .icon(Icon::empty().path("icons/folder-open.svg"))
+                        .label("Open Snapshot")
+                        .small()
+                        .ghost()
+                        .on_click(cx.listener(|this, _, win, cx| {
+                            this.open_snapshots(win, cx);
+                        })),
+                ),
         }
     }
 }
+
+impl Render for ResultsPanel {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.show_column_filters {
+            self.ensure_column_filters(window, cx);
+        }
+        let content = if self.messages.is_empty() {
+            self.render_result(window, cx)
+        } else {
+            v_flex().size_full().child(self.render_tabs(cx)).map(|d| {
+                if self.show_messages {
+                    d.child(self.render_messages(cx))
+                } else {
+                    d.child(self.render_result(window, cx))
+                }
+            })
+        };
+        if self.result_sets.is_empty() {
+            return content;
+        }
+
+        v_flex()
+            .size_full()
+            .child(self.render_result_set_tabs(cx))
+            .child(content)
+    }
+}
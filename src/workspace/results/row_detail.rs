@@ -0,0 +1,86 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{ActiveTheme as _, h_flex, label::Label, v_flex};
+
+use crate::services::{
+    NullDisplay, ResultCell, decode_binary, display_value, format_byte_size, is_binary_type,
+    mark_whitespace,
+};
+use crate::state::DisplayState;
+
+/// The full value of `cell`, as the grid would show it but never cut short.
+fn field_value(cell: &ResultCell, cx: &App) -> AnyElement {
+    let cells = cx.global::<DisplayState>().cells;
+    let muted = cx.theme().muted_foreground;
+    let type_name = &cell.column_metadata.type_name;
+
+    if cell.is_null {
+        return match cells.null {
+            NullDisplay::Chip => div()
+                .px_1()
+                .rounded(cx.theme().radius)
+                .bg(cx.theme().secondary)
+                .child(Label::new("NULL").text_xs().text_color(muted).italic())
+                .into_any_element(),
+            NullDisplay::Text => Label::new("NULL")
+                .text_color(muted)
+                .italic()
+                .into_any_element(),
+        };
+    }
+    if cell.value.is_empty() {
+        return Label::new("empty")
+            .text_color(muted)
+            .italic()
+            .into_any_element();
+    }
+    // Bytes would only be a wall of hex; the cell inspector previews them
+    if is_binary_type(type_name)
+        && let Some(bytes) = decode_binary(&cell.value)
+    {
+        return Label::new(format_byte_size(bytes.len()))
+            .text_color(muted)
+            .into_any_element();
+    }
+
+    let display = &cx.global::<DisplayState>().date_time;
+    let converted = display_value(&cell.value, type_name, display);
+    let shown = converted.as_deref().unwrap_or(&cell.value);
+    let shown = cells
+        .whitespace_markers
+        .then(|| mark_whitespace(shown))
+        .flatten()
+        .unwrap_or_else(|| shown.to_string());
+
+    v_flex()
+        .child(div().text_sm().child(shown))
+        // Keep the stored value in view when the shown one is converted
+        .when(converted.is_some(), |d| {
+            d.child(Label::new(cell.value.clone()).text_xs().text_color(muted))
+        })
+        .into_any_element()
+}
+
+/// Each cell of a row as its column's name and type above its value.
+pub(crate) fn render_fields(cells: &[ResultCell], cx: &App) -> Div {
+    v_flex().children(cells.iter().map(|cell| {
+        let column = &cell.column_metadata;
+        v_flex()
+            .gap_0p5()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(column.name.clone()).text_xs().font_medium())
+                    .child(
+                        Label::new(column.type_name.to_lowercase())
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(field_value(cell, cx))
+    }))
+}
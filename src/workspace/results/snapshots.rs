@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, StyledExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    label::Label,
+    v_flex,
+};
+
+use crate::services::export::{SnapshotEntry, delete_snapshot, list_snapshots, snapshots_dir};
+
+pub enum SnapshotsEvent {
+    /// Show the snapshot with this metadata file in the results grid
+    Open(PathBuf),
+    /// Pick a snapshot file from another folder
+    Browse,
+}
+
+impl EventEmitter<SnapshotsEvent> for SnapshotsPanel {}
+
+/// Dialog body listing the snapshots saved under `~/.pgui/snapshots`.
+pub struct SnapshotsPanel {
+    entries: Vec<SnapshotEntry>,
+    filter_input: Entity<InputState>,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SnapshotsPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let filter_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Filter by name or query"));
+        let _subscriptions = vec![cx.subscribe(&filter_input, |_, _, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                cx.notify();
+            }
+        })];
+
+        let mut this = Self {
+            entries: vec![],
+            filter_input,
+            error: None,
+            _subscriptions,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let entries = cx
+                .background_executor()
+                .spawn(async move { list_snapshots(&snapshots_dir()?) })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                match entries {
+                    Ok(entries) => {
+                        this.entries = entries;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("Failed to list snapshots: {}", e)),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn delete(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let deleted = cx
+                .background_executor()
+                .spawn(async move { delete_snapshot(&path) })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                if let Err(e) = deleted {
+                    this.error = Some(format!("Failed to delete snapshot: {}", e));
+                }
+                this.load(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn render_entry(&self, ix: usize, entry: &SnapshotEntry, cx: &mut Context<Self>) -> AnyElement {
+        let created = DateTime::parse_from_rfc3339(&entry.meta.created_at)
+            .map(|at| {
+                at.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| entry.meta.created_at.clone());
+        let query: String = entry
+            .meta
+            .original_query
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let details = format!(
+            "{} • {} rows • {} columns",
+            created,
+            entry.meta.row_count,
+            entry.meta.columns.len()
+        );
+
+        let open_path = entry.path.clone();
+        let delete_path = entry.path.clone();
+        h_flex()
+            .id(("snapshot-entry", ix))
+            .gap_2()
+            .p_2()
+            .rounded(cx.theme().radius)
+            .hover(|s| s.bg(cx.theme().list_active))
+            .child(
+                v_flex()
+                    .flex_1()
+                    .overflow_hidden()
+                    .gap_0p5()
+                    .child(Label::new(entry.name()).text_sm().font_medium())
+                    .child(
+                        Label::new(details)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .font_family("monospace")
+                            .text_color(cx.theme().muted_foreground)
+                            .whitespace_nowrap()
+                            .overflow_x_hidden()
+                            .child(query),
+                    ),
+            )
+            .child(
+                Button::new(("open-snapshot-entry", ix))
+                    .label("Open")
+                    .xsmall()
+                    .on_click(cx.listener(move |_, _, _, cx| {
+                        cx.emit(SnapshotsEvent::Open(open_path.clone()));
+                    })),
+            )
+            .child(
+                Button::new(("delete-snapshot-entry", ix))
+                    .icon(Icon::empty().path("icons/trash.svg"))
+                    .xsmall()
+                    .ghost()
+                    .tooltip("Delete Snapshot")
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.delete(delete_path.clone(), cx);
+                    })),
+            )
+            .into_any_element()
+    }
+}
+
+impl Render for SnapshotsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let filter = self.filter_input.read(cx).value().trim().to_lowercase();
+        let visible: Vec<SnapshotEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                filter.is_empty()
+                    || entry.name().to_lowercase().contains(&filter)
+                    || entry.meta.original_query.to_lowercase().contains(&filter)
+            })
+            .cloned()
+            .collect();
+        let rows = visible
+            .iter()
+            .enumerate()
+            .map(|(ix, entry)| self.render_entry(ix, entry, cx))
+            .collect::<Vec<_>>();
+
+        let empty_message = if self.entries.is_empty() {
+            "No snapshots yet. Save one from the results toolbar."
+        } else {
+            "No matching snapshots"
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().flex_1().child(Input::new(&self.filter_input).small()))
+                    .child(
+                        Button::new("browse-snapshots")
+                            .icon(Icon::empty().path("icons/folder-open.svg"))
+                            .label("Browse…")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|_, _, _, cx| {
+                                cx.emit(SnapshotsEvent::Browse);
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |col, error| {
+                col.child(Label::new(error).text_xs().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("snapshot-entries")
+                    .max_h(px(420.))
+                    .overflow_y_scroll()
+                    .children(rows)
+                    .when(visible.is_empty(), |d| {
+                        d.child(
+                            Label::new(empty_message)
+                                .p_2()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    }),
+            )
+    }
+}
@@ -1,62 +1,322 @@
 use std::ops::Range;
+use std::sync::Arc;
 
-use crate::services::{QueryResult, ResultCell};
+use crate::services::{
+    CellValue, ColumnLayout, ColumnStats, DatabaseDriver, FIT_SAMPLE_ROWS, NullDisplay,
+    QueryResult, ResultCell, ResultColumnMetadata, ResultFilter, ResultRows, column_stats,
+    display_value, fit_width, is_binary_type, mark_whitespace,
+};
+use crate::state::{DisplayState, WindowConnection, WindowScoped, window_id};
+use crate::workspace::results::ResultsPanel;
+use crate::workspace::results::cell_inspector::save_cell_to_file;
+use crate::workspace::results::column_stats::show_column_stats;
+use crate::workspace::results::panel::FollowKey;
+use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use gpui_component::{
     ActiveTheme as _,
     label::Label,
-    table::{Column, TableDelegate, TableState},
+    menu::{ContextMenuExt as _, PopupMenuItem},
+    table::{Column, ColumnFixed, TableDelegate, TableState},
+    tooltip::Tooltip,
 };
 
+const TOP_VALUES: usize = 5;
+
 pub struct EnhancedResultsTableDelegate {
     columns: Vec<Column>,
-    // Store the full ResultCell data with metadata
-    rows: Vec<Vec<ResultCell>>,
+    column_metadata: Arc<Vec<ResultColumnMetadata>>,
+    /// Result column shown at each display position. Hidden columns have
+    /// none.
+    column_order: Vec<usize>,
+    /// Order, hidden and pinned columns and widths of this query's results.
+    layout: ColumnLayout,
+    /// Key `layout` is saved under, from the query's shape.
+    layout_key: Option<String>,
+    // Rows stay undecoded; cells are decoded as they scroll into view
+    rows: ResultRows,
+    filter: ResultFilter,
+    /// Source row shown at each display position while a filter is set.
+    filtered_rows: Option<Vec<usize>>,
     loading: bool,
     visible_rows: Range<usize>,
+    /// Keyboard cursor as `(row, column)` display positions.
+    cursor: Option<(usize, usize)>,
+    /// Panel showing this table, which opens cells for inspection.
+    panel: WeakEntity<ResultsPanel>,
 }
 
 impl EnhancedResultsTableDelegate {
-    pub fn new() -> Self {
+    pub fn new(panel: WeakEntity<ResultsPanel>) -> Self {
         Self {
-            rows: vec![],
+            rows: ResultRows::default(),
+            filter: ResultFilter::default(),
+            filtered_rows: None,
             columns: vec![],
+            column_metadata: Arc::default(),
+            column_order: vec![],
+            layout: ColumnLayout::default(),
+            layout_key: None,
             loading: false,
             visible_rows: Range::default(),
+            cursor: None,
+            panel,
         }
     }
 
     pub fn update(&mut self, result: QueryResult) {
-        // Convert ResultRows to Vec<Vec<ResultCell>>
-        let rows: Vec<Vec<ResultCell>> = result
-            .rows
-            .clone()
+        // A re-run keeps its layout; another query's is loaded by the panel
+        let layout_key = ColumnLayout::key(&result.original_query);
+        if self.layout_key.as_ref() != Some(&layout_key) {
+            self.layout = ColumnLayout::default();
+            self.layout_key = Some(layout_key);
+        }
+
+        self.column_metadata = Arc::new(result.columns);
+        self.rows = result.rows;
+        self.filter = ResultFilter::default();
+        self.filtered_rows = None;
+        self.arrange_columns();
+    }
+
+    /// Rebuild the shown columns from the layout.
+    fn arrange_columns(&mut self) {
+        let names: Vec<String> = self
+            .column_metadata
             .iter()
-            .map(|row| row.cells.clone())
+            .map(|c| c.name.clone())
             .collect();
-
-        // Create columns from metadata
-        let columns: Vec<Column> = result
-            .columns
-            .clone()
+        self.column_order = self.layout.arrange(&names);
+        self.columns = self
+            .column_order
             .iter()
-            .map(|col_meta| {
-                Column::new(&col_meta.name, &col_meta.name).sortable() // Enable sorting for all columns
+            .map(|&col| {
+                let name = &names[col];
+                let mut column = Column::new(name, name).sortable(); // Enable sorting for all columns
+                if let Some(width) = self.layout.width(name) {
+                    column = column.width(px(width));
+                }
+                if self.layout.is_pinned(name) {
+                    column = column.fixed(ColumnFixed::Left);
+                }
+                column
             })
             .collect();
+        self.cursor = None;
+    }
+
+    pub fn layout_key(&self) -> Option<&str> {
+        self.layout_key.as_deref()
+    }
+
+    /// The layout and the key to save it under.
+    pub fn layout(&self) -> Option<(String, ColumnLayout)> {
+        let key = self.layout_key.clone()?;
+        Some((key, self.layout.clone()))
+    }
+
+    /// Apply a saved layout, when it is for the query shown.
+    pub fn apply_layout(&mut self, key: &str, layout: ColumnLayout) {
+        if self.layout_key.as_deref() == Some(key) {
+            self.layout = layout;
+            self.arrange_columns();
+        }
+    }
+
+    fn column_name(&self, col_ix: usize) -> Option<String> {
+        let col = *self.column_order.get(col_ix)?;
+        Some(self.column_metadata[col].name.clone())
+    }
+
+    /// Result columns not shown.
+    pub fn hidden_count(&self) -> usize {
+        self.column_metadata.len() - self.column_order.len()
+    }
 
-        self.rows = rows;
-        self.columns = columns;
+    /// Hide the column at display position `col_ix`, unless it is the
+    /// last one shown.
+    pub fn hide_column(&mut self, col_ix: usize) {
+        if self.column_order.len() <= 1 {
+            return;
+        }
+        if let Some(name) = self.column_name(col_ix) {
+            self.layout.hide(&name);
+            self.arrange_columns();
+        }
+    }
+
+    pub fn show_all_columns(&mut self) {
+        self.layout.hidden.clear();
+        self.arrange_columns();
+    }
+
+    pub fn is_pinned(&self, col_ix: usize) -> bool {
+        self.column_name(col_ix)
+            .is_some_and(|name| self.layout.is_pinned(&name))
+    }
+
+    /// Pin the column at `col_ix` to the left, or unpin it.
+    pub fn toggle_pinned(&mut self, col_ix: usize) {
+        if let Some(name) = self.column_name(col_ix) {
+            self.layout.toggle_pinned(&name);
+            self.arrange_columns();
+        }
+    }
+
+    /// Fit the column at `col_ix`, or every shown column, to its header
+    /// and the first rows' values.
+    pub fn fit_columns(&mut self, col_ix: Option<usize>) {
+        let positions = match col_ix {
+            Some(col_ix) => vec![col_ix],
+            None => (0..self.column_order.len()).collect(),
+        };
+        let sample = self.rows.len().min(FIT_SAMPLE_ROWS);
+        for position in positions {
+            let Some(&col) = self.column_order.get(position) else {
+                continue;
+            };
+            let values: Vec<String> = (0..sample)
+                .map(|row| self.rows.cell_text(row, col).0)
+                .collect();
+            let name = &self.column_metadata[col].name;
+            let width = fit_width(name, values.iter().map(String::as_str));
+            self.layout.set_width(name, width);
+        }
+        self.arrange_columns();
+    }
+
+    /// Record widths the user resized the shown columns to.
+    pub fn set_widths(&mut self, widths: &[Pixels]) {
+        for (position, width) in widths.iter().enumerate() {
+            if let Some(name) = self.column_name(position) {
+                self.layout.set_width(&name, f32::from(*width));
+            }
+        }
+    }
+
+    /// Forget the order, hidden and pinned columns and widths.
+    pub fn reset_layout(&mut self) {
+        self.layout = ColumnLayout::default();
+        self.arrange_columns();
+    }
+
+    /// Show only the rows passing `filter`, highlighting the matching cells.
+    pub fn set_filter(&mut self, filter: ResultFilter) {
+        self.filtered_rows = if filter.is_empty() {
+            None
+        } else {
+            Some(filter.matching_rows(&self.rows, self.column_metadata.len()))
+        };
+        self.filter = filter;
+        self.cursor = None;
+    }
+
+    /// Rows shown and rows in the result.
+    pub fn row_counts(&self) -> (usize, usize) {
+        let total = self.rows.len();
+        (self.filtered_rows.as_ref().map_or(total, Vec::len), total)
+    }
+
+    pub fn cursor(&self) -> Option<(usize, usize)> {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        self.cursor = cursor;
+    }
+
+    /// Rows that fit in the table, for PageUp and PageDown.
+    pub fn page_rows(&self) -> usize {
+        self.visible_rows.len().saturating_sub(1).max(1)
+    }
+
+    /// The cursor moved by `d_row` rows and `d_col` columns, kept inside
+    /// the table. Without a cursor it starts at the first cell.
+    pub fn moved_cursor(&self, d_row: isize, d_col: isize) -> Option<(usize, usize)> {
+        let rows = self.row_counts().0;
+        let cols = self.columns.len();
+        if rows == 0 || cols == 0 {
+            return None;
+        }
+        let Some((row, col)) = self.cursor else {
+            return Some((0, 0));
+        };
+        let clamp =
+            |ix: usize, delta: isize, len: usize| ix.saturating_add_signed(delta).min(len - 1);
+        Some((clamp(row, d_row, rows), clamp(col, d_col, cols)))
+    }
+
+    fn source_row(&self, row_ix: usize) -> Option<usize> {
+        match &self.filtered_rows {
+            Some(rows) => rows.get(row_ix).copied(),
+            None => (row_ix < self.rows.len()).then_some(row_ix),
+        }
+    }
+
+    /// Name and statistics of the column at display position `col_ix`,
+    /// over the rows currently shown.
+    pub fn column_stats(&self, col_ix: usize) -> Option<(String, ColumnStats)> {
+        let col = *self.column_order.get(col_ix)?;
+        let stats = match &self.filtered_rows {
+            Some(rows) => column_stats(&self.rows, rows.iter().copied(), col, TOP_VALUES),
+            None => column_stats(&self.rows, 0..self.rows.len(), col, TOP_VALUES),
+        };
+        Some((self.column_metadata[col].name.clone(), stats))
+    }
+
+    /// Cells of the row at `row_ix`, in display order, then the cells of
+    /// hidden columns.
+    pub fn row(&self, row_ix: usize) -> Option<Vec<ResultCell>> {
+        let row_ix = self.source_row(row_ix)?;
+
+        let hidden = (0..self.column_metadata.len()).filter(|col| !self.column_order.contains(col));
+        let cells = self
+            .column_order
+            .iter()
+            .copied()
+            .chain(hidden)
+            .map(|col| {
+                let cell = self.rows.cell(row_ix, col);
+                let undecoded = matches!(cell, CellValue::Undecoded(_));
+                let (value, is_null) = cell.into_text();
+                ResultCell {
+                    value,
+                    is_null,
+                    undecoded,
+                    column_metadata: self.column_metadata[col].clone(),
+                }
+            })
+            .collect();
+        Some(cells)
     }
 }
 
+/// A column header menu item changing the layout, which is then saved.
+fn layout_item(
+    label: SharedString,
+    table: WeakEntity<TableState<EnhancedResultsTableDelegate>>,
+    panel: WeakEntity<ResultsPanel>,
+    change: impl Fn(&mut EnhancedResultsTableDelegate) + 'static,
+) -> PopupMenuItem {
+    PopupMenuItem::new(label).on_click(move |_, _window, cx| {
+        let Some(table) = table.upgrade() else {
+            return;
+        };
+        table.update(cx, |table, cx| {
+            change(table.delegate_mut());
+            table.refresh(cx);
+        });
+        let _ = panel.update(cx, |panel, cx| panel.save_column_layout(cx));
+    })
+}
+
 impl TableDelegate for EnhancedResultsTableDelegate {
     fn columns_count(&self, _: &App) -> usize {
         self.columns.len()
     }
 
     fn rows_count(&self, _: &App) -> usize {
-        self.rows.len()
+        self.row_counts().0
     }
 
     fn column(&self, col_ix: usize, _: &App) -> &Column {
@@ -66,11 +326,77 @@ impl TableDelegate for EnhancedResultsTableDelegate {
     fn render_th(
         &mut self,
         col_ix: usize,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<TableState<Self>>,
     ) -> impl IntoElement {
         let col = self.column(col_ix, cx);
-        div().child(format!("{}", col.clone().name))
+        let table = cx.entity().downgrade();
+        let panel = self.panel.clone();
+        let pinned = self.is_pinned(col_ix);
+        let hidden = self.hidden_count();
+        let can_hide = self.column_order.len() > 1;
+        // MySQL results carry no source table, so only Postgres can tell
+        // computed columns apart
+        let tracks_sources = WindowConnection::of(window_id(window), cx)
+            .active_connection
+            .as_ref()
+            .is_none_or(|c| c.driver == DatabaseDriver::Postgres);
+        let lineage: Option<SharedString> = self
+            .column_order
+            .get(col_ix)
+            .and_then(|&source| self.column_metadata.get(source))
+            .map(|meta| {
+                if tracks_sources || meta.table_name.is_some() {
+                    meta.lineage()
+                } else {
+                    format!("{} · {}", meta.name, meta.type_name)
+                }
+                .into()
+            });
+        div()
+            .id(("column-header", col_ix))
+            .child(format!("{}", col.clone().name))
+            .when_some(lineage, |d, lineage| {
+                d.tooltip(move |window, cx| Tooltip::new(lineage.clone()).build(window, cx))
+            })
+            .context_menu(move |menu, _window, _cx| {
+                let stats_table = table.clone();
+                let item =
+                    |label: SharedString, change: fn(&mut EnhancedResultsTableDelegate, usize)| {
+                        layout_item(label, table.clone(), panel.clone(), move |delegate| {
+                            change(delegate, col_ix)
+                        })
+                    };
+                menu.item(
+                    PopupMenuItem::new("Column stats").on_click(move |_, window, cx| {
+                        let Some(table) = stats_table.upgrade() else {
+                            return;
+                        };
+                        let stats = table.read(cx).delegate().column_stats(col_ix);
+                        if let Some((name, stats)) = stats {
+                            show_column_stats(name, stats, window, cx);
+                        }
+                    }),
+                )
+                .separator()
+                .when(can_hide, |menu| {
+                    menu.item(item("Hide Column".into(), |d, ix| d.hide_column(ix)))
+                })
+                .item(item(
+                    if pinned { "Unpin Column" } else { "Pin Column" }.into(),
+                    |d, ix| d.toggle_pinned(ix),
+                ))
+                .item(item("Fit Width".into(), |d, ix| d.fit_columns(Some(ix))))
+                .separator()
+                .item(item("Fit All Columns".into(), |d, _| d.fit_columns(None)))
+                .when(hidden > 0, |menu| {
+                    menu.item(item(
+                        format!("Show Hidden Columns ({})", hidden).into(),
+                        |d, _| d.show_all_columns(),
+                    ))
+                })
+                .item(item("Reset Layout".into(), |d, _| d.reset_layout()))
+            })
         // let col_meta = if !self.rows.is_empty() && col_ix < self.rows[0].len() {
         //     Some(&self.rows[0][col_ix].column_metadata)
         // } else {
@@ -124,37 +450,141 @@ impl TableDelegate for EnhancedResultsTableDelegate {
         _: &mut Window,
         cx: &mut Context<TableState<Self>>,
     ) -> impl IntoElement {
-        // println!("render_td called: row={}, col={}", row_ix, col_ix);
-        // Don't clone all rows - access directly instead
-        if let Some(row) = self.rows.get(row_ix) {
-            if let Some(cell) = row.get(col_ix) {
-                // Only clone the specific cell we need for the closure
-                let cell_clone = cell.clone();
+        // Only the visible cells are decoded, each time they render
+        if let Some(&col) = self.column_order.get(col_ix) {
+            if let Some(source_row) = self.source_row(row_ix) {
+                let cell = self.rows.cell(source_row, col);
+                let undecoded = matches!(cell, CellValue::Undecoded(_));
+                let (value, is_null) = cell.into_text();
+                let highlighted = self.filter.highlights(col, &value);
+                let column_metadata = self.column_metadata.clone();
+                // Dates and times may be shown converted; the original
+                // value stays in the tooltip.
+                let shown = if is_null || undecoded {
+                    None
+                } else {
+                    let display = &cx.global::<DisplayState>().date_time;
+                    display_value(&value, &column_metadata[col].type_name, display)
+                };
+                let original = shown.as_ref().map(|_| SharedString::from(value.clone()));
+                let shown_value = shown.unwrap_or_else(|| value.clone());
+                let cells = cx.global::<DisplayState>().cells;
+                let muted = cx.theme().muted_foreground;
+                let meta = column_metadata[col].clone();
+                let binary = !is_null && !undecoded && is_binary_type(&meta.type_name);
+                // NULL, or a value that was not read, holds no key to follow.
+                let keyed = !is_null && !undecoded && meta.table_name.is_some();
+                let cell_value = value.clone();
+                let at_cursor = self.cursor == Some((row_ix, col_ix));
+                let table = cx.entity().downgrade();
                 // Create a clickable cell that logs metadata on click
                 return div()
+                    .id(("cell", col))
                     .cursor_pointer()
-                    .on_mouse_up(MouseButton::Left, move |_ev, _, _| {
+                    .border_1()
+                    .border_color(if at_cursor {
+                        cx.theme().ring
+                    } else {
+                        transparent_black()
+                    })
+                    .when_some(original, |d, original| {
+                        d.tooltip(move |window, cx| {
+                            Tooltip::new(original.clone()).build(window, cx)
+                        })
+                    })
+                    .when(highlighted, |d| d.bg(cx.theme().warning.opacity(0.25)))
+                    .context_menu({
+                        let panel = self.panel.clone();
+                        move |menu, _window, _cx| {
+                            let panel = panel.clone();
+                            let follow_panel = panel.clone();
+                            let (meta, value) = (meta.clone(), cell_value.clone());
+                            menu.item(PopupMenuItem::new("Inspect Cell").on_click(
+                                move |_, window, cx| {
+                                    let _ = panel.update(cx, |panel, cx| {
+                                        panel.inspect_cell(row_ix, col_ix, window, cx);
+                                    });
+                                },
+                            ))
+                            .when(binary, |menu| {
+                                menu.item(PopupMenuItem::new("Save to File…").on_click(
+                                    move |_, window, cx| {
+                                        save_cell_to_file(&meta, &value, window, cx);
+                                    },
+                                ))
+                            })
+                            .when(keyed, |menu| {
+                                let follow_item = |label: &'static str, follow: FollowKey| {
+                                    let panel = follow_panel.clone();
+                                    PopupMenuItem::new(label).on_click(move |_, window, cx| {
+                                        let _ = panel.update(cx, |panel, cx| {
+                                            panel.follow_key(row_ix, col_ix, follow, window, cx);
+                                        });
+                                    })
+                                };
+                                menu.separator()
+                                    .item(follow_item(
+                                        "Go to Referenced Row",
+                                        FollowKey::Referenced,
+                                    ))
+                                    .item(follow_item(
+                                        "Show Referencing Rows",
+                                        FollowKey::Referencing,
+                                    ))
+                            })
+                        }
+                    })
+                    .on_mouse_up(MouseButton::Left, move |_ev, _, cx| {
+                        let _ = table.update(cx, |table, cx| {
+                            table.delegate_mut().set_cursor(Some((row_ix, col_ix)));
+                            cx.notify();
+                        });
                         // Log all the metadata for this cell
+                        let meta = &column_metadata[col];
                         tracing::debug!("\n=== CELL METADATA ===");
-                        tracing::debug!("Column Name: {}", cell_clone.column_metadata.name);
-                        tracing::debug!("Column Type: {}", cell_clone.column_metadata.type_name);
-                        tracing::debug!("Column Ordinal: {}", cell_clone.column_metadata.ordinal);
-                        tracing::debug!("Table Name: {:?}", cell_clone.column_metadata.table_name);
-                        tracing::debug!(
-                            "Is Nullable: {:?}",
-                            cell_clone.column_metadata.is_nullable
-                        );
-                        tracing::debug!("Value: {}", cell_clone.value);
-                        tracing::debug!("Is NULL: {}", cell_clone.is_null);
+                        tracing::debug!("Column Name: {}", meta.name);
+                        tracing::debug!("Column Type: {}", meta.type_name);
+                        tracing::debug!("Column Ordinal: {}", meta.ordinal);
+                        tracing::debug!("Table Name: {:?}", meta.table_name);
+                        tracing::debug!("Is Nullable: {:?}", meta.is_nullable);
+                        tracing::debug!("Row: {}", source_row);
+                        tracing::debug!("Is NULL: {}", is_null);
+                        tracing::debug!("Value: {}", value);
                         tracing::debug!("====================\n");
                     })
-                    .child(if cell.is_null {
-                        // Style NULL values differently
-                        Label::new(&cell.value)
-                            .text_color(cx.theme().muted_foreground)
+                    .child(if is_null {
+                        // NULL must never pass for a value, even the text "NULL"
+                        match cells.null {
+                            NullDisplay::Chip => div()
+                                .px_1()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .child(Label::new("NULL").text_xs().text_color(muted).italic())
+                                .into_any_element(),
+                            NullDisplay::Text => Label::new("NULL")
+                                .text_color(muted)
+                                .italic()
+                                .into_any_element(),
+                        }
+                    } else if undecoded {
+                        // Only the type is known; the text is not the value
+                        Label::new(shown_value)
+                            .text_color(muted)
+                            .italic()
+                            .into_any_element()
+                    } else if cells.whitespace_markers && value.is_empty() {
+                        Label::new("empty")
+                            .text_color(muted)
                             .italic()
+                            .into_any_element()
+                    } else if let Some(marked) = cells
+                        .whitespace_markers
+                        .then(|| mark_whitespace(&shown_value))
+                        .flatten()
+                    {
+                        Label::new(marked).into_any_element()
                     } else {
-                        Label::new(&cell.value)
+                        Label::new(shown_value).into_any_element()
                     })
                     .into_any_element();
             }
@@ -173,13 +603,14 @@ impl TableDelegate for EnhancedResultsTableDelegate {
         let col = self.columns.remove(col_ix);
         self.columns.insert(to_ix, col);
 
-        // Also move the cells in each row
-        for row in &mut self.rows {
-            if col_ix < row.len() && to_ix < row.len() {
-                let cell = row.remove(col_ix);
-                row.insert(to_ix, cell);
-            }
-        }
+        // Rows are untouched; only the display order changes
+        let source = self.column_order.remove(col_ix);
+        self.column_order.insert(to_ix, source);
+        self.layout.order = self
+            .column_order
+            .iter()
+            .map(|&col| self.column_metadata[col].name.clone())
+            .collect();
     }
 
     fn loading(&self, _: &App) -> bool {
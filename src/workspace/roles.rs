@@ -0,0 +1,428 @@
+use anyhow::{Result, anyhow};
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Subscription, WeakEntity, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable as _, WindowExt as _,
+    checkbox::Checkbox,
+    dialog::DialogButtonProps,
+    input::{Input, InputState},
+    label::Label,
+    notification::NotificationType,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+
+use crate::{
+    services::{
+        RoleDefinition, alter_role_sql, create_role_sql, password_salt, reset_password_sql,
+    },
+    state::{WindowConnection, WindowScoped, window_id},
+    workspace::{
+        editor::Editor,
+        notifications::{RecordedNotifications as _, Toasts as _},
+    },
+};
+
+fn field_label(label: &'static str) -> Label {
+    Label::new(label).text_xs()
+}
+
+/// Dialog body for creating a role or altering an existing one. The
+/// statements are generated for review rather than run; a password is
+/// only ever sent as a SCRAM verifier.
+pub struct RoleForm {
+    altering: bool,
+    /// Existing roles, for picking memberships and the role to alter.
+    roles: Vec<RoleDefinition>,
+    /// The role being altered, as loaded.
+    original: Option<RoleDefinition>,
+    role_select: Entity<SelectState<Vec<SharedString>>>,
+    name_input: Entity<InputState>,
+    password_input: Entity<InputState>,
+    valid_until_input: Entity<InputState>,
+    connection_limit_input: Entity<InputState>,
+    login: bool,
+    member_of: Vec<String>,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl RoleForm {
+    fn view(altering: bool, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let form = cx.new(|cx| Self::new(altering, window, cx));
+        form.update(cx, |form, cx| form.load(window, cx));
+        form
+    }
+
+    fn new(altering: bool, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let role_select =
+            cx.new(|cx| SelectState::new(Vec::<SharedString>::new(), None, window, cx));
+        let name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Role name"));
+        let password_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .masked(true)
+                .placeholder(if altering {
+                    "Leave empty to keep the current password"
+                } else {
+                    "Leave empty for no password"
+                })
+        });
+        let valid_until_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("e.g. 2027-01-01, leave empty to never expire")
+        });
+        let connection_limit_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Leave empty for no limit"));
+
+        let _subscriptions = vec![cx.subscribe_in(&role_select, window, Self::on_select_role)];
+
+        Self {
+            altering,
+            roles: vec![],
+            original: None,
+            role_select,
+            name_input,
+            password_input,
+            valid_until_input,
+            connection_limit_input,
+            login: true,
+            member_of: vec![],
+            error: None,
+            _subscriptions,
+        }
+    }
+
+    fn load(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.get_roles().await;
+            let _ = this.update_in(cx, |this, window, cx| {
+                match result {
+                    Ok(roles) => {
+                        let names: Vec<SharedString> =
+                            roles.iter().map(|r| r.name.clone().into()).collect();
+                        this.role_select.update(cx, |select, cx| {
+                            select.set_items(names, window, cx);
+                        });
+                        this.roles = roles;
+                    }
+                    Err(e) => this.error = Some(e.to_string()),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Fill the form from the role picked for altering.
+    fn on_select_role(
+        &mut self,
+        _: &Entity<SelectState<Vec<SharedString>>>,
+        event: &SelectEvent<Vec<SharedString>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let SelectEvent::Confirm(Some(name)) = event else {
+            return;
+        };
+        let Some(role) = self.roles.iter().find(|r| r.name == name.as_ref()).cloned() else {
+            return;
+        };
+
+        let values = [
+            (&self.name_input, role.name.clone()),
+            (
+                &self.valid_until_input,
+                role.valid_until.clone().unwrap_or_default(),
+            ),
+            (
+                &self.connection_limit_input,
+                role.connection_limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_default(),
+            ),
+        ];
+        for (input, value) in values {
+            input.update(cx, |input, cx| input.set_value(value, window, cx));
+        }
+        self.login = role.login;
+        self.member_of = role.member_of.clone();
+        self.original = Some(role);
+        cx.notify();
+    }
+
+    fn definition(&self, cx: &App) -> Result<RoleDefinition> {
+        let value = |input: &Entity<InputState>| input.read(cx).value().trim().to_string();
+        let valid_until = value(&self.valid_until_input);
+        let connection_limit = match value(&self.connection_limit_input).as_str() {
+            "" => None,
+            limit => Some(
+                limit
+                    .parse::<i32>()
+                    .map_err(|_| anyhow!("The connection limit must be a number"))?,
+            ),
+        };
+        Ok(RoleDefinition {
+            name: value(&self.name_input),
+            login: self.login,
+            valid_until: (!valid_until.is_empty()).then_some(valid_until),
+            connection_limit,
+            member_of: self.member_of.clone(),
+        })
+    }
+
+    /// The generated statements, one per line.
+    pub fn script(&self, cx: &App) -> Result<String> {
+        let role = self.definition(cx)?;
+        let password = self.password_input.read(cx).value().to_string();
+        let salt = password_salt();
+        let statements = match (&self.original, self.altering) {
+            (Some(original), true) => alter_role_sql(original, &role, Some(&password), &salt)?,
+            (None, true) => return Err(anyhow!("Pick the role to alter")),
+            (_, false) => create_role_sql(&role, Some(&password), &salt)?,
+        };
+        Ok(statements.join("\n"))
+    }
+}
+
+impl Render for RoleForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let editing = self.original.as_ref().map(|r| r.name.clone());
+        let groups = self
+            .roles
+            .iter()
+            .filter(|r| Some(&r.name) != editing.as_ref())
+            .enumerate()
+            .map(|(ix, group)| {
+                let name = group.name.clone();
+                Checkbox::new(("role-member-of", ix))
+                    .label(group.name.clone())
+                    .checked(self.member_of.contains(&group.name))
+                    .on_click(cx.listener(move |this, checked: &bool, _win, cx| {
+                        this.member_of.retain(|g| g != &name);
+                        if *checked {
+                            this.member_of.push(name.clone());
+                        }
+                        cx.notify();
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_3()
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .when(self.altering, |d| {
+                d.child(Select::new(&self.role_select).placeholder("Pick a role"))
+            })
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(field_label("Name"))
+                    .child(Input::new(&self.name_input).small()),
+            )
+            .child(
+                Checkbox::new("role-login")
+                    .label("Can log in (LOGIN)")
+                    .checked(self.login)
+                    .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                        this.login = *checked;
+                        cx.notify();
+                    })),
+            )
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(field_label("Password"))
+                    .child(Input::new(&self.password_input).small())
+                    .child(
+                        Label::new(
+                            "Sent as a SCRAM-SHA-256 verifier, so the script never contains \
+                             it; not saved anywhere.",
+                        )
+                        .text_xs()
+                        .text_color(muted),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(field_label("Valid until"))
+                    .child(Input::new(&self.valid_until_input).small()),
+            )
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(field_label("Connection limit"))
+                    .child(Input::new(&self.connection_limit_input).small()),
+            )
+            .when(!groups.is_empty(), |d| {
+                d.child(
+                    v_flex().gap_1().child(field_label("Member of")).child(
+                        div()
+                            .id("role-member-of")
+                            .max_h(px(140.))
+                            .overflow_y_scroll()
+                            .child(v_flex().gap_1().children(groups)),
+                    ),
+                )
+            })
+    }
+}
+
+/// Dialog body for setting a role's password. Unlike the role dialogs it
+/// runs straight away, as there is nothing to review.
+pub struct ResetPasswordForm {
+    role_select: Entity<SelectState<Vec<SharedString>>>,
+    password_input: Entity<InputState>,
+    confirm_input: Entity<InputState>,
+}
+
+impl ResetPasswordForm {
+    fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let form = cx.new(|cx| {
+            let role_select =
+                cx.new(|cx| SelectState::new(Vec::<SharedString>::new(), None, window, cx));
+            let password_input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .masked(true)
+                    .placeholder("New password")
+            });
+            let confirm_input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .masked(true)
+                    .placeholder("Repeat the new password")
+            });
+            Self {
+                role_select,
+                password_input,
+                confirm_input,
+            }
+        });
+
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        let role_select = form.read(cx).role_select.clone();
+        window
+            .spawn(cx, async move |cx| {
+                let roles = db_manager.get_roles().await.unwrap_or_default();
+                let names: Vec<SharedString> = roles
+                    .into_iter()
+                    .filter(|r| r.login)
+                    .map(|r| r.name.into())
+                    .collect();
+                let _ = cx.update(|window, cx| {
+                    role_select.update(cx, |select, cx| select.set_items(names, window, cx));
+                });
+            })
+            .detach();
+        form
+    }
+
+    fn reset(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let Some(role) = self.role_select.read(cx).selected_value().cloned() else {
+            window.push_toast((NotificationType::Warning, "Pick a role"), cx);
+            return false;
+        };
+        let password = self.password_input.read(cx).value().to_string();
+        if password != self.confirm_input.read(cx).value().as_ref() {
+            window.push_toast((NotificationType::Warning, "The passwords differ"), cx);
+            return false;
+        }
+        // Checked here as well so an empty password keeps the dialog open.
+        if let Err(e) = reset_password_sql(&role, &password, &password_salt()) {
+            window.push_toast(
+                (NotificationType::Warning, SharedString::from(e.to_string())),
+                cx,
+            );
+            return false;
+        }
+
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        window
+            .spawn(cx, async move |cx| {
+                let result = db_manager.reset_role_password(&role, &password).await;
+                let _ = cx.update(|window, cx| {
+                    let notification = match result {
+                        Ok(_) => (
+                            NotificationType::Success,
+                            SharedString::from(format!("Reset the password of {}", role)),
+                        ),
+                        Err(e) => (NotificationType::Error, SharedString::from(e.to_string())),
+                    };
+                    window.push_recorded_notification(notification, cx);
+                });
+            })
+            .detach();
+        true
+    }
+}
+
+impl Render for ResetPasswordForm {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_3()
+            .child(Select::new(&self.role_select).placeholder("Pick a role"))
+            .child(Input::new(&self.password_input).small())
+            .child(Input::new(&self.confirm_input).small())
+    }
+}
+
+/// Generate `CREATE ROLE`, or `ALTER ROLE` when `altering`, into the
+/// editor for review.
+pub fn open_role_dialog(
+    altering: bool,
+    editor: WeakEntity<Editor>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let form = RoleForm::view(altering, window, cx);
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        let editor = editor.clone();
+        dialog
+            .title(if altering { "Alter Role" } else { "New Role" })
+            .width(px(520.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Generate SQL"))
+            .on_ok(move |_, window, cx| match form.read(cx).script(cx) {
+                Ok(script) => {
+                    let _ = editor.update(cx, |editor, cx| editor.set_query(script, window, cx));
+                    true
+                }
+                Err(e) => {
+                    window.push_toast(
+                        (NotificationType::Warning, SharedString::from(e.to_string())),
+                        cx,
+                    );
+                    false
+                }
+            })
+    });
+}
+
+pub fn open_reset_password_dialog(window: &mut Window, cx: &mut App) {
+    let form = ResetPasswordForm::view(window, cx);
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        dialog
+            .title("Reset Password")
+            .width(px(420.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Reset Password"))
+            .on_ok(move |_, window, cx| form.update(cx, |form, cx| form.reset(window, cx)))
+    });
+}
@@ -0,0 +1,272 @@
+use gpui::{
+    App, AppContext, Context, Entity, Hsla, InteractiveElement as _, IntoElement, ParentElement,
+    Render, StatefulInteractiveElement as _, Styled, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    v_flex,
+};
+use uuid::Uuid;
+
+use crate::{
+    services::{
+        AppStore, DatabaseManager, SchemaChangeEntry, SchemaChangeKind, SchemaSnapshot, change_log,
+    },
+    state::{WindowConnection, WindowScoped, record_schema_snapshot, window_id},
+};
+
+/// Dialog body listing how the schema of the connected database changed
+/// between the snapshots taken on connecting, latest first.
+pub struct SchemaChangesPanel {
+    db_manager: DatabaseManager,
+    /// The saved connection and database snapshotted, when connected.
+    target: Option<(Uuid, String)>,
+    entries: Vec<SchemaChangeEntry>,
+    loading: bool,
+    snapshotting: bool,
+    error: Option<String>,
+}
+
+impl SchemaChangesPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let window = window_id(window);
+        cx.new(|cx| {
+            let state = WindowConnection::of(window, cx);
+            let db_manager = state.db_manager.clone();
+            let target = state
+                .active_connection
+                .as_ref()
+                .map(|connection| (connection.id, connection.database.clone()));
+            let mut this = Self {
+                db_manager,
+                target,
+                entries: vec![],
+                loading: false,
+                snapshotting: false,
+                error: None,
+            };
+            this.load(cx);
+            this
+        })
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        let Some((connection_id, database)) = self.target.clone() else {
+            self.error = Some("Not connected".to_string());
+            return;
+        };
+        self.loading = true;
+
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                let store = AppStore::singleton().await?;
+                let snapshots = store
+                    .schema_snapshots()
+                    .load(&connection_id, &database)
+                    .await?
+                    .into_iter()
+                    .map(|(taken_at, snapshot)| {
+                        Ok((taken_at, serde_json::from_str::<SchemaSnapshot>(&snapshot)?))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                anyhow::Ok(change_log(&snapshots))
+            }
+            .await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(entries) => {
+                        this.entries = entries;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn snapshot_now(&mut self, cx: &mut Context<Self>) {
+        let Some((connection_id, database)) = self.target.clone() else {
+            return;
+        };
+        self.snapshotting = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                let schema = db_manager.get_schema(None).await?;
+                let snapshot = SchemaSnapshot::from_schema(&schema);
+                record_schema_snapshot(connection_id, &database, &snapshot).await
+            }
+            .await;
+            this.update(cx, |this, cx| {
+                this.snapshotting = false;
+                match result {
+                    Ok(_) => this.load(cx),
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn render_entry(&self, entry: &SchemaChangeEntry, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let summary = if entry.baseline {
+            format!("First snapshot · {} tables", entry.tables)
+        } else {
+            format!(
+                "{} change{} · {} tables",
+                entry.changes.len(),
+                if entry.changes.len() == 1 { "" } else { "s" },
+                entry.tables
+            )
+        };
+        let changes = entry.changes.iter().map(|change| {
+            let (mark, color): (&str, Hsla) = match change.kind() {
+                SchemaChangeKind::Added => ("+", cx.theme().success),
+                SchemaChangeKind::Dropped => ("−", cx.theme().danger),
+                SchemaChangeKind::Altered => ("~", cx.theme().warning),
+            };
+            h_flex()
+                .gap_2()
+                .items_start()
+                .child(
+                    Label::new(mark)
+                        .text_sm()
+                        .font_family("monospace")
+                        .text_color(color),
+                )
+                .child(
+                    Label::new(change.description())
+                        .text_sm()
+                        .font_family("monospace"),
+                )
+        });
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .py_1p5()
+            .px_2()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        Label::new(
+                            entry
+                                .taken_at
+                                .with_timezone(&chrono::Local)
+                                .format("%b %d, %Y %H:%M")
+                                .to_string(),
+                        )
+                        .text_sm(),
+                    )
+                    .child(Label::new(summary).text_xs().text_color(muted)),
+            )
+            .children(changes)
+    }
+}
+
+impl Render for SchemaChangesPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| self.render_entry(entry, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let summary = if self.loading {
+            "Loading snapshots...".to_string()
+        } else if self.entries.is_empty() {
+            "No snapshots taken yet".to_string()
+        } else {
+            let changes: usize = self.entries.iter().map(|entry| entry.changes.len()).sum();
+            format!(
+                "{} changes across {} snapshots",
+                changes,
+                self.entries.len()
+            )
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Button::new("snapshot-schema")
+                                    .label("Snapshot Now")
+                                    .small()
+                                    .ghost()
+                                    .loading(self.snapshotting)
+                                    .disabled(self.target.is_none())
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.snapshot_now(cx);
+                                        cx.notify();
+                                    })),
+                            )
+                            .child(
+                                Button::new("reload-schema-changes")
+                                    .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                                    .small()
+                                    .ghost()
+                                    .tooltip("Reload")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.load(cx);
+                                        cx.notify();
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                Label::new(
+                    "Tables and columns are snapshotted on connecting to a saved connection; \
+                     unchanged snapshots are not kept",
+                )
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("schema-change-entries")
+                    .max_h(px(480.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(entries),
+            )
+    }
+}
+
+pub fn open_schema_changes_dialog(window: &mut Window, cx: &mut App) {
+    let panel = SchemaChangesPanel::view(window, cx);
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        dialog
+            .title("Schema Changes")
+            .width(px(720.))
+            .child(panel.clone())
+    });
+}
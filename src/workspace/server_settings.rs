@@ -0,0 +1,263 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement as _, Styled, Subscription, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Selectable as _, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    label::Label,
+    v_flex,
+};
+
+use crate::{
+    services::{DatabaseManager, ServerSetting, SettingsFilter, group_settings},
+    state::{WindowConnection, WindowScoped, window_id},
+};
+
+/// Dialog body browsing the server's configuration, grouped by category,
+/// with each value in readable units next to its built-in default.
+pub struct ServerSettingsPanel {
+    db_manager: DatabaseManager,
+    settings: Vec<ServerSetting>,
+    filter_input: Entity<InputState>,
+    commonly_tuned: bool,
+    changed_only: bool,
+    loading: bool,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ServerSettingsPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        let filter_input = cx.new(|cx| InputState::new(window, cx).placeholder("Filter settings"));
+        let _subscriptions =
+            vec![cx.subscribe(&filter_input, |_, _, _: &InputEvent, cx| cx.notify())];
+
+        let mut this = Self {
+            db_manager,
+            settings: vec![],
+            filter_input,
+            commonly_tuned: false,
+            changed_only: false,
+            loading: false,
+            error: None,
+            _subscriptions,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.get_server_settings().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(settings) => {
+                        this.settings = settings;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn render_setting(&self, setting: &ServerSetting, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let changed = setting.is_changed();
+        let default = setting
+            .default_value()
+            .filter(|_| changed)
+            .map(|default| format!("default {} · ", default))
+            .unwrap_or_default();
+
+        v_flex()
+            .w_full()
+            .gap_0p5()
+            .py_1()
+            .px_2()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(
+                                Label::new(setting.name.clone())
+                                    .text_sm()
+                                    .font_family("monospace"),
+                            )
+                            .when(setting.pending_restart, |d| {
+                                d.child(
+                                    Label::new("pending restart")
+                                        .text_xs()
+                                        .text_color(cx.theme().warning),
+                                )
+                            }),
+                    )
+                    .child(
+                        Label::new(setting.value())
+                            .text_sm()
+                            .font_family("monospace")
+                            .when(changed, |label| label.text_color(cx.theme().info)),
+                    ),
+            )
+            .child(
+                Label::new(format!(
+                    "{}set by {} · applies on {}",
+                    default, setting.source, setting.context
+                ))
+                .text_xs()
+                .text_color(muted),
+            )
+            .child(
+                Label::new(setting.short_desc.clone())
+                    .text_xs()
+                    .text_color(muted),
+            )
+    }
+}
+
+impl Render for ServerSettingsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let filter = SettingsFilter {
+            text: self.filter_input.read(cx).value().to_string(),
+            commonly_tuned: self.commonly_tuned,
+            changed_only: self.changed_only,
+        };
+        let groups = group_settings(&self.settings, &filter);
+        let shown: usize = groups.iter().map(|(_, group)| group.len()).sum();
+        let rows = groups
+            .iter()
+            .map(|(category, group)| {
+                v_flex()
+                    .child(
+                        div()
+                            .px_2()
+                            .py_1()
+                            .bg(cx.theme().secondary)
+                            .border_b_1()
+                            .border_color(cx.theme().border)
+                            .child(Label::new(category.to_string()).text_xs().font_medium()),
+                    )
+                    .children(
+                        group
+                            .iter()
+                            .map(|setting| self.render_setting(setting, cx).into_any_element()),
+                    )
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        let changed = self.settings.iter().filter(|s| s.is_changed()).count();
+        let pending = self.settings.iter().filter(|s| s.pending_restart).count();
+        let summary = if self.loading {
+            "Loading settings...".to_string()
+        } else {
+            let mut summary = format!(
+                "{} of {} settings · {} changed from default",
+                shown,
+                self.settings.len(),
+                changed
+            );
+            if pending > 0 {
+                summary.push_str(&format!(" · {} pending restart", pending));
+            }
+            summary
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        Button::new("reload-settings")
+                            .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip("Reload")
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.load(cx);
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .child(div().flex_1().child(Input::new(&self.filter_input).small()))
+                    .child(
+                        Button::new("commonly-tuned")
+                            .label("Commonly Tuned")
+                            .small()
+                            .ghost()
+                            .selected(self.commonly_tuned)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.commonly_tuned = !this.commonly_tuned;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("changed-only")
+                            .label("Changed from Default")
+                            .small()
+                            .ghost()
+                            .selected(self.changed_only)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.changed_only = !this.changed_only;
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("server-setting-rows")
+                    .max_h(px(480.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(rows),
+            )
+    }
+}
+
+pub fn open_server_settings_dialog(window: &mut Window, cx: &mut App) {
+    let panel = ServerSettingsPanel::view(window, cx);
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        dialog
+            .title("Server Configuration")
+            .width(px(720.))
+            .child(panel.clone())
+    });
+}
@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::sql::QueryVariables;
+
+use super::footer_bar::ResultsOrientation;
+use super::layout::PanelLayout;
+
+/// What a named workspace restores: the connection, editor contents and
+/// the file open in it, visible panels and their sizes, the table last
+/// selected in the tree and the variable environments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceSnapshot {
+    pub connection_id: Option<Uuid>,
+    pub editor_sql: String,
+    /// The `.sql` file open in the editor; `editor_sql` may hold unsaved
+    /// changes to it.
+    pub editor_file: Option<PathBuf>,
+    pub show_tables: bool,
+    pub show_agent: bool,
+    pub show_history: bool,
+    pub show_jobs: bool,
+    pub results_orientation: ResultsOrientation,
+    /// Where the side panels are docked and how wide they are.
+    pub panel_layout: Option<PanelLayout>,
+    /// `(schema, table)`
+    pub selected_table: Option<(String, String)>,
+    /// `{{name}}` variable environments.
+    pub variables: QueryVariables,
+}
@@ -0,0 +1,236 @@
+use gpui::{
+    App, AppContext, Context, Div, Entity, InteractiveElement as _, IntoElement, ParentElement,
+    Render, SharedString, StatefulInteractiveElement as _, Styled, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputState, TabSize},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::services::sql::{Snippet, builtin_snippets, sql_language};
+use crate::state::SnippetsState;
+use crate::workspace::notifications::Toasts as _;
+
+/// Dialog body listing the built-in snippets and editing the user's own.
+pub struct SnippetsForm {
+    user: Vec<Snippet>,
+    prefix_input: Entity<InputState>,
+    description_input: Entity<InputState>,
+    body_input: Entity<InputState>,
+}
+
+impl SnippetsForm {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let user = cx.global::<SnippetsState>().user.clone();
+        cx.new(|cx| Self {
+            user,
+            prefix_input: cx.new(|cx| InputState::new(window, cx).placeholder("Prefix, e.g. selu")),
+            description_input: cx.new(|cx| InputState::new(window, cx).placeholder("Description")),
+            body_input: cx.new(|cx| {
+                InputState::new(window, cx)
+                    .code_editor(sql_language())
+                    .tab_size(TabSize {
+                        tab_size: 2,
+                        hard_tabs: false,
+                    })
+                    .placeholder("SELECT $1 FROM users WHERE id = $2;$0")
+            }),
+        })
+    }
+
+    /// Add the snippet entered in the inputs, replacing the user's snippet
+    /// with the same prefix.
+    fn add_snippet(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let prefix = self.prefix_input.read(cx).value().trim().to_string();
+        let description = self.description_input.read(cx).value().trim().to_string();
+        let body = self.body_input.read(cx).value().trim_end().to_string();
+        let warning = if prefix.is_empty() || body.is_empty() {
+            Some("A snippet needs a prefix and a body")
+        } else if !prefix.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            Some("A prefix is a single word of letters, digits and underscores")
+        } else {
+            None
+        };
+        if let Some(warning) = warning {
+            window.push_toast((NotificationType::Warning, SharedString::from(warning)), cx);
+            return;
+        }
+
+        self.user.retain(|snippet| snippet.prefix != prefix);
+        self.user.push(Snippet {
+            prefix,
+            description,
+            body,
+        });
+        for input in [
+            &self.prefix_input,
+            &self.description_input,
+            &self.body_input,
+        ] {
+            input.update(cx, |input, cx| input.set_value("", window, cx));
+        }
+        cx.notify();
+    }
+
+    fn render_snippet(&self, snippet: &Snippet, note: Option<&str>, cx: &App) -> Div {
+        h_flex()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .w(px(64.))
+                    .font_family("Monaco")
+                    .text_xs()
+                    .child(snippet.prefix.clone()),
+            )
+            .child(
+                Label::new(snippet.description.clone())
+                    .flex_1()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .children(note.map(|note| {
+                Label::new(note.to_string())
+                    .text_xs()
+                    .text_color(cx.theme().warning)
+            }))
+    }
+}
+
+impl Render for SnippetsForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let user = self
+            .user
+            .iter()
+            .enumerate()
+            .map(|(ix, snippet)| {
+                self.render_snippet(snippet, None, cx)
+                    .child(
+                        Button::new(("remove-snippet", ix))
+                            .icon(Icon::empty().path("icons/trash.svg"))
+                            .xsmall()
+                            .ghost()
+                            .tooltip("Remove Snippet")
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.user.remove(ix);
+                                cx.notify();
+                            })),
+                    )
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+        let builtin = builtin_snippets()
+            .iter()
+            .map(|snippet| {
+                let replaced = self.user.iter().any(|user| user.prefix == snippet.prefix);
+                self.render_snippet(snippet, replaced.then_some("replaced"), cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_3()
+            .child(
+                Label::new(
+                    "Type a prefix and press Tab, or pick it from the completions. \
+                     $1, $2, ... are where Tab moves next, $0 where it ends; \\$ is a literal $.",
+                )
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+            )
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(Label::new("Your Snippets").text_sm())
+                    .when(user.is_empty(), |d| {
+                        d.child(
+                            Label::new("None yet")
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    })
+                    .children(user),
+            )
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .w(px(160.))
+                                    .child(Input::new(&self.prefix_input).small()),
+                            )
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .child(Input::new(&self.description_input).small()),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .h(px(120.))
+                            .child(Input::new(&self.body_input).h_full()),
+                    )
+                    .child(
+                        h_flex().child(
+                            Button::new("add-snippet")
+                                .label("Add Snippet")
+                                .icon(Icon::empty().path("icons/plus.svg"))
+                                .small()
+                                .ghost()
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.add_snippet(window, cx);
+                                })),
+                        ),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(Label::new("Built In").text_sm())
+                    .child(
+                        div()
+                            .id("builtin-snippets")
+                            .max_h(px(200.))
+                            .overflow_y_scroll()
+                            .border_1()
+                            .border_color(cx.theme().border)
+                            .rounded(cx.theme().radius)
+                            .children(builtin),
+                    ),
+            )
+    }
+}
+
+/// Open the snippets, saving the user's own on Save.
+pub fn open_snippets_dialog(window: &mut Window, cx: &mut App) {
+    let form = SnippetsForm::view(window, cx);
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        dialog
+            .title("Snippets")
+            .width(px(640.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Save"))
+            .on_ok(move |_, _window, cx| {
+                let user = form.read(cx).user.clone();
+                SnippetsState::set(user, cx);
+                true
+            })
+    });
+}
@@ -0,0 +1,311 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement as _, Styled, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    tooltip::Tooltip,
+    v_flex,
+};
+use uuid::Uuid;
+
+use crate::{
+    services::{
+        AppStore, DatabaseManager, format_byte_size,
+        storage::{PROJECTION_DAYS, SAMPLE_INTERVAL_HOURS, TableGrowth, table_growth},
+    },
+    state::{WindowConnection, WindowScoped, record_table_sizes, window_id},
+};
+
+const SPARKLINE_HEIGHT: f32 = 24.;
+
+/// Dialog body charting how the tables of the connected database grew
+/// over the sizes sampled on connecting, and where that growth leads.
+pub struct TableGrowthPanel {
+    db_manager: DatabaseManager,
+    /// The saved connection and database sampled, when connected.
+    target: Option<(Uuid, String)>,
+    tables: Vec<TableGrowth>,
+    loading: bool,
+    sampling: bool,
+    error: Option<String>,
+}
+
+impl TableGrowthPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let window = window_id(window);
+        cx.new(|cx| {
+            let state = WindowConnection::of(window, cx);
+            let db_manager = state.db_manager.clone();
+            let target = state
+                .active_connection
+                .as_ref()
+                .map(|connection| (connection.id, connection.database.clone()));
+            let mut this = Self {
+                db_manager,
+                target,
+                tables: vec![],
+                loading: false,
+                sampling: false,
+                error: None,
+            };
+            this.load(cx);
+            this
+        })
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        let Some((connection_id, database)) = self.target.clone() else {
+            self.error = Some("Not connected".to_string());
+            return;
+        };
+        self.loading = true;
+
+        cx.spawn(async move |this, cx| {
+            let result = async {
+                let store = AppStore::singleton().await?;
+                store.table_sizes().load(&connection_id, &database).await
+            }
+            .await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(samples) => {
+                        this.tables = table_growth(&samples);
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn sample_now(&mut self, cx: &mut Context<Self>) {
+        let Some((connection_id, database)) = self.target.clone() else {
+            return;
+        };
+        self.sampling = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = record_table_sizes(&db_manager, connection_id, &database).await;
+            this.update(cx, |this, cx| {
+                this.sampling = false;
+                match result {
+                    Ok(0) => {
+                        this.error = Some("Only saved connections keep size history".to_string())
+                    }
+                    Ok(_) => this.load(cx),
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn render_row(
+        &self,
+        ix: usize,
+        table: &TableGrowth,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let max = table
+            .samples
+            .iter()
+            .map(|(_, bytes)| *bytes)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let bars = table
+            .samples
+            .iter()
+            .enumerate()
+            .map(|(sample_ix, (at, bytes))| {
+                let height = (SPARKLINE_HEIGHT * *bytes as f32 / max as f32).max(2.);
+                let details = format!(
+                    "{} · {}",
+                    format_byte_size((*bytes).max(0) as usize),
+                    at.with_timezone(&chrono::Local).format("%b %d, %Y %H:%M")
+                );
+                div()
+                    .id(("table-growth-sample", ix * 10_000 + sample_ix))
+                    .flex_1()
+                    .max_w(px(6.))
+                    .h(px(height))
+                    .rounded(px(1.))
+                    .bg(cx.theme().primary)
+                    .tooltip(move |window, cx| Tooltip::new(details.clone()).build(window, cx))
+            });
+
+        let growth = if table.samples.len() < 2 {
+            "Sampled once; growth shows after the next sample".to_string()
+        } else {
+            let per_day = format_byte_size(table.bytes_per_day.abs() as usize);
+            let direction = if table.bytes_per_day < 0. {
+                "shrinking"
+            } else {
+                "growing"
+            };
+            let projections = PROJECTION_DAYS
+                .iter()
+                .map(|days| {
+                    format!(
+                        "{} in {} days",
+                        format_byte_size(table.projected_bytes(*days) as usize),
+                        days
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" · ");
+            format!(
+                "{} {}/day over {:.1} days · {}",
+                direction,
+                per_day,
+                table.days_sampled(),
+                projections
+            )
+        };
+
+        h_flex()
+            .w_full()
+            .gap_3()
+            .py_1()
+            .px_2()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_0p5()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .justify_between()
+                            .child(
+                                Label::new(format!("{}.{}", table.table_schema, table.table_name))
+                                    .text_sm()
+                                    .font_family("monospace"),
+                            )
+                            .child(
+                                Label::new(format_byte_size(table.current_bytes().max(0) as usize))
+                                    .text_sm(),
+                            ),
+                    )
+                    .child(Label::new(growth).text_xs().text_color(muted)),
+            )
+            .child(
+                h_flex()
+                    .w(px(120.))
+                    .h(px(SPARKLINE_HEIGHT))
+                    .items_end()
+                    .gap(px(1.))
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .children(bars),
+            )
+    }
+}
+
+impl Render for TableGrowthPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .tables
+            .iter()
+            .enumerate()
+            .map(|(ix, table)| self.render_row(ix, table, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let summary = if self.loading {
+            "Loading samples...".to_string()
+        } else if self.tables.is_empty() {
+            "No sizes sampled yet".to_string()
+        } else {
+            let total: i64 = self.tables.iter().map(|table| table.current_bytes()).sum();
+            format!(
+                "{} tables · {} in total",
+                self.tables.len(),
+                format_byte_size(total.max(0) as usize)
+            )
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Button::new("sample-table-sizes")
+                                    .label("Sample Now")
+                                    .small()
+                                    .ghost()
+                                    .loading(self.sampling)
+                                    .disabled(self.target.is_none())
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.sample_now(cx);
+                                        cx.notify();
+                                    })),
+                            )
+                            .child(
+                                Button::new("reload-table-growth")
+                                    .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                                    .small()
+                                    .ghost()
+                                    .tooltip("Reload")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.load(cx);
+                                        cx.notify();
+                                    })),
+                            ),
+                    ),
+            )
+            .child(
+                Label::new(format!(
+                    "Sizes include indexes and are sampled on connecting, at most every {} hours",
+                    SAMPLE_INTERVAL_HOURS
+                ))
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("table-growth-rows")
+                    .max_h(px(480.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(rows),
+            )
+    }
+}
+
+pub fn open_table_growth_dialog(window: &mut Window, cx: &mut App) {
+    let panel = TableGrowthPanel::view(window, cx);
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        dialog
+            .title("Table Growth")
+            .width(px(720.))
+            .child(panel.clone())
+    });
+}
@@ -0,0 +1,207 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement as _, Styled, Subscription, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+};
+
+use crate::{
+    services::{DatabaseDriver, TableInfo, comment_script, grant_script, table_privileges},
+    state::{WindowConnection, WindowScoped, window_id},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum BatchMode {
+    Comment,
+    Grant,
+}
+
+/// Dialog body for applying one comment template or one set of grants to
+/// several tables. The generated script is previewed live and loaded into
+/// the editor rather than run directly.
+pub struct BatchScriptForm {
+    tables: Vec<TableInfo>,
+    driver: DatabaseDriver,
+    mode: BatchMode,
+    comment_input: Entity<InputState>,
+    grantee_input: Entity<InputState>,
+    privileges: Vec<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl BatchScriptForm {
+    pub fn view(tables: Vec<TableInfo>, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(tables, window, cx))
+    }
+
+    fn new(tables: Vec<TableInfo>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let driver = WindowConnection::of(window_id(window), cx)
+            .active_connection
+            .as_ref()
+            .map_or(DatabaseDriver::Postgres, |c| c.driver);
+
+        let comment_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("e.g. Owned by billing ({schema}.{table})")
+        });
+        let grantee_input = cx.new(|cx| {
+            let placeholder = match driver {
+                DatabaseDriver::Postgres => "Role name or PUBLIC",
+                DatabaseDriver::MySql => "Account, e.g. 'app'@'%'",
+            };
+            InputState::new(window, cx).placeholder(placeholder)
+        });
+
+        // Refresh the preview as the user types.
+        let _subscriptions = vec![
+            cx.subscribe(&comment_input, |_, _, _: &InputEvent, cx| cx.notify()),
+            cx.subscribe(&grantee_input, |_, _, _: &InputEvent, cx| cx.notify()),
+        ];
+
+        Self {
+            tables,
+            driver,
+            mode: BatchMode::Comment,
+            comment_input,
+            grantee_input,
+            privileges: vec!["SELECT".to_string()],
+            _subscriptions,
+        }
+    }
+
+    /// The generated script; empty until the grant form is complete.
+    pub fn script(&self, cx: &App) -> String {
+        match self.mode {
+            BatchMode::Comment => comment_script(
+                &self.driver,
+                &self.tables,
+                self.comment_input.read(cx).value().trim(),
+            ),
+            BatchMode::Grant => grant_script(
+                &self.driver,
+                &self.tables,
+                &self.privileges,
+                &self.grantee_input.read(cx).value(),
+            ),
+        }
+    }
+
+    fn set_mode(&mut self, mode: BatchMode, cx: &mut Context<Self>) {
+        self.mode = mode;
+        cx.notify();
+    }
+
+    fn toggle_privilege(&mut self, privilege: &str, checked: bool, cx: &mut Context<Self>) {
+        self.privileges.retain(|p| p != privilege);
+        if checked {
+            self.privileges.push(privilege.to_string());
+        }
+        // Keep the statement's privilege order stable.
+        let order = table_privileges(&self.driver);
+        self.privileges
+            .sort_by_key(|p| order.iter().position(|o| o == p));
+        cx.notify();
+    }
+}
+
+impl Render for BatchScriptForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let objects = self
+            .tables
+            .iter()
+            .map(|t| format!("{}.{}", t.table_schema, t.table_name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mode_toggle = h_flex()
+            .gap_1()
+            .child(
+                Button::new("batch-comment")
+                    .label("Comment")
+                    .small()
+                    .ghost()
+                    .selected(self.mode == BatchMode::Comment)
+                    .on_click(cx.listener(|this, _, _win, cx| {
+                        this.set_mode(BatchMode::Comment, cx);
+                    })),
+            )
+            .child(
+                Button::new("batch-grant")
+                    .label("Grant")
+                    .small()
+                    .ghost()
+                    .selected(self.mode == BatchMode::Grant)
+                    .on_click(cx.listener(|this, _, _win, cx| {
+                        this.set_mode(BatchMode::Grant, cx);
+                    })),
+            );
+
+        let privileges = table_privileges(&self.driver).iter().enumerate().fold(
+            h_flex().gap_3().flex_wrap(),
+            |row, (ix, privilege)| {
+                let privilege = *privilege;
+                row.child(
+                    Checkbox::new(("privilege", ix))
+                        .label(privilege)
+                        .checked(self.privileges.iter().any(|p| p == privilege))
+                        .on_click(cx.listener(move |this, checked: &bool, _win, cx| {
+                            this.toggle_privilege(privilege, *checked, cx);
+                        })),
+                )
+            },
+        );
+
+        let script = self.script(cx);
+
+        v_form()
+            .small()
+            .child(
+                field()
+                    .label(format!("Objects ({})", self.tables.len()))
+                    .child(objects),
+            )
+            .child(field().label("Action").child(mode_toggle))
+            .when(self.mode == BatchMode::Comment, |form| {
+                form.child(
+                    field()
+                        .label("Comment template")
+                        .description("{schema} and {table} are replaced per object")
+                        .child(Input::new(&self.comment_input)),
+                )
+            })
+            .when(self.mode == BatchMode::Grant, |form| {
+                form.child(field().label("Privileges").child(privileges))
+                    .child(
+                        field()
+                            .label("Grantee")
+                            .required(true)
+                            .child(Input::new(&self.grantee_input)),
+                    )
+            })
+            .child(
+                field().label("Preview").child(
+                    div()
+                        .id("batch-script-preview")
+                        .max_h(px(200.))
+                        .overflow_y_scroll()
+                        .p_2()
+                        .rounded(cx.theme().radius)
+                        .bg(cx.theme().secondary)
+                        .font_family("monospace")
+                        .text_xs()
+                        .child(if script.is_empty() {
+                            "-- nothing to generate yet".to_string()
+                        } else {
+                            script
+                        }),
+                ),
+            )
+            .w_full()
+    }
+}
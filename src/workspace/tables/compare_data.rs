@@ -0,0 +1,101 @@
+use gpui::{
+    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
+    Window,
+};
+use gpui_component::{
+    IndexPath, Sizable as _,
+    form::{field, v_form},
+    select::{Select, SelectState},
+    switch::Switch,
+};
+
+use crate::{
+    services::{ConnectionInfo, TableInfo},
+    state::{ConnectionState, WindowConnection, WindowScoped, window_id},
+};
+
+/// Dialog body for comparing a table's rows against another saved
+/// connection. The active connection is always the source.
+pub struct CompareDataForm {
+    pub table: TableInfo,
+    targets: Vec<ConnectionInfo>,
+    target_select: Entity<SelectState<Vec<SharedString>>>,
+    with_script: bool,
+}
+
+impl CompareDataForm {
+    pub fn view(table: TableInfo, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(table, window, cx))
+    }
+
+    fn new(table: TableInfo, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let active_connection = &WindowConnection::of(window_id(window), cx).active_connection;
+        let active_id = active_connection.as_ref().map(|c| c.id);
+        let active_driver = active_connection.as_ref().map(|c| c.driver);
+        let state = cx.global::<ConnectionState>();
+
+        // Rows can only be compared between engines of the same kind.
+        let targets: Vec<ConnectionInfo> = state
+            .saved_connections
+            .iter()
+            .filter(|c| Some(c.id) != active_id && Some(c.driver) == active_driver)
+            .cloned()
+            .collect();
+
+        let names: Vec<SharedString> = targets.iter().map(|c| c.name.clone().into()).collect();
+        let selected = if names.is_empty() {
+            None
+        } else {
+            Some(IndexPath::new(0))
+        };
+        let target_select = cx.new(|cx| SelectState::new(names, selected, window, cx));
+
+        Self {
+            table,
+            targets,
+            target_select,
+            with_script: true,
+        }
+    }
+
+    pub fn selected_target(&self, cx: &App) -> Option<ConnectionInfo> {
+        let name = self.target_select.read(cx).selected_value()?;
+        self.targets
+            .iter()
+            .find(|c| c.name.as_str() == name.as_ref())
+            .cloned()
+    }
+
+    pub fn with_script(&self) -> bool {
+        self.with_script
+    }
+}
+
+impl Render for CompareDataForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_form()
+            .small()
+            .child(field().label("Table").child(format!(
+                "{}.{}",
+                self.table.table_schema, self.table.table_name
+            )))
+            .child(
+                field().label("Compare against").required(true).child(
+                    Select::new(&self.target_select)
+                        .placeholder("No other connections for this driver"),
+                ),
+            )
+            .child(
+                field().label_indent(false).child(
+                    Switch::new("compare-with-script")
+                        .checked(self.with_script)
+                        .label("Generate INSERT/UPDATE sync script")
+                        .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                            this.with_script = *checked;
+                            cx.notify();
+                        })),
+                ),
+            )
+            .w_full()
+    }
+}
@@ -0,0 +1,435 @@
+use anyhow::Result;
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Subscription, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, IndexPath, Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+
+use crate::{
+    services::{
+        ConstraintSpec, ConstraintValidation, DatabaseDriver, ReferentialAction, TableInfo,
+        TableSchema, add_constraint_sql,
+    },
+    state::{EditorState, WindowConnection, WindowScoped, window_id},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConstraintKind {
+    ForeignKey,
+    Check,
+    Unique,
+}
+
+impl ConstraintKind {
+    fn label(&self) -> &'static str {
+        match self {
+            ConstraintKind::ForeignKey => "Foreign Key",
+            ConstraintKind::Check => "Check",
+            ConstraintKind::Unique => "Unique",
+        }
+    }
+}
+
+/// Dialog body for adding a foreign key, check or unique constraint to a
+/// table. Columns and referenced tables come from the schema cache loaded
+/// on connect; the generated statements are previewed as they change.
+pub struct ConstraintForm {
+    pub table: TableInfo,
+    driver: DatabaseDriver,
+    kind: ConstraintKind,
+    /// Columns of the table, in order.
+    columns: Vec<String>,
+    /// Tables a foreign key can reference.
+    tables: Vec<TableSchema>,
+    /// Picked columns of the table, in the order they were picked.
+    picked_columns: Vec<String>,
+    /// Picked columns of the referenced table, in the order they were picked.
+    referenced_columns: Vec<String>,
+    name_input: Entity<InputState>,
+    check_input: Entity<InputState>,
+    table_select: Entity<SelectState<Vec<SharedString>>>,
+    on_delete_select: Entity<SelectState<Vec<SharedString>>>,
+    on_update_select: Entity<SelectState<Vec<SharedString>>>,
+    not_valid: bool,
+    validate: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ConstraintForm {
+    pub fn view(table: TableInfo, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(table, window, cx))
+    }
+
+    fn new(table: TableInfo, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let driver = WindowConnection::of(window_id(window), cx)
+            .active_connection
+            .as_ref()
+            .map_or(DatabaseDriver::Postgres, |c| c.driver);
+        let tables: Vec<TableSchema> = EditorState::of(window_id(window), cx)
+            .schema
+            .as_ref()
+            .map(|schema| {
+                schema
+                    .tables
+                    .iter()
+                    .filter(|t| t.table_type.contains("TABLE"))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let columns = tables
+            .iter()
+            .find(|t| t.table_schema == table.table_schema && t.table_name == table.table_name)
+            .map(|t| t.columns.iter().map(|c| c.column_name.clone()).collect())
+            .unwrap_or_default();
+
+        let name_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Leave empty for the default"));
+        let check_input = cx.new(|cx| InputState::new(window, cx).placeholder("e.g. total >= 0"));
+        let table_names: Vec<SharedString> = tables
+            .iter()
+            .map(|t| format!("{}.{}", t.table_schema, t.table_name).into())
+            .collect();
+        let table_select = cx.new(|cx| SelectState::new(table_names, None, window, cx));
+        let actions: Vec<SharedString> = ReferentialAction::all()
+            .iter()
+            .map(|action| action.sql().into())
+            .collect();
+        let on_delete_select =
+            cx.new(|cx| SelectState::new(actions.clone(), Some(IndexPath::new(0)), window, cx));
+        let on_update_select =
+            cx.new(|cx| SelectState::new(actions, Some(IndexPath::new(0)), window, cx));
+
+        let _subscriptions = vec![
+            cx.subscribe_in(&table_select, window, Self::on_select_table),
+            cx.observe(&name_input, |_, _, cx| cx.notify()),
+            cx.observe(&check_input, |_, _, cx| cx.notify()),
+        ];
+
+        Self {
+            table,
+            driver,
+            kind: ConstraintKind::ForeignKey,
+            columns,
+            tables,
+            picked_columns: vec![],
+            referenced_columns: vec![],
+            name_input,
+            check_input,
+            table_select,
+            on_delete_select,
+            on_update_select,
+            not_valid: false,
+            validate: true,
+            _subscriptions,
+        }
+    }
+
+    /// Reference the new table's primary key unless other columns are picked.
+    fn on_select_table(
+        &mut self,
+        _: &Entity<SelectState<Vec<SharedString>>>,
+        event: &SelectEvent<Vec<SharedString>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            SelectEvent::Confirm(_) => {
+                self.referenced_columns = self
+                    .referenced_table(cx)
+                    .map(|t| t.primary_keys.clone())
+                    .unwrap_or_default();
+                cx.notify();
+            }
+        }
+    }
+
+    fn referenced_table(&self, cx: &App) -> Option<&TableSchema> {
+        let name = self.table_select.read(cx).selected_value()?;
+        self.tables
+            .iter()
+            .find(|t| format!("{}.{}", t.table_schema, t.table_name) == name.as_ref())
+    }
+
+    fn action(select: &Entity<SelectState<Vec<SharedString>>>, cx: &App) -> ReferentialAction {
+        let selected = select.read(cx).selected_value().cloned();
+        ReferentialAction::all()
+            .into_iter()
+            .find(|action| {
+                selected
+                    .as_ref()
+                    .is_some_and(|s| s.as_ref() == action.sql())
+            })
+            .unwrap_or_default()
+    }
+
+    fn spec(&self, cx: &App) -> ConstraintSpec {
+        match self.kind {
+            ConstraintKind::ForeignKey => {
+                let referenced = self.referenced_table(cx);
+                ConstraintSpec::ForeignKey {
+                    columns: self.picked_columns.clone(),
+                    referenced_schema: referenced
+                        .map(|t| t.table_schema.clone())
+                        .unwrap_or_default(),
+                    referenced_table: referenced.map(|t| t.table_name.clone()).unwrap_or_default(),
+                    referenced_columns: self.referenced_columns.clone(),
+                    on_delete: Self::action(&self.on_delete_select, cx),
+                    on_update: Self::action(&self.on_update_select, cx),
+                }
+            }
+            ConstraintKind::Check => ConstraintSpec::Check {
+                expression: self.check_input.read(cx).value().to_string(),
+            },
+            ConstraintKind::Unique => ConstraintSpec::Unique {
+                columns: self.picked_columns.clone(),
+            },
+        }
+    }
+
+    fn validation(&self) -> ConstraintValidation {
+        let deferrable = self.driver == DatabaseDriver::Postgres
+            && self.kind != ConstraintKind::Unique
+            && self.not_valid;
+        match (deferrable, self.validate) {
+            (false, _) => ConstraintValidation::Immediate,
+            (true, false) => ConstraintValidation::NotValid,
+            (true, true) => ConstraintValidation::NotValidThenValidate,
+        }
+    }
+
+    /// The statements adding the constraint as entered.
+    pub fn statements(&self, cx: &App) -> Result<Vec<String>> {
+        add_constraint_sql(
+            &self.driver,
+            &self.table.table_schema,
+            &self.table.table_name,
+            self.name_input.read(cx).value().as_ref(),
+            &self.spec(cx),
+            self.validation(),
+        )
+    }
+
+    fn set_kind(&mut self, kind: ConstraintKind, cx: &mut Context<Self>) {
+        if self.kind != kind {
+            self.kind = kind;
+            self.picked_columns.clear();
+            cx.notify();
+        }
+    }
+
+    /// Checkboxes picking `columns` in order into the table's columns, or
+    /// into the referenced table's when `referenced`.
+    fn render_column_picker(
+        &self,
+        id: &'static str,
+        columns: &[String],
+        referenced: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let picked = if referenced {
+            &self.referenced_columns
+        } else {
+            &self.picked_columns
+        };
+        let items = columns.iter().enumerate().map(|(ix, column)| {
+            let position = picked.iter().position(|c| c == column);
+            let label = match position {
+                Some(position) if picked.len() > 1 => format!("{} ({})", column, position + 1),
+                _ => column.clone(),
+            };
+            let column = column.clone();
+            Checkbox::new((id, ix))
+                .label(label)
+                .checked(position.is_some())
+                .on_click(cx.listener(move |this, checked: &bool, _win, cx| {
+                    let picked = if referenced {
+                        &mut this.referenced_columns
+                    } else {
+                        &mut this.picked_columns
+                    };
+                    picked.retain(|c| c != &column);
+                    if *checked {
+                        picked.push(column.clone());
+                    }
+                    cx.notify();
+                }))
+        });
+        div()
+            .id(id)
+            .max_h(px(140.))
+            .overflow_y_scroll()
+            .child(v_flex().gap_1().children(items))
+    }
+}
+
+impl Render for ConstraintForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let field_label = |text: &'static str| Label::new(text).text_xs().text_color(muted);
+
+        let kinds = [
+            ConstraintKind::ForeignKey,
+            ConstraintKind::Check,
+            ConstraintKind::Unique,
+        ];
+        let kind_buttons = h_flex().gap_1().children(kinds.into_iter().map(|kind| {
+            Button::new(kind.label())
+                .label(kind.label())
+                .small()
+                .ghost()
+                .selected(self.kind == kind)
+                .on_click(cx.listener(move |this, _, _window, cx| this.set_kind(kind, cx)))
+        }));
+
+        let referenced_columns: Vec<String> = self
+            .referenced_table(cx)
+            .map(|t| t.columns.iter().map(|c| c.column_name.clone()).collect())
+            .unwrap_or_default();
+        let columns = self.columns.clone();
+        let can_defer =
+            self.driver == DatabaseDriver::Postgres && self.kind != ConstraintKind::Unique;
+
+        let preview = match self.statements(cx) {
+            Ok(statements) => div()
+                .p_2()
+                .rounded(cx.theme().radius)
+                .bg(cx.theme().secondary)
+                .font_family("monospace")
+                .text_sm()
+                .child(statements.join("\n")),
+            Err(e) => div()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child(e.to_string()),
+        };
+
+        v_flex()
+            .gap_3()
+            .child(
+                Label::new(format!(
+                    "{}.{}",
+                    self.table.table_schema, self.table.table_name
+                ))
+                .text_sm(),
+            )
+            .child(kind_buttons)
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(field_label("Name"))
+                    .child(Input::new(&self.name_input).small()),
+            )
+            .when(
+                self.columns.is_empty() && self.kind != ConstraintKind::Check,
+                |d| {
+                    d.child(
+                        Label::new(
+                            "This table's columns are not in the schema cache; reconnect to \
+                         reload it.",
+                        )
+                        .text_sm()
+                        .text_color(cx.theme().warning),
+                    )
+                },
+            )
+            .when(self.kind == ConstraintKind::ForeignKey, |d| {
+                d.child(
+                    h_flex()
+                        .gap_4()
+                        .items_start()
+                        .child(
+                            v_flex()
+                                .flex_1()
+                                .gap_1()
+                                .child(field_label("Columns"))
+                                .child(self.render_column_picker(
+                                    "constraint-columns",
+                                    &columns,
+                                    false,
+                                    cx,
+                                )),
+                        )
+                        .child(
+                            v_flex()
+                                .flex_1()
+                                .gap_1()
+                                .child(field_label("References"))
+                                .child(Select::new(&self.table_select).placeholder("Pick a table"))
+                                .child(self.render_column_picker(
+                                    "constraint-referenced-columns",
+                                    &referenced_columns,
+                                    true,
+                                    cx,
+                                )),
+                        ),
+                )
+                .child(
+                    h_flex()
+                        .gap_4()
+                        .child(
+                            v_flex()
+                                .flex_1()
+                                .gap_1()
+                                .child(field_label("On delete"))
+                                .child(Select::new(&self.on_delete_select)),
+                        )
+                        .child(
+                            v_flex()
+                                .flex_1()
+                                .gap_1()
+                                .child(field_label("On update"))
+                                .child(Select::new(&self.on_update_select)),
+                        ),
+                )
+            })
+            .when(self.kind == ConstraintKind::Check, |d| {
+                d.child(
+                    v_flex()
+                        .gap_1()
+                        .child(field_label("Condition"))
+                        .child(Input::new(&self.check_input).small()),
+                )
+            })
+            .when(self.kind == ConstraintKind::Unique, |d| {
+                d.child(v_flex().gap_1().child(field_label("Columns")).child(
+                    self.render_column_picker("constraint-unique-columns", &columns, false, cx),
+                ))
+            })
+            .when(can_defer, |d| {
+                d.child(
+                    Checkbox::new("constraint-not-valid")
+                        .label("Add NOT VALID, without checking existing rows (large tables)")
+                        .checked(self.not_valid)
+                        .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                            this.not_valid = *checked;
+                            cx.notify();
+                        })),
+                )
+                .when(self.not_valid, |d| {
+                    d.child(
+                        Checkbox::new("constraint-validate")
+                            .label(
+                                "Then VALIDATE CONSTRAINT, checking them without blocking writes",
+                            )
+                            .checked(self.validate)
+                            .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                                this.validate = *checked;
+                                cx.notify();
+                            })),
+                    )
+                })
+            })
+            .child(preview)
+    }
+}
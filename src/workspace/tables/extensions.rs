@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Subscription, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::{
+    services::{DatabaseManager, ExtensionAction, ExtensionInfo, extension_sql},
+    state::{ChangeRecorderState, WindowConnection, WindowScoped, window_id},
+    workspace::notifications::RecordedNotifications as _,
+};
+
+/// Dialog body listing the server's extensions, installed ones first, with
+/// install, update and drop actions. Each action shows its statement for
+/// confirmation before it runs.
+pub struct ExtensionsPanel {
+    db_manager: DatabaseManager,
+    extensions: Vec<ExtensionInfo>,
+    /// Objects owned by each installed extension.
+    object_counts: HashMap<String, usize>,
+    filter_input: Entity<InputState>,
+    loading: bool,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ExtensionsPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        let filter_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Filter extensions"));
+        let _subscriptions =
+            vec![cx.subscribe(&filter_input, |_, _, _: &InputEvent, cx| cx.notify())];
+
+        let mut this = Self {
+            db_manager,
+            extensions: vec![],
+            object_counts: HashMap::new(),
+            filter_input,
+            loading: false,
+            error: None,
+            _subscriptions,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.list_extensions().await;
+            let objects = db_manager
+                .list_extension_objects()
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("Failed to load extension objects: {}", e);
+                    vec![]
+                });
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(extensions) => {
+                        this.extensions = extensions;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                this.object_counts.clear();
+                for object in objects {
+                    *this.object_counts.entry(object.extension).or_default() += 1;
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Ask for confirmation, then run `action` on the extension at `ix`.
+    fn confirm_action(
+        &mut self,
+        ix: usize,
+        action: ExtensionAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(extension) = self.extensions.get(ix).cloned() else {
+            return;
+        };
+        let sql = extension_sql(&extension, action);
+
+        let panel = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            let extension = extension.clone();
+            dialog
+                .title(match action {
+                    ExtensionAction::Install => "Install Extension",
+                    ExtensionAction::Update => "Update Extension",
+                    ExtensionAction::Drop => "Drop Extension",
+                })
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(Label::new("The following statement will be executed:").text_sm())
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(sql.clone()),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = panel.update(cx, |this, cx| {
+                        this.run_action(extension.clone(), action, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn run_action(
+        &mut self,
+        extension: ExtensionInfo,
+        action: ExtensionAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.run_extension_action(&extension, action).await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok(sql) => {
+                        ChangeRecorderState::record(window_id(window), [sql.as_str()], cx);
+                        (NotificationType::Success, sql.into())
+                    }
+                    Err(e) => (NotificationType::Error, format!("{}", e).into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.load(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn render_row(
+        &self,
+        ix: usize,
+        extension: &ExtensionInfo,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let installed = extension.is_installed();
+        let has_update = extension.has_update();
+
+        let detail = match (&extension.installed_version, &extension.default_version) {
+            (Some(installed), _) => {
+                let mut detail = format!("v{}", installed);
+                if let Some(schema) = &extension.schema {
+                    detail.push_str(&format!(" in {}", schema));
+                }
+                if let Some(count) = self.object_counts.get(&extension.name) {
+                    detail.push_str(&format!(" · {} objects", count));
+                }
+                detail
+            }
+            (None, Some(default)) => format!("available v{}", default),
+            (None, None) => "available".to_string(),
+        };
+
+        h_flex()
+            .id(("extension", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .flex_1()
+                    .child(Label::new(extension.name.clone()).text_sm())
+                    .child(
+                        Label::new(detail)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .when_some(extension.comment.clone(), |d, comment| {
+                        d.child(
+                            Label::new(comment)
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .when(!installed, |d| {
+                        d.child(
+                            Button::new(("install-extension", ix))
+                                .label("Install")
+                                .small()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.confirm_action(ix, ExtensionAction::Install, window, cx);
+                                })),
+                        )
+                    })
+                    .when(has_update, |d| {
+                        d.child(
+                            Button::new(("update-extension", ix))
+                                .label("Update")
+                                .small()
+                                .warning()
+                                .tooltip(format!(
+                                    "Update to v{}",
+                                    extension.default_version.clone().unwrap_or_default()
+                                ))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.confirm_action(ix, ExtensionAction::Update, window, cx);
+                                })),
+                        )
+                    })
+                    .when(installed, |d| {
+                        d.child(
+                            Button::new(("drop-extension", ix))
+                                .label("Drop")
+                                .small()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.confirm_action(ix, ExtensionAction::Drop, window, cx);
+                                })),
+                        )
+                    }),
+            )
+    }
+}
+
+impl Render for ExtensionsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let filter = self.filter_input.read(cx).value().trim().to_lowercase();
+        let rows = self
+            .extensions
+            .iter()
+            .enumerate()
+            .filter(|(_, ext)| filter.is_empty() || ext.name.to_lowercase().contains(&filter))
+            .map(|(ix, ext)| self.render_row(ix, ext, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let installed = self.extensions.iter().filter(|e| e.is_installed()).count();
+        let updates = self.extensions.iter().filter(|e| e.has_update()).count();
+        let summary = if self.loading {
+            "Loading extensions...".to_string()
+        } else {
+            format!(
+                "{} available, {} installed, {} with updates",
+                self.extensions.len(),
+                installed,
+                updates
+            )
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .items_center()
+                            .child(
+                                div()
+                                    .w(px(180.))
+                                    .child(Input::new(&self.filter_input).small()),
+                            )
+                            .child(
+                                Button::new("reload-extensions")
+                                    .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                                    .small()
+                                    .ghost()
+                                    .tooltip("Reload")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.load(cx);
+                                        cx.notify();
+                                    })),
+                            ),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("extension-rows")
+                    .max_h(px(420.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(rows),
+            )
+    }
+}
@@ -0,0 +1,288 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Subscription, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, IndexPath, Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    select::{Select, SelectEvent, SelectState},
+};
+
+use crate::{
+    services::{
+        ConnectionInfo, CrossDatabaseTool, DatabaseDriver, DatabaseManager, ForeignServerSetup,
+        available_tools, foreign_server_script,
+    },
+    state::{ConnectionState, WindowConnection, WindowScoped, window_id},
+};
+
+/// Dialog body for querying another saved Postgres connection from the
+/// active one. Which of `postgres_fdw` and `dblink` can be used is read
+/// from the server's extensions; the setup script is previewed live and
+/// loaded into the editor rather than run directly.
+pub struct ForeignServerForm {
+    db_manager: DatabaseManager,
+    targets: Vec<ConnectionInfo>,
+    target_select: Entity<SelectState<Vec<SharedString>>>,
+    /// Tools the server offers, with whether each is already installed.
+    tools: Vec<(CrossDatabaseTool, bool)>,
+    tool: CrossDatabaseTool,
+    server_name_input: Entity<InputState>,
+    remote_schema_input: Entity<InputState>,
+    local_schema_input: Entity<InputState>,
+    loading: bool,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ForeignServerForm {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let window_connection = WindowConnection::of(window_id(window), cx);
+        let db_manager = window_connection.db_manager.clone();
+        let active_id = window_connection.active_connection.as_ref().map(|c| c.id);
+
+        // Both tools only speak to other Postgres servers.
+        let targets: Vec<ConnectionInfo> = cx
+            .global::<ConnectionState>()
+            .saved_connections
+            .iter()
+            .filter(|c| Some(c.id) != active_id && c.driver == DatabaseDriver::Postgres)
+            .cloned()
+            .collect();
+
+        let names: Vec<SharedString> = targets.iter().map(|c| c.name.clone().into()).collect();
+        let selected = if names.is_empty() {
+            None
+        } else {
+            Some(IndexPath::new(0))
+        };
+        let target_select = cx.new(|cx| SelectState::new(names, selected, window, cx));
+
+        let server_name_input = cx.new(|cx| InputState::new(window, cx).placeholder("remote"));
+        let remote_schema_input = cx.new(|cx| {
+            let mut input = InputState::new(window, cx);
+            input.set_value("public", window, cx);
+            input
+        });
+        let local_schema_input = cx.new(|cx| InputState::new(window, cx).placeholder("remote"));
+
+        // Refresh the preview as the user types.
+        let _subscriptions = vec![
+            cx.subscribe_in(&target_select, window, Self::on_select_target),
+            cx.subscribe(&server_name_input, |_, _, _: &InputEvent, cx| cx.notify()),
+            cx.subscribe(&remote_schema_input, |_, _, _: &InputEvent, cx| cx.notify()),
+            cx.subscribe(&local_schema_input, |_, _, _: &InputEvent, cx| cx.notify()),
+        ];
+
+        let mut this = Self {
+            db_manager,
+            targets,
+            target_select,
+            tools: vec![],
+            tool: CrossDatabaseTool::PostgresFdw,
+            server_name_input,
+            remote_schema_input,
+            local_schema_input,
+            loading: false,
+            error: None,
+            _subscriptions,
+        };
+        this.fill_names(window, cx);
+        this.load_extensions(cx);
+        this
+    }
+
+    fn load_extensions(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.list_extensions().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(extensions) => {
+                        this.tools = available_tools(&extensions);
+                        // Prefer postgres_fdw, and an installed tool over one
+                        // that still needs CREATE EXTENSION.
+                        if let Some((tool, _)) = this
+                            .tools
+                            .iter()
+                            .find(|(_, installed)| *installed)
+                            .or_else(|| this.tools.first())
+                        {
+                            this.tool = *tool;
+                        }
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn on_select_target(
+        &mut self,
+        _: &Entity<SelectState<Vec<SharedString>>>,
+        _: &SelectEvent<Vec<SharedString>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.fill_names(window, cx);
+        cx.notify();
+    }
+
+    /// Name the server and local schema after the selected target.
+    fn fill_names(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(target) = self.selected_target(cx) else {
+            return;
+        };
+        let defaults = ForeignServerSetup::new(self.tool, &target);
+        self.server_name_input.update(cx, |input, cx| {
+            input.set_value(defaults.server_name.clone(), window, cx)
+        });
+        self.local_schema_input.update(cx, |input, cx| {
+            input.set_value(defaults.local_schema.clone(), window, cx)
+        });
+    }
+
+    fn selected_target(&self, cx: &App) -> Option<ConnectionInfo> {
+        let name = self.target_select.read(cx).selected_value()?;
+        self.targets
+            .iter()
+            .find(|c| c.name.as_str() == name.as_ref())
+            .cloned()
+    }
+
+    fn set_tool(&mut self, tool: CrossDatabaseTool, cx: &mut Context<Self>) {
+        self.tool = tool;
+        cx.notify();
+    }
+
+    fn tool_installed(&self, tool: CrossDatabaseTool) -> Option<bool> {
+        self.tools
+            .iter()
+            .find(|(t, _)| *t == tool)
+            .map(|(_, installed)| *installed)
+    }
+
+    /// The generated script; empty until a target and an available tool
+    /// are picked and the names are filled in.
+    pub fn script(&self, cx: &App) -> String {
+        let Some(installed) = self.tool_installed(self.tool) else {
+            return String::new();
+        };
+        let Some(target) = self.selected_target(cx) else {
+            return String::new();
+        };
+
+        let mut setup = ForeignServerSetup::new(self.tool, &target);
+        setup.install_extension = !installed;
+        setup.server_name = self.server_name_input.read(cx).value().to_string();
+        setup.remote_schema = self.remote_schema_input.read(cx).value().to_string();
+        setup.local_schema = self.local_schema_input.read(cx).value().to_string();
+        foreign_server_script(&setup)
+    }
+}
+
+impl Render for ForeignServerForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let tool_toggle = CrossDatabaseTool::ALL
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, tool| {
+                row.child(
+                    Button::new(tool.extension())
+                        .label(tool.extension())
+                        .small()
+                        .ghost()
+                        .selected(self.tool == tool)
+                        .disabled(self.tool_installed(tool).is_none())
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.set_tool(tool, cx);
+                        })),
+                )
+            });
+
+        let tool_status = if self.loading {
+            "Checking the server's extensions…".to_string()
+        } else if let Some(error) = &self.error {
+            error.clone()
+        } else {
+            match self.tool_installed(self.tool) {
+                Some(true) => format!("{} is installed", self.tool.extension()),
+                Some(false) => format!(
+                    "{} is available and will be installed (needs CREATE privilege)",
+                    self.tool.extension()
+                ),
+                None if self.tools.is_empty() => {
+                    "Neither postgres_fdw nor dblink is available on this server".to_string()
+                }
+                None => format!("{} is not available on this server", self.tool.extension()),
+            }
+        };
+
+        let script = self.script(cx);
+
+        v_form()
+            .small()
+            .child(
+                field()
+                    .label("Tool")
+                    .description(tool_status)
+                    .child(tool_toggle),
+            )
+            .child(field().label("Remote connection").required(true).child(
+                Select::new(&self.target_select).placeholder("No other Postgres connections"),
+            ))
+            .child(
+                field()
+                    .label("Foreign server name")
+                    .required(true)
+                    .child(Input::new(&self.server_name_input)),
+            )
+            .when(self.tool == CrossDatabaseTool::PostgresFdw, |form| {
+                form.child(
+                    field()
+                        .label("Remote schema")
+                        .required(true)
+                        .child(Input::new(&self.remote_schema_input)),
+                )
+                .child(
+                    field()
+                        .label("Import into local schema")
+                        .required(true)
+                        .child(Input::new(&self.local_schema_input)),
+                )
+            })
+            .child(
+                field().label("Preview").child(
+                    div()
+                        .id("foreign-server-preview")
+                        .max_h(px(200.))
+                        .overflow_y_scroll()
+                        .p_2()
+                        .rounded(cx.theme().radius)
+                        .bg(cx.theme().secondary)
+                        .font_family("monospace")
+                        .text_xs()
+                        .child(if script.is_empty() {
+                            "-- nothing to generate yet".to_string()
+                        } else {
+                            script
+                        }),
+                ),
+            )
+            .w_full()
+    }
+}
@@ -1,3 +1,24 @@
+mod batch_script;
+mod compare_data;
+mod constraint_form;
+mod extensions;
+mod foreign_server;
+mod new_row_form;
+mod object_search;
+mod sequences;
+mod structure_editor;
 mod tables_tree;
+mod triggers;
+mod user_types;
 
-pub use tables_tree::{TableEvent, TablesTree};
+pub use batch_script::BatchScriptForm;
+pub use compare_data::CompareDataForm;
+pub use constraint_form::ConstraintForm;
+pub use extensions::ExtensionsPanel;
+pub use foreign_server::ForeignServerForm;
+pub use new_row_form::NewRowForm;
+pub use object_search::{ObjectSearch, ObjectSearchEvent, SearchDatabase};
+pub use sequences::SequencesPanel;
+pub use structure_editor::StructureEditor;
+pub use tables_tree::{SelectItem, TableEvent, TablesTree};
+pub use user_types::UserTypeDetails;
@@ -0,0 +1,237 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    select::{Select, SelectState},
+    switch::Switch,
+    v_flex,
+};
+
+use crate::services::{EditableColumn, ParamStatement, TableEditInfo};
+
+/// Editor for a single column of the new row: free text, or a select for
+/// enum columns.
+enum ValueEditor {
+    Text(Entity<InputState>),
+    Enum(Entity<SelectState<Vec<SharedString>>>),
+}
+
+struct ColumnField {
+    column: EditableColumn,
+    editor: ValueEditor,
+    is_null: bool,
+}
+
+/// Dialog body for inserting a row, generated from the table's columns.
+///
+/// Fields left empty are omitted from the INSERT so the server applies the
+/// column default; nullable columns get a NULL toggle. The generated INSERT
+/// is shown for review before it runs.
+pub struct NewRowForm {
+    pub info: TableEditInfo,
+    fields: Vec<ColumnField>,
+    /// The INSERT built from the fields, while it is being reviewed.
+    review: Option<ParamStatement>,
+}
+
+impl NewRowForm {
+    pub fn view(info: TableEditInfo, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(info, window, cx))
+    }
+
+    fn new(info: TableEditInfo, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let fields = info
+            .columns
+            .iter()
+            .map(|column| {
+                let editor = if column.enum_values.is_empty() {
+                    let placeholder = match &column.column_default {
+                        Some(default) => format!("DEFAULT {}", default),
+                        None => column.data_type.clone(),
+                    };
+                    ValueEditor::Text(
+                        cx.new(|cx| InputState::new(window, cx).placeholder(placeholder)),
+                    )
+                } else {
+                    let labels: Vec<SharedString> = column
+                        .enum_values
+                        .iter()
+                        .map(|v| SharedString::from(v.clone()))
+                        .collect();
+                    ValueEditor::Enum(cx.new(|cx| SelectState::new(labels, None, window, cx)))
+                };
+                ColumnField {
+                    column: column.clone(),
+                    editor,
+                    is_null: false,
+                }
+            })
+            .collect();
+
+        Self {
+            info,
+            fields,
+            review: None,
+        }
+    }
+
+    /// Show `statement` in place of the fields until it is confirmed.
+    pub fn review(&mut self, statement: ParamStatement, cx: &mut Context<Self>) {
+        self.review = Some(statement);
+        cx.notify();
+    }
+
+    /// The INSERT under review, once the fields were confirmed.
+    pub fn reviewed(&self) -> Option<&ParamStatement> {
+        self.review.as_ref()
+    }
+
+    /// Values to insert, by column name. Empty fields are left out.
+    pub fn values(&self, cx: &App) -> Vec<(String, Option<String>)> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                if field.is_null {
+                    return Some((field.column.name.clone(), None));
+                }
+                let value = match &field.editor {
+                    ValueEditor::Text(input) => {
+                        let text = input.read(cx).value().to_string();
+                        (!text.is_empty()).then_some(text)
+                    }
+                    ValueEditor::Enum(select) => {
+                        select.read(cx).selected_value().map(|v| v.to_string())
+                    }
+                };
+                value.map(|v| (field.column.name.clone(), Some(v)))
+            })
+            .collect()
+    }
+
+    /// Required columns (NOT NULL without a default) that have no value.
+    pub fn missing_required(&self, cx: &App) -> Vec<String> {
+        let values = self.values(cx);
+        self.fields
+            .iter()
+            .filter(|f| !f.column.is_nullable && f.column.column_default.is_none())
+            .filter(|f| !values.iter().any(|(name, _)| *name == f.column.name))
+            .map(|f| f.column.name.clone())
+            .collect()
+    }
+}
+
+impl Render for NewRowForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if let Some(statement) = &self.review {
+            let params = statement
+                .params
+                .iter()
+                .enumerate()
+                .map(|(ix, value)| match value {
+                    Some(value) => format!("{}: {}", ix + 1, value),
+                    None => format!("{}: NULL", ix + 1),
+                })
+                .collect::<Vec<_>>();
+            return v_flex()
+                .gap_2()
+                .child(Label::new("The following statement will be executed:").text_sm())
+                .child(
+                    div()
+                        .p_2()
+                        .rounded(cx.theme().radius)
+                        .bg(cx.theme().secondary)
+                        .font_family("monospace")
+                        .text_sm()
+                        .child(statement.sql.clone()),
+                )
+                .when(!params.is_empty(), |d| {
+                    d.child(Label::new("With the values:").text_sm()).child(
+                        div()
+                            .id("new-row-params")
+                            .p_2()
+                            .max_h(px(240.))
+                            .overflow_y_scroll()
+                            .rounded(cx.theme().radius)
+                            .bg(cx.theme().secondary)
+                            .font_family("monospace")
+                            .text_sm()
+                            .child(params.join("\n")),
+                    )
+                })
+                .child(
+                    h_flex().child(
+                        Button::new("edit-new-row")
+                            .label("Edit Values")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.review = None;
+                                cx.notify();
+                            })),
+                    ),
+                )
+                .into_any_element();
+        }
+
+        let form = self
+            .fields
+            .iter()
+            .enumerate()
+            .fold(v_form().small(), |form, (ix, f)| {
+                let column = &f.column;
+                let mut description = column.data_type.clone();
+                if column.is_primary_key {
+                    description.push_str(" · primary key");
+                }
+
+                let editor = match &f.editor {
+                    ValueEditor::Text(input) => {
+                        Input::new(input).disabled(f.is_null).into_any_element()
+                    }
+                    ValueEditor::Enum(select) => Select::new(select)
+                        .placeholder("Default")
+                        .disabled(f.is_null)
+                        .into_any_element(),
+                };
+
+                form.child(
+                    field()
+                        .label(column.name.clone())
+                        .description(description)
+                        .required(!column.is_nullable && column.column_default.is_none())
+                        .child(h_flex().gap_2().child(div().flex_1().child(editor)).when(
+                            column.is_nullable,
+                            |d| {
+                                d.child(
+                                    Switch::new(("null", ix))
+                                        .checked(f.is_null)
+                                        .label("NULL")
+                                        .small()
+                                        .on_click(cx.listener(
+                                            move |this, checked: &bool, _win, cx| {
+                                                this.fields[ix].is_null = *checked;
+                                                cx.notify();
+                                            },
+                                        )),
+                                )
+                            },
+                        )),
+                )
+            });
+
+        div()
+            .id("new-row-fields")
+            .max_h(px(480.))
+            .overflow_y_scroll()
+            .child(form)
+            .into_any_element()
+    }
+}
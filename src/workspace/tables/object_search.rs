@@ -0,0 +1,231 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, InteractiveElement as _, IntoElement,
+    ParentElement, Render, StatefulInteractiveElement as _, Styled, Subscription, Window, WindowId,
+    actions, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    label::Label,
+    v_flex,
+};
+
+use crate::{
+    services::{
+        DatabaseManager, ObjectHit, ObjectMatch, ObjectSource, RoutineInfo, search_objects,
+    },
+    state::{EditorState, WindowConnection, WindowScoped, window_id},
+};
+
+actions!(workspace, [SearchDatabase]);
+
+const MAX_RESULTS: usize = 50;
+
+pub enum ObjectSearchEvent {
+    Selected(ObjectHit),
+}
+
+impl EventEmitter<ObjectSearchEvent> for ObjectSearch {}
+
+/// Dialog body for finding tables, views, columns and functions by name,
+/// optionally also inside view and function source.
+pub struct ObjectSearch {
+    window_id: WindowId,
+    db_manager: DatabaseManager,
+    query_input: Entity<InputState>,
+    routines: Vec<RoutineInfo>,
+    search_source: bool,
+    /// Fetched the first time source search is turned on.
+    sources: Option<Vec<ObjectSource>>,
+    loading_sources: bool,
+    matches: Vec<ObjectMatch>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ObjectSearch {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let search = cx.new(|cx| Self::new(window, cx));
+        let query_input = search.read(cx).query_input.clone();
+        query_input.update(cx, |input, cx| input.focus(window, cx));
+        search
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let window_id = window_id(window);
+        let db_manager = WindowConnection::of(window_id, cx).db_manager.clone();
+        let query_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("Tables, views, columns, functions...")
+        });
+
+        let _subscriptions = vec![cx.subscribe(&query_input, Self::on_query_event)];
+
+        let this = Self {
+            window_id,
+            db_manager,
+            query_input,
+            routines: vec![],
+            search_source: false,
+            sources: None,
+            loading_sources: false,
+            matches: vec![],
+            _subscriptions,
+        };
+        this.load_routines(cx);
+        this
+    }
+
+    fn on_query_event(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::Change => self.search(cx),
+            // Enter picks the best match.
+            InputEvent::PressEnter { .. } => {
+                if let Some(first) = self.matches.first() {
+                    cx.emit(ObjectSearchEvent::Selected(first.hit.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn load_routines(&self, cx: &mut Context<Self>) {
+        let db_manager = self.db_manager.clone();
+        cx.spawn(async move |this, cx| {
+            let routines = db_manager.get_routines().await.unwrap_or_else(|e| {
+                tracing::error!("Failed to load routines: {}", e);
+                vec![]
+            });
+            this.update(cx, |this, cx| {
+                this.routines = routines;
+                this.search(cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn toggle_search_source(&mut self, checked: bool, cx: &mut Context<Self>) {
+        self.search_source = checked;
+        if checked && self.sources.is_none() && !self.loading_sources {
+            self.loading_sources = true;
+            let db_manager = self.db_manager.clone();
+            cx.spawn(async move |this, cx| {
+                let sources = db_manager.get_object_sources().await.unwrap_or_else(|e| {
+                    tracing::error!("Failed to load object sources: {}", e);
+                    vec![]
+                });
+                this.update(cx, |this, cx| {
+                    this.loading_sources = false;
+                    this.sources = Some(sources);
+                    this.search(cx);
+                })
+                .ok();
+            })
+            .detach();
+        }
+        self.search(cx);
+    }
+
+    fn search(&mut self, cx: &mut Context<Self>) {
+        let query = self.query_input.read(cx).value().to_string();
+        let state = EditorState::of(self.window_id, cx);
+        let sources = match (&self.sources, self.search_source) {
+            (Some(sources), true) => sources.as_slice(),
+            _ => &[],
+        };
+        self.matches = search_objects(
+            &query,
+            &state.tables,
+            state.schema.as_ref(),
+            &self.routines,
+            sources,
+            MAX_RESULTS,
+        );
+        cx.notify();
+    }
+}
+
+impl Render for ObjectSearch {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let hover_bg = cx.theme().accent;
+        let rows = self
+            .matches
+            .iter()
+            .enumerate()
+            .map(|(ix, found)| {
+                let hit = found.hit.clone();
+                h_flex()
+                    .id(("object-match", ix))
+                    .w_full()
+                    .gap_2()
+                    .py_1()
+                    .px_2()
+                    .items_center()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .hover(move |s| s.bg(hover_bg))
+                    .cursor_pointer()
+                    .child(
+                        div().w(px(56.)).child(
+                            Label::new(found.kind())
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        ),
+                    )
+                    .child(Label::new(found.title()).text_sm())
+                    .when(found.in_source, |d| {
+                        d.child(
+                            Label::new("in source")
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    })
+                    .on_click(cx.listener(move |_this, _, _window, cx| {
+                        cx.emit(ObjectSearchEvent::Selected(hit.clone()));
+                    }))
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        let query_empty = self.query_input.read(cx).value().trim().is_empty();
+        let status = if self.loading_sources {
+            Some("Loading view and function source...".to_string())
+        } else if !query_empty && self.matches.is_empty() {
+            Some("No matching objects".to_string())
+        } else {
+            None
+        };
+
+        v_flex()
+            .gap_2()
+            .child(Input::new(&self.query_input))
+            .child(
+                Checkbox::new("search-source")
+                    .label("Also search view and function source")
+                    .checked(self.search_source)
+                    .on_click(cx.listener(|this, checked: &bool, _win, cx| {
+                        this.toggle_search_source(*checked, cx);
+                    })),
+            )
+            .when_some(status, |d, status| {
+                d.child(
+                    Label::new(status)
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .child(
+                div()
+                    .id("object-matches")
+                    .max_h(px(420.))
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
+    }
+}
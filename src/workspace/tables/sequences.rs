@@ -0,0 +1,315 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::{
+    services::{DatabaseDriver, DatabaseManager, SequenceInfo, restart_sequence_sql},
+    state::{ChangeRecorderState, WindowConnection, WindowScoped, window_id},
+    workspace::notifications::RecordedNotifications as _,
+};
+
+/// Dialog body listing sequences (AUTO_INCREMENT counters on MySQL) with
+/// actions to restart them, after confirmation, or move them past their
+/// column's maximum.
+pub struct SequencesPanel {
+    db_manager: DatabaseManager,
+    driver: Option<DatabaseDriver>,
+    sequences: Vec<SequenceInfo>,
+    restart_value: Entity<InputState>,
+    loading: bool,
+    error: Option<String>,
+}
+
+enum SequenceAction {
+    Restart(Option<i64>),
+    Sync,
+}
+
+impl SequencesPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        let restart_value = cx.new(|cx| InputState::new(window, cx).placeholder("Start value"));
+
+        let mut this = Self {
+            db_manager,
+            driver: None,
+            sequences: vec![],
+            restart_value,
+            loading: false,
+            error: None,
+        };
+        this.load(cx);
+        this
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.list_sequences().await;
+            let driver = db_manager.driver().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                this.driver = driver;
+                match result {
+                    Ok(sequences) => {
+                        this.sequences = sequences;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn run(
+        &mut self,
+        ix: usize,
+        action: SequenceAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(seq) = self.sequences.get(ix).cloned() else {
+            return;
+        };
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = match action {
+                SequenceAction::Restart(value) => db_manager.restart_sequence(&seq, value).await,
+                SequenceAction::Sync => db_manager.sync_sequence(&seq).await,
+            };
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok(sql) => {
+                        ChangeRecorderState::record(window_id(window), [sql.as_str()], cx);
+                        (NotificationType::Success, sql.into())
+                    }
+                    Err(e) => (NotificationType::Error, format!("{}", e).into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.load(cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Show the restart statement for the sequence at `ix`, and run it
+    /// once confirmed.
+    fn confirm_restart(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(seq), Some(driver)) = (self.sequences.get(ix), self.driver) else {
+            return;
+        };
+        let value = self.restart_value(cx);
+        let sql = restart_sequence_sql(&driver, seq, value);
+
+        let panel = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            dialog
+                .title("Restart Sequence")
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(Label::new("The following statement will be executed:").text_sm())
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(sql.clone()),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = panel.update(cx, |this, cx| {
+                        this.run(ix, SequenceAction::Restart(value), window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn restart_value(&self, cx: &App) -> Option<i64> {
+        self.restart_value.read(cx).value().trim().parse().ok()
+    }
+
+    fn render_row(
+        &self,
+        ix: usize,
+        seq: &SequenceInfo,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let owner = seq
+            .owner
+            .as_ref()
+            .map(|o| format!("{}.{}.{}", o.table_schema, o.table_name, o.column_name));
+        let out_of_sync = seq.is_out_of_sync();
+
+        let mut detail = match seq.next_value() {
+            Some(next) => format!("next {}", next),
+            None => "exhausted".to_string(),
+        };
+        if let Some(max) = seq.column_max {
+            detail.push_str(&format!(" · max {}", max));
+        }
+        if seq.is_identity {
+            detail.push_str(" · identity");
+        }
+
+        h_flex()
+            .id(("sequence", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(
+                        Label::new(format!("{}.{}", seq.sequence_schema, seq.sequence_name))
+                            .text_sm(),
+                    )
+                    .when_some(owner, |d, owner| {
+                        d.child(
+                            Label::new(owner)
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    })
+                    .child(
+                        Label::new(detail)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .when(out_of_sync, |d| {
+                        d.child(
+                            Label::new("Out of sync")
+                                .text_xs()
+                                .text_color(cx.theme().warning),
+                        )
+                    })
+                    .child(
+                        Button::new(("sync-sequence", ix))
+                            .label("Sync")
+                            .small()
+                            .when(out_of_sync, |b| b.warning())
+                            .when(!out_of_sync, |b| b.ghost())
+                            .disabled(seq.owner.is_none())
+                            .tooltip("Set next value from MAX() of the column")
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.run(ix, SequenceAction::Sync, window, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(("restart-sequence", ix))
+                            .label("Restart")
+                            .small()
+                            .ghost()
+                            .tooltip("Restart at the value above, or the start value")
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.confirm_restart(ix, window, cx);
+                            })),
+                    ),
+            )
+    }
+}
+
+impl Render for SequencesPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .sequences
+            .iter()
+            .enumerate()
+            .map(|(ix, seq)| self.render_row(ix, seq, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let out_of_sync = self.sequences.iter().filter(|s| s.is_out_of_sync()).count();
+        let summary = if self.loading {
+            "Loading sequences...".to_string()
+        } else {
+            format!(
+                "{} sequences, {} out of sync",
+                self.sequences.len(),
+                out_of_sync
+            )
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .items_center()
+                            .child(
+                                div()
+                                    .w(px(140.))
+                                    .child(Input::new(&self.restart_value).small()),
+                            )
+                            .child(
+                                Button::new("reload-sequences")
+                                    .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                                    .small()
+                                    .ghost()
+                                    .tooltip("Reload")
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.load(cx);
+                                        cx.notify();
+                                    })),
+                            ),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("sequence-rows")
+                    .max_h(px(420.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(rows),
+            )
+    }
+}
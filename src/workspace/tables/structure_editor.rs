@@ -0,0 +1,539 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Icon, Selectable as _, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::{
+    services::{
+        ColumnChange, ColumnDefinition, DatabaseDriver, DatabaseManager, QueryExecutionResult,
+        TableInfo, TableStorage, alter_table_sql, parse_storage_parameters, storage_parameters_sql,
+    },
+    state::{ChangeRecorderState, WindowConnection, WindowScoped, window_id},
+    workspace::notifications::Toasts as _,
+};
+
+use super::triggers::TriggersPanel;
+
+/// One column of the editor, existing or being added.
+struct ColumnRow {
+    /// The column as loaded; `None` for a column being added.
+    original: Option<ColumnDefinition>,
+    name: Entity<InputState>,
+    data_type: Entity<InputState>,
+    default: Entity<InputState>,
+    nullable: bool,
+    /// Marked to be dropped.
+    dropped: bool,
+}
+
+impl ColumnRow {
+    fn new(
+        original: Option<ColumnDefinition>,
+        column: ColumnDefinition,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        let name = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("name")
+                .default_value(column.name)
+        });
+        let data_type = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("type")
+                .default_value(column.data_type)
+        });
+        let default = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("no default")
+                .default_value(column.default.unwrap_or_default())
+        });
+        Self {
+            original,
+            name,
+            data_type,
+            default,
+            nullable: column.nullable,
+            dropped: false,
+        }
+    }
+
+    fn column(&self, cx: &App) -> ColumnDefinition {
+        let default = self.default.read(cx).value().trim().to_string();
+        ColumnDefinition {
+            name: self.name.read(cx).value().trim().to_string(),
+            data_type: self.data_type.read(cx).value().trim().to_string(),
+            nullable: self.nullable,
+            default: (!default.is_empty()).then_some(default),
+            comment: self.original.as_ref().and_then(|c| c.comment.clone()),
+            collation: self.original.as_ref().and_then(|c| c.collation.clone()),
+            attributes: self.original.as_ref().and_then(|c| c.attributes.clone()),
+        }
+    }
+
+    fn is_unchanged(&self, cx: &App) -> bool {
+        !self.dropped
+            && self
+                .original
+                .as_ref()
+                .is_some_and(|original| *original == self.column(cx))
+    }
+
+    fn change(&self, cx: &App) -> Option<ColumnChange> {
+        match &self.original {
+            Some(original) if self.dropped => Some(ColumnChange::Drop(original.name.clone())),
+            Some(original) => Some(ColumnChange::Alter {
+                original: original.clone(),
+                column: self.column(cx),
+            }),
+            None => Some(ColumnChange::Add(self.column(cx))),
+        }
+    }
+}
+
+/// Dialog body for editing a table's columns: add a column, change a
+/// column's name, type, nullability or default, or drop it. On Postgres
+/// the table's storage parameters can be changed too. The `ALTER TABLE`
+/// statements are shown for review before they run. The table's triggers
+/// are listed below the columns.
+pub struct StructureEditor {
+    db_manager: DatabaseManager,
+    table: TableInfo,
+    driver: Option<DatabaseDriver>,
+    rows: Vec<ColumnRow>,
+    /// The tablespace and storage parameters as loaded; Postgres only.
+    storage: Option<TableStorage>,
+    storage_input: Entity<InputState>,
+    triggers: Entity<TriggersPanel>,
+    loading: bool,
+    running: bool,
+    error: Option<String>,
+}
+
+impl StructureEditor {
+    pub fn view(table: TableInfo, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(table, window, cx))
+    }
+
+    fn new(table: TableInfo, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+        let storage_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("e.g. fillfactor=70, autovacuum_enabled=false")
+        });
+        let triggers = TriggersPanel::view(table.clone(), window, cx);
+
+        let mut this = Self {
+            db_manager,
+            table,
+            driver: None,
+            rows: vec![],
+            storage: None,
+            storage_input,
+            triggers,
+            loading: false,
+            running: false,
+            error: None,
+        };
+        this.load(window, cx);
+        this
+    }
+
+    fn load(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+        let table = self.table.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager
+                .get_column_details(&table.table_schema, &table.table_name)
+                .await;
+            let storage = db_manager
+                .get_table_storage(&table.table_schema, &table.table_name)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to load storage parameters: {}", e);
+                    None
+                });
+            let driver = db_manager.driver().await;
+            this.update_in(cx, |this, window, cx| {
+                this.loading = false;
+                this.driver = driver;
+                let parameters = storage
+                    .as_ref()
+                    .map(|storage| storage.parameters_text())
+                    .unwrap_or_default();
+                this.storage_input.update(cx, |input, cx| {
+                    input.set_value(parameters, window, cx);
+                });
+                this.storage = storage;
+                match (result, driver) {
+                    (Ok(columns), Some(driver)) => {
+                        this.rows = columns
+                            .iter()
+                            .map(|detail| {
+                                let column = ColumnDefinition::from_detail(&driver, detail);
+                                ColumnRow::new(Some(column.clone()), column, window, cx)
+                            })
+                            .collect();
+                        this.error = None;
+                    }
+                    (Ok(_), None) => this.error = Some("Database not connected".to_string()),
+                    (Err(e), _) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn add_column(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let column = ColumnDefinition {
+            name: String::new(),
+            data_type: String::new(),
+            nullable: true,
+            default: None,
+            comment: None,
+            collation: None,
+            attributes: None,
+        };
+        self.rows.push(ColumnRow::new(None, column, window, cx));
+        cx.notify();
+    }
+
+    /// Drop an existing column (or keep it again), or remove a new one.
+    fn toggle_drop(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(row) = self.rows.get_mut(ix) else {
+            return;
+        };
+        if row.original.is_some() {
+            row.dropped = !row.dropped;
+        } else {
+            self.rows.remove(ix);
+        }
+        cx.notify();
+    }
+
+    /// Show the generated statements, and run them once confirmed.
+    fn review(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(driver) = self.driver else {
+            return;
+        };
+        let storage = match self.storage_statements(cx) {
+            Ok(storage) => storage,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                cx.notify();
+                return;
+            }
+        };
+        let changes: Vec<ColumnChange> = self.rows.iter().filter_map(|r| r.change(cx)).collect();
+        let statements = match alter_table_sql(
+            &driver,
+            &self.table.table_schema,
+            &self.table.table_name,
+            &changes,
+        ) {
+            Ok(mut statements) => {
+                statements.extend(storage);
+                statements
+            }
+            // Only the storage parameters changed.
+            Err(_) if !storage.is_empty() && self.rows.iter().all(|r| r.is_unchanged(cx)) => {
+                storage
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                cx.notify();
+                return;
+            }
+        };
+        self.error = None;
+
+        let editor = cx.entity().downgrade();
+        let count = statements.len();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let editor = editor.clone();
+            let statements = statements.clone();
+            dialog
+                .title("Review Changes")
+                .width(px(640.))
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Run"))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(
+                            Label::new(format!(
+                                "The following {} will be executed, stopping at the first \
+                                 that fails:",
+                                if count == 1 {
+                                    "statement".to_string()
+                                } else {
+                                    format!("{} statements", count)
+                                }
+                            ))
+                            .text_sm(),
+                        )
+                        .child(
+                            div()
+                                .id("structure-statements")
+                                .p_2()
+                                .max_h(px(320.))
+                                .overflow_y_scroll()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(statements.join("\n")),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let statements = statements.clone();
+                    let _ = editor.update(cx, |this, cx| this.apply(statements, window, cx));
+                    true
+                })
+        });
+    }
+
+    /// `SET`/`RESET` statements for edited storage parameters.
+    fn storage_statements(&self, cx: &App) -> anyhow::Result<Vec<String>> {
+        let Some(storage) = &self.storage else {
+            return Ok(vec![]);
+        };
+        let edited = parse_storage_parameters(&self.storage_input.read(cx).value())?;
+        Ok(storage_parameters_sql(
+            &self.table.table_schema,
+            &self.table.table_name,
+            &storage.parameters,
+            &edited,
+        ))
+    }
+
+    fn apply(&mut self, statements: Vec<String>, window: &mut Window, cx: &mut Context<Self>) {
+        if self.running {
+            return;
+        }
+        self.running = true;
+        cx.notify();
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let total = statements.len();
+            let mut applied = vec![];
+            let mut failure = None;
+            for (ix, sql) in statements.into_iter().enumerate() {
+                match db_manager.execute_query_enhanced(&sql).await {
+                    QueryExecutionResult::Error(e) => {
+                        failure = Some(format!("Statement {} of {}: {}", ix + 1, total, e.message));
+                        break;
+                    }
+                    _ => applied.push(sql),
+                }
+            }
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.running = false;
+                ChangeRecorderState::record(
+                    window_id(window),
+                    applied.iter().map(String::as_str),
+                    cx,
+                );
+                match failure {
+                    Some(error) => this.error = Some(error),
+                    None => {
+                        let message: SharedString = format!(
+                            "Applied {} statement{} to {}",
+                            applied.len(),
+                            if applied.len() == 1 { "" } else { "s" },
+                            this.table.table_name
+                        )
+                        .into();
+                        window.push_toast((NotificationType::Success, message), cx);
+                    }
+                }
+                this.load(window, cx);
+            });
+        })
+        .detach();
+    }
+
+    fn render_row(&self, ix: usize, row: &ColumnRow, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_new = row.original.is_none();
+        h_flex()
+            .id(("structure-column", ix))
+            .gap_2()
+            .py_1()
+            .items_center()
+            .when(row.dropped, |d| d.opacity(0.5))
+            .child(div().w(px(160.)).child(Input::new(&row.name).small()))
+            .child(div().w(px(160.)).child(Input::new(&row.data_type).small()))
+            .child(
+                div().w(px(70.)).child(
+                    Checkbox::new(("structure-nullable", ix))
+                        .checked(row.nullable)
+                        .disabled(row.dropped)
+                        .on_click(cx.listener(move |this, checked: &bool, _win, cx| {
+                            if let Some(row) = this.rows.get_mut(ix) {
+                                row.nullable = *checked;
+                            }
+                            cx.notify();
+                        })),
+                ),
+            )
+            .child(div().flex_1().child(Input::new(&row.default).small()))
+            .child(
+                Button::new(("structure-drop", ix))
+                    .icon(Icon::empty().path("icons/trash.svg"))
+                    .small()
+                    .ghost()
+                    .selected(row.dropped)
+                    .tooltip(if is_new {
+                        "Remove"
+                    } else if row.dropped {
+                        "Keep Column"
+                    } else {
+                        "Drop Column"
+                    })
+                    .on_click(cx.listener(move |this, _, _window, cx| this.toggle_drop(ix, cx))),
+            )
+            .when(is_new, |d| {
+                d.child(Label::new("new").text_xs().text_color(cx.theme().success))
+            })
+    }
+}
+
+impl Render for StructureEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(ix, row)| self.render_row(ix, row, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let summary = if self.loading {
+            "Loading columns...".to_string()
+        } else {
+            format!(
+                "{}.{} · {} columns",
+                self.table.table_schema,
+                self.table.table_name,
+                self.rows.iter().filter(|r| r.original.is_some()).count()
+            )
+        };
+        let muted = cx.theme().muted_foreground;
+        let heading = |text: &'static str, width: Option<f32>| {
+            let label = Label::new(text).text_xs().text_color(muted);
+            match width {
+                Some(width) => div().w(px(width)).child(label),
+                None => div().flex_1().child(label),
+            }
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        Button::new("reload-structure")
+                            .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip("Discard Edits and Reload")
+                            .disabled(self.running)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.load(window, cx);
+                                this.triggers.update(cx, |triggers, cx| triggers.load(cx));
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(heading("Name", Some(160.)))
+                    .child(heading("Type", Some(160.)))
+                    .child(heading("Nullable", Some(70.)))
+                    .child(heading("Default", None))
+                    .child(div().w(px(64.))),
+            )
+            .child(
+                div()
+                    .id("structure-columns")
+                    .max_h(px(400.))
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
+            .when_some(self.storage.as_ref(), |d, storage| {
+                d.child(
+                    v_flex()
+                        .gap_1()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Label::new("Storage parameters").text_xs().text_color(muted))
+                                .child(
+                                    Label::new(format!(
+                                        "tablespace: {}",
+                                        storage.tablespace.as_deref().unwrap_or("database default")
+                                    ))
+                                    .text_xs()
+                                    .text_color(muted),
+                                ),
+                        )
+                        .child(Input::new(&self.storage_input).small()),
+                )
+            })
+            .child(self.triggers.clone())
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_between()
+                    .child(
+                        Button::new("structure-add-column")
+                            .icon(Icon::empty().path("icons/plus.svg"))
+                            .label("Add Column")
+                            .small()
+                            .ghost()
+                            .disabled(self.loading || self.running)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.add_column(window, cx)),
+                            ),
+                    )
+                    .child(
+                        Button::new("structure-review")
+                            .label(if self.running {
+                                "Running..."
+                            } else {
+                                "Review Changes…"
+                            })
+                            .small()
+                            .primary()
+                            .disabled(self.loading || self.running || self.driver.is_none())
+                            .on_click(cx.listener(|this, _, window, cx| this.review(window, cx))),
+                    ),
+            )
+    }
+}
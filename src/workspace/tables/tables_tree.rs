@@ -1,25 +1,93 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
 use gpui::{
-    App, AppContext, ClickEvent, Context, Entity, EventEmitter, InteractiveElement, ParentElement,
-    Render, Styled, Subscription, Window, actions, div, px,
+    App, AppContext, ClickEvent, Context, Entity, EventEmitter, Focusable as _, InteractiveElement,
+    IntoElement, KeyDownEvent, ParentElement, Render, ScrollStrategy, SharedString, Styled,
+    Subscription, Window, WindowId, actions, div, prelude::FluentBuilder as _, px,
 };
 
 use gpui_component::{
-    ActiveTheme as _, Disableable, Icon, IconName, Sizable as _, StyledExt as _,
+    ActiveTheme as _, Disableable, Icon, IconName, Selectable as _, Sizable as _, StyledExt as _,
     button::{Button, ButtonVariants as _},
     h_flex,
+    input::{Input, InputEvent, InputState},
     label::Label,
     list::ListItem,
+    menu::{ContextMenuExt as _, PopupMenuItem},
     tree::{TreeEntry, TreeItem, TreeState, tree},
     v_flex,
 };
 
 use crate::{
-    services::{ConnectionInfo, DatabaseManager, TableInfo},
-    state::ConnectionState,
+    services::{
+        ConnectionInfo, DatabaseDriver, DatabaseManager, DatabaseSchema, ObjectHit, RoutineInfo,
+        ScriptTemplate, TableInfo, UserTypeInfo, UserTypeKind,
+    },
+    state::{EditorState, PerWindow, WindowConnection, WindowScoped, window_id},
+    workspace::database_admin::{open_create_schema_dialog, open_drop_schema_dialog},
 };
 
+/// Separates a table's tree id from a matched column name, e.g.
+/// `public.orders-BASE TABLE::tenant_id`.
+const COLUMN_ID_SEPARATOR: &str = "::";
+
+/// Prefix of function/procedure tree ids, followed by the signature.
+const ROUTINE_ID_PREFIX: &str = "routine:";
+
+/// Pause after which typing starts a new typeahead search.
+const TYPEAHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Tables fetched per round trip while loading the tree.
+const TABLE_PAGE_SIZE: usize = 500;
+
+/// Prefix of enum, composite type and domain tree ids, followed by
+/// `schema.name`.
+const TYPE_ID_PREFIX: &str = "type:";
+
+fn routine_item_id(routine: &RoutineInfo) -> String {
+    format!("{}{}", ROUTINE_ID_PREFIX, routine.signature())
+}
+
+fn type_item_id(user_type: &UserTypeInfo) -> String {
+    format!(
+        "{}{}.{}",
+        TYPE_ID_PREFIX, user_type.type_schema, user_type.type_name
+    )
+}
+
 pub enum TableEvent {
     TableSelected(TableInfo),
+    /// Compare the table's rows against another saved connection.
+    CompareData(TableInfo),
+    /// Open the generated "New row" form for the table.
+    NewRow(TableInfo),
+    /// Open the sequence / identity column manager.
+    ManageSequences,
+    /// Set up a foreign server to query another saved connection.
+    CrossDatabase,
+    /// List, install, update and drop Postgres extensions.
+    ManageExtensions,
+    /// Add, alter or drop the table's columns, and enable or disable its
+    /// triggers.
+    EditStructure(TableInfo),
+    /// Add a foreign key, check or unique constraint to the table.
+    AddConstraint(TableInfo),
+    /// Generate COMMENT or GRANT statements for several tables.
+    BatchScript(Vec<TableInfo>),
+    /// Show the `CREATE` statement of a view or materialized view.
+    ShowViewSource(TableInfo),
+    /// Show a table's foreign server and inheritance details.
+    ShowTableDetails(TableInfo),
+    RefreshMaterializedView(TableInfo),
+    /// Show a function's or procedure's arguments and source.
+    RoutineSelected(RoutineInfo),
+    /// Find any object of the database by name.
+    SearchDatabase,
+    /// Load a statement template for the table into the editor.
+    GenerateScript(TableInfo, ScriptTemplate),
+    /// Show an enum's labels or a composite type's or domain's definition.
+    UserTypeSelected(UserTypeInfo),
 }
 
 impl EventEmitter<TableEvent> for TablesTree {}
@@ -27,51 +95,220 @@ impl EventEmitter<TableEvent> for TablesTree {}
 actions!(tables_tree, [SelectItem]);
 
 pub struct TablesTree {
+    window_id: WindowId,
     tree_state: Entity<TreeState>,
     selected_item: Option<TreeItem>,
+    selected_table: Option<TableInfo>,
+    /// Tables picked with Ctrl/Cmd-click for batch actions.
+    marked_tables: Vec<TableInfo>,
     db_manager: Option<DatabaseManager>,
     active_connection: Option<ConnectionInfo>,
+    /// All tables of the connection, before filtering.
+    tables: Vec<TableInfo>,
+    routines: Vec<RoutineInfo>,
+    user_types: Vec<UserTypeInfo>,
+    /// Owning extension per `(schema, name)` of extension-provided tables,
+    /// views and functions.
+    extension_objects: HashMap<(String, String), String>,
+    filter_input: Entity<InputState>,
+    /// Also match the filter against column names.
+    match_columns: bool,
+    /// The session's search path; its schemas are listed first.
+    search_path: Vec<String>,
+    /// The database's schemas; when one is created or dropped the tree
+    /// reloads.
+    schemas: Vec<String>,
+    /// Tables loaded so far while pages are still arriving.
+    loading_tables: Option<usize>,
+    /// Bumped on every (re)load so a superseded load stops early.
+    load_generation: usize,
+    /// Items last shown in the tree, for typeahead.
+    tree_items: Vec<TreeItem>,
+    /// Letters typed into the tree, and when the last one was.
+    typeahead: String,
+    typeahead_at: Option<Instant>,
     _subscriptions: Vec<Subscription>,
 }
 
-fn build_tree_items(tables: Vec<TableInfo>) -> Vec<TreeItem> {
-    use std::collections::HashMap;
+/// Column names per `(schema, table)`, from the schema loaded on connect.
+fn column_index(schema: Option<&DatabaseSchema>) -> HashMap<(String, String), Vec<String>> {
+    schema
+        .map(|schema| {
+            schema
+                .tables
+                .iter()
+                .map(|t| {
+                    (
+                        (t.table_schema.clone(), t.table_name.clone()),
+                        t.columns.iter().map(|c| c.column_name.clone()).collect(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Tables matching `query` (case-insensitive substring), each with the
+/// column names that matched. Columns are only searched when `columns` is
+/// non-empty, so a table can match through its columns alone.
+fn filter_tables(
+    tables: &[TableInfo],
+    columns: &HashMap<(String, String), Vec<String>>,
+    query: &str,
+) -> Vec<(TableInfo, Vec<String>)> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return tables.iter().map(|t| (t.clone(), vec![])).collect();
+    }
+
+    tables
+        .iter()
+        .filter_map(|table| {
+            let matched_columns: Vec<String> = columns
+                .get(&(table.table_schema.clone(), table.table_name.clone()))
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter(|name| name.to_lowercase().contains(&query))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default();
 
+            if table.table_name.to_lowercase().contains(&query) || !matched_columns.is_empty() {
+                Some((table.clone(), matched_columns))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parse a table item id, `"{schema}.{table_name}-{table_type}"`. A
+/// matched column resolves to its table.
+fn table_from_item_id(id: &str) -> Option<TableInfo> {
+    if id.starts_with(ROUTINE_ID_PREFIX) || id.starts_with(TYPE_ID_PREFIX) {
+        return None;
+    }
+    let table_id = id
+        .split_once(COLUMN_ID_SEPARATOR)
+        .map_or(id, |(table_id, _)| table_id);
+    let (schema_and_table, table_type) = table_id.rsplit_once('-')?;
+    let (table_schema, table_name) = schema_and_table.split_once('.')?;
+    Some(TableInfo {
+        table_schema: table_schema.to_string(),
+        table_name: table_name.to_string(),
+        table_type: table_type.to_string(),
+    })
+}
+
+/// Schemas on `search_path` come first, in path order, followed by the
+/// rest alphabetically and collapsed unless `expand_all`.
+fn build_tree_items(
+    tables: Vec<(TableInfo, Vec<String>)>,
+    routines: Vec<RoutineInfo>,
+    user_types: Vec<UserTypeInfo>,
+    search_path: &[String],
+    expand_all: bool,
+) -> Vec<TreeItem> {
     // Group tables by schema
-    let mut schema_map: HashMap<String, Vec<TableInfo>> = HashMap::new();
-    for table in tables {
+    let mut schema_map: HashMap<String, Vec<(TableInfo, Vec<String>)>> = HashMap::new();
+    for (table, columns) in tables {
         schema_map
             .entry(table.table_schema.clone())
             .or_insert_with(Vec::new)
-            .push(table);
+            .push((table, columns));
+    }
+
+    let mut routine_map: HashMap<String, Vec<RoutineInfo>> = HashMap::new();
+    for routine in routines {
+        schema_map
+            .entry(routine.routine_schema.clone())
+            .or_default();
+        routine_map
+            .entry(routine.routine_schema.clone())
+            .or_default()
+            .push(routine);
+    }
+
+    let mut type_map: HashMap<String, Vec<UserTypeInfo>> = HashMap::new();
+    for user_type in user_types {
+        schema_map.entry(user_type.type_schema.clone()).or_default();
+        type_map
+            .entry(user_type.type_schema.clone())
+            .or_default()
+            .push(user_type);
     }
 
     // Convert to sorted vec of (schema, tables)
-    let mut schemas: Vec<(String, Vec<TableInfo>)> = schema_map.into_iter().collect();
-    schemas.sort_by(|a, b| a.0.cmp(&b.0));
+    let path_position = |schema: &str| {
+        search_path
+            .iter()
+            .position(|s| s == schema)
+            .unwrap_or(search_path.len())
+    };
+    let mut schemas: Vec<(String, Vec<(TableInfo, Vec<String>)>)> =
+        schema_map.into_iter().collect();
+    schemas.sort_by(|a, b| {
+        path_position(&a.0)
+            .cmp(&path_position(&b.0))
+            .then_with(|| a.0.cmp(&b.0))
+    });
 
-    // Build tree items with schema -> tables hierarchy
+    // Build tree items with schema -> tables, then routines, then types
     schemas
         .into_iter()
         .map(|(schema, mut tables)| {
             // Sort tables within each schema
-            tables.sort_by(|a, b| a.table_name.cmp(&b.table_name));
+            tables.sort_by(|a, b| a.0.table_name.cmp(&b.0.table_name));
 
-            // Create table items
+            // Create table items, with matched columns (if any) underneath
             let table_items: Vec<TreeItem> = tables
                 .into_iter()
-                .map(|t| {
-                    TreeItem::new(
-                        format!("{}.{}-{}", schema, t.table_name, t.table_type), // id
-                        t.table_name,                                            // label
-                    )
+                .map(|(t, columns)| {
+                    let id = format!("{}.{}-{}", schema, t.table_name, t.table_type);
+                    let column_items: Vec<TreeItem> = columns
+                        .into_iter()
+                        .map(|column| {
+                            TreeItem::new(
+                                format!("{}{}{}", id, COLUMN_ID_SEPARATOR, column),
+                                column,
+                            )
+                        })
+                        .collect();
+                    let item = TreeItem::new(id, t.table_name);
+                    if column_items.is_empty() {
+                        item
+                    } else {
+                        item.expanded(true).children(column_items)
+                    }
                 })
                 .collect();
 
+            // Functions and procedures follow the tables, already sorted
+            let routine_items = routine_map
+                .remove(&schema)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| TreeItem::new(routine_item_id(&r), r.routine_name));
+
+            // Enums, composite types and domains come last, already sorted
+            let type_items = type_map
+                .remove(&schema)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|t| TreeItem::new(type_item_id(&t), t.type_name));
+
             // Create schema item with tables as children
+            let expanded = expand_all || search_path.is_empty() || search_path.contains(&schema);
             TreeItem::new(format!("{}-schema", schema.clone()), schema)
-                .expanded(true)
-                .children(table_items)
+                .expanded(expanded)
+                .children(
+                    table_items
+                        .into_iter()
+                        .chain(routine_items)
+                        .chain(type_items),
+                )
         })
         .collect()
 }
@@ -90,26 +327,78 @@ impl TablesTree {
             return;
         };
 
+        self.load_generation += 1;
+        let generation = self.load_generation;
+        self.tables.clear();
+        self.loading_tables = Some(0);
+        cx.notify();
+
+        // Tables arrive a page at a time so huge catalogs fill the tree
+        // progressively instead of stalling until the full list is in.
         cx.spawn(async move |this, cx| {
-            let result = db_manager.get_tables().await;
+            let mut after: Option<TableInfo> = None;
+            loop {
+                let result = db_manager
+                    .get_tables_page(after.as_ref(), TABLE_PAGE_SIZE)
+                    .await;
+
+                let more = this
+                    .update(cx, |this, cx| {
+                        if this.load_generation != generation {
+                            return false;
+                        }
+                        let more = match result {
+                            Ok(page) => {
+                                let more = page.len() == TABLE_PAGE_SIZE;
+                                after = page.last().cloned();
+                                this.tables.extend(page);
+                                more
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to load tables: {}", e);
+                                false
+                            }
+                        };
+                        this.loading_tables = more.then_some(this.tables.len());
+                        this.apply_filter(cx);
+                        cx.notify();
+                        more
+                    })
+                    .unwrap_or(false);
+                if !more {
+                    break;
+                }
+            }
+
+            let routines = db_manager.get_routines().await.unwrap_or_else(|e| {
+                tracing::error!("Failed to load routines: {}", e);
+                vec![]
+            });
+
+            let user_types = db_manager.get_user_types().await.unwrap_or_else(|e| {
+                tracing::error!("Failed to load user-defined types: {}", e);
+                vec![]
+            });
+
+            let extension_objects = db_manager
+                .list_extension_objects()
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("Failed to load extension objects: {}", e);
+                    vec![]
+                });
 
             this.update(cx, |this, cx| {
-                match result {
-                    Ok(tables) => {
-                        let items = build_tree_items(tables);
-                        this.tree_state.update(cx, |state, cx| {
-                            state.set_items(items, cx);
-                            cx.notify();
-                        });
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to load tables: {}", e);
-                        this.tree_state.update(cx, |state, cx| {
-                            state.set_items(vec![], cx);
-                            cx.notify();
-                        });
-                    }
+                if this.load_generation != generation {
+                    return;
                 }
+                this.routines = routines;
+                this.user_types = user_types;
+                this.extension_objects = extension_objects
+                    .into_iter()
+                    .map(|o| ((o.object_schema, o.object_name), o.extension))
+                    .collect();
+                this.apply_filter(cx);
                 cx.notify();
             })
             .ok();
@@ -118,42 +407,268 @@ impl TablesTree {
     }
 
     fn clear_tables(&mut self, cx: &mut Context<Self>) {
+        self.load_generation += 1;
+        self.loading_tables = None;
+        self.selected_table = None;
+        self.marked_tables.clear();
+        self.tables.clear();
+        self.routines.clear();
+        self.user_types.clear();
+        self.extension_objects.clear();
+        self.tree_items.clear();
         self.tree_state.update(cx, |state, cx| {
             state.set_items(vec![], cx);
             cx.notify();
         });
     }
 
+    /// Rebuild the tree from the loaded tables and the current filter.
+    fn apply_filter(&mut self, cx: &mut Context<Self>) {
+        let query = self.filter_input.read(cx).value().trim().to_string();
+        let columns = if self.match_columns {
+            column_index(EditorState::of(self.window_id, cx).schema.as_ref())
+        } else {
+            HashMap::new()
+        };
+
+        let lowered = query.to_lowercase();
+        let routines = self
+            .routines
+            .iter()
+            .filter(|r| r.routine_name.to_lowercase().contains(&lowered))
+            .cloned()
+            .collect();
+        let user_types = self
+            .user_types
+            .iter()
+            .filter(|t| t.type_name.to_lowercase().contains(&lowered))
+            .cloned()
+            .collect();
+
+        // A filter shows its matches in every schema.
+        let items = build_tree_items(
+            filter_tables(&self.tables, &columns, &query),
+            routines,
+            user_types,
+            &self.search_path,
+            !query.is_empty(),
+        );
+        self.tree_items = items.clone();
+        self.tree_state.update(cx, |state, cx| {
+            state.set_items(items, cx);
+            cx.notify();
+        });
+    }
+
+    fn toggle_match_columns(
+        &mut self,
+        _: &ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.match_columns = !self.match_columns;
+        self.apply_filter(cx);
+        cx.notify();
+    }
+
     pub fn refresh_tables(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
         self.load_tables(cx);
     }
 
+    fn new_row_for_selected(
+        &mut self,
+        _: &ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(table) = self.selected_table.clone() {
+            cx.emit(TableEvent::NewRow(table));
+        }
+    }
+
+    fn manage_sequences(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(TableEvent::ManageSequences);
+    }
+
+    fn cross_database(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(TableEvent::CrossDatabase);
+    }
+
+    fn manage_extensions(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(TableEvent::ManageExtensions);
+    }
+
+    fn edit_structure(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(table) = self.selected_table.clone() {
+            cx.emit(TableEvent::EditStructure(table));
+        }
+    }
+
+    fn add_constraint(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(table) = self.selected_table.clone() {
+            cx.emit(TableEvent::AddConstraint(table));
+        }
+    }
+
+    /// Tables for batch actions: the marked ones, or else the selection.
+    fn batch_tables(&self) -> Vec<TableInfo> {
+        if self.marked_tables.is_empty() {
+            self.selected_table.clone().into_iter().collect()
+        } else {
+            self.marked_tables.clone()
+        }
+    }
+
+    fn batch_script(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        let tables = self.batch_tables();
+        if !tables.is_empty() {
+            cx.emit(TableEvent::BatchScript(tables));
+        }
+    }
+
+    fn toggle_marked(&mut self, item_id: &str, cx: &mut Context<Self>) {
+        // Schema folders cannot be marked.
+        if item_id.ends_with("-schema") {
+            return;
+        }
+        let Some(table) = table_from_item_id(item_id) else {
+            return;
+        };
+        if let Some(pos) = self.marked_tables.iter().position(|t| *t == table) {
+            self.marked_tables.remove(pos);
+        } else {
+            self.marked_tables.push(table);
+        }
+        cx.notify();
+    }
+
+    fn show_view_source(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(table) = self.selected_table.clone() {
+            cx.emit(TableEvent::ShowViewSource(table));
+        }
+    }
+
+    fn show_table_details(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(table) = self.selected_table.clone() {
+            cx.emit(TableEvent::ShowTableDetails(table));
+        }
+    }
+
+    fn refresh_materialized_view(
+        &mut self,
+        _: &ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(table) = self.selected_table.clone() {
+            cx.emit(TableEvent::RefreshMaterializedView(table));
+        }
+    }
+
+    fn search_database(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(TableEvent::SearchDatabase);
+    }
+
+    /// Bring a search result into view by filtering the tree to its name
+    /// (matching columns for a column), then open it.
+    pub fn reveal(&mut self, hit: &ObjectHit, window: &mut Window, cx: &mut Context<Self>) {
+        let filter = match hit {
+            ObjectHit::Table(table) => table.table_name.clone(),
+            ObjectHit::Column { column, .. } => {
+                self.match_columns = true;
+                column.clone()
+            }
+            ObjectHit::Routine(routine) => routine.routine_name.clone(),
+        };
+        self.filter_input
+            .update(cx, |input, cx| input.set_value(filter, window, cx));
+        self.apply_filter(cx);
+
+        match hit {
+            ObjectHit::Table(table) | ObjectHit::Column { table, .. } => {
+                self.selected_table = Some(table.clone());
+                cx.emit(TableEvent::TableSelected(table.clone()));
+            }
+            ObjectHit::Routine(routine) => {
+                self.selected_table = None;
+                cx.emit(TableEvent::RoutineSelected(routine.clone()));
+            }
+        }
+        cx.notify();
+    }
+
+    fn compare_selected(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(table) = self.selected_table.clone() {
+            cx.emit(TableEvent::CompareData(table));
+        }
+    }
+
     fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let tree_state = cx.new(|cx| TreeState::new(cx));
+        let filter_input = cx.new(|cx| InputState::new(window, cx).placeholder("Filter tables..."));
 
-        let _subscriptions =
-            vec![
-                cx.observe_global_in::<ConnectionState>(window, move |this, _win, cx| {
-                    let state = cx.global::<ConnectionState>();
-                    let active_connection = state.active_connection.clone();
+        let _subscriptions = vec![
+            cx.observe_global_in::<PerWindow<WindowConnection>>(window, move |this, _win, cx| {
+                let state = WindowConnection::of(this.window_id, cx);
+                let active_connection = state.active_connection.clone();
 
-                    this.db_manager = Some(state.db_manager.clone());
-                    this.active_connection = active_connection.clone();
-                    if active_connection.is_some() {
+                this.db_manager = Some(state.db_manager.clone());
+                this.active_connection = active_connection.clone();
+                if active_connection.is_some() {
+                    this.load_tables(cx);
+                } else {
+                    this.clear_tables(cx);
+                }
+
+                cx.notify();
+            }),
+            cx.subscribe(&filter_input, |this, _, event: &InputEvent, cx| {
+                if let InputEvent::Change = event {
+                    this.apply_filter(cx);
+                }
+            }),
+            // Column matches come from the schema loaded after connecting.
+            cx.observe_global::<PerWindow<EditorState>>(|this, cx| {
+                let search_path = &EditorState::of(this.window_id, cx).search_path;
+                let path_changed = this.search_path != *search_path;
+                if path_changed {
+                    this.search_path = search_path.clone();
+                }
+                let schemas = &EditorState::of(this.window_id, cx).schemas;
+                if this.schemas != *schemas {
+                    this.schemas = schemas.clone();
+                    if this.active_connection.is_some() {
                         this.load_tables(cx);
-                    } else {
-                        this.clear_tables(cx);
+                        return;
                     }
-
-                    cx.notify();
-                }),
-            ];
+                }
+                if this.match_columns || path_changed {
+                    this.apply_filter(cx);
+                }
+            }),
+        ];
 
         Self {
+            window_id: window_id(window),
             tree_state,
             selected_item: None,
+            selected_table: None,
             db_manager: None,
             active_connection: None,
+            marked_tables: vec![],
+            tables: vec![],
+            routines: vec![],
+            user_types: vec![],
+            extension_objects: HashMap::new(),
+            filter_input,
+            match_columns: false,
+            search_path: vec![],
+            schemas: vec![],
+            loading_tables: None,
+            load_generation: 0,
+            tree_items: vec![],
+            typeahead: String::new(),
+            typeahead_at: None,
             _subscriptions,
         }
     }
@@ -167,28 +682,78 @@ impl TablesTree {
         if let Some(entry) = self.tree_state.read(cx).selected_entry() {
             self.selected_item = Some(entry.item().clone());
             let item = entry.item();
-            // Parse the id format: "{schema}.{table_name}-{table_type}"
-            let parts: Vec<&str> = item.id.rsplitn(2, '-').collect();
-            if parts.len() == 2 {
-                let table_type = parts[0].to_string();
-                let schema_and_table: Vec<&str> = parts[1].splitn(2, '.').collect();
-
-                if schema_and_table.len() == 2 {
-                    let table_schema = schema_and_table[0].to_string();
-                    let table_name = schema_and_table[1].to_string();
-
-                    let table_info = TableInfo {
-                        table_schema,
-                        table_name,
-                        table_type,
-                    };
-                    cx.emit(TableEvent::TableSelected(table_info));
-                }
+            if let Some(table_info) = table_from_item_id(&item.id) {
+                self.selected_table = Some(table_info.clone());
+                cx.emit(TableEvent::TableSelected(table_info));
+            } else if let Some(routine) = self
+                .routines
+                .iter()
+                .find(|r| routine_item_id(r) == &*item.id)
+            {
+                self.selected_table = None;
+                cx.emit(TableEvent::RoutineSelected(routine.clone()));
+            } else if let Some(user_type) = self
+                .user_types
+                .iter()
+                .find(|t| type_item_id(t) == &*item.id)
+            {
+                self.selected_table = None;
+                cx.emit(TableEvent::UserTypeSelected(user_type.clone()));
             }
             cx.notify();
         }
     }
 
+    /// Typing a name while the tree has focus selects the next row whose
+    /// label starts with what was typed; Enter then opens it.
+    fn on_tree_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let modifiers = &event.keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform {
+            return;
+        }
+        let Some(ch) = event
+            .keystroke
+            .key_char
+            .as_deref()
+            .and_then(|key| key.chars().next())
+            .filter(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '.'))
+        else {
+            return;
+        };
+
+        let now = Instant::now();
+        if self
+            .typeahead_at
+            .is_none_or(|at| now.duration_since(at) > TYPEAHEAD_TIMEOUT)
+        {
+            self.typeahead.clear();
+        }
+        self.typeahead_at = Some(now);
+        self.typeahead.extend(ch.to_lowercase());
+
+        let mut labels = vec![];
+        visible_labels(&self.tree_items, &mut labels);
+        let current = self.tree_state.read(cx).selected_index();
+        // A single letter moves on to the next match, so repeating it
+        // cycles through the names starting with it.
+        let start = match current {
+            Some(ix) if self.typeahead.chars().count() == 1 => ix + 1,
+            Some(ix) => ix,
+            None => 0,
+        };
+        let found = (0..labels.len())
+            .map(|offset| (start + offset) % labels.len())
+            .find(|&ix| labels[ix].to_lowercase().starts_with(&self.typeahead));
+        if let Some(ix) = found {
+            self.tree_state.update(cx, |state, cx| {
+                state.set_selected_index(Some(ix), cx);
+                state.scroll_to_item(ix, ScrollStrategy::Center);
+                cx.notify();
+            });
+            cx.stop_propagation();
+        }
+    }
+
     fn render_tree_item(
         &self,
         ix: usize,
@@ -197,18 +762,69 @@ impl TablesTree {
         cx: &mut Context<Self>,
     ) -> ListItem {
         let item = entry.item();
-        let is_selected = selected;
+        let is_marked = !entry.is_folder()
+            && table_from_item_id(&item.id).is_some_and(|t| self.marked_tables.contains(&t));
+        let is_selected = selected || is_marked;
 
         let name = truncate(item.label.clone().as_str(), 23);
 
-        let table_type = if item.id.clone().ends_with("-VIEW") {
+        let is_column = item.id.contains(COLUMN_ID_SEPARATOR);
+        let routine = if item.id.starts_with(ROUTINE_ID_PREFIX) {
+            self.routines
+                .iter()
+                .find(|r| routine_item_id(r) == &*item.id)
+        } else {
+            None
+        };
+        let user_type = if item.id.starts_with(TYPE_ID_PREFIX) {
+            self.user_types
+                .iter()
+                .find(|t| type_item_id(t) == &*item.id)
+        } else {
+            None
+        };
+        let is_matview = item.id.ends_with("-MATERIALIZED VIEW");
+
+        let table_type = if is_column {
+            "COLUMN"
+        } else if let Some(routine) = routine {
+            if routine.routine_type == "PROCEDURE" {
+                "PROC"
+            } else {
+                "FUNC"
+            }
+        } else if let Some(user_type) = user_type {
+            match user_type.kind {
+                UserTypeKind::Enum => "ENUM",
+                UserTypeKind::Composite => "TYPE",
+                UserTypeKind::Domain => "DOM",
+            }
+        } else if is_matview {
+            "MATV"
+        } else if item.id.clone().ends_with("-VIEW") {
             "VIEW"
         } else if item.id.clone().ends_with("-BASE TABLE") {
             "BASE"
+        } else if item.id.ends_with("-FOREIGN") {
+            "FDW"
+        } else if item.id.ends_with("-LOCAL TEMPORARY") {
+            "TEMP"
+        } else if item.id.ends_with("-INHERITED TABLE") {
+            "INH"
         } else {
             "SCHEMA"
         };
 
+        // Objects installed by an extension are labelled with its name
+        let object_key = if is_column {
+            None
+        } else if let Some(routine) = routine {
+            Some((routine.routine_schema.clone(), routine.routine_name.clone()))
+        } else {
+            table_from_item_id(&item.id).map(|t| (t.table_schema, t.table_name))
+        };
+        let extension = object_key.and_then(|key| self.extension_objects.get(&key).cloned());
+
         // Determine colors based on selection state
         let text_color = if is_selected {
             cx.theme().accent_foreground
@@ -225,20 +841,81 @@ impl TablesTree {
         };
 
         // Icon based on item type
-        let icon = if !entry.is_folder() {
+        let icon: Icon = if is_column {
+            IconName::Minus.into()
+        } else if routine.is_some() {
+            Icon::empty().path("icons/square-terminal.svg")
+        } else if let Some(user_type) = user_type {
+            match user_type.kind {
+                UserTypeKind::Enum => Icon::empty().path("icons/list-ordered.svg"),
+                _ => Icon::empty().path("icons/asterisk.svg"),
+            }
+        } else if is_matview && !entry.is_folder() {
+            Icon::empty().path("icons/database-zap.svg")
+        } else if !entry.is_folder() {
             // check if id ends with -view
             if item.id.clone().ends_with("-VIEW") {
-                IconName::Eye
+                IconName::Eye.into()
+            } else if item.id.ends_with("-FOREIGN") {
+                Icon::empty().path("icons/globe.svg")
+            } else if item.id.ends_with("-LOCAL TEMPORARY") {
+                Icon::empty().path("icons/history.svg")
+            } else if item.id.ends_with("-INHERITED TABLE") {
+                Icon::empty().path("icons/gallery-vertical-end.svg")
             } else {
-                IconName::Frame
+                IconName::Frame.into()
             }
         } else if entry.is_expanded() {
-            IconName::ChevronDown
+            IconName::ChevronDown.into()
         } else {
-            IconName::ChevronRight
+            IconName::ChevronRight.into()
         };
 
-        let icon: Icon = icon.into();
+        // Tables, views and the like offer statement templates.
+        let script_table = if entry.is_folder() || is_column {
+            None
+        } else {
+            table_from_item_id(&item.id)
+        };
+        // Schema folders offer creating and dropping schemas on Postgres.
+        let postgres = self
+            .active_connection
+            .as_ref()
+            .is_some_and(|c| c.driver == DatabaseDriver::Postgres);
+        let schema_folder = item
+            .id
+            .strip_suffix("-schema")
+            .filter(|_| entry.is_folder() && postgres)
+            .map(str::to_string);
+        let tree = cx.entity().downgrade();
+        let content = div()
+            .h_flex()
+            .justify_between()
+            .child(
+                h_flex()
+                    .items_center()
+                    .gap_2()
+                    .text_color(text_color)
+                    .child(icon.size_4().text_color(text_color.opacity(0.7)))
+                    .child(Label::new(name).font_medium().text_sm().whitespace_nowrap()),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .when_some(extension, |d, extension| {
+                        d.child(
+                            Label::new(truncate(&extension, 12))
+                                .text_xs()
+                                .text_color(cx.theme().info),
+                        )
+                    })
+                    .child(
+                        Label::new(table_type)
+                            .text_xs()
+                            .text_color(text_color.opacity(0.6)),
+                    ),
+            );
 
         ListItem::new(ix)
             .w_full()
@@ -253,27 +930,58 @@ impl TablesTree {
                 bg_color
             })
             .rounded(cx.theme().radius)
-            .child(
-                div()
-                    .h_flex()
-                    .justify_between()
-                    .child(
-                        h_flex()
-                            .items_center()
-                            .gap_2()
-                            .text_color(text_color)
-                            .child(icon.size_4().text_color(text_color.opacity(0.7)))
-                            .child(Label::new(name).font_medium().text_sm().whitespace_nowrap()),
-                    )
-                    .child(
-                        Label::new(table_type)
-                            .text_xs()
-                            .text_color(text_color.opacity(0.6)),
-                    ),
-            )
+            .child(match (script_table, schema_folder) {
+                (Some(table), _) => content
+                    .context_menu(move |menu, window, cx| {
+                        let tree = tree.clone();
+                        let table = table.clone();
+                        menu.submenu("Scripts", window, cx, move |menu, _window, _cx| {
+                            let templates: &[ScriptTemplate] = if table.is_table() {
+                                &ScriptTemplate::ALL
+                            } else {
+                                &[ScriptTemplate::Select]
+                            };
+                            templates.iter().fold(menu, |menu, &template| {
+                                let tree = tree.clone();
+                                let table = table.clone();
+                                menu.item(PopupMenuItem::new(template.label()).on_click(
+                                    move |_, _window, cx| {
+                                        let _ = tree.update(cx, |_, cx| {
+                                            cx.emit(TableEvent::GenerateScript(
+                                                table.clone(),
+                                                template,
+                                            ));
+                                        });
+                                    },
+                                ))
+                            })
+                        })
+                    })
+                    .into_any_element(),
+                (None, Some(schema)) => content
+                    .context_menu(move |menu, _window, _cx| {
+                        let schema = schema.clone();
+                        menu.item(PopupMenuItem::new("New Schema…").on_click(|_, window, cx| {
+                            open_create_schema_dialog(window, cx);
+                        }))
+                        .item(
+                            PopupMenuItem::new(format!("Drop Schema {}…", schema)).on_click(
+                                move |_, window, cx| {
+                                    open_drop_schema_dialog(Some(schema.clone()), window, cx);
+                                },
+                            ),
+                        )
+                    })
+                    .into_any_element(),
+                (None, None) => content.into_any_element(),
+            })
             .on_click(cx.listener({
                 let item = item.clone();
-                move |this, _, window, cx| {
+                move |this, event: &ClickEvent, window, cx| {
+                    if event.modifiers().secondary() {
+                        this.toggle_marked(&item.id, cx);
+                        return;
+                    }
                     this.selected_item = Some(item.clone());
                     this.on_select_table_item(&SelectItem, window, cx);
                     cx.notify();
@@ -285,10 +993,14 @@ impl TablesTree {
 impl Render for TablesTree {
     fn render(
         &mut self,
-        _: &mut gpui::Window,
+        window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> impl gpui::IntoElement {
         let view = cx.entity();
+        let tree_focused = self
+            .tree_state
+            .focus_handle(cx)
+            .contains_focused(window, cx);
 
         let refresh_button = Button::new("refresh")
             .icon(Icon::empty().path("icons/rotate-ccw.svg"))
@@ -298,37 +1010,225 @@ impl Render for TablesTree {
             .disabled(self.active_connection.clone().is_none())
             .on_click(cx.listener(Self::refresh_tables));
 
+        let new_row_button = Button::new("new-row")
+            .icon(Icon::empty().path("icons/plus.svg"))
+            .small()
+            .ghost()
+            .tooltip("New Row")
+            .disabled(self.selected_table.as_ref().is_none_or(|t| !t.is_table()))
+            .on_click(cx.listener(Self::new_row_for_selected));
+
+        let compare_button = Button::new("compare-data")
+            .icon(Icon::empty().path("icons/git-compare.svg"))
+            .small()
+            .ghost()
+            .tooltip("Compare Data With Another Connection")
+            .disabled(self.selected_table.is_none())
+            .on_click(cx.listener(Self::compare_selected));
+
+        let sequences_button = Button::new("sequences")
+            .icon(Icon::empty().path("icons/list-ordered.svg"))
+            .small()
+            .ghost()
+            .tooltip("Sequences")
+            .disabled(self.active_connection.is_none())
+            .on_click(cx.listener(Self::manage_sequences));
+
+        let cross_database_button = Button::new("cross-database")
+            .icon(Icon::empty().path("icons/globe.svg"))
+            .small()
+            .ghost()
+            .tooltip("Query Another Database (postgres_fdw / dblink)")
+            .disabled(
+                self.active_connection
+                    .as_ref()
+                    .is_none_or(|c| c.driver != DatabaseDriver::Postgres),
+            )
+            .on_click(cx.listener(Self::cross_database));
+
+        let extensions_button = Button::new("extensions")
+            .icon(Icon::empty().path("icons/archive.svg"))
+            .small()
+            .ghost()
+            .tooltip("Extensions")
+            .disabled(
+                self.active_connection
+                    .as_ref()
+                    .is_none_or(|c| c.driver != DatabaseDriver::Postgres),
+            )
+            .on_click(cx.listener(Self::manage_extensions));
+
+        let structure_button = Button::new("edit-structure")
+            .icon(Icon::empty().path("icons/hammer.svg"))
+            .small()
+            .ghost()
+            .tooltip("Edit Structure")
+            .disabled(self.selected_table.as_ref().is_none_or(|t| !t.is_table()))
+            .on_click(cx.listener(Self::edit_structure));
+
+        let constraint_button = Button::new("add-constraint")
+            .icon(Icon::empty().path("icons/cable.svg"))
+            .small()
+            .ghost()
+            .tooltip("Add Foreign Key / Check / Unique Constraint")
+            .disabled(self.selected_table.as_ref().is_none_or(|t| !t.is_table()))
+            .on_click(cx.listener(Self::add_constraint));
+
+        let batch_count = self.batch_tables().len();
+        let batch_button = Button::new("batch-script")
+            .icon(Icon::empty().path("icons/pencil-line.svg"))
+            .small()
+            .ghost()
+            .tooltip(if self.marked_tables.is_empty() {
+                "Comment / Grant (Ctrl/Cmd-click to pick several tables)".to_string()
+            } else {
+                format!("Comment / Grant {} Tables", batch_count)
+            })
+            .disabled(batch_count == 0)
+            .on_click(cx.listener(Self::batch_script));
+
+        let selected_type = self.selected_table.as_ref().map(|t| t.table_type.as_str());
+
+        let view_source_button = Button::new("view-source")
+            .icon(Icon::empty().path("icons/file-braces.svg"))
+            .small()
+            .ghost()
+            .tooltip("Show View Source")
+            .disabled(!matches!(
+                selected_type,
+                Some("VIEW") | Some("MATERIALIZED VIEW")
+            ))
+            .on_click(cx.listener(Self::show_view_source));
+
+        let is_postgres = self
+            .active_connection
+            .as_ref()
+            .is_some_and(|c| c.driver == DatabaseDriver::Postgres);
+        let table_details_button = Button::new("table-details")
+            .icon(Icon::empty().path("icons/info.svg"))
+            .small()
+            .ghost()
+            .tooltip("Foreign Server / Inheritance Details")
+            .disabled(
+                !is_postgres
+                    || self
+                        .selected_table
+                        .as_ref()
+                        .is_none_or(|t| !t.is_table() && t.table_type != "FOREIGN"),
+            )
+            .on_click(cx.listener(Self::show_table_details));
+
+        let refresh_matview_button = Button::new("refresh-matview")
+            .icon(Icon::empty().path("icons/database-zap.svg"))
+            .small()
+            .ghost()
+            .tooltip("Refresh Materialized View")
+            .disabled(selected_type != Some("MATERIALIZED VIEW"))
+            .on_click(cx.listener(Self::refresh_materialized_view));
+
+        let search_button = Button::new("search-database")
+            .icon(Icon::empty().path("icons/search.svg"))
+            .small()
+            .ghost()
+            .tooltip("Search Database (Cmd/Ctrl+P)")
+            .disabled(self.active_connection.is_none())
+            .on_click(cx.listener(Self::search_database));
+
+        let match_columns_button = Button::new("match-columns")
+            .icon(Icon::empty().path("icons/table-properties.svg"))
+            .small()
+            .ghost()
+            .selected(self.match_columns)
+            .tooltip("Also Match Column Names")
+            .on_click(cx.listener(Self::toggle_match_columns));
+
         let header = div().child(
             div()
                 .h_flex()
                 .justify_between()
                 .items_center()
-                .child(Label::new("Tables").font_bold().text_base())
-                .child(refresh_button),
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .child(Label::new("Tables").font_bold().text_base())
+                        .when_some(self.loading_tables, |d, loaded| {
+                            d.child(
+                                Label::new(format!("Loading… {}", loaded))
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground),
+                            )
+                        }),
+                )
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(new_row_button)
+                        .child(compare_button)
+                        .child(sequences_button)
+                        .child(cross_database_button)
+                        .child(extensions_button)
+                        .child(structure_button)
+                        .child(constraint_button)
+                        .child(batch_button)
+                        .child(view_source_button)
+                        .child(table_details_button)
+                        .child(refresh_matview_button)
+                        .child(search_button)
+                        .child(refresh_button),
+                ),
         );
 
+        let filter = h_flex()
+            .gap_1()
+            .items_center()
+            .child(div().flex_1().child(Input::new(&self.filter_input).small()))
+            .child(match_columns_button);
+
         v_flex()
             .flex_1()
             .gap_2()
             .p_2()
             .on_action(cx.listener(Self::on_select_table_item))
             .child(header)
+            .child(filter)
             .child(
-                tree(&self.tree_state, move |ix, entry, selected, _window, cx| {
-                    view.update(cx, |this, cx| {
-                        this.render_tree_item(ix, entry, selected, cx)
-                    })
-                })
-                .p(px(8.))
-                .flex_1()
-                .w_full()
-                .border_1()
-                .border_color(cx.theme().border)
-                .rounded(cx.theme().radius),
+                div()
+                    .key_context("TablesTree")
+                    .flex_1()
+                    .w_full()
+                    .on_key_down(cx.listener(Self::on_tree_key_down))
+                    .child(
+                        tree(&self.tree_state, move |ix, entry, selected, _window, cx| {
+                            view.update(cx, |this, cx| {
+                                this.render_tree_item(ix, entry, selected, cx)
+                            })
+                        })
+                        .p(px(8.))
+                        .size_full()
+                        .border_1()
+                        .border_color(if tree_focused {
+                            cx.theme().ring
+                        } else {
+                            cx.theme().border
+                        })
+                        .rounded(cx.theme().radius),
+                    ),
             )
     }
 }
 
+/// Labels of the rows the tree shows, top to bottom: the children of
+/// expanded items only.
+fn visible_labels(items: &[TreeItem], labels: &mut Vec<SharedString>) {
+    for item in items {
+        labels.push(item.label.clone());
+        if item.is_expanded() {
+            visible_labels(&item.children, labels);
+        }
+    }
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() > max_len {
         format!("{}...", &s[..max_len - 3])
@@ -0,0 +1,286 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement as _, Styled, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::{
+    services::{DatabaseDriver, DatabaseManager, TableInfo, TriggerInfo, set_trigger_enabled_sql},
+    state::{ChangeRecorderState, WindowConnection, WindowScoped, window_id},
+    workspace::notifications::RecordedNotifications as _,
+};
+
+/// A table's triggers, shown in the structure editor, with enable/disable
+/// actions on Postgres. Each toggle shows its `ALTER TABLE` statement for
+/// confirmation before it runs.
+pub struct TriggersPanel {
+    db_manager: DatabaseManager,
+    table: TableInfo,
+    triggers: Vec<TriggerInfo>,
+    driver: Option<DatabaseDriver>,
+    loading: bool,
+    error: Option<String>,
+}
+
+impl TriggersPanel {
+    pub fn view(table: TableInfo, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(table, window, cx))
+    }
+
+    fn new(table: TableInfo, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let db_manager = WindowConnection::of(window_id(window), cx)
+            .db_manager
+            .clone();
+
+        let mut this = Self {
+            db_manager,
+            table,
+            triggers: vec![],
+            driver: None,
+            loading: false,
+            error: None,
+        };
+        this.load(cx);
+        this
+    }
+
+    pub fn load(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+        let table = self.table.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager
+                .get_triggers(&table.table_schema, &table.table_name)
+                .await;
+            let driver = db_manager.driver().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                this.driver = driver;
+                match result {
+                    Ok(triggers) => {
+                        this.triggers = triggers;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Ask for confirmation, then enable or disable the trigger at `ix`.
+    fn confirm_toggle(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(trigger), Some(driver)) = (self.triggers.get(ix).cloned(), self.driver) else {
+            return;
+        };
+        let enable = !trigger.enabled;
+        let table = self.table.clone();
+        let sql = match set_trigger_enabled_sql(
+            &driver,
+            &table.table_schema,
+            &table.table_name,
+            &trigger.trigger_name,
+            enable,
+        ) {
+            Ok(sql) => sql,
+            Err(e) => {
+                window.push_recorded_notification(
+                    (
+                        NotificationType::Error,
+                        SharedString::from(format!("{}", e)),
+                    ),
+                    cx,
+                );
+                return;
+            }
+        };
+
+        let panel = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let panel = panel.clone();
+            let table = table.clone();
+            let trigger_name = trigger.trigger_name.clone();
+            dialog
+                .title(if enable {
+                    "Enable Trigger"
+                } else {
+                    "Disable Trigger"
+                })
+                .confirm()
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(Label::new("The following statement will be executed:").text_sm())
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_sm()
+                                .child(sql.clone()),
+                        ),
+                )
+                .on_ok(move |_, window, cx| {
+                    let _ = panel.update(cx, |this, cx| {
+                        this.run_toggle(table.clone(), trigger_name.clone(), enable, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn run_toggle(
+        &mut self,
+        table: TableInfo,
+        trigger_name: String,
+        enable: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager
+                .set_trigger_enabled(
+                    &table.table_schema,
+                    &table.table_name,
+                    &trigger_name,
+                    enable,
+                )
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match result {
+                    Ok(sql) => {
+                        ChangeRecorderState::record(window_id(window), [sql.as_str()], cx);
+                        (NotificationType::Success, sql.into())
+                    }
+                    Err(e) => (NotificationType::Error, format!("{}", e).into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.load(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn render_row(
+        &self,
+        ix: usize,
+        trigger: &TriggerInfo,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let mut detail = format!("{} {}", trigger.timing, trigger.event);
+        if let Some(function) = &trigger.function_name {
+            detail.push_str(&format!(" · {}", function));
+        }
+
+        h_flex()
+            .id(("trigger", ix))
+            .w_full()
+            .gap_2()
+            .py_1()
+            .px_2()
+            .justify_between()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                v_flex()
+                    .gap_0p5()
+                    .child(Label::new(trigger.trigger_name.clone()).text_sm())
+                    .child(
+                        Label::new(detail)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .items_center()
+                    .when(!trigger.enabled, |d| {
+                        d.child(
+                            Label::new("Disabled")
+                                .text_xs()
+                                .text_color(cx.theme().warning),
+                        )
+                    })
+                    .when(self.driver == Some(DatabaseDriver::Postgres), |d| {
+                        d.child(
+                            Button::new(("toggle-trigger", ix))
+                                .label(if trigger.enabled { "Disable" } else { "Enable" })
+                                .small()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.confirm_toggle(ix, window, cx);
+                                })),
+                        )
+                    }),
+            )
+    }
+}
+
+impl Render for TriggersPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .triggers
+            .iter()
+            .enumerate()
+            .map(|(ix, trigger)| self.render_row(ix, trigger, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let disabled = self.triggers.iter().filter(|t| !t.enabled).count();
+        let summary = if self.loading {
+            "Loading triggers...".to_string()
+        } else {
+            format!("{} triggers, {} disabled", self.triggers.len(), disabled)
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        Button::new("reload-triggers")
+                            .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip("Reload")
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.load(cx);
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("trigger-rows")
+                    .max_h(px(160.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(rows),
+            )
+    }
+}
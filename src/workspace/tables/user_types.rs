@@ -0,0 +1,155 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement as _, Styled, Subscription, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Selectable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+};
+
+use crate::services::{EnumValuePosition, UserTypeInfo, UserTypeKind, add_enum_value_sql};
+
+/// Dialog body describing an enum, composite type or domain. Enums also
+/// get a form generating the `ALTER TYPE ... ADD VALUE` for a new label.
+pub struct UserTypeDetails {
+    user_type: UserTypeInfo,
+    label_input: Entity<InputState>,
+    /// Existing label the new one goes after; `None` appends it.
+    after: Option<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl UserTypeDetails {
+    pub fn view(user_type: UserTypeInfo, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(user_type, window, cx))
+    }
+
+    fn new(user_type: UserTypeInfo, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let label_input = cx.new(|cx| InputState::new(window, cx).placeholder("New label"));
+
+        // Refresh the preview as the user types.
+        let _subscriptions =
+            vec![cx.subscribe(&label_input, |_, _, _: &InputEvent, cx| cx.notify())];
+
+        Self {
+            user_type,
+            label_input,
+            after: None,
+            _subscriptions,
+        }
+    }
+
+    pub fn is_enum(&self) -> bool {
+        self.user_type.kind == UserTypeKind::Enum
+    }
+
+    /// The `ADD VALUE` statement; empty until a label is entered.
+    pub fn script(&self, cx: &App) -> String {
+        let label = self.label_input.read(cx).value();
+        if !self.is_enum() || label.trim().is_empty() {
+            return String::new();
+        }
+        let position = match self.after.and_then(|ix| self.user_type.labels.get(ix)) {
+            Some(existing) => EnumValuePosition::After(existing.clone()),
+            None => EnumValuePosition::Last,
+        };
+        add_enum_value_sql(&self.user_type, &label, &position)
+    }
+
+    fn toggle_after(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.after = if self.after == Some(ix) {
+            None
+        } else {
+            Some(ix)
+        };
+        cx.notify();
+    }
+}
+
+impl Render for UserTypeDetails {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let user_type = &self.user_type;
+        let used_by = if user_type.column_names.is_empty() {
+            "No columns".to_string()
+        } else {
+            user_type.column_names.join(", ")
+        };
+
+        let form = v_form()
+            .small()
+            .child(field().label("Kind").child(user_type.kind.label()))
+            .when_some(user_type.definition.clone(), |form, definition| {
+                let label = match user_type.kind {
+                    UserTypeKind::Domain => "Base type and constraints",
+                    _ => "Attributes",
+                };
+                form.child(
+                    field().label(label).child(
+                        div()
+                            .p_2()
+                            .rounded(cx.theme().radius)
+                            .bg(cx.theme().secondary)
+                            .font_family("monospace")
+                            .text_xs()
+                            .child(definition),
+                    ),
+                )
+            })
+            .child(field().label("Used by columns").child(used_by));
+
+        if !self.is_enum() {
+            return form;
+        }
+
+        let labels = user_type.labels.iter().enumerate().fold(
+            h_flex().gap_1().flex_wrap(),
+            |row, (ix, label)| {
+                row.child(
+                    Button::new(("enum-label", ix))
+                        .label(label.clone())
+                        .small()
+                        .ghost()
+                        .selected(self.after == Some(ix))
+                        .on_click(cx.listener(move |this, _, _win, cx| {
+                            this.toggle_after(ix, cx);
+                        })),
+                )
+            },
+        );
+        let script = self.script(cx);
+
+        form.child(
+            field()
+                .label(format!("Labels ({})", user_type.labels.len()))
+                .description("Pick a label to add the new one after it, otherwise it goes last")
+                .child(labels),
+        )
+        .child(
+            field()
+                .label("Add label")
+                .child(Input::new(&self.label_input)),
+        )
+        .child(
+            field().label("Preview").child(
+                div()
+                    .id("enum-value-preview")
+                    .max_h(px(120.))
+                    .overflow_y_scroll()
+                    .p_2()
+                    .rounded(cx.theme().radius)
+                    .bg(cx.theme().secondary)
+                    .font_family("monospace")
+                    .text_xs()
+                    .child(if script.is_empty() {
+                        "-- nothing to generate yet".to_string()
+                    } else {
+                        script
+                    }),
+            ),
+        )
+    }
+}
@@ -0,0 +1,187 @@
+use gpui::{
+    App, AppContext, Context, Entity, InteractiveElement as _, IntoElement, ParentElement, Render,
+    StatefulInteractiveElement as _, Styled, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    v_flex,
+};
+
+use crate::{
+    services::{DatabaseManager, TablespaceInfo, format_byte_size},
+    state::{WindowConnection, WindowScoped, window_id},
+};
+
+/// Dialog body listing the server's tablespaces: where each lives, its
+/// size, and which tables and indexes of this database are placed in it.
+pub struct TablespacesPanel {
+    db_manager: DatabaseManager,
+    tablespaces: Vec<TablespaceInfo>,
+    loading: bool,
+    error: Option<String>,
+}
+
+impl TablespacesPanel {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let window = window_id(window);
+        cx.new(|cx| {
+            let db_manager = WindowConnection::of(window, cx).db_manager.clone();
+            let mut this = Self {
+                db_manager,
+                tablespaces: vec![],
+                loading: false,
+                error: None,
+            };
+            this.load(cx);
+            this
+        })
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        let db_manager = self.db_manager.clone();
+
+        cx.spawn(async move |this, cx| {
+            let result = db_manager.get_tablespaces().await;
+            this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(tablespaces) => {
+                        this.tablespaces = tablespaces;
+                        this.error = None;
+                    }
+                    Err(e) => this.error = Some(format!("{}", e)),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn render_row(
+        &self,
+        ix: usize,
+        tablespace: &TablespaceInfo,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let location = if tablespace.location.is_empty() {
+            "data directory".to_string()
+        } else {
+            tablespace.location.clone()
+        };
+        let size = tablespace
+            .size_bytes
+            .map(|size| format_byte_size(size.max(0) as usize))
+            .unwrap_or("size not readable".to_string());
+        let placed = if tablespace.is_database_default {
+            "Default of this database: holds every table and index not placed elsewhere".to_string()
+        } else {
+            match tablespace.relations.len() {
+                0 => "Nothing of this database is placed here".to_string(),
+                1 => "1 relation of this database".to_string(),
+                n => format!("{} relations of this database", n),
+            }
+        };
+
+        v_flex()
+            .id(("tablespace", ix))
+            .w_full()
+            .gap_0p5()
+            .py_1()
+            .px_2()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(Label::new(tablespace.name.clone()).text_sm())
+                    .when(tablespace.is_database_default, |d| {
+                        d.child(Label::new("default").text_xs().text_color(cx.theme().info))
+                    }),
+            )
+            .child(
+                Label::new(format!(
+                    "{} · owner {} · {}",
+                    location, tablespace.owner, size
+                ))
+                .text_xs()
+                .text_color(muted),
+            )
+            .child(Label::new(placed).text_xs().text_color(muted))
+            .children(tablespace.relations.iter().map(|relation| {
+                Label::new(format!(
+                    "{} {}.{}",
+                    relation.kind, relation.schema, relation.name
+                ))
+                .text_xs()
+                .font_family("monospace")
+            }))
+    }
+}
+
+impl Render for TablespacesPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .tablespaces
+            .iter()
+            .enumerate()
+            .map(|(ix, tablespace)| self.render_row(ix, tablespace, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        let summary = if self.loading {
+            "Loading tablespaces...".to_string()
+        } else {
+            format!("{} tablespaces", self.tablespaces.len())
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .justify_between()
+                    .child(Label::new(summary).text_sm())
+                    .child(
+                        Button::new("reload-tablespaces")
+                            .icon(Icon::empty().path("icons/rotate-ccw.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip("Reload")
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.load(cx);
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |d, error| {
+                d.child(Label::new(error).text_sm().text_color(cx.theme().danger))
+            })
+            .child(
+                div()
+                    .id("tablespace-rows")
+                    .max_h(px(420.))
+                    .overflow_y_scroll()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded(cx.theme().radius)
+                    .children(rows),
+            )
+    }
+}
+
+pub fn open_tablespaces_dialog(window: &mut Window, cx: &mut App) {
+    let panel = TablespacesPanel::view(window, cx);
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        dialog
+            .title("Tablespaces")
+            .width(px(640.))
+            .child(panel.clone())
+    });
+}
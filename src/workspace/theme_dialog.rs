@@ -0,0 +1,261 @@
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, IndexPath, Sizable as _, ThemeMode,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    label::Label,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+
+use crate::themes::{
+    ThemeOverrides, ThemeSettings, reload_user_themes, theme_names, user_themes_dir,
+};
+
+/// Select entry for the built-in theme of a color mode.
+const DEFAULT_THEME: &str = "Default";
+
+/// Dialog body for picking the light and dark themes and overriding
+/// individual colors. Changes preview live; `revert` restores the settings
+/// the dialog was opened with.
+pub struct ThemeDialog {
+    original: ThemeSettings,
+    light_select: Entity<SelectState<Vec<SharedString>>>,
+    dark_select: Entity<SelectState<Vec<SharedString>>>,
+    editor_background: Entity<InputState>,
+    editor_foreground: Entity<InputState>,
+    grid_stripe: Entity<InputState>,
+    production_accent: Entity<InputState>,
+    user_theme_count: Option<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ThemeDialog {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let original = ThemeSettings::global(cx);
+
+        let light_select = cx.new(|cx| {
+            let (items, selected) = Self::theme_items(ThemeMode::Light, &original);
+            SelectState::new(items, selected, window, cx)
+        });
+        let dark_select = cx.new(|cx| {
+            let (items, selected) = Self::theme_items(ThemeMode::Dark, &original);
+            SelectState::new(items, selected, window, cx)
+        });
+
+        let color_input = |value: &Option<String>, window: &mut Window, cx: &mut Context<Self>| {
+            let value = value.clone().unwrap_or_default();
+            cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder("#rrggbb")
+                    .default_value(value)
+            })
+        };
+        let overrides = &original.overrides;
+        let editor_background = color_input(&overrides.editor_background, window, cx);
+        let editor_foreground = color_input(&overrides.editor_foreground, window, cx);
+        let grid_stripe = color_input(&overrides.grid_stripe, window, cx);
+        let production_accent = color_input(&overrides.production_accent, window, cx);
+
+        let mut _subscriptions = vec![
+            cx.subscribe_in(&light_select, window, Self::on_select_event),
+            cx.subscribe_in(&dark_select, window, Self::on_select_event),
+        ];
+        for input in [
+            &editor_background,
+            &editor_foreground,
+            &grid_stripe,
+            &production_accent,
+        ] {
+            _subscriptions.push(cx.subscribe_in(input, window, Self::on_input_event));
+        }
+
+        Self {
+            original,
+            light_select,
+            dark_select,
+            editor_background,
+            editor_foreground,
+            grid_stripe,
+            production_accent,
+            user_theme_count: None,
+            _subscriptions,
+        }
+    }
+
+    /// Theme names for `mode`, after the built-in default, and the index of
+    /// the one `settings` picks.
+    fn theme_items(
+        mode: ThemeMode,
+        settings: &ThemeSettings,
+    ) -> (Vec<SharedString>, Option<IndexPath>) {
+        let mut items: Vec<SharedString> = vec![DEFAULT_THEME.into()];
+        items.extend(theme_names(mode));
+        let selected = settings
+            .theme_for(mode)
+            .and_then(|name| items.iter().position(|item| item == name))
+            .unwrap_or(0);
+        (items, Some(IndexPath::new(selected)))
+    }
+
+    fn on_select_event(
+        &mut self,
+        _: &Entity<SelectState<Vec<SharedString>>>,
+        event: &SelectEvent<Vec<SharedString>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            SelectEvent::Confirm(_) => self.settings(cx).preview(window, cx),
+        }
+    }
+
+    fn on_input_event(
+        &mut self,
+        _: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            self.settings(cx).preview(window, cx);
+        }
+    }
+
+    /// The settings the dialog currently shows.
+    fn settings(&self, cx: &App) -> ThemeSettings {
+        let theme = |select: &Entity<SelectState<Vec<SharedString>>>| {
+            select
+                .read(cx)
+                .selected_value()
+                .filter(|name| name.as_ref() != DEFAULT_THEME)
+                .cloned()
+        };
+        let color = |input: &Entity<InputState>| {
+            let value = input.read(cx).value().trim().to_string();
+            (!value.is_empty()).then_some(value)
+        };
+        ThemeSettings {
+            light_theme: theme(&self.light_select),
+            dark_theme: theme(&self.dark_select),
+            overrides: ThemeOverrides {
+                editor_background: color(&self.editor_background),
+                editor_foreground: color(&self.editor_foreground),
+                grid_stripe: color(&self.grid_stripe),
+                production_accent: color(&self.production_accent),
+            },
+        }
+    }
+
+    /// Keep the previewed settings.
+    pub fn save(&self, window: &mut Window, cx: &mut App) {
+        self.settings(cx).set(window, cx);
+    }
+
+    /// Go back to the settings from before the dialog opened.
+    pub fn revert(&self, window: &mut Window, cx: &mut App) {
+        self.original.clone().preview(window, cx);
+    }
+
+    fn reload_themes(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        self.user_theme_count = Some(reload_user_themes());
+        let settings = self.settings(cx);
+        for (select, mode) in [
+            (&self.light_select, ThemeMode::Light),
+            (&self.dark_select, ThemeMode::Dark),
+        ] {
+            let (items, selected) = Self::theme_items(mode, &settings);
+            select.update(cx, |select, cx| {
+                select.set_items(items, window, cx);
+                select.set_selected_index(selected, window, cx);
+            });
+        }
+        settings.preview(window, cx);
+        cx.notify();
+    }
+
+    fn open_themes_folder(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(dir) = user_themes_dir() else {
+            return;
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("Failed to create {}: {}", dir.display(), e);
+            return;
+        }
+        cx.open_with_system(&dir);
+    }
+}
+
+impl Render for ThemeDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let folder = user_themes_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let reload_label = match self.user_theme_count {
+            Some(1) => "Reloaded 1 theme".to_string(),
+            Some(count) => format!("Reloaded {} themes", count),
+            None => "Reload Themes".to_string(),
+        };
+
+        v_flex()
+            .gap_3()
+            .child(
+                v_form()
+                    .child(field().label("Light theme").child(Select::new(&self.light_select)))
+                    .child(field().label("Dark theme").child(Select::new(&self.dark_select)))
+                    .child(
+                        field()
+                            .label("Editor background")
+                            .child(Input::new(&self.editor_background).small()),
+                    )
+                    .child(
+                        field()
+                            .label("Editor text")
+                            .child(Input::new(&self.editor_foreground).small()),
+                    )
+                    .child(
+                        field()
+                            .label("Results stripe")
+                            .child(Input::new(&self.grid_stripe).small()),
+                    )
+                    .child(
+                        field()
+                            .label("Production accent")
+                            .description("Marks the window while connected to a production database")
+                            .child(Input::new(&self.production_accent).small()),
+                    ),
+            )
+            .child(
+                Label::new(format!(
+                    "Theme files in {} use the gpui-component theme format; leave a color empty to use the theme's own.",
+                    folder
+                ))
+                .text_xs()
+                .text_color(cx.theme().muted_foreground),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("reload-themes")
+                            .label(reload_label)
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(Self::reload_themes)),
+                    )
+                    .child(
+                        Button::new("open-themes-folder")
+                            .label("Open Themes Folder")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(Self::open_themes_folder)),
+                    ),
+            )
+    }
+}
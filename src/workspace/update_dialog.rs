@@ -0,0 +1,211 @@
+use gpui::prelude::FluentBuilder as _;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme as _, Selectable as _, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    progress::Progress,
+    text::TextView,
+    v_flex,
+};
+
+use crate::services::updates::{UpdateChannel, can_install_updates, can_verify_updates};
+use crate::state::{UpdateState, UpdateStatus};
+
+/// Dialog body for the update channel, the newer release's changelog and
+/// downloading and installing it.
+pub struct UpdateDialog {
+    _subscriptions: Vec<Subscription>,
+}
+
+impl UpdateDialog {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let _subscriptions = vec![cx.observe_global::<UpdateState>(|_, cx| cx.notify())];
+        Self { _subscriptions }
+    }
+
+    fn render_actions(status: &UpdateStatus) -> impl IntoElement {
+        let can_download = status.info().is_some_and(|info| {
+            info.asset.is_some() && info.signature.is_some() && can_verify_updates()
+        });
+        let release_url = status.info().map(|info| info.release_url.clone());
+
+        h_flex()
+            .gap_2()
+            .justify_end()
+            .when_some(release_url, |row, url| {
+                row.child(
+                    Button::new("open-release")
+                        .label("Release Page")
+                        .small()
+                        .ghost()
+                        .on_click(move |_, _, cx| cx.open_url(&url)),
+                )
+            })
+            .map(|row| match status {
+                UpdateStatus::Available(_) | UpdateStatus::Failed { info: Some(_), .. }
+                    if can_download =>
+                {
+                    row.child(
+                        Button::new("download-update")
+                            .label("Download")
+                            .small()
+                            .primary()
+                            .on_click(|_, _, cx| UpdateState::download(cx)),
+                    )
+                }
+                UpdateStatus::Ready { .. } if can_install_updates() => row.child(
+                    Button::new("install-update")
+                        .label("Install and Relaunch")
+                        .small()
+                        .primary()
+                        .on_click(|_, _, cx| UpdateState::install_and_relaunch(cx)),
+                ),
+                UpdateStatus::Ready { path, .. } => {
+                    let path = path.clone();
+                    row.child(
+                        Button::new("reveal-update")
+                            .label("Show in Folder")
+                            .small()
+                            .primary()
+                            .on_click(move |_, _, cx| cx.reveal_path(&path)),
+                    )
+                }
+                UpdateStatus::Idle | UpdateStatus::UpToDate | UpdateStatus::Failed { .. } => row
+                    .child(
+                        Button::new("check-update")
+                            .label("Check Now")
+                            .small()
+                            .on_click(|_, _, cx| UpdateState::check(cx)),
+                    ),
+                _ => row,
+            })
+    }
+}
+
+impl Render for UpdateDialog {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state = cx.global::<UpdateState>();
+        let channel = state.channel;
+        let status = state.status.clone();
+
+        let channels = [UpdateChannel::Stable, UpdateChannel::Beta]
+            .into_iter()
+            .fold(h_flex().gap_1(), |row, option| {
+                row.child(
+                    Button::new(option.label())
+                        .label(option.label())
+                        .small()
+                        .ghost()
+                        .selected(channel == option)
+                        .on_click(move |_, _, cx| UpdateState::set_channel(option, cx)),
+                )
+            });
+
+        let summary = match &status {
+            UpdateStatus::Idle => "Not checked yet".to_string(),
+            UpdateStatus::Checking => "Checking for updates…".to_string(),
+            UpdateStatus::UpToDate => {
+                format!("pgui v{} is up to date", env!("CARGO_PKG_VERSION"))
+            }
+            UpdateStatus::Available(info) => format!(
+                "v{} is available (you have v{})",
+                info.latest_version, info.current_version
+            ),
+            UpdateStatus::Downloading {
+                info,
+                downloaded,
+                total,
+            } => format!(
+                "Downloading v{}… {:.1} of {:.1} MB",
+                info.latest_version,
+                *downloaded as f64 / 1_048_576.,
+                *total as f64 / 1_048_576.
+            ),
+            UpdateStatus::Ready { info, .. } => {
+                format!("v{} is downloaded and verified", info.latest_version)
+            }
+            UpdateStatus::Installing(info) => format!("Installing v{}…", info.latest_version),
+            UpdateStatus::Failed { message, .. } => message.clone(),
+        };
+        let failed = matches!(status, UpdateStatus::Failed { .. });
+
+        let note = status.info().and_then(|info| {
+            if info.asset.is_none() {
+                Some("This release has no build for this platform.")
+            } else if info.signature.is_none() || !can_verify_updates() {
+                Some("This build cannot be verified; download it from the release page.")
+            } else {
+                None
+            }
+        });
+
+        let changelog = status.info().map(|info| {
+            let notes = info
+                .release_notes
+                .clone()
+                .filter(|notes| !notes.trim().is_empty())
+                .unwrap_or_else(|| "_No release notes._".to_string());
+            let title = if info.prerelease {
+                format!("v{} (pre-release)", info.latest_version)
+            } else {
+                format!("v{}", info.latest_version)
+            };
+            v_flex().gap_1().child(Label::new(title).text_sm()).child(
+                div()
+                    .id("update-changelog")
+                    .max_h(px(320.))
+                    .overflow_y_scroll()
+                    .p_2()
+                    .rounded(cx.theme().radius)
+                    .bg(cx.theme().secondary)
+                    .child(TextView::markdown("update-notes", notes, window, cx)),
+            )
+        });
+
+        let progress = match &status {
+            UpdateStatus::Downloading {
+                downloaded, total, ..
+            } if *total > 0 => Some(*downloaded as f32 / *total as f32 * 100.),
+            _ => None,
+        };
+
+        v_flex()
+            .gap_3()
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(Label::new("Channel").text_sm())
+                    .child(channels),
+            )
+            .child(
+                Label::new(summary)
+                    .text_sm()
+                    .when(failed, |l| l.text_color(cx.theme().danger)),
+            )
+            .when_some(progress, |col, value| {
+                col.child(Progress::new().value(value))
+            })
+            .when_some(note, |col, note| {
+                col.child(
+                    Label::new(note)
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .children(changelog)
+            .child(Self::render_actions(&status))
+    }
+}
+
+pub fn open_update_dialog(window: &mut Window, cx: &mut App) {
+    let view = UpdateDialog::view(window, cx);
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        dialog.title("Updates").width(px(560.)).child(view.clone())
+    });
+}
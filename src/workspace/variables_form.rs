@@ -0,0 +1,320 @@
+use gpui::{
+    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
+    Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, Selectable as _, Sizable as _, WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::DialogButtonProps,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    notification::NotificationType,
+    v_flex,
+};
+
+use crate::services::sql::{QueryVariable, QueryVariables, VariableEnvironment};
+use crate::state::VariablesState;
+use crate::workspace::notifications::Toasts as _;
+
+type VariableRow = (Entity<InputState>, Entity<InputState>);
+
+/// Dialog body for the `{{name}}` variable environments of the workspace.
+/// One environment is edited at a time; the one shown when saving becomes
+/// the active one.
+pub struct VariablesForm {
+    variables: QueryVariables,
+    /// Index into `variables.environments` of the one being edited.
+    selected: Option<usize>,
+    rows: Vec<VariableRow>,
+    environment_input: Entity<InputState>,
+}
+
+impl VariablesForm {
+    /// The form for the current environments, with a row ready for each of
+    /// `names` the active environment does not define yet.
+    pub fn view(names: Vec<String>, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        let variables = cx.global::<VariablesState>().variables.clone();
+        cx.new(|cx| {
+            let environment_input = cx
+                .new(|cx| InputState::new(window, cx).placeholder("New environment, e.g. staging"));
+            let selected = variables
+                .active
+                .as_ref()
+                .and_then(|active| {
+                    variables
+                        .environments
+                        .iter()
+                        .position(|e| &e.name == active)
+                })
+                .or((!variables.environments.is_empty()).then_some(0));
+            let mut this = Self {
+                variables,
+                selected,
+                rows: vec![],
+                environment_input,
+            };
+            this.load_rows(window, cx);
+            if this.selected.is_some() {
+                for name in names {
+                    let defined = this
+                        .rows
+                        .iter()
+                        .any(|(n, _)| n.read(cx).value().trim() == name);
+                    if !defined {
+                        this.add_row(name, String::new(), window, cx);
+                    }
+                }
+            }
+            this
+        })
+    }
+
+    /// The environments as entered, the one shown being active.
+    pub fn variables(&mut self, cx: &App) -> QueryVariables {
+        self.store_rows(cx);
+        let mut variables = self.variables.clone();
+        variables.active = self
+            .selected
+            .and_then(|ix| variables.environments.get(ix))
+            .map(|env| env.name.clone());
+        variables
+    }
+
+    fn add_row(&mut self, name: String, value: String, window: &mut Window, cx: &mut App) {
+        let name_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("name")
+                .default_value(name)
+        });
+        let value_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("value")
+                .default_value(value)
+        });
+        self.rows.push((name_input, value_input));
+    }
+
+    /// Rows for the selected environment's values.
+    fn load_rows(&mut self, window: &mut Window, cx: &mut App) {
+        self.rows.clear();
+        let values = self
+            .selected
+            .and_then(|ix| self.variables.environments.get(ix))
+            .map(|env| env.values.clone())
+            .unwrap_or_default();
+        for value in values {
+            self.add_row(value.name, value.value, window, cx);
+        }
+    }
+
+    /// Write the rows back to the selected environment, dropping those
+    /// without a name.
+    fn store_rows(&mut self, cx: &App) {
+        let Some(env) = self
+            .selected
+            .and_then(|ix| self.variables.environments.get_mut(ix))
+        else {
+            return;
+        };
+        env.values = self
+            .rows
+            .iter()
+            .map(|(name, value)| QueryVariable {
+                name: name.read(cx).value().trim().to_string(),
+                value: value.read(cx).value().to_string(),
+            })
+            .filter(|v| !v.name.is_empty())
+            .collect();
+    }
+
+    fn select(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.store_rows(cx);
+        self.selected = Some(ix);
+        self.load_rows(window, cx);
+        cx.notify();
+    }
+
+    /// Add the environment named in the input, copying the selected one's
+    /// variable names with empty values.
+    fn add_environment(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.environment_input.read(cx).value().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        if self.variables.environments.iter().any(|e| e.name == name) {
+            window.push_toast(
+                (
+                    NotificationType::Warning,
+                    SharedString::from(format!("There already is an environment \"{}\"", name)),
+                ),
+                cx,
+            );
+            return;
+        }
+
+        self.store_rows(cx);
+        let values = self
+            .selected
+            .and_then(|ix| self.variables.environments.get(ix))
+            .map(|env| {
+                env.values
+                    .iter()
+                    .map(|v| QueryVariable {
+                        name: v.name.clone(),
+                        value: String::new(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.variables
+            .environments
+            .push(VariableEnvironment { name, values });
+        self.environment_input
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        self.selected = Some(self.variables.environments.len() - 1);
+        self.load_rows(window, cx);
+        cx.notify();
+    }
+
+    fn remove_environment(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.selected else {
+            return;
+        };
+        self.variables.environments.remove(ix);
+        self.selected = (!self.variables.environments.is_empty()).then(|| ix.saturating_sub(1));
+        self.load_rows(window, cx);
+        cx.notify();
+    }
+}
+
+impl Render for VariablesForm {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let environments = self.variables.environments.iter().enumerate().fold(
+            h_flex().gap_1().flex_wrap(),
+            |row, (ix, env)| {
+                row.child(
+                    Button::new(("variable-environment", ix))
+                        .label(env.name.clone())
+                        .small()
+                        .ghost()
+                        .selected(self.selected == Some(ix))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.select(ix, window, cx);
+                        })),
+                )
+            },
+        );
+
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(ix, (name, value))| {
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(div().w(px(160.)).child(Input::new(name).small()))
+                    .child(div().flex_1().child(Input::new(value).small()))
+                    .child(
+                        Button::new(("remove-variable", ix))
+                            .icon(Icon::empty().path("icons/trash.svg"))
+                            .xsmall()
+                            .ghost()
+                            .tooltip("Remove Variable")
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.rows.remove(ix);
+                                cx.notify();
+                            })),
+                    )
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_3()
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(Label::new("Environments").text_sm())
+                    .child(environments)
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .child(Input::new(&self.environment_input).small()),
+                            )
+                            .child(
+                                Button::new("add-environment")
+                                    .label("Add")
+                                    .small()
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.add_environment(window, cx);
+                                    })),
+                            )
+                            .when(self.selected.is_some(), |d| {
+                                d.child(
+                                    Button::new("remove-environment")
+                                        .label("Remove")
+                                        .small()
+                                        .danger()
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.remove_environment(window, cx);
+                                        })),
+                                )
+                            }),
+                    ),
+            )
+            .when(self.selected.is_some(), |d| {
+                d.child(
+                    v_flex()
+                        .gap_1()
+                        .child(Label::new("Variables, used as {{name}} in queries").text_sm())
+                        .children(rows)
+                        .child(
+                            h_flex().child(
+                                Button::new("add-variable")
+                                    .label("Add Variable")
+                                    .icon(Icon::empty().path("icons/plus.svg"))
+                                    .small()
+                                    .ghost()
+                                    .on_click(cx.listener(|this, _, window, cx| {
+                                        this.add_row(String::new(), String::new(), window, cx);
+                                        cx.notify();
+                                    })),
+                            ),
+                        ),
+                )
+            })
+            .when(self.selected.is_none(), |d| {
+                d.child(
+                    Label::new("Add an environment, such as dev or prod, to define variables in")
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+    }
+}
+
+/// Open the variable environments, adding rows for `names` when the
+/// active environment lacks them.
+pub fn open_variables_dialog(names: Vec<String>, window: &mut Window, cx: &mut App) {
+    let form = VariablesForm::view(names, window, cx);
+
+    window.open_dialog(cx, move |dialog, _win, _cx| {
+        let form = form.clone();
+        dialog
+            .title("Variables")
+            .width(px(560.))
+            .child(form.clone())
+            .confirm()
+            .button_props(DialogButtonProps::default().ok_text("Save"))
+            .on_ok(move |_, _window, cx| {
+                let variables = form.update(cx, |form, cx| form.variables(cx));
+                VariablesState::set(variables, cx);
+                true
+            })
+    });
+}
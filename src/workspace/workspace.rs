@@ -1,27 +1,114 @@
-use super::connections::ConnectionManager;
+use super::batch_run::{BatchRunEvent, BatchRunPanel};
+use super::connections::{ConnectionManager, PasswordPrompt, open_unlock_vault_dialog};
 use super::editor::Editor;
 use super::editor::EditorEvent;
-use super::footer_bar::{FooterBar, FooterBarEvent};
+use super::editor::{
+    OpenFile, OpenRecentFile, RunScript, RunSelection, RunStatement, SaveFile, SaveFileAs,
+};
+use super::error_explanation::ErrorExplanationView;
+use super::footer_bar::{FooterBar, FooterBarEvent, ResultsOrientation};
 use super::header_bar::HeaderBar;
-use super::tables::{TableEvent, TablesTree};
+use super::layout::{
+    DraggedPanel, MAX_PANEL_WIDTH, MIN_PANEL_WIDTH, PANEL_LAYOUT_KEY, PanelDock, PanelLayout,
+    PanelPlacement, SidePanel, ToggleZenMode,
+};
+use super::migrations::MigrationsPanel;
+use super::query_params_form::QueryParamsForm;
+use super::snapshot::WorkspaceSnapshot;
+use super::tables::{
+    BatchScriptForm, CompareDataForm, ConstraintForm, ExtensionsPanel, ForeignServerForm,
+    NewRowForm, ObjectSearch, ObjectSearchEvent, SearchDatabase, SequencesPanel, StructureEditor,
+    TableEvent, TablesTree, UserTypeDetails,
+};
 
-use crate::services::AppStore;
-use crate::services::{ErrorResult, QueryExecutionResult, TableInfo};
-use crate::state::{ConnectionState, ConnectionStatus};
+use crate::services::i18n::tr;
+use crate::services::sql::{
+    BoundParams, ParamQuery, StatementRun, captured_result, history_entry, parse_params, query_hash,
+};
+use crate::services::storage::QueryHistoryEntry;
+use crate::services::{
+    AppStore, DEFAULT_MAX_ROWS, DatabaseManager, JobRun, LARGE_RESULT_ROWS, format_row_count,
+    has_row_limit, is_row_query, with_limit,
+};
+use crate::services::{
+    ConnectionInfo, ConnectionsRepository, ParamStatement, TableEditInfo, build_insert,
+};
+use crate::services::{ErrorResult, QueryExecutionResult, RoutineInfo, TableInfo};
+use crate::services::{HookContext, HookStage};
+use crate::services::{ScriptTemplate, TransactionAlert, UserTypeInfo, script_template_sql};
+use crate::state::{
+    ChangeRecorderState, ConnectionStatus, EditorState, HooksState, JobStatus, JobsState,
+    NotificationAction, NotificationSource, NotificationsState, PerWindow, QuickSlotsState,
+    TransactionAlerts, VariablesState, VaultState, WindowConnection, WindowScoped, WorkspacesState,
+    close_window, connect_with_password, open_window, save_workspace, window_id,
+};
+use crate::themes::busy_indicator;
+use crate::window::{SavedWindowBounds, display_layout_key, save_window_bounds};
 use crate::workspace::agent::AgentPanel;
 use crate::workspace::agent::AgentPanelEvent;
 use crate::workspace::history::HistoryEvent;
 use crate::workspace::history::HistoryPanel;
-use crate::workspace::results::ResultsPanel;
+use crate::workspace::history::{
+    QuickSlot1, QuickSlot2, QuickSlot3, QuickSlot4, QuickSlot5, QuickSlot6, QuickSlot7, QuickSlot8,
+    QuickSlot9, quick_slot_shortcut,
+};
+use crate::workspace::jobs::{JobsEvent, JobsPanel};
+use crate::workspace::notifications::{
+    NotificationsEvent, NotificationsPanel, RecordedNotifications as _, Toast, Toasts as _,
+    open_notifications_dialog, render_toast_layer,
+};
+use crate::workspace::open_log_viewer;
+use crate::workspace::performance::{
+    ActivityPanel, LocksPanel, PerformanceEvent, PerformancePanel, StatementStatsPanel,
+};
+use crate::workspace::results::{ResultsEvent, ResultsPanel};
+use crate::workspace::update_dialog::open_update_dialog;
 use gpui::prelude::FluentBuilder as _;
 use gpui::*;
+use std::collections::HashMap;
+use uuid::Uuid;
 
 use gpui_component::ActiveTheme;
 use gpui_component::Root;
-use gpui_component::resizable::{resizable_panel, v_resizable};
-use gpui_component::spinner::Spinner;
+use gpui_component::Sizable as _;
+use gpui_component::WindowExt as _;
+use gpui_component::button::{Button, ButtonVariants as _};
+use gpui_component::dialog::DialogButtonProps;
+use gpui_component::h_flex;
+use gpui_component::input::{Input, InputState};
+use gpui_component::label::Label;
+use gpui_component::notification::NotificationType;
+use gpui_component::resizable::{
+    ResizablePanel, ResizableState, h_resizable, resizable_panel, v_resizable,
+};
+use gpui_component::v_flex;
+use gpui_component::{Icon, IconName};
+
+const RESULTS_ORIENTATION_KEY: &str = "results_orientation";
+
+/// Estimated rows of `query` when it would fetch a large result with no
+/// limit at all: a SELECT without its own LIMIT while max rows is off.
+async fn large_result_estimate(db_manager: &DatabaseManager, query: &str) -> Option<u64> {
+    if db_manager.max_rows().await.is_some() || !is_row_query(query) || has_row_limit(query) {
+        return None;
+    }
+    match db_manager.estimate_rows(query).await {
+        Ok(rows) => rows.filter(|rows| *rows >= LARGE_RESULT_ROWS),
+        Err(e) => {
+            tracing::debug!("Failed to estimate rows: {}", e);
+            None
+        }
+    }
+}
+
+/// Workspace state key for the last parameter values used with a query.
+fn query_params_key(query: &str) -> String {
+    format!("query_params:{}", query_hash(query))
+}
 
 pub struct Workspace {
+    /// The window this workspace fills, which its connection belongs to.
+    window_id: WindowId,
     connection_state: ConnectionStatus,
     header_bar: Entity<HeaderBar>,
     footer_bar: Entity<FooterBar>,
@@ -29,52 +116,189 @@ pub struct Workspace {
     editor: Entity<Editor>,
     agent_panel: Entity<AgentPanel>,
     history_panel: Entity<HistoryPanel>,
+    jobs_panel: Entity<JobsPanel>,
+    notifications_panel: Entity<NotificationsPanel>,
     connection_manager: Entity<ConnectionManager>,
     results_panel: Entity<ResultsPanel>,
     _subscriptions: Vec<Subscription>,
     show_tables: bool,
     show_agent: bool,
     show_history: bool,
+    show_jobs: bool,
+    /// The job whose result goes straight to the results panel; earlier
+    /// jobs finish in the background and notify instead.
+    foreground_job: Option<Uuid>,
+    /// Last table selected in the tree, as `(schema, table)`.
+    selected_table: Option<(String, String)>,
+    /// Table to select once a restored workspace has connected, as
+    /// `(connection, schema, table)`. Kept until it has been selected.
+    pending_table: Option<(Option<Uuid>, String, String)>,
+    results_orientation: ResultsOrientation,
+    panel_layout: PanelLayout,
+    /// Bumped when a restored workspace replaces the panel widths, so the
+    /// resizable group starts over from them.
+    layout_generation: usize,
+    /// Only the editor and results, in a larger font. The panel toggles
+    /// and layout are left alone, so leaving restores them.
+    zen_mode: bool,
+    /// Zen mode made the window full screen and undoes that on exit.
+    zen_fullscreen: bool,
+    _save_bounds_task: Option<Task<()>>,
+    _save_layout_task: Option<Task<()>>,
 }
 
 impl Workspace {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let window_id = window_id(window);
+        open_window(window_id, cx);
+        cx.on_release(move |_this, cx| close_window(window_id, cx))
+            .detach();
+
         let header_bar = HeaderBar::view(window, cx);
         let footer_bar = FooterBar::view(window, cx);
         let tables_tree = TablesTree::view(window, cx);
         let agent_panel = AgentPanel::view(window, cx);
         let history_panel = HistoryPanel::view(window, cx);
+        let jobs_panel = JobsPanel::view(window, cx);
+        let notifications_panel = NotificationsPanel::view(cx);
         let editor = Editor::view(window, cx);
         let results_panel = ResultsPanel::view(window, cx);
         let connection_manager = ConnectionManager::view(window, cx);
 
         let _subscriptions = vec![
-            cx.observe_global::<ConnectionState>(move |this, cx| {
-                this.connection_state = cx.global::<ConnectionState>().connection_state.clone();
+            cx.observe_window_bounds(window, |this, window, cx| {
+                this.schedule_save_window_bounds(window, cx);
+            }),
+            cx.observe_global::<PerWindow<WindowConnection>>(move |this, cx| {
+                this.connection_state = WindowConnection::of(this.window_id, cx)
+                    .connection_state
+                    .clone();
+                this.restore_pending_table(cx);
                 cx.notify();
             }),
-            cx.subscribe(&editor, |this, _, event: &EditorEvent, cx| match event {
-                EditorEvent::ExecuteQuery(query) => {
-                    this.execute_query(query.clone(), cx);
+            cx.observe_global::<PerWindow<EditorState>>(move |this, cx| {
+                this.restore_pending_table(cx);
+            }),
+            cx.observe_global_in::<PerWindow<WindowConnection>>(window, move |this, window, cx| {
+                let Some(connection) = WindowConnection::of(this.window_id, cx)
+                    .password_prompt
+                    .clone()
+                else {
+                    return;
+                };
+                WindowConnection::update(this.window_id, cx, |state, _cx| {
+                    state.password_prompt = None;
+                });
+                this.open_password_prompt(connection, window, cx);
+            }),
+            cx.observe_global_in::<VaultState>(window, move |_this, window, cx| {
+                if !cx.global::<VaultState>().prompt_unlock {
+                    return;
                 }
+                cx.update_global::<VaultState, _>(|state, _cx| {
+                    state.prompt_unlock = false;
+                });
+                open_unlock_vault_dialog(window, cx);
             }),
-            cx.subscribe(&tables_tree, |this, _, event: &TableEvent, cx| {
-                this.handle_table_event(event, cx);
+            cx.observe_global_in::<PerWindow<TransactionAlerts>>(
+                window,
+                move |this, window, cx| {
+                    if TransactionAlerts::of(this.window_id, cx).pending.is_empty() {
+                        return;
+                    }
+                    let alerts = TransactionAlerts::update(this.window_id, cx, |state, _cx| {
+                        std::mem::take(&mut state.pending)
+                    });
+                    this.notify_transaction_alerts(alerts, window, cx);
+                },
+            ),
+            cx.observe_global_in::<WorkspacesState>(window, move |this, window, cx| {
+                let Some((_, saved)) = cx
+                    .global::<WorkspacesState>()
+                    .pending_restore
+                    .clone()
+                    .filter(|(window, _)| *window == this.window_id)
+                else {
+                    return;
+                };
+                cx.update_global::<WorkspacesState, _>(|state, _cx| {
+                    state.pending_restore = None;
+                });
+                this.restore_workspace(saved, window, cx);
             }),
-            cx.subscribe(&footer_bar, |this, _, event: &FooterBarEvent, cx| {
-                match event {
-                    FooterBarEvent::ToggleTables(show) => {
-                        this.show_tables = *show;
+            cx.subscribe_in(
+                &editor,
+                window,
+                |this, _, event: &EditorEvent, window, cx| match event {
+                    EditorEvent::ExecuteQuery(query) => {
+                        this.execute_query(query.clone(), window, cx);
                     }
-                    FooterBarEvent::ToggleAgent(show) => {
-                        this.show_agent = *show;
+                    EditorEvent::RunOnTargets(script) => {
+                        this.open_batch_run_dialog(script.clone(), window, cx);
                     }
-                    FooterBarEvent::ToggleHistory(show) => {
-                        this.show_history = *show;
+                },
+            ),
+            cx.subscribe_in(
+                &tables_tree,
+                window,
+                |this, _, event: &TableEvent, window, cx| {
+                    this.handle_table_event(event, window, cx);
+                },
+            ),
+            cx.subscribe_in(
+                &footer_bar,
+                window,
+                |this, _, event: &FooterBarEvent, window, cx| {
+                    match event {
+                        FooterBarEvent::ToggleTables(show) => {
+                            this.show_tables = *show;
+                        }
+                        FooterBarEvent::ToggleAgent(show) => {
+                            this.show_agent = *show;
+                        }
+                        FooterBarEvent::ToggleHistory(show) => {
+                            this.show_history = *show;
+                        }
+                        FooterBarEvent::ToggleJobs(show) => {
+                            this.show_jobs = *show;
+                            if *show {
+                                JobsState::update(this.window_id, cx, |state, _cx| {
+                                    state.mark_all_seen();
+                                });
+                            }
+                        }
+                        FooterBarEvent::SetResultsOrientation(orientation) => {
+                            this.results_orientation = *orientation;
+                            Self::save_results_orientation(*orientation, cx);
+                        }
+                        FooterBarEvent::OpenPerformance => {
+                            this.open_performance_dialog(window, cx);
+                        }
+                        FooterBarEvent::OpenLocks => {
+                            this.open_locks_dialog(window, cx);
+                        }
+                        FooterBarEvent::OpenActivity => {
+                            this.open_activity_dialog(None, window, cx);
+                        }
+                        FooterBarEvent::OpenMigrations => {
+                            this.open_migrations_dialog(window, cx);
+                        }
+                        FooterBarEvent::SaveWorkspace => {
+                            this.open_save_workspace_dialog(window, cx);
+                        }
+                        FooterBarEvent::EnterZenMode => {
+                            this.toggle_zen_mode(window, cx);
+                        }
+                        FooterBarEvent::OpenNotifications => {
+                            open_notifications_dialog(&this.notifications_panel, window, cx);
+                        }
+                        FooterBarEvent::OpenUpdates => {
+                            open_update_dialog(window, cx);
+                        }
                     }
-                }
-                cx.notify();
-            }),
+                    cx.notify();
+                },
+            ),
             // Subscribe to history panel events
             cx.subscribe_in(
                 &history_panel,
@@ -83,6 +307,43 @@ impl Workspace {
                     HistoryEvent::LoadQuery(sql) => {
                         this.load_query_into_editor(sql.clone(), win, cx);
                     }
+                    HistoryEvent::OpenEntry(entry) => {
+                        this.open_history_entry(entry.clone(), win, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &jobs_panel,
+                window,
+                |this, _, event: &JobsEvent, win, cx| match event {
+                    JobsEvent::ShowResult(job_id) => {
+                        this.show_job_result(*job_id, cx);
+                    }
+                    JobsEvent::LoadQuery(sql) => {
+                        this.load_query_into_editor(sql.clone(), win, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &notifications_panel,
+                window,
+                |this, _, event: &NotificationsEvent, window, cx| match event {
+                    NotificationsEvent::Open(action) => {
+                        this.open_notification_action(*action, window, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &results_panel,
+                window,
+                |this, _, event: &ResultsEvent, window, cx| match event {
+                    ResultsEvent::RunQuery(sql) => {
+                        this.load_query_into_editor(sql.clone(), window, cx);
+                        this.execute_query(sql.clone(), window, cx);
+                    }
+                    ResultsEvent::ExplainError { sql, error } => {
+                        this.open_error_explanation(sql.clone(), error.clone(), window, cx);
+                    }
                 },
             ),
             cx.subscribe_in(
@@ -92,13 +353,17 @@ impl Workspace {
                     AgentPanelEvent::RunQuery(sql) => {
                         // Load into editor and execute
                         this.load_query_into_editor(sql.clone().to_string(), window, cx);
-                        this.execute_query(sql.clone().to_string(), cx);
+                        this.execute_query(sql.clone().to_string(), window, cx);
                     }
                 },
             ),
         ];
 
+        Self::load_results_orientation(cx);
+        Self::load_panel_layout(cx);
+
         Self {
+            window_id,
             header_bar,
             footer_bar,
             connection_manager,
@@ -106,99 +371,1842 @@ impl Workspace {
             editor,
             agent_panel,
             history_panel,
+            jobs_panel,
+            notifications_panel,
             results_panel,
             _subscriptions,
             connection_state: ConnectionStatus::Disconnected,
             show_tables: true,
             show_agent: false,
             show_history: false,
+            show_jobs: false,
+            foreground_job: None,
+            selected_table: None,
+            pending_table: None,
+            results_orientation: ResultsOrientation::default(),
+            panel_layout: PanelLayout::default(),
+            layout_generation: 0,
+            zen_mode: false,
+            zen_fullscreen: false,
+            _save_bounds_task: None,
+            _save_layout_task: None,
         }
     }
 
+    /// Restore the user's preferred results panel position once the app
+    /// store has loaded it.
+    fn load_results_orientation(cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let Ok(store) = AppStore::singleton().await else {
+                return;
+            };
+            let saved = store
+                .workspace_state()
+                .get_json::<ResultsOrientation>(RESULTS_ORIENTATION_KEY)
+                .await
+                .ok()
+                .flatten();
+            if let Some(orientation) = saved {
+                let _ = this.update(cx, |this, cx| {
+                    this.results_orientation = orientation;
+                    this.footer_bar.update(cx, |footer, cx| {
+                        footer.set_results_orientation(orientation, cx);
+                    });
+                    cx.notify();
+                });
+            }
+        })
+        .detach();
+    }
+
+    fn save_results_orientation(orientation: ResultsOrientation, cx: &mut Context<Self>) {
+        cx.spawn(async move |_this, _cx| {
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(RESULTS_ORIENTATION_KEY, &orientation)
+                    .await
+                {
+                    tracing::warn!("Failed to save results orientation: {}", e);
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Restore where the side panels were docked and how wide they were.
+    fn load_panel_layout(cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let Ok(store) = AppStore::singleton().await else {
+                return;
+            };
+            let saved = store
+                .workspace_state()
+                .get_json::<PanelLayout>(PANEL_LAYOUT_KEY)
+                .await
+                .ok()
+                .flatten();
+            if let Some(layout) = saved {
+                let _ = this.update(cx, |this, cx| {
+                    this.panel_layout = layout.normalized();
+                    cx.notify();
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// Persist the panel layout. Resizing reports new widths continuously,
+    /// so writes are debounced like the window bounds.
+    fn schedule_save_panel_layout(&mut self, cx: &mut Context<Self>) {
+        let layout = self.panel_layout.clone();
+        self._save_layout_task = Some(cx.spawn(async move |_this, cx| {
+            cx.background_executor()
+                .timer(std::time::Duration::from_millis(500))
+                .await;
+            if let Ok(store) = AppStore::singleton().await {
+                if let Err(e) = store
+                    .workspace_state()
+                    .set_json(PANEL_LAYOUT_KEY, &layout)
+                    .await
+                {
+                    tracing::warn!("Failed to save panel layout: {}", e);
+                }
+            }
+        }));
+    }
+
     pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
         cx.new(|cx| Self::new(window, cx))
     }
 
+    /// Persist the window placement for the current display configuration.
+    /// Bounds change continuously while dragging, so writes are debounced;
+    /// replacing the task cancels the previous pending save.
+    fn schedule_save_window_bounds(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let layout_key = display_layout_key(cx);
+        let bounds = SavedWindowBounds::from_window(window);
+        self._save_bounds_task = Some(cx.spawn(async move |_this, cx| {
+            cx.background_executor()
+                .timer(std::time::Duration::from_millis(500))
+                .await;
+            save_window_bounds(&layout_key, bounds).await;
+        }));
+    }
+
+    fn open_performance_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let panel = PerformancePanel::view(window, cx);
+        cx.subscribe_in(
+            &panel,
+            window,
+            |this, _, event: &PerformanceEvent, window, cx| match event {
+                PerformanceEvent::ReviewStatement(sql) => {
+                    this.load_query_into_editor(sql.clone(), window, cx);
+                    window.close_dialog(cx);
+                }
+                PerformanceEvent::OpenStatementStats => {
+                    window.close_dialog(cx);
+                    this.open_statement_stats_dialog(window, cx);
+                }
+            },
+        )
+        .detach();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title("Performance")
+                .width(px(860.))
+                .child(panel.clone())
+        });
+    }
+
+    /// Pick connections or databases to run `script` on, one after another.
+    fn open_batch_run_dialog(
+        &mut self,
+        script: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let driver = WindowConnection::driver(self.window_id, cx);
+        let script = match cx
+            .global::<VariablesState>()
+            .variables
+            .substitute(&driver, &script)
+        {
+            Ok(script) => script,
+            Err(e) => {
+                window.push_recorded_notification(
+                    (NotificationType::Error, SharedString::from(e.to_string())),
+                    cx,
+                );
+                return;
+            }
+        };
+
+        let panel = BatchRunPanel::view(script, window, cx);
+        cx.subscribe_in(
+            &panel,
+            window,
+            |this, _, event: &BatchRunEvent, window, cx| match event {
+                BatchRunEvent::ShowResults(label, results) => {
+                    this.results_panel.update(cx, |panel, cx| {
+                        panel.update_results(results.clone(), cx);
+                    });
+                    window.close_dialog(cx);
+                    window.push_toast(
+                        (
+                            NotificationType::Info,
+                            SharedString::from(format!("Showing the results of {}", label)),
+                        ),
+                        cx,
+                    );
+                }
+            },
+        )
+        .detach();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title("Run on Several Targets")
+                .width(px(720.))
+                .child(panel.clone())
+        });
+    }
+
+    fn open_locks_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let panel = LocksPanel::view(window, cx);
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog.title("Locks").width(px(860.)).child(panel.clone())
+        });
+    }
+
+    fn open_migrations_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let panel = MigrationsPanel::view(window, cx);
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title("Migrations")
+                .width(px(760.))
+                .child(panel.clone())
+        });
+    }
+
+    /// Open the session list, pointing out session `highlight` if given.
+    fn open_activity_dialog(
+        &mut self,
+        highlight: Option<i32>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let panel = ActivityPanel::view(highlight, window, cx);
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title("Activity")
+                .width(px(860.))
+                .child(panel.clone())
+        });
+    }
+
+    /// A warning per flagged session; clicking one opens its Activity row.
+    fn notify_transaction_alerts(
+        &mut self,
+        alerts: Vec<TransactionAlert>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let workspace = cx.entity().downgrade();
+        for alert in alerts {
+            let workspace = workspace.clone();
+            let pid = alert.pid;
+            let toast = Toast::new(NotificationType::Warning, alert.message())
+                .title("Transaction Alert")
+                .on_click(move |_, window, cx| {
+                    let _ = workspace.update(cx, |this, cx| {
+                        this.open_activity_dialog(Some(pid), window, cx);
+                    });
+                });
+            window.push_toast(toast, cx);
+        }
+    }
+
+    fn open_statement_stats_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let panel = StatementStatsPanel::view(window, cx);
+        cx.subscribe_in(
+            &panel,
+            window,
+            |this, _, event: &PerformanceEvent, window, cx| {
+                if let PerformanceEvent::ReviewStatement(sql) = event {
+                    this.load_query_into_editor(sql.clone(), window, cx);
+                    window.close_dialog(cx);
+                }
+            },
+        )
+        .detach();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title("Statement Statistics")
+                .width(px(960.))
+                .child(panel.clone())
+        });
+    }
+
+    fn snapshot(&self, cx: &App) -> WorkspaceSnapshot {
+        WorkspaceSnapshot {
+            connection_id: WindowConnection::of(self.window_id, cx)
+                .active_connection
+                .as_ref()
+                .map(|c| c.id),
+            editor_sql: self.editor.read(cx).query_text(cx),
+            editor_file: self.editor.read(cx).file_path().cloned(),
+            show_tables: self.show_tables,
+            show_agent: self.show_agent,
+            show_history: self.show_history,
+            show_jobs: self.show_jobs,
+            results_orientation: self.results_orientation,
+            panel_layout: Some(self.panel_layout.clone()),
+            selected_table: self.selected_table.clone(),
+            variables: cx.global::<VariablesState>().variables.clone(),
+        }
+    }
+
+    /// Ask for a new password after the server rejected `connection`'s,
+    /// then retry.
+    fn open_password_prompt(
+        &mut self,
+        connection: ConnectionInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let prompt = PasswordPrompt::view(connection, window, cx);
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let prompt = prompt.clone();
+            dialog
+                .title("Authentication Failed")
+                .width(px(440.))
+                .child(prompt.clone())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Connect"))
+                .on_ok(move |_, _window, cx| {
+                    let prompt = prompt.read(cx);
+                    let connection = prompt.connection().clone();
+                    let password = prompt.password(cx);
+                    let update_keychain = prompt.update_keychain();
+                    connect_with_password(
+                        window_id(window),
+                        &connection,
+                        password,
+                        update_keychain,
+                        cx,
+                    );
+                    true
+                })
+        });
+    }
+
+    /// Ask for a name and save the current layout as a named workspace.
+    fn open_save_workspace_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let snapshot = self.snapshot(cx);
+        let default_name = WindowConnection::of(self.window_id, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| c.name.clone())
+            .unwrap_or_default();
+        let name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Workspace name"));
+        name_input.update(cx, |input, cx| input.set_value(default_name, window, cx));
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let name_input = name_input.clone();
+            let snapshot = snapshot.clone();
+            dialog
+                .title("Save Workspace")
+                .width(px(400.))
+                .child(Input::new(&name_input))
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Save"))
+                .on_ok(move |_, window, cx| {
+                    let name = name_input.read(cx).value().trim().to_string();
+                    if name.is_empty() {
+                        window.push_toast(
+                            (NotificationType::Warning, "Enter a name for the workspace"),
+                            cx,
+                        );
+                        return false;
+                    }
+                    window.push_toast(
+                        (
+                            NotificationType::Success,
+                            SharedString::from(format!("Saved workspace \"{}\"", name)),
+                        ),
+                        cx,
+                    );
+                    save_workspace(name, snapshot.clone(), cx);
+                    true
+                })
+        });
+    }
+
+    /// Apply a named workspace's layout and editor contents. Its table is
+    /// selected once the connection is up.
+    fn restore_workspace(
+        &mut self,
+        saved: SavedWorkspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(snapshot) = saved.snapshot::<WorkspaceSnapshot>() else {
+            tracing::warn!("Workspace '{}' has an unreadable snapshot", saved.name);
+            return;
+        };
+
+        self.show_tables = snapshot.show_tables;
+        self.show_agent = snapshot.show_agent;
+        self.show_history = snapshot.show_history;
+        self.show_jobs = snapshot.show_jobs;
+        self.results_orientation = snapshot.results_orientation;
+        Self::save_results_orientation(snapshot.results_orientation, cx);
+        self.footer_bar.update(cx, |footer, cx| {
+            footer.set_results_orientation(snapshot.results_orientation, cx);
+            footer.set_panels(
+                snapshot.show_tables,
+                snapshot.show_agent,
+                snapshot.show_history,
+                snapshot.show_jobs,
+                cx,
+            );
+        });
+
+        if let Some(layout) = snapshot.panel_layout {
+            self.panel_layout = layout.normalized();
+            self.layout_generation += 1;
+            self.schedule_save_panel_layout(cx);
+        }
+
+        match snapshot.editor_file {
+            Some(path) => self.editor.update(cx, |editor, cx| {
+                editor.restore_file(path, snapshot.editor_sql, window, cx);
+            }),
+            None if !snapshot.editor_sql.is_empty() => {
+                self.load_query_into_editor(snapshot.editor_sql, window, cx);
+            }
+            None => {}
+        }
+
+        // Workspaces saved before variables existed keep the current ones
+        if !snapshot.variables.environments.is_empty() {
+            VariablesState::set(snapshot.variables, cx);
+        }
+
+        self.pending_table = snapshot
+            .selected_table
+            .map(|(schema, table)| (snapshot.connection_id, schema, table));
+        self.restore_pending_table(cx);
+        cx.notify();
+    }
+
+    /// Select the restored workspace's table once its connection is up and
+    /// the table list has it. Until then the table stays pending, as the
+    /// window may still be on the connection the workspace replaces.
+    fn restore_pending_table(&mut self, cx: &mut Context<Self>) {
+        let Some((connection_id, schema, name)) = self.pending_table.clone() else {
+            return;
+        };
+        let connection = WindowConnection::of(self.window_id, cx);
+        if connection.connection_state != ConnectionStatus::Connected
+            || connection_id
+                .is_some_and(|id| connection.active_connection.as_ref().map(|c| c.id) != Some(id))
+        {
+            return;
+        }
+
+        let Some(table) = EditorState::of(self.window_id, cx)
+            .tables
+            .iter()
+            .find(|t| t.table_schema == schema && t.table_name == name)
+            .cloned()
+        else {
+            return;
+        };
+        self.pending_table = None;
+        self.selected_table = Some((schema, name));
+        self.show_table_columns(table, cx);
+    }
+
     fn load_query_into_editor(&mut self, sql: String, window: &mut Window, cx: &mut App) {
         self.editor.update(cx, |editor, cx| {
             editor.set_query(sql, window, cx);
         });
     }
 
-    fn execute_query(&mut self, query: String, cx: &mut Context<Self>) {
+    /// Ask the agent why `sql` failed, offering to apply its fix to the
+    /// failed statement in the editor.
+    fn open_error_explanation(
+        &mut self,
+        sql: String,
+        error: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let view = ErrorExplanationView::view(sql.clone(), error, window, cx);
+        let editor = self.editor.clone();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let view = view.clone();
+            let editor = editor.clone();
+            let sql = sql.clone();
+            dialog
+                .title("Explain Error")
+                .width(px(640.))
+                .child(view.clone())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Apply Fix"))
+                .on_ok(move |_, window, cx| {
+                    let Some(fix) = view.read(cx).fix() else {
+                        window.push_toast("No fix to apply yet", cx);
+                        return false;
+                    };
+                    let applied =
+                        editor.update(cx, |editor, cx| editor.apply_fix(&sql, &fix, window, cx));
+                    if !applied {
+                        window.push_toast(
+                            (
+                                NotificationType::Warning,
+                                SharedString::from(
+                                    "The failed statement changed in the editor; \
+                                     copy the fix instead",
+                                ),
+                            ),
+                            cx,
+                        );
+                    }
+                    applied
+                })
+        });
+    }
+
+    /// Run an editor query. Queries with bind placeholders first open the
+    /// parameters dialog, prefilled with the values used last time.
+    fn execute_query(&mut self, query: String, window: &mut Window, cx: &mut Context<Self>) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        // `{{name}}` variables are filled in first, so an undefined one is
+        // reported as such rather than as a syntax error
+        let driver = WindowConnection::driver(self.window_id, cx);
+        let query = match cx
+            .global::<VariablesState>()
+            .variables
+            .substitute(&driver, &query)
+        {
+            Ok(query) => query,
+            Err(e) => {
+                self.results_panel.update(cx, |results, cx| {
+                    results.update_result(
+                        QueryExecutionResult::Error(ErrorResult {
+                            message: e.to_string(),
+                            execution_time_ms: 0,
+                        }),
+                        cx,
+                    );
+                    results.set_query(query);
+                });
+                return;
+            }
+        };
+
+        cx.spawn_in(window, async move |this, cx| {
+            let param_query = match db_manager.driver().await {
+                Some(driver) => parse_params(&driver, &query),
+                None => None,
+            };
+            let Some(param_query) = param_query else {
+                let estimate = large_result_estimate(&db_manager, &query).await;
+                let _ = this.update_in(cx, |this, window, cx| match estimate {
+                    Some(rows) => this.confirm_large_result(query, rows, window, cx),
+                    None => this.run_query(query, None, window, cx),
+                });
+                return;
+            };
+
+            let types = match db_manager.describe_params(&param_query).await {
+                Ok(types) => types,
+                Err(e) => {
+                    tracing::warn!("Failed to describe query parameters: {}", e);
+                    vec![None; param_query.labels.len()]
+                }
+            };
+
+            let mut saved = HashMap::new();
+            if let Ok(store) = AppStore::singleton().await {
+                if let Ok(Some(values)) = store
+                    .workspace_state()
+                    .get_json::<HashMap<String, Option<String>>>(&query_params_key(&query))
+                    .await
+                {
+                    saved = values;
+                }
+            }
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.show_query_params_dialog(query, param_query, types, saved, window, cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Offer to add a LIMIT before fetching every row of a large result.
+    fn confirm_large_result(
+        &mut self,
+        query: String,
+        estimated_rows: u64,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let workspace = cx.entity().downgrade();
+        let message = format!(
+            "This query is estimated to return about {} rows, and no row limit is set. \
+             Fetching them all may take a while and use a lot of memory.",
+            format_row_count(estimated_rows)
+        );
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let limited = with_limit(&query, DEFAULT_MAX_ROWS);
+            let (run_limited, run_all) = (workspace.clone(), workspace.clone());
+            let query = query.clone();
+            dialog.title("Large Result").width(px(480.)).child(
+                v_flex()
+                    .gap_3()
+                    .child(Label::new(message.clone()).text_sm())
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .justify_end()
+                            .child(
+                                Button::new("cancel-large-result")
+                                    .label("Cancel")
+                                    .small()
+                                    .ghost()
+                                    .on_click(|_, window, cx| window.close_dialog(cx)),
+                            )
+                            .child(
+                                Button::new("run-large-result")
+                                    .label("Run Anyway")
+                                    .small()
+                                    .on_click(move |_, window, cx| {
+                                        window.close_dialog(cx);
+                                        let _ = run_all.update(cx, |this, cx| {
+                                            this.run_query(query.clone(), None, window, cx);
+                                        });
+                                    }),
+                            )
+                            .child(
+                                Button::new("limit-large-result")
+                                    .label(format!("Add LIMIT {}", DEFAULT_MAX_ROWS))
+                                    .small()
+                                    .primary()
+                                    .on_click(move |_, window, cx| {
+                                        window.close_dialog(cx);
+                                        let _ = run_limited.update(cx, |this, cx| {
+                                            this.run_query(limited.clone(), None, window, cx);
+                                        });
+                                    }),
+                            ),
+                    ),
+            )
+        });
+    }
+
+    fn show_query_params_dialog(
+        &mut self,
+        query: String,
+        param_query: ParamQuery,
+        types: Vec<Option<String>>,
+        saved: HashMap<String, Option<String>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let form = QueryParamsForm::view(param_query, types, saved, window, cx);
+        let workspace = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            let workspace = workspace.clone();
+            let query = query.clone();
+            dialog
+                .title("Query Parameters")
+                .width(px(560.))
+                .child(form.clone())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Run"))
+                .on_ok(move |_, window, cx| {
+                    let bound = form.read(cx).bound(cx);
+                    let _ = workspace.update(cx, |this, cx| {
+                        this.run_query(query.clone(), Some(bound), window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    /// Run a query as a background job. The latest job owns the results
+    /// panel; one that finishes after another was started only notifies.
+    fn run_query(
+        &mut self,
+        query: String,
+        params: Option<BoundParams>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let job_id = Uuid::new_v4();
+        self.foreground_job = Some(job_id);
+
         // Set editor to executing state
         self.editor.update(cx, |editor, cx| {
             editor.set_executing(true, cx);
             cx.notify();
         });
 
-        tracing::debug!("execute_query");
-
         // Get database manager from global state
-        let db_manager = cx.global::<ConnectionState>().db_manager.clone();
-        tracing::debug!("execute_query - db_manager");
-        let active_connection = cx.global::<ConnectionState>().active_connection.clone();
-        tracing::debug!("execute_query - active_connection");
+        let connection = WindowConnection::of(self.window_id, cx);
+        let db_manager = connection.db_manager.clone();
+        let active_connection = connection.active_connection.clone();
 
-        cx.spawn(async move |this, cx| {
-            tracing::debug!("execute_query spawn - before execute_query_enhanced");
-            let result = db_manager.execute_query_enhanced(&query).await;
-            tracing::debug!("execute_query_enhanced result");
-            // Extract execution info before moving result
-            let (execution_time_ms, rows_affected) = match &result {
-                QueryExecutionResult::Modified(modified) => (
-                    Some(modified.execution_time_ms as i64),
-                    Some(modified.rows_affected as i64),
-                ),
-                QueryExecutionResult::Select(r) => (Some(r.execution_time_ms as i64), None),
-                QueryExecutionResult::Error(err) => (Some(err.execution_time_ms as i64), None),
+        let connection_name = active_connection
+            .as_ref()
+            .map(|c| c.name.clone())
+            .unwrap_or_default();
+        JobsState::update(self.window_id, cx, |state, _cx| {
+            state.start(job_id, query.clone(), connection_name.clone());
+        });
+
+        let hooks = HooksState::active(self.window_id, cx);
+        let hook_context = HookContext {
+            stage: HookStage::Before,
+            connection: connection_name,
+            database: active_connection
+                .as_ref()
+                .map(|c| c.database.clone())
+                .unwrap_or_default(),
+            query: query.clone(),
+            outcome: None,
+        };
+
+        cx.spawn_in(window, async move |this, cx| {
+            tracing::debug!("run_query spawn - job {}", job_id);
+            // Scripts run statement by statement, each with its own result;
+            // a failing before hook stops the query from running.
+            let JobRun {
+                statements,
+                results,
+                notices,
+                after_error,
+                transaction_open,
+                ..
+            } = db_manager
+                .run_job(
+                    job_id,
+                    &query,
+                    params.as_ref(),
+                    hooks.is_active().then_some((&hooks, &hook_context)),
+                )
+                .await;
+            // The last result is the failed statement, or the script's end.
+            let Some(result) = results.last().cloned() else {
+                return;
             };
+            let labelled: Vec<(String, QueryExecutionResult)> =
+                statements.into_iter().zip(results).collect();
 
-            this.update(cx, |this, cx| {
-                // Update results panel
-                this.results_panel.update(cx, |results, cx| {
-                    results.update_result(result, cx);
-                });
+            let execution_time_ms = match &result {
+                QueryExecutionResult::Modified(modified) => modified.execution_time_ms,
+                QueryExecutionResult::Select(r) => r.execution_time_ms,
+                QueryExecutionResult::Error(err) => err.execution_time_ms,
+            };
+            let succeeded = !matches!(result, QueryExecutionResult::Error(_));
+
+            // One history entry per statement of a script, linked to this job
+            let finished_at = chrono::Utc::now();
+            let history: Vec<QueryHistoryEntry> = match &active_connection {
+                Some(conn) if labelled.len() > 1 => labelled
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, (sql, result))| {
+                        history_entry(conn.id, job_id, Some(ix as i64), sql, result, finished_at)
+                    })
+                    .collect(),
+                Some(conn) => vec![history_entry(
+                    conn.id,
+                    job_id,
+                    None,
+                    &query,
+                    &result,
+                    finished_at,
+                )],
+                None => vec![],
+            };
 
-                // Set editor back to normal state
+            this.update_in(cx, |this, window, cx| {
+                let foreground = this.foreground_job == Some(job_id);
+                let seen = foreground || this.show_jobs;
+
+                // Annotate each statement with how this run went
                 this.editor.update(cx, |editor, cx| {
-                    editor.set_executing(false, cx);
+                    if labelled.is_empty() {
+                        editor.record_run(
+                            &query,
+                            StatementRun::from_result(&result, finished_at),
+                            cx,
+                        );
+                    }
+                    for (sql, result) in &labelled {
+                        editor.record_run(sql, StatementRun::from_result(result, finished_at), cx);
+                    }
                 });
 
+                // DDL that ran joins the change script while recording
+                if labelled.is_empty() {
+                    if succeeded {
+                        ChangeRecorderState::record(this.window_id, [query.as_str()], cx);
+                    }
+                } else {
+                    ChangeRecorderState::record(
+                        this.window_id,
+                        labelled
+                            .iter()
+                            .filter(|(_, result)| !matches!(result, QueryExecutionResult::Error(_)))
+                            .map(|(sql, _)| sql.as_str()),
+                        cx,
+                    );
+                }
+
+                // Kept with the job so history can reopen a statement's tab
+                let statement_results = if labelled.len() > 1 {
+                    labelled.clone()
+                } else {
+                    vec![]
+                };
+
+                if foreground {
+                    // Update results panel
+                    this.results_panel.update(cx, |results, cx| {
+                        if labelled.len() > 1 {
+                            results.update_results(labelled, cx);
+                        } else {
+                            let sql = labelled.first().map_or(&query, |(sql, _)| sql);
+                            results.update_result(result.clone(), cx);
+                            results.set_query(sql.clone());
+                        }
+                        results.set_messages(notices.clone(), cx);
+                    });
+
+                    // Set editor back to normal state
+                    this.foreground_job = None;
+                    this.editor.update(cx, |editor, cx| {
+                        editor.set_executing(false, cx);
+                    });
+                }
+
+                JobsState::update(this.window_id, cx, |state, _cx| {
+                    state.finish(job_id, result, notices, seen);
+                    if let Some(job) = state.get_mut(job_id) {
+                        job.statement_results = statement_results;
+                    }
+                });
+                if let Some(open) = transaction_open {
+                    WindowConnection::update(this.window_id, cx, |state, _cx| {
+                        state.transaction_open = open;
+                    });
+                }
+
+                if succeeded {
+                    this.editor.read(cx).remember_query(&query);
+                }
+
+                if let Some(e) = &after_error {
+                    window.push_toast(
+                        (
+                            NotificationType::Warning,
+                            SharedString::from(format!("After-query hook failed: {}", e)),
+                        ),
+                        cx,
+                    );
+                }
+
+                if !foreground {
+                    let status = JobsState::of(this.window_id, cx)
+                        .get(job_id)
+                        .map(|job| job.status);
+                    let notification = match status {
+                        Some(JobStatus::Failed) => (
+                            NotificationType::Error,
+                            SharedString::from("Background query failed. See Jobs."),
+                        ),
+                        Some(JobStatus::Cancelled) => (
+                            NotificationType::Info,
+                            SharedString::from("Background query cancelled."),
+                        ),
+                        _ => (
+                            NotificationType::Success,
+                            SharedString::from(format!(
+                                "Background query finished in {}ms. See Jobs.",
+                                execution_time_ms
+                            )),
+                        ),
+                    };
+                    NotificationsState::push(
+                        NotificationSource::Job,
+                        notification.1.to_string(),
+                        Some(NotificationAction::ShowJobs),
+                        cx,
+                    );
+                    window.push_toast(notification, cx);
+                }
+
                 cx.notify();
             })
             .ok();
 
-            if let Some(conn) = active_connection {
-                if let Ok(store) = AppStore::singleton().await {
-                    let _ = store
-                        .history()
-                        .record(
-                            &conn.id,
-                            &query.clone(),
-                            execution_time_ms.unwrap_or(0),
-                            rows_affected,
-                            true,
-                            None,
-                        )
-                        .await;
+            if let Ok(store) = AppStore::singleton().await {
+                if let Some(bound) = &params {
+                    let values = bound.query.values_by_label(&bound.values);
+                    if let Err(e) = store
+                        .workspace_state()
+                        .set_json(&query_params_key(&query), &values)
+                        .await
+                    {
+                        tracing::warn!("Failed to save query parameters: {}", e);
+                    }
+                }
+
+                if let Err(e) = store.history().record_entries(&history).await {
+                    tracing::warn!("Failed to record query history: {}", e);
                 }
             }
         })
         .detach();
     }
 
-    fn handle_table_event(&mut self, event: &TableEvent, cx: &mut Context<Self>) {
+    /// Follow a notification's button out of the notification center.
+    fn open_notification_action(
+        &mut self,
+        action: NotificationAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.close_dialog(cx);
+        match action {
+            NotificationAction::ShowJobs => {
+                self.show_jobs = true;
+                self.show_agent = false;
+                self.show_history = false;
+                JobsState::update(self.window_id, cx, |state, _cx| {
+                    state.mark_all_seen();
+                });
+                let tables = self.show_tables;
+                self.footer_bar.update(cx, |footer, cx| {
+                    footer.set_panels(tables, false, false, true, cx);
+                });
+            }
+            NotificationAction::ShowUpdates => open_update_dialog(window, cx),
+            NotificationAction::ShowLogs => open_log_viewer(cx),
+        }
+        cx.notify();
+    }
+
+    /// Load a finished job's result and messages into the results panel.
+    fn show_job_result(&mut self, job_id: Uuid, cx: &mut Context<Self>) {
+        let Some((result, notices)) = JobsState::update(self.window_id, cx, |state, _cx| {
+            let job = state.get_mut(job_id)?;
+            job.seen = true;
+            Some((job.result.clone()?, job.notices.clone()))
+        }) else {
+            return;
+        };
+
+        self.results_panel.update(cx, |results, cx| {
+            results.update_result(result, cx);
+            results.set_messages(notices, cx);
+        });
+        cx.notify();
+    }
+
+    /// Load a history entry's SQL along with the result of its run: the
+    /// job's own result while Jobs still lists it, else what history kept.
+    fn open_history_entry(
+        &mut self,
+        entry: QueryHistoryEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.load_query_into_editor(entry.sql.clone(), window, cx);
+
+        let job = entry
+            .run_id
+            .and_then(|id| JobsState::of(self.window_id, cx).get(id))
+            .map(|job| {
+                (
+                    job.statement_results.clone(),
+                    job.result.clone(),
+                    job.notices.clone(),
+                )
+            });
+        let captured = match job {
+            Some((statements, _, notices)) if !statements.is_empty() => {
+                let statement = entry.statement_index.unwrap_or(0).max(0) as usize;
+                self.results_panel.update(cx, |results, cx| {
+                    results.update_results_at(statements, statement, cx);
+                    results.set_messages(notices, cx);
+                });
+                false
+            }
+            Some((_, Some(result), notices)) => {
+                self.results_panel.update(cx, |results, cx| {
+                    results.update_result(result, cx);
+                    results.set_query(entry.sql.clone());
+                    results.set_messages(notices, cx);
+                });
+                false
+            }
+            _ => {
+                self.results_panel.update(cx, |results, cx| {
+                    results.update_result(captured_result(&entry), cx);
+                    results.set_query(entry.sql.clone());
+                });
+                true
+            }
+        };
+
+        if captured && entry.success && !entry.columns().is_empty() {
+            let rows = entry.rows_affected.unwrap_or(0);
+            window.push_toast(
+                (
+                    NotificationType::Info,
+                    SharedString::from(format!(
+                        "History keeps columns, not rows. Run the query again to see its {} row{}.",
+                        rows,
+                        if rows == 1 { "" } else { "s" }
+                    )),
+                ),
+                cx,
+            );
+        }
+        cx.notify();
+    }
+
+    fn handle_table_event(
+        &mut self,
+        event: &TableEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         match event {
             TableEvent::TableSelected(table) => {
+                self.selected_table = Some((table.table_schema.clone(), table.table_name.clone()));
                 self.show_table_columns(table.clone(), cx);
             }
+            TableEvent::CompareData(table) => {
+                self.open_compare_dialog(table.clone(), window, cx);
+            }
+            TableEvent::NewRow(table) => {
+                self.open_new_row_dialog(table.clone(), window, cx);
+            }
+            TableEvent::ManageSequences => {
+                let panel = SequencesPanel::view(window, cx);
+                window.open_dialog(cx, move |dialog, _win, _cx| {
+                    dialog
+                        .title("Sequences")
+                        .width(px(720.))
+                        .child(panel.clone())
+                });
+            }
+            TableEvent::ManageExtensions => {
+                let panel = ExtensionsPanel::view(window, cx);
+                window.open_dialog(cx, move |dialog, _win, _cx| {
+                    dialog
+                        .title("Extensions")
+                        .width(px(720.))
+                        .child(panel.clone())
+                });
+            }
+            TableEvent::CrossDatabase => {
+                self.open_foreign_server_dialog(window, cx);
+            }
+            TableEvent::EditStructure(table) => {
+                let editor = StructureEditor::view(table.clone(), window, cx);
+                window.open_dialog(cx, move |dialog, _win, _cx| {
+                    dialog
+                        .title("Edit Structure")
+                        .width(px(760.))
+                        .child(editor.clone())
+                });
+            }
+            TableEvent::AddConstraint(table) => {
+                self.open_constraint_dialog(table.clone(), window, cx);
+            }
+            TableEvent::BatchScript(tables) => {
+                self.open_batch_script_dialog(tables.clone(), window, cx);
+            }
+            TableEvent::ShowViewSource(table) => {
+                self.show_view_source(table.clone(), window, cx);
+            }
+            TableEvent::ShowTableDetails(table) => {
+                self.show_table_details(table.clone(), window, cx);
+            }
+            TableEvent::RefreshMaterializedView(table) => {
+                self.refresh_materialized_view(table.clone(), window, cx);
+            }
+            TableEvent::RoutineSelected(routine) => {
+                self.show_routine_source(routine.clone(), window, cx);
+            }
+            TableEvent::SearchDatabase => {
+                self.open_object_search(window, cx);
+            }
+            TableEvent::GenerateScript(table, template) => {
+                self.generate_script(table.clone(), *template, window, cx);
+            }
+            TableEvent::UserTypeSelected(user_type) => {
+                self.open_user_type_dialog(user_type.clone(), window, cx);
+            }
+        }
+    }
+
+    /// Load and run the query in quick slot `slot` of the active connection.
+    fn run_quick_slot(&mut self, slot: u8, window: &mut Window, cx: &mut Context<Self>) {
+        if self.connection_state != ConnectionStatus::Connected {
+            return;
+        }
+        let Some(connection_id) = WindowConnection::of(self.window_id, cx)
+            .active_connection
+            .as_ref()
+            .map(|c| c.id)
+        else {
+            return;
+        };
+
+        match cx.global::<QuickSlotsState>().slot(connection_id, slot) {
+            Some(quick_slot) => {
+                let sql = quick_slot.sql.clone();
+                self.load_query_into_editor(sql.clone(), window, cx);
+                self.execute_query(sql, window, cx);
+            }
+            None => {
+                let message: SharedString = format!(
+                    "{} is empty. Right-click a query in History to assign it.",
+                    quick_slot_shortcut(slot)
+                )
+                .into();
+                window.push_toast((NotificationType::Info, message), cx);
+            }
+        }
+    }
+
+    /// The "Search database" dialog; picking a result shows it in the
+    /// table tree.
+    fn open_object_search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.connection_state != ConnectionStatus::Connected {
+            return;
         }
+
+        let search = ObjectSearch::view(window, cx);
+        cx.subscribe_in(
+            &search,
+            window,
+            |this, _, event: &ObjectSearchEvent, window, cx| match event {
+                ObjectSearchEvent::Selected(hit) => {
+                    window.close_dialog(cx);
+                    this.show_tables = true;
+                    this.tables_tree
+                        .update(cx, |tree, cx| tree.reveal(hit, window, cx));
+                    cx.notify();
+                }
+            },
+        )
+        .detach();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            dialog
+                .title("Search Database")
+                .width(px(640.))
+                .child(search.clone())
+        });
+    }
+
+    fn show_view_source(&mut self, table: TableInfo, window: &mut Window, cx: &mut Context<Self>) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager
+                .get_view_definition(&table.table_schema, &table.table_name)
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| match result {
+                Ok(source) => {
+                    let title = format!("{}.{}", table.table_schema, table.table_name);
+                    let details = vec![table.table_type.to_lowercase()];
+                    this.open_source_dialog(title, details, source, window, cx);
+                }
+                Err(e) => {
+                    window.push_recorded_notification(
+                        (
+                            NotificationType::Error,
+                            SharedString::from(format!("Failed to load view source: {}", e)),
+                        ),
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn show_table_details(
+        &mut self,
+        table: TableInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |_this, cx| {
+            let result = db_manager
+                .get_table_details(&table.table_schema, &table.table_name)
+                .await;
+
+            let _ = cx.update(|window, cx| match result {
+                Ok(details) => {
+                    let title = format!("{}.{}", table.table_schema, table.table_name);
+                    let rows = details.rows();
+                    window.open_dialog(cx, move |dialog, _win, cx| {
+                        let content = if rows.is_empty() {
+                            v_flex().child(
+                                Label::new(
+                                    "Not a foreign table, and not part of an inheritance tree",
+                                )
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground),
+                            )
+                        } else {
+                            v_flex().gap_2().children(rows.iter().map(|(label, value)| {
+                                h_flex()
+                                    .gap_2()
+                                    .items_start()
+                                    .child(
+                                        div().w(px(120.)).flex_shrink_0().child(
+                                            Label::new(*label)
+                                                .text_sm()
+                                                .text_color(cx.theme().muted_foreground),
+                                        ),
+                                    )
+                                    .child(
+                                        div()
+                                            .flex_1()
+                                            .font_family("monospace")
+                                            .text_sm()
+                                            .child(value.clone()),
+                                    )
+                            }))
+                        };
+                        dialog.title(title.clone()).width(px(560.)).child(content)
+                    });
+                }
+                Err(e) => {
+                    window.push_recorded_notification(
+                        (
+                            NotificationType::Error,
+                            SharedString::from(format!("Failed to load table details: {}", e)),
+                        ),
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn show_routine_source(
+        &mut self,
+        routine: RoutineInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.get_routine_definition(&routine).await;
+
+            let _ = this.update_in(cx, |this, window, cx| match result {
+                Ok(source) => {
+                    let mut details = vec![format!(
+                        "{}: {}",
+                        routine.routine_type.to_lowercase(),
+                        if routine.arguments.is_empty() {
+                            "no arguments"
+                        } else {
+                            &routine.arguments
+                        }
+                    )];
+                    if let Some(result_type) = &routine.result_type {
+                        details.push(format!("returns {}", result_type));
+                    }
+                    this.open_source_dialog(routine.signature(), details, source, window, cx);
+                }
+                Err(e) => {
+                    window.push_recorded_notification(
+                        (
+                            NotificationType::Error,
+                            SharedString::from(format!("Failed to load source: {}", e)),
+                        ),
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Read-only source view with an option to copy it into the editor.
+    fn open_source_dialog(
+        &mut self,
+        title: String,
+        details: Vec<String>,
+        source: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let workspace = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let workspace = workspace.clone();
+            let source_to_open = source.clone();
+            dialog
+                .title(title.clone())
+                .width(px(720.))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .children(details.iter().map(|d| {
+                            Label::new(d.clone())
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                        }))
+                        .child(
+                            div()
+                                .id("object-source")
+                                .max_h(px(480.))
+                                .overflow_y_scroll()
+                                .p_2()
+                                .rounded(cx.theme().radius)
+                                .bg(cx.theme().secondary)
+                                .font_family("monospace")
+                                .text_xs()
+                                .child(source.clone()),
+                        ),
+                )
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Open in Editor"))
+                .on_ok(move |_, window, cx| {
+                    let _ = workspace.update(cx, |this, cx| {
+                        this.load_query_into_editor(source_to_open.clone(), window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn refresh_materialized_view(
+        &mut self,
+        table: TableInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |_this, cx| {
+            let result = db_manager
+                .refresh_materialized_view(&table.table_schema, &table.table_name)
+                .await;
+
+            let _ = cx.update(|window, cx| {
+                let notification = match result {
+                    Ok(()) => (
+                        NotificationType::Success,
+                        SharedString::from(format!(
+                            "Refreshed {}.{}",
+                            table.table_schema, table.table_name
+                        )),
+                    ),
+                    Err(e) => (
+                        NotificationType::Error,
+                        SharedString::from(format!("Refresh failed: {}", e)),
+                    ),
+                };
+                window.push_recorded_notification(notification, cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Load a statement template for `table`, built from its columns, into
+    /// the editor.
+    fn generate_script(
+        &mut self,
+        table: TableInfo,
+        template: ScriptTemplate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let driver = db_manager.driver().await;
+            let info = db_manager
+                .get_table_edit_info(&table.table_schema, &table.table_name)
+                .await;
+            let sql = match (driver, info) {
+                (Some(driver), Ok(info)) => script_template_sql(&driver, &info, template),
+                (None, _) => Err(anyhow::anyhow!("Database not connected")),
+                (_, Err(e)) => Err(e),
+            };
+
+            let _ = this.update_in(cx, |this, window, cx| match sql {
+                Ok(sql) => this.load_query_into_editor(sql, window, cx),
+                Err(e) => {
+                    window.push_recorded_notification(
+                        (
+                            NotificationType::Error,
+                            SharedString::from(format!(
+                                "Failed to generate {}: {}",
+                                template.label(),
+                                e
+                            )),
+                        ),
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Describe an enum, composite type or domain. For enums the dialog
+    /// generates an `ADD VALUE` statement and loads it into the editor.
+    fn open_user_type_dialog(
+        &mut self,
+        user_type: UserTypeInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let title = format!("{}.{}", user_type.type_schema, user_type.type_name);
+        let details = UserTypeDetails::view(user_type, window, cx);
+        let workspace = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let details = details.clone();
+            let workspace = workspace.clone();
+            let dialog = dialog
+                .title(title.clone())
+                .width(px(560.))
+                .child(details.clone());
+            if !details.read(cx).is_enum() {
+                return dialog;
+            }
+
+            dialog
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Open in Editor"))
+                .on_ok(move |_, window, cx| {
+                    let script = details.read(cx).script(cx);
+                    if script.is_empty() {
+                        window
+                            .push_toast((NotificationType::Warning, "Enter the label to add"), cx);
+                        return false;
+                    }
+                    let _ = workspace.update(cx, |this, cx| {
+                        this.load_query_into_editor(script, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    /// Generate COMMENT / GRANT statements for several tables and load
+    /// them into the editor for review.
+    fn open_batch_script_dialog(
+        &mut self,
+        tables: Vec<TableInfo>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let form = BatchScriptForm::view(tables, window, cx);
+        let workspace = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            let workspace = workspace.clone();
+            dialog
+                .title("Batch Comment / Grant")
+                .width(px(640.))
+                .child(form.clone())
+                .confirm()
+                .on_ok(move |_, window, cx| {
+                    let script = form.read(cx).script(cx);
+                    if script.is_empty() {
+                        window.push_toast(
+                            (
+                                NotificationType::Warning,
+                                "Pick at least one privilege and a grantee",
+                            ),
+                            cx,
+                        );
+                        return false;
+                    }
+                    let _ = workspace.update(cx, |this, cx| {
+                        this.load_query_into_editor(script, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn open_constraint_dialog(
+        &mut self,
+        table: TableInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let form = ConstraintForm::view(table, window, cx);
+        let workspace = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            let workspace = workspace.clone();
+            dialog
+                .title("Add Constraint")
+                .width(px(640.))
+                .child(form.clone())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text("Add Constraint"))
+                .on_ok(move |_, window, cx| match form.read(cx).statements(cx) {
+                    Ok(statements) => {
+                        let _ = workspace.update(cx, |this, cx| {
+                            this.run_ddl(statements, window, cx);
+                        });
+                        true
+                    }
+                    Err(e) => {
+                        window.push_toast(
+                            (NotificationType::Warning, SharedString::from(e.to_string())),
+                            cx,
+                        );
+                        false
+                    }
+                })
+        });
+    }
+
+    /// Run generated DDL one statement at a time, stopping at the first
+    /// failure, and report the outcome.
+    fn run_ddl(&mut self, statements: Vec<String>, window: &mut Window, cx: &mut Context<Self>) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |_this, cx| {
+            let total = statements.len();
+            let mut applied = vec![];
+            let mut failure = None;
+            for (ix, sql) in statements.into_iter().enumerate() {
+                match db_manager.execute_query_enhanced(&sql).await {
+                    QueryExecutionResult::Error(e) => {
+                        failure = Some(format!("Statement {} of {}: {}", ix + 1, total, e.message));
+                        break;
+                    }
+                    _ => applied.push(sql),
+                }
+            }
+
+            let _ = cx.update(|window, cx| {
+                ChangeRecorderState::record(
+                    window_id(window),
+                    applied.iter().map(String::as_str),
+                    cx,
+                );
+                let notification = match failure {
+                    Some(error) => (NotificationType::Error, SharedString::from(error)),
+                    None => (
+                        NotificationType::Success,
+                        SharedString::from(applied.join("\n")),
+                    ),
+                };
+                window.push_recorded_notification(notification, cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Generate a foreign server setup for querying another saved
+    /// connection and load it into the editor for review.
+    fn open_foreign_server_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let form = ForeignServerForm::view(window, cx);
+        let workspace = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            let workspace = workspace.clone();
+            dialog
+                .title("Query Another Database")
+                .width(px(640.))
+                .child(form.clone())
+                .confirm()
+                .on_ok(move |_, window, cx| {
+                    let script = form.read(cx).script(cx);
+                    if script.is_empty() {
+                        window.push_toast(
+                            (
+                                NotificationType::Warning,
+                                "Pick an available tool, a connection and the names to use",
+                            ),
+                            cx,
+                        );
+                        return false;
+                    }
+                    let _ = workspace.update(cx, |this, cx| {
+                        this.load_query_into_editor(script, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    fn open_new_row_dialog(
+        &mut self,
+        table: TableInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager
+                .get_table_edit_info(&table.table_schema, &table.table_name)
+                .await;
+
+            let _ = this.update_in(cx, |this, window, cx| match result {
+                Ok(info) => this.show_new_row_dialog(info, window, cx),
+                Err(e) => {
+                    window.push_recorded_notification(
+                        (
+                            NotificationType::Error,
+                            SharedString::from(format!("Failed to load table columns: {}", e)),
+                        ),
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn show_new_row_dialog(
+        &mut self,
+        info: TableEditInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let title = format!("New Row in {}.{}", info.table_schema, info.table_name);
+        let form = NewRowForm::view(info, window, cx);
+        let workspace = cx.entity().downgrade();
+        let window_id = self.window_id;
+
+        window.open_dialog(cx, move |dialog, _win, cx| {
+            let form = form.clone();
+            let workspace = workspace.clone();
+            let ok_text = match form.read(cx).reviewed() {
+                Some(_) => "Insert",
+                None => "Review",
+            };
+            dialog
+                .title(title.clone())
+                .width(px(560.))
+                .child(form.clone())
+                .confirm()
+                .button_props(DialogButtonProps::default().ok_text(ok_text))
+                .on_ok(move |_, window, cx| {
+                    // The INSERT runs once it has been shown
+                    if let Some(statement) = form.read(cx).reviewed().cloned() {
+                        let _ = workspace.update(cx, |this, cx| {
+                            this.insert_row(statement, window, cx);
+                        });
+                        return true;
+                    }
+
+                    let missing = form.read(cx).missing_required(cx);
+                    if !missing.is_empty() {
+                        window.push_toast(
+                            (
+                                NotificationType::Warning,
+                                SharedString::from(format!("Required: {}", missing.join(", "))),
+                            ),
+                            cx,
+                        );
+                        return false;
+                    }
+                    let Some(driver) = WindowConnection::of(window_id, cx)
+                        .active_connection
+                        .as_ref()
+                        .map(|connection| connection.driver)
+                    else {
+                        return false;
+                    };
+                    let statement = {
+                        let form = form.read(cx);
+                        build_insert(&driver, &form.info, &form.values(cx))
+                    };
+                    form.update(cx, |form, cx| form.review(statement, cx));
+                    false
+                })
+        });
+    }
+
+    /// Run the parameterized INSERT of a new row, showing the outcome in
+    /// the results panel.
+    fn insert_row(
+        &mut self,
+        statement: ParamStatement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = db_manager.execute_statement(&statement).await;
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                let (notification_type, message): (_, SharedString) = match &result {
+                    QueryExecutionResult::Error(e) => {
+                        (NotificationType::Error, e.message.clone().into())
+                    }
+                    _ => (NotificationType::Success, "Row inserted".into()),
+                };
+                window.push_recorded_notification((notification_type, message), cx);
+                this.results_panel.update(cx, |results, cx| {
+                    results.update_result(result, cx);
+                });
+            });
+        })
+        .detach();
+    }
+
+    fn open_compare_dialog(
+        &mut self,
+        table: TableInfo,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let form = CompareDataForm::view(table, window, cx);
+        let workspace = cx.entity().downgrade();
+
+        window.open_dialog(cx, move |dialog, _win, _cx| {
+            let form = form.clone();
+            let workspace = workspace.clone();
+            dialog
+                .title("Compare Table Data")
+                .child(form.clone())
+                .confirm()
+                .on_ok(move |_, window, cx| {
+                    let (table, target, with_script) = {
+                        let form = form.read(cx);
+                        (
+                            form.table.clone(),
+                            form.selected_target(cx),
+                            form.with_script(),
+                        )
+                    };
+                    let Some(target) = target else {
+                        window.push_toast(
+                            (
+                                NotificationType::Warning,
+                                "Select a connection to compare against",
+                            ),
+                            cx,
+                        );
+                        return false;
+                    };
+                    let _ = workspace.update(cx, |this, cx| {
+                        this.compare_table_data(table, target, with_script, window, cx);
+                    });
+                    true
+                })
+        });
+    }
+
+    /// Compare the rows of `table` between the active connection and
+    /// `target`, then load the report (and sync script) into the editor.
+    fn compare_table_data(
+        &mut self,
+        table: TableInfo,
+        mut target: ConnectionInfo,
+        with_script: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
+
+        window.push_toast(
+            (
+                NotificationType::Info,
+                SharedString::from(format!(
+                    "Comparing {}.{} with {}...",
+                    table.table_schema, table.table_name, target.name
+                )),
+            ),
+            cx,
+        );
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = match ConnectionsRepository::get_connection_password(&target.id) {
+                Ok(password) => {
+                    target.password = password;
+                    db_manager
+                        .compare_table_data(
+                            &target,
+                            &table.table_schema,
+                            &table.table_name,
+                            with_script,
+                        )
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            let _ = this.update_in(cx, |this, window, cx| match result {
+                Ok(report) => {
+                    let notification_type = if report.is_identical() {
+                        NotificationType::Success
+                    } else {
+                        NotificationType::Warning
+                    };
+                    window.push_recorded_notification(
+                        (notification_type, SharedString::from(report.summary())),
+                        cx,
+                    );
+                    this.load_query_into_editor(report.to_sql(), window, cx);
+                }
+                Err(e) => {
+                    tracing::error!("Data comparison failed: {}", e);
+                    window.push_recorded_notification(
+                        (
+                            NotificationType::Error,
+                            SharedString::from(format!("Data comparison failed: {}", e)),
+                        ),
+                        cx,
+                    );
+                }
+            });
+        })
+        .detach();
     }
 
     fn show_table_columns(&mut self, table: TableInfo, cx: &mut Context<Self>) {
         // Get database manager from global state
-        let db_manager = cx.global::<ConnectionState>().db_manager.clone();
+        let db_manager = WindowConnection::of(self.window_id, cx).db_manager.clone();
 
         cx.spawn(async move |this, cx| {
             let result = db_manager
@@ -242,73 +2250,306 @@ impl Workspace {
         content
     }
 
-    fn render_connected(&mut self, cx: &mut Context<Self>) -> Stateful<Div> {
-        let sidebar = div()
-            .id("connected-sidebar")
-            .flex()
-            .flex_col()
-            .h_full()
-            .border_color(cx.theme().border)
-            .border_r_1()
-            .min_w(px(300.0))
-            .child(self.tables_tree.clone());
+    fn toggle_zen_mode(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.zen_mode = !self.zen_mode;
+        if self.zen_mode && !window.is_fullscreen() {
+            window.toggle_fullscreen();
+            self.zen_fullscreen = true;
+        } else if !self.zen_mode && self.zen_fullscreen {
+            window.toggle_fullscreen();
+            self.zen_fullscreen = false;
+        }
+        let large_text = self.zen_mode;
+        self.editor
+            .update(cx, |editor, cx| editor.set_large_text(large_text, cx));
+        self.results_panel
+            .update(cx, |results, cx| results.set_large_text(large_text, cx));
+        cx.notify();
+    }
 
-        let agent = div()
-            .id("connected-agent")
-            .flex()
-            .flex_col()
-            .h_full()
-            .w(px(400.))
-            .border_color(cx.theme().border)
-            .border_l_1()
-            .child(self.agent_panel.clone());
+    fn panel_visible(&self, panel: SidePanel) -> bool {
+        match panel {
+            SidePanel::Tables => self.show_tables,
+            SidePanel::Agent => self.show_agent,
+            SidePanel::History => self.show_history,
+            SidePanel::Jobs => self.show_jobs,
+        }
+    }
 
-        let history = div()
-            .id("connected-history")
-            .flex()
-            .flex_col()
-            .h_full()
-            .w(px(400.))
+    /// Collapse a side panel from its header, keeping the footer toggles
+    /// in step.
+    fn hide_panel(&mut self, panel: SidePanel, cx: &mut Context<Self>) {
+        match panel {
+            SidePanel::Tables => self.show_tables = false,
+            SidePanel::Agent => self.show_agent = false,
+            SidePanel::History => self.show_history = false,
+            SidePanel::Jobs => self.show_jobs = false,
+        }
+        let (tables, agent, history, jobs) = (
+            self.show_tables,
+            self.show_agent,
+            self.show_history,
+            self.show_jobs,
+        );
+        self.footer_bar.update(cx, |footer, cx| {
+            footer.set_panels(tables, agent, history, jobs, cx);
+        });
+        cx.notify();
+    }
+
+    fn dock_panel(
+        &mut self,
+        panel: SidePanel,
+        dock: PanelDock,
+        before: Option<SidePanel>,
+        cx: &mut Context<Self>,
+    ) {
+        self.panel_layout.move_panel(panel, dock, before);
+        self.schedule_save_panel_layout(cx);
+        cx.notify();
+    }
+
+    /// Record the widths of the docked panels after a resize. `order` lists
+    /// the resizable panels left to right, `None` being the editor.
+    fn store_panel_widths(
+        &mut self,
+        order: &[Option<SidePanel>],
+        sizes: &[Pixels],
+        cx: &mut Context<Self>,
+    ) {
+        let mut changed = false;
+        for (panel, size) in order.iter().zip(sizes) {
+            let Some(panel) = panel else {
+                continue;
+            };
+            let width = f32::from(*size);
+            if (self.panel_layout.placement(*panel).width - width).abs() >= 1. {
+                self.panel_layout.set_width(*panel, width);
+                changed = true;
+            }
+        }
+        if changed {
+            self.schedule_save_panel_layout(cx);
+        }
+    }
+
+    /// A docked panel with a header to drag it by, move it to the other
+    /// dock or collapse it. Dropping another panel on the header places
+    /// that panel before this one.
+    fn render_side_panel(&self, panel: SidePanel, cx: &mut Context<Self>) -> impl IntoElement {
+        let dock = self.panel_layout.placement(panel).dock;
+        let (move_icon, move_tooltip) = match dock {
+            PanelDock::Left => ("icons/panel-right.svg", tr("panel-move-right")),
+            PanelDock::Right => ("icons/panel-left.svg", tr("panel-move-left")),
+        };
+
+        let header = h_flex()
+            .id(SharedString::from(format!("panel-header-{}", panel.key())))
+            .flex_none()
+            .justify_between()
+            .px_2()
+            .h_7()
+            .border_b_1()
             .border_color(cx.theme().border)
-            .border_l_1()
-            .child(self.history_panel.clone());
+            .cursor_grab()
+            .on_drag(DraggedPanel(panel), |dragged, _offset, _window, cx| {
+                cx.new(|_| dragged.clone())
+            })
+            .drag_over::<DraggedPanel>(|style, _, _, cx| style.bg(cx.theme().drop_target))
+            .on_drop(
+                cx.listener(move |this, dragged: &DraggedPanel, _window, cx| {
+                    this.dock_panel(dragged.0, dock, Some(panel), cx);
+                }),
+            )
+            .child(
+                Label::new(panel.title())
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new(SharedString::from(format!("move-{}", panel.key())))
+                            .icon(Icon::empty().path(move_icon))
+                            .xsmall()
+                            .ghost()
+                            .tooltip(move_tooltip)
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.dock_panel(panel, dock.other(), None, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new(SharedString::from(format!("hide-{}", panel.key())))
+                            .icon(IconName::Close)
+                            .xsmall()
+                            .ghost()
+                            .tooltip(tr("panel-hide"))
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.hide_panel(panel, cx);
+                            })),
+                    ),
+            );
+
+        let body = match panel {
+            SidePanel::Tables => self.tables_tree.clone().into_any_element(),
+            SidePanel::Agent => self.agent_panel.clone().into_any_element(),
+            SidePanel::History => self.history_panel.clone().into_any_element(),
+            SidePanel::Jobs => self.jobs_panel.clone().into_any_element(),
+        };
+
+        v_flex()
+            .size_full()
+            .overflow_hidden()
+            .child(header)
+            .child(div().flex_1().min_h_0().child(body))
+    }
+
+    fn render_docked(&self, placement: &PanelPlacement, cx: &mut Context<Self>) -> ResizablePanel {
+        resizable_panel()
+            .size(px(placement.width))
+            .size_range(px(MIN_PANEL_WIDTH)..px(MAX_PANEL_WIDTH))
+            .child(self.render_side_panel(placement.panel, cx))
+    }
 
+    /// Drop zone along one edge of the editor, shown while a panel is
+    /// dragged, that docks the panel next to the editor.
+    fn render_dock_target(&self, dock: PanelDock, cx: &mut Context<Self>) -> impl IntoElement {
+        let before = match dock {
+            PanelDock::Left => None,
+            PanelDock::Right => self.panel_layout.docked(dock).next().map(|p| p.panel),
+        };
+        div()
+            .absolute()
+            .top_0()
+            .bottom_0()
+            .w(px(96.))
+            .map(|d| match dock {
+                PanelDock::Left => d.left_0(),
+                PanelDock::Right => d.right_0(),
+            })
+            .drag_over::<DraggedPanel>(|style, _, _, cx| style.bg(cx.theme().drop_target))
+            .on_drop(
+                cx.listener(move |this, dragged: &DraggedPanel, _window, cx| {
+                    this.dock_panel(dragged.0, dock, before, cx);
+                }),
+            )
+    }
+
+    fn render_connected(&mut self, cx: &mut Context<Self>) -> Stateful<Div> {
         let main = div()
             .id("connected-main")
+            .relative()
             .flex()
             .flex_col()
             .flex_1()
             .h_full()
             .w_full()
             .overflow_hidden()
-            .child(
-                v_resizable("resizable-results")
-                    .child(
-                        resizable_panel()
-                            .size(px(400.))
-                            .size_range(px(200.)..px(800.))
-                            .child(self.editor.clone()),
-                    )
-                    .child(
-                        resizable_panel()
-                            .size(px(200.))
-                            .child(self.results_panel.clone()),
-                    ),
-            );
+            .map(|d| match self.results_orientation {
+                ResultsOrientation::Below => d.child(
+                    v_resizable("resizable-results")
+                        .child(
+                            resizable_panel()
+                                .size(px(400.))
+                                .size_range(px(200.)..px(800.))
+                                .child(self.editor.clone()),
+                        )
+                        .child(
+                            resizable_panel()
+                                .size(px(200.))
+                                .child(self.results_panel.clone()),
+                        ),
+                ),
+                ResultsOrientation::Right => d.child(
+                    h_resizable("resizable-results-right")
+                        .child(
+                            resizable_panel()
+                                .size(px(600.))
+                                .size_range(px(300.)..px(1600.))
+                                .child(self.editor.clone()),
+                        )
+                        .child(
+                            resizable_panel()
+                                .size(px(600.))
+                                .child(self.results_panel.clone()),
+                        ),
+                ),
+            })
+            .when(!self.zen_mode && cx.has_active_drag(), |d| {
+                d.child(self.render_dock_target(PanelDock::Left, cx))
+                    .child(self.render_dock_target(PanelDock::Right, cx))
+            });
 
-        let content = div()
+        let visible = |dock| {
+            self.panel_layout
+                .docked(dock)
+                .filter(|p| self.panel_visible(p.panel))
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        let (left, right) = if self.zen_mode {
+            (vec![], vec![])
+        } else {
+            (visible(PanelDock::Left), visible(PanelDock::Right))
+        };
+
+        // The resizable group keeps its sizes by position, so it gets a new
+        // id whenever the set or order of panels changes, or a restored
+        // workspace sets new widths.
+        let order: Vec<Option<SidePanel>> = left
+            .iter()
+            .map(|p| Some(p.panel))
+            .chain([None])
+            .chain(right.iter().map(|p| Some(p.panel)))
+            .collect();
+        let group_id = order
+            .iter()
+            .map(|panel| panel.map_or("main", |p| p.key()))
+            .collect::<Vec<_>>()
+            .join("-");
+
+        let workspace = cx.entity().downgrade();
+        let group_id = format!("docks-{}-{}", self.layout_generation, group_id);
+        let mut docks = h_resizable(SharedString::from(group_id)).on_resize(
+            move |state: &Entity<ResizableState>, _window, cx| {
+                let sizes = state.read(cx).sizes().clone();
+                let _ = workspace.update(cx, |this, cx| {
+                    this.store_panel_widths(&order, &sizes, cx);
+                });
+            },
+        );
+        for placement in &left {
+            docks = docks.child(self.render_docked(placement, cx));
+        }
+        docks = docks.child(resizable_panel().child(main));
+        for placement in &right {
+            docks = docks.child(self.render_docked(placement, cx));
+        }
+
+        div()
             .id("connected-content")
             .flex()
             .flex_row()
             .flex_1()
             .h_full()
+            .relative()
             .bg(cx.theme().background)
-            .when(self.show_tables.clone(), |d| d.child(sidebar))
-            .child(main)
-            .when(self.show_agent.clone(), |d| d.child(agent))
-            .when(self.show_history.clone(), |d| d.child(history));
-
-        content
+            .child(docks)
+            .when(self.zen_mode, |d| {
+                d.child(
+                    div().absolute().bottom_2().right_2().child(
+                        Button::new("exit-zen-mode")
+                            .icon(Icon::empty().path("icons/minimize.svg"))
+                            .small()
+                            .ghost()
+                            .tooltip(tr("zen-mode-exit"))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_zen_mode(window, cx);
+                            })),
+                    ),
+                )
+            })
     }
 
     fn render_loading(&mut self, cx: &mut Context<Self>) -> Stateful<Div> {
@@ -324,7 +2565,7 @@ impl Workspace {
                     .flex()
                     .flex_col()
                     .items_center()
-                    .child(Spinner::new())
+                    .child(busy_indicator(cx))
                     .child("Loading"),
             );
 
@@ -334,6 +2575,7 @@ impl Workspace {
 
 impl Render for Workspace {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let zen_mode = self.zen_mode && self.connection_state == ConnectionStatus::Connected;
         let content = match self.connection_state.clone() {
             ConnectionStatus::Disconnected => self.render_disconnected(cx),
             ConnectionStatus::Connected => self.render_connected(cx),
@@ -345,11 +2587,71 @@ impl Render for Workspace {
             .flex()
             .flex_col()
             .size_full()
-            .child(self.header_bar.clone())
+            .on_action(cx.listener(|this, _: &OpenFile, window, cx| {
+                this.editor
+                    .update(cx, |editor, cx| editor.open_file(window, cx));
+            }))
+            .on_action(cx.listener(|this, _: &OpenRecentFile, window, cx| {
+                this.editor
+                    .update(cx, |editor, cx| editor.open_recent_file(window, cx));
+            }))
+            .on_action(cx.listener(|this, _: &SaveFile, window, cx| {
+                this.editor
+                    .update(cx, |editor, cx| editor.save_file(window, cx));
+            }))
+            .on_action(cx.listener(|this, _: &SaveFileAs, window, cx| {
+                this.editor
+                    .update(cx, |editor, cx| editor.save_file_as(window, cx));
+            }))
+            .on_action(cx.listener(|this, _: &RunStatement, window, cx| {
+                this.editor
+                    .update(cx, |editor, cx| editor.run_statement(window, cx));
+            }))
+            .on_action(cx.listener(|this, _: &RunSelection, window, cx| {
+                this.editor
+                    .update(cx, |editor, cx| editor.run_selection(window, cx));
+            }))
+            .on_action(cx.listener(|this, _: &RunScript, _window, cx| {
+                this.editor.update(cx, |editor, cx| editor.run_script(cx));
+            }))
+            .on_action(cx.listener(|this, _: &SearchDatabase, window, cx| {
+                this.open_object_search(window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot1, window, cx| {
+                this.run_quick_slot(1, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot2, window, cx| {
+                this.run_quick_slot(2, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot3, window, cx| {
+                this.run_quick_slot(3, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot4, window, cx| {
+                this.run_quick_slot(4, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot5, window, cx| {
+                this.run_quick_slot(5, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot6, window, cx| {
+                this.run_quick_slot(6, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot7, window, cx| {
+                this.run_quick_slot(7, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot8, window, cx| {
+                this.run_quick_slot(8, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &QuickSlot9, window, cx| {
+                this.run_quick_slot(9, window, cx);
+            }))
+            .on_action(cx.listener(|this, _: &ToggleZenMode, window, cx| {
+                this.toggle_zen_mode(window, cx);
+            }))
+            .when(!zen_mode, |d| d.child(self.header_bar.clone()))
             .child(content)
-            .child(self.footer_bar.clone())
+            .when(!zen_mode, |d| d.child(self.footer_bar.clone()))
             .children(Root::render_dialog_layer(window, cx))
             .children(Root::render_sheet_layer(window, cx))
-            .children(Root::render_notification_layer(window, cx))
+            .children(render_toast_layer(window, cx))
     }
 }